@@ -0,0 +1,41 @@
+/*
+ * C API of pixi-outdated, built with the `ffi` feature:
+ *
+ *     cargo rustc --lib --release --features ffi --crate-type cdylib
+ *
+ * The check functions return a NUL-terminated JSON string: the report, as
+ * printed by `--json`, or {"error": "..."}. Release it with
+ * pixi_outdated_free_string.
+ */
+#ifndef PIXI_OUTDATED_H
+#define PIXI_OUTDATED_H
+
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define PIXI_OUTDATED_API_VERSION 1
+
+/* The version of the C API the library implements */
+uint32_t pixi_outdated_api_version(void);
+
+/*
+ * Check the pixi.lock next to a pixi.toml or pyproject.toml. options_json is
+ * NULL or a JSON object with any of "environment", "platforms", "explicit"
+ * and "fixtures".
+ */
+char *pixi_outdated_check_manifest(const char *manifest_path, const char *options_json);
+
+/* Check a pixi.lock without its manifest, so no update counts as direct */
+char *pixi_outdated_check_lockfile(const char *lock_path, const char *options_json);
+
+/* Release a string returned by this library. NULL is ignored. */
+void pixi_outdated_free_string(char *value);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* PIXI_OUTDATED_H */
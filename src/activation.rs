@@ -0,0 +1,327 @@
+use anyhow::{Context, Result};
+use rattler_conda_types::package::{PackageFile, PathsJson};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::debug;
+
+use crate::upstream::Artifact;
+
+/// Where conda packages put scripts that run, and variables that are set,
+/// when an environment is activated or deactivated
+const ACTIVATION_DIRS: [&str; 3] = [
+    "etc/conda/activate.d/",
+    "etc/conda/deactivate.d/",
+    "etc/conda/env_vars.d/",
+];
+
+/// Whether a file installed by a package changes how the environment is
+/// activated
+pub fn is_activation_file(path: &str) -> bool {
+    let path = path.replace('\\', "/");
+    ACTIVATION_DIRS.iter().any(|dir| path.starts_with(dir))
+}
+
+/// The parts of a `conda-meta/*.json` record that are read
+#[derive(Debug, Deserialize)]
+struct PrefixRecord {
+    name: String,
+    #[serde(default)]
+    files: Vec<String>,
+}
+
+/// The activation scripts of the conda packages installed in an environment
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ActivationScripts {
+    by_package: HashMap<String, Vec<String>>,
+}
+
+impl ActivationScripts {
+    /// Read the installed package records of the environment at `prefix`.
+    /// An environment that isn't installed has none.
+    pub fn load(prefix: &Path) -> Self {
+        let Ok(entries) = fs::read_dir(prefix.join("conda-meta")) else {
+            return Self::default();
+        };
+
+        let mut by_package = HashMap::new();
+        for path in entries.filter_map(|entry| entry.ok().map(|entry| entry.path())) {
+            if path.extension().is_none_or(|extension| extension != "json") {
+                continue;
+            }
+            let record = fs::read_to_string(&path)
+                .map_err(anyhow::Error::from)
+                .and_then(|content| Ok(serde_json::from_str::<PrefixRecord>(&content)?));
+            match record {
+                Ok(record) => {
+                    let mut scripts: Vec<String> = record
+                        .files
+                        .into_iter()
+                        .filter(|file| is_activation_file(file))
+                        .collect();
+                    if !scripts.is_empty() {
+                        scripts.sort();
+                        by_package.insert(record.name, scripts);
+                    }
+                }
+                Err(e) => debug!(path = %path.display(), error = %e, "Skipping package record"),
+            }
+        }
+        Self { by_package }
+    }
+
+    /// The activation scripts the installed version of a package ships
+    pub fn get(&self, name: &str) -> &[String] {
+        self.by_package.get(name).map_or(&[], Vec::as_slice)
+    }
+}
+
+/// The activation scripts among the files a package records in its
+/// `info/paths.json`, sorted
+fn package_scripts(paths: &PathsJson) -> Vec<String> {
+    let mut scripts: Vec<String> = paths
+        .paths
+        .iter()
+        .map(|entry| entry.relative_path.to_string_lossy().replace('\\', "/"))
+        .filter(|path| is_activation_file(path))
+        .collect();
+    scripts.sort();
+    scripts
+}
+
+/// The name of a conda package file without its extension, e.g.
+/// `openssl-3.6.0-h26f9b46_0`, which is also the directory pixi's package
+/// cache extracts it to
+fn package_stem(file_name: &str) -> Option<&str> {
+    file_name
+        .strip_suffix(".conda")
+        .or_else(|| file_name.strip_suffix(".tar.bz2"))
+}
+
+/// The activation scripts a conda package file ships. They are read from
+/// pixi's package cache if the package was extracted there, or from what an
+/// earlier run found, and otherwise from the downloaded file. Package files
+/// never change, so what is found is kept under `cache_dir` for good.
+pub async fn artifact_scripts(
+    client: &reqwest_middleware::ClientWithMiddleware,
+    cache_dir: Option<&Path>,
+    artifact: &Artifact,
+) -> Result<Vec<String>> {
+    let stem = package_stem(&artifact.file_name)
+        .with_context(|| format!("{} is not a conda package", artifact.file_name))?;
+    let remembered = cache_dir.map(|dir| {
+        dir.join("pixi-outdated")
+            .join("activation")
+            .join(format!("{}.json", stem))
+    });
+    if let Some(cache_dir) = cache_dir {
+        let extracted = cache_dir.join("pkgs").join(stem);
+        if let Ok(paths) = PathsJson::from_package_directory(&extracted) {
+            return Ok(package_scripts(&paths));
+        }
+    }
+    if let Some(scripts) = remembered
+        .as_ref()
+        .and_then(|path| fs::read(path).ok())
+        .and_then(|content| serde_json::from_slice(&content).ok())
+    {
+        return Ok(scripts);
+    }
+
+    let paths = match url::Url::parse(&artifact.url)
+        .ok()
+        .filter(|url| url.scheme() == "file")
+        .and_then(|url| url.to_file_path().ok())
+    {
+        // Packages in local channels are read where they are
+        Some(path) => read_paths(&path)?,
+        None => {
+            let body = client
+                .get(artifact.url.as_str())
+                .send()
+                .await
+                .with_context(|| format!("Failed to download {}", artifact.url))?
+                .error_for_status()
+                .with_context(|| format!("Failed to download {}", artifact.url))?
+                .bytes()
+                .await
+                .with_context(|| format!("Failed to download {}", artifact.url))?;
+            // The archive format is told by the file name
+            let file = tempfile::Builder::new()
+                .suffix(&format!("-{}", artifact.file_name))
+                .tempfile()
+                .context("Failed to create a temporary file")?;
+            fs::write(file.path(), &body)
+                .with_context(|| format!("Failed to write {}", file.path().display()))?;
+            read_paths(file.path())?
+        }
+    };
+
+    let scripts = package_scripts(&paths);
+    if let Some(path) = remembered {
+        let written = path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| fs::write(&path, serde_json::to_vec(&scripts).unwrap_or_default()));
+        if let Err(e) = written {
+            debug!(path = %path.display(), error = %e, "Failed to remember activation scripts");
+        }
+    }
+    Ok(scripts)
+}
+
+fn read_paths(package: &Path) -> Result<PathsJson> {
+    rattler_package_streaming::seek::read_package_file(package)
+        .map_err(|e| anyhow::anyhow!("Failed to read the files of {}: {}", package.display(), e))
+}
+
+/// Looks up the activation scripts of the versions updates move to, from
+/// their package files, falling back to those of the installed version when
+/// the package file can't be read
+pub struct ActivationLookup {
+    installed: ActivationScripts,
+    /// `None` to only consult the installed environment, e.g. for fixtures
+    client: Option<reqwest_middleware::ClientWithMiddleware>,
+    cache_dir: Option<PathBuf>,
+}
+
+impl ActivationLookup {
+    pub fn new(
+        installed: ActivationScripts,
+        client: Option<reqwest_middleware::ClientWithMiddleware>,
+        cache_dir: Option<PathBuf>,
+    ) -> Self {
+        Self {
+            installed,
+            client,
+            cache_dir,
+        }
+    }
+
+    /// The activation scripts of a package's latest version, given one of
+    /// its package files
+    pub async fn scripts(&self, name: &str, latest: Option<&Artifact>) -> Vec<String> {
+        if let (Some(client), Some(artifact)) = (&self.client, latest) {
+            match artifact_scripts(client, self.cache_dir.as_deref(), artifact).await {
+                Ok(scripts) => return scripts,
+                Err(e) => {
+                    debug!(package = name, error = %e, "Falling back to installed activation scripts")
+                }
+            }
+        }
+        self.installed.get(name).to_vec()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_activation_file() {
+        assert!(is_activation_file(
+            "etc/conda/activate.d/openssl_activate.sh"
+        ));
+        assert!(is_activation_file("etc/conda/env_vars.d/proj.json"));
+        assert!(is_activation_file(r"etc\conda\deactivate.d\gdal.bat"));
+        assert!(!is_activation_file("bin/openssl"));
+        assert!(!is_activation_file("share/etc/conda/activate.d/x.sh"));
+    }
+
+    #[test]
+    fn test_load() {
+        let prefix = tempfile::tempdir().unwrap();
+        let conda_meta = prefix.path().join("conda-meta");
+        fs::create_dir(&conda_meta).unwrap();
+        fs::write(
+            conda_meta.join("openssl-3.5.4-h26f9b46_0.json"),
+            r#"{"name": "openssl", "version": "3.5.4", "files": [
+                "bin/openssl",
+                "etc/conda/deactivate.d/openssl_deactivate.sh",
+                "etc/conda/activate.d/openssl_activate.sh"
+            ]}"#,
+        )
+        .unwrap();
+        fs::write(
+            conda_meta.join("libzlib-1.3.1-hb9d3cd8_2.json"),
+            r#"{"name": "libzlib", "files": ["lib/libz.so"]}"#,
+        )
+        .unwrap();
+        fs::write(conda_meta.join("history"), "").unwrap();
+        fs::write(conda_meta.join("broken.json"), "{").unwrap();
+
+        let scripts = ActivationScripts::load(prefix.path());
+        assert_eq!(
+            scripts.get("openssl"),
+            [
+                "etc/conda/activate.d/openssl_activate.sh",
+                "etc/conda/deactivate.d/openssl_deactivate.sh"
+            ]
+        );
+        assert!(scripts.get("libzlib").is_empty());
+
+        let missing = ActivationScripts::load(&prefix.path().join("missing"));
+        assert_eq!(missing, ActivationScripts::default());
+    }
+
+    #[tokio::test]
+    async fn test_artifact_scripts_from_package_cache() {
+        let cache_dir = tempfile::tempdir().unwrap();
+        let info = cache_dir.path().join("pkgs/openssl-3.6.0-h26f9b46_0/info");
+        fs::create_dir_all(&info).unwrap();
+        fs::write(
+            info.join("paths.json"),
+            r#"{"paths_version": 1, "paths": [
+                {"_path": "bin/openssl", "path_type": "hardlink"},
+                {"_path": "etc/conda/activate.d/openssl_activate.sh", "path_type": "hardlink"}
+            ]}"#,
+        )
+        .unwrap();
+
+        let client = reqwest_middleware::ClientBuilder::new(reqwest::Client::new()).build();
+        let artifact = |file_name: &str| Artifact {
+            file_name: file_name.to_string(),
+            // Nothing listens there, so nothing may be downloaded
+            url: format!("http://127.0.0.1:9/conda-forge/linux-64/{}", file_name),
+            sha256: None,
+        };
+        let scripts = artifact_scripts(
+            &client,
+            Some(cache_dir.path()),
+            &artifact("openssl-3.6.0-h26f9b46_0.conda"),
+        )
+        .await
+        .unwrap();
+        assert_eq!(scripts, ["etc/conda/activate.d/openssl_activate.sh"]);
+
+        // Not extracted and not downloadable: the lookup falls back to the
+        // installed version
+        let mut installed = ActivationScripts::default();
+        installed.by_package.insert(
+            "gdal".to_string(),
+            vec!["etc/conda/activate.d/gdal.sh".to_string()],
+        );
+        let lookup = ActivationLookup::new(
+            installed,
+            Some(client),
+            Some(cache_dir.path().to_path_buf()),
+        );
+        assert_eq!(
+            lookup
+                .scripts("gdal", Some(&artifact("gdal-3.11.0-h0_0.conda")))
+                .await,
+            ["etc/conda/activate.d/gdal.sh"]
+        );
+    }
+
+    #[test]
+    fn test_package_stem() {
+        assert_eq!(
+            package_stem("openssl-3.6.0-h26f9b46_0.conda"),
+            Some("openssl-3.6.0-h26f9b46_0")
+        );
+        assert_eq!(package_stem("zlib-1.3.1-0.tar.bz2"), Some("zlib-1.3.1-0"));
+        assert_eq!(package_stem("requests-2.32.3-py3-none-any.whl"), None);
+    }
+}
@@ -0,0 +1,63 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+
+/// Parse a lag threshold such as `90d`, `12w` or `1y` into a number of days.
+/// A bare number is interpreted as days.
+pub fn parse_max_lag(input: &str) -> Result<u32> {
+    let input = input.trim();
+    let (number, multiplier) = match input.char_indices().last() {
+        Some((idx, 'd')) => (&input[..idx], 1),
+        Some((idx, 'w')) => (&input[..idx], 7),
+        Some((idx, 'y')) => (&input[..idx], 365),
+        _ => (input, 1),
+    };
+
+    let number: u32 = number.parse().map_err(|_| {
+        anyhow::anyhow!(
+            "Invalid lag '{}': expected a number of days, weeks or years like 90d, 12w or 1y",
+            input
+        )
+    })?;
+
+    number
+        .checked_mul(multiplier)
+        .ok_or_else(|| anyhow::anyhow!("Lag '{}' is too large", input))
+}
+
+/// Whole days elapsed between a release and `now`
+pub fn age_in_days(released: DateTime<Utc>, now: DateTime<Utc>) -> u32 {
+    u32::try_from((now - released).num_days()).unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_parse_max_lag_units() {
+        assert_eq!(parse_max_lag("90d").unwrap(), 90);
+        assert_eq!(parse_max_lag("12w").unwrap(), 84);
+        assert_eq!(parse_max_lag("1y").unwrap(), 365);
+        assert_eq!(parse_max_lag("30").unwrap(), 30);
+    }
+
+    #[test]
+    fn test_parse_max_lag_invalid() {
+        assert!(parse_max_lag("").is_err());
+        assert!(parse_max_lag("d").is_err());
+        assert!(parse_max_lag("90 days").is_err());
+        assert!(parse_max_lag("-5d").is_err());
+        assert!(parse_max_lag("99999999999y").is_err());
+    }
+
+    #[test]
+    fn test_age_in_days() {
+        let released = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let now = Utc.with_ymd_and_hms(2024, 4, 1, 12, 0, 0).unwrap();
+        assert_eq!(age_in_days(released, now), 91);
+
+        // Clock skew never yields a negative age
+        assert_eq!(age_in_days(now, released), 0);
+    }
+}
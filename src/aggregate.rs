@@ -0,0 +1,307 @@
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+use crate::report::Report;
+
+/// Outdated and broken package counts of a single report
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+pub struct ProjectSummary {
+    /// The report's file name without extension
+    pub project: String,
+    /// The name of the directory containing the report
+    pub team: String,
+    /// Distinct packages with an update on any platform
+    pub outdated: usize,
+    /// Distinct packages whose locked version was yanked or removed
+    pub locked_issues: usize,
+}
+
+/// How many projects a package is outdated in
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+pub struct PackageSummary {
+    pub name: String,
+    pub projects: usize,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+pub struct TeamSummary {
+    pub team: String,
+    pub projects: usize,
+    pub outdated: usize,
+}
+
+/// A summary of many reports, e.g. from every project in an organization
+#[derive(Debug, Serialize, Clone, Default, PartialEq, Eq)]
+pub struct AggregateSummary {
+    /// Projects sorted by the number of outdated packages, worst first
+    pub projects: Vec<ProjectSummary>,
+    /// Packages sorted by the number of projects they are outdated in
+    pub packages: Vec<PackageSummary>,
+    /// Per-team totals, sorted by team name
+    pub teams: Vec<TeamSummary>,
+}
+
+/// Read a report written by `--json` or `--json-out`
+pub fn load_report(path: &Path) -> Result<Report> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read report {}", path.display()))?;
+    parse_report(&content).with_context(|| format!("Failed to parse report {}", path.display()))
+}
+
+/// Parse a report of any schema version. Version 1 reports are just the
+/// updates per platform.
+fn parse_report(content: &str) -> Result<Report> {
+    let value: serde_json::Value = serde_json::from_str(content)?;
+    if value.get("schema_version").is_some() || value.get("platforms").is_some() {
+        return Ok(serde_json::from_value(value)?);
+    }
+    Ok(Report {
+        platforms: serde_json::from_value(value)?,
+        ..Default::default()
+    })
+}
+
+/// Project and team names for a report, derived from its path
+pub fn project_and_team(path: &Path) -> (String, String) {
+    let project = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.display().to_string());
+    let team = path
+        .parent()
+        .and_then(Path::file_name)
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| ".".to_string());
+    (project, team)
+}
+
+/// Summarize reports given as `(project, team, report)`
+pub fn aggregate(reports: &[(String, String, Report)]) -> AggregateSummary {
+    let mut projects = Vec::new();
+    let mut package_projects: BTreeMap<&str, usize> = BTreeMap::new();
+    let mut teams: BTreeMap<&str, TeamSummary> = BTreeMap::new();
+
+    for (project, team, report) in reports {
+        let outdated: BTreeSet<&str> = report
+            .platforms
+            .values()
+            .flatten()
+            .map(|update| update.name.as_str())
+            .collect();
+        let locked_issues: BTreeSet<&str> = report
+            .locked_issues
+            .iter()
+            .map(|issue| issue.name.as_str())
+            .collect();
+
+        for name in &outdated {
+            *package_projects.entry(name).or_default() += 1;
+        }
+
+        let team_summary = teams.entry(team.as_str()).or_insert_with(|| TeamSummary {
+            team: team.clone(),
+            projects: 0,
+            outdated: 0,
+        });
+        team_summary.projects += 1;
+        team_summary.outdated += outdated.len();
+
+        projects.push(ProjectSummary {
+            project: project.clone(),
+            team: team.clone(),
+            outdated: outdated.len(),
+            locked_issues: locked_issues.len(),
+        });
+    }
+
+    projects.sort_by(|a, b| {
+        (b.outdated, b.locked_issues)
+            .cmp(&(a.outdated, a.locked_issues))
+            .then_with(|| (&a.team, &a.project).cmp(&(&b.team, &b.project)))
+    });
+
+    let mut packages: Vec<PackageSummary> = package_projects
+        .into_iter()
+        .map(|(name, projects)| PackageSummary {
+            name: name.to_string(),
+            projects,
+        })
+        .collect();
+    // Stable sort keeps packages with equal counts in name order
+    packages.sort_by_key(|package| std::cmp::Reverse(package.projects));
+
+    AggregateSummary {
+        projects,
+        packages,
+        teams: teams.into_values().collect(),
+    }
+}
+
+/// Render the summary as text, listing at most `top` projects and packages
+pub fn render_text(summary: &AggregateSummary, top: usize) -> String {
+    let mut out = format!("Aggregated {} report(s)\n", summary.projects.len());
+
+    if !summary.projects.is_empty() {
+        out.push_str("\n=== Worst offenders ===\n");
+        for project in summary.projects.iter().take(top) {
+            let _ = write!(
+                out,
+                "{}/{}: {} outdated",
+                project.team, project.project, project.outdated
+            );
+            if project.locked_issues > 0 {
+                let _ = write!(out, ", {} yanked or removed", project.locked_issues);
+            }
+            out.push('\n');
+        }
+    }
+
+    if !summary.packages.is_empty() {
+        out.push_str("\n=== Most common outdated packages ===\n");
+        for package in summary.packages.iter().take(top) {
+            let _ = writeln!(out, "{}: {} project(s)", package.name, package.projects);
+        }
+    }
+
+    if !summary.teams.is_empty() {
+        out.push_str("\n=== Teams ===\n");
+        for team in &summary.teams {
+            let _ = writeln!(
+                out,
+                "{}: {} project(s), {} outdated package(s)",
+                team.team, team.projects, team.outdated
+            );
+        }
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::{LockedIssue, LockedStatus, PackageUpdate, PlatformUpdates};
+
+    fn report(updates: &[(&str, &[&str])]) -> Report {
+        let mut platforms = PlatformUpdates::new();
+        for (platform, names) in updates {
+            platforms.insert(
+                platform.to_string(),
+                names
+                    .iter()
+                    .map(|name| PackageUpdate {
+                        name: name.to_string(),
+                        installed_version: "1.0".to_string(),
+                        latest_version: "2.0".to_string(),
+                        ..Default::default()
+                    })
+                    .collect(),
+            );
+        }
+        Report {
+            platforms,
+            ..Report::default()
+        }
+    }
+
+    fn sample() -> Vec<(String, String, Report)> {
+        let mut api = report(&[
+            ("linux-64", &["python", "numpy", "openssl"]),
+            ("osx-arm64", &["python", "numpy"]),
+        ]);
+        api.locked_issues.push(LockedIssue {
+            name: "cowsay".to_string(),
+            version: "5.0".to_string(),
+            platform: "linux-64".to_string(),
+            status: LockedStatus::Removed,
+            annotations: Default::default(),
+        });
+
+        vec![
+            (
+                "web".to_string(),
+                "frontend".to_string(),
+                report(&[("linux-64", &["python"])]),
+            ),
+            ("api".to_string(), "backend".to_string(), api),
+            (
+                "etl".to_string(),
+                "backend".to_string(),
+                report(&[("linux-64", &["numpy"])]),
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_parse_report_schema_versions() {
+        let report = sample().remove(1).2;
+        let current = crate::report::render_json(&report).unwrap();
+        assert_eq!(parse_report(&current).unwrap(), report);
+
+        let v1 = serde_json::to_string(&report.platforms).unwrap();
+        assert_eq!(parse_report(&v1).unwrap().platforms, report.platforms);
+    }
+
+    #[test]
+    fn test_project_and_team() {
+        assert_eq!(
+            project_and_team(Path::new("reports/backend/api.json")),
+            ("api".to_string(), "backend".to_string())
+        );
+    }
+
+    #[test]
+    fn test_aggregate_worst_offenders() {
+        let summary = aggregate(&sample());
+        let projects: Vec<(&str, usize)> = summary
+            .projects
+            .iter()
+            .map(|p| (p.project.as_str(), p.outdated))
+            .collect();
+        // Updates on several platforms count once per package
+        assert_eq!(projects, vec![("api", 3), ("etl", 1), ("web", 1)]);
+        assert_eq!(summary.projects[0].locked_issues, 1);
+    }
+
+    #[test]
+    fn test_aggregate_common_packages_and_teams() {
+        let summary = aggregate(&sample());
+        let packages: Vec<(&str, usize)> = summary
+            .packages
+            .iter()
+            .map(|p| (p.name.as_str(), p.projects))
+            .collect();
+        assert_eq!(packages, vec![("numpy", 2), ("python", 2), ("openssl", 1)]);
+
+        assert_eq!(
+            summary.teams,
+            vec![
+                TeamSummary {
+                    team: "backend".to_string(),
+                    projects: 2,
+                    outdated: 4,
+                },
+                TeamSummary {
+                    team: "frontend".to_string(),
+                    projects: 1,
+                    outdated: 1,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_render_text_limits_lists() {
+        let text = render_text(&aggregate(&sample()), 1);
+        assert!(text.starts_with("Aggregated 3 report(s)\n"));
+        assert!(text.contains("backend/api: 3 outdated, 1 yanked or removed\n"));
+        assert!(!text.contains("backend/etl"));
+        assert!(text.contains("numpy: 2 project(s)\n"));
+        assert!(!text.contains("openssl"));
+        assert!(text.contains("frontend: 1 project(s), 1 outdated package(s)\n"));
+    }
+}
@@ -0,0 +1,127 @@
+use anyhow::{Context, Result};
+use globset::{Glob, GlobMatcher};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+/// Key/value metadata attached to report entries, e.g. `team = "ml"`
+pub type Annotation = BTreeMap<String, String>;
+
+/// Metadata for the packages matching a name pattern, configured in
+/// `[tool.pixi-outdated.annotations]`:
+///
+/// ```toml
+/// [tool.pixi-outdated.annotations]
+/// "torch*" = { team = "ml", criticality = "high" }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "BTreeMap<String, Annotation>")]
+pub struct Annotations {
+    rules: Vec<AnnotationRule>,
+}
+
+#[derive(Debug, Clone)]
+struct AnnotationRule {
+    glob: Glob,
+    matcher: GlobMatcher,
+    values: Annotation,
+}
+
+impl PartialEq for AnnotationRule {
+    fn eq(&self, other: &Self) -> bool {
+        self.glob == other.glob && self.values == other.values
+    }
+}
+
+impl Eq for AnnotationRule {}
+
+impl TryFrom<BTreeMap<String, Annotation>> for Annotations {
+    type Error = anyhow::Error;
+
+    fn try_from(patterns: BTreeMap<String, Annotation>) -> Result<Self> {
+        let mut rules = patterns
+            .into_iter()
+            .map(|(pattern, values)| {
+                let glob = Glob::new(&pattern)
+                    .with_context(|| format!("Invalid annotation pattern: {}", pattern))?;
+                Ok(AnnotationRule {
+                    matcher: glob.compile_matcher(),
+                    glob,
+                    values,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        // Plain package names are applied last, so they override the
+        // patterns they also match
+        rules.sort_by_key(|rule| !is_pattern(rule.glob.glob()));
+        Ok(Self { rules })
+    }
+}
+
+impl Annotations {
+    /// The metadata of every pattern matching `name`, merged
+    pub fn get(&self, name: &str) -> Annotation {
+        let mut annotation = Annotation::new();
+        for rule in self.rules.iter().filter(|rule| rule.matcher.is_match(name)) {
+            annotation.extend(rule.values.clone());
+        }
+        annotation
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+}
+
+fn is_pattern(glob: &str) -> bool {
+    glob.contains(['*', '?', '[', '{'])
+}
+
+/// Format an annotation as `key=value` pairs joined by `separator`
+pub fn describe(annotation: &Annotation, separator: &str) -> String {
+    annotation
+        .iter()
+        .map(|(key, value)| format!("{}={}", key, value))
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn annotations(toml: &str) -> Result<Annotations> {
+        let patterns: BTreeMap<String, Annotation> = toml::from_str(toml)?;
+        Annotations::try_from(patterns)
+    }
+
+    #[test]
+    fn test_annotations() {
+        let annotations = annotations(
+            r#"
+            "torch*" = { team = "ml", criticality = "high" }
+            "torchvision" = { criticality = "low" }
+            "*" = { owner = "platform" }
+            "#,
+        )
+        .unwrap();
+
+        let torch = annotations.get("torch");
+        assert_eq!(torch["team"], "ml");
+        assert_eq!(torch["criticality"], "high");
+        assert_eq!(torch["owner"], "platform");
+        assert_eq!(
+            describe(&torch, ", "),
+            "criticality=high, owner=platform, team=ml"
+        );
+
+        // A plain name wins over the patterns it matches
+        assert_eq!(annotations.get("torchvision")["criticality"], "low");
+        assert_eq!(annotations.get("numpy").len(), 1);
+    }
+
+    #[test]
+    fn test_invalid_pattern() {
+        assert!(annotations("\"torch[\" = { team = \"ml\" }").is_err());
+        assert!(annotations("").unwrap().is_empty());
+    }
+}
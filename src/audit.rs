@@ -0,0 +1,807 @@
+use anyhow::{Context, Result};
+use futures::stream::{self, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::pixi::{PackageKind, PixiPackage};
+use crate::report::Report;
+
+/// The OSV.dev API
+pub const OSV_API_URL: &str = "https://api.osv.dev/v1";
+
+/// How many package versions one `querybatch` request may ask about
+const QUERY_BATCH_SIZE: usize = 1000;
+
+/// How many advisories are fetched at once
+const ADVISORY_JOBS: usize = 8;
+
+/// How long a single OSV request may take
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The OSV ecosystem of PyPI packages, under which Python packages from
+/// conda channels are looked up too
+const PYPI_ECOSYSTEM: &str = "PyPI";
+
+/// A locked package with known vulnerabilities
+#[derive(Debug, Serialize, Clone, Deserialize, PartialEq, Eq)]
+pub struct VulnerablePackage {
+    pub name: String,
+    pub kind: PackageKind,
+    pub version: String,
+    /// The platforms locking this version
+    pub platforms: Vec<String>,
+    /// The latest version, when it's newer than the locked one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latest_version: Option<String>,
+    /// For a conda package, the OSV package its advisories were looked up
+    /// under, e.g. `PyPI/torch`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub matched_as: Option<String>,
+    pub vulnerabilities: Vec<Vulnerability>,
+}
+
+/// An advisory affecting a locked version
+#[derive(Debug, Serialize, Clone, Deserialize, PartialEq, Eq)]
+pub struct Vulnerability {
+    /// The OSV identifier, e.g. `GHSA-9wx4-h78v-vm56` or `PYSEC-2023-74`
+    pub id: String,
+    /// Other identifiers of the same vulnerability, such as its CVE
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub aliases: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+    /// The versions that fix it, if any were released
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fixed_in: Vec<String>,
+    /// Whether updating to the latest version fixes it
+    pub fixed_by_latest: bool,
+}
+
+impl VulnerablePackage {
+    /// Whether updating to the latest version fixes every vulnerability
+    pub fn fixed_by_latest(&self) -> bool {
+        self.vulnerabilities.iter().all(|v| v.fixed_by_latest)
+    }
+}
+
+impl std::fmt::Display for VulnerablePackage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let vulnerabilities: Vec<String> = self
+            .vulnerabilities
+            .iter()
+            .map(|vulnerability| match vulnerability.fixed_in.as_slice() {
+                [] => format!("{} (no fix released)", vulnerability.id),
+                fixed_in => format!("{} (fixed in {})", vulnerability.id, fixed_in.join(", ")),
+            })
+            .collect();
+        write!(
+            f,
+            "{} {}: {}",
+            self.name,
+            self.version,
+            vulnerabilities.join(", ")
+        )?;
+        match self.latest_version {
+            Some(ref latest) if self.fixed_by_latest() => write!(f, "; {} fixes all", latest),
+            Some(ref latest) => write!(f, "; still affected in {}", latest),
+            None => Ok(()),
+        }
+    }
+}
+
+/// An OSV advisory, as `/v1/vulns/<id>` returns it
+#[derive(Debug, Clone, Deserialize)]
+pub struct Advisory {
+    pub id: String,
+    #[serde(default)]
+    pub summary: Option<String>,
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    #[serde(default)]
+    affected: Vec<Affected>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct Affected {
+    package: AffectedPackage,
+    #[serde(default)]
+    ranges: Vec<AffectedRange>,
+    /// Affected versions listed one by one
+    #[serde(default)]
+    versions: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct AffectedPackage {
+    name: String,
+    ecosystem: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct AffectedRange {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    events: Vec<RangeEvent>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+struct RangeEvent {
+    #[serde(default)]
+    introduced: Option<String>,
+    #[serde(default)]
+    fixed: Option<String>,
+    #[serde(default)]
+    last_affected: Option<String>,
+}
+
+/// PyPI names compare case-insensitively, with `-`, `_` and `.` alike
+fn normalize_name(name: &str) -> String {
+    name.to_lowercase().replace(['_', '.'], "-")
+}
+
+/// Compare versions of an ecosystem: PEP 440 for PyPI, and conda's
+/// ordering, which fits most upstream version schemes, for the rest
+fn compare_versions(ecosystem: &str, a: &str, b: &str) -> Option<Ordering> {
+    if ecosystem == PYPI_ECOSYSTEM {
+        let a = pep440_rs::Version::from_str(a).ok()?;
+        let b = pep440_rs::Version::from_str(b).ok()?;
+        Some(a.cmp(&b))
+    } else {
+        let a = rattler_conda_types::Version::from_str(a).ok()?;
+        let b = rattler_conda_types::Version::from_str(b).ok()?;
+        Some(a.cmp(&b))
+    }
+}
+
+impl Advisory {
+    fn affected<'a>(
+        &'a self,
+        ecosystem: &'a str,
+        name: &'a str,
+    ) -> impl Iterator<Item = &'a Affected> {
+        let name = normalize_name(name);
+        self.affected.iter().filter(move |affected| {
+            affected.package.ecosystem == ecosystem
+                && normalize_name(&affected.package.name) == name
+        })
+    }
+
+    /// Whether the advisory affects `version` of a package. Versions are
+    /// affected from an `introduced` event until the next `fixed` one, or
+    /// through a `last_affected` one.
+    pub fn affects(&self, ecosystem: &str, name: &str, version: &str) -> bool {
+        self.affected(ecosystem, name).any(|affected| {
+            affected.versions.iter().any(|v| v == version)
+                || affected
+                    .ranges
+                    .iter()
+                    .filter(|range| range.kind == "ECOSYSTEM")
+                    .any(|range| {
+                        let mut affects = false;
+                        for event in &range.events {
+                            let at_least = |bound: &str| {
+                                bound == "0"
+                                    || compare_versions(ecosystem, version, bound)
+                                        .is_some_and(Ordering::is_ge)
+                            };
+                            if event.introduced.as_deref().is_some_and(at_least) {
+                                affects = true;
+                            }
+                            if event.fixed.as_deref().is_some_and(at_least) {
+                                affects = false;
+                            }
+                            if let Some(ref last) = event.last_affected {
+                                if compare_versions(ecosystem, version, last)
+                                    .is_some_and(Ordering::is_gt)
+                                {
+                                    affects = false;
+                                }
+                            }
+                        }
+                        affects
+                    })
+        })
+    }
+
+    /// The versions fixing the advisory for a package, oldest first
+    pub fn fixed_versions(&self, ecosystem: &str, name: &str) -> Vec<String> {
+        let mut fixed: Vec<String> = self
+            .affected(ecosystem, name)
+            .flat_map(|affected| &affected.ranges)
+            .flat_map(|range| &range.events)
+            .filter_map(|event| event.fixed.clone())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        fixed.sort_by(|a, b| compare_versions(ecosystem, a, b).unwrap_or_else(|| a.cmp(b)));
+        fixed
+    }
+}
+
+/// A package version to look up advisories for
+#[derive(Debug, Clone, Serialize)]
+struct Query<'a> {
+    package: QueryPackage<'a>,
+    version: &'a str,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct QueryPackage<'a> {
+    name: &'a str,
+    ecosystem: &'a str,
+}
+
+#[derive(Debug, Deserialize)]
+struct BatchResponse {
+    #[serde(default)]
+    results: Vec<BatchResult>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct BatchResult {
+    #[serde(default)]
+    vulns: Vec<BatchVuln>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BatchVuln {
+    id: String,
+}
+
+/// Where advisories come from
+#[derive(Debug, Clone)]
+pub enum OsvSource {
+    /// An OSV API, such as [`OSV_API_URL`]
+    Api(String),
+    /// Advisories on disk, as `<dir>/<ecosystem>/<package>.json` holding
+    /// every advisory of the package, for hermetic tests
+    Fixtures(PathBuf),
+}
+
+impl Default for OsvSource {
+    fn default() -> Self {
+        OsvSource::Api(OSV_API_URL.to_string())
+    }
+}
+
+impl OsvSource {
+    /// The advisories affecting each of the given package versions
+    async fn advisories(
+        &self,
+        client: &reqwest::Client,
+        queries: &[Query<'_>],
+    ) -> Result<Vec<Vec<Advisory>>> {
+        match self {
+            OsvSource::Api(url) => query_api(client, url.trim_end_matches('/'), queries).await,
+            OsvSource::Fixtures(dir) => queries
+                .iter()
+                .map(|query| -> Result<Vec<Advisory>> {
+                    let path = dir
+                        .join(query.package.ecosystem)
+                        .join(format!("{}.json", normalize_name(query.package.name)));
+                    if !path.is_file() {
+                        return Ok(Vec::new());
+                    }
+                    let content = fs::read_to_string(&path)
+                        .with_context(|| format!("Failed to read {}", path.display()))?;
+                    let advisories: Vec<Advisory> = serde_json::from_str(&content)
+                        .with_context(|| format!("Failed to parse {}", path.display()))?;
+                    Ok(advisories
+                        .into_iter()
+                        .filter(|advisory| {
+                            advisory.affects(
+                                query.package.ecosystem,
+                                query.package.name,
+                                query.version,
+                            )
+                        })
+                        .collect())
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Ask `querybatch` which advisories affect each version, then fetch the
+/// details of each advisory, which the batch endpoint leaves out
+async fn query_api(
+    client: &reqwest::Client,
+    url: &str,
+    queries: &[Query<'_>],
+) -> Result<Vec<Vec<Advisory>>> {
+    let mut ids: Vec<Vec<String>> = Vec::with_capacity(queries.len());
+    for chunk in queries.chunks(QUERY_BATCH_SIZE) {
+        let response: BatchResponse = client
+            .post(format!("{}/querybatch", url))
+            .timeout(REQUEST_TIMEOUT)
+            .json(&serde_json::json!({ "queries": chunk }))
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .with_context(|| format!("Failed to query {}/querybatch", url))?
+            .json()
+            .await
+            .with_context(|| format!("Failed to parse the response of {}/querybatch", url))?;
+        let mut results = response.results.into_iter();
+        ids.extend(chunk.iter().map(|_| {
+            results
+                .next()
+                .unwrap_or_default()
+                .vulns
+                .into_iter()
+                .map(|vuln| vuln.id)
+                .collect()
+        }));
+    }
+
+    let unique: BTreeSet<&String> = ids.iter().flatten().collect();
+    let fetched: Vec<Result<Advisory>> = stream::iter(unique)
+        .map(|id| {
+            let client = &client;
+            async move {
+                client
+                    .get(format!("{}/vulns/{}", url, id))
+                    .timeout(REQUEST_TIMEOUT)
+                    .send()
+                    .await
+                    .and_then(reqwest::Response::error_for_status)
+                    .with_context(|| format!("Failed to fetch advisory {}", id))?
+                    .json::<Advisory>()
+                    .await
+                    .with_context(|| format!("Failed to parse advisory {}", id))
+            }
+        })
+        .buffer_unordered(ADVISORY_JOBS)
+        .collect()
+        .await;
+    let mut advisories = HashMap::new();
+    for advisory in fetched {
+        let advisory = advisory?;
+        advisories.insert(advisory.id.clone(), advisory);
+    }
+
+    Ok(ids
+        .into_iter()
+        .map(|ids| {
+            ids.iter()
+                .filter_map(|id| advisories.get(id).cloned())
+                .collect()
+        })
+        .collect())
+}
+
+/// Conda packages whose PyPI project goes by another name
+const PYPI_NAMES: &[(&str, &str)] = &[
+    ("matplotlib-base", "matplotlib"),
+    ("msgpack-python", "msgpack"),
+    ("pytorch", "torch"),
+    ("tensorflow-base", "tensorflow"),
+];
+
+/// A package as OSV files advisories under it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OsvPackage {
+    pub ecosystem: String,
+    pub name: String,
+}
+
+impl OsvPackage {
+    /// Parse `<ecosystem>/<package>`, e.g. `PyPI/torch` or `OSS-Fuzz/libxml2`
+    pub fn parse(text: &str) -> Result<Self> {
+        match text.split_once('/') {
+            Some((ecosystem, name)) if !ecosystem.is_empty() && !name.is_empty() => Ok(Self {
+                ecosystem: ecosystem.to_string(),
+                name: name.to_string(),
+            }),
+            _ => anyhow::bail!(
+                "Invalid OSV package '{}' (expected <ecosystem>/<package>)",
+                text
+            ),
+        }
+    }
+}
+
+impl std::fmt::Display for OsvPackage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}", self.ecosystem, self.name)
+    }
+}
+
+/// The OSV package the advisories of a locked package are filed under.
+/// PyPI packages are their own. Conda packages are looked up in
+/// `overrides` (the `osv-packages` setting), and otherwise matched to the
+/// PyPI project of the same name if they are Python packages, which
+/// conda-forge rebuilds from the PyPI release of the same version. Other
+/// conda packages aren't covered.
+pub fn osv_package(
+    package: &PixiPackage,
+    overrides: &BTreeMap<String, OsvPackage>,
+) -> Option<OsvPackage> {
+    match package.kind {
+        PackageKind::Pypi => Some(OsvPackage {
+            ecosystem: PYPI_ECOSYSTEM.to_string(),
+            name: package.name.clone(),
+        }),
+        PackageKind::Conda => {
+            if let Some(target) = overrides.get(&package.name) {
+                return Some(target.clone());
+            }
+            let is_python_package = package.depends.iter().any(|name| name == "python");
+            is_python_package.then(|| OsvPackage {
+                ecosystem: PYPI_ECOSYSTEM.to_string(),
+                name: PYPI_NAMES
+                    .iter()
+                    .find(|(conda, _)| *conda == package.name)
+                    .map_or(package.name.as_str(), |&(_, pypi)| pypi)
+                    .to_string(),
+            })
+        }
+    }
+}
+
+/// The locked conda packages that can't be audited because OSV files their
+/// advisories under no known package: neither Python packages nor mapped in
+/// `overrides`. Sorted, each named once.
+pub fn unaudited(report: &Report, overrides: &BTreeMap<String, OsvPackage>) -> Vec<String> {
+    let names: BTreeSet<&str> = report
+        .locked
+        .values()
+        .flatten()
+        .filter(|package| osv_package(package, overrides).is_none())
+        .map(|package| package.name.as_str())
+        .collect();
+    names.into_iter().map(str::to_string).collect()
+}
+
+/// Parse the `osv-packages` setting, which maps conda package names to
+/// `<ecosystem>/<package>`
+pub fn parse_overrides(
+    overrides: &BTreeMap<String, String>,
+) -> Result<BTreeMap<String, OsvPackage>> {
+    overrides
+        .iter()
+        .map(|(name, target)| {
+            let target = OsvPackage::parse(target)
+                .with_context(|| format!("Invalid osv-packages entry for {}", name))?;
+            Ok((name.clone(), target))
+        })
+        .collect()
+}
+
+/// The latest version the report found for a locked version, if newer
+fn latest_version(report: &Report, kind: PackageKind, name: &str, version: &str) -> Option<String> {
+    report
+        .platforms
+        .values()
+        .flatten()
+        .find(|update| {
+            update.name == name
+                && update.installed_version == version
+                && update.kind.is_none_or(|update_kind| update_kind == kind)
+        })
+        .map(|update| update.latest_version.clone())
+}
+
+/// Look up the locked versions of the report in OSV, along with the latest
+/// version the report found for each, to tell whether updating fixes the
+/// advisories found
+pub async fn audit(
+    client: &reqwest::Client,
+    source: &OsvSource,
+    report: &Report,
+    overrides: &BTreeMap<String, OsvPackage>,
+) -> Result<Vec<VulnerablePackage>> {
+    // (kind, name, version) -> (OSV package, platforms)
+    type Locked<'a> = BTreeMap<(PackageKind, &'a str, &'a str), (OsvPackage, Vec<String>)>;
+    let mut locked: Locked = BTreeMap::new();
+    for (platform, packages) in &report.locked {
+        for package in packages {
+            let Some(osv) = osv_package(package, overrides) else {
+                continue;
+            };
+            locked
+                .entry((
+                    package.kind,
+                    package.name.as_str(),
+                    package.version.as_str(),
+                ))
+                .or_insert_with(|| (osv, Vec::new()))
+                .1
+                .push(platform.clone());
+        }
+    }
+
+    let latest: Vec<Option<String>> = locked
+        .keys()
+        .map(|&(kind, name, version)| latest_version(report, kind, name, version))
+        .collect();
+    let mut queries = Vec::new();
+    for ((&(_, _, version), (osv, _)), latest) in locked.iter().zip(&latest) {
+        let package = QueryPackage {
+            name: &osv.name,
+            ecosystem: &osv.ecosystem,
+        };
+        queries.push(Query {
+            package: package.clone(),
+            version,
+        });
+        if let Some(latest) = latest {
+            queries.push(Query {
+                package,
+                version: latest,
+            });
+        }
+    }
+    let mut advisories = source.advisories(client, &queries).await?.into_iter();
+
+    let mut vulnerable = Vec::new();
+    for (((kind, name, version), (osv, platforms)), latest_version) in
+        locked.into_iter().zip(latest)
+    {
+        let installed = advisories.next().unwrap_or_default();
+        let still_affected: BTreeSet<String> = match latest_version {
+            Some(_) => advisories
+                .next()
+                .unwrap_or_default()
+                .into_iter()
+                .map(|advisory| advisory.id)
+                .collect(),
+            None => BTreeSet::new(),
+        };
+        if installed.is_empty() {
+            continue;
+        }
+        let vulnerabilities = installed
+            .into_iter()
+            .map(|advisory| Vulnerability {
+                fixed_in: advisory.fixed_versions(&osv.ecosystem, &osv.name),
+                fixed_by_latest: latest_version.is_some() && !still_affected.contains(&advisory.id),
+                id: advisory.id,
+                aliases: advisory.aliases,
+                summary: advisory.summary,
+            })
+            .collect();
+        vulnerable.push(VulnerablePackage {
+            name: name.to_string(),
+            kind,
+            version: version.to_string(),
+            platforms,
+            latest_version,
+            matched_as: (kind == PackageKind::Conda).then(|| osv.to_string()),
+            vulnerabilities,
+        });
+    }
+    Ok(vulnerable)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::{PackageUpdate, PlatformUpdates};
+
+    const ADVISORY: &str = r#"{
+        "id": "GHSA-9wx4-h78v-vm56",
+        "summary": "Requests leaks Proxy-Authorization headers",
+        "aliases": ["CVE-2023-32681"],
+        "affected": [{
+            "package": {"name": "requests", "ecosystem": "PyPI"},
+            "ranges": [{
+                "type": "ECOSYSTEM",
+                "events": [{"introduced": "2.3.0"}, {"fixed": "2.31.0"}]
+            }],
+            "versions": ["2.3.0", "2.30.0"]
+        }]
+    }"#;
+
+    fn advisory() -> Advisory {
+        serde_json::from_str(ADVISORY).unwrap()
+    }
+
+    #[test]
+    fn test_affects() {
+        let advisory = advisory();
+        assert!(advisory.affects("PyPI", "requests", "2.30.0"));
+        assert!(advisory.affects("PyPI", "Requests", "2.28.1"));
+        assert!(!advisory.affects("PyPI", "requests", "2.31.0"));
+        assert!(!advisory.affects("PyPI", "requests", "2.2.1"));
+        assert!(!advisory.affects("PyPI", "urllib3", "2.30.0"));
+        assert_eq!(advisory.fixed_versions("PyPI", "requests"), vec!["2.31.0"]);
+    }
+
+    #[test]
+    fn test_affects_last_affected() {
+        let advisory: Advisory = serde_json::from_str(
+            r#"{
+                "id": "PYSEC-0000-1",
+                "affected": [{
+                    "package": {"name": "cowsay", "ecosystem": "PyPI"},
+                    "ranges": [{
+                        "type": "ECOSYSTEM",
+                        "events": [{"introduced": "0"}, {"last_affected": "5.0"}]
+                    }]
+                }]
+            }"#,
+        )
+        .unwrap();
+        assert!(advisory.affects("PyPI", "cowsay", "5.0"));
+        assert!(!advisory.affects("PyPI", "cowsay", "6.0"));
+        assert!(advisory.fixed_versions("PyPI", "cowsay").is_empty());
+    }
+
+    fn report() -> Report {
+        let package = PixiPackage {
+            name: "requests".to_string(),
+            version: "2.28.1".to_string(),
+            build: None,
+            size_bytes: None,
+            kind: PackageKind::Pypi,
+            source: None,
+            is_explicit: true,
+            depends: vec![],
+        };
+        let mut report = Report::default();
+        for platform in ["linux-64", "osx-arm64"] {
+            report
+                .locked
+                .insert(platform.to_string(), vec![package.clone()]);
+        }
+        let mut platforms = PlatformUpdates::new();
+        platforms.insert(
+            "linux-64".to_string(),
+            vec![PackageUpdate {
+                name: "requests".to_string(),
+                kind: Some(PackageKind::Pypi),
+                installed_version: "2.28.1".to_string(),
+                latest_version: "2.32.3".to_string(),
+                ..Default::default()
+            }],
+        );
+        report.platforms = platforms;
+        report
+    }
+
+    #[tokio::test]
+    async fn test_audit() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("PyPI")).unwrap();
+        fs::write(
+            dir.path().join("PyPI/requests.json"),
+            format!("[{}]", ADVISORY),
+        )
+        .unwrap();
+
+        let source = OsvSource::Fixtures(dir.path().to_path_buf());
+        let vulnerable = audit(
+            &reqwest::Client::new(),
+            &source,
+            &report(),
+            &BTreeMap::new(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(vulnerable.len(), 1);
+        let requests = &vulnerable[0];
+        assert_eq!(requests.platforms, vec!["linux-64", "osx-arm64"]);
+        assert_eq!(requests.latest_version.as_deref(), Some("2.32.3"));
+        assert!(requests.fixed_by_latest());
+        assert_eq!(requests.vulnerabilities[0].aliases, vec!["CVE-2023-32681"]);
+        assert_eq!(
+            requests.to_string(),
+            "requests 2.28.1: GHSA-9wx4-h78v-vm56 (fixed in 2.31.0); 2.32.3 fixes all"
+        );
+    }
+
+    #[test]
+    fn test_unaudited() {
+        let mut report = report();
+        for platform in ["linux-64", "osx-arm64"] {
+            let locked = report.locked.get_mut(platform).unwrap();
+            locked.push(conda("openssl", &["ca-certificates"]));
+            locked.push(conda("numpy", &["python"]));
+            locked.push(conda("libxml2", &["libzlib"]));
+        }
+        let overrides = BTreeMap::from([(
+            "libxml2".to_string(),
+            OsvPackage::parse("OSS-Fuzz/libxml2").unwrap(),
+        )]);
+
+        assert_eq!(unaudited(&report, &overrides), vec!["openssl"]);
+        assert_eq!(
+            unaudited(&report, &BTreeMap::new()),
+            vec!["libxml2", "openssl"]
+        );
+    }
+
+    fn conda(name: &str, depends: &[&str]) -> PixiPackage {
+        PixiPackage {
+            name: name.to_string(),
+            version: "1.0".to_string(),
+            build: None,
+            size_bytes: None,
+            kind: PackageKind::Conda,
+            source: None,
+            is_explicit: false,
+            depends: depends.iter().map(|name| name.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_osv_package() {
+        let overrides = parse_overrides(&BTreeMap::from([(
+            "libxml2".to_string(),
+            "OSS-Fuzz/libxml2".to_string(),
+        )]))
+        .unwrap();
+        let osv = |package| osv_package(&package, &overrides).map(|osv| osv.to_string());
+
+        assert_eq!(
+            osv(conda("numpy", &["libblas", "python"])).as_deref(),
+            Some("PyPI/numpy")
+        );
+        assert_eq!(
+            osv(conda("pytorch", &["python"])).as_deref(),
+            Some("PyPI/torch")
+        );
+        assert_eq!(
+            osv(conda("libxml2", &["libzlib"])).as_deref(),
+            Some("OSS-Fuzz/libxml2")
+        );
+        assert_eq!(osv(conda("openssl", &["ca-certificates"])), None);
+
+        assert!(parse_overrides(&BTreeMap::from([(
+            "openssl".to_string(),
+            "openssl".to_string()
+        )]))
+        .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_audit_conda_package() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("PyPI")).unwrap();
+        fs::write(
+            dir.path().join("PyPI/requests.json"),
+            format!("[{}]", ADVISORY),
+        )
+        .unwrap();
+
+        let mut package = conda("requests", &["python", "urllib3"]);
+        package.version = "2.30.0".to_string();
+        let mut report = Report::default();
+        report.locked.insert("noarch".to_string(), vec![package]);
+
+        let source = OsvSource::Fixtures(dir.path().to_path_buf());
+        let vulnerable = audit(&reqwest::Client::new(), &source, &report, &BTreeMap::new())
+            .await
+            .unwrap();
+        assert_eq!(vulnerable[0].kind, PackageKind::Conda);
+        assert_eq!(vulnerable[0].matched_as.as_deref(), Some("PyPI/requests"));
+        // Without a newer version, updating fixes nothing
+        assert!(!vulnerable[0].fixed_by_latest());
+        assert_eq!(
+            vulnerable[0].to_string(),
+            "requests 2.30.0: GHSA-9wx4-h78v-vm56 (fixed in 2.31.0)"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_audit_without_advisories() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = OsvSource::Fixtures(dir.path().to_path_buf());
+        assert!(audit(
+            &reqwest::Client::new(),
+            &source,
+            &report(),
+            &BTreeMap::new()
+        )
+        .await
+        .unwrap()
+        .is_empty());
+    }
+}
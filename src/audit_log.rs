@@ -0,0 +1,164 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+use std::process::Command;
+
+use crate::report::{Report, RunStatus};
+
+/// One line of the `--audit-log`, summarizing a single run
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct AuditRecord {
+    pub timestamp: DateTime<Utc>,
+    /// The pixi-outdated version that ran the check
+    pub tool_version: String,
+    /// The commit checked out in the workspace, if it is a git repository
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git_sha: Option<String>,
+    pub environment: String,
+    pub status: RunStatus,
+    /// Distinct packages with an update on any platform
+    pub outdated: usize,
+    /// Distinct packages declared in the manifest with an update
+    pub outdated_direct: usize,
+    /// Updates counted per platform
+    pub updates: usize,
+    /// Locked versions that were yanked or removed upstream
+    pub locked_issues: usize,
+    /// Packages that were not found upstream or whose query failed, counted per platform
+    #[serde(default)]
+    pub unchecked: usize,
+}
+
+impl AuditRecord {
+    pub fn new(
+        report: &Report,
+        environment: &str,
+        git_sha: Option<String>,
+        timestamp: DateTime<Utc>,
+    ) -> Self {
+        let outdated: BTreeSet<&str> = report
+            .platforms
+            .values()
+            .flatten()
+            .map(|update| update.name.as_str())
+            .collect();
+
+        Self {
+            timestamp,
+            tool_version: env!("CARGO_PKG_VERSION").to_string(),
+            git_sha,
+            environment: environment.to_string(),
+            status: report.status,
+            outdated: outdated.len(),
+            outdated_direct: report.outdated_direct().len(),
+            updates: report.platforms.values().map(Vec::len).sum(),
+            locked_issues: report.locked_issues.len(),
+            unchecked: report.unchecked.len(),
+        }
+    }
+
+    /// Append the record as a single JSON line, creating the file if needed
+    pub fn append_to(&self, path: &Path) -> Result<()> {
+        let mut line = serde_json::to_string(self)?;
+        line.push('\n');
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("Failed to open audit log {}", path.display()))?;
+        file.write_all(line.as_bytes())
+            .with_context(|| format!("Failed to write audit log {}", path.display()))
+    }
+}
+
+/// The commit checked out in `dir`, or `None` outside a git repository or
+/// without git installed
+pub fn git_sha(dir: &Path) -> Option<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(["rev-parse", "HEAD"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let sha = String::from_utf8(output.stdout).ok()?.trim().to_string();
+    (!sha.is_empty()).then_some(sha)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::{LockedIssue, LockedStatus, PackageUpdate, PlatformUpdates};
+
+    fn update(name: &str, direct: bool) -> PackageUpdate {
+        PackageUpdate {
+            name: name.to_string(),
+            installed_version: "1.0".to_string(),
+            latest_version: "2.0".to_string(),
+            direct,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_record_counts() {
+        let mut platforms = PlatformUpdates::new();
+        platforms.insert(
+            "linux-64".to_string(),
+            vec![update("python", true), update("openssl", false)],
+        );
+        platforms.insert("osx-arm64".to_string(), vec![update("python", true)]);
+        let report = Report {
+            platforms,
+            locked_issues: vec![LockedIssue {
+                name: "cowsay".to_string(),
+                version: "5.0".to_string(),
+                platform: "linux-64".to_string(),
+                status: LockedStatus::Removed,
+                annotations: Default::default(),
+            }],
+            ..Report::default()
+        };
+
+        let record = AuditRecord::new(&report, "default", None, Utc::now());
+        assert_eq!(record.outdated, 2);
+        assert_eq!(record.outdated_direct, 1);
+        assert_eq!(record.updates, 3);
+        assert_eq!(record.locked_issues, 1);
+        assert_eq!(record.tool_version, env!("CARGO_PKG_VERSION"));
+    }
+
+    #[test]
+    fn test_append_to() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audit.ndjson");
+        let record = AuditRecord::new(
+            &Report::default(),
+            "default",
+            Some("0123abc".to_string()),
+            Utc::now(),
+        );
+
+        record.append_to(&path).unwrap();
+        record.append_to(&path).unwrap();
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(lines.len(), 2);
+        let parsed: AuditRecord = serde_json::from_str(lines[1]).unwrap();
+        assert_eq!(parsed, record);
+    }
+
+    #[test]
+    fn test_git_sha_outside_repository() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(git_sha(dir.path()), None);
+    }
+}
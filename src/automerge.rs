@@ -0,0 +1,336 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fmt;
+
+use crate::audit::VulnerablePackage;
+use crate::report::PlatformUpdates;
+use crate::severity::Severity;
+
+/// Rules deciding which updates may be merged without review, configured in
+/// `[tool.pixi-outdated.auto-merge]`
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields, default)]
+pub struct AutoMergeRules {
+    /// The biggest update that may be merged automatically
+    pub max_severity: Severity,
+    /// Whether the latest version may depend on packages the installed
+    /// version doesn't
+    pub allow_new_dependencies: bool,
+    /// Whether an update may jump over releases that were yanked, commonly
+    /// for security problems
+    pub allow_skipped_yanked: bool,
+    /// Whether packages that ship activation scripts may be updated, which
+    /// can change the environment of every shell it's activated in
+    pub allow_activation_scripts: bool,
+    /// Whether an update may leave the package with a known vulnerability
+    /// that the latest version doesn't fix
+    pub allow_vulnerable: bool,
+}
+
+impl Default for AutoMergeRules {
+    fn default() -> Self {
+        Self {
+            max_severity: Severity::Patch,
+            allow_new_dependencies: false,
+            allow_skipped_yanked: false,
+            allow_activation_scripts: false,
+            allow_vulnerable: false,
+        }
+    }
+}
+
+/// Whether a bot may merge an update on its own
+#[derive(Debug, Serialize, Clone, Copy, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "kebab-case")]
+pub enum MergeDecision {
+    AutoMergeable,
+    ReviewRequired,
+}
+
+impl MergeDecision {
+    /// The identifier used in JSON and CSV output
+    pub fn as_str(self) -> &'static str {
+        match self {
+            MergeDecision::AutoMergeable => "auto-mergeable",
+            MergeDecision::ReviewRequired => "review-required",
+        }
+    }
+}
+
+impl fmt::Display for MergeDecision {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MergeDecision::AutoMergeable => write!(f, "auto-mergeable"),
+            MergeDecision::ReviewRequired => write!(f, "review required"),
+        }
+    }
+}
+
+/// The facts about a single update that the rules are checked against
+#[derive(Debug, Clone)]
+pub struct MergeInput<'a> {
+    pub severity: Severity,
+    /// Dependencies of the installed version
+    pub installed_dependencies: &'a [String],
+    /// Dependencies of the latest version, or `None` if the channel or index
+    /// didn't describe them
+    pub latest_dependencies: Option<&'a [String]>,
+    /// Yanked releases between the installed and the latest version
+    pub skipped_yanked: &'a [String],
+    /// Activation scripts the installed version ships
+    pub activation_scripts: &'a [String],
+}
+
+impl AutoMergeRules {
+    /// Decide whether an update can be merged automatically, and why not
+    pub fn decide(&self, input: &MergeInput) -> (MergeDecision, Vec<String>) {
+        let mut reasons = Vec::new();
+
+        if input.severity > self.max_severity {
+            reasons.push(format!("{} update", input.severity));
+        }
+
+        if !self.allow_new_dependencies {
+            match input.latest_dependencies {
+                None => reasons.push("dependencies of the latest version unknown".to_string()),
+                Some(latest) => {
+                    let installed: BTreeSet<&str> = input
+                        .installed_dependencies
+                        .iter()
+                        .map(String::as_str)
+                        .collect();
+                    let added: BTreeSet<&str> = latest
+                        .iter()
+                        .map(String::as_str)
+                        .filter(|name| !installed.contains(name))
+                        .collect();
+                    if !added.is_empty() {
+                        reasons.push(format!(
+                            "new dependencies: {}",
+                            added.into_iter().collect::<Vec<_>>().join(", ")
+                        ));
+                    }
+                }
+            }
+        }
+
+        if !self.allow_skipped_yanked && !input.skipped_yanked.is_empty() {
+            reasons.push(format!("skips yanked {}", input.skipped_yanked.join(", ")));
+        }
+
+        if !self.allow_activation_scripts && !input.activation_scripts.is_empty() {
+            reasons.push("ships activation scripts".to_string());
+        }
+
+        let decision = if reasons.is_empty() {
+            MergeDecision::AutoMergeable
+        } else {
+            MergeDecision::ReviewRequired
+        };
+        (decision, reasons)
+    }
+
+    /// Require review for labeled updates that leave a vulnerability from the
+    /// audit unfixed, and for all of them if the audit was unavailable
+    /// (`None`). Packages the audit couldn't look up pass.
+    pub fn review_vulnerable(
+        &self,
+        updates: &mut PlatformUpdates,
+        vulnerable: Option<&[VulnerablePackage]>,
+    ) {
+        if self.allow_vulnerable {
+            return;
+        }
+        for (platform, platform_updates) in updates.iter_mut() {
+            for update in platform_updates
+                .iter_mut()
+                .filter(|update| update.merge.is_some())
+            {
+                let reason = match vulnerable {
+                    None => Some("vulnerability audit unavailable".to_string()),
+                    Some(vulnerable) => {
+                        let unfixed: Vec<&str> = vulnerable
+                            .iter()
+                            .filter(|package| {
+                                package.name == update.name
+                                    && update.kind.map_or(true, |kind| kind == package.kind)
+                                    && package.version == update.installed_version
+                                    && package.platforms.contains(platform)
+                            })
+                            .flat_map(|package| &package.vulnerabilities)
+                            .filter(|vulnerability| !vulnerability.fixed_by_latest)
+                            .map(|vulnerability| vulnerability.id.as_str())
+                            .collect();
+                        (!unfixed.is_empty()).then(|| {
+                            format!("latest version still affected by {}", unfixed.join(", "))
+                        })
+                    }
+                };
+                if let Some(reason) = reason {
+                    update.merge = Some(MergeDecision::ReviewRequired);
+                    update.merge_reasons.push(reason);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::Vulnerability;
+    use crate::pixi::PackageKind;
+    use crate::report::PackageUpdate;
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn test_patch_update_is_auto_mergeable() {
+        let deps = names(&["python", "libzlib"]);
+        let input = MergeInput {
+            severity: Severity::Patch,
+            installed_dependencies: &deps,
+            latest_dependencies: Some(&deps),
+            skipped_yanked: &[],
+            activation_scripts: &[],
+        };
+        assert_eq!(
+            AutoMergeRules::default().decide(&input),
+            (MergeDecision::AutoMergeable, vec![])
+        );
+    }
+
+    #[test]
+    fn test_review_reasons() {
+        let installed = names(&["python"]);
+        let latest = names(&["python", "typing-extensions", "zstd"]);
+        let yanked = names(&["2.0.1"]);
+        let input = MergeInput {
+            severity: Severity::Minor,
+            installed_dependencies: &installed,
+            latest_dependencies: Some(&latest),
+            skipped_yanked: &yanked,
+            activation_scripts: &[],
+        };
+
+        let (decision, reasons) = AutoMergeRules::default().decide(&input);
+        assert_eq!(decision, MergeDecision::ReviewRequired);
+        assert_eq!(
+            reasons,
+            vec![
+                "minor update",
+                "new dependencies: typing-extensions, zstd",
+                "skips yanked 2.0.1"
+            ]
+        );
+
+        let permissive = AutoMergeRules {
+            max_severity: Severity::Minor,
+            allow_new_dependencies: true,
+            allow_skipped_yanked: true,
+            allow_activation_scripts: false,
+            allow_vulnerable: false,
+        };
+        assert_eq!(permissive.decide(&input).0, MergeDecision::AutoMergeable);
+    }
+
+    #[test]
+    fn test_unknown_dependencies_need_review() {
+        let input = MergeInput {
+            severity: Severity::Patch,
+            installed_dependencies: &[],
+            latest_dependencies: None,
+            skipped_yanked: &[],
+            activation_scripts: &[],
+        };
+        let (decision, reasons) = AutoMergeRules::default().decide(&input);
+        assert_eq!(decision, MergeDecision::ReviewRequired);
+        assert_eq!(reasons, vec!["dependencies of the latest version unknown"]);
+    }
+
+    #[test]
+    fn test_activation_scripts_need_review() {
+        let deps = names(&["openssl"]);
+        let scripts = names(&["etc/conda/activate.d/openssl_activate.sh"]);
+        let input = MergeInput {
+            severity: Severity::Patch,
+            installed_dependencies: &deps,
+            latest_dependencies: Some(&deps),
+            skipped_yanked: &[],
+            activation_scripts: &scripts,
+        };
+        let (decision, reasons) = AutoMergeRules::default().decide(&input);
+        assert_eq!(decision, MergeDecision::ReviewRequired);
+        assert_eq!(reasons, vec!["ships activation scripts"]);
+
+        let permissive = AutoMergeRules {
+            allow_activation_scripts: true,
+            ..Default::default()
+        };
+        assert_eq!(permissive.decide(&input).0, MergeDecision::AutoMergeable);
+    }
+
+    #[test]
+    fn test_review_vulnerable() {
+        let update = |name: &str, installed: &str| PackageUpdate {
+            name: name.to_string(),
+            kind: Some(PackageKind::Pypi),
+            installed_version: installed.to_string(),
+            latest_version: "6.0".to_string(),
+            merge: Some(MergeDecision::AutoMergeable),
+            ..Default::default()
+        };
+        let vulnerability = |id: &str, fixed_by_latest: bool| Vulnerability {
+            id: id.to_string(),
+            aliases: vec![],
+            summary: None,
+            fixed_in: vec![],
+            fixed_by_latest,
+        };
+        let vulnerable = vec![VulnerablePackage {
+            name: "cowsay".to_string(),
+            kind: PackageKind::Pypi,
+            version: "5.0".to_string(),
+            platforms: vec!["linux-64".to_string()],
+            latest_version: Some("6.0".to_string()),
+            matched_as: None,
+            vulnerabilities: vec![
+                vulnerability("PYSEC-1", true),
+                vulnerability("PYSEC-2", false),
+            ],
+        }];
+        let mut updates = PlatformUpdates::from([(
+            "linux-64".to_string(),
+            vec![update("cowsay", "5.0"), update("requests", "2.0")],
+        )]);
+
+        AutoMergeRules::default().review_vulnerable(&mut updates, Some(&vulnerable));
+        let [cowsay, requests] = updates["linux-64"].as_slice() else {
+            panic!("expected two updates");
+        };
+        assert_eq!(cowsay.merge, Some(MergeDecision::ReviewRequired));
+        assert_eq!(
+            cowsay.merge_reasons,
+            vec!["latest version still affected by PYSEC-2"]
+        );
+        assert_eq!(requests.merge, Some(MergeDecision::AutoMergeable));
+
+        // Without an audit, nothing is known to be safe
+        AutoMergeRules::default().review_vulnerable(&mut updates, None);
+        assert_eq!(
+            updates["linux-64"][1].merge,
+            Some(MergeDecision::ReviewRequired)
+        );
+    }
+
+    #[test]
+    fn test_rules_from_toml() {
+        let rules: AutoMergeRules =
+            toml::from_str("max-severity = \"minor\"\nallow-new-dependencies = true\n").unwrap();
+        assert_eq!(rules.max_severity, Severity::Minor);
+        assert!(rules.allow_new_dependencies);
+        assert!(!rules.allow_skipped_yanked);
+    }
+}
@@ -0,0 +1,191 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+/// How long cached PyPI responses are used when `--cache-ttl` isn't given
+pub const DEFAULT_TTL: Duration = Duration::from_secs(6 * 60 * 60);
+
+/// Parse a cache lifetime such as `30m`, `6h` or `1d`. A bare number is
+/// interpreted as seconds, so `0` disables the cache.
+pub fn parse_ttl(input: &str) -> Result<Duration> {
+    let input = input.trim();
+    let (number, multiplier) = match input.char_indices().last() {
+        Some((idx, 's')) => (&input[..idx], 1),
+        Some((idx, 'm')) => (&input[..idx], 60),
+        Some((idx, 'h')) => (&input[..idx], 60 * 60),
+        Some((idx, 'd')) => (&input[..idx], 24 * 60 * 60),
+        _ => (input, 1),
+    };
+
+    let number: u64 = number.parse().map_err(|_| {
+        anyhow::anyhow!(
+            "Invalid cache TTL '{}': expected a duration like 30m, 6h or 1d",
+            input
+        )
+    })?;
+
+    number
+        .checked_mul(multiplier)
+        .map(Duration::from_secs)
+        .ok_or_else(|| anyhow::anyhow!("Cache TTL '{}' is too large", input))
+}
+
+/// The cache of PyPI responses next to pixi's own caches, `None` if `ttl` is
+/// zero or pixi has no cache directory
+pub fn pypi_cache(ttl: Duration) -> Option<ResponseCache> {
+    pixi_cache(&["pixi-outdated", "pypi"], ttl)
+}
+
+/// The cache of channels' `channeldata.json`, next to pixi's repodata
+/// cache, `None` if `ttl` is zero or pixi has no cache directory
+pub fn channeldata_cache(ttl: Duration) -> Option<ResponseCache> {
+    pixi_cache(&["channeldata"], ttl)
+}
+
+fn pixi_cache(path: &[&str], ttl: Duration) -> Option<ResponseCache> {
+    if ttl.is_zero() {
+        return None;
+    }
+    let cache_dir = pixi_config::get_cache_dir().ok()?;
+    Some(ResponseCache::new(
+        path.iter().fold(cache_dir, |dir, part| dir.join(part)),
+        ttl,
+    ))
+}
+
+/// Responses of a package index stored on disk, one file per index and package
+#[derive(Debug, Clone)]
+pub struct ResponseCache {
+    dir: PathBuf,
+    ttl: Duration,
+    /// Only store responses, replacing what is cached
+    refresh: bool,
+}
+
+impl ResponseCache {
+    pub fn new(dir: PathBuf, ttl: Duration) -> Self {
+        Self {
+            dir,
+            ttl,
+            refresh: false,
+        }
+    }
+
+    /// The same cache, but storing fresh responses without reusing any
+    pub fn refreshed(&self) -> Self {
+        Self {
+            refresh: true,
+            ..self.clone()
+        }
+    }
+
+    /// The cached response for a package, unless it is older than the TTL
+    pub fn get(&self, index_url: &str, package: &str) -> Option<Vec<u8>> {
+        if self.refresh {
+            return None;
+        }
+        let path = self.path(index_url, package);
+        let modified = fs::metadata(&path).ok()?.modified().ok()?;
+        let age = SystemTime::now()
+            .duration_since(modified)
+            .unwrap_or_default();
+        if age >= self.ttl {
+            return None;
+        }
+        fs::read(path).ok()
+    }
+
+    /// Store a response, replacing the file atomically so concurrent runs
+    /// never read a partial write
+    pub fn put(&self, index_url: &str, package: &str, body: &[u8]) -> Result<()> {
+        let path = self.path(index_url, package);
+        let dir = path.parent().unwrap_or(&self.dir);
+        fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create cache directory {}", dir.display()))?;
+
+        let tmp = path.with_extension(format!("json.{}.tmp", std::process::id()));
+        fs::write(&tmp, body).with_context(|| format!("Failed to write {}", tmp.display()))?;
+        fs::rename(&tmp, &path).with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    fn path(&self, index_url: &str, package: &str) -> PathBuf {
+        self.dir
+            .join(file_name(index_url))
+            .join(format!("{}.json", file_name(&package.to_ascii_lowercase())))
+    }
+}
+
+/// A string made safe to use as a single path component
+fn file_name(value: &str) -> String {
+    value
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '-' | '.') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ttl() {
+        assert_eq!(parse_ttl("30m").unwrap(), Duration::from_secs(1800));
+        assert_eq!(parse_ttl("6h").unwrap(), DEFAULT_TTL);
+        assert_eq!(parse_ttl("1d").unwrap(), Duration::from_secs(86400));
+        assert_eq!(parse_ttl("0").unwrap(), Duration::ZERO);
+        assert!(parse_ttl("6 hours").is_err());
+        assert!(parse_ttl("h").is_err());
+    }
+
+    #[test]
+    fn test_get_and_put() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = ResponseCache::new(dir.path().to_path_buf(), DEFAULT_TTL);
+
+        assert_eq!(cache.get("https://pypi.org/pypi", "cowsay"), None);
+        cache
+            .put("https://pypi.org/pypi", "cowsay", b"{\"info\": {}}")
+            .unwrap();
+        assert_eq!(
+            cache.get("https://pypi.org/pypi", "Cowsay").as_deref(),
+            Some(&b"{\"info\": {}}"[..])
+        );
+        // Each index has its own entries
+        assert_eq!(
+            cache.get("https://internal.example.com/pypi", "cowsay"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_refreshed_cache_only_stores() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = ResponseCache::new(dir.path().to_path_buf(), DEFAULT_TTL);
+        cache.put("https://pypi.org/pypi", "cowsay", b"{}").unwrap();
+
+        let refreshed = cache.refreshed();
+        assert_eq!(refreshed.get("https://pypi.org/pypi", "cowsay"), None);
+        refreshed
+            .put("https://pypi.org/pypi", "cowsay", b"{\"info\": {}}")
+            .unwrap();
+        assert_eq!(
+            cache.get("https://pypi.org/pypi", "cowsay").as_deref(),
+            Some(&b"{\"info\": {}}"[..])
+        );
+    }
+
+    #[test]
+    fn test_expired_entries_are_ignored() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = ResponseCache::new(dir.path().to_path_buf(), Duration::ZERO);
+        cache.put("https://pypi.org/pypi", "cowsay", b"{}").unwrap();
+        assert_eq!(cache.get("https://pypi.org/pypi", "cowsay"), None);
+    }
+}
@@ -0,0 +1,187 @@
+use std::fmt;
+
+/// Why a candidate version was not chosen as the latest version
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExclusionReason {
+    /// Pre-releases are not considered for the latest version
+    Prerelease,
+    /// Every file of the release has been yanked
+    Yanked,
+    /// The release has no files to install
+    NoFiles,
+    /// The release doesn't support the locked Python interpreter
+    RequiresNewerPython,
+    /// The release is published under a license the project doesn't allow
+    License,
+    /// The release is beyond the maximum version set in the project config
+    AboveMaximum,
+}
+
+impl fmt::Display for ExclusionReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExclusionReason::Prerelease => write!(f, "pre-release"),
+            ExclusionReason::Yanked => write!(f, "yanked"),
+            ExclusionReason::NoFiles => write!(f, "no files uploaded"),
+            ExclusionReason::RequiresNewerPython => write!(f, "requires a newer Python"),
+            ExclusionReason::License => write!(f, "license not allowed"),
+            ExclusionReason::AboveMaximum => write!(f, "above the maximum version"),
+        }
+    }
+}
+
+/// The outcome of the latest version selection for a single candidate
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Decision {
+    /// This candidate was reported as the latest version
+    Selected,
+    /// Eligible, but lower than the selected version
+    Older,
+    /// Filtered out before choosing the latest version
+    Excluded(ExclusionReason),
+}
+
+impl fmt::Display for Decision {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Decision::Selected => write!(f, "selected (latest)"),
+            Decision::Older => write!(f, "older"),
+            Decision::Excluded(reason) => write!(f, "excluded: {}", reason),
+        }
+    }
+}
+
+/// A version that was considered while determining the latest version
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Candidate {
+    pub version: String,
+    /// Extra information about the candidate, like its builds or files
+    pub detail: Option<String>,
+    pub decision: Decision,
+}
+
+/// Hold back the candidates newer than `reported`, the version reported as
+/// the latest once `reason` was taken into account. Candidates are listed
+/// newest first; when `reported` isn't among them, every eligible one is
+/// held back.
+pub fn hold_back(candidates: &mut [Candidate], reported: Option<&str>, reason: ExclusionReason) {
+    let mut newer = true;
+    for candidate in candidates.iter_mut() {
+        if reported == Some(candidate.version.as_str()) {
+            candidate.decision = Decision::Selected;
+            newer = false;
+        } else if matches!(candidate.decision, Decision::Selected | Decision::Older) {
+            candidate.decision = if newer {
+                Decision::Excluded(reason)
+            } else {
+                Decision::Older
+            };
+        }
+    }
+}
+
+/// Render a list of candidates as an aligned table, one candidate per line
+pub fn render_candidates(candidates: &[Candidate]) -> String {
+    let width = candidates
+        .iter()
+        .map(|c| c.version.len())
+        .max()
+        .unwrap_or(0);
+
+    candidates
+        .iter()
+        .map(|c| match &c.detail {
+            Some(detail) => format!(
+                "  {:width$}  {} [{}]\n",
+                c.version,
+                c.decision,
+                detail,
+                width = width
+            ),
+            None => format!("  {:width$}  {}\n", c.version, c.decision, width = width),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decision_display() {
+        assert_eq!(Decision::Selected.to_string(), "selected (latest)");
+        assert_eq!(Decision::Older.to_string(), "older");
+        assert_eq!(
+            Decision::Excluded(ExclusionReason::Prerelease).to_string(),
+            "excluded: pre-release"
+        );
+    }
+
+    fn candidate(version: &str, decision: Decision) -> Candidate {
+        Candidate {
+            version: version.to_string(),
+            detail: None,
+            decision,
+        }
+    }
+
+    #[test]
+    fn test_hold_back() {
+        let mut candidates = vec![
+            candidate("3.0", Decision::Selected),
+            candidate("2.1rc1", Decision::Excluded(ExclusionReason::Prerelease)),
+            candidate("2.0", Decision::Older),
+            candidate("1.0", Decision::Older),
+        ];
+
+        hold_back(
+            &mut candidates,
+            Some("2.0"),
+            ExclusionReason::RequiresNewerPython,
+        );
+        let decisions: Vec<Decision> = candidates.iter().map(|c| c.decision).collect();
+        assert_eq!(
+            decisions,
+            vec![
+                Decision::Excluded(ExclusionReason::RequiresNewerPython),
+                Decision::Excluded(ExclusionReason::Prerelease),
+                Decision::Selected,
+                Decision::Older,
+            ]
+        );
+
+        // Held back candidates keep the first reason they were excluded for
+        hold_back(&mut candidates, None, ExclusionReason::License);
+        let decisions: Vec<Decision> = candidates.iter().map(|c| c.decision).collect();
+        assert_eq!(
+            decisions,
+            vec![
+                Decision::Excluded(ExclusionReason::RequiresNewerPython),
+                Decision::Excluded(ExclusionReason::Prerelease),
+                Decision::Excluded(ExclusionReason::License),
+                Decision::Excluded(ExclusionReason::License),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_render_candidates_aligns_versions() {
+        let candidates = vec![
+            Candidate {
+                version: "2.0.0rc1".to_string(),
+                detail: None,
+                decision: Decision::Excluded(ExclusionReason::Prerelease),
+            },
+            Candidate {
+                version: "1.9".to_string(),
+                detail: Some("2 builds".to_string()),
+                decision: Decision::Selected,
+            },
+        ];
+
+        assert_eq!(
+            render_candidates(&candidates),
+            "  2.0.0rc1  excluded: pre-release\n  1.9       selected (latest) [2 builds]\n"
+        );
+    }
+}
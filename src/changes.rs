@@ -0,0 +1,168 @@
+use anyhow::{Context, Result};
+use std::collections::{BTreeMap, HashSet};
+use std::path::Path;
+use std::process::Command;
+
+use crate::parser::DependencySpecs;
+use crate::pixi::{PackageKind, PixiPackage};
+
+/// Locked packages of an environment, keyed by platform
+pub type LockedPackages = BTreeMap<String, Vec<PixiPackage>>;
+
+/// The dependencies touched by a range of commits: those whose manifest
+/// requirement or locked version changed. Packages that are no longer
+/// locked aren't included, since there is nothing left to check.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ChangedDependencies {
+    packages: HashSet<(String, PackageKind)>,
+}
+
+impl ChangedDependencies {
+    pub fn between(
+        old_specs: &DependencySpecs,
+        new_specs: &DependencySpecs,
+        old_locked: &LockedPackages,
+        new_locked: &LockedPackages,
+    ) -> Self {
+        let requirement_changes = new_specs.changed_from(old_specs);
+
+        let mut packages = HashSet::new();
+        for (platform, locked) in new_locked {
+            let old = old_locked.get(platform).map_or(&[][..], Vec::as_slice);
+            for package in locked {
+                let key = (package.name.clone(), package.kind);
+                let relocked = old
+                    .iter()
+                    .find(|old| old.name == package.name && old.kind == package.kind)
+                    .is_none_or(|old| old.version != package.version || old.build != package.build);
+                if relocked || requirement_changes.contains(&key) {
+                    packages.insert(key);
+                }
+            }
+        }
+
+        Self { packages }
+    }
+
+    pub fn contains(&self, name: &str, kind: PackageKind) -> bool {
+        self.packages.contains(&(name.to_string(), kind))
+    }
+
+    pub fn len(&self) -> usize {
+        self.packages.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.packages.is_empty()
+    }
+}
+
+/// The content of a file at a git revision, or `None` if the file didn't
+/// exist yet
+pub fn file_at_revision(root: &Path, revision: &str, path: &Path) -> Result<Option<String>> {
+    let verified = Command::new("git")
+        .current_dir(root)
+        .args(["rev-parse", "--verify", "--quiet"])
+        .arg(format!("{}^{{commit}}", revision))
+        .output()
+        .context("Failed to run git")?;
+    if !verified.status.success() {
+        anyhow::bail!("Unknown git revision '{}' in {}", revision, root.display());
+    }
+
+    // `./` makes the path relative to the workspace root instead of the
+    // root of the repository
+    let relative = path.strip_prefix(root).unwrap_or(path);
+    let spec = format!(
+        "{}:./{}",
+        revision,
+        relative.to_string_lossy().replace('\\', "/")
+    );
+    let shown = Command::new("git")
+        .current_dir(root)
+        .args(["show", &spec])
+        .output()
+        .context("Failed to run git")?;
+    if !shown.status.success() {
+        return Ok(None);
+    }
+
+    String::from_utf8(shown.stdout)
+        .map(Some)
+        .with_context(|| format!("{} is not valid UTF-8 at {}", path.display(), revision))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn package(name: &str, version: &str, kind: PackageKind) -> PixiPackage {
+        PixiPackage {
+            name: name.to_string(),
+            version: version.to_string(),
+            build: None,
+            size_bytes: None,
+            kind,
+            source: None,
+            is_explicit: false,
+            depends: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_changed_dependencies() {
+        let specs = DependencySpecs::parse(
+            "[dependencies]\npython = \">=3.12\"\nnumpy = \"*\"\n",
+            false,
+            &crate::parser::EnvironmentFeatures::All,
+        )
+        .unwrap();
+        let new_specs = DependencySpecs::parse(
+            "[dependencies]\npython = \">=3.12\"\nnumpy = \">=2\"\n",
+            false,
+            &crate::parser::EnvironmentFeatures::All,
+        )
+        .unwrap();
+
+        let old_locked = LockedPackages::from([(
+            "linux-64".to_string(),
+            vec![
+                package("python", "3.12.11", PackageKind::Conda),
+                package("numpy", "2.3.0", PackageKind::Conda),
+                package("openssl", "3.5.0", PackageKind::Conda),
+                package("cowsay", "5.0", PackageKind::Pypi),
+            ],
+        )]);
+        let new_locked = LockedPackages::from([(
+            "linux-64".to_string(),
+            vec![
+                package("python", "3.12.11", PackageKind::Conda),
+                package("numpy", "2.3.0", PackageKind::Conda),
+                package("openssl", "3.5.1", PackageKind::Conda),
+                package("libzlib", "1.3.1", PackageKind::Conda),
+                package("cowsay", "5.0", PackageKind::Pypi),
+            ],
+        )]);
+
+        let changed = ChangedDependencies::between(&specs, &new_specs, &old_locked, &new_locked);
+        assert_eq!(changed.len(), 3);
+        // Relocked, newly locked, and a changed requirement
+        assert!(changed.contains("openssl", PackageKind::Conda));
+        assert!(changed.contains("libzlib", PackageKind::Conda));
+        assert!(changed.contains("numpy", PackageKind::Conda));
+        assert!(!changed.contains("python", PackageKind::Conda));
+        assert!(!changed.contains("cowsay", PackageKind::Pypi));
+
+        let unchanged = ChangedDependencies::between(&specs, &specs, &new_locked, &new_locked);
+        assert!(unchanged.is_empty());
+
+        // Without a lockfile at the revision everything is new
+        let all = ChangedDependencies::between(
+            &DependencySpecs::default(),
+            &new_specs,
+            &LockedPackages::new(),
+            &new_locked,
+        );
+        assert_eq!(all.len(), 5);
+    }
+}
@@ -0,0 +1,868 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use pixi_manifest::FeaturesExt;
+
+use crate::parser::{DependencySpecs, EnvironmentFeatures, ManifestChannels, PixiLock};
+use crate::pixi::{PackageKind, PixiPackage};
+use crate::report::{
+    CheckStatus, DisallowedPackage, EnvironmentReports, PackageUpdate, Report, ReportMetadata,
+    RunStatus, UncheckedPackage,
+};
+use crate::retry::RetryPolicy;
+use crate::select::Selection;
+use crate::upstream::{PackageKey, VersionLookup, VersionSource};
+
+/// What an embedded check looks at, e.g. as passed through the C API. Every
+/// field is optional, so `{}` checks every platform of the default
+/// environment against the network.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CheckOptions {
+    /// The environment to check, `default` unless given
+    pub environment: Option<String>,
+    /// The platforms to check; all locked platforms when empty
+    pub platforms: Vec<String>,
+    /// Only check the dependencies declared in the manifest
+    pub explicit: bool,
+    /// Read the latest versions from fixture files instead of the network,
+    /// like `--source fixtures=<DIR>`
+    pub fixtures: Option<PathBuf>,
+    /// Which packages to check and which of their updates to report, like
+    /// the selection, ignore and `--filter` options of a workspace run;
+    /// every package and update unless given
+    #[serde(skip)]
+    pub selection: Selection,
+    /// How to reach the network; the global pixi config unless given
+    #[serde(skip)]
+    pub network: Option<NetworkConfig>,
+}
+
+impl CheckOptions {
+    /// The network config of the options, or the global pixi config
+    fn network(&self) -> Cow<'_, NetworkConfig> {
+        match self.network {
+            Some(ref network) => Cow::Borrowed(network),
+            None => Cow::Owned(NetworkConfig::load_global()),
+        }
+    }
+}
+
+/// How a check reaches channels and indexes, set up from the pixi config
+/// like a workspace run: the repodata gateway fetches through pixi's
+/// authenticated client and cache, and PyPI uses the configured keyring
+/// provider and response cache
+#[derive(Debug, Clone)]
+pub struct NetworkConfig {
+    pub pixi_config: pixi_config::Config,
+    /// The client PyPI is queried with, e.g. one going through a proxy
+    pub http_client: reqwest::Client,
+    /// How long PyPI responses are reused, zero to disable the cache
+    pub cache_ttl: Duration,
+    /// How many packages to query at once, pixi's `concurrency.downloads`
+    /// setting unless given
+    pub jobs: Option<usize>,
+    /// When to retry queries that failed for a reason that may go away
+    pub retry: RetryPolicy,
+    /// How long a request may take per attempt, without limit unless given
+    pub timeout: Option<Duration>,
+}
+
+impl NetworkConfig {
+    /// The global pixi config, with the default client, cache lifetime and
+    /// retries
+    pub fn load_global() -> Self {
+        Self {
+            pixi_config: pixi_config::Config::load_global(),
+            http_client: reqwest::Client::new(),
+            cache_ttl: crate::cache::DEFAULT_TTL,
+            jobs: None,
+            retry: RetryPolicy::default(),
+            timeout: None,
+        }
+    }
+
+    /// What channel names resolve to, from the pixi config
+    pub fn channel_alias(&self) -> String {
+        self.pixi_config
+            .global_channel_config()
+            .channel_alias
+            .to_string()
+    }
+
+    /// The conda and PyPI sources, with `indexes` replacing PyPI if given
+    fn sources(&self, indexes: &[&str]) -> Result<Vec<Box<dyn VersionSource>>> {
+        let gateway = crate::conda::configured_gateway(&self.pixi_config)?;
+        let mut pypi_client = crate::pypi::PyPiClient::new(
+            crate::pypi::KeyringProvider::from_config(&self.pixi_config),
+            crate::auth::CredentialStore::from_environment(),
+        )
+        .with_http_client(self.http_client.clone());
+        if let Some((index_url, extra_index_urls)) = indexes.split_first() {
+            pypi_client = pypi_client.with_indexes(Some(index_url), extra_index_urls);
+        }
+        if let Some(cache) = crate::cache::pypi_cache(self.cache_ttl) {
+            pypi_client = pypi_client.with_cache(cache);
+        }
+        let jobs = self
+            .jobs
+            .unwrap_or_else(|| self.pixi_config.max_concurrent_downloads());
+        let mut conda_source = crate::conda::CondaSource::new(gateway)
+            .with_jobs(jobs)
+            .with_retry(self.retry);
+        let mut pypi_client = pypi_client.with_jobs(jobs).with_retry(self.retry);
+        if let Some(timeout) = self.timeout {
+            conda_source = conda_source.with_timeout(timeout);
+            pypi_client = pypi_client.with_timeout(timeout);
+        }
+        Ok(vec![Box::new(conda_source), Box::new(pypi_client)])
+    }
+}
+
+/// An environment of a workspace as pixi resolves it. Workspace runs of
+/// the CLI and [`check_manifest`] both start from it, so they read the same
+/// features, lockfile and channels.
+#[derive(Debug, Clone)]
+pub struct ResolvedEnvironment {
+    pub name: String,
+    /// The features making up the environment, whose tables apply to it
+    pub features: EnvironmentFeatures,
+    /// The platforms the environment is solved for
+    pub platforms: Vec<String>,
+    /// Where the workspace keeps its lockfile
+    pub lock_file_path: PathBuf,
+    /// What channel names resolve to, from pixi's configuration
+    pub channel_alias: String,
+}
+
+impl ResolvedEnvironment {
+    pub fn new(
+        workspace: &pixi_core::Workspace,
+        environment: &pixi_core::workspace::Environment<'_>,
+    ) -> Self {
+        Self {
+            name: environment.name().as_str().to_string(),
+            features: EnvironmentFeatures::only(
+                environment
+                    .features()
+                    .map(|feature| feature.name.as_str().to_string()),
+            ),
+            platforms: environment
+                .platforms()
+                .into_iter()
+                .map(|platform| platform.to_string())
+                .collect(),
+            lock_file_path: workspace.lock_file_path(),
+            channel_alias: workspace.channel_config().channel_alias.to_string(),
+        }
+    }
+
+    /// Load the workspace of `manifest_path` through pixi and resolve
+    /// `environment` of it, or its default environment
+    pub fn load(
+        manifest_path: &Path,
+        environment: Option<&str>,
+        config: Option<&pixi_config::Config>,
+    ) -> Result<Self> {
+        let mut workspace = pixi_core::WorkspaceLocator::for_cli()
+            .with_search_start(pixi_core::workspace::DiscoveryStart::ExplicitManifest(
+                manifest_path.to_path_buf(),
+            ))
+            .locate()?;
+        if let Some(config) = config {
+            workspace = workspace.with_cli_config(config.clone());
+        }
+        let resolved = match environment {
+            Some(name) => Self::new(
+                &workspace,
+                &workspace
+                    .environment(name)
+                    .ok_or_else(|| anyhow::anyhow!("Environment '{}' not found", name))?,
+            ),
+            None => Self::new(&workspace, &workspace.default_environment()),
+        };
+        Ok(resolved)
+    }
+}
+
+/// Check the lockfile of a manifest's workspace. Updates of the
+/// dependencies the checked environment declares are marked as direct.
+pub async fn check_manifest(manifest_path: &Path, options: &CheckOptions) -> Result<Report> {
+    let resolved = ResolvedEnvironment::load(
+        manifest_path,
+        options.environment.as_deref(),
+        options.network.as_ref().map(|network| &network.pixi_config),
+    )?;
+    let manifest = Manifest {
+        path: manifest_path,
+        dependency_specs: DependencySpecs::load(manifest_path, &resolved.features)?,
+        channels: ManifestChannels::load(manifest_path, &resolved.features)?,
+    };
+    if !resolved.lock_file_path.is_file() {
+        return Ok(Report {
+            status: RunStatus::LockfileMissing,
+            ..Report::default()
+        });
+    }
+    let options = CheckOptions {
+        environment: Some(resolved.name.clone()),
+        ..options.clone()
+    };
+    check(&resolved.lock_file_path, Some(&manifest), &options).await
+}
+
+/// Check a lockfile on its own. Without a manifest, no update counts as
+/// direct and `explicit` has nothing to filter by.
+pub async fn check_lockfile(lock_path: &Path, options: &CheckOptions) -> Result<Report> {
+    check(lock_path, None, options).await
+}
+
+/// A package to check without a workspace, as read by `--stdin-packages`.
+/// Other fields are ignored, so the output of `pixi list --json` parses too.
+#[derive(Debug, Clone, Deserialize)]
+pub struct InputPackage {
+    pub name: String,
+    pub version: String,
+    #[serde(default = "default_kind")]
+    pub kind: PackageKind,
+    /// The channel of a conda package, by name or URL; `conda-forge` unless
+    /// given. `pixi list --json` calls this `source`.
+    #[serde(default, alias = "source")]
+    pub channel: Option<String>,
+}
+
+fn default_kind() -> PackageKind {
+    PackageKind::Conda
+}
+
+/// Check a list of packages as they are, without a manifest or lockfile.
+/// Every package is checked on each platform of the options, or on the
+/// current platform if there are none.
+pub async fn check_packages(packages: &[InputPackage], options: &CheckOptions) -> Result<Report> {
+    let platforms = match options.platforms.as_slice() {
+        [] => vec![rattler_conda_types::Platform::current().to_string()],
+        platforms => platforms.to_vec(),
+    };
+    let metadata = ReportMetadata::new("stdin", &platforms, None, None, chrono::Utc::now())?;
+    if packages.is_empty() {
+        return Ok(Report {
+            metadata: Some(metadata),
+            status: RunStatus::EmptyEnvironment,
+            ..Report::default()
+        });
+    }
+
+    // A python among the packages limits their PyPI releases
+    let python_version = packages
+        .iter()
+        .find(|package| package.kind == PackageKind::Conda && package.name == "python")
+        .map(|package| package.version.clone());
+    let python_versions: HashMap<String, String> = platforms
+        .iter()
+        .filter_map(|platform| Some((platform.clone(), python_version.clone()?)))
+        .collect();
+
+    let channel_alias = options.network().channel_alias();
+    let mut platform_packages = Vec::new();
+    for platform in &platforms {
+        for package in packages
+            .iter()
+            .filter(|package| options.selection.includes(&package.name))
+        {
+            let channel = match package.kind {
+                PackageKind::Conda => {
+                    crate::conda::extract_channel_url(&crate::policy::channel_url(
+                        package.channel.as_deref().unwrap_or("conda-forge"),
+                        &channel_alias,
+                    ))
+                }
+                PackageKind::Pypi => None,
+            };
+            let key = PackageKey {
+                name: package.name.clone(),
+                channel: channel.clone(),
+                kind: package.kind,
+            };
+            let package = PixiPackage {
+                name: package.name.clone(),
+                version: package.version.clone(),
+                build: None,
+                size_bytes: None,
+                kind: package.kind,
+                source: channel,
+                is_explicit: false,
+                depends: vec![],
+            };
+            platform_packages.push((platform.clone(), package, key));
+        }
+    }
+
+    report_updates(
+        platform_packages,
+        &default_sources(options)?,
+        metadata,
+        None,
+        |_, _| false,
+        &options.selection,
+        &python_versions,
+    )
+    .await
+}
+
+/// Check the environments `pixi global` installed tools into, under a pixi
+/// home such as `~/.pixi`. Global environments have no lockfile, so the
+/// versions installed in each environment's prefix are checked, on the
+/// platform it was installed for. The tools the global manifest declares
+/// count as direct dependencies.
+pub async fn check_global(home: &Path, options: &CheckOptions) -> Result<EnvironmentReports> {
+    let manifest_path = home.join(crate::global::MANIFEST_PATH);
+    let manifest = crate::global::GlobalManifest::load(&manifest_path)?;
+    let sources = default_sources(options)?;
+
+    let mut reports = EnvironmentReports::default();
+    for (name, environment) in &manifest.envs {
+        let platform = match (&environment.platform, options.platforms.first()) {
+            (Some(platform), _) | (None, Some(platform)) => platform.clone(),
+            (None, None) => rattler_conda_types::Platform::current().to_string(),
+        };
+        let platforms = vec![platform.clone()];
+        let metadata = ReportMetadata::new(
+            name,
+            &platforms,
+            Some(&manifest_path),
+            None,
+            chrono::Utc::now(),
+        )?;
+        let prefix = home.join("envs").join(name);
+        if !prefix.join("conda-meta").is_dir() {
+            reports.environments.insert(
+                name.clone(),
+                Report {
+                    metadata: Some(metadata),
+                    status: RunStatus::EmptyEnvironment,
+                    ..Report::default()
+                },
+            );
+            continue;
+        }
+
+        let dependencies: Vec<&str> = environment
+            .dependencies
+            .keys()
+            .map(String::as_str)
+            .collect();
+        let installed = crate::global::installed_packages(&prefix, &dependencies)?;
+        let python_versions: HashMap<String, String> = crate::pixi::python_version(&installed)
+            .map(|python| (platform.clone(), python.to_string()))
+            .into_iter()
+            .collect();
+        let platform_packages = installed
+            .into_iter()
+            .filter(|package| !options.explicit || package.is_explicit)
+            .filter(|package| options.selection.includes(&package.name))
+            .map(|package| {
+                let key = PackageKey {
+                    name: package.name.clone(),
+                    channel: crate::conda::locked_channel(&package),
+                    kind: package.kind,
+                };
+                (platform.clone(), package, key)
+            })
+            .collect();
+        let report = report_updates(
+            platform_packages,
+            &sources,
+            metadata,
+            None,
+            |name, _| environment.dependencies.contains_key(name),
+            &options.selection,
+            &python_versions,
+        )
+        .await?;
+        reports.environments.insert(name.clone(), report);
+    }
+    Ok(reports)
+}
+
+/// The fixtures if given, otherwise conda-forge style channels and PyPI
+fn default_sources(options: &CheckOptions) -> Result<Vec<Box<dyn VersionSource>>> {
+    sources(None, "default", options)
+}
+
+/// What a check reads from the manifest
+struct Manifest<'a> {
+    path: &'a Path,
+    dependency_specs: DependencySpecs,
+    channels: ManifestChannels,
+}
+
+async fn check(
+    lock_path: &Path,
+    manifest: Option<&Manifest<'_>>,
+    options: &CheckOptions,
+) -> Result<Report> {
+    let dependency_specs = manifest.map(|manifest| &manifest.dependency_specs);
+    let listed_channels = manifest
+        .map(|manifest| &manifest.channels)
+        .filter(|channels| !channels.is_empty());
+    let lock_file = crate::parser::parse_lockfile(lock_path)?;
+    let environment = options.environment.as_deref().unwrap_or("default");
+    if !lock_file.environments.contains_key(environment) {
+        anyhow::bail!(
+            "Environment '{}' is not in {}",
+            environment,
+            lock_path.display()
+        );
+    }
+
+    let platforms = match options.platforms.as_slice() {
+        [] => lock_file.platforms(environment),
+        platforms => platforms.to_vec(),
+    };
+    let metadata = ReportMetadata::new(
+        environment,
+        &platforms,
+        manifest.map(|manifest| manifest.path),
+        Some(lock_path),
+        chrono::Utc::now(),
+    )?;
+    let is_direct = |name: &str, kind: PackageKind| {
+        dependency_specs.is_some_and(|specs| specs.is_direct(name, kind))
+    };
+
+    let mut locked_package_count = 0;
+    let mut python_versions = HashMap::new();
+    let mut platform_packages = Vec::new();
+    for platform in &platforms {
+        let packages = lock_file.packages(environment, platform);
+        locked_package_count += packages.len();
+        if let Some(python) = crate::pixi::python_version(&packages) {
+            python_versions.insert(platform.clone(), python.to_string());
+        }
+        for package in packages {
+            if options.explicit && !is_direct(&package.name, package.kind) {
+                continue;
+            }
+            if !options.selection.includes(&package.name) {
+                continue;
+            }
+            let key = PackageKey {
+                name: package.name.clone(),
+                channel: crate::conda::locked_channel(&package),
+                kind: package.kind,
+            };
+            platform_packages.push((platform.clone(), package, key));
+        }
+    }
+    if platform_packages.is_empty() {
+        let status = if locked_package_count == 0 {
+            RunStatus::EmptyEnvironment
+        } else {
+            RunStatus::AllPackagesSkipped
+        };
+        return Ok(Report {
+            metadata: Some(metadata),
+            status,
+            ..Report::default()
+        });
+    }
+
+    let sources = sources(Some(&lock_file), environment, options)?;
+    report_updates(
+        platform_packages,
+        &sources,
+        metadata,
+        listed_channels,
+        is_direct,
+        &options.selection,
+        &python_versions,
+    )
+    .await
+}
+
+/// Look up the latest versions of the packages to check on each platform
+/// and report which of them are outdated, as far as `selection` reports
+/// them. The Python of a platform in `python_versions` limits the PyPI
+/// releases considered.
+async fn report_updates(
+    platform_packages: Vec<(String, PixiPackage, PackageKey)>,
+    sources: &[Box<dyn VersionSource>],
+    metadata: ReportMetadata,
+    listed_channels: Option<&ManifestChannels>,
+    is_direct: impl Fn(&str, PackageKind) -> bool,
+    selection: &Selection,
+    python_versions: &HashMap<String, String>,
+) -> Result<Report> {
+    let mut keys: Vec<PackageKey> = platform_packages
+        .iter()
+        .map(|(_, _, key)| key.clone())
+        .collect();
+    keys.sort_by(|a, b| (&a.name, &a.channel, a.kind).cmp(&(&b.name, &b.channel, b.kind)));
+    keys.dedup();
+    let platform_refs: Vec<&str> = metadata.platforms.iter().map(String::as_str).collect();
+    let lookups: HashMap<PackageKey, VersionLookup> =
+        crate::upstream::query_all(sources, &keys, &platform_refs)
+            .await
+            .into_iter()
+            .map(|(key, response)| {
+                let lookup = VersionLookup::from_response(response.info);
+                let lookup = selection.with_prereleases(&key.name, lookup);
+                (key, lookup)
+            })
+            .collect();
+
+    let now = metadata.generated_at;
+    let mut report = Report {
+        metadata: Some(metadata),
+        ..Report::default()
+    };
+    for (platform, package, key) in platform_packages {
+        report
+            .locked
+            .entry(platform.clone())
+            .or_default()
+            .push(package.clone());
+        report
+            .checked
+            .insert((platform.clone(), package.kind, package.name.clone()));
+        if let (Some(channels), Some(origin)) = (listed_channels, &key.channel) {
+            if !channels.lists(origin) {
+                report.unlisted_channels.push(DisallowedPackage {
+                    name: package.name.clone(),
+                    version: package.version.clone(),
+                    platform: platform.clone(),
+                    channel: origin.clone(),
+                });
+            }
+        }
+        let status = match lookups.get(&key) {
+            Some(VersionLookup::Found(info)) => {
+                let python_version = python_versions.get(&platform).map(String::as_str);
+                let latest = selection
+                    .latest(info, &package.name, &package.version, python_version)
+                    .filter(|latest| {
+                        info.is_update(latest, &package.version)
+                            && selection.reports(
+                                &package.name,
+                                latest,
+                                crate::severity::classify(&package.version, latest),
+                            )
+                    });
+                match latest.as_deref() {
+                    Some(latest) => {
+                        report
+                            .platforms
+                            .entry(platform)
+                            .or_default()
+                            .push(PackageUpdate {
+                                direct: is_direct(&package.name, package.kind),
+                                size_delta: info.size_delta(&package, latest),
+                                installed_age_days: info
+                                    .release_date(&package.version)
+                                    .map(|released| crate::age::age_in_days(released, now)),
+                                newer_available_days: info
+                                    .first_release_after(&package.version, latest)
+                                    .map(|released| crate::age::age_in_days(released, now)),
+                                lag_days: info.release_lag_days(&package.version, latest),
+                                severity: Some(crate::severity::classify(&package.version, latest)),
+                                name: package.name,
+                                kind: Some(package.kind),
+                                channel: key.channel,
+                                installed_version: package.version,
+                                latest_version: latest.to_string(),
+                                ..Default::default()
+                            });
+                    }
+                    None => {
+                        report.platforms.entry(platform).or_default();
+                    }
+                }
+                continue;
+            }
+            Some(VersionLookup::NotFound) => CheckStatus::NotFound,
+            Some(VersionLookup::Failed(message)) => CheckStatus::Error {
+                message: message.clone(),
+            },
+            // Packages without a channel URL can't be looked up
+            None if key.channel.is_none() => CheckStatus::NoSource,
+            None => continue,
+        };
+        report.unchecked.push(UncheckedPackage {
+            name: package.name,
+            kind: package.kind,
+            version: package.version,
+            platform,
+            status,
+            annotations: Default::default(),
+        });
+    }
+    for updates in report.platforms.values_mut() {
+        updates.sort_by(|a, b| a.name.cmp(&b.name));
+    }
+    report.libyears = Some(crate::libyears::Libyears::from_updates(&report.platforms));
+    report
+        .unlisted_channels
+        .sort_by(|a, b| (&a.name, &a.platform).cmp(&(&b.name, &b.platform)));
+    report.content_hash = Some(report.compute_content_hash()?);
+    Ok(report)
+}
+
+/// Where to look up the latest versions: the fixtures if given, otherwise
+/// the channels of the locked packages and the environment's indexes
+fn sources(
+    lock_file: Option<&PixiLock>,
+    environment: &str,
+    options: &CheckOptions,
+) -> Result<Vec<Box<dyn VersionSource>>> {
+    if let Some(ref dir) = options.fixtures {
+        return Ok(vec![Box::new(crate::fixtures::FixtureSource::new(
+            dir.clone(),
+        ))]);
+    }
+
+    let indexes: Vec<&str> = lock_file
+        .and_then(|lock_file| lock_file.environments.get(environment))
+        .map(|env| env.indexes.iter().map(String::as_str).collect())
+        .unwrap_or_default();
+    options.network().sources(&indexes)
+}
+
+/// Run a check to completion on a runtime of its own, for callers that
+/// aren't async, such as the C API
+pub fn block_on<F: std::future::Future<Output = Result<Report>>>(check: F) -> Result<Report> {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .context("Failed to start the async runtime")?
+        .block_on(check)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn examples() -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("examples")
+    }
+
+    fn options() -> CheckOptions {
+        CheckOptions {
+            platforms: vec!["linux-64".to_string()],
+            fixtures: Some(PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures")),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_check_manifest() {
+        let report = check_manifest(&examples().join("pixi.toml"), &options())
+            .await
+            .unwrap();
+        assert_eq!(report.status, RunStatus::Ok);
+        let python = report.platforms["linux-64"]
+            .iter()
+            .find(|update| update.name == "python")
+            .unwrap();
+        assert_eq!(python.installed_version, "3.12.11");
+        assert_eq!(python.latest_version, "3.14.0");
+        assert!(python.direct);
+        assert!(report.content_hash.is_some());
+
+        let metadata = report.metadata.unwrap();
+        assert_eq!(metadata.environment, "default");
+        assert_eq!(metadata.platforms, ["linux-64"]);
+        assert_eq!(metadata.lockfile_sha256.unwrap().len(), 64);
+        assert!(metadata.manifest.unwrap().ends_with("pixi.toml"));
+    }
+
+    #[tokio::test]
+    async fn test_check_manifest_unlisted_channels() {
+        let report = check_manifest(&examples().join("pixi.toml"), &options())
+            .await
+            .unwrap();
+        assert!(report.unlisted_channels.is_empty());
+
+        // Moving to a mirror leaves everything locked from conda-forge behind
+        let dir = tempfile::tempdir().unwrap();
+        let manifest = std::fs::read_to_string(examples().join("pixi.toml")).unwrap();
+        std::fs::write(
+            dir.path().join("pixi.toml"),
+            manifest.replace(
+                r#"channels = ["conda-forge"]"#,
+                r#"channels = ["https://prefix.dev/conda-forge"]"#,
+            ),
+        )
+        .unwrap();
+        std::fs::copy(examples().join("pixi.lock"), dir.path().join("pixi.lock")).unwrap();
+
+        let report = check_manifest(&dir.path().join("pixi.toml"), &options())
+            .await
+            .unwrap();
+        let python = report
+            .unlisted_channels
+            .iter()
+            .find(|unlisted| unlisted.name == "python")
+            .unwrap();
+        assert_eq!(python.platform, "linux-64");
+        assert_eq!(python.channel, "https://conda.anaconda.org/conda-forge");
+        // PyPI packages have no channel
+        assert!(report
+            .unlisted_channels
+            .iter()
+            .all(|unlisted| unlisted.name != "cowsay"));
+    }
+
+    #[tokio::test]
+    async fn test_check_manifest_environment_features() {
+        // Dependencies of features the default environment doesn't include
+        // aren't direct dependencies of it
+        let dir = tempfile::tempdir().unwrap();
+        let manifest = std::fs::read_to_string(examples().join("pixi.toml")).unwrap();
+        std::fs::write(
+            dir.path().join("pixi.toml"),
+            manifest.replace(
+                "[dependencies]\npython",
+                "[environments]\nextra = [\"extra\"]\n\n[feature.extra.dependencies]\npython",
+            ),
+        )
+        .unwrap();
+        std::fs::copy(examples().join("pixi.lock"), dir.path().join("pixi.lock")).unwrap();
+
+        let report = check_manifest(&dir.path().join("pixi.toml"), &options())
+            .await
+            .unwrap();
+        let python = report.platforms["linux-64"]
+            .iter()
+            .find(|update| update.name == "python")
+            .unwrap();
+        assert!(!python.direct);
+    }
+
+    #[tokio::test]
+    async fn test_check_manifest_selection() {
+        // Excluded packages aren't checked at all
+        let options = CheckOptions {
+            selection: Selection {
+                exclude: crate::ignore::ExcludePatterns::new(&["python"]).unwrap(),
+                ..Default::default()
+            },
+            ..options()
+        };
+        let report = check_manifest(&examples().join("pixi.toml"), &options)
+            .await
+            .unwrap();
+        assert!(report.checked.iter().all(|(_, _, name)| name != "python"));
+
+        // Python 3.14 is a minor update, left out when only major ones count
+        let options = CheckOptions {
+            selection: Selection {
+                severities: vec![crate::severity::Severity::Major],
+                ..Default::default()
+            },
+            ..options()
+        };
+        let report = check_manifest(&examples().join("pixi.toml"), &options)
+            .await
+            .unwrap();
+        assert!(report.checked.iter().any(|(_, _, name)| name == "python"));
+        assert!(report.platforms["linux-64"]
+            .iter()
+            .all(|update| update.name != "python"));
+    }
+
+    #[tokio::test]
+    async fn test_check_lockfile() {
+        let options = CheckOptions {
+            explicit: true,
+            ..options()
+        };
+        // Without a manifest nothing is explicit
+        let report = check_lockfile(&examples().join("pixi.lock"), &options)
+            .await
+            .unwrap();
+        assert_eq!(report.status, RunStatus::AllPackagesSkipped);
+
+        let options = CheckOptions {
+            environment: Some("missing".to_string()),
+            ..Default::default()
+        };
+        assert!(check_lockfile(&examples().join("pixi.lock"), &options)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_check_packages() {
+        // As printed by `pixi list --json`
+        let packages: Vec<InputPackage> = serde_json::from_str(
+            r#"[
+                {"name": "python", "version": "3.12.11", "kind": "conda",
+                 "source": "https://conda.anaconda.org/conda-forge/", "is_explicit": true},
+                {"name": "numpy", "version": "0.1"}
+            ]"#,
+        )
+        .unwrap();
+        assert_eq!(packages[1].kind, PackageKind::Conda);
+
+        let report = check_packages(&packages, &options()).await.unwrap();
+        assert_eq!(report.status, RunStatus::Ok);
+        let python = report.platforms["linux-64"]
+            .iter()
+            .find(|update| update.name == "python")
+            .unwrap();
+        assert_eq!(python.latest_version, "3.14.0");
+        assert_eq!(
+            python.channel.as_deref(),
+            Some("https://conda.anaconda.org/conda-forge")
+        );
+        assert!(!python.direct);
+        let numpy = report
+            .unchecked
+            .iter()
+            .find(|unchecked| unchecked.name == "numpy")
+            .unwrap();
+        assert_eq!(numpy.status, CheckStatus::NotFound);
+        assert_eq!(report.metadata.unwrap().environment, "stdin");
+
+        let report = check_packages(&[], &options()).await.unwrap();
+        assert_eq!(report.status, RunStatus::EmptyEnvironment);
+    }
+
+    #[tokio::test]
+    async fn test_check_global() {
+        let home = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/pixi-home");
+        let reports = check_global(&home, &options()).await.unwrap();
+        let report = &reports.environments["python"];
+        let python = &report.platforms["linux-64"][0];
+        assert_eq!(python.name, "python");
+        assert_eq!(python.installed_version, "3.12.11");
+        assert_eq!(python.latest_version, "3.14.0");
+        assert!(python.direct);
+        assert_eq!(python.size_delta, Some(5_000_000));
+        assert!(report
+            .metadata
+            .as_ref()
+            .unwrap()
+            .manifest
+            .as_deref()
+            .unwrap()
+            .ends_with("pixi-global.toml"));
+
+        assert!(check_global(Path::new("/nonexistent"), &options())
+            .await
+            .is_err());
+    }
+
+    #[test]
+    fn test_parse_options() {
+        let options: CheckOptions =
+            serde_json::from_str(r#"{"environment": "test", "explicit": true}"#).unwrap();
+        assert_eq!(options.environment.as_deref(), Some("test"));
+        assert!(options.explicit);
+        assert!(serde_json::from_str::<CheckOptions>(r#"{"platform": "linux-64"}"#).is_err());
+    }
+}
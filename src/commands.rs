@@ -0,0 +1,195 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::pixi::PackageKind;
+use crate::report::Report;
+
+/// What applying an update takes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Action {
+    /// The latest version is a pre-release, which `pixi upgrade` doesn't
+    /// pick, so the requirement is pinned to it
+    Pin,
+    /// The manifest's requirement excludes the latest version and is bumped
+    Upgrade,
+    /// The requirements admit the latest version; relocking is enough
+    Update,
+}
+
+/// The pixi commands that apply every update of a report, in the order to
+/// run them: requirements that need changing first, one command per
+/// manifest feature, then a single `pixi update` of everything the
+/// requirements already admit. Each package is named once, however many
+/// platforms it's outdated on. Updates a trial solve found unsolvable are
+/// left out.
+pub fn suggest_commands(report: &Report, environment: &str) -> Vec<String> {
+    // The action and latest version of each package, with the features
+    // declaring it; `None` is the default feature
+    let mut packages: BTreeMap<(&str, PackageKind), (Action, &str, BTreeSet<Option<&str>>)> =
+        BTreeMap::new();
+    for update in report.platforms.values().flatten() {
+        if update.unsolvable.is_some() {
+            continue;
+        }
+        let kind = update.kind.unwrap_or(PackageKind::Conda);
+        let is_prerelease = match kind {
+            PackageKind::Conda => crate::conda::is_prerelease(&update.latest_version),
+            PackageKind::Pypi => crate::pypi::is_prerelease(&update.latest_version),
+        };
+        let action = if !update.direct {
+            Action::Update
+        } else if is_prerelease {
+            Action::Pin
+        } else if update.needs_requirement_change() {
+            Action::Upgrade
+        } else {
+            Action::Update
+        };
+        // The features come from the checked environment, so the default
+        // feature is the one `pixi upgrade` edits without `--feature`
+        let features: BTreeSet<Option<&str>> = match update.features.as_slice() {
+            [] => BTreeSet::from([None]),
+            features => features
+                .iter()
+                .map(|feature| Some(feature.as_str()).filter(|f| *f != "default"))
+                .collect(),
+        };
+        let entry = packages.entry((update.name.as_str(), kind)).or_insert((
+            action,
+            update.latest_version.as_str(),
+            BTreeSet::new(),
+        ));
+        // The most involved action any platform needs wins
+        entry.0 = entry.0.min(action);
+        entry.2.extend(features);
+    }
+
+    // Package arguments grouped by the command they go into
+    let mut groups: BTreeMap<(Action, PackageKind, Option<&str>), Vec<String>> = BTreeMap::new();
+    for ((name, kind), (action, latest, features)) in &packages {
+        let features = match action {
+            Action::Update => BTreeSet::from([None]),
+            _ => features.clone(),
+        };
+        for feature in features {
+            let argument = match action {
+                Action::Pin => format!("\"{}=={}\"", name, latest),
+                _ => name.to_string(),
+            };
+            // `pixi update` takes both kinds of packages in one command
+            let kind = match action {
+                Action::Update => PackageKind::Conda,
+                _ => *kind,
+            };
+            groups
+                .entry((*action, kind, feature))
+                .or_default()
+                .push(argument);
+        }
+    }
+
+    groups
+        .into_iter()
+        .map(|((action, kind, feature), arguments)| {
+            let mut command = match action {
+                Action::Pin => "pixi add".to_string(),
+                Action::Upgrade => "pixi upgrade".to_string(),
+                Action::Update => "pixi update".to_string(),
+            };
+            if action == Action::Pin && kind == PackageKind::Pypi {
+                command.push_str(" --pypi");
+            }
+            if let Some(feature) = feature {
+                command.push_str(&format!(" --feature {}", feature));
+            }
+            if action == Action::Update && environment != "default" {
+                command.push_str(&format!(" --environment {}", environment));
+            }
+            format!("{} {}", command, arguments.join(" "))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::{PackageUpdate, PlatformUpdates};
+
+    fn update(name: &str, latest: &str) -> PackageUpdate {
+        PackageUpdate {
+            name: name.to_string(),
+            kind: Some(PackageKind::Conda),
+            installed_version: "1.0.0".to_string(),
+            latest_version: latest.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_suggest_commands() {
+        let mut platforms = PlatformUpdates::new();
+        platforms.insert(
+            "linux-64".to_string(),
+            vec![
+                // Transitive dependencies follow whatever requires them
+                update("libzlib", "1.3.1"),
+                // A requirement admitting the latest version
+                PackageUpdate {
+                    direct: true,
+                    latest_compatible: Some("2.0.0".to_string()),
+                    ..update("numpy", "2.0.0")
+                },
+                // A requirement that has to be bumped
+                PackageUpdate {
+                    direct: true,
+                    latest_compatible: Some("3.12.11".to_string()),
+                    ..update("python", "3.14.0")
+                },
+                PackageUpdate {
+                    direct: true,
+                    features: vec!["test".to_string()],
+                    latest_compatible: Some("7.4.0".to_string()),
+                    ..update("pytest", "8.3.0")
+                },
+                // Declared by the default feature and another one
+                PackageUpdate {
+                    direct: true,
+                    features: vec!["default".to_string(), "lint".to_string()],
+                    latest_compatible: Some("0.5.0".to_string()),
+                    ..update("ruff", "0.6.0")
+                },
+                PackageUpdate {
+                    kind: Some(PackageKind::Pypi),
+                    direct: true,
+                    ..update("cowsay", "7.0.0rc1")
+                },
+                // A dead end
+                PackageUpdate {
+                    unsolvable: Some("failed to solve".to_string()),
+                    ..update("openssl", "4.0.0")
+                },
+            ],
+        );
+        // Listed once, though outdated on another platform too
+        platforms.insert("osx-arm64".to_string(), vec![update("libzlib", "1.3.1")]);
+        let report = Report {
+            platforms,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            suggest_commands(&report, "default"),
+            [
+                "pixi add --pypi \"cowsay==7.0.0rc1\"",
+                "pixi upgrade python ruff",
+                "pixi upgrade --feature lint ruff",
+                "pixi upgrade --feature test pytest",
+                "pixi update libzlib numpy",
+            ]
+        );
+        assert_eq!(
+            suggest_commands(&report, "test").last().unwrap(),
+            "pixi update --environment test libzlib numpy"
+        );
+        assert!(suggest_commands(&Report::default(), "default").is_empty());
+    }
+}
@@ -1,25 +1,426 @@
 use anyhow::{Context, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures::stream::{self, StreamExt};
 use rattler_conda_types::{
-    Channel, ChannelConfig, MatchSpec, PackageName, Platform, VersionWithSource,
+    Channel, ChannelConfig, MatchSpec, PackageName, PackageRecord, ParseStrictness, Platform,
+    RepoDataRecord, Version, VersionSpec, VersionWithSource,
 };
-use rattler_repodata_gateway::Gateway;
+use rattler_repodata_gateway::fetch::CacheAction;
+use rattler_repodata_gateway::{Gateway, Reporter};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use tracing::{debug, info};
 use url::Url;
 
-/// Extract the channel URL from a conda package source
-/// Example: "https://conda.anaconda.org/conda-forge/" from package source
+use crate::candidates::{Candidate, Decision, ExclusionReason};
+use crate::pixi::{PackageKind, PixiPackage};
+use crate::report::LockedStatus;
+use crate::retry::{self, RetryPolicy};
+use crate::sources::{QueryOutcome, RepodataFetch};
+use crate::upstream::{
+    Artifact, PackageKey, SourceResponse, UpstreamInfo, VersionSource, DEFAULT_JOBS,
+};
+
+/// The versions of a package that are available in a channel
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CondaVersionInfo {
+    /// The highest version across all queried platforms, skipping pre-releases
+    /// unless there is no stable release
+    pub latest_version: Option<String>,
+    /// Every `(version, build)` pair present in the channel's repodata
+    pub artifacts: HashSet<(String, String)>,
+    /// Earliest upload timestamp of each version, where repodata records one
+    pub release_dates: HashMap<String, DateTime<Utc>>,
+    /// Largest artifact size of each `(version, subdir)` pair, where repodata records one
+    #[serde(with = "pair_keyed")]
+    pub sizes: HashMap<(String, String), u64>,
+    /// Names of the dependencies of each `(version, subdir)` pair, across all
+    /// of its builds
+    #[serde(with = "pair_keyed")]
+    pub depends: HashMap<(String, String), BTreeSet<String>>,
+    /// The package files of each `(version, subdir)` pair, one per build
+    #[serde(default, with = "pair_keyed")]
+    pub files: HashMap<(String, String), Vec<Artifact>>,
+    /// The license of each version, where repodata records one
+    #[serde(default)]
+    pub licenses: HashMap<String, String>,
+}
+
+/// Maps keyed by `(version, subdir)` as a list of entries, since JSON only
+/// has string keys
+mod pair_keyed {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::collections::HashMap;
+
+    type Key = (String, String);
+
+    pub fn serialize<V: Serialize, S: Serializer>(
+        map: &HashMap<Key, V>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(map)
+    }
+
+    pub fn deserialize<'de, V: Deserialize<'de>, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<HashMap<Key, V>, D::Error> {
+        Ok(Vec::<(Key, V)>::deserialize(deserializer)?
+            .into_iter()
+            .collect())
+    }
+}
+
+impl CondaVersionInfo {
+    /// Every version found in the channel, newest first, with the decision
+    /// taken for each when picking the latest version
+    pub fn candidates(&self) -> Vec<Candidate> {
+        let mut builds: HashMap<&str, usize> = HashMap::new();
+        for (version, _) in &self.artifacts {
+            *builds.entry(version.as_str()).or_default() += 1;
+        }
+
+        let mut versions: Vec<(&str, usize)> = builds.into_iter().collect();
+        versions.sort_by(
+            |(a, _), (b, _)| match (Version::from_str(a), Version::from_str(b)) {
+                (Ok(a), Ok(b)) => b.cmp(&a),
+                _ => b.cmp(a),
+            },
+        );
+
+        versions
+            .into_iter()
+            .map(|(version, build_count)| {
+                let is_latest = self.latest_version.as_deref().is_some_and(|latest| {
+                    Version::from_str(latest).ok() == Version::from_str(version).ok()
+                });
+                Candidate {
+                    version: version.to_string(),
+                    detail: Some(if build_count == 1 {
+                        "1 build".to_string()
+                    } else {
+                        format!("{} builds", build_count)
+                    }),
+                    decision: if is_latest {
+                        Decision::Selected
+                    } else if is_prerelease(version) {
+                        Decision::Excluded(ExclusionReason::Prerelease)
+                    } else {
+                        Decision::Older
+                    },
+                }
+            })
+            .collect()
+    }
+
+    /// Consider pre-releases for the latest version
+    pub fn with_prereleases(mut self) -> Self {
+        let newest = self
+            .artifacts
+            .iter()
+            .filter_map(|(version, _)| Version::from_str(version).ok().map(|v| (v, version)))
+            .max_by(|(a, _), (b, _)| a.cmp(b))
+            .map(|(_, version)| version.clone());
+        if newest.is_some() {
+            self.latest_version = newest;
+        }
+        self
+    }
+
+    /// Add the versions another channel has of the same package, as if both
+    /// channels were one. The latest version is the newer of the two, though
+    /// a pre-release never replaces a stable release.
+    pub fn merge(&mut self, other: CondaVersionInfo) {
+        self.latest_version = match (self.latest_version.take(), other.latest_version) {
+            (Some(ours), Some(theirs)) => {
+                let newer = match (is_prerelease(&ours), is_prerelease(&theirs)) {
+                    (false, true) => false,
+                    (true, false) => true,
+                    _ => is_older(&ours, &theirs),
+                };
+                Some(if newer { theirs } else { ours })
+            }
+            (ours, theirs) => ours.or(theirs),
+        };
+        self.artifacts.extend(other.artifacts);
+        for (version, date) in other.release_dates {
+            self.release_dates
+                .entry(version)
+                .and_modify(|earliest| *earliest = (*earliest).min(date))
+                .or_insert(date);
+        }
+        for (key, size) in other.sizes {
+            self.sizes
+                .entry(key)
+                .and_modify(|largest| *largest = (*largest).max(size))
+                .or_insert(size);
+        }
+        for (key, depends) in other.depends {
+            self.depends.entry(key).or_default().extend(depends);
+        }
+        for (key, files) in other.files {
+            self.files.entry(key).or_default().extend(files);
+        }
+        for (version, license) in other.licenses {
+            self.licenses.entry(version).or_insert(license);
+        }
+    }
+
+    /// The license a version is published under, if repodata records one
+    pub fn license(&self, version: &str) -> Option<&str> {
+        self.licenses.get(version).map(String::as_str)
+    }
+
+    /// The newest version whose license is unknown or passes `allowed`.
+    /// Pre-releases are only considered if the latest version is one.
+    pub fn latest_allowed(&self, allowed: &dyn Fn(&str) -> bool) -> Option<String> {
+        let prereleases = self.latest_version.as_deref().is_some_and(is_prerelease);
+        self.artifacts
+            .iter()
+            .map(|(version, _)| version)
+            .filter(|version| prereleases || !is_prerelease(version))
+            .filter(|version| self.license(version).is_none_or(allowed))
+            .filter_map(|version| Version::from_str(version).ok().map(|v| (v, version)))
+            .max_by(|(a, _), (b, _)| a.cmp(b))
+            .map(|(_, version)| version.clone())
+    }
+
+    /// The newest stable version that satisfies every requirement in `specs`
+    pub fn latest_matching(&self, specs: &[&str]) -> Option<String> {
+        self.artifacts
+            .iter()
+            .map(|(version, _)| version)
+            .filter(|version| !is_prerelease(version))
+            .filter(|version| specs.iter().all(|spec| spec_matches(spec, version)))
+            .filter_map(|version| Version::from_str(version).ok().map(|v| (v, version)))
+            .max_by(|(a, _), (b, _)| a.cmp(b))
+            .map(|(_, version)| version.clone())
+    }
+
+    /// When the given version was first published to the channel
+    pub fn release_date(&self, version: &str) -> Option<DateTime<Utc>> {
+        self.release_dates.get(version).copied()
+    }
+
+    /// The size of `version` in `subdir`, falling back to its noarch build
+    pub fn artifact_size(&self, version: &str, subdir: &str) -> Option<u64> {
+        self.sizes
+            .get(&(version.to_string(), subdir.to_string()))
+            .or_else(|| self.sizes.get(&(version.to_string(), "noarch".to_string())))
+            .copied()
+    }
+
+    /// The names of the dependencies of a version in a subdir, falling back
+    /// to `noarch` like [`Self::artifact_size`]
+    pub fn dependencies(&self, version: &str, subdir: &str) -> Option<Vec<String>> {
+        self.depends
+            .get(&(version.to_string(), subdir.to_string()))
+            .or_else(|| {
+                self.depends
+                    .get(&(version.to_string(), "noarch".to_string()))
+            })
+            .map(|names| names.iter().cloned().collect())
+    }
+
+    /// The package files of a version in a subdir, falling back to `noarch`
+    /// like [`Self::artifact_size`]
+    pub fn artifact_files(&self, version: &str, subdir: &str) -> Vec<Artifact> {
+        self.files
+            .get(&(version.to_string(), subdir.to_string()))
+            .or_else(|| self.files.get(&(version.to_string(), "noarch".to_string())))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Whether the given locked artifact is still present in the channel
+    pub fn locked_status(&self, version: &str, build: Option<&str>) -> LockedStatus {
+        let available = self
+            .artifacts
+            .iter()
+            .any(|(v, b)| v == version && build.is_none_or(|build| b == build));
+
+        if available {
+            LockedStatus::Available
+        } else {
+            LockedStatus::Removed
+        }
+    }
+}
+
+/// Extract the channel URL from a conda package source: everything up to
+/// the subdir, with the port. Example:
+/// "https://repo.prefix.dev/org/channel" from
+/// "https://repo.prefix.dev/org/channel/linux-64/pkg.conda". Local channels
+/// given as absolute paths become `file://` URLs, and a channel URL is the
+/// channel itself.
 pub fn extract_channel_url(source: &str) -> Option<String> {
-    if let Ok(url) = Url::parse(source) {
-        // Get the base channel URL (scheme + host + first path segment)
-        let channel_base = format!(
-            "{}://{}/{}",
-            url.scheme(),
-            url.host_str()?,
-            url.path_segments()?.next()?
-        );
-        Some(channel_base)
-    } else {
-        None
+    if let Some(url) = crate::local::file_url(source) {
+        return Some(format!("file:///{}", channel_segments(&url)?.join("/")));
+    }
+    let url = Url::parse(source).ok()?;
+    let host = match url.port() {
+        Some(port) => format!("{}:{}", url.host_str()?, port),
+        None => url.host_str()?.to_string(),
+    };
+    Some(format!(
+        "{}://{}/{}",
+        url.scheme(),
+        host,
+        channel_segments(&url)?.join("/")
+    ))
+}
+
+/// A channel of a locked environment as a full base URL, comparable with
+/// the channels of its locked packages: the whole path and port are kept,
+/// and channel names resolve against `channel_alias`.
+pub fn lockfile_channel(channel: &str, channel_alias: &str) -> String {
+    let url = crate::policy::channel_url(channel, channel_alias);
+    extract_channel_url(&url).unwrap_or(url)
+}
+
+/// The channel to query for a locked conda package: the main label of the
+/// channel its artifact was downloaded from. PyPI packages aren't in a
+/// channel.
+pub fn locked_channel(package: &PixiPackage) -> Option<String> {
+    if package.kind != PackageKind::Conda {
+        return None;
+    }
+    let channel = extract_channel_url(package.source.as_deref()?)?;
+    Some(without_label(&channel).to_string())
+}
+
+/// A channel without its label, i.e. the channel's main label:
+/// "https://conda.anaconda.org/conda-forge" from
+/// "https://conda.anaconda.org/conda-forge/label/rc"
+pub fn without_label(channel: &str) -> &str {
+    channel
+        .split_once("/label/")
+        .map_or(channel, |(channel, _)| channel)
+}
+
+/// The path of a channel, from the URL of the channel or of a package file
+/// in one of its subdirs
+fn channel_segments(url: &Url) -> Option<Vec<&str>> {
+    let mut segments: Vec<&str> = url.path_segments()?.filter(|s| !s.is_empty()).collect();
+    if segments
+        .last()
+        .is_some_and(|file| file.ends_with(".conda") || file.ends_with(".tar.bz2"))
+    {
+        segments.truncate(segments.len().saturating_sub(2));
+    }
+    Some(segments)
+}
+
+/// The package name of a dependency spec such as `python >=3.9,<3.13`
+pub fn dependency_name(spec: &str) -> Option<String> {
+    let name: String = spec
+        .trim()
+        .chars()
+        .take_while(|c| !c.is_whitespace() && !matches!(c, '<' | '>' | '=' | '!' | '~' | '['))
+        .collect();
+    (!name.is_empty()).then_some(name)
+}
+
+/// Extract a non-default label from a conda package source
+/// Example: "rc" from "https://conda.anaconda.org/conda-forge/label/rc/linux-64/pkg.conda"
+pub fn extract_label(source: &str) -> Option<String> {
+    let url = Url::parse(source).ok()?;
+    let mut segments = url.path_segments()?;
+    segments.find(|segment| *segment == "label")?;
+    segments
+        .next()
+        .filter(|label| !label.is_empty() && *label != "main")
+        .map(str::to_string)
+}
+
+/// Whether `latest` sorts after `installed` by conda's version ordering, so
+/// `1.0` and `1.0.0` are the same version. Versions that don't parse are
+/// newer if they differ at all.
+pub fn is_newer(latest: &str, installed: &str) -> bool {
+    match (Version::from_str(latest), Version::from_str(installed)) {
+        (Ok(latest), Ok(installed)) => latest > installed,
+        _ => latest != installed,
+    }
+}
+
+/// Whether `version` sorts before `than` by conda's version ordering.
+/// Versions that don't parse are never older.
+pub fn is_older(version: &str, than: &str) -> bool {
+    match (Version::from_str(version), Version::from_str(than)) {
+        (Ok(version), Ok(than)) => version < than,
+        _ => false,
+    }
+}
+
+/// Whether a conda version is an alpha, beta, release candidate or
+/// development version, such as `3.15.0a1`, `2.0.0rc1` or `1.0.dev0`.
+/// Single letters only count when followed by a number, so openssl's
+/// `1.1.1w` is a regular release.
+pub fn is_prerelease(version: &str) -> bool {
+    const MARKERS: [&str; 6] = ["alpha", "beta", "rc", "pre", "preview", "dev"];
+
+    // Split into runs of digits and letters, e.g. `3.15.0a1` into 3, 15, 0, a, 1.
+    // The local version after `+` doesn't say anything about the release.
+    let version = version.split('+').next().unwrap_or(version);
+    let mut tokens: Vec<String> = Vec::new();
+    let mut current = String::new();
+    for c in version.chars() {
+        let boundary = !c.is_ascii_alphanumeric()
+            || current
+                .chars()
+                .last()
+                .is_some_and(|last| last.is_ascii_digit() != c.is_ascii_digit());
+        if boundary && !current.is_empty() {
+            tokens.push(std::mem::take(&mut current));
+        }
+        if c.is_ascii_alphanumeric() {
+            current.push(c.to_ascii_lowercase());
+        }
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+
+    tokens.iter().enumerate().any(|(i, token)| {
+        let followed_by_number = tokens
+            .get(i + 1)
+            .is_some_and(|next| next.starts_with(|c: char| c.is_ascii_digit()));
+        MARKERS.contains(&token.as_str())
+            || (matches!(token.as_str(), "a" | "b" | "c") && followed_by_number)
+    })
+}
+
+/// Whether a conda version requirement such as `>=3.12,<3.13` admits `version`.
+/// Requirements that can't be parsed don't rule anything out.
+pub fn spec_matches(spec: &str, version: &str) -> bool {
+    match (
+        VersionSpec::from_str(spec, ParseStrictness::Lenient),
+        Version::from_str(version),
+    ) {
+        (Ok(spec), Ok(version)) => spec.matches(&version),
+        _ => true,
+    }
+}
+
+/// Whether `stable` is at least as new as `installed`, using conda version ordering
+pub fn supersedes(stable: &str, installed: &str) -> bool {
+    match (Version::from_str(stable), Version::from_str(installed)) {
+        (Ok(stable), Ok(installed)) => stable >= installed,
+        _ => false,
+    }
+}
+
+/// Extract the subdir from a conda package source
+/// Example: "osx-arm64" from "https://conda.anaconda.org/conda-forge/osx-arm64/python-3.12.0.conda"
+pub fn extract_subdir(source: &str) -> Option<String> {
+    let url = Url::parse(source).ok()?;
+    let segments: Vec<&str> = url.path_segments()?.collect();
+    match segments.as_slice() {
+        [.., subdir, _file] if !subdir.is_empty() => Some(subdir.to_string()),
+        _ => None,
     }
 }
 
@@ -30,17 +431,105 @@ pub async fn get_latest_conda_version_multi_platform(
     channel_url: &str,
     platforms: &[&str],
 ) -> Result<Option<String>> {
+    let info = query_conda_versions(gateway, package_name, channel_url, platforms).await?;
+    Ok(info.latest_version)
+}
+
+/// Query conda channels for all versions of a package across multiple platforms
+pub async fn query_conda_versions(
+    gateway: &Gateway,
+    package_name: &str,
+    channel_url: &str,
+    platforms: &[&str],
+) -> Result<CondaVersionInfo> {
+    let mut infos = query_conda_channel(
+        gateway,
+        &[package_name],
+        channel_url,
+        platforms,
+        RetryPolicy::default(),
+        None,
+    )
+    .await?;
+    Ok(infos.remove(package_name).unwrap_or_default())
+}
+
+/// Query a channel for all versions of several packages at once. The
+/// repodata of each platform is read once for all of them, instead of once
+/// per package. Every requested package has an entry, empty if the channel
+/// doesn't have it. Repodata fetches that failed transiently are retried as
+/// `retry` allows, and an attempt that takes longer than `timeout` counts as
+/// failed.
+pub async fn query_conda_channel(
+    gateway: &Gateway,
+    package_names: &[&str],
+    channel_url: &str,
+    platforms: &[&str],
+    retry: RetryPolicy,
+    timeout: Option<Duration>,
+) -> Result<HashMap<String, CondaVersionInfo>> {
+    let mut infos = query_conda_channels(
+        gateway,
+        &[(channel_url, package_names.to_vec())],
+        platforms,
+        retry,
+        timeout,
+    )
+    .await?;
+    Ok(infos.remove(channel_url).unwrap_or_default())
+}
+
+/// Query several channels in a single gateway query, each for its own
+/// packages, keyed by channel URL. Channels that are checked for the same
+/// package names are read together this way, so the repodata is loaded and
+/// parsed in one pass rather than once per channel. Otherwise like
+/// [`query_conda_channel`]; a failure of any channel fails the whole query.
+pub async fn query_conda_channels(
+    gateway: &Gateway,
+    requests: &[(&str, Vec<&str>)],
+    platforms: &[&str],
+    retry: RetryPolicy,
+    timeout: Option<Duration>,
+) -> Result<HashMap<String, HashMap<String, CondaVersionInfo>>> {
+    query_reporting(
+        gateway,
+        requests,
+        platforms,
+        retry,
+        timeout,
+        &DownloadCounter::default(),
+    )
+    .await
+}
+
+/// [`query_conda_channels`], reporting the repodata the gateway downloads
+/// for the query to `downloads`
+async fn query_reporting(
+    gateway: &Gateway,
+    requests: &[(&str, Vec<&str>)],
+    platforms: &[&str],
+    retry: RetryPolicy,
+    timeout: Option<Duration>,
+    downloads: &DownloadCounter,
+) -> Result<HashMap<String, HashMap<String, CondaVersionInfo>>> {
+    let channel_urls: Vec<&str> = requests.iter().map(|(url, _)| *url).collect();
     debug!(
-        package = package_name,
-        channel = channel_url,
+        packages = requests.iter().map(|(_, names)| names.len()).sum::<usize>(),
+        channels = ?channel_urls,
         platforms = ?platforms,
-        "Querying conda package across platforms"
+        "Querying conda packages across platforms"
     );
 
-    // Parse the channel
+    // Parse the channels
     let channel_config = ChannelConfig::default_with_root_dir(std::env::current_dir()?);
-    let channel = Channel::from_str(channel_url, &channel_config)
-        .with_context(|| format!("Invalid channel URL: {}", channel_url))?;
+    let channels = channel_urls
+        .iter()
+        .map(|channel_url| {
+            Channel::from_str(channel_url, &channel_config)
+                .with_context(|| format!("Invalid channel URL: {}", channel_url))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let channel_description = channel_urls.join(", ");
 
     // Parse all platforms
     let mut parsed_platforms = vec![Platform::NoArch];
@@ -51,75 +540,634 @@ pub async fn get_latest_conda_version_multi_platform(
         parsed_platforms.push(plat);
     }
 
-    // Create a match spec for the package (any version)
-    let package_name_typed = PackageName::try_from(package_name.to_string())
-        .with_context(|| format!("Invalid package name: {}", package_name))?;
-
-    let match_spec = MatchSpec::from_nameless(
-        rattler_conda_types::NamelessMatchSpec {
-            version: None,
-            build: None,
-            build_number: None,
-            file_name: None,
-            channel: None,
-            subdir: None,
-            namespace: None,
-            md5: None,
-            sha256: None,
-            url: None,
-            license: None,
-            extras: None,
-        },
-        Some(package_name_typed),
-    );
+    // Create a match spec for each package (any version), once however many
+    // channels it's looked up in
+    let package_names: BTreeSet<&str> = requests
+        .iter()
+        .flat_map(|(_, names)| names.iter().copied())
+        .collect();
+    let mut match_specs = Vec::with_capacity(package_names.len());
+    for package_name in &package_names {
+        let package_name_typed = PackageName::try_from(package_name.to_string())
+            .with_context(|| format!("Invalid package name: {}", package_name))?;
 
-    let mut latest_version: Option<VersionWithSource> = None;
+        match_specs.push(MatchSpec::from_nameless(
+            rattler_conda_types::NamelessMatchSpec {
+                version: None,
+                build: None,
+                build_number: None,
+                file_name: None,
+                channel: None,
+                subdir: None,
+                namespace: None,
+                md5: None,
+                sha256: None,
+                url: None,
+                license: None,
+                extras: None,
+            },
+            Some(package_name_typed),
+        ));
+    }
 
-    // Query all platforms in a single call for efficiency
+    // Query all channels, platforms and packages in a single call for efficiency
     let start = std::time::Instant::now();
     debug!(platforms = ?parsed_platforms, "Querying repodata");
 
-    let records = gateway
-        .query(
-            vec![channel.clone()],
-            parsed_platforms.clone(),
-            vec![match_spec.clone()],
-        )
-        .await
-        .with_context(|| format!("Failed to query channel {}", channel_url))?;
+    let mut attempt = 1;
+    let records = loop {
+        let query = gateway
+            .query(
+                channels.clone(),
+                parsed_platforms.clone(),
+                match_specs.clone(),
+            )
+            .with_reporter(downloads.clone());
+        let result = match timeout {
+            Some(timeout) => match tokio::time::timeout(timeout, query).await {
+                Ok(result) => result.map_err(anyhow::Error::from),
+                Err(elapsed) => Err(anyhow::Error::new(elapsed).context(format!(
+                    "Repodata query timed out after {}s",
+                    timeout.as_secs()
+                ))),
+            },
+            None => query.await.map_err(anyhow::Error::from),
+        };
+        match result {
+            Ok(records) => break records,
+            // The gateway's client already retries some failures itself, so
+            // only those that may still go away are tried again, and a
+            // missing channel or bad credentials fail right away
+            Err(e) if retry::is_transient_failure(&e) && retry.should_retry(attempt) => {
+                let delay = retry.delay(attempt);
+                debug!(
+                    channel = channel_description,
+                    attempt,
+                    error = %e,
+                    delay_ms = delay.as_millis(),
+                    "Retrying repodata query"
+                );
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            }
+            Err(e) => {
+                return Err(e)
+                    .with_context(|| format!("Failed to query channel {}", channel_description))
+            }
+        }
+    };
 
     let elapsed = start.elapsed();
-    if elapsed.as_secs() > 1 {
+    if downloads.total() > 0 {
         info!(
-            package = package_name,
+            channel = channel_description,
+            packages = package_names.len(),
             elapsed_ms = elapsed.as_millis(),
-            "Query completed (initial load)"
+            bytes = downloads.total(),
+            "Query completed (downloaded repodata)"
         );
     } else {
         debug!(
-            package = package_name,
+            channel = channel_description,
+            packages = package_names.len(),
             elapsed_us = elapsed.as_micros(),
             "Query completed (cached)"
         );
     }
 
-    // Process all records from all platforms
-    for repo_data in records.iter() {
-        for record in repo_data.iter() {
-            let version = &record.package_record.version;
+    // The gateway returns one repodata per channel, holding the records of
+    // all its subdirs, so the records are split by the channel they name
+    let mut records_by_channel: HashMap<&str, Vec<&RepoDataRecord>> = HashMap::new();
+    for record in records.iter().flat_map(|repo_data| repo_data.iter()) {
+        if let Some(channel) = record.channel.as_deref() {
+            records_by_channel
+                .entry(channel.trim_end_matches('/'))
+                .or_default()
+                .push(record);
+        }
+    }
 
-            match &latest_version {
-                None => latest_version = Some(version.clone()),
-                Some(current) => {
-                    if version.version() > current.version() {
-                        latest_version = Some(version.clone());
-                    }
+    // Split the records of all platforms by channel and package
+    let mut infos = HashMap::with_capacity(requests.len());
+    for ((channel_url, names), channel) in requests.iter().zip(&channels) {
+        let mut records_by_name: HashMap<&str, Vec<(&PackageRecord, String)>> =
+            names.iter().map(|name| (*name, Vec::new())).collect();
+        let channel_records = records_by_channel
+            .get(channel.base_url.as_str().trim_end_matches('/'))
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        for record in channel_records {
+            let name = record.package_record.name.as_normalized();
+            if let Some(package_records) = records_by_name.get_mut(name) {
+                package_records.push((&record.package_record, record.url.to_string()));
+            }
+        }
+        infos.insert(
+            channel_url.to_string(),
+            records_by_name
+                .into_iter()
+                .map(|(name, package_records)| (name.to_string(), version_info(package_records)))
+                .collect(),
+        );
+    }
+    Ok(infos)
+}
+
+/// Collect the versions, release dates and sizes found in repodata records,
+/// each given with the URL of its package file
+pub fn version_info<'a>(
+    records: impl IntoIterator<Item = (&'a PackageRecord, String)>,
+) -> CondaVersionInfo {
+    let mut latest_version: Option<&VersionWithSource> = None;
+    let mut latest_prerelease: Option<&VersionWithSource> = None;
+    let mut artifacts = HashSet::new();
+    let mut release_dates: HashMap<String, DateTime<Utc>> = HashMap::new();
+    let mut sizes: HashMap<(String, String), u64> = HashMap::new();
+    let mut depends: HashMap<(String, String), BTreeSet<String>> = HashMap::new();
+    let mut files: HashMap<(String, String), Vec<Artifact>> = HashMap::new();
+    let mut licenses: HashMap<String, String> = HashMap::new();
+
+    for (record, url) in records {
+        let version = &record.version;
+        artifacts.insert((version.to_string(), record.build.clone()));
+
+        if let Some(timestamp) = record
+            .timestamp
+            .as_ref()
+            .and_then(|ts| DateTime::from_timestamp_millis(ts.timestamp_millis()))
+        {
+            release_dates
+                .entry(version.to_string())
+                .and_modify(|earliest| *earliest = (*earliest).min(timestamp))
+                .or_insert(timestamp);
+        }
+
+        if let Some(size) = record.size {
+            sizes
+                .entry((version.to_string(), record.subdir.clone()))
+                .and_modify(|largest| *largest = (*largest).max(size))
+                .or_insert(size);
+        }
+
+        depends
+            .entry((version.to_string(), record.subdir.clone()))
+            .or_default()
+            .extend(
+                record
+                    .depends
+                    .iter()
+                    .filter_map(|spec| dependency_name(spec)),
+            );
+
+        if let Some(license) = record.license.as_ref().filter(|l| !l.trim().is_empty()) {
+            licenses
+                .entry(version.to_string())
+                .or_insert_with(|| license.trim().to_string());
+        }
+
+        files
+            .entry((version.to_string(), record.subdir.clone()))
+            .or_default()
+            .push(Artifact {
+                file_name: url.rsplit('/').next().unwrap_or_default().to_string(),
+                sha256: record.sha256.map(|sha256| format!("{:x}", sha256)),
+                url,
+            });
+
+        let latest = if is_prerelease(&version.to_string()) {
+            &mut latest_prerelease
+        } else {
+            &mut latest_version
+        };
+        if latest.is_none_or(|current| version.version() > current.version()) {
+            *latest = Some(version);
+        }
+    }
+
+    CondaVersionInfo {
+        latest_version: latest_version
+            .or(latest_prerelease)
+            .map(|v| v.version().to_string()),
+        artifacts,
+        release_dates,
+        sizes,
+        depends,
+        files,
+        licenses,
+    }
+}
+
+/// Conda channels queried through the repodata gateway
+#[derive(Clone)]
+pub struct CondaSource {
+    gateway: Gateway,
+    /// How many channels are queried at once
+    jobs: usize,
+    retry: RetryPolicy,
+    /// How long a single repodata query may take
+    timeout: Option<Duration>,
+    /// Packages locked as noarch everywhere, queried in the `noarch` subdir only
+    noarch: HashSet<PackageKey>,
+    /// The gateway rechecks go through, bypassing the repodata cache
+    refresh_gateway: Option<Gateway>,
+    /// Channels whose repodata [`CondaSource::warm`] downloaded rather than
+    /// read from the cache
+    downloaded_channels: HashSet<String>,
+}
+
+impl CondaSource {
+    pub fn new(gateway: Gateway) -> Self {
+        Self {
+            gateway,
+            jobs: DEFAULT_JOBS,
+            retry: RetryPolicy::default(),
+            timeout: None,
+            noarch: HashSet::new(),
+            refresh_gateway: None,
+            downloaded_channels: HashSet::new(),
+        }
+    }
+
+    /// Recheck answers that look wrong through `gateway`, which should
+    /// share the workspace's client so credentials and mirrors still apply,
+    /// and use [`uncached_channel_config`]. Without one there are no rechecks.
+    pub fn with_refresh_gateway(mut self, gateway: Gateway) -> Self {
+        self.refresh_gateway = Some(gateway);
+        self
+    }
+
+    /// Only look for these packages in the `noarch` subdir, skipping the
+    /// repodata of the platform subdirs. Meant for packages that are locked
+    /// as noarch on every platform.
+    pub fn with_noarch_packages(mut self, keys: impl IntoIterator<Item = PackageKey>) -> Self {
+        self.noarch = keys.into_iter().collect();
+        self
+    }
+
+    /// Query up to `jobs` channels at once
+    pub fn with_jobs(mut self, jobs: usize) -> Self {
+        self.jobs = jobs.max(1);
+        self
+    }
+
+    /// Retry failed repodata fetches according to `retry`
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Give up on a repodata query attempt after `timeout`
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// The `(channel, subdir)` pairs a query of `keys` reads: `noarch` of
+    /// every channel, plus the platform subdirs unless a package is noarch
+    pub fn subdirs(&self, keys: &[PackageKey], platforms: &[&str]) -> BTreeSet<(String, String)> {
+        let mut subdirs = BTreeSet::new();
+        for key in keys.iter().filter(|key| key.kind == PackageKind::Conda) {
+            let Some(ref channel_url) = key.channel else {
+                continue;
+            };
+            if !is_gateway_channel(channel_url) {
+                continue;
+            }
+            subdirs.insert((channel_url.clone(), Platform::NoArch.to_string()));
+            if !self.noarch.contains(key) {
+                for platform in platforms {
+                    subdirs.insert((channel_url.clone(), platform.to_string()));
                 }
             }
         }
+        subdirs
     }
 
-    Ok(latest_version.map(|v| v.version().to_string()))
+    /// Fetch the repodata of each subdir once, up to `jobs` at a time, so
+    /// the queries that follow read it from the gateway instead of
+    /// triggering the same fetch per channel batch or environment. A failed
+    /// fetch is only recorded; the queries try again and report the error.
+    /// Queries of a channel count as cache hits unless its repodata was
+    /// downloaded here or by the query itself.
+    pub async fn warm(&mut self, subdirs: &BTreeSet<(String, String)>) -> Vec<RepodataFetch> {
+        let mut fetches: Vec<RepodataFetch> = stream::iter(subdirs)
+            .map(|(channel_url, subdir)| async move {
+                let start = std::time::Instant::now();
+                let downloads = DownloadCounter::default();
+                let result = warm_subdir(
+                    &self.gateway,
+                    channel_url,
+                    subdir,
+                    self.timeout,
+                    downloads.clone(),
+                )
+                .await;
+                if let Err(ref e) = result {
+                    debug!(
+                        channel = channel_url,
+                        subdir, "Failed to warm repodata: {:#}", e
+                    );
+                }
+                RepodataFetch {
+                    channel: channel_url.clone(),
+                    subdir: subdir.clone(),
+                    total_ms: u64::try_from(start.elapsed().as_millis()).unwrap_or(u64::MAX),
+                    shared: false,
+                    failed: result.is_err(),
+                    bytes: downloads.total(),
+                }
+            })
+            .buffer_unordered(self.jobs)
+            .collect()
+            .await;
+        fetches.sort_by(|a, b| (&a.channel, &a.subdir).cmp(&(&b.channel, &b.subdir)));
+        self.downloaded_channels.extend(
+            fetches
+                .iter()
+                .filter(|fetch| fetch.bytes > 0)
+                .map(|fetch| fetch.channel.clone()),
+        );
+        fetches
+    }
+}
+
+/// Whether a channel is queried through the repodata gateway. Local
+/// channels are read by [`LocalChannelSource`] instead. OCI channels go
+/// through the gateway too: the workspace's client pulls their repodata
+/// from the registry.
+///
+/// [`LocalChannelSource`]: crate::local::LocalChannelSource
+pub fn is_gateway_channel(channel_url: &str) -> bool {
+    crate::local::channel_path(channel_url).is_none()
+}
+
+/// Counts the bytes the gateway downloads while it reports to it
+#[derive(Debug, Clone, Default)]
+struct DownloadCounter {
+    /// Bytes received so far, per download the gateway started
+    downloads: Arc<Mutex<Vec<usize>>>,
+}
+
+impl DownloadCounter {
+    fn total(&self) -> u64 {
+        let downloads = self.downloads.lock().unwrap_or_else(|e| e.into_inner());
+        downloads.iter().map(|&bytes| bytes as u64).sum()
+    }
+}
+
+impl Reporter for DownloadCounter {
+    fn on_download_start(&self, _url: &Url) -> usize {
+        let mut downloads = self.downloads.lock().unwrap_or_else(|e| e.into_inner());
+        downloads.push(0);
+        downloads.len() - 1
+    }
+
+    fn on_download_progress(
+        &self,
+        _url: &Url,
+        index: usize,
+        bytes_downloaded: usize,
+        _total_bytes: Option<usize>,
+    ) {
+        let mut downloads = self.downloads.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(bytes) = downloads.get_mut(index) {
+            *bytes = bytes_downloaded;
+        }
+    }
+}
+
+/// Load the repodata of one subdir into the gateway, without querying any
+/// package. The bytes it downloads are reported to `downloads`.
+async fn warm_subdir(
+    gateway: &Gateway,
+    channel_url: &str,
+    subdir: &str,
+    timeout: Option<Duration>,
+    downloads: DownloadCounter,
+) -> Result<()> {
+    let channel_config = ChannelConfig::default_with_root_dir(std::env::current_dir()?);
+    let channel = Channel::from_str(channel_url, &channel_config)
+        .with_context(|| format!("Invalid channel URL: {}", channel_url))?;
+    let platform: Platform = subdir
+        .parse()
+        .with_context(|| format!("Invalid platform: {}", subdir))?;
+
+    let query = gateway
+        .query(vec![channel], vec![platform], Vec::<MatchSpec>::new())
+        .with_reporter(downloads);
+    match timeout {
+        Some(timeout) => tokio::time::timeout(timeout, query)
+            .await
+            .map_err(|_| anyhow::anyhow!("Timed out after {}s", timeout.as_secs()))??,
+        None => query.await?,
+    };
+    Ok(())
+}
+
+/// Split the channels to query into batches of one gateway query each:
+/// channels that share a package name with another channel are queried
+/// together, the rest one by one so they can run concurrently
+fn channel_batches<'a>(
+    by_channel: BTreeMap<&'a str, Vec<&'a PackageKey>>,
+) -> Vec<Vec<(&'a str, Vec<&'a PackageKey>)>> {
+    let mut channels_per_name: HashMap<&str, usize> = HashMap::new();
+    for key in by_channel.values().flatten() {
+        *channels_per_name.entry(key.name.as_str()).or_default() += 1;
+    }
+
+    let (shared, single): (Vec<_>, Vec<_>) = by_channel.into_iter().partition(|(_, keys)| {
+        keys.iter()
+            .any(|key| channels_per_name[key.name.as_str()] > 1)
+    });
+    let mut batches: Vec<_> = single.into_iter().map(|channel| vec![channel]).collect();
+    if !shared.is_empty() {
+        batches.push(shared);
+    }
+    batches
+}
+
+#[async_trait]
+impl VersionSource for CondaSource {
+    /// Query the conda packages of each channel in one batch, across all
+    /// platforms at once and up to `jobs` batches concurrently. Channels
+    /// checked for the same package names share a single batch, which falls
+    /// back to querying them one by one if it fails. Noarch packages are
+    /// batched separately, so the platform subdirs are only fetched for the
+    /// packages that need them. Packages without a known channel are skipped,
+    /// as are those in local channels, see [`is_gateway_channel`].
+    async fn query(
+        &self,
+        keys: &[PackageKey],
+        platforms: &[&str],
+    ) -> Vec<(PackageKey, SourceResponse)> {
+        let mut by_channel: BTreeMap<bool, BTreeMap<&str, Vec<&PackageKey>>> = BTreeMap::new();
+        for key in keys.iter().filter(|key| key.kind == PackageKind::Conda) {
+            if let Some(ref channel_url) = key.channel {
+                if !is_gateway_channel(channel_url) {
+                    continue;
+                }
+                let noarch = self.noarch.contains(key);
+                by_channel
+                    .entry(noarch)
+                    .or_default()
+                    .entry(channel_url)
+                    .or_default()
+                    .push(key);
+            }
+        }
+        let batches = by_channel.into_iter().flat_map(|(noarch, by_channel)| {
+            channel_batches(by_channel)
+                .into_iter()
+                .map(move |batch| (noarch, batch))
+        });
+
+        stream::iter(batches)
+            .map(|(noarch, batch)| async move {
+                let platforms: &[&str] = if noarch { &[] } else { platforms };
+                let requests: Vec<(&str, Vec<&str>)> = batch
+                    .iter()
+                    .map(|(channel_url, keys)| {
+                        (
+                            *channel_url,
+                            keys.iter().map(|key| key.name.as_str()).collect(),
+                        )
+                    })
+                    .collect();
+                let start = std::time::Instant::now();
+                let downloads = DownloadCounter::default();
+                let results = match query_reporting(
+                    &self.gateway,
+                    &requests,
+                    platforms,
+                    self.retry,
+                    self.timeout,
+                    &downloads,
+                )
+                .await
+                {
+                    Ok(infos) => {
+                        let batched = requests.len() > 1;
+                        vec![(Ok(infos), start.elapsed(), downloads, batched, requests)]
+                    }
+                    // One failing channel shouldn't fail the others it was
+                    // batched with
+                    Err(e) if requests.len() > 1 => {
+                        debug!("Batched channel query failed, querying one by one: {:#}", e);
+                        let mut results = Vec::with_capacity(requests.len());
+                        for request in requests {
+                            let start = std::time::Instant::now();
+                            let downloads = DownloadCounter::default();
+                            let result = query_reporting(
+                                &self.gateway,
+                                std::slice::from_ref(&request),
+                                platforms,
+                                self.retry,
+                                self.timeout,
+                                &downloads,
+                            )
+                            .await;
+                            results.push((
+                                result,
+                                start.elapsed(),
+                                downloads,
+                                false,
+                                vec![request],
+                            ));
+                        }
+                        results
+                    }
+                    Err(e) => vec![(Err(e), start.elapsed(), downloads, false, requests)],
+                };
+
+                let mut responses = Vec::new();
+                for (result, elapsed, downloads, batched, requests) in results {
+                    // Spread the time of the batch over its packages, so the
+                    // per-source totals stay accurate
+                    let key_count: usize = requests.iter().map(|(_, names)| names.len()).sum();
+                    let elapsed_per_key = elapsed / key_count.max(1) as u32;
+
+                    let mut infos = result.map_err(|e| format!("{:#}", e));
+                    for (channel_url, _) in requests {
+                        // What the gateway downloaded for a batch counts
+                        // against each of its channels
+                        let outcome = if downloads.total() > 0
+                            || self.downloaded_channels.contains(channel_url)
+                        {
+                            QueryOutcome::Fetched
+                        } else {
+                            QueryOutcome::CacheHit
+                        };
+                        let channel_keys = batch
+                            .iter()
+                            .find(|(url, _)| *url == channel_url)
+                            .map(|(_, keys)| keys.as_slice())
+                            .unwrap_or_default();
+                        for key in channel_keys {
+                            let info = match infos {
+                                Ok(ref mut infos) => Ok(UpstreamInfo::Conda(
+                                    infos
+                                        .get_mut(channel_url)
+                                        .and_then(|infos| infos.remove(&key.name))
+                                        .unwrap_or_default(),
+                                )),
+                                Err(ref message) => Err(anyhow::anyhow!("{}", message)),
+                            };
+                            responses.push((
+                                (*key).clone(),
+                                SourceResponse {
+                                    source_url: channel_url.to_string(),
+                                    elapsed: elapsed_per_key,
+                                    outcome,
+                                    // The gateway doesn't report transfer sizes
+                                    bytes: 0,
+                                    batched,
+                                    info,
+                                },
+                            ));
+                        }
+                    }
+                }
+                responses
+            })
+            .buffer_unordered(self.jobs)
+            .flat_map(stream::iter)
+            .collect()
+            .await
+    }
+
+    /// Fetch the repodata again instead of reading pixi's cache, through
+    /// the refresh gateway
+    fn refreshed(&self) -> Option<Box<dyn VersionSource>> {
+        Some(Box::new(Self {
+            gateway: self.refresh_gateway.clone()?,
+            ..self.clone()
+        }))
+    }
+}
+
+/// A repodata gateway set up from the pixi config the way a workspace's is:
+/// fetching through pixi's authenticated client, so mirrors and credentials
+/// apply, into pixi's repodata cache. For checks outside a workspace.
+pub fn configured_gateway(config: &pixi_config::Config) -> Result<Gateway> {
+    let (_, client) = pixi_utils::reqwest::build_reqwest_clients(Some(config), None)
+        .map_err(|e| anyhow::anyhow!("Failed to build the HTTP client: {}", e))?;
+    let mut builder = Gateway::builder().with_client(client);
+    if let Ok(cache_dir) = pixi_config::get_cache_dir() {
+        builder = builder.with_cache_dir(cache_dir.join("repodata"));
+    }
+    Ok(builder.finish())
+}
+
+/// Gateway settings that fetch every channel's repodata again rather than
+/// reading it from the cache
+pub fn uncached_channel_config() -> rattler_repodata_gateway::ChannelConfig {
+    rattler_repodata_gateway::ChannelConfig {
+        default: rattler_repodata_gateway::SourceConfig {
+            cache_action: CacheAction::NoCache,
+            ..Default::default()
+        },
+        ..Default::default()
+    }
 }
 
 /// Query conda channels for the latest version of a package
@@ -137,6 +1185,37 @@ pub async fn get_latest_conda_version(
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_channel_batches() {
+        let key = |name: &str, channel: &str| PackageKey {
+            name: name.to_string(),
+            channel: Some(channel.to_string()),
+            kind: PackageKind::Conda,
+        };
+        let keys = [
+            key("python", "conda-forge"),
+            key("pytorch", "conda-forge"),
+            key("pytorch", "pytorch"),
+            key("cuda", "nvidia"),
+        ];
+        let mut by_channel: BTreeMap<&str, Vec<&PackageKey>> = BTreeMap::new();
+        for key in &keys {
+            by_channel
+                .entry(key.channel.as_deref().unwrap())
+                .or_default()
+                .push(key);
+        }
+
+        let batches: Vec<Vec<&str>> = channel_batches(by_channel)
+            .into_iter()
+            .map(|batch| batch.into_iter().map(|(channel, _)| channel).collect())
+            .collect();
+        assert_eq!(
+            batches,
+            vec![vec!["nvidia"], vec!["conda-forge", "pytorch"]]
+        );
+    }
+
     #[test]
     fn test_extract_channel_url_valid() {
         let source =
@@ -158,6 +1237,58 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_extract_channel_url_full_path() {
+        assert_eq!(
+            extract_channel_url("https://repo.prefix.dev/org/channel/noarch/pkg-1.0-0.conda")
+                .as_deref(),
+            Some("https://repo.prefix.dev/org/channel")
+        );
+        assert_eq!(
+            extract_channel_url(
+                "https://conda.anaconda.org/conda-forge/label/rc/linux-64/pkg-1.0-0.tar.bz2"
+            )
+            .as_deref(),
+            Some("https://conda.anaconda.org/conda-forge/label/rc")
+        );
+        assert_eq!(
+            extract_channel_url("http://localhost:8080/channel/linux-64/pkg-1.0-0.conda")
+                .as_deref(),
+            Some("http://localhost:8080/channel")
+        );
+    }
+
+    #[test]
+    fn test_lockfile_channel() {
+        let alias = "https://conda.anaconda.org";
+        assert_eq!(
+            lockfile_channel("conda-forge", alias),
+            "https://conda.anaconda.org/conda-forge"
+        );
+        assert_eq!(
+            lockfile_channel("https://repo.example.com:8443/org/channel/", alias),
+            "https://repo.example.com:8443/org/channel"
+        );
+        // Matches the channel of a package locked from it
+        assert_eq!(
+            lockfile_channel("https://repo.example.com:8443/org/channel", alias),
+            extract_channel_url("https://repo.example.com:8443/org/channel/noarch/pkg-1.0-0.conda")
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_without_label() {
+        assert_eq!(
+            without_label("https://conda.anaconda.org/conda-forge/label/rc"),
+            "https://conda.anaconda.org/conda-forge"
+        );
+        assert_eq!(
+            without_label("https://conda.anaconda.org/conda-forge"),
+            "https://conda.anaconda.org/conda-forge"
+        );
+    }
+
     #[test]
     fn test_extract_channel_url_no_path() {
         let source = "https://conda.anaconda.org/";
@@ -166,6 +1297,36 @@ mod tests {
         assert_eq!(channel, Some("https://conda.anaconda.org/".to_string()));
     }
 
+    #[test]
+    fn test_dependency_name() {
+        assert_eq!(
+            dependency_name("python >=3.9,<3.13").as_deref(),
+            Some("python")
+        );
+        assert_eq!(dependency_name("libzlib>=1.3").as_deref(), Some("libzlib"));
+        assert_eq!(
+            dependency_name("__glibc >=2.17").as_deref(),
+            Some("__glibc")
+        );
+        assert_eq!(dependency_name(" "), None);
+    }
+
+    #[test]
+    fn test_dependencies_fall_back_to_noarch() {
+        let info = CondaVersionInfo {
+            depends: HashMap::from([(
+                ("1.0".to_string(), "noarch".to_string()),
+                BTreeSet::from(["python".to_string()]),
+            )]),
+            ..Default::default()
+        };
+        assert_eq!(
+            info.dependencies("1.0", "linux-64"),
+            Some(vec!["python".to_string()])
+        );
+        assert_eq!(info.dependencies("2.0", "linux-64"), None);
+    }
+
     #[test]
     fn test_extract_channel_url_invalid_url() {
         let source = "not-a-valid-url";
@@ -173,11 +1334,295 @@ mod tests {
         assert_eq!(channel, None);
     }
 
+    #[test]
+    fn test_extract_subdir() {
+        assert_eq!(
+            extract_subdir(
+                "https://conda.anaconda.org/conda-forge/osx-arm64/python-3.12.0-h1234567_0.conda"
+            )
+            .as_deref(),
+            Some("osx-arm64")
+        );
+        assert_eq!(
+            extract_subdir(
+                "https://conda.anaconda.org/conda-forge/noarch/six-1.16.0-pyh6c4a22f_0.tar.bz2"
+            )
+            .as_deref(),
+            Some("noarch")
+        );
+        assert_eq!(extract_subdir("not-a-valid-url"), None);
+    }
+
+    #[test]
+    fn test_extract_label() {
+        assert_eq!(
+            extract_label(
+                "https://conda.anaconda.org/conda-forge/label/rc/linux-64/pytorch-2.5.0rc1.conda"
+            )
+            .as_deref(),
+            Some("rc")
+        );
+        assert_eq!(
+            extract_label(
+                "https://conda.anaconda.org/conda-forge/label/main/linux-64/pytorch-2.4.1.conda"
+            ),
+            None
+        );
+        assert_eq!(
+            extract_label("https://conda.anaconda.org/conda-forge/linux-64/pytorch-2.4.1.conda"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_spec_matches() {
+        assert!(!spec_matches(">=3.12.0,<3.13", "3.14.0"));
+        assert!(spec_matches(">=3.12", "3.14.0"));
+        assert!(spec_matches("3.14.*", "3.14.0"));
+        assert!(!spec_matches("3.12.*", "3.14.0"));
+    }
+
+    #[test]
+    fn test_latest_matching() {
+        let info = CondaVersionInfo {
+            latest_version: Some("3.14.0".to_string()),
+            artifacts: HashSet::from([
+                ("3.12.11".to_string(), "h1_0".to_string()),
+                ("3.12.12".to_string(), "h1_0".to_string()),
+                ("3.14.0".to_string(), "h1_0".to_string()),
+            ]),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            info.latest_matching(&[">=3.12.0,<3.13"]).as_deref(),
+            Some("3.12.12")
+        );
+        assert_eq!(info.latest_matching(&[]).as_deref(), Some("3.14.0"));
+        assert_eq!(info.latest_matching(&["<3"]), None);
+    }
+
+    #[test]
+    fn test_latest_allowed() {
+        let info = CondaVersionInfo {
+            latest_version: Some("8.3".to_string()),
+            artifacts: HashSet::from([
+                ("8.1".to_string(), "h1_0".to_string()),
+                ("8.2".to_string(), "h1_0".to_string()),
+                ("8.3".to_string(), "h1_0".to_string()),
+                ("9.0rc1".to_string(), "h1_0".to_string()),
+            ]),
+            licenses: HashMap::from([
+                ("8.1".to_string(), "BSD-3-Clause".to_string()),
+                ("8.3".to_string(), "GPL-3.0-only".to_string()),
+                ("9.0rc1".to_string(), "BSD-3-Clause".to_string()),
+            ]),
+            ..Default::default()
+        };
+
+        assert_eq!(info.license("8.3"), Some("GPL-3.0-only"));
+        // 8.2 doesn't declare a license, so it isn't held back
+        let allowed = |license: &str| license.starts_with("BSD");
+        assert_eq!(info.latest_allowed(&allowed).as_deref(), Some("8.2"));
+        assert_eq!(info.latest_allowed(&|_| false).as_deref(), Some("8.2"));
+    }
+
+    #[test]
+    fn test_is_prerelease() {
+        assert!(is_prerelease("3.15.0a1"));
+        assert!(is_prerelease("2.0.0rc1"));
+        assert!(is_prerelease("2.0.0b2"));
+        assert!(is_prerelease("1.0.dev0"));
+        assert!(is_prerelease("1.0_alpha"));
+        assert!(!is_prerelease("3.14.0"));
+        assert!(!is_prerelease("1.0.post1"));
+        // Letters that mark regular releases
+        assert!(!is_prerelease("1.1.1w"));
+        assert!(!is_prerelease("2024a"));
+    }
+
+    #[test]
+    fn test_with_prereleases() {
+        let info = CondaVersionInfo {
+            latest_version: Some("3.14.0".to_string()),
+            artifacts: HashSet::from([
+                ("3.14.0".to_string(), "h1_0".to_string()),
+                ("3.15.0a1".to_string(), "h1_0".to_string()),
+            ]),
+            ..Default::default()
+        };
+        let decisions: Vec<Decision> = info.candidates().iter().map(|c| c.decision).collect();
+        assert_eq!(
+            decisions,
+            vec![
+                Decision::Excluded(ExclusionReason::Prerelease),
+                Decision::Selected
+            ]
+        );
+        assert_eq!(info.latest_matching(&[]).as_deref(), Some("3.14.0"));
+
+        let info = info.with_prereleases();
+        assert_eq!(info.latest_version.as_deref(), Some("3.15.0a1"));
+    }
+
+    #[test]
+    fn test_merge() {
+        let info = |latest: &str, versions: &[&str]| CondaVersionInfo {
+            latest_version: Some(latest.to_string()),
+            artifacts: versions
+                .iter()
+                .map(|version| (version.to_string(), "h1_0".to_string()))
+                .collect(),
+            ..Default::default()
+        };
+
+        let mut merged = info("1.26.4", &["1.26.4"]);
+        merged.merge(info("2.1.0", &["2.0.0", "2.1.0"]));
+        assert_eq!(merged.latest_version.as_deref(), Some("2.1.0"));
+        assert_eq!(merged.artifacts.len(), 3);
+
+        // A pre-release elsewhere doesn't replace a stable release
+        let mut merged = info("1.26.4", &["1.26.4"]);
+        merged.merge(info("2.0.0rc1", &["2.0.0rc1"]));
+        assert_eq!(merged.latest_version.as_deref(), Some("1.26.4"));
+
+        let mut merged = CondaVersionInfo::default();
+        merged.merge(info("2.1.0", &["2.1.0"]));
+        assert_eq!(merged.latest_version.as_deref(), Some("2.1.0"));
+    }
+
+    #[test]
+    fn test_is_newer() {
+        assert!(is_newer("3.14.0", "3.12.11"));
+        assert!(is_newer("1.1.1w", "1.1.1v"));
+        assert!(!is_newer("1.0", "1.0.0"));
+        assert!(!is_newer("2.0.0", "2.1.0"));
+        assert!(is_newer("not a version", "2.0.0"));
+    }
+
+    #[test]
+    fn test_supersedes() {
+        assert!(supersedes("2.5.0", "2.5.0rc1"));
+        assert!(supersedes("2.5.0", "2.5.0"));
+        assert!(!supersedes("2.4.1", "2.5.0rc1"));
+        assert!(!supersedes("not a version", "2.5.0"));
+    }
+
     #[test]
     fn test_extract_channel_url_file_path() {
         let source = "/local/path/to/package.conda";
         let channel = extract_channel_url(source);
-        assert_eq!(channel, None);
+        assert_eq!(channel.as_deref(), Some("file:///local/path"));
+    }
+
+    #[test]
+    fn test_extract_channel_url_file_url() {
+        assert_eq!(
+            extract_channel_url("file:///opt/channel/linux-64/mylib-1.0-h0_0.tar.bz2").as_deref(),
+            Some("file:///opt/channel")
+        );
+        // A channel URL is the channel itself
+        assert_eq!(
+            extract_channel_url("file:///opt/channel/").as_deref(),
+            Some("file:///opt/channel")
+        );
+        assert_eq!(extract_channel_url("relative/linux-64/mylib.conda"), None);
+    }
+
+    #[test]
+    fn test_extract_channel_url_oci() {
+        assert_eq!(
+            extract_channel_url(
+                "oci://ghcr.io/channel-mirrors/conda-forge/noarch/tzdata-2025b-h78e105d_0.conda"
+            )
+            .as_deref(),
+            Some("oci://ghcr.io/channel-mirrors/conda-forge")
+        );
+        assert_eq!(
+            extract_channel_url("oci://ghcr.io/channel-mirrors/conda-forge").as_deref(),
+            Some("oci://ghcr.io/channel-mirrors/conda-forge")
+        );
+    }
+
+    #[test]
+    fn test_extract_channel_url_round_trip() {
+        // The channel found for a locked package must serve that package
+        // again, registry and server ports included
+        let channel_config = ChannelConfig::default_with_root_dir(std::path::PathBuf::from("/"));
+        for source in [
+            "oci://localhost:5000/channels/mine/linux-64/pkg-1.0-h0_0.conda",
+            "https://conda.example.com:8443/org/channel/noarch/pkg-1.0-0.tar.bz2",
+            "http://localhost:8080/channel/linux-64/pkg-1.0-0.conda",
+        ] {
+            let channel_url = extract_channel_url(source).unwrap();
+            let channel = Channel::from_str(&channel_url, &channel_config).unwrap();
+            let (subdir_path, file) = source.rsplit_once('/').unwrap();
+            let subdir = subdir_path.rsplit('/').next().unwrap();
+            let package_url = channel
+                .base_url
+                .url()
+                .join(&format!("{}/{}", subdir, file))
+                .unwrap();
+            assert_eq!(package_url.as_str(), source, "via {}", channel_url);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_query_conda_channels_splits_by_channel() {
+        // Two local channels, each with `mylib` on a different platform
+        let repodata = |subdir: &str, version: Option<&str>| {
+            let packages = version.map_or(String::new(), |version| {
+                format!(
+                    r#""mylib-{version}-0.conda": {{
+                        "name": "mylib", "version": "{version}", "build": "0",
+                        "build_number": 0, "depends": [], "subdir": "{subdir}"
+                    }}"#
+                )
+            });
+            format!(
+                r#"{{ "info": {{ "subdir": "{subdir}" }}, "packages": {{}},
+                    "packages.conda": {{ {packages} }} }}"#
+            )
+        };
+        let channel = |linux: Option<&str>, osx: Option<&str>| {
+            let dir = tempfile::tempdir().unwrap();
+            for (subdir, version) in [("noarch", None), ("linux-64", linux), ("osx-arm64", osx)] {
+                std::fs::create_dir_all(dir.path().join(subdir)).unwrap();
+                std::fs::write(
+                    dir.path().join(subdir).join("repodata.json"),
+                    repodata(subdir, version),
+                )
+                .unwrap();
+            }
+            dir
+        };
+        let first = channel(Some("1.0"), None);
+        let second = channel(None, Some("2.0"));
+        let first_url = Url::from_directory_path(first.path()).unwrap().to_string();
+        let second_url = Url::from_directory_path(second.path()).unwrap().to_string();
+
+        let gateway = Gateway::builder().finish();
+        let infos = query_conda_channels(
+            &gateway,
+            &[
+                (first_url.as_str(), vec!["mylib"]),
+                (second_url.as_str(), vec!["mylib"]),
+            ],
+            &["linux-64", "osx-arm64"],
+            RetryPolicy::default(),
+            None,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            infos[&first_url]["mylib"].latest_version.as_deref(),
+            Some("1.0")
+        );
+        assert_eq!(
+            infos[&second_url]["mylib"].latest_version.as_deref(),
+            Some("2.0")
+        );
     }
 
     #[tokio::test]
@@ -204,6 +1649,115 @@ mod tests {
         assert!(result.is_ok() || result.is_err());
     }
 
+    #[test]
+    fn test_subdirs() {
+        let key = |name: &str, channel: Option<&str>, kind| PackageKey {
+            name: name.to_string(),
+            channel: channel.map(str::to_string),
+            kind,
+        };
+        let forge = "https://conda.anaconda.org/conda-forge";
+        let bioconda = "https://conda.anaconda.org/bioconda";
+        let oci = "oci://ghcr.io/channel-mirrors/conda-forge";
+        let keys = [
+            key("python", Some(forge), PackageKind::Conda),
+            key("tzdata", Some(forge), PackageKind::Conda),
+            key("samtools-docs", Some(bioconda), PackageKind::Conda),
+            key("cowsay", None, PackageKind::Pypi),
+            // Read without the gateway
+            key("mylib", Some("file:///opt/channel"), PackageKind::Conda),
+            // Pulled from the registry through the gateway's client
+            key("numpy", Some(oci), PackageKind::Conda),
+        ];
+        let source = CondaSource::new(Gateway::builder().finish())
+            .with_noarch_packages([keys[1].clone(), keys[2].clone()]);
+
+        let pair = |channel: &str, subdir: &str| (channel.to_string(), subdir.to_string());
+        assert_eq!(
+            source.subdirs(&keys, &["linux-64", "osx-arm64"]),
+            BTreeSet::from([
+                pair(bioconda, "noarch"),
+                pair(forge, "linux-64"),
+                pair(forge, "noarch"),
+                pair(forge, "osx-arm64"),
+                pair(oci, "linux-64"),
+                pair(oci, "noarch"),
+                pair(oci, "osx-arm64"),
+            ])
+        );
+        // A channel with only noarch packages keeps its platform subdirs unfetched
+        assert_eq!(
+            source.subdirs(&keys[1..5], &["linux-64"]),
+            BTreeSet::from([pair(bioconda, "noarch"), pair(forge, "noarch")])
+        );
+    }
+
+    #[test]
+    fn test_download_counter() {
+        let counter = DownloadCounter::default();
+        let url =
+            Url::parse("https://conda.anaconda.org/conda-forge/linux-64/repodata.json").unwrap();
+        let first = counter.on_download_start(&url);
+        let second = counter.on_download_start(&url);
+        counter.on_download_progress(&url, first, 1000, Some(3000));
+        counter.on_download_progress(&url, first, 3000, Some(3000));
+        counter.on_download_progress(&url, second, 500, None);
+        assert_eq!(counter.total(), 3500);
+    }
+
+    #[test]
+    fn test_locked_status() {
+        let info = CondaVersionInfo {
+            latest_version: Some("3.13.0".to_string()),
+            artifacts: HashSet::from([
+                ("3.12.0".to_string(), "h1234567_0".to_string()),
+                ("3.13.0".to_string(), "h7654321_0".to_string()),
+            ]),
+            release_dates: HashMap::new(),
+            sizes: HashMap::new(),
+            depends: HashMap::new(),
+            files: HashMap::new(),
+            licenses: HashMap::new(),
+        };
+
+        assert_eq!(
+            info.locked_status("3.12.0", Some("h1234567_0")),
+            LockedStatus::Available
+        );
+        assert_eq!(info.locked_status("3.12.0", None), LockedStatus::Available);
+        // Same version, but the locked build was removed
+        assert_eq!(
+            info.locked_status("3.12.0", Some("h0000000_0")),
+            LockedStatus::Removed
+        );
+        assert_eq!(info.locked_status("3.11.0", None), LockedStatus::Removed);
+    }
+
+    #[test]
+    fn test_candidates_sorted_newest_first() {
+        let info = CondaVersionInfo {
+            latest_version: Some("3.10.0".to_string()),
+            artifacts: HashSet::from([
+                ("3.9.0".to_string(), "h1_0".to_string()),
+                ("3.10.0".to_string(), "h1_0".to_string()),
+                ("3.10.0".to_string(), "h2_1".to_string()),
+            ]),
+            release_dates: HashMap::new(),
+            sizes: HashMap::new(),
+            depends: HashMap::new(),
+            files: HashMap::new(),
+            licenses: HashMap::new(),
+        };
+
+        let candidates = info.candidates();
+        assert_eq!(candidates.len(), 2);
+        assert_eq!(candidates[0].version, "3.10.0");
+        assert_eq!(candidates[0].decision, Decision::Selected);
+        assert_eq!(candidates[0].detail.as_deref(), Some("2 builds"));
+        assert_eq!(candidates[1].version, "3.9.0");
+        assert_eq!(candidates[1].decision, Decision::Older);
+    }
+
     #[test]
     fn test_platform_parsing() {
         // Test that platform strings can be parsed correctly
@@ -0,0 +1,264 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use crate::annotations::Annotations;
+use crate::automerge::AutoMergeRules;
+use crate::hooks::Hooks;
+use crate::stats::StatsConfig;
+
+/// Name of the project config file that is looked up next to the manifest
+pub const CONFIG_FILE_NAME: &str = ".pixi-outdated.toml";
+
+/// Settings from a `.pixi-outdated.toml`, or from the `[tool.pixi-outdated]`
+/// table of a `pixi.toml` or `pyproject.toml`
+#[derive(Debug, Default, Clone, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct ToolConfig {
+    /// Commands to run before and after the check
+    #[serde(default)]
+    pub hooks: Hooks,
+    /// Channels locked conda packages may come from; any channel is allowed
+    /// when unset
+    #[serde(default)]
+    pub allowed_channels: Option<Vec<String>>,
+    /// Licenses updates may be published under; versions under any other
+    /// license are skipped when choosing the latest version. Any license is
+    /// allowed when unset.
+    #[serde(default)]
+    pub allowed_licenses: Option<Vec<String>>,
+    /// Rules for `--auto-merge`
+    #[serde(default)]
+    pub auto_merge: AutoMergeRules,
+    /// Metadata attached to the report entries of matching packages
+    #[serde(default)]
+    pub annotations: Annotations,
+    /// An internal endpoint collecting a summary of every run
+    #[serde(default)]
+    pub stats: StatsConfig,
+    /// Packages that are never reported, in the format of the lines of a
+    /// `.pixi-outdated-ignore`
+    #[serde(default)]
+    pub ignore: Vec<String>,
+    /// The newest versions packages may be updated to, as a version
+    /// requirement per package name (e.g. `numpy = "<2"`)
+    #[serde(default)]
+    pub max_versions: BTreeMap<String, String>,
+    /// Options added to every invocation, ahead of those on the command line
+    #[serde(default)]
+    pub default_args: Vec<String>,
+    /// The OSV package `--audit` looks up a conda package's advisories
+    /// under, as `<ecosystem>/<package>` per conda package name (e.g.
+    /// `libxml2 = "OSS-Fuzz/libxml2"`)
+    #[serde(default)]
+    pub osv_packages: BTreeMap<String, String>,
+}
+
+impl ToolConfig {
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        Self::parse(&content).with_context(|| {
+            format!(
+                "Invalid [tool.pixi-outdated] settings in {}",
+                path.display()
+            )
+        })
+    }
+
+    /// Load the settings of the project in `dir`: its `.pixi-outdated.toml`
+    /// if there is one, otherwise the `[tool.pixi-outdated]` table of the
+    /// manifest. Settings in both places are an error rather than merged.
+    pub fn discover(dir: &Path, manifest_path: Option<&Path>) -> Result<Self> {
+        let from_manifest = match manifest_path {
+            Some(path) => Self::load(path)?,
+            None => Self::default(),
+        };
+        let path = dir.join(CONFIG_FILE_NAME);
+        if !path.is_file() {
+            return Ok(from_manifest);
+        }
+        if from_manifest != Self::default() {
+            anyhow::bail!(
+                "pixi-outdated is configured in both {} and [tool.pixi-outdated] of {}; keep the settings in one place",
+                path.display(),
+                manifest_path.unwrap_or(&path).display()
+            );
+        }
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        Self::parse_file(&content)
+            .with_context(|| format!("Invalid settings in {}", path.display()))
+    }
+
+    /// Parse a `.pixi-outdated.toml`, which holds the settings at its top level
+    pub fn parse_file(content: &str) -> Result<Self> {
+        Ok(toml::from_str(content)?)
+    }
+
+    pub fn parse(content: &str) -> Result<Self> {
+        let document: toml::Table = toml::from_str(content)?;
+        match document
+            .get("tool")
+            .and_then(|tool| tool.get("pixi-outdated"))
+        {
+            Some(table) => Ok(table.clone().try_into()?),
+            None => Ok(Self::default()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_hooks() {
+        let config = ToolConfig::parse(
+            r#"
+            [workspace]
+            name = "example"
+
+            [tool.pixi-outdated.hooks]
+            pre = ["echo starting"]
+            post = ["./scripts/file-tickets.sh", "echo done"]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.hooks.pre, vec!["echo starting"]);
+        assert_eq!(config.hooks.post.len(), 2);
+    }
+
+    #[test]
+    fn test_parse_allowed_channels() {
+        let config = ToolConfig::parse(
+            r#"
+            [tool.pixi-outdated]
+            allowed-channels = ["conda-forge", "https://repo.prefix.dev/internal"]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.allowed_channels,
+            Some(vec![
+                "conda-forge".to_string(),
+                "https://repo.prefix.dev/internal".to_string()
+            ])
+        );
+        assert_eq!(config.hooks, Hooks::default());
+    }
+
+    #[test]
+    fn test_parse_allowed_licenses() {
+        let config = ToolConfig::parse(
+            r#"
+            [tool.pixi-outdated]
+            allowed-licenses = ["MIT", "BSD-*"]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.allowed_licenses,
+            Some(vec!["MIT".to_string(), "BSD-*".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_parse_stats() {
+        let config = ToolConfig::parse(
+            r#"
+            [tool.pixi-outdated.stats]
+            endpoint = "https://metrics.example.com/pixi-outdated"
+            project = "backend/api"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.stats.endpoint.as_deref(),
+            Some("https://metrics.example.com/pixi-outdated")
+        );
+        assert_eq!(config.stats.project.as_deref(), Some("backend/api"));
+
+        // Nothing is sent by default
+        assert_eq!(ToolConfig::parse("").unwrap().stats.endpoint, None);
+    }
+
+    #[test]
+    fn test_parse_annotations() {
+        let config = ToolConfig::parse(
+            r#"
+            [tool.pixi-outdated.annotations]
+            "torch*" = { team = "ml", criticality = "high" }
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.annotations.get("pytorch").len(), 0);
+        assert_eq!(config.annotations.get("torchaudio")["team"], "ml");
+
+        let result = ToolConfig::parse("[tool.pixi-outdated.annotations]\ntorch = \"ml\"\n");
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_parse_project_settings() {
+        let config = ToolConfig::parse(
+            r#"
+            [tool.pixi-outdated]
+            ignore = ["libstdcxx*", "numpy >=2"]
+            default-args = ["--explicit", "--platform", "linux-64"]
+
+            [tool.pixi-outdated.max-versions]
+            python = "<3.14"
+
+            [tool.pixi-outdated.osv-packages]
+            libxml2 = "OSS-Fuzz/libxml2"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.ignore, vec!["libstdcxx*", "numpy >=2"]);
+        assert_eq!(config.max_versions["python"], "<3.14");
+        assert_eq!(config.osv_packages["libxml2"], "OSS-Fuzz/libxml2");
+        assert_eq!(config.default_args.len(), 3);
+    }
+
+    #[test]
+    fn test_discover_config_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let manifest = dir.path().join("pixi.toml");
+        fs::write(&manifest, "[workspace]\nname = \"example\"\n").unwrap();
+        assert_eq!(
+            ToolConfig::discover(dir.path(), Some(&manifest)).unwrap(),
+            ToolConfig::default()
+        );
+
+        fs::write(
+            dir.path().join(CONFIG_FILE_NAME),
+            "ignore = [\"python\"]\n\n[max-versions]\nnumpy = \"<2\"\n",
+        )
+        .unwrap();
+        let config = ToolConfig::discover(dir.path(), Some(&manifest)).unwrap();
+        assert_eq!(config.ignore, vec!["python"]);
+        assert_eq!(config.max_versions["numpy"], "<2");
+
+        fs::write(
+            &manifest,
+            "[workspace]\nname = \"example\"\n\n[tool.pixi-outdated]\nignore = [\"numpy\"]\n",
+        )
+        .unwrap();
+        assert!(ToolConfig::discover(dir.path(), Some(&manifest)).is_err());
+    }
+
+    #[test]
+    fn test_parse_without_tool_table() {
+        let config = ToolConfig::parse("[workspace]\nname = \"example\"\n").unwrap();
+        assert_eq!(config, ToolConfig::default());
+    }
+
+    #[test]
+    fn test_parse_rejects_unknown_settings() {
+        let result = ToolConfig::parse("[tool.pixi-outdated]\nhook = []\n");
+        assert!(result.is_err());
+    }
+}
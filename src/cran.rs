@@ -0,0 +1,63 @@
+use rattler_conda_types::Version;
+use std::str::FromStr;
+
+use crate::severity::{self, Severity};
+
+/// Whether a conda package repackages an R package from CRAN, like `r-ggplot2`
+pub fn is_r_package(name: &str) -> bool {
+    name.starts_with("r-")
+}
+
+/// A CRAN version in dotted form. CRAN separates the revision with `-`, which
+/// conda-forge spells `_`, so `1.0-9` and `1.0_9` both become `1.0.9`.
+pub fn normalize_version(version: &str) -> String {
+    version.replace(['-', '_'], ".")
+}
+
+/// Whether `latest` is newer than the installed version of an R package
+pub fn is_newer(latest: &str, installed: &str) -> bool {
+    match (
+        Version::from_str(&normalize_version(latest)),
+        Version::from_str(&normalize_version(installed)),
+    ) {
+        (Ok(latest), Ok(installed)) => latest > installed,
+        _ => latest != installed,
+    }
+}
+
+/// Classify an R package update, counting the CRAN revision as a version
+/// component rather than a build suffix
+pub fn classify(installed: &str, latest: &str) -> Severity {
+    severity::classify(&normalize_version(installed), &normalize_version(latest))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_r_package() {
+        assert!(is_r_package("r-ggplot2"));
+        assert!(is_r_package("r-base"));
+        assert!(!is_r_package("rust"));
+        assert!(!is_r_package("python"));
+    }
+
+    #[test]
+    fn test_is_newer() {
+        assert!(is_newer("1.0_10", "1.0_9"));
+        assert!(is_newer("1.1_0", "1.0_12"));
+        assert!(!is_newer("1.0_9", "1.0_10"));
+        // The CRAN and conda spellings of a version are the same release
+        assert!(!is_newer("1.0-9", "1.0_9"));
+        assert!(!is_newer("3.5.1", "3.5.1"));
+    }
+
+    #[test]
+    fn test_classify() {
+        assert_eq!(classify("1.0_9", "1.0_10"), Severity::Patch);
+        assert_eq!(classify("0.5_1", "0.6_0"), Severity::Minor);
+        assert_eq!(classify("3.5.1", "4.0.0"), Severity::Major);
+        assert_eq!(classify("1.0_9", "1.0-9"), Severity::Other);
+    }
+}
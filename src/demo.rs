@@ -0,0 +1,89 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::Path;
+
+use crate::check::{check_manifest, CheckOptions};
+use crate::report::Report;
+
+/// The example workspace and the fixtures answering its queries, bundled
+/// into the binary so a demo needs neither a project nor a network
+const FILES: &[(&str, &str)] = &[
+    ("pixi.toml", include_str!("../examples/pixi.toml")),
+    ("pixi.lock", include_str!("../examples/pixi.lock")),
+    (
+        "fixtures/conda-forge/linux-64/repodata.json",
+        include_str!("../tests/fixtures/conda-forge/linux-64/repodata.json"),
+    ),
+    (
+        "fixtures/conda-forge/osx-arm64/repodata.json",
+        include_str!("../tests/fixtures/conda-forge/osx-arm64/repodata.json"),
+    ),
+    (
+        "fixtures/pypi/cowsay.json",
+        include_str!("../tests/fixtures/pypi/cowsay.json"),
+    ),
+];
+
+/// Write the example workspace to `dir`, with its fixtures in `dir/fixtures`
+pub fn write_workspace(dir: &Path) -> Result<()> {
+    for (path, content) in FILES {
+        let path = dir.join(path);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        fs::write(&path, content).with_context(|| format!("Failed to write {}", path.display()))?;
+    }
+    Ok(())
+}
+
+/// Check the bundled example workspace against its fixtures, giving the
+/// report a real run would write for it. The workspace is unpacked to a
+/// temporary directory for the check, so the report names its manifest
+/// `pixi.toml`.
+pub async fn demo_report() -> Result<Report> {
+    // A fresh directory only this process can write to, removed on drop
+    let dir = tempfile::tempdir().context("Failed to create a temporary directory")?;
+    write_workspace(dir.path())?;
+    let options = CheckOptions {
+        fixtures: Some(dir.path().join("fixtures")),
+        ..Default::default()
+    };
+    let mut report = check_manifest(&dir.path().join("pixi.toml"), &options).await?;
+    if let Some(ref mut metadata) = report.metadata {
+        metadata.manifest = Some("pixi.toml".to_string());
+    }
+    report.content_hash = Some(report.compute_content_hash()?);
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_workspace() {
+        let dir = tempfile::tempdir().unwrap();
+        write_workspace(dir.path()).unwrap();
+        assert!(dir.path().join("pixi.lock").is_file());
+        assert!(dir
+            .path()
+            .join("fixtures/conda-forge/linux-64/repodata.json")
+            .is_file());
+    }
+
+    #[tokio::test]
+    async fn test_demo_report() {
+        let report = demo_report().await.unwrap();
+        let python = report.platforms["linux-64"]
+            .iter()
+            .find(|update| update.name == "python")
+            .unwrap();
+        assert_eq!(python.installed_version, "3.12.11");
+        assert!(python.direct);
+        assert_eq!(
+            report.metadata.unwrap().manifest.as_deref(),
+            Some("pixi.toml")
+        );
+    }
+}
@@ -0,0 +1,217 @@
+use std::fmt::Write as _;
+
+/// Number of unchanged lines shown around each change, matching `diff -u`
+pub const DEFAULT_CONTEXT: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Equal(usize, usize),
+    Delete(usize),
+    Insert(usize),
+}
+
+impl Op {
+    fn touches_old(self) -> bool {
+        matches!(self, Op::Equal(..) | Op::Delete(_))
+    }
+
+    fn touches_new(self) -> bool {
+        matches!(self, Op::Equal(..) | Op::Insert(_))
+    }
+}
+
+/// Compute the line operations turning `old` into `new` using a longest
+/// common subsequence table. Manifests are small, so the quadratic table is fine.
+fn diff_lines(old: &[&str], new: &[&str]) -> Vec<Op> {
+    let (n, m) = (old.len(), new.len());
+    let mut lcs = vec![vec![0u32; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut ops = Vec::with_capacity(n.max(m));
+    let (mut i, mut j) = (0, 0);
+    while i < n && j < m {
+        if old[i] == new[j] {
+            ops.push(Op::Equal(i, j));
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            ops.push(Op::Delete(i));
+            i += 1;
+        } else {
+            ops.push(Op::Insert(j));
+            j += 1;
+        }
+    }
+    ops.extend((i..n).map(Op::Delete));
+    ops.extend((j..m).map(Op::Insert));
+    ops
+}
+
+/// Render a unified diff between two texts, suitable for `git apply`.
+///
+/// Returns an empty string when the texts are identical.
+pub fn unified_diff(
+    old: &str,
+    new: &str,
+    old_label: &str,
+    new_label: &str,
+    context: usize,
+) -> String {
+    // Lines keep their newline, so a missing one at the end of a file
+    // counts as a change
+    let old_lines: Vec<&str> = old.split_inclusive('\n').collect();
+    let new_lines: Vec<&str> = new.split_inclusive('\n').collect();
+    let ops = diff_lines(&old_lines, &new_lines);
+
+    let changes: Vec<usize> = ops
+        .iter()
+        .enumerate()
+        .filter(|(_, op)| !matches!(op, Op::Equal(..)))
+        .map(|(idx, _)| idx)
+        .collect();
+
+    if changes.is_empty() {
+        return String::new();
+    }
+
+    // Group changes into hunks, merging those whose context would overlap
+    let mut hunks: Vec<(usize, usize)> = Vec::new();
+    for &idx in &changes {
+        let start = idx.saturating_sub(context);
+        let end = (idx + context + 1).min(ops.len());
+        match hunks.last_mut() {
+            Some(last) if start <= last.1 => last.1 = end,
+            _ => hunks.push((start, end)),
+        }
+    }
+
+    let mut out = String::new();
+    let _ = writeln!(out, "--- {}", old_label);
+    let _ = writeln!(out, "+++ {}", new_label);
+
+    for (start, end) in hunks {
+        let old_before = ops[..start].iter().filter(|op| op.touches_old()).count();
+        let new_before = ops[..start].iter().filter(|op| op.touches_new()).count();
+        let old_len = ops[start..end].iter().filter(|op| op.touches_old()).count();
+        let new_len = ops[start..end].iter().filter(|op| op.touches_new()).count();
+
+        // Empty ranges point at the line before the hunk, as in GNU diff
+        let old_start = if old_len == 0 {
+            old_before
+        } else {
+            old_before + 1
+        };
+        let new_start = if new_len == 0 {
+            new_before
+        } else {
+            new_before + 1
+        };
+
+        let _ = writeln!(
+            out,
+            "@@ -{},{} +{},{} @@",
+            old_start, old_len, new_start, new_len
+        );
+
+        for op in &ops[start..end] {
+            match *op {
+                Op::Equal(i, _) => write_line(&mut out, ' ', old_lines[i]),
+                Op::Delete(i) => write_line(&mut out, '-', old_lines[i]),
+                Op::Insert(j) => write_line(&mut out, '+', new_lines[j]),
+            }
+        }
+    }
+
+    out
+}
+
+/// Write a line of a hunk, marking a last line without a newline the way
+/// `diff -u` does
+fn write_line(out: &mut String, prefix: char, line: &str) {
+    let _ = writeln!(out, "{}{}", prefix, line.strip_suffix('\n').unwrap_or(line));
+    if !line.ends_with('\n') {
+        out.push_str("\\ No newline at end of file\n");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_identical_texts() {
+        let text = "a\nb\nc\n";
+        assert_eq!(unified_diff(text, text, "a", "b", DEFAULT_CONTEXT), "");
+    }
+
+    #[test]
+    fn test_single_change() {
+        let old = "[dependencies]\npython = \">=3.12,<3.13\"\nnumpy = \"*\"\n";
+        let new = "[dependencies]\npython = \">=3.14,<3.15\"\nnumpy = \"*\"\n";
+        let diff = unified_diff(old, new, "a/pixi.toml", "b/pixi.toml", DEFAULT_CONTEXT);
+
+        assert_eq!(
+            diff,
+            "--- a/pixi.toml\n\
+             +++ b/pixi.toml\n\
+             @@ -1,3 +1,3 @@\n \
+             [dependencies]\n\
+             -python = \">=3.12,<3.13\"\n\
+             +python = \">=3.14,<3.15\"\n \
+             numpy = \"*\"\n"
+        );
+    }
+
+    #[test]
+    fn test_insertion_into_empty_file() {
+        let diff = unified_diff("", "a\n", "old", "new", DEFAULT_CONTEXT);
+        assert!(diff.contains("@@ -0,0 +1,1 @@"));
+        assert!(diff.contains("+a"));
+    }
+
+    #[test]
+    fn test_missing_newline_at_end_of_file() {
+        let diff = unified_diff("a\nb", "a\nc", "old", "new", DEFAULT_CONTEXT);
+        assert_eq!(
+            diff,
+            "--- old\n+++ new\n@@ -1,2 +1,2 @@\n a\n-b\n\\ No newline at end of file\n+c\n\\ No newline at end of file\n"
+        );
+
+        // Adding the newline is a change of its own
+        let diff = unified_diff("a\nb", "a\nb\n", "old", "new", DEFAULT_CONTEXT);
+        assert_eq!(
+            diff,
+            "--- old\n+++ new\n@@ -1,2 +1,2 @@\n a\n-b\n\\ No newline at end of file\n+b\n"
+        );
+    }
+
+    #[test]
+    fn test_distant_changes_produce_separate_hunks() {
+        let old: String = (1..=20).map(|i| format!("line{}\n", i)).collect();
+        let new = old
+            .replace("line2\n", "LINE2\n")
+            .replace("line19\n", "LINE19\n");
+        let diff = unified_diff(&old, &new, "old", "new", DEFAULT_CONTEXT);
+
+        assert_eq!(diff.matches("@@ -").count(), 2);
+        assert!(diff.contains("@@ -1,5 +1,5 @@"));
+        assert!(diff.contains("@@ -16,5 +16,5 @@"));
+    }
+
+    #[test]
+    fn test_nearby_changes_share_a_hunk() {
+        let old = "a\nb\nc\nd\ne\n";
+        let new = "A\nb\nc\nd\nE\n";
+        let diff = unified_diff(old, new, "old", "new", DEFAULT_CONTEXT);
+        assert_eq!(diff.matches("@@ -").count(), 1);
+        assert!(diff.contains("@@ -1,5 +1,5 @@"));
+    }
+}
@@ -0,0 +1,277 @@
+use serde::{Deserialize, Serialize};
+
+use crate::parser::{DependencySpecs, PixiLock};
+use crate::pixi::PackageKind;
+use crate::report::Report;
+
+/// A requirement that rules out the latest version of a package
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct Blocker {
+    /// The locked package with the requirement, e.g. `python 3.12.11`, or
+    /// `None` for the manifest
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub required_by: Option<String>,
+    /// The requirement, e.g. `python_abi 3.12.* *_cp312`
+    pub requirement: String,
+}
+
+impl std::fmt::Display for Blocker {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.required_by {
+            Some(ref package) => write!(f, "{} requires {}", package, self.requirement),
+            None => write!(f, "the manifest requires {}", self.requirement),
+        }
+    }
+}
+
+/// Why a package isn't at its latest version on a platform, for `--explain`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct Explanation {
+    pub name: String,
+    pub platform: String,
+    pub installed_version: String,
+    pub latest_version: String,
+    pub blockers: Vec<Blocker>,
+    /// Why a trial solve with the latest version failed, with `--verify-solvable`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unsolvable: Option<String>,
+}
+
+impl std::fmt::Display for Explanation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{} [{}]: {} -> {}",
+            self.name, self.platform, self.installed_version, self.latest_version
+        )?;
+        for blocker in &self.blockers {
+            writeln!(f, "  {}", blocker)?;
+        }
+        if let Some(ref reason) = self.unsolvable {
+            writeln!(f, "  the solver failed: {}", reason)?;
+        }
+        if self.blockers.is_empty() && self.unsolvable.is_none() {
+            writeln!(
+                f,
+                "  no locked package or manifest requirement rules it out; `pixi update {}` should pick it up",
+                self.name
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Name the requirements keeping a package below its latest version on each
+/// platform it's outdated on: those of the manifest, then those of the
+/// other locked packages, conda `constrains` included
+pub fn explain(
+    report: &Report,
+    name: &str,
+    lock: &PixiLock,
+    environment: &str,
+    specs: &DependencySpecs,
+) -> Vec<Explanation> {
+    let mut explanations = Vec::new();
+    for (platform, updates) in &report.platforms {
+        for update in updates.iter().filter(|update| update.name == name) {
+            let kind = update.kind.unwrap_or(PackageKind::Conda);
+            let admits = |constraint: &str| match kind {
+                PackageKind::Conda => {
+                    crate::conda::spec_matches(constraint, &update.latest_version)
+                }
+                PackageKind::Pypi => {
+                    crate::pypi::specifiers_match(constraint, &update.latest_version)
+                }
+            };
+
+            let mut blockers: Vec<Blocker> = specs
+                .get(name, kind, platform)
+                .into_iter()
+                .filter(|spec| !admits(spec))
+                .map(|spec| Blocker {
+                    required_by: None,
+                    requirement: format!("{} {}", name, spec),
+                })
+                .collect();
+            blockers.extend(
+                lock.requirements_on(environment, platform, name, kind)
+                    .into_iter()
+                    .filter(|requirement| !admits(&requirement.constraint))
+                    .map(|requirement| Blocker {
+                        required_by: Some(format!(
+                            "{} {}",
+                            requirement.package, requirement.version
+                        )),
+                        requirement: requirement.requirement,
+                    }),
+            );
+
+            explanations.push(Explanation {
+                name: update.name.clone(),
+                platform: platform.clone(),
+                installed_version: update.installed_version.clone(),
+                latest_version: update.latest_version.clone(),
+                blockers,
+                unsolvable: update.unsolvable.clone(),
+            });
+        }
+    }
+    explanations
+}
+
+/// Why `--explain` has nothing to explain for a package that isn't outdated:
+/// it isn't locked at all (e.g. a misspelled name), was left out of the
+/// check by `--exclude` or an ignore rule, couldn't be checked, or is up to
+/// date. Only known for reports of this run, since the locked packages
+/// aren't serialized.
+pub fn nothing_to_explain(report: &Report, name: &str) -> String {
+    let locked: Vec<(&String, &crate::pixi::PixiPackage)> = report
+        .locked
+        .iter()
+        .flat_map(|(platform, packages)| {
+            packages
+                .iter()
+                .filter(|package| package.name == name)
+                .map(move |package| (platform, package))
+        })
+        .collect();
+    if locked.is_empty() {
+        return format!("{} is not locked in this environment", name);
+    }
+    if !locked
+        .iter()
+        .any(|(platform, package)| report.was_checked(platform, package))
+    {
+        return format!("{} is excluded or ignored, so it wasn't checked", name);
+    }
+    match report.unchecked.iter().find(|package| package.name == name) {
+        Some(package) => format!("{} couldn't be checked: {}", name, package.status),
+        None => format!("{} is up to date", name),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::{PackageUpdate, PlatformUpdates};
+
+    const LOCK: &str = r#"
+version: 6
+environments:
+  default:
+    packages:
+      linux-64:
+      - conda: https://conda.anaconda.org/conda-forge/linux-64/python-3.12.11-h9e4cc4f_0_cpython.conda
+      - conda: https://conda.anaconda.org/conda-forge/noarch/python_abi-3.12-8_cp312.conda
+      - conda: https://conda.anaconda.org/conda-forge/noarch/tzdata-2025b-h78e105d_0.conda
+packages:
+- conda: https://conda.anaconda.org/conda-forge/linux-64/python-3.12.11-h9e4cc4f_0_cpython.conda
+  depends:
+  - tzdata
+  constrains:
+  - python_abi 3.12.* *_cp312
+- conda: https://conda.anaconda.org/conda-forge/noarch/python_abi-3.12-8_cp312.conda
+  constrains:
+  - python 3.12.* *_cpython
+- conda: https://conda.anaconda.org/conda-forge/noarch/tzdata-2025b-h78e105d_0.conda
+"#;
+
+    fn report(name: &str, installed: &str, latest: &str) -> Report {
+        let mut platforms = PlatformUpdates::new();
+        platforms.insert(
+            "linux-64".to_string(),
+            vec![PackageUpdate {
+                name: name.to_string(),
+                kind: Some(PackageKind::Conda),
+                installed_version: installed.to_string(),
+                latest_version: latest.to_string(),
+                ..Default::default()
+            }],
+        );
+        Report {
+            platforms,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_explain() {
+        let lock = PixiLock::parse(LOCK).unwrap();
+        let specs = DependencySpecs::parse(
+            "[dependencies]\npython = \">=3.12.0,<3.13\"\ntzdata = \"*\"\n",
+            false,
+            &crate::parser::EnvironmentFeatures::All,
+        )
+        .unwrap();
+
+        let explanations = explain(
+            &report("python", "3.12.11", "3.14.0"),
+            "python",
+            &lock,
+            "default",
+            &specs,
+        );
+        assert_eq!(explanations.len(), 1);
+        assert_eq!(
+            explanations[0].to_string(),
+            "python [linux-64]: 3.12.11 -> 3.14.0\n  the manifest requires python >=3.12.0,<3.13\n  python_abi 3.12 requires python 3.12.* *_cpython\n"
+        );
+
+        // Nothing rules out the latest tzdata
+        let explanations = explain(
+            &report("tzdata", "2025b", "2025c"),
+            "tzdata",
+            &lock,
+            "default",
+            &specs,
+        );
+        assert!(explanations[0].blockers.is_empty());
+        assert!(explanations[0].to_string().contains("`pixi update tzdata`"));
+    }
+
+    #[test]
+    fn test_nothing_to_explain() {
+        let package = |name: &str| crate::pixi::PixiPackage {
+            name: name.to_string(),
+            version: "1.0".to_string(),
+            build: None,
+            size_bytes: None,
+            kind: PackageKind::Conda,
+            source: None,
+            is_explicit: true,
+            depends: Vec::new(),
+        };
+        let mut report = Report::default();
+        report.locked.insert(
+            "linux-64".to_string(),
+            vec![package("numpy"), package("pandas"), package("mylib")],
+        );
+        for name in ["numpy", "mylib"] {
+            report
+                .checked
+                .insert(("linux-64".to_string(), PackageKind::Conda, name.to_string()));
+        }
+        report.unchecked.push(crate::report::UncheckedPackage {
+            name: "mylib".to_string(),
+            kind: PackageKind::Conda,
+            version: "1.0".to_string(),
+            platform: "linux-64".to_string(),
+            status: crate::report::CheckStatus::NoSource,
+            annotations: Default::default(),
+        });
+
+        assert_eq!(
+            nothing_to_explain(&report, "nunpy"),
+            "nunpy is not locked in this environment"
+        );
+        assert_eq!(
+            nothing_to_explain(&report, "pandas"),
+            "pandas is excluded or ignored, so it wasn't checked"
+        );
+        assert_eq!(
+            nothing_to_explain(&report, "mylib"),
+            "mylib couldn't be checked: no channel or index locked"
+        );
+        assert_eq!(nothing_to_explain(&report, "numpy"), "numpy is up to date");
+    }
+}
@@ -0,0 +1,186 @@
+//! A C API for embedding the checker, enabled with the `ffi` feature.
+//!
+//! Every function returns a JSON document as a NUL-terminated string owned by
+//! the library: the report, as written by `--json`, or `{"error": "..."}`.
+//! Release it with [`pixi_outdated_free_string`]. The declarations are in
+//! `include/pixi_outdated.h`.
+
+use anyhow::{Context, Result};
+use std::ffi::{c_char, CStr, CString};
+use std::path::Path;
+
+use crate::check::{self, CheckOptions};
+use crate::report::Report;
+
+/// Version of the C API, bumped when a function's signature or the meaning
+/// of its result changes
+pub const API_VERSION: u32 = 1;
+
+/// The version of the C API the library implements
+#[no_mangle]
+pub extern "C" fn pixi_outdated_api_version() -> u32 {
+    API_VERSION
+}
+
+/// Check the `pixi.lock` next to a `pixi.toml` or `pyproject.toml`.
+///
+/// `options_json` may be null, or a JSON object with any of `environment`,
+/// `platforms`, `explicit` and `fixtures`.
+///
+/// # Safety
+///
+/// `manifest_path` must be a valid NUL-terminated string and `options_json`
+/// null or a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn pixi_outdated_check_manifest(
+    manifest_path: *const c_char,
+    options_json: *const c_char,
+) -> *mut c_char {
+    respond(|| {
+        let path = path_arg(manifest_path, "manifest_path")?;
+        let options = options_arg(options_json)?;
+        check::block_on(check::check_manifest(Path::new(&path), &options))
+    })
+}
+
+/// Check a `pixi.lock` without its manifest, so no update counts as direct.
+///
+/// Takes the same options as [`pixi_outdated_check_manifest`].
+///
+/// # Safety
+///
+/// `lock_path` must be a valid NUL-terminated string and `options_json` null
+/// or a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn pixi_outdated_check_lockfile(
+    lock_path: *const c_char,
+    options_json: *const c_char,
+) -> *mut c_char {
+    respond(|| {
+        let path = path_arg(lock_path, "lock_path")?;
+        let options = options_arg(options_json)?;
+        check::block_on(check::check_lockfile(Path::new(&path), &options))
+    })
+}
+
+/// Release a string returned by this library. Null is ignored.
+///
+/// # Safety
+///
+/// `value` must be null or a string returned by this library that hasn't
+/// been released yet.
+#[no_mangle]
+pub unsafe extern "C" fn pixi_outdated_free_string(value: *mut c_char) {
+    if !value.is_null() {
+        drop(CString::from_raw(value));
+    }
+}
+
+/// Run a check, turning its report or error into a JSON string for the
+/// caller. Panics are caught, since unwinding into C is undefined behavior.
+fn respond(check: impl FnOnce() -> Result<Report> + std::panic::UnwindSafe) -> *mut c_char {
+    let json = match std::panic::catch_unwind(check) {
+        Ok(Ok(report)) => crate::report::render_json(&report),
+        Ok(Err(e)) => Ok(error_json(&format!("{:#}", e))),
+        Err(_) => Ok(error_json("The check panicked")),
+    }
+    .unwrap_or_else(|e| error_json(&format!("{:#}", e)));
+    // JSON escapes control characters, so there's no NUL to reject
+    CString::new(json).unwrap_or_default().into_raw()
+}
+
+fn error_json(message: &str) -> String {
+    serde_json::json!({ "error": message }).to_string()
+}
+
+/// # Safety
+///
+/// `value` must be null or a valid NUL-terminated string.
+unsafe fn path_arg(value: *const c_char, name: &str) -> Result<String> {
+    if value.is_null() {
+        anyhow::bail!("{} is null", name);
+    }
+    Ok(CStr::from_ptr(value)
+        .to_str()
+        .with_context(|| format!("{} is not valid UTF-8", name))?
+        .to_string())
+}
+
+/// # Safety
+///
+/// `value` must be null or a valid NUL-terminated string.
+unsafe fn options_arg(value: *const c_char) -> Result<CheckOptions> {
+    if value.is_null() {
+        return Ok(CheckOptions::default());
+    }
+    let json = CStr::from_ptr(value)
+        .to_str()
+        .context("options_json is not valid UTF-8")?;
+    serde_json::from_str(json).context("Invalid options_json")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Call a function of the C API and parse its JSON result
+    fn call(
+        function: unsafe extern "C" fn(*const c_char, *const c_char) -> *mut c_char,
+        path: &str,
+        options: Option<&str>,
+    ) -> serde_json::Value {
+        let path = CString::new(path).unwrap();
+        let options = options.map(|options| CString::new(options).unwrap());
+        unsafe {
+            let result = function(
+                path.as_ptr(),
+                options.as_ref().map_or(std::ptr::null(), |o| o.as_ptr()),
+            );
+            let json = CStr::from_ptr(result).to_str().unwrap().to_string();
+            pixi_outdated_free_string(result);
+            serde_json::from_str(&json).unwrap()
+        }
+    }
+
+    #[test]
+    fn test_check_manifest() {
+        let manifest = concat!(env!("CARGO_MANIFEST_DIR"), "/examples/pixi.toml");
+        let options = serde_json::json!({
+            "platforms": ["linux-64"],
+            "fixtures": concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures"),
+        })
+        .to_string();
+
+        let report = call(pixi_outdated_check_manifest, manifest, Some(&options));
+        assert_eq!(report["status"], "ok");
+        assert!(report["platforms"]["linux-64"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|update| update["name"] == "python"));
+    }
+
+    #[test]
+    fn test_errors_are_json() {
+        let report = call(pixi_outdated_check_lockfile, "/nonexistent/pixi.lock", None);
+        assert!(report["error"].as_str().is_some());
+
+        let report = call(
+            pixi_outdated_check_lockfile,
+            "/nonexistent/pixi.lock",
+            Some("{\"unknown\": true}"),
+        );
+        assert!(report["error"]
+            .as_str()
+            .unwrap()
+            .starts_with("Invalid options_json"));
+
+        unsafe {
+            let result = pixi_outdated_check_manifest(std::ptr::null(), std::ptr::null());
+            let json = CStr::from_ptr(result).to_str().unwrap().to_string();
+            pixi_outdated_free_string(result);
+            assert!(json.contains("manifest_path is null"));
+        }
+        assert_eq!(pixi_outdated_api_version(), API_VERSION);
+    }
+}
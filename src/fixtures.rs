@@ -0,0 +1,190 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Instant;
+
+use crate::output::channel_label;
+use crate::pixi::PackageKind;
+use crate::pypi::PyPiVersionInfo;
+use crate::sources::QueryOutcome;
+use crate::upstream::{PackageKey, PackageNotFound, SourceResponse, UpstreamInfo, VersionSource};
+
+/// Answers queries from files on disk instead of the network, for hermetic
+/// tests and offline demos.
+///
+/// The directory mirrors the layout of the real sources:
+///
+/// ```text
+/// <dir>/<channel>/<subdir>/repodata.json   e.g. conda-forge/linux-64/repodata.json
+/// <dir>/<channel>/channeldata.json         read for `--release-notes`
+/// <dir>/pypi/<package>.json                PyPI JSON API responses
+/// ```
+#[derive(Debug, Clone)]
+pub struct FixtureSource {
+    dir: PathBuf,
+}
+
+impl FixtureSource {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// The URL reported for PyPI fixtures in the source statistics
+    fn pypi_url(&self) -> String {
+        format!("fixtures:{}", self.dir.join("pypi").display())
+    }
+
+    fn query_conda(&self, name: &str, channel: &str, platforms: &[&str]) -> Result<UpstreamInfo> {
+        let channel_dir = self.dir.join(channel_label(channel));
+        if !channel_dir.is_dir() {
+            anyhow::bail!("No fixtures for channel {}", channel);
+        }
+
+        // Package files are reported at the URLs the channel would serve them from
+        crate::local::read_channel(&channel_dir, channel, name, platforms)
+    }
+
+    fn query_pypi(&self, name: &str) -> Result<UpstreamInfo> {
+        let path = self.dir.join("pypi").join(format!("{}.json", name));
+        if !path.is_file() {
+            return Err(PackageNotFound {
+                name: name.to_string(),
+                location: self.pypi_url(),
+            }
+            .into());
+        }
+        let body = fs::read(&path)
+            .with_context(|| format!("Failed to read PyPI fixture {}", path.display()))?;
+        let info = PyPiVersionInfo::from_json(&body)
+            .with_context(|| format!("Failed to parse PyPI fixture {}", path.display()))?;
+        Ok(UpstreamInfo::Pypi(info))
+    }
+}
+
+#[async_trait]
+impl VersionSource for FixtureSource {
+    async fn query(
+        &self,
+        keys: &[PackageKey],
+        platforms: &[&str],
+    ) -> Vec<(PackageKey, SourceResponse)> {
+        let mut responses = Vec::new();
+
+        for key in keys {
+            let start = Instant::now();
+            let (source_url, info) = match (key.kind, key.channel.as_deref()) {
+                (PackageKind::Conda, Some(channel)) => (
+                    channel.to_string(),
+                    self.query_conda(&key.name, channel, platforms),
+                ),
+                (PackageKind::Conda, None) => continue,
+                (PackageKind::Pypi, _) => (self.pypi_url(), self.query_pypi(&key.name)),
+            };
+
+            responses.push((
+                key.clone(),
+                SourceResponse {
+                    source_url,
+                    elapsed: start.elapsed(),
+                    outcome: QueryOutcome::CacheHit,
+                    bytes: 0,
+                    batched: false,
+                    info,
+                },
+            ));
+        }
+
+        responses
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REPODATA: &str = r#"{
+        "info": { "subdir": "linux-64" },
+        "packages": {},
+        "packages.conda": {
+            "python-3.12.11-h9e4cc4f_0_cpython.conda": {
+                "name": "python", "version": "3.12.11", "build": "h9e4cc4f_0_cpython",
+                "build_number": 0, "depends": [], "subdir": "linux-64", "size": 31000000
+            },
+            "python-3.14.0-h1234567_0_cp314.conda": {
+                "name": "python", "version": "3.14.0", "build": "h1234567_0_cp314",
+                "build_number": 0, "depends": [], "subdir": "linux-64", "size": 36000000
+            },
+            "openssl-3.5.4-h26f9b46_0.conda": {
+                "name": "openssl", "version": "3.5.4", "build": "h26f9b46_0",
+                "build_number": 0, "depends": [], "subdir": "linux-64"
+            }
+        }
+    }"#;
+
+    fn fixture_dir() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        let subdir = dir.path().join("conda-forge").join("linux-64");
+        fs::create_dir_all(&subdir).unwrap();
+        fs::write(subdir.join("repodata.json"), REPODATA).unwrap();
+
+        let pypi = dir.path().join("pypi");
+        fs::create_dir_all(&pypi).unwrap();
+        fs::write(
+            pypi.join("cowsay.json"),
+            r#"{"info": {"version": "6.1"}, "releases": {"5.0": [], "6.1": []}}"#,
+        )
+        .unwrap();
+        dir
+    }
+
+    fn key(name: &str, channel: Option<&str>, kind: PackageKind) -> PackageKey {
+        PackageKey {
+            name: name.to_string(),
+            channel: channel.map(str::to_string),
+            kind,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fixture_source() {
+        let dir = fixture_dir();
+        let source = FixtureSource::new(dir.path());
+        let keys = [
+            key(
+                "python",
+                Some("https://conda.anaconda.org/conda-forge"),
+                PackageKind::Conda,
+            ),
+            key("cowsay", None, PackageKind::Pypi),
+            key("six", None, PackageKind::Pypi),
+        ];
+
+        let responses = source.query(&keys, &["linux-64"]).await;
+        assert_eq!(responses.len(), 3);
+
+        let python = responses[0].1.info.as_ref().unwrap();
+        assert_eq!(python.latest_version(), Some("3.14.0"));
+        assert_eq!(python.artifact_size("3.12.11", "linux-64"), Some(31000000));
+
+        let cowsay = responses[1].1.info.as_ref().unwrap();
+        assert_eq!(cowsay.latest_version(), Some("6.1"));
+
+        // Missing fixtures fail like an unknown package would
+        let error = responses[2].1.info.as_ref().unwrap_err();
+        assert!(error.downcast_ref::<PackageNotFound>().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_fixture_source_unknown_channel() {
+        let dir = fixture_dir();
+        let source = FixtureSource::new(dir.path());
+        let keys = [key("pytorch", Some("pytorch"), PackageKind::Conda)];
+
+        let responses = source.query(&keys, &["linux-64"]).await;
+        let error = responses[0].1.info.as_ref().unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("No fixtures for channel pytorch"));
+    }
+}
@@ -0,0 +1,280 @@
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+use crate::report::Report;
+
+/// Render the report as GitHub Actions workflow commands, which show each
+/// outdated package as a warning on the manifest in the PR diff. Updates
+/// that are the same on several platforms are annotated once.
+pub fn render_github(report: &Report) -> String {
+    let manifest = report
+        .metadata
+        .as_ref()
+        .and_then(|metadata| metadata.manifest.as_deref())
+        .map(Path::new);
+    let file = manifest.map_or_else(|| "pixi.toml".to_string(), workspace_relative);
+    let content = manifest.and_then(|path| std::fs::read_to_string(path).ok());
+    render_annotations(report, &file, content.as_deref())
+}
+
+/// The annotations of a report on `file`, pointing at the line declaring
+/// each package when the manifest's `content` is known
+pub fn render_annotations(report: &Report, file: &str, content: Option<&str>) -> String {
+    let mut updates: BTreeMap<(String, &str, &str), Vec<&str>> = BTreeMap::new();
+    for (platform, platform_updates) in &report.platforms {
+        for update in platform_updates {
+            updates
+                .entry((
+                    update.name.clone(),
+                    update.installed_version.as_str(),
+                    update.latest_version.as_str(),
+                ))
+                .or_default()
+                .push(platform);
+        }
+    }
+
+    let mut out = String::new();
+    for ((name, installed, latest), platforms) in updates {
+        let _ = writeln!(
+            out,
+            "::warning {}::{}",
+            properties(
+                file,
+                content.and_then(|content| declaration_line(content, &name)),
+                &format!("Outdated on {}", platforms.join(", "))
+            ),
+            escape_data(&format!("{} {} -> {}", name, installed, latest))
+        );
+    }
+    for issue in &report.locked_issues {
+        let _ = writeln!(
+            out,
+            "::error {}::{}",
+            properties(
+                file,
+                content.and_then(|content| declaration_line(content, &issue.name)),
+                &format!("Locked version {} on {}", issue.status, issue.platform)
+            ),
+            escape_data(&format!(
+                "{} {} is {}",
+                issue.name, issue.version, issue.status
+            ))
+        );
+    }
+    out
+}
+
+/// The `file`, `line` and `title` properties of an annotation
+fn properties(file: &str, line: Option<usize>, title: &str) -> String {
+    let mut properties = format!("file={}", escape_property(file));
+    if let Some(line) = line {
+        let _ = write!(properties, ",line={}", line);
+    }
+    let _ = write!(properties, ",title={}", escape_property(title));
+    properties
+}
+
+/// The 1-based line declaring `name` as a dependency, either as a key of a
+/// dependency table (`numpy = ">=1.26"`) or as a quoted requirement
+/// (`"numpy>=1.26"`) of `[project]` dependencies, optional dependencies or
+/// dependency groups, as in pixi.toml and pyproject.toml. Lines in other
+/// tables, like a `name = ...` key, are never matched.
+fn declaration_line(content: &str, name: &str) -> Option<usize> {
+    let mut table = DeclaringTable::None;
+    let mut in_project_dependencies = false;
+    content
+        .lines()
+        .position(|line| {
+            let line = line.trim_start();
+            if line.starts_with('[') {
+                table = DeclaringTable::of(line);
+                in_project_dependencies = false;
+                return false;
+            }
+            match table {
+                DeclaringTable::Keys => {
+                    let key = line.strip_prefix('"').unwrap_or(line);
+                    key.strip_prefix(name).is_some_and(|rest| {
+                        rest.strip_prefix('"')
+                            .unwrap_or(rest)
+                            .trim_start()
+                            .starts_with('=')
+                    })
+                }
+                DeclaringTable::Requirements => quotes_requirement(line, name),
+                DeclaringTable::Project => {
+                    if let Some(rest) = line.strip_prefix("dependencies") {
+                        in_project_dependencies = rest.trim_start().starts_with('=');
+                    }
+                    let found = in_project_dependencies && quotes_requirement(line, name);
+                    if line.contains(']') {
+                        in_project_dependencies = false;
+                    }
+                    found
+                }
+                DeclaringTable::None => false,
+            }
+        })
+        .map(|index| index + 1)
+}
+
+/// How a manifest table declares dependencies, if it does
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DeclaringTable {
+    /// Dependencies are keys, as in pixi's `[dependencies]`,
+    /// `[feature.test.pypi-dependencies]` or `[tool.pixi.dependencies]`
+    Keys,
+    /// Every array holds requirements, as in `[dependency-groups]`
+    Requirements,
+    /// Only the `dependencies` array holds requirements
+    Project,
+    None,
+}
+
+impl DeclaringTable {
+    fn of(header: &str) -> Self {
+        let header = header
+            .trim_start_matches('[')
+            .split(']')
+            .next()
+            .unwrap_or_default();
+        let segments: Vec<&str> = header
+            .split('.')
+            .map(|segment| segment.trim().trim_matches('"'))
+            .collect();
+        match segments.as_slice() {
+            ["project"] => DeclaringTable::Project,
+            ["project", "optional-dependencies"] | ["dependency-groups"] => {
+                DeclaringTable::Requirements
+            }
+            [.., "dependencies" | "host-dependencies" | "build-dependencies" | "pypi-dependencies"] => {
+                DeclaringTable::Keys
+            }
+            _ => DeclaringTable::None,
+        }
+    }
+}
+
+/// Whether `line` holds a quoted requirement on `name`, e.g. `"numpy>=1.26",`
+fn quotes_requirement(line: &str, name: &str) -> bool {
+    line.match_indices('"').any(|(index, _)| {
+        line[index + 1..]
+            .strip_prefix(name)
+            .and_then(|rest| rest.chars().next())
+            .is_some_and(|c| !c.is_alphanumeric() && c != '-' && c != '_' && c != '.')
+    })
+}
+
+/// The manifest path relative to the repository, which is where GitHub
+/// expects annotations to point
+fn workspace_relative(path: &Path) -> String {
+    let roots = [
+        std::env::var_os("GITHUB_WORKSPACE").map(PathBuf::from),
+        std::env::current_dir().ok(),
+    ];
+    roots
+        .iter()
+        .flatten()
+        .find_map(|root| path.strip_prefix(root).ok())
+        .unwrap_or(path)
+        .display()
+        .to_string()
+}
+
+/// Escape the message of a workflow command
+fn escape_data(text: &str) -> String {
+    text.replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+/// Escape a property value of a workflow command
+fn escape_property(text: &str) -> String {
+    escape_data(text).replace(':', "%3A").replace(',', "%2C")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::{LockedIssue, LockedStatus, PackageUpdate, PlatformUpdates};
+
+    fn update(name: &str, installed: &str, latest: &str) -> PackageUpdate {
+        PackageUpdate {
+            name: name.to_string(),
+            installed_version: installed.to_string(),
+            latest_version: latest.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_render_annotations() {
+        let mut platforms = PlatformUpdates::new();
+        platforms.insert(
+            "linux-64".to_string(),
+            vec![
+                update("numpy", "1.26.0", "2.1.0"),
+                update("zlib", "1.2", "1.3"),
+            ],
+        );
+        platforms.insert(
+            "osx-arm64".to_string(),
+            vec![update("numpy", "1.26.0", "2.1.0")],
+        );
+        let report = Report {
+            platforms,
+            ..Report::default()
+        };
+        let manifest = "[dependencies]\nnumpy-base = \"*\"\nnumpy = \">=1.26\"\n";
+
+        assert_eq!(
+            render_annotations(&report, "pixi.toml", Some(manifest)),
+            "::warning file=pixi.toml,line=3,title=Outdated on linux-64%2C osx-arm64::numpy 1.26.0 -> 2.1.0\n\
+             ::warning file=pixi.toml,title=Outdated on linux-64::zlib 1.2 -> 1.3\n"
+        );
+    }
+
+    #[test]
+    fn test_render_annotations_locked_issues() {
+        let report = Report {
+            locked_issues: vec![LockedIssue {
+                name: "requests".to_string(),
+                version: "2.32.0".to_string(),
+                platform: "linux-64".to_string(),
+                status: LockedStatus::Yanked { reason: None },
+                annotations: Default::default(),
+            }],
+            ..Report::default()
+        };
+        assert_eq!(
+            render_annotations(&report, "pyproject.toml", None),
+            "::error file=pyproject.toml,title=Locked version yanked on linux-64::requests 2.32.0 is yanked\n"
+        );
+    }
+
+    #[test]
+    fn test_declaration_line() {
+        let manifest = "[project]\ndependencies = [\n  \"requests>=2\",\n  \"numpy\",\n]\n";
+        assert_eq!(declaration_line(manifest, "requests"), Some(3));
+        assert_eq!(declaration_line(manifest, "numpy"), Some(4));
+        assert_eq!(declaration_line(manifest, "pandas"), None);
+
+        // Only dependency tables count, not a key or string of the same name
+        // elsewhere in the manifest
+        let manifest = "[workspace]\nname = \"numpy\"\n\n[tasks]\nnumpy = \"python -c 'import numpy'\"\n\n[feature.test.dependencies]\nnumpy = \">=1.26\"\n";
+        assert_eq!(declaration_line(manifest, "numpy"), Some(8));
+        assert_eq!(declaration_line(manifest, "name"), None);
+
+        let manifest = "[project]\nkeywords = [\"requests\"]\ndependencies = [\"requests>=2\"]\n\n[project.optional-dependencies]\ntest = [\n  \"pytest\",\n]\n\n[tool.pixi.pypi-dependencies]\n\"my-lib\" = { path = \".\" }\n";
+        assert_eq!(declaration_line(manifest, "requests"), Some(3));
+        assert_eq!(declaration_line(manifest, "pytest"), Some(7));
+        assert_eq!(declaration_line(manifest, "my-lib"), Some(11));
+    }
+
+    #[test]
+    fn test_escape_property() {
+        assert_eq!(escape_property("a:b,c%\n"), "a%3Ab%2Cc%25%0A");
+    }
+}
@@ -0,0 +1,43 @@
+use anyhow::{Context, Result};
+use globset::{GlobBuilder, GlobMatcher};
+
+/// Compile a glob such as `jupyter*` or `BSD-*`
+pub fn matcher(pattern: &str, case_insensitive: bool) -> Result<GlobMatcher, globset::Error> {
+    GlobBuilder::new(pattern)
+        .case_insensitive(case_insensitive)
+        .build()
+        .map(|glob| glob.compile_matcher())
+}
+
+/// Compile every glob of `patterns`, naming the invalid one as `what`, e.g.
+/// "Invalid exclude pattern: [numpy"
+pub fn matchers<S: AsRef<str>>(
+    patterns: &[S],
+    case_insensitive: bool,
+    what: &str,
+) -> Result<Vec<GlobMatcher>> {
+    patterns
+        .iter()
+        .map(|pattern| {
+            let pattern = pattern.as_ref();
+            matcher(pattern, case_insensitive)
+                .with_context(|| format!("Invalid {}: {}", what, pattern))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matchers() {
+        let matchers = matchers(&["jupyter*", "BSD-*"], true, "pattern").unwrap();
+        assert!(matchers[0].is_match("jupyterlab"));
+        assert!(matchers[1].is_match("bsd-3-clause"));
+        assert!(!matcher("BSD-*", false).unwrap().is_match("bsd-3-clause"));
+
+        let error = super::matchers(&["[numpy"], false, "exclude pattern").unwrap_err();
+        assert_eq!(error.to_string(), "Invalid exclude pattern: [numpy");
+    }
+}
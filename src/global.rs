@@ -0,0 +1,180 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::pixi::{PackageKind, PixiPackage};
+
+/// The manifest of `pixi global`, relative to the pixi home
+pub const MANIFEST_PATH: &str = "manifests/pixi-global.toml";
+
+/// `$PIXI_HOME`, or `.pixi` in the home directory, where `pixi global`
+/// keeps its manifest and environments
+pub fn pixi_home(var: impl Fn(&str) -> Option<String>) -> Option<PathBuf> {
+    if let Some(home) = var("PIXI_HOME") {
+        return Some(PathBuf::from(home));
+    }
+    var("HOME")
+        .or_else(|| var("USERPROFILE"))
+        .map(|home| PathBuf::from(home).join(".pixi"))
+}
+
+/// The environments of globally installed tools, as declared in
+/// `pixi-global.toml`
+#[derive(Debug, Default, Clone, Deserialize)]
+pub struct GlobalManifest {
+    #[serde(default)]
+    pub envs: BTreeMap<String, GlobalEnvironment>,
+}
+
+#[derive(Debug, Default, Clone, Deserialize)]
+pub struct GlobalEnvironment {
+    #[serde(default)]
+    pub channels: Vec<String>,
+    /// The platform the environment was installed for, when not the current one
+    #[serde(default)]
+    pub platform: Option<String>,
+    /// The packages installed on purpose, by name; their specs are not needed
+    #[serde(default)]
+    pub dependencies: BTreeMap<String, toml::Value>,
+}
+
+impl GlobalManifest {
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        toml::from_str(&content).with_context(|| format!("Failed to parse {}", path.display()))
+    }
+}
+
+/// A package record in an environment's `conda-meta` directory
+#[derive(Debug, Deserialize)]
+struct PrefixRecord {
+    name: String,
+    version: String,
+    #[serde(default)]
+    build: Option<String>,
+    #[serde(default)]
+    size: Option<u64>,
+    /// The URL the package was downloaded from
+    #[serde(default)]
+    url: Option<String>,
+}
+
+/// The packages installed in an environment prefix, read from its
+/// `conda-meta` records. Global environments have no lockfile, so these are
+/// the versions in use.
+pub fn installed_packages(prefix: &Path, dependencies: &[&str]) -> Result<Vec<PixiPackage>> {
+    let conda_meta = prefix.join("conda-meta");
+    let entries = fs::read_dir(&conda_meta)
+        .with_context(|| format!("Failed to read {}", conda_meta.display()))?;
+    let mut packages = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        if path.extension().is_none_or(|extension| extension != "json") {
+            continue;
+        }
+        let content =
+            fs::read(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+        let record: PrefixRecord = serde_json::from_slice(&content)
+            .with_context(|| format!("Failed to parse {}", path.display()))?;
+        packages.push(PixiPackage {
+            is_explicit: dependencies.contains(&record.name.as_str()),
+            name: record.name,
+            version: record.version,
+            build: record.build,
+            size_bytes: record.size,
+            kind: PackageKind::Conda,
+            source: record.url,
+            depends: vec![],
+        });
+    }
+    packages.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(packages)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pixi_home() {
+        let vars = |vars: &'static [(&str, &str)]| {
+            move |name: &str| {
+                vars.iter()
+                    .find(|(key, _)| *key == name)
+                    .map(|(_, value)| value.to_string())
+            }
+        };
+        assert_eq!(
+            pixi_home(vars(&[("PIXI_HOME", "/opt/pixi"), ("HOME", "/home/me")])),
+            Some(PathBuf::from("/opt/pixi"))
+        );
+        assert_eq!(
+            pixi_home(vars(&[("HOME", "/home/me")])),
+            Some(PathBuf::from("/home/me/.pixi"))
+        );
+        assert_eq!(pixi_home(vars(&[])), None);
+    }
+
+    #[test]
+    fn test_installed_packages() {
+        let dir = tempfile::tempdir().unwrap();
+        let conda_meta = dir.path().join("conda-meta");
+        fs::create_dir(&conda_meta).unwrap();
+        fs::write(
+            conda_meta.join("ripgrep-14.1.0-h4f0e25e_0.json"),
+            r#"{
+                "name": "ripgrep",
+                "version": "14.1.0",
+                "build": "h4f0e25e_0",
+                "size": 1500000,
+                "url": "https://conda.anaconda.org/conda-forge/linux-64/ripgrep-14.1.0-h4f0e25e_0.conda"
+            }"#,
+        )
+        .unwrap();
+        fs::write(
+            conda_meta.join("libgcc-15.1.0-h767d61c_2.json"),
+            r#"{"name": "libgcc", "version": "15.1.0"}"#,
+        )
+        .unwrap();
+        fs::write(conda_meta.join("history"), "").unwrap();
+
+        let packages = installed_packages(dir.path(), &["ripgrep"]).unwrap();
+        assert_eq!(packages.len(), 2);
+        assert_eq!(packages[0].name, "libgcc");
+        assert!(!packages[0].is_explicit);
+        assert_eq!(packages[1].name, "ripgrep");
+        assert!(packages[1].is_explicit);
+        assert_eq!(packages[1].size_bytes, Some(1_500_000));
+    }
+
+    #[test]
+    fn test_load_manifest() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("pixi-global.toml");
+        fs::write(
+            &path,
+            r#"
+version = 1
+
+[envs.rg]
+channels = ["conda-forge"]
+dependencies = { ripgrep = "*" }
+exposed = { rg = "rg" }
+
+[envs.python]
+channels = ["conda-forge"]
+platform = "osx-64"
+dependencies = { python = "3.12.*", ipython = { version = "*" } }
+"#,
+        )
+        .unwrap();
+        let manifest = GlobalManifest::load(&path).unwrap();
+        assert_eq!(manifest.envs.len(), 2);
+        assert!(manifest.envs["rg"].dependencies.contains_key("ripgrep"));
+        assert_eq!(manifest.envs["python"].platform.as_deref(), Some("osx-64"));
+        assert_eq!(manifest.envs["python"].dependencies.len(), 2);
+    }
+}
@@ -0,0 +1,264 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::fmt::Write as _;
+
+use crate::pixi::PixiPackage;
+
+/// Which locked packages depend on which, for a single platform
+#[derive(Debug, Clone, Default)]
+pub struct DependencyGraph {
+    /// The locked dependencies of each locked package, sorted
+    depends: BTreeMap<String, Vec<String>>,
+    /// The locked version of each package
+    versions: HashMap<String, String>,
+}
+
+impl DependencyGraph {
+    /// Build the graph from the `depends` of locked packages. Dependencies
+    /// that aren't locked, e.g. virtual packages, are left out.
+    pub fn new(packages: &[PixiPackage]) -> Self {
+        let locked: BTreeSet<&str> = packages.iter().map(|p| p.name.as_str()).collect();
+        let depends = packages
+            .iter()
+            .map(|package| {
+                let dependencies: BTreeSet<String> = package
+                    .depends
+                    .iter()
+                    .filter(|name| **name != package.name && locked.contains(name.as_str()))
+                    .cloned()
+                    .collect();
+                (package.name.clone(), dependencies.into_iter().collect())
+            })
+            .collect();
+        let versions = packages
+            .iter()
+            .map(|package| (package.name.clone(), package.version.clone()))
+            .collect();
+        Self { depends, versions }
+    }
+
+    /// The locked dependencies of a package
+    pub fn dependencies(&self, name: &str) -> &[String] {
+        self.depends.get(name).map_or(&[], Vec::as_slice)
+    }
+
+    /// The shortest chain through which one of `roots` pulls in each package
+    /// it depends on, from the root to the package that requires it. Roots
+    /// and packages no root depends on are left out.
+    pub fn chains<'a>(
+        &self,
+        roots: impl IntoIterator<Item = &'a str>,
+    ) -> HashMap<String, Vec<String>> {
+        let roots: BTreeSet<&str> = roots.into_iter().collect();
+        let mut required_by: HashMap<&str, &str> = HashMap::new();
+        let mut queue: VecDeque<&str> = roots.iter().copied().collect();
+        while let Some(name) = queue.pop_front() {
+            for dependency in self.dependencies(name) {
+                if roots.contains(dependency.as_str())
+                    || required_by.contains_key(dependency.as_str())
+                {
+                    continue;
+                }
+                required_by.insert(dependency, name);
+                queue.push_back(dependency);
+            }
+        }
+
+        required_by
+            .keys()
+            .map(|name| {
+                let mut chain = Vec::new();
+                let mut current = *name;
+                while let Some(parent) = required_by.get(current) {
+                    chain.push(parent.to_string());
+                    current = parent;
+                }
+                chain.reverse();
+                (name.to_string(), chain)
+            })
+            .collect()
+    }
+
+    /// Packages no other locked package depends on
+    pub fn leaves(&self) -> Vec<&str> {
+        let required: HashSet<&str> = self
+            .depends
+            .values()
+            .flatten()
+            .map(String::as_str)
+            .collect();
+        self.depends
+            .keys()
+            .map(String::as_str)
+            .filter(|name| !required.contains(name))
+            .collect()
+    }
+
+    /// Render the dependency tree below `roots`, one package per line with
+    /// its locked version and, for outdated packages, the latest version.
+    /// Subtrees without an outdated package are collapsed into a count, and
+    /// a package already shown is marked `(*)` instead of being repeated.
+    pub fn render_tree(&self, roots: &[&str], latest: &HashMap<String, String>) -> String {
+        let mut tree = Tree {
+            graph: self,
+            latest,
+            outdated_below: HashMap::new(),
+            shown: HashSet::new(),
+            out: String::new(),
+        };
+        let mut roots: Vec<&str> = roots.to_vec();
+        roots.sort_unstable();
+        roots.dedup();
+        let (outdated, up_to_date): (Vec<&str>, Vec<&str>) = roots
+            .into_iter()
+            .partition(|root| tree.has_outdated(*root, &mut HashSet::new()));
+        for root in outdated {
+            tree.node(root, "", "", "");
+        }
+        if !up_to_date.is_empty() {
+            let _ = writeln!(tree.out, "{} up to date", up_to_date.len());
+        }
+        tree.out
+    }
+}
+
+/// State while rendering a dependency tree
+struct Tree<'a> {
+    graph: &'a DependencyGraph,
+    latest: &'a HashMap<String, String>,
+    /// Whether a package or any of its dependencies is outdated
+    outdated_below: HashMap<&'a str, bool>,
+    shown: HashSet<&'a str>,
+    out: String,
+}
+
+impl<'a> Tree<'a> {
+    fn has_outdated(&mut self, name: &'a str, visiting: &mut HashSet<&'a str>) -> bool {
+        if let Some(&outdated) = self.outdated_below.get(name) {
+            return outdated;
+        }
+        // A dependency cycle adds nothing the rest of the walk won't find
+        if !visiting.insert(name) {
+            return false;
+        }
+        let graph = self.graph;
+        let outdated = self.latest.contains_key(name)
+            || graph
+                .dependencies(name)
+                .iter()
+                .any(|dependency| self.has_outdated(dependency, visiting));
+        visiting.remove(name);
+        self.outdated_below.insert(name, outdated);
+        outdated
+    }
+
+    /// Write a package and the dependencies that lead to outdated packages.
+    /// `connector` precedes the package, `indent` its dependencies.
+    fn node(&mut self, name: &'a str, prefix: &str, connector: &str, indent: &str) {
+        let _ = write!(self.out, "{}{}{}", prefix, connector, name);
+        if let Some(version) = self.graph.versions.get(name) {
+            let _ = write!(self.out, " {}", version);
+        }
+        if let Some(latest) = self.latest.get(name) {
+            let _ = write!(self.out, " -> {}", latest);
+        }
+        if !self.shown.insert(name) {
+            self.out.push_str(" (*)\n");
+            return;
+        }
+        self.out.push('\n');
+
+        let graph = self.graph;
+        let (outdated, up_to_date): (Vec<&'a str>, Vec<&'a str>) = graph
+            .dependencies(name)
+            .iter()
+            .map(String::as_str)
+            .partition(|dependency| self.has_outdated(*dependency, &mut HashSet::new()));
+        let prefix = format!("{}{}", prefix, indent);
+        for (i, &dependency) in outdated.iter().enumerate() {
+            if i + 1 == outdated.len() && up_to_date.is_empty() {
+                self.node(dependency, &prefix, "└── ", "    ");
+            } else {
+                self.node(dependency, &prefix, "├── ", "│   ");
+            }
+        }
+        if !up_to_date.is_empty() {
+            let _ = writeln!(self.out, "{}└── {} up to date", prefix, up_to_date.len());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pixi::PackageKind;
+
+    fn package(name: &str, depends: &[&str]) -> PixiPackage {
+        PixiPackage {
+            name: name.to_string(),
+            version: "1.0".to_string(),
+            build: None,
+            size_bytes: None,
+            kind: PackageKind::Conda,
+            source: None,
+            is_explicit: false,
+            depends: depends.iter().map(|name| name.to_string()).collect(),
+        }
+    }
+
+    fn graph() -> DependencyGraph {
+        DependencyGraph::new(&[
+            package("python", &["libsqlite", "openssl", "__glibc"]),
+            package("libsqlite", &["libzlib"]),
+            package("openssl", &["ca-certificates"]),
+            package("ca-certificates", &[]),
+            package("libzlib", &[]),
+            package("pytest", &["python", "pluggy"]),
+            package("pluggy", &["python"]),
+        ])
+    }
+
+    #[test]
+    fn test_dependencies() {
+        let graph = graph();
+        // Virtual packages aren't locked
+        assert_eq!(graph.dependencies("python"), ["libsqlite", "openssl"]);
+        assert!(graph.dependencies("unknown").is_empty());
+    }
+
+    #[test]
+    fn test_render_tree() {
+        let graph = graph();
+        let latest = HashMap::from([
+            ("libzlib".to_string(), "1.1".to_string()),
+            ("pluggy".to_string(), "2.0".to_string()),
+        ]);
+
+        assert_eq!(
+            graph.render_tree(&["python", "pytest", "openssl"], &latest),
+            "python 1.0\n\
+             ├── libsqlite 1.0\n\
+             │   └── libzlib 1.0 -> 1.1\n\
+             └── 1 up to date\n\
+             pytest 1.0\n\
+             ├── pluggy 1.0 -> 2.0\n\
+             │   └── python 1.0 (*)\n\
+             └── python 1.0 (*)\n\
+             1 up to date\n"
+        );
+        assert_eq!(graph.leaves(), ["pytest"]);
+    }
+
+    #[test]
+    fn test_chains() {
+        let chains = graph().chains(["python", "pytest"]);
+        assert_eq!(chains["libzlib"], ["python", "libsqlite"]);
+        assert_eq!(chains["openssl"], ["python"]);
+        assert_eq!(chains["pluggy"], ["pytest"]);
+        // Roots are required by the manifest itself
+        assert!(!chains.contains_key("python"));
+
+        let chains = graph().chains(["openssl"]);
+        assert_eq!(chains.len(), 1);
+        assert_eq!(chains["ca-certificates"], ["openssl"]);
+    }
+}
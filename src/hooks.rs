@@ -0,0 +1,111 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::path::Path;
+use std::process::Command;
+
+use crate::audit_log::AuditRecord;
+
+/// Shell commands run around a check, configured in `[tool.pixi-outdated.hooks]`
+#[derive(Debug, Default, Clone, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct Hooks {
+    /// Run before anything is checked; a failing command aborts the run
+    #[serde(default)]
+    pub pre: Vec<String>,
+    /// Run after the report has been written
+    #[serde(default)]
+    pub post: Vec<String>,
+}
+
+/// Environment variables describing a finished run, for post hooks
+pub fn report_env(report_path: &Path, record: &AuditRecord) -> Vec<(&'static str, String)> {
+    let status = serde_json::to_value(&record.status)
+        .ok()
+        .and_then(|status| status.as_str().map(str::to_string))
+        .unwrap_or_default();
+
+    vec![
+        ("PIXI_OUTDATED_REPORT", report_path.display().to_string()),
+        ("PIXI_OUTDATED_ENVIRONMENT", record.environment.clone()),
+        ("PIXI_OUTDATED_STATUS", status),
+        ("PIXI_OUTDATED_OUTDATED", record.outdated.to_string()),
+        (
+            "PIXI_OUTDATED_OUTDATED_DIRECT",
+            record.outdated_direct.to_string(),
+        ),
+        ("PIXI_OUTDATED_UPDATES", record.updates.to_string()),
+        (
+            "PIXI_OUTDATED_LOCKED_ISSUES",
+            record.locked_issues.to_string(),
+        ),
+        ("PIXI_OUTDATED_UNCHECKED", record.unchecked.to_string()),
+    ]
+}
+
+/// Run each command through the shell in `dir`, stopping at the first one
+/// that fails. The commands' output goes to stderr, so structured output on
+/// stdout stays parseable.
+pub fn run(commands: &[String], dir: &Path, env: &[(&str, String)]) -> Result<()> {
+    for command in commands {
+        let mut cmd = shell(command);
+        cmd.current_dir(dir)
+            .envs(env.iter().map(|(key, value)| (key, value)))
+            .stdout(std::io::stderr());
+
+        let status = cmd
+            .status()
+            .with_context(|| format!("Failed to run hook `{}`", command))?;
+        if !status.success() {
+            anyhow::bail!("Hook `{}` failed with {}", command, status);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(windows)]
+fn shell(command: &str) -> Command {
+    let mut cmd = Command::new("cmd");
+    cmd.arg("/C").arg(command);
+    cmd
+}
+
+#[cfg(not(windows))]
+fn shell(command: &str) -> Command {
+    let mut cmd = Command::new("sh");
+    cmd.arg("-c").arg(command);
+    cmd
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::report::Report;
+
+    #[test]
+    fn test_run_passes_environment() {
+        let dir = tempfile::tempdir().unwrap();
+        let record = AuditRecord::new(&Report::default(), "default", None, chrono::Utc::now());
+        let env = report_env(Path::new("report.json"), &record);
+
+        run(
+            &["echo \"$PIXI_OUTDATED_STATUS $PIXI_OUTDATED_OUTDATED $PIXI_OUTDATED_REPORT\" > out.txt"
+                .to_string()],
+            dir.path(),
+            &env,
+        )
+        .unwrap();
+
+        let out = std::fs::read_to_string(dir.path().join("out.txt")).unwrap();
+        assert_eq!(out.trim(), "ok 0 report.json");
+    }
+
+    #[test]
+    fn test_run_stops_at_failing_command() {
+        let dir = tempfile::tempdir().unwrap();
+        let commands = ["exit 3".to_string(), "touch reached".to_string()];
+
+        let error = run(&commands, dir.path(), &[]).unwrap_err();
+        assert!(error.to_string().contains("Hook `exit 3` failed"));
+        assert!(!dir.path().join("reached").exists());
+    }
+}
@@ -0,0 +1,315 @@
+use std::fmt::Write as _;
+
+use crate::pixi::PackageKind;
+use crate::report::{PackageUpdate, Report, RunStatus};
+
+/// Styles of the HTML report; everything is inlined so the file can be
+/// shared on its own
+const STYLE: &str = r#"
+body { font-family: system-ui, sans-serif; margin: 2rem; color: #1f2328; }
+h1 { font-size: 1.5rem; }
+h2 { font-size: 1.2rem; margin-top: 2rem; }
+.metadata { color: #59636e; font-size: 0.85rem; }
+.tabs { display: flex; gap: 0.25rem; border-bottom: 1px solid #d1d9e0; margin-top: 1rem; }
+.tabs button { border: 1px solid transparent; border-bottom: none; background: none; padding: 0.5rem 1rem; cursor: pointer; font: inherit; }
+.tabs button.active { border-color: #d1d9e0; background: #fff; border-radius: 6px 6px 0 0; font-weight: 600; }
+.panel { display: none; }
+.panel.active { display: block; }
+input[type=search] { margin: 1rem 0; padding: 0.4rem; width: 20rem; max-width: 100%; }
+table { border-collapse: collapse; width: 100%; }
+th, td { border: 1px solid #d1d9e0; padding: 0.35rem 0.6rem; text-align: left; }
+th { background: #f6f8fa; cursor: pointer; user-select: none; }
+th[data-order=asc]::after { content: " \25B2"; }
+th[data-order=desc]::after { content: " \25BC"; }
+.severity-major { color: #cf222e; font-weight: 600; }
+.severity-minor { color: #9a6700; }
+"#;
+
+/// Tab switching, filtering and sorting of the tables
+const SCRIPT: &str = r##"
+document.querySelectorAll(".tabs button").forEach((button) => {
+  button.addEventListener("click", () => {
+    document.querySelectorAll(".tabs button, .panel").forEach((el) => el.classList.remove("active"));
+    button.classList.add("active");
+    document.getElementById(button.dataset.panel).classList.add("active");
+  });
+});
+document.querySelectorAll("input[type=search]").forEach((input) => {
+  input.addEventListener("input", () => {
+    const needle = input.value.toLowerCase();
+    document.querySelectorAll("#" + input.dataset.table + " tbody tr").forEach((row) => {
+      row.hidden = !row.textContent.toLowerCase().includes(needle);
+    });
+  });
+});
+document.querySelectorAll("th").forEach((th) => {
+  th.addEventListener("click", () => {
+    const table = th.closest("table");
+    const body = table.querySelector("tbody");
+    const index = Array.from(th.parentNode.children).indexOf(th);
+    const order = th.dataset.order === "asc" ? "desc" : "asc";
+    table.querySelectorAll("th").forEach((other) => delete other.dataset.order);
+    th.dataset.order = order;
+    const collator = new Intl.Collator(undefined, { numeric: true });
+    Array.from(body.rows)
+      .sort((a, b) => {
+        const result = collator.compare(a.cells[index].textContent, b.cells[index].textContent);
+        return order === "asc" ? result : -result;
+      })
+      .forEach((row) => body.appendChild(row));
+  });
+});
+"##;
+
+/// Render the report as a self-contained HTML page, with a sortable,
+/// filterable table of updates per platform
+pub fn render_html(report: &Report) -> String {
+    let mut out = String::from("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n");
+    out.push_str("<meta charset=\"utf-8\">\n<title>Outdated packages</title>\n");
+    let _ = writeln!(out, "<style>{}</style>", STYLE);
+    out.push_str("</head>\n<body>\n<h1>Outdated packages</h1>\n");
+    if let Some(ref metadata) = report.metadata {
+        let _ = writeln!(
+            out,
+            "<p class=\"metadata\">{}</p>",
+            escape(&metadata.summary())
+        );
+    }
+
+    if report.status != RunStatus::Ok {
+        let _ = writeln!(out, "<p>{}.</p>", escape(&report.status_message()));
+    }
+
+    let platforms: Vec<(&String, &Vec<PackageUpdate>)> = report
+        .platforms
+        .iter()
+        .filter(|(_, updates)| !updates.is_empty())
+        .collect();
+    if platforms.is_empty() {
+        if report.status == RunStatus::Ok {
+            out.push_str("<p>All packages are up to date.</p>\n");
+        }
+    } else {
+        out.push_str("<div class=\"tabs\">\n");
+        for (index, (platform, updates)) in platforms.iter().enumerate() {
+            let _ = writeln!(
+                out,
+                "<button data-panel=\"platform-{}\"{}>{} ({})</button>",
+                index,
+                if index == 0 { " class=\"active\"" } else { "" },
+                escape(platform),
+                updates.len()
+            );
+        }
+        out.push_str("</div>\n");
+        for (index, (platform, updates)) in platforms.iter().enumerate() {
+            let _ = writeln!(
+                out,
+                "<div class=\"panel{}\" id=\"platform-{}\">",
+                if index == 0 { " active" } else { "" },
+                index
+            );
+            let _ = writeln!(
+                out,
+                "<input type=\"search\" placeholder=\"Filter {}\" data-table=\"updates-{}\">",
+                escape(platform),
+                index
+            );
+            write_updates(&mut out, &format!("updates-{}", index), updates);
+            out.push_str("</div>\n");
+        }
+    }
+
+    write_findings(
+        &mut out,
+        "Yanked or removed upstream",
+        report.locked_issues.iter().map(|issue| {
+            [
+                issue.name.clone(),
+                issue.version.clone(),
+                issue.platform.clone(),
+                issue.status.to_string(),
+            ]
+        }),
+    );
+    write_findings(
+        &mut out,
+        "Not checked",
+        report.unchecked.iter().map(|unchecked| {
+            [
+                unchecked.name.clone(),
+                unchecked.version.clone(),
+                unchecked.platform.clone(),
+                unchecked.status.to_string(),
+            ]
+        }),
+    );
+    write_findings(
+        &mut out,
+        "Not from an allowed channel",
+        report.disallowed_channels.iter().map(|package| {
+            [
+                package.name.clone(),
+                package.version.clone(),
+                package.platform.clone(),
+                package.channel.clone(),
+            ]
+        }),
+    );
+    write_findings(
+        &mut out,
+        "Locked from channels the manifest no longer lists",
+        report.unlisted_channels.iter().map(|package| {
+            [
+                package.name.clone(),
+                package.version.clone(),
+                package.platform.clone(),
+                package.channel.clone(),
+            ]
+        }),
+    );
+
+    let _ = writeln!(out, "<script>{}</script>", SCRIPT);
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+/// A table of the updates of one platform
+fn write_updates(out: &mut String, id: &str, updates: &[PackageUpdate]) {
+    let _ = writeln!(out, "<table id=\"{}\">", id);
+    out.push_str("<thead><tr><th>Package</th><th>Kind</th><th>Channel</th><th>Installed</th><th>Latest</th><th>Severity</th><th>Direct</th><th>Risk</th></tr></thead>\n<tbody>\n");
+    for update in updates {
+        let kind = update.kind.map(|kind| kind.to_string()).unwrap_or_default();
+        let channel = match (update.channel.as_deref(), update.kind) {
+            (Some(channel), _) => crate::output::channel_label(channel),
+            (None, Some(PackageKind::Pypi)) => "PyPI",
+            (None, _) => "",
+        };
+        let severity = update
+            .severity
+            .map(|severity| severity.to_string())
+            .unwrap_or_default();
+        let _ = writeln!(
+            out,
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td class=\"severity-{}\">{}</td><td>{}</td><td>{}</td></tr>",
+            escape(&update.label()),
+            kind,
+            escape(channel),
+            escape(&update.installed_version),
+            escape(&update.latest_version),
+            severity,
+            severity,
+            if update.direct { "yes" } else { "" },
+            update.risk.map(|risk| risk.to_string()).unwrap_or_default()
+        );
+    }
+    out.push_str("</tbody>\n</table>\n");
+}
+
+/// A section listing locked packages with an issue, skipped when empty
+fn write_findings(out: &mut String, title: &str, rows: impl Iterator<Item = [String; 4]>) {
+    let mut rows = rows.peekable();
+    if rows.peek().is_none() {
+        return;
+    }
+
+    let _ = writeln!(out, "<h2>{}</h2>", escape(title));
+    out.push_str("<table>\n<thead><tr><th>Package</th><th>Locked</th><th>Platform</th><th>Details</th></tr></thead>\n<tbody>\n");
+    for row in rows {
+        out.push_str("<tr>");
+        for cell in &row {
+            let _ = write!(out, "<td>{}</td>", escape(cell));
+        }
+        out.push_str("</tr>\n");
+    }
+    out.push_str("</tbody>\n</table>\n");
+}
+
+/// Escape text for use in HTML or XML content and attribute values
+pub(crate) fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::{DisallowedPackage, PlatformUpdates};
+    use crate::severity::Severity;
+
+    fn report() -> Report {
+        let mut platforms = PlatformUpdates::new();
+        platforms.insert(
+            "linux-64".to_string(),
+            vec![PackageUpdate {
+                name: "python".to_string(),
+                kind: Some(PackageKind::Conda),
+                channel: Some("https://conda.anaconda.org/conda-forge".to_string()),
+                installed_version: "3.12.11".to_string(),
+                latest_version: "3.14.0".to_string(),
+                severity: Some(Severity::Minor),
+                direct: true,
+                ..Default::default()
+            }],
+        );
+        platforms.insert("osx-arm64".to_string(), vec![]);
+        Report {
+            platforms,
+            ..Report::default()
+        }
+    }
+
+    #[test]
+    fn test_render_html() {
+        let html = render_html(&report());
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html
+            .contains("<button data-panel=\"platform-0\" class=\"active\">linux-64 (1)</button>"));
+        assert!(html.contains("<td>python</td><td>conda</td><td>conda-forge</td><td>3.12.11</td><td>3.14.0</td><td class=\"severity-minor\">minor</td><td>yes</td>"));
+        // Platforms without updates get no tab
+        assert!(!html.contains("osx-arm64"));
+        // Nothing is loaded from elsewhere
+        assert!(!html.contains("src="));
+        assert!(!html.contains("href="));
+    }
+
+    #[test]
+    fn test_render_html_findings() {
+        let mut report = report();
+        report.disallowed_channels.push(DisallowedPackage {
+            name: "samtools".to_string(),
+            version: "1.21".to_string(),
+            platform: "linux-64".to_string(),
+            channel: "https://conda.anaconda.org/bioconda".to_string(),
+        });
+        let html = render_html(&report);
+        assert!(html.contains("<h2>Not from an allowed channel</h2>"));
+        assert!(html.contains("<td>samtools</td><td>1.21</td>"));
+        assert!(!html.contains("Not checked"));
+    }
+
+    #[test]
+    fn test_render_html_up_to_date() {
+        let html = render_html(&Report::default());
+        assert!(html.contains("<p>All packages are up to date.</p>"));
+        assert!(!html.contains("class=\"tabs\""));
+    }
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(
+            escape("<script>\"a\" & 'b'</script>"),
+            "&lt;script&gt;&quot;a&quot; &amp; &#39;b&#39;&lt;/script&gt;"
+        );
+    }
+}
@@ -0,0 +1,28 @@
+use anyhow::{Context, Result};
+use url::Url;
+
+/// An HTTP client sending its requests through `proxy` if given, and
+/// otherwise through the proxy in `HTTPS_PROXY`/`HTTP_PROXY`, like reqwest's
+/// default client. Hosts in `NO_PROXY` are reached directly either way.
+pub fn client(proxy: Option<&Url>) -> Result<reqwest::Client> {
+    let mut builder = reqwest::Client::builder();
+    if let Some(proxy) = proxy {
+        let proxy = reqwest::Proxy::all(proxy.as_str())
+            .with_context(|| format!("Invalid proxy {}", proxy))?
+            .no_proxy(reqwest::NoProxy::from_env());
+        builder = builder.proxy(proxy);
+    }
+    builder.build().context("Failed to build the HTTP client")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_client() {
+        assert!(client(None).is_ok());
+        let proxy = Url::parse("http://proxy.example.com:3128").unwrap();
+        assert!(client(Some(&proxy)).is_ok());
+    }
+}
@@ -0,0 +1,249 @@
+use anyhow::{Context, Result};
+use globset::GlobMatcher;
+use rattler_conda_types::{ParseStrictness, Version, VersionSpec};
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+/// Name of the ignore file that is looked up next to the manifest
+pub const IGNORE_FILE_NAME: &str = ".pixi-outdated-ignore";
+
+/// Packages left out of a run with `--exclude`, by name. Unlike ignore
+/// rules they are dropped before anything is queried.
+#[derive(Debug, Clone, Default)]
+pub struct ExcludePatterns {
+    matchers: Vec<GlobMatcher>,
+}
+
+impl ExcludePatterns {
+    pub fn new<S: AsRef<str>>(patterns: &[S]) -> Result<Self> {
+        let matchers = crate::glob::matchers(patterns, false, "exclude pattern")?;
+        Ok(Self { matchers })
+    }
+
+    pub fn excludes(&self, name: &str) -> bool {
+        self.matchers.iter().any(|matcher| matcher.is_match(name))
+    }
+}
+
+/// A single line of an ignore file
+#[derive(Debug, Clone)]
+pub struct IgnoreRule {
+    matcher: GlobMatcher,
+    negated: bool,
+    version: Option<VersionSpec>,
+}
+
+impl IgnoreRule {
+    /// Parse a single (non-empty, non-comment) line.
+    ///
+    /// The line format is `[!]<glob> [version-spec]`, for example:
+    /// `libstdcxx*`, `numpy >=2` or `!libstdcxx-devel`.
+    pub fn parse(line: &str) -> Result<Self> {
+        let (negated, rest) = match line.strip_prefix('!') {
+            Some(rest) => (true, rest.trim_start()),
+            None => (false, line),
+        };
+
+        let (pattern, version) = match rest.split_once(char::is_whitespace) {
+            Some((pattern, version)) => (pattern, Some(version.trim())),
+            None => (rest, None),
+        };
+
+        let matcher = crate::glob::matcher(pattern, false)
+            .with_context(|| format!("Invalid ignore pattern: {}", pattern))?;
+
+        let version = version
+            .filter(|v| !v.is_empty())
+            .map(|v| {
+                VersionSpec::from_str(v, ParseStrictness::Lenient)
+                    .with_context(|| format!("Invalid version rule for {}: {}", pattern, v))
+            })
+            .transpose()?;
+
+        Ok(Self {
+            matcher,
+            negated,
+            version,
+        })
+    }
+
+    fn matches(&self, name: &str, latest_version: Option<&str>) -> bool {
+        if !self.matcher.is_match(name) {
+            return false;
+        }
+
+        match (&self.version, latest_version) {
+            (None, _) => true,
+            (Some(spec), Some(latest)) => Version::from_str(latest)
+                .map(|v| spec.matches(&v))
+                .unwrap_or(false),
+            // A version rule can only be evaluated once the latest version is known
+            (Some(_), None) => false,
+        }
+    }
+}
+
+/// Ignore rules loaded from a `.pixi-outdated-ignore` file.
+///
+/// Patterns follow gitignore conventions: blank lines and lines starting
+/// with `#` are skipped, a leading `!` re-includes a previously ignored
+/// package and the last matching rule wins.
+#[derive(Debug, Clone, Default)]
+pub struct IgnoreFile {
+    rules: Vec<IgnoreRule>,
+}
+
+impl IgnoreFile {
+    pub fn parse(content: &str) -> Result<Self> {
+        let rules = content
+            .lines()
+            .enumerate()
+            .map(|(idx, line)| (idx, line.trim()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+            .map(|(idx, line)| IgnoreRule::parse(line).with_context(|| format!("line {}", idx + 1)))
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self { rules })
+    }
+
+    /// Rules given one per entry, e.g. the `ignore` setting of the project
+    /// config
+    pub fn from_rules(rules: &[String]) -> Result<Self> {
+        let rules = rules
+            .iter()
+            .map(|rule| {
+                IgnoreRule::parse(rule.trim())
+                    .with_context(|| format!("Invalid ignore rule: {}", rule))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self { rules })
+    }
+
+    /// Add the rules of `other` after these, so they take precedence
+    pub fn extend(&mut self, other: IgnoreFile) {
+        self.rules.extend(other.rules);
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        Self::parse(&content).with_context(|| format!("Failed to parse {}", path.display()))
+    }
+
+    /// Load the ignore file from the given directory, returning an empty
+    /// set of rules if it does not exist
+    pub fn discover(dir: &Path) -> Result<Self> {
+        let path = dir.join(IGNORE_FILE_NAME);
+        if path.is_file() {
+            Self::load(&path)
+        } else {
+            Ok(Self::default())
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Whether an update of `name` to `latest_version` should be ignored
+    pub fn is_ignored(&self, name: &str, latest_version: Option<&str>) -> bool {
+        self.rules
+            .iter()
+            .rev()
+            .find(|rule| rule.matches(name, latest_version))
+            .is_some_and(|rule| !rule.negated)
+    }
+
+    /// Whether `name` is ignored regardless of its latest version, meaning
+    /// it does not need to be queried at all
+    pub fn ignores_all_versions(&self, name: &str) -> bool {
+        self.rules
+            .iter()
+            .rev()
+            .find(|rule| rule.matcher.is_match(name) && (rule.negated || rule.version.is_none()))
+            .is_some_and(|rule| !rule.negated)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_skips_comments_and_blank_lines() {
+        let ignore = IgnoreFile::parse("# comment\n\n   \npython\n").unwrap();
+        assert!(ignore.is_ignored("python", Some("3.13.0")));
+        assert!(!ignore.is_ignored("numpy", Some("2.0.0")));
+    }
+
+    #[test]
+    fn test_glob_patterns() {
+        let ignore = IgnoreFile::parse("libstdcxx*\n").unwrap();
+        assert!(ignore.is_ignored("libstdcxx-ng", None));
+        assert!(ignore.is_ignored("libstdcxx", None));
+        assert!(!ignore.is_ignored("libgcc-ng", None));
+    }
+
+    #[test]
+    fn test_negation_last_match_wins() {
+        let ignore = IgnoreFile::parse("lib*\n!libsqlite\n").unwrap();
+        assert!(ignore.is_ignored("libffi", None));
+        assert!(!ignore.is_ignored("libsqlite", None));
+        assert!(!ignore.ignores_all_versions("libsqlite"));
+    }
+
+    #[test]
+    fn test_version_rule() {
+        let ignore = IgnoreFile::parse("numpy >=2\n").unwrap();
+        assert!(ignore.is_ignored("numpy", Some("2.1.0")));
+        assert!(!ignore.is_ignored("numpy", Some("1.26.4")));
+        // Version rules need the latest version, so numpy must still be queried
+        assert!(!ignore.ignores_all_versions("numpy"));
+    }
+
+    #[test]
+    fn test_from_rules() {
+        let mut ignore = IgnoreFile::from_rules(&["lib*".to_string()]).unwrap();
+        ignore.extend(IgnoreFile::parse("!libsqlite\n").unwrap());
+        assert!(ignore.is_ignored("libffi", None));
+        assert!(!ignore.is_ignored("libsqlite", None));
+
+        assert!(IgnoreFile::from_rules(&["foo[".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_exclude_patterns() {
+        let exclude = ExcludePatterns::new(&["py*", "libstdcxx-ng"]).unwrap();
+        assert!(exclude.excludes("python"));
+        assert!(exclude.excludes("pytest"));
+        assert!(exclude.excludes("libstdcxx-ng"));
+        assert!(!exclude.excludes("libstdcxx"));
+        assert!(!exclude.excludes("numpy"));
+
+        assert!(!ExcludePatterns::default().excludes("python"));
+        assert!(ExcludePatterns::new(&["py["]).is_err());
+    }
+
+    #[test]
+    fn test_invalid_pattern() {
+        let result = IgnoreFile::parse("foo[\n");
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_discover_missing_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let ignore = IgnoreFile::discover(dir.path()).unwrap();
+        assert!(ignore.is_empty());
+    }
+
+    #[test]
+    fn test_discover_existing_file() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join(IGNORE_FILE_NAME), "cowsay\n").unwrap();
+        let ignore = IgnoreFile::discover(dir.path()).unwrap();
+        assert!(ignore.is_ignored("cowsay", Some("6.1")));
+    }
+}
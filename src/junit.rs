@@ -0,0 +1,217 @@
+use std::fmt::Write as _;
+
+use crate::html::escape;
+use crate::report::{PackageUpdate, Report, UncheckedPackage};
+
+/// Render the report as JUnit XML for the test views of CI systems: a test
+/// suite per platform in which every outdated package is a failed test case,
+/// every package that couldn't be checked a skipped one and every up to date
+/// package a passing one
+pub fn render_junit(report: &Report) -> String {
+    let failures: usize = report.platforms.values().map(Vec::len).sum();
+    let skipped = report.unchecked.len();
+    let up_to_date = report.up_to_date();
+    let passed: usize = up_to_date.values().map(Vec::len).sum();
+
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    let _ = writeln!(
+        out,
+        "<testsuites name=\"pixi-outdated\" tests=\"{}\" failures=\"{}\" skipped=\"{}\">",
+        failures + skipped + passed,
+        failures,
+        skipped
+    );
+
+    let mut platforms: Vec<&str> = report.platforms.keys().map(String::as_str).collect();
+    for platform in report
+        .unchecked
+        .iter()
+        .map(|unchecked| unchecked.platform.as_str())
+        .chain(up_to_date.keys().copied())
+    {
+        if !platforms.contains(&platform) {
+            platforms.push(platform);
+        }
+    }
+    platforms.sort_unstable();
+
+    for platform in platforms {
+        let updates = report
+            .platforms
+            .get(platform)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        let unchecked: Vec<&UncheckedPackage> = report
+            .unchecked
+            .iter()
+            .filter(|unchecked| unchecked.platform == platform)
+            .collect();
+        let passed = up_to_date
+            .get(platform)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        let _ = writeln!(
+            out,
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" skipped=\"{}\">",
+            escape(platform),
+            updates.len() + unchecked.len() + passed.len(),
+            updates.len(),
+            unchecked.len()
+        );
+        for update in updates {
+            write_update(&mut out, platform, update);
+        }
+        for unchecked in unchecked {
+            let _ = writeln!(
+                out,
+                "    <testcase name=\"{}\" classname=\"{}\">\n      <skipped message=\"{}\"/>\n    </testcase>",
+                escape(&unchecked.name),
+                escape(platform),
+                escape(&unchecked.status.to_string())
+            );
+        }
+        for package in passed {
+            let _ = writeln!(
+                out,
+                "    <testcase name=\"{}\" classname=\"{}\"/>",
+                escape(&package.name),
+                escape(platform)
+            );
+        }
+        out.push_str("  </testsuite>\n");
+    }
+
+    out.push_str("</testsuites>\n");
+    out
+}
+
+fn write_update(out: &mut String, platform: &str, update: &PackageUpdate) {
+    let message = format!(
+        "{} {} -> {}",
+        update.name, update.installed_version, update.latest_version
+    );
+    let kind = update
+        .severity
+        .map_or_else(|| "outdated".to_string(), |severity| severity.to_string());
+    let _ = writeln!(
+        out,
+        "    <testcase name=\"{}\" classname=\"{}\">",
+        escape(&update.label()),
+        escape(platform)
+    );
+    let _ = writeln!(
+        out,
+        "      <failure message=\"{}\" type=\"{}\">{}</failure>",
+        escape(&message),
+        escape(&kind),
+        escape(&update.to_string())
+    );
+    out.push_str("    </testcase>\n");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pixi::{PackageKind, PixiPackage};
+    use crate::report::{CheckStatus, PlatformUpdates};
+    use crate::severity::Severity;
+    use std::collections::{BTreeMap, BTreeSet};
+
+    fn report() -> Report {
+        let mut platforms = PlatformUpdates::new();
+        platforms.insert(
+            "linux-64".to_string(),
+            vec![PackageUpdate {
+                name: "python".to_string(),
+                installed_version: "3.12.11".to_string(),
+                latest_version: "3.14.0".to_string(),
+                severity: Some(Severity::Minor),
+                ..Default::default()
+            }],
+        );
+        platforms.insert("osx-arm64".to_string(), vec![]);
+        Report {
+            platforms,
+            unchecked: vec![UncheckedPackage {
+                name: "internal-lib".to_string(),
+                kind: PackageKind::Pypi,
+                version: "1.0".to_string(),
+                platform: "win-64".to_string(),
+                status: CheckStatus::NotFound,
+                annotations: Default::default(),
+            }],
+            ..Report::default()
+        }
+    }
+
+    #[test]
+    fn test_render_junit() {
+        let xml = render_junit(&report());
+        assert!(xml.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n"));
+        assert!(xml.contains(
+            "<testsuites name=\"pixi-outdated\" tests=\"2\" failures=\"1\" skipped=\"1\">"
+        ));
+        assert!(
+            xml.contains("<testsuite name=\"linux-64\" tests=\"1\" failures=\"1\" skipped=\"0\">")
+        );
+        assert!(xml.contains("<testcase name=\"python\" classname=\"linux-64\">"));
+        assert!(xml.contains(
+            "<failure message=\"python 3.12.11 -&gt; 3.14.0\" type=\"minor\">python: 3.12.11 -&gt; 3.14.0</failure>"
+        ));
+        // Platforms without findings still show up as passing suites
+        assert!(
+            xml.contains("<testsuite name=\"osx-arm64\" tests=\"0\" failures=\"0\" skipped=\"0\">")
+        );
+        assert!(xml.contains("<testcase name=\"internal-lib\" classname=\"win-64\">"));
+        assert!(xml.contains("<skipped message=\"not found upstream\"/>"));
+        assert!(xml.ends_with("</testsuites>\n"));
+    }
+
+    #[test]
+    fn test_render_junit_passing_packages() {
+        let mut report = report();
+        let package = |name: &str| PixiPackage {
+            name: name.to_string(),
+            version: "1.0".to_string(),
+            build: None,
+            size_bytes: None,
+            kind: PackageKind::Conda,
+            source: None,
+            is_explicit: true,
+            depends: vec![],
+        };
+        report.locked = BTreeMap::from([(
+            "linux-64".to_string(),
+            vec![package("python"), package("zlib"), package("filtered-out")],
+        )]);
+        report.checked = BTreeSet::from([
+            (
+                "linux-64".to_string(),
+                PackageKind::Conda,
+                "python".to_string(),
+            ),
+            (
+                "linux-64".to_string(),
+                PackageKind::Conda,
+                "zlib".to_string(),
+            ),
+        ]);
+
+        let xml = render_junit(&report);
+        assert!(xml.contains(
+            "<testsuites name=\"pixi-outdated\" tests=\"3\" failures=\"1\" skipped=\"1\">"
+        ));
+        assert!(
+            xml.contains("<testsuite name=\"linux-64\" tests=\"2\" failures=\"1\" skipped=\"0\">")
+        );
+        assert!(xml.contains("<testcase name=\"zlib\" classname=\"linux-64\"/>"));
+        assert!(!xml.contains("filtered-out"));
+    }
+
+    #[test]
+    fn test_render_junit_up_to_date() {
+        let xml = render_junit(&Report::default());
+        assert!(xml.contains("tests=\"0\" failures=\"0\" skipped=\"0\""));
+        assert!(!xml.contains("<testsuite "));
+    }
+}
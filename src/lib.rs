@@ -1,4 +1,61 @@
+pub mod activation;
+pub mod age;
+pub mod aggregate;
+pub mod annotations;
+pub mod audit;
+pub mod audit_log;
+pub mod auth;
+pub mod automerge;
+pub mod cache;
+pub mod candidates;
+pub mod changes;
+pub mod check;
+pub mod commands;
 pub mod conda;
+pub mod config;
+pub mod cran;
+pub mod demo;
+pub mod diff;
+pub mod explain;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod fixtures;
+pub mod github;
+pub mod glob;
+pub mod global;
+pub mod graph;
+pub mod hooks;
+pub mod html;
+pub mod http;
+pub mod ignore;
+pub mod junit;
+pub mod libyears;
+pub mod local;
+pub mod locale;
+pub mod oneline;
+pub mod output;
 pub mod parser;
+pub mod pins;
 pub mod pixi;
+pub mod plan;
+pub mod platform;
+pub mod policy;
+pub mod priority;
 pub mod pypi;
+pub mod python;
+pub mod release_notes;
+pub mod report;
+pub mod retry;
+pub mod risk;
+pub mod sbom;
+pub mod select;
+pub mod session;
+pub mod severity;
+pub mod solvable;
+pub mod sources;
+pub mod spread;
+pub mod stats;
+pub mod upgrade;
+pub mod upstream;
+pub mod webhook;
+pub mod workspaces;
@@ -0,0 +1,115 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::BTreeMap;
+
+use crate::report::PlatformUpdates;
+
+/// Days per year, counting leap years
+const DAYS_PER_YEAR: f64 = 365.25;
+
+/// How far behind an environment is, as the sum over its outdated packages
+/// of the time between the release of the installed version and that of
+/// the latest one. Serialized as years with two decimals.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Libyears {
+    days: u64,
+}
+
+impl Libyears {
+    pub fn from_days(days: u64) -> Self {
+        Self { days }
+    }
+
+    /// Sum the release lag of the updates, counting a package once however
+    /// many platforms it's outdated on. Where release dates differ between
+    /// platforms, the largest lag is counted.
+    pub fn from_updates(updates: &PlatformUpdates) -> Self {
+        let mut lags: BTreeMap<(&str, &str, &str), u32> = BTreeMap::new();
+        for update in updates.values().flatten() {
+            if let Some(lag) = update.lag_days {
+                let key = (
+                    update.name.as_str(),
+                    update.installed_version.as_str(),
+                    update.latest_version.as_str(),
+                );
+                let entry = lags.entry(key).or_default();
+                *entry = (*entry).max(lag);
+            }
+        }
+        Self {
+            days: lags.values().map(|lag| u64::from(*lag)).sum(),
+        }
+    }
+
+    pub fn days(&self) -> u64 {
+        self.days
+    }
+
+    pub fn years(&self) -> f64 {
+        self.days as f64 / DAYS_PER_YEAR
+    }
+}
+
+impl std::fmt::Display for Libyears {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:.1} libyears", self.years())
+    }
+}
+
+impl Serialize for Libyears {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_f64((self.years() * 100.0).round() / 100.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for Libyears {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let years = f64::deserialize(deserializer)?;
+        Ok(Self {
+            days: (years.max(0.0) * DAYS_PER_YEAR).round() as u64,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::PackageUpdate;
+
+    fn update(name: &str, lag_days: Option<u32>) -> PackageUpdate {
+        PackageUpdate {
+            name: name.to_string(),
+            installed_version: "1.0".to_string(),
+            latest_version: "2.0".to_string(),
+            lag_days,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_from_updates() {
+        let mut updates = PlatformUpdates::new();
+        updates.insert(
+            "linux-64".to_string(),
+            vec![
+                update("numpy", Some(400)),
+                update("python", Some(330)),
+                update("cowsay", None),
+            ],
+        );
+        // The same update on another platform counts once, with its larger lag
+        updates.insert("osx-arm64".to_string(), vec![update("numpy", Some(431))]);
+
+        let libyears = Libyears::from_updates(&updates);
+        assert_eq!(libyears.days(), 761);
+        assert_eq!(libyears.to_string(), "2.1 libyears");
+    }
+
+    #[test]
+    fn test_serialize() {
+        let libyears = Libyears::from_days(761);
+        assert_eq!(serde_json::to_string(&libyears).unwrap(), "2.08");
+        let parsed: Libyears = serde_json::from_str("2.08").unwrap();
+        assert_eq!(parsed.days(), 760);
+        assert_eq!(serde_json::to_string(&Libyears::default()).unwrap(), "0.0");
+    }
+}
@@ -0,0 +1,250 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use rattler_conda_types::RepoData;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+use url::Url;
+
+use crate::conda::version_info;
+use crate::pixi::PackageKind;
+use crate::sources::QueryOutcome;
+use crate::upstream::{PackageKey, SourceResponse, UpstreamInfo, VersionSource};
+
+/// A local channel or package as a `file://` URL, given either as one or
+/// as an absolute path
+pub fn file_url(source: &str) -> Option<Url> {
+    match Url::parse(source) {
+        Ok(url) => (url.scheme() == "file").then_some(url),
+        Err(_) if Path::new(source).is_absolute() => Url::from_file_path(source).ok(),
+        Err(_) => None,
+    }
+}
+
+/// The directory of a local channel, `None` for remote channels
+pub fn channel_path(channel: &str) -> Option<PathBuf> {
+    file_url(channel)?.to_file_path().ok()
+}
+
+/// The versions of a package in a channel laid out on disk, read from the
+/// repodata of its `noarch` and platform subdirs. Package files are reported
+/// at `channel_url`.
+pub fn read_channel(
+    channel_dir: &Path,
+    channel_url: &str,
+    name: &str,
+    platforms: &[&str],
+) -> Result<UpstreamInfo> {
+    let repodata = load_subdirs(channel_dir, platforms)?;
+    Ok(repodata_info(channel_url, name, &subdir_refs(&repodata)))
+}
+
+/// The repodata of a local channel's `noarch` and platform subdirs, from
+/// `repodata.json` or its zstd-compressed `repodata.json.zst`. Subdirs
+/// without either are treated as empty, like a channel that doesn't build
+/// for that platform.
+pub fn load_subdirs(channel_dir: &Path, platforms: &[&str]) -> Result<Vec<(String, RepoData)>> {
+    let mut repodata = Vec::new();
+    for subdir in std::iter::once("noarch").chain(platforms.iter().copied()) {
+        if let Some(subdir_repodata) = load_repodata(&channel_dir.join(subdir))? {
+            repodata.push((subdir.to_string(), subdir_repodata));
+        }
+    }
+    Ok(repodata)
+}
+
+fn subdir_refs(repodata: &[(String, RepoData)]) -> Vec<(&str, &RepoData)> {
+    repodata
+        .iter()
+        .map(|(subdir, repodata)| (subdir.as_str(), repodata))
+        .collect()
+}
+
+/// The versions of a package in the repodata of a channel's subdirs, with
+/// package files reported at `channel_url`
+pub fn repodata_info(
+    channel_url: &str,
+    name: &str,
+    repodata: &[(&str, &RepoData)],
+) -> UpstreamInfo {
+    let channel_url = channel_url.trim_end_matches('/');
+    let records = repodata
+        .iter()
+        .flat_map(|(subdir, repodata)| {
+            repodata
+                .packages
+                .iter()
+                .chain(repodata.conda_packages.iter())
+                .map(move |(file_name, record)| {
+                    (record, format!("{}/{}/{}", channel_url, subdir, file_name))
+                })
+        })
+        .filter(|(record, _)| record.name.as_normalized() == name);
+    UpstreamInfo::Conda(version_info(records))
+}
+
+fn load_repodata(subdir: &Path) -> Result<Option<RepoData>> {
+    let path = subdir.join("repodata.json");
+    let compressed = subdir.join("repodata.json.zst");
+    let content = if path.is_file() {
+        fs::read(&path).with_context(|| format!("Failed to read {}", path.display()))?
+    } else if compressed.is_file() {
+        let file = fs::File::open(&compressed)
+            .with_context(|| format!("Failed to read {}", compressed.display()))?;
+        zstd::decode_all(file)
+            .with_context(|| format!("Failed to decompress {}", compressed.display()))?
+    } else {
+        return Ok(None);
+    };
+    let repodata = serde_json::from_slice(&content)
+        .with_context(|| format!("Failed to parse the repodata in {}", subdir.display()))?;
+    Ok(Some(repodata))
+}
+
+/// Conda channels on the local filesystem, such as those `rattler-build`
+/// writes to, read directly rather than through the repodata gateway
+#[derive(Debug, Clone, Default)]
+pub struct LocalChannelSource;
+
+#[async_trait]
+impl VersionSource for LocalChannelSource {
+    async fn query(
+        &self,
+        keys: &[PackageKey],
+        platforms: &[&str],
+    ) -> Vec<(PackageKey, SourceResponse)> {
+        // Each channel's repodata is read once, for all of its packages
+        let mut channels: BTreeMap<&str, Vec<&PackageKey>> = BTreeMap::new();
+        for key in keys.iter().filter(|key| key.kind == PackageKind::Conda) {
+            if let Some(channel) = key.channel.as_deref() {
+                channels.entry(channel).or_default().push(key);
+            }
+        }
+
+        let mut responses = Vec::new();
+        for (channel, channel_keys) in channels {
+            let Some(dir) = channel_path(channel) else {
+                continue;
+            };
+
+            let start = Instant::now();
+            let subdirs: Vec<String> = platforms.iter().map(|p| p.to_string()).collect();
+            let repodata = tokio::task::spawn_blocking(move || {
+                if !dir.is_dir() {
+                    anyhow::bail!("Local channel {} does not exist", dir.display());
+                }
+                let subdirs: Vec<&str> = subdirs.iter().map(String::as_str).collect();
+                load_subdirs(&dir, &subdirs)
+            })
+            .await
+            .map_err(anyhow::Error::from)
+            .and_then(|repodata| repodata);
+            let elapsed = start.elapsed();
+
+            for key in channel_keys {
+                let info = match repodata {
+                    Ok(ref repodata) => {
+                        Ok(repodata_info(channel, &key.name, &subdir_refs(repodata)))
+                    }
+                    Err(ref e) => Err(anyhow::anyhow!("{:#}", e)),
+                };
+                responses.push((
+                    key.clone(),
+                    SourceResponse {
+                        source_url: channel.to_string(),
+                        elapsed,
+                        outcome: QueryOutcome::Fetched,
+                        bytes: 0,
+                        batched: false,
+                        info,
+                    },
+                ));
+            }
+        }
+
+        responses
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REPODATA: &str = r#"{
+        "info": { "subdir": "noarch" },
+        "packages": {
+            "mylib-1.0-pyh0_0.tar.bz2": {
+                "name": "mylib", "version": "1.0", "build": "pyh0_0",
+                "build_number": 0, "depends": [], "subdir": "noarch"
+            }
+        },
+        "packages.conda": {
+            "mylib-1.2-pyh0_0.conda": {
+                "name": "mylib", "version": "1.2", "build": "pyh0_0",
+                "build_number": 0, "depends": [], "subdir": "noarch"
+            }
+        }
+    }"#;
+
+    #[test]
+    fn test_channel_path() {
+        assert_eq!(
+            channel_path("file:///opt/channel"),
+            Some(PathBuf::from("/opt/channel"))
+        );
+        assert_eq!(
+            channel_path("/opt/channel"),
+            Some(PathBuf::from("/opt/channel"))
+        );
+        assert_eq!(channel_path("https://conda.anaconda.org/conda-forge"), None);
+        assert_eq!(channel_path("conda-forge"), None);
+    }
+
+    #[tokio::test]
+    async fn test_local_channel_source() {
+        let dir = tempfile::tempdir().unwrap();
+        let noarch = dir.path().join("noarch");
+        fs::create_dir_all(&noarch).unwrap();
+        fs::write(noarch.join("repodata.json"), REPODATA).unwrap();
+        let channel = Url::from_directory_path(dir.path()).unwrap().to_string();
+
+        let key = |name: &str, channel: &str| PackageKey {
+            name: name.to_string(),
+            channel: Some(channel.to_string()),
+            kind: PackageKind::Conda,
+        };
+        let keys = [
+            key("mylib", &channel),
+            key("mylib", "file:///no/such/channel"),
+            key("python", "https://conda.anaconda.org/conda-forge"),
+        ];
+        let responses = LocalChannelSource.query(&keys, &["linux-64"]).await;
+        assert_eq!(responses.len(), 2);
+
+        // Responses are grouped by channel
+        let mylib = responses
+            .iter()
+            .find(|(key, _)| key.channel.as_deref() == Some(channel.as_str()));
+        assert_eq!(
+            mylib.unwrap().1.info.as_ref().unwrap().latest_version(),
+            Some("1.2")
+        );
+        let missing = responses
+            .iter()
+            .find(|(key, _)| key.channel.as_deref() == Some("file:///no/such/channel"));
+        assert!(missing.unwrap().1.info.is_err());
+    }
+
+    #[test]
+    fn test_read_channel_zst() {
+        let dir = tempfile::tempdir().unwrap();
+        let linux = dir.path().join("linux-64");
+        fs::create_dir_all(&linux).unwrap();
+        let compressed = zstd::encode_all(REPODATA.as_bytes(), 0).unwrap();
+        fs::write(linux.join("repodata.json.zst"), compressed).unwrap();
+
+        let info = read_channel(dir.path(), "file:///channel", "mylib", &["linux-64"]).unwrap();
+        assert_eq!(info.latest_version(), Some("1.2"));
+    }
+}
@@ -0,0 +1,215 @@
+use anyhow::Result;
+
+/// How numbers are written in human-readable output. JSON, YAML and CSV
+/// always use raw values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Locale {
+    decimal: char,
+    /// Separator between groups of three digits, if numbers are grouped
+    group: Option<char>,
+}
+
+impl Default for Locale {
+    /// English number formatting, e.g. `1,234.5`
+    fn default() -> Self {
+        Self {
+            decimal: '.',
+            group: Some(','),
+        }
+    }
+}
+
+impl Locale {
+    /// Parse a locale such as `de-DE`, `fr_FR.UTF-8` or `C`. Unknown languages
+    /// use English formatting.
+    pub fn parse(tag: &str) -> Result<Self> {
+        let tag = tag.trim();
+        // Drop the encoding and modifier, e.g. `.UTF-8` and `@euro`
+        let tag = tag.split(['.', '@']).next().unwrap_or(tag);
+        let mut parts = tag.split(['-', '_']);
+        let language = parts.next().unwrap_or_default().to_ascii_lowercase();
+        let region = parts.next().unwrap_or_default().to_ascii_uppercase();
+
+        if language.is_empty() || !language.chars().all(|c| c.is_ascii_alphabetic()) {
+            anyhow::bail!(
+                "Invalid locale '{}': expected a language tag like en-US or de_DE.UTF-8",
+                tag
+            );
+        }
+
+        let (decimal, group) = match (language.as_str(), region.as_str()) {
+            ("c" | "posix", _) => ('.', None),
+            (_, "CH" | "LI") => ('.', Some('\u{2019}')),
+            (
+                "de" | "nl" | "it" | "es" | "pt" | "da" | "id" | "tr" | "el" | "ro" | "hr" | "sl"
+                | "sr",
+                _,
+            ) => (',', Some('.')),
+            (
+                "fr" | "ru" | "pl" | "cs" | "sk" | "sv" | "fi" | "nb" | "no" | "uk" | "hu" | "bg"
+                | "et" | "lt" | "lv",
+                _,
+            ) => (',', Some('\u{a0}')),
+            _ => ('.', Some(',')),
+        };
+
+        Ok(Self { decimal, group })
+    }
+
+    /// The locale of the environment, from `LC_ALL`, `LC_NUMERIC` or `LANG`
+    pub fn from_environment() -> Self {
+        ["LC_ALL", "LC_NUMERIC", "LANG"]
+            .iter()
+            .filter_map(|name| std::env::var(name).ok())
+            .find(|value| !value.is_empty())
+            .and_then(|value| Self::parse(&value).ok())
+            .unwrap_or_default()
+    }
+
+    /// A whole number with grouped digits, e.g. `12,345` or `12.345`
+    pub fn format_count(&self, count: u64) -> String {
+        let digits = count.to_string();
+        let Some(group) = self.group else {
+            return digits;
+        };
+
+        let mut formatted = String::with_capacity(digits.len() + digits.len() / 3);
+        for (i, digit) in digits.chars().enumerate() {
+            if i > 0 && (digits.len() - i) % 3 == 0 {
+                formatted.push(group);
+            }
+            formatted.push(digit);
+        }
+        formatted
+    }
+
+    /// A number with one decimal, e.g. `1.5` or `1,5`
+    pub fn format_decimal(&self, value: f64) -> String {
+        let formatted = format!("{:.1}", value);
+        let (whole, fraction) = formatted.split_once('.').unwrap_or((&formatted, "0"));
+        let whole = whole
+            .parse::<u64>()
+            .map_or_else(|_| whole.to_string(), |whole| self.format_count(whole));
+        format!("{}{}{}", whole, self.decimal, fraction)
+    }
+
+    /// A byte count with a decimal unit, e.g. `184 MB` or `1.5 kB`. Values
+    /// below 10 keep one decimal.
+    pub fn format_size(&self, bytes: u64) -> String {
+        const UNITS: [&str; 4] = ["kB", "MB", "GB", "TB"];
+        if bytes < 1000 {
+            return format!("{} B", bytes);
+        }
+
+        let mut value = bytes as f64 / 1000.0;
+        let mut unit = UNITS[0];
+        for next in &UNITS[1..] {
+            if value < 1000.0 {
+                break;
+            }
+            value /= 1000.0;
+            unit = next;
+        }
+
+        if value < 10.0 {
+            format!("{} {}", self.format_decimal(value), unit)
+        } else {
+            format!("{} {}", self.format_count(value.round() as u64), unit)
+        }
+    }
+
+    /// A change in size with its sign, e.g. `+2.5 MB` or `-640 kB`
+    pub fn format_size_delta(&self, delta: i64) -> String {
+        let sign = if delta < 0 { '-' } else { '+' };
+        format!("{}{}", sign, self.format_size(delta.unsigned_abs()))
+    }
+
+    /// How long ago something happened, e.g. `3 weeks ago`
+    pub fn format_age(&self, days: u32) -> String {
+        let (count, unit) = match days {
+            0 => return "today".to_string(),
+            1 => return "yesterday".to_string(),
+            2..=13 => (days, "day"),
+            14..=59 => (days / 7, "week"),
+            60..=364 => (days / 30, "month"),
+            _ => (days / 365, "year"),
+        };
+        let plural = if count == 1 { "" } else { "s" };
+        format!(
+            "{} {}{} ago",
+            self.format_count(u64::from(count)),
+            unit,
+            plural
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(Locale::parse("en-US").unwrap(), Locale::default());
+        assert_eq!(
+            Locale::parse("de_DE.UTF-8").unwrap(),
+            Locale {
+                decimal: ',',
+                group: Some('.')
+            }
+        );
+        assert_eq!(
+            Locale::parse("C").unwrap(),
+            Locale {
+                decimal: '.',
+                group: None
+            }
+        );
+        assert_eq!(Locale::parse("xx").unwrap(), Locale::default());
+        assert!(Locale::parse("").is_err());
+        assert!(Locale::parse("1234").is_err());
+    }
+
+    #[test]
+    fn test_format_count() {
+        let en = Locale::default();
+        assert_eq!(en.format_count(0), "0");
+        assert_eq!(en.format_count(999), "999");
+        assert_eq!(en.format_count(1234), "1,234");
+        assert_eq!(en.format_count(1234567), "1,234,567");
+        assert_eq!(
+            Locale::parse("de").unwrap().format_count(1234567),
+            "1.234.567"
+        );
+        assert_eq!(
+            Locale::parse("fr").unwrap().format_count(1234),
+            "1\u{a0}234"
+        );
+        assert_eq!(Locale::parse("C").unwrap().format_count(1234), "1234");
+    }
+
+    #[test]
+    fn test_format_size() {
+        let en = Locale::default();
+        assert_eq!(en.format_size(512), "512 B");
+        assert_eq!(en.format_size(1536), "1.5 kB");
+        assert_eq!(en.format_size(184_000_000), "184 MB");
+        assert_eq!(en.format_size(2_500_000_000), "2.5 GB");
+        assert_eq!(en.format_size(1_234_000_000_000_000), "1,234 TB");
+        assert_eq!(Locale::parse("de").unwrap().format_size(1536), "1,5 kB");
+        assert_eq!(en.format_size_delta(2_500_000), "+2.5 MB");
+        assert_eq!(en.format_size_delta(-640_000), "-640 kB");
+    }
+
+    #[test]
+    fn test_format_age() {
+        let en = Locale::default();
+        assert_eq!(en.format_age(0), "today");
+        assert_eq!(en.format_age(1), "yesterday");
+        assert_eq!(en.format_age(5), "5 days ago");
+        assert_eq!(en.format_age(21), "3 weeks ago");
+        assert_eq!(en.format_age(90), "3 months ago");
+        assert_eq!(en.format_age(365), "1 year ago");
+        assert_eq!(en.format_age(800), "2 years ago");
+    }
+}
@@ -1,64 +1,418 @@
+use std::io::{IsTerminal, Read};
 use std::path::PathBuf;
+use std::process::ExitCode;
 
-use anyhow::Result;
-use clap::Parser;
+use anyhow::{Context, Result};
+use clap::{Args, Parser, Subcommand};
 use pixi_config::ConfigCli;
 use pixi_core::{
     environment::LockFileUsage, repodata::Repodata, workspace::DiscoveryStart,
     UpdateLockFileOptions, WorkspaceLocator,
 };
 use pixi_manifest::FeaturesExt;
-use serde::Serialize;
-
-#[derive(Debug, Serialize, Clone, serde::Deserialize)]
-struct PackageUpdate {
-    name: String,
-    installed_version: String,
-    latest_version: String,
-}
+use pixi_outdated::oneline::OnelineSummary;
+use pixi_outdated::plan::{LockedVersions, PlannedUpdate, UpdatePlan};
+use pixi_outdated::report::{
+    CheckStatus, DisallowedPackage, HeldBackPackage, LabeledPackage, LockedIssue, LockedStatus,
+    OtherChannelRelease, OutputFormat, PackageUpdate, PlatformUpdates, Report, RunStatus,
+    UncheckedPackage, DISALLOWED_CHANNEL_EXIT_CODE, OUTDATED_DIRECT_EXIT_CODE, OUTDATED_EXIT_CODE,
+    VULNERABLE_EXIT_CODE,
+};
+use pixi_outdated::sources::QueryOutcome;
+use pixi_outdated::upstream::{PackageKey, UpstreamInfo, VersionLookup, VersionSource};
 
-#[derive(Parser, Debug)]
+#[derive(Parser, Debug, Clone)]
 #[command(
     name = "pixi-outdated",
     version,
     about = "Check for outdated dependencies in pixi projects",
-    long_about = "A CLI tool to determine out-of-date dependencies in pixi.toml/pyproject.toml and pixi.lock files"
+    long_about = "A CLI tool to determine out-of-date dependencies in pixi.toml/pyproject.toml and pixi.lock files",
+    args_conflicts_with_subcommands = true,
+    args_override_self = true
 )]
 struct Cli {
-    /// Specific package names to check (if not provided, checks all packages)
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Package names or glob patterns (e.g. 'jupyter*') to check (if not
+    /// provided, checks all packages)
     packages: Vec<String>,
 
+    /// Only check packages whose name matches this regular expression (can
+    /// be repeated)
+    #[arg(long, value_name = "REGEX")]
+    regex: Vec<String>,
+
     /// Only check packages explicitly listed in pixi.toml (not transitive dependencies)
     #[arg(short = 'x', long)]
     explicit: bool,
 
+    /// Leave out packages matching this glob pattern (e.g. 'py*') without
+    /// querying them (can be repeated)
+    #[arg(long, value_name = "PATTERN")]
+    exclude: Vec<String>,
+
+    /// Only check dependencies whose manifest requirement or locked version
+    /// changed since this git revision (e.g. origin/main)
+    #[arg(long, value_name = "REV")]
+    changed_since: Option<String>,
+
+    /// Exit with code 2 if a dependency declared in the manifest is outdated.
+    /// Outdated transitive dependencies are still reported, but don't fail the run.
+    #[arg(long)]
+    fail_on_direct: bool,
+
     /// The environment to check (defaults to the default environment)
     #[arg(short = 'e', long)]
     environment: Option<String>,
 
+    /// Check every environment in the lockfile, querying packages they share
+    /// only once
+    #[arg(
+        long,
+        conflicts_with_all = [
+            "environment", "update", "interactive", "json_out", "markdown_out",
+            "report_html", "junit_out", "plan_out", "audit_log", "hash_file", "pins_out"
+        ]
+    )]
+    all_environments: bool,
+
     /// The platform to check (if not specified, checks all common platforms)
     #[arg(short = 'p', long)]
     platform: Option<String>,
 
-    /// Output in JSON format
-    #[arg(short, long)]
+    /// Flag packages whose installed version is older than this (e.g. 90d, 12w, 1y)
+    /// while a newer release exists
+    #[arg(long, value_name = "AGE", value_parser = pixi_outdated::age::parse_max_lag)]
+    max_lag: Option<u32>,
+
+    /// Link each update to its release notes: the changelog a PyPI project
+    /// names, or the GitHub releases of a conda package's repository
+    #[arg(long)]
+    release_notes: bool,
+
+    /// Look up the locked PyPI and Python conda packages in the OSV
+    /// vulnerability database, and exit with 9 if any has a known vulnerability
+    #[arg(long)]
+    audit: bool,
+
+    /// Print the `pixi upgrade`, `pixi add` and `pixi update` commands that
+    /// apply every reported update
+    #[arg(long)]
+    suggest_commands: bool,
+
+    /// Solve the environment with each conda update pinned, on each platform
+    /// it's outdated on, and mark the updates that leave it unsolvable
+    #[arg(long)]
+    verify_solvable: bool,
+
+    /// Check only this package and name the requirements keeping it from its
+    /// latest version
+    #[arg(long, value_name = "PACKAGE", conflicts_with_all = ["packages", "regex"])]
+    explain: Option<String>,
+
+    /// Post a summary to this webhook URL when the run finds updates, e.g. a
+    /// Slack incoming webhook
+    #[arg(
+        long,
+        value_name = "URL",
+        env = "PIXI_OUTDATED_WEBHOOK",
+        hide_env_values = true
+    )]
+    notify_webhook: Option<String>,
+
+    /// The payload to post to `--notify-webhook`; Slack for hooks.slack.com
+    /// URLs and JSON otherwise, unless given
+    #[arg(long, value_enum, value_name = "FORMAT", requires = "notify_webhook")]
+    notify_format: Option<pixi_outdated::webhook::WebhookFormat>,
+
+    /// Print every candidate version considered for this package and why it
+    /// was or wasn't chosen as the latest version
+    #[arg(long, value_name = "PACKAGE")]
+    why_latest: Option<String>,
+
+    /// Consider pre-releases (alpha, beta, rc and dev versions) for the latest
+    /// version, for every package or only the given ones (`--pre=numpy,scipy`)
+    #[arg(
+        long,
+        value_name = "PACKAGE",
+        num_args = 0..,
+        require_equals = true,
+        value_delimiter = ','
+    )]
+    pre: Option<Vec<String>>,
+
+    /// Only report updates of the given severity (can be repeated)
+    #[arg(long, value_enum, value_name = "SEVERITY")]
+    filter: Vec<pixi_outdated::severity::Severity>,
+
+    /// Show outdated packages in the environment's dependency tree, with
+    /// subtrees that are up to date collapsed
+    #[arg(long, conflicts_with_all = ["format", "json", "risk_profile", "all_environments"])]
+    tree: bool,
+
+    /// Group updates by how risky they are to apply, using the given profile
+    #[arg(long, value_enum, value_name = "PROFILE")]
+    risk_profile: Option<pixi_outdated::risk::RiskProfile>,
+
+    /// Label each update as auto-mergeable or review-required, using the rules
+    /// in `[tool.pixi-outdated.auto-merge]`
+    #[arg(long)]
+    auto_merge: bool,
+
+    /// Bump the version requirements of outdated dependencies in the manifest
+    #[arg(long, visible_alias = "write")]
+    update: bool,
+
+    /// Pick the outdated packages to update from a checklist, then bump their
+    /// requirements in the manifest and update the lockfile
+    #[arg(long, conflicts_with_all = ["update", "format", "json"])]
+    interactive: bool,
+
+    /// With --update, print the manifest changes as a unified diff instead of writing them
+    #[arg(long, requires = "update")]
+    dry_run: bool,
+
+    /// Relock the outdated packages like `pixi update`, limited to those whose
+    /// manifest requirement admits the latest version, or to all of them
+    /// along with --update
+    #[arg(long, conflicts_with_all = ["interactive", "dry_run"])]
+    write_lockfile: bool,
+
+    /// Print per-source timings and the amount of metadata downloaded at the end of the run
+    #[arg(long)]
+    timings: bool,
+
+    /// Query this package index for PyPI packages instead of PyPI or the
+    /// `index-url` from the manifest (e.g. an internal mirror)
+    #[arg(long, value_name = "URL")]
+    index_url: Option<String>,
+
+    /// Send requests to channels and indexes through this proxy instead of
+    /// the one in `HTTPS_PROXY`/`HTTP_PROXY`
+    #[arg(long, value_name = "URL")]
+    proxy: Option<url::Url>,
+
+    /// How long PyPI responses are reused from the on-disk cache (e.g. 30m,
+    /// 6h, 1d); 0 disables the cache
+    #[arg(
+        long,
+        value_name = "DURATION",
+        default_value = "6h",
+        value_parser = pixi_outdated::cache::parse_ttl
+    )]
+    cache_ttl: std::time::Duration,
+
+    /// How many packages to query at once (defaults to pixi's
+    /// `concurrency.downloads` setting)
+    #[arg(long, value_name = "N")]
+    jobs: Option<std::num::NonZeroUsize>,
+
+    /// How many times to try a query that fails with a rate limit, server
+    /// error or timeout before reporting it as failed
+    #[arg(long, value_name = "N", default_value_t = std::num::NonZeroU32::new(pixi_outdated::retry::DEFAULT_MAX_ATTEMPTS).unwrap())]
+    max_attempts: std::num::NonZeroU32,
+
+    /// Give up on a request to a channel or index after this many seconds
+    /// (per attempt, see --max-attempts)
+    #[arg(long, value_name = "SECS", value_parser = clap::value_parser!(u64).range(1..))]
+    timeout: Option<u64>,
+
+    /// Query every package again instead of resuming an interrupted run
+    #[arg(long)]
+    no_resume: bool,
+
+    /// Output format
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    format: OutputFormat,
+
+    /// Format numbers and sizes in text output for this locale (e.g. de-DE);
+    /// defaults to LC_ALL, LC_NUMERIC or LANG
+    #[arg(long, value_name = "LOCALE", value_parser = pixi_outdated::locale::Locale::parse)]
+    locale: Option<pixi_outdated::locale::Locale>,
+
+    /// Output in JSON format (shorthand for `--format json`)
+    #[arg(short, long, conflicts_with = "format")]
     json: bool,
 
     /// Verbose output
     #[arg(short, long)]
     verbose: bool,
 
+    /// Where to look up the latest versions. `--source fixtures=<DIR>` reads
+    /// repodata.json and PyPI JSON files from DIR instead of the network.
+    #[arg(long, value_name = "BACKEND=DIR")]
+    source: Option<String>,
+
+    /// Check the packages given as a JSON array of `{name, version, kind,
+    /// channel}` objects on stdin instead of a workspace, e.g. the output of
+    /// `pixi list --json`
+    #[arg(
+        long,
+        conflicts_with_all = [
+            "all_environments", "changed_since", "update", "interactive",
+            "plan_out", "pins_out"
+        ]
+    )]
+    stdin_packages: bool,
+
+    /// Check the tools installed with `pixi global` instead of a workspace,
+    /// one report per global environment. The pixi home is `$PIXI_HOME`, or
+    /// `~/.pixi` if unset.
+    #[arg(
+        long,
+        conflicts_with_all = [
+            "stdin_packages", "all_environments", "changed_since", "update", "interactive",
+            "json_out", "markdown_out", "report_html", "junit_out", "plan_out", "audit_log",
+            "hash_file", "pins_out"
+        ]
+    )]
+    global: bool,
+
+    /// Check every pixi workspace found under DIR (the current directory if
+    /// not given), printing a combined report grouped by project
+    #[arg(
+        long,
+        value_name = "DIR",
+        num_args = 0..=1,
+        default_missing_value = ".",
+        conflicts_with_all = [
+            "stdin_packages", "global", "all_environments", "manifest", "changed_since",
+            "update", "interactive", "json_out", "markdown_out", "report_html", "junit_out",
+            "plan_out", "audit_log", "hash_file", "pins_out"
+        ]
+    )]
+    recursive: Option<PathBuf>,
+
+    /// Run the hooks of the workspaces `--recursive` finds, which run
+    /// commands from their manifests and are skipped otherwise
+    #[arg(long, requires = "recursive")]
+    run_hooks: bool,
+
+    /// Exit successfully even if there was nothing to check (empty environment,
+    /// all packages skipped or no lockfile)
+    #[arg(long)]
+    allow_empty: bool,
+
+    /// Exit with code 0 if the report is the same as on the previous run, as
+    /// recorded in `--hash-file`, whatever it found, so schedulers only act
+    /// on findings once
+    #[arg(long, requires = "hash_file")]
+    if_changed_exit_zero: bool,
+
     /// Path to the pixi.toml file (defaults to current directory)
-    #[arg(short = 'f', long)]
+    #[arg(short = 'f', long, global = true)]
     manifest: Option<PathBuf>,
 
+    #[clap(flatten)]
+    output_files: OutputFiles,
+
     #[clap(flatten)]
     pub config: ConfigCli,
 }
 
+/// Additional report files written alongside the stdout output
+#[derive(Args, Debug, Clone, Default)]
+struct OutputFiles {
+    /// Also write the JSON report to this file
+    #[arg(long, value_name = "PATH")]
+    json_out: Option<PathBuf>,
+
+    /// Also write a markdown summary to this file
+    #[arg(long, value_name = "PATH")]
+    markdown_out: Option<PathBuf>,
+
+    /// Also write a self-contained HTML report to this file
+    #[arg(long, value_name = "PATH")]
+    report_html: Option<PathBuf>,
+
+    /// Also write the updates as JUnit XML to this file, for the test views of CI systems
+    #[arg(long, value_name = "PATH")]
+    junit_out: Option<PathBuf>,
+
+    /// Also write an update plan to this file, to be applied later with `pixi-outdated apply`
+    #[arg(long, value_name = "PATH")]
+    plan_out: Option<PathBuf>,
+
+    /// Append a one-line JSON summary of the run to this file
+    #[arg(long, value_name = "PATH")]
+    audit_log: Option<PathBuf>,
+
+    /// Write the content hash of the report to this file
+    #[arg(long, value_name = "PATH")]
+    hash_file: Option<PathBuf>,
+
+    /// Write the URLs and sha256 hashes of the files each update would
+    /// download to this file, for mirroring them ahead of the update
+    #[arg(long, value_name = "PATH")]
+    pins_out: Option<PathBuf>,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+enum Command {
+    /// Update the lockfile to the versions recorded in an update plan
+    Apply(ApplyArgs),
+    /// Merge JSON reports from many projects into one summary
+    Aggregate(AggregateArgs),
+    /// Print a sample report of a bundled example workspace, without network access
+    Demo(DemoArgs),
+}
+
+#[derive(Args, Debug, Clone)]
+struct ApplyArgs {
+    /// The update plan written by `--plan-out`
+    #[arg(long, value_name = "PATH")]
+    plan: PathBuf,
+}
+
+#[derive(Args, Debug, Clone)]
+struct AggregateArgs {
+    /// JSON reports written by `--json` or `--json-out`. Each report's parent
+    /// directory is used as its team name.
+    #[arg(required = true, value_name = "REPORT")]
+    reports: Vec<PathBuf>,
+
+    /// Number of projects and packages to list in text output
+    #[arg(long, default_value_t = 10)]
+    top: usize,
+
+    /// Output in JSON format
+    #[arg(short, long)]
+    json: bool,
+}
+
+#[derive(Args, Debug, Clone)]
+struct DemoArgs {
+    /// Output format
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    format: OutputFormat,
+}
+
 #[tokio::main]
-async fn main() -> Result<()> {
-    let cli = Cli::parse();
+async fn main() -> Result<ExitCode> {
+    let mut cli = Cli::parse();
+
+    // Options from `default-args` in the project config go ahead of those on
+    // the command line, which override them
+    if cli.command.is_none() && !cli.stdin_packages && !cli.global && cli.recursive.is_none() {
+        let default_args = default_args(cli.manifest.as_deref())?;
+        if !default_args.is_empty() {
+            let mut args = std::env::args_os();
+            cli = Cli::parse_from(
+                args.next()
+                    .into_iter()
+                    .chain(default_args.into_iter().map(Into::into))
+                    .chain(args),
+            );
+        }
+    }
+
+    // Explaining the latest version of a package only requires checking that package
+    if let Some(ref package) = cli.why_latest {
+        if cli.packages.is_empty() {
+            cli.packages.push(package.clone());
+        }
+    }
 
     // Initialize tracing if verbose mode is enabled
     if cli.verbose {
@@ -71,6 +425,27 @@ async fn main() -> Result<()> {
             .init();
     }
 
+    if cli.json {
+        cli.format = OutputFormat::Json;
+    }
+
+    match cli.command.take() {
+        Some(Command::Apply(args)) => {
+            apply_plan(&args.plan, cli.manifest, cli.config, cli.proxy.as_ref()).await?;
+            return Ok(ExitCode::SUCCESS);
+        }
+        Some(Command::Aggregate(args)) => {
+            aggregate_reports(&args)?;
+            return Ok(ExitCode::SUCCESS);
+        }
+        Some(Command::Demo(args)) => {
+            cli.format = args.format;
+            print_demo_report(&cli).await?;
+            return Ok(ExitCode::SUCCESS);
+        }
+        None => {}
+    }
+
     if cli.verbose {
         println!("Running pixi-outdated with options:");
         if let Some(ref manifest) = cli.manifest {
@@ -83,35 +458,477 @@ async fn main() -> Result<()> {
         if let Some(ref platform) = cli.platform {
             println!("  Platform: {}", platform);
         }
-        println!("  JSON output: {}", cli.json);
-        if !cli.packages.is_empty() {
-            println!("  Checking packages: {}", cli.packages.join(", "));
+        if let Some(max_lag) = cli.max_lag {
+            println!("  Max lag: {} days", max_lag);
+        }
+        match cli.pre {
+            Some(ref packages) if packages.is_empty() => println!("  Pre-releases: all packages"),
+            Some(ref packages) => println!("  Pre-releases: {}", packages.join(", ")),
+            None => {}
+        }
+        println!("  Output format: {:?}", cli.format);
+        if let Some(ref path) = cli.output_files.json_out {
+            println!("  JSON report: {}", path.display());
+        }
+        if let Some(ref path) = cli.output_files.markdown_out {
+            println!("  Markdown report: {}", path.display());
+        }
+        if let Some(ref path) = cli.output_files.plan_out {
+            println!("  Update plan: {}", path.display());
+        }
+        if let Some(ref path) = cli.output_files.audit_log {
+            println!("  Audit log: {}", path.display());
+        }
+        if let Some(ref path) = cli.output_files.hash_file {
+            println!("  Content hash: {}", path.display());
+        }
+        if !cli.packages.is_empty() || !cli.regex.is_empty() {
+            let patterns: Vec<String> = cli
+                .packages
+                .iter()
+                .cloned()
+                .chain(cli.regex.iter().map(|regex| format!("/{}/", regex)))
+                .collect();
+            println!("  Checking packages: {}", patterns.join(", "));
         } else {
             println!("  Checking all packages");
         }
+        if !cli.exclude.is_empty() {
+            println!("  Excluding: {}", cli.exclude.join(", "));
+        }
         println!();
     }
 
-    run(cli).await
+    let allow_empty = cli.allow_empty;
+    let fail_on_direct = cli.fail_on_direct;
+    let oneline = cli.format == OutputFormat::Oneline;
+    // Read before the run replaces it
+    let previous_hash = match cli.output_files.hash_file {
+        Some(ref path) if cli.if_changed_exit_zero => std::fs::read_to_string(path)
+            .ok()
+            .map(|hash| hash.trim().to_string()),
+        _ => None,
+    };
+    if cli.all_environments {
+        return check_all_environments(cli, fail_on_direct, oneline, allow_empty).await;
+    }
+    if cli.global {
+        return check_global_tools(cli, fail_on_direct, oneline, allow_empty).await;
+    }
+    if let Some(root) = cli.recursive.take() {
+        return check_workspaces(&root, cli, fail_on_direct, oneline, allow_empty).await;
+    }
+
+    let webhook = webhook(&cli);
+    let report = if cli.stdin_packages {
+        check_stdin_packages(&cli).await?
+    } else {
+        run(cli, &mut RunContext::default()).await?
+    };
+    // An unchanged report was already notified about
+    let unchanged = previous_hash.is_some() && previous_hash == report.content_hash;
+    if let Some(ref webhook) = webhook.filter(|_| !unchanged) {
+        let environment = report
+            .metadata
+            .as_ref()
+            .map_or("default", |metadata| metadata.environment.as_str());
+        notify_webhook(webhook, [(environment, &report)]).await;
+    }
+
+    if unchanged {
+        eprintln!("Report unchanged since the previous run");
+        Ok(ExitCode::SUCCESS)
+    } else if !report.disallowed_channels.is_empty() {
+        Ok(ExitCode::from(DISALLOWED_CHANNEL_EXIT_CODE))
+    } else if !report.vulnerabilities.is_empty() {
+        Ok(ExitCode::from(VULNERABLE_EXIT_CODE))
+    } else if fail_on_direct && !report.outdated_direct().is_empty() {
+        Ok(ExitCode::from(OUTDATED_DIRECT_EXIT_CODE))
+    } else if oneline && !OnelineSummary::from_reports([&report]).is_empty() {
+        Ok(ExitCode::from(OUTDATED_EXIT_CODE))
+    } else if allow_empty {
+        Ok(ExitCode::SUCCESS)
+    } else {
+        Ok(ExitCode::from(report.status.exit_code()))
+    }
 }
 
-async fn run(cli: Cli) -> Result<()> {
-    // Determine which platforms to check
-    let config = cli.config;
-    let manifest_search_path = match cli.manifest {
-        Some(path) => DiscoveryStart::ExplicitManifest(path.clone()),
-        None => DiscoveryStart::CurrentDir,
+/// The `default-args` of the project config, read from the directory of the
+/// given manifest, or else the closest directory with a manifest or config
+/// file, the way pixi finds the workspace
+fn default_args(manifest: Option<&std::path::Path>) -> Result<Vec<String>> {
+    let start = match manifest {
+        Some(path) if path.is_file() => path.parent().map(std::path::Path::to_path_buf),
+        Some(path) => Some(path.to_path_buf()),
+        None => std::env::current_dir().ok(),
+    };
+    let Some(start) = start else {
+        return Ok(Vec::new());
     };
+    for dir in start.ancestors() {
+        let manifest_path = manifest
+            .filter(|path| path.is_file())
+            .map(std::path::Path::to_path_buf)
+            .or_else(|| pixi_outdated::parser::find_manifest(dir));
+        if manifest_path.is_some() || dir.join(pixi_outdated::config::CONFIG_FILE_NAME).is_file() {
+            let config =
+                pixi_outdated::config::ToolConfig::discover(dir, manifest_path.as_deref())?;
+            return Ok(config.default_args);
+        }
+    }
+    Ok(Vec::new())
+}
 
-    let workspace = WorkspaceLocator::for_cli()
-        .with_search_start(manifest_search_path)
-        .locate()?
-        .with_cli_config(config);
+/// State shared by the environments checked in one invocation
+#[derive(Default)]
+struct RunContext {
+    /// Answers of earlier environments, keyed by the platforms they were
+    /// queried for
+    lookups: std::collections::HashMap<
+        Vec<String>,
+        std::collections::HashMap<PackageKey, VersionLookup>,
+    >,
+    /// Leave printing reports to the caller, which combines them
+    combined_output: bool,
+    /// The repodata gateway of each workspace root, whose in-memory
+    /// repodata later environments of the workspace share. Workspaces get
+    /// their own, built from their own config (mirrors, auth, cache).
+    gateways: std::collections::HashMap<std::path::PathBuf, WorkspaceGateway>,
+    /// Leave out the hooks workspaces configure, e.g. those of workspaces
+    /// found by `--recursive` that the user didn't opt into
+    skip_hooks: bool,
+    /// The caller already ran the pre hooks, once for all environments
+    pre_hooks_done: bool,
+}
+
+/// A workspace's repodata gateway, with the channel subdirs whose repodata
+/// is already loaded into it
+#[derive(Clone)]
+struct WorkspaceGateway {
+    gateway: rattler_repodata_gateway::Gateway,
+    warmed_subdirs: std::collections::BTreeSet<(String, String)>,
+}
+
+/// Check every environment in the lockfile in turn and print their reports
+/// together
+async fn check_all_environments(
+    cli: Cli,
+    fail_on_direct: bool,
+    oneline: bool,
+    allow_empty: bool,
+) -> Result<ExitCode> {
+    let environments = lockfile_environments(cli.manifest.clone(), cli.config.clone())?;
+
+    // The pre hooks prepare the workspace, so they run once rather than for
+    // every environment
+    let workspace = locate_workspace(cli.manifest.clone(), cli.config.clone(), None)?;
+    let manifest_path = cli
+        .manifest
+        .clone()
+        .filter(|path| path.is_file())
+        .or_else(|| pixi_outdated::parser::find_manifest(workspace.root()));
+    let tool_config =
+        pixi_outdated::config::ToolConfig::discover(workspace.root(), manifest_path.as_deref())?;
+    run_pre_hooks(
+        &tool_config.hooks,
+        workspace.root(),
+        manifest_path.as_deref(),
+        &environments,
+    )?;
+
+    let mut context = RunContext {
+        combined_output: true,
+        pre_hooks_done: true,
+        ..Default::default()
+    };
+    let mut reports = pixi_outdated::report::EnvironmentReports::default();
+    for environment in &environments {
+        let environment_cli = Cli {
+            environment: Some(environment.clone()),
+            all_environments: false,
+            ..cli.clone()
+        };
+        let report = run(environment_cli, &mut context).await?;
+        reports.environments.insert(environment.clone(), report);
+    }
+    let mut spreads = version_spreads(&cli, &environments)?;
+    pixi_outdated::spread::consolidate_on_latest(&mut spreads, &reports);
+    reports.version_spreads = spreads;
+
+    let locale = locale(&cli);
+    if let Some(rendered) = reports.render(cli.format, &locale)? {
+        println!("{}", rendered.trim_end());
+    } else {
+        // Updates every environment needs are listed once, ahead of the
+        // environments' own sections
+        let shared = reports.shared_updates();
+        let platforms: Vec<String> = reports
+            .environments
+            .values()
+            .flat_map(|report| report.platforms.keys().cloned())
+            .collect::<std::collections::BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        if !shared.is_empty() {
+            println!("##### All Environments #####");
+            print_updates(
+                &shared,
+                &platforms,
+                cli.platform.is_none(),
+                cli.risk_profile.is_some(),
+                &locale,
+            );
+            println!();
+        }
+        for (index, environment) in environments.iter().enumerate() {
+            if index > 0 {
+                println!();
+            }
+            println!("##### Environment: {} #####", environment);
+            let report = reports.environments[environment].without_updates(&shared);
+            let platforms: Vec<String> = report.platforms.keys().cloned().collect();
+            print_report(&report, &cli, &platforms, &Default::default(), locale)?;
+        }
+        if !reports.version_spreads.is_empty() {
+            println!("\n##### Versions differing between environments #####");
+            for spread in &reports.version_spreads {
+                println!("{}", spread);
+            }
+        }
+    }
+
+    if let Some(ref webhook) = webhook(&cli) {
+        let named = reports
+            .environments
+            .iter()
+            .map(|(name, report)| (name.as_str(), report));
+        notify_webhook(webhook, named).await;
+    }
+    Ok(combined_exit_code(
+        reports.exit_code(),
+        reports.environments.values(),
+        fail_on_direct,
+        oneline,
+        allow_empty,
+    ))
+}
+
+/// Check every workspace under `root` in turn and print their reports
+/// together, grouped by project
+async fn check_workspaces(
+    root: &std::path::Path,
+    cli: Cli,
+    fail_on_direct: bool,
+    oneline: bool,
+    allow_empty: bool,
+) -> Result<ExitCode> {
+    let manifests = pixi_outdated::workspaces::find_workspaces(root)?;
+    if manifests.is_empty() {
+        anyhow::bail!("No pixi workspaces found under {}", root.display());
+    }
+
+    // Packages shared by several workspaces are only queried once, each
+    // workspace fetching repodata through its own gateway
+    let mut context = RunContext {
+        combined_output: true,
+        skip_hooks: !cli.run_hooks,
+        ..Default::default()
+    };
+    let mut reports = pixi_outdated::report::ProjectReports::default();
+    for manifest in &manifests {
+        let project = pixi_outdated::workspaces::project_name(root, manifest);
+        let project_cli = Cli {
+            manifest: Some(manifest.clone()),
+            ..cli.clone()
+        };
+        // A workspace that fails to load or check gets a failed section
+        // rather than aborting the others
+        let report = match run(project_cli, &mut context)
+            .await
+            .with_context(|| format!("Failed to check {}", manifest.display()))
+        {
+            Ok(report) => report,
+            Err(error) => {
+                eprintln!("Error: {:#}", error);
+                Report::failed(&error)
+            }
+        };
+        reports.projects.insert(project, report);
+    }
+
+    let locale = locale(&cli);
+    if let Some(rendered) = reports.render(cli.format, &locale)? {
+        println!("{}", rendered.trim_end());
+    } else {
+        for (index, (project, report)) in reports.projects.iter().enumerate() {
+            if index > 0 {
+                println!();
+            }
+            println!("##### Project: {} #####", project);
+            let platforms: Vec<String> = report.platforms.keys().cloned().collect();
+            print_report(report, &cli, &platforms, &Default::default(), locale)?;
+        }
+    }
+
+    if let Some(ref webhook) = webhook(&cli) {
+        let named = reports
+            .projects
+            .iter()
+            .map(|(name, report)| (name.as_str(), report));
+        notify_webhook(webhook, named).await;
+    }
+    Ok(combined_exit_code(
+        reports.exit_code(),
+        reports.projects.values(),
+        fail_on_direct,
+        oneline,
+        allow_empty,
+    ))
+}
+
+/// The exit code of a run checking several environments or workspaces,
+/// given the exit code their reports combine to
+fn combined_exit_code<'a>(
+    exit_code: u8,
+    reports: impl IntoIterator<Item = &'a Report>,
+    fail_on_direct: bool,
+    oneline: bool,
+    allow_empty: bool,
+) -> ExitCode {
+    let reports: Vec<&Report> = reports.into_iter().collect();
+    let outdated_direct = reports
+        .iter()
+        .any(|report| !report.outdated_direct().is_empty());
+    let failed = reports
+        .iter()
+        .any(|report| report.status == RunStatus::Failed);
+    if failed
+        || matches!(
+            exit_code,
+            DISALLOWED_CHANNEL_EXIT_CODE | VULNERABLE_EXIT_CODE
+        )
+    {
+        ExitCode::from(exit_code)
+    } else if fail_on_direct && outdated_direct {
+        ExitCode::from(OUTDATED_DIRECT_EXIT_CODE)
+    } else if oneline && !OnelineSummary::from_reports(reports).is_empty() {
+        ExitCode::from(OUTDATED_EXIT_CODE)
+    } else if allow_empty {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::from(exit_code)
+    }
+}
+
+/// Check the environments of `pixi global`, printing a section per
+/// environment like `--all-environments`
+async fn check_global_tools(
+    cli: Cli,
+    fail_on_direct: bool,
+    oneline: bool,
+    allow_empty: bool,
+) -> Result<ExitCode> {
+    let home = pixi_outdated::global::pixi_home(|name| std::env::var(name).ok())
+        .context("Could not find the pixi home: set PIXI_HOME")?;
+    let options = pixi_outdated::check::CheckOptions {
+        platforms: cli.platform.iter().cloned().collect(),
+        explicit: cli.explicit,
+        fixtures: fixtures_dir(cli.source.as_deref())?,
+        selection: standalone_selection(&cli, &home)?,
+        network: Some(network_config(&cli)?),
+        ..Default::default()
+    };
+    let reports = pixi_outdated::check::check_global(&home, &options).await?;
+
+    let locale = locale(&cli);
+    if let Some(rendered) = reports.render(cli.format, &locale)? {
+        println!("{}", rendered.trim_end());
+    } else {
+        for (index, (environment, report)) in reports.environments.iter().enumerate() {
+            if index > 0 {
+                println!();
+            }
+            println!("##### Global environment: {} #####", environment);
+            let platforms: Vec<String> = report.platforms.keys().cloned().collect();
+            print_report(report, &cli, &platforms, &Default::default(), locale)?;
+        }
+    }
+
+    if let Some(ref webhook) = webhook(&cli) {
+        let named = reports
+            .environments
+            .iter()
+            .map(|(name, report)| (name.as_str(), report));
+        notify_webhook(webhook, named).await;
+    }
+    Ok(combined_exit_code(
+        reports.exit_code(),
+        reports.environments.values(),
+        fail_on_direct,
+        oneline,
+        allow_empty,
+    ))
+}
+
+async fn run(cli: Cli, context: &mut RunContext) -> Result<Report> {
+    // Structured formats are printed in one piece at the end, without progress output
+    let structured = cli.format != OutputFormat::Text;
+    let locale = locale(&cli);
+
+    // Validate the requested platform before doing any work
+    let requested_platform = cli
+        .platform
+        .as_deref()
+        .map(pixi_outdated::platform::parse_platform)
+        .transpose()?;
+
+    if cli.interactive && !std::io::stdin().is_terminal() {
+        anyhow::bail!("--interactive requires a terminal");
+    }
+
+    let fixtures_dir = fixtures_dir(cli.source.as_deref())?;
+    let packages = match cli.explain {
+        Some(ref name) => {
+            pixi_outdated::select::PackageSelection::new(std::slice::from_ref(name), &[])?
+        }
+        None => pixi_outdated::select::PackageSelection::new(&cli.packages, &cli.regex)?,
+    };
+
+    // Determine which platforms to check
+    let explicit_manifest = cli.manifest.clone().filter(|path| path.is_file());
+    let workspace = locate_workspace(cli.manifest.clone(), cli.config.clone(), cli.proxy.as_ref())?;
+    let http_client = pixi_outdated::http::client(cli.proxy.as_ref())?;
+
+    let manifest_path =
+        explicit_manifest.or_else(|| pixi_outdated::parser::find_manifest(workspace.root()));
+    let mut tool_config =
+        pixi_outdated::config::ToolConfig::discover(workspace.root(), manifest_path.as_deref())?;
+    if context.skip_hooks {
+        tool_config.hooks = Default::default();
+    }
 
-    // Get the repodata gateway from the workspace
-    let gateway = workspace
-        .repodata_gateway()
-        .map_err(|e| anyhow::anyhow!("Failed to get repodata gateway: {}", e))?;
+    let selection = selection(&cli, packages, &tool_config, workspace.root())?;
+
+    // Get the repodata gateway from the workspace, or the one an earlier
+    // environment of the same workspace loaded repodata into
+    let workspace_root = workspace.root().to_path_buf();
+    let gateway = match context.gateways.get(&workspace_root) {
+        Some(shared) => shared.gateway.clone(),
+        None => {
+            let gateway = workspace
+                .repodata_gateway()
+                .map_err(|e| anyhow::anyhow!("Failed to get repodata gateway: {}", e))?
+                .clone();
+            context.gateways.insert(
+                workspace_root.clone(),
+                WorkspaceGateway {
+                    gateway: gateway.clone(),
+                    warmed_subdirs: Default::default(),
+                },
+            );
+            gateway
+        }
+    };
 
     // Get the environment to work with
     let environment = if let Some(ref env_name) = cli.environment {
@@ -122,37 +939,204 @@ async fn run(cli: Cli) -> Result<()> {
         workspace.default_environment()
     };
 
+    // The features, lockfile and channel alias of the environment, resolved
+    // like library checks of a manifest do
+    let resolved = pixi_outdated::check::ResolvedEnvironment::new(&workspace, &environment);
+    // Only the requirements of the features making up the environment apply
+    // to it
+    let environment_features = &resolved.features;
+    // What channel names resolve to, from pixi's configuration
+    let channel_alias = resolved.channel_alias.clone();
+    // Channels pinned on individual dependencies take precedence over where
+    // the locked artifact was downloaded from (e.g. a mirror)
+    let channel_overrides = match manifest_path {
+        Some(ref path) => {
+            pixi_outdated::parser::ChannelOverrides::load(path, environment_features)?
+        }
+        None => pixi_outdated::parser::ChannelOverrides::default(),
+    };
+    let dependency_specs = match manifest_path {
+        Some(ref path) => pixi_outdated::parser::DependencySpecs::load(path, environment_features)?,
+        None => pixi_outdated::parser::DependencySpecs::default(),
+    };
+
+    if !context.pre_hooks_done {
+        run_pre_hooks(
+            &tool_config.hooks,
+            workspace.root(),
+            manifest_path.as_deref(),
+            &[environment.name().as_str().to_string()],
+        )?;
+    }
+
     let platforms_to_check: Vec<String> = if let Some(ref plat) = cli.platform {
         vec![plat.clone()]
     } else {
-        environment
-            .platforms()
-            .into_iter()
-            .map(|p| p.to_string())
-            .collect()
+        resolved.platforms.clone()
     };
 
+    let metadata = pixi_outdated::report::ReportMetadata::new(
+        environment.name().as_str(),
+        &platforms_to_check,
+        manifest_path.as_deref(),
+        Some(&resolved.lock_file_path),
+        chrono::Utc::now(),
+    )?;
+
     let check_multiple_platforms = cli.platform.is_none();
 
-    if cli.verbose && !cli.json && check_multiple_platforms {
+    if cli.verbose && !structured && check_multiple_platforms {
         println!("Checking platforms: {}\n", platforms_to_check.join(", "));
     }
 
-    // Load the lock file once
-    let lock_file = workspace
-        .update_lock_file(UpdateLockFileOptions {
-            lock_file_usage: LockFileUsage::Locked,
-            no_install: true,
-            max_concurrent_solves: workspace.config().max_concurrent_solves(),
-        })
-        .await
-        .map_err(|e| anyhow::anyhow!("Failed to load lock file: {}", e))?
-        .0
-        .into_lock_file();
+    // Without a lockfile there is nothing to compare against
+    let lock_file_path = resolved.lock_file_path.clone();
+    if !lock_file_path.is_file() {
+        if cli.verbose && !structured {
+            println!("Lockfile {} does not exist", lock_file_path.display());
+        }
+        return report_empty_run(
+            cli.format,
+            &locale,
+            context.combined_output,
+            &cli.output_files,
+            workspace.root(),
+            &tool_config,
+            &http_client,
+            metadata,
+            RunStatus::LockfileMissing,
+        )
+        .await;
+    }
+
+    // Load the lock file once. A lockfile written by a newer pixi can't be
+    // loaded by the pixi version built in, so read what we can of it instead.
+    let lock_content = std::fs::read_to_string(&lock_file_path)
+        .with_context(|| format!("Failed to read {}", lock_file_path.display()))?;
+    let lock_version = pixi_outdated::parser::lockfile_version(&lock_content)
+        .with_context(|| format!("Failed to read {}", lock_file_path.display()))?;
+    let lock_file = if lock_version > pixi_outdated::parser::SUPPORTED_LOCK_VERSION {
+        let lock_file =
+            pixi_outdated::parser::parse_lockfile(&lock_file_path).with_context(|| {
+                format!(
+                    "{} uses lockfile format version {}, which this version of pixi-outdated \
+                     can't read (it supports up to version {})",
+                    lock_file_path.display(),
+                    lock_version,
+                    pixi_outdated::parser::SUPPORTED_LOCK_VERSION
+                )
+            })?;
+        warn_newer_lock_file(&lock_file_path, &lock_file);
+        LoadedLockFile::BestEffort(lock_file)
+    } else {
+        LoadedLockFile::Full(
+            workspace
+                .update_lock_file(UpdateLockFileOptions {
+                    lock_file_usage: LockFileUsage::Locked,
+                    no_install: true,
+                    max_concurrent_solves: workspace.config().max_concurrent_solves(),
+                })
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to load lock file: {}", e))?
+                .0
+                .into_lock_file(),
+        )
+    };
+
+    // Platforms the lockfile still carries for the environment although the
+    // manifest no longer solves it for them are never checked
+    let locked_platforms: Vec<rattler_conda_types::Platform> = lock_file
+        .platforms(environment.name().as_str())
+        .iter()
+        .filter_map(|platform| platform.parse().ok())
+        .collect();
+    let solved_platforms: Vec<rattler_conda_types::Platform> =
+        environment.platforms().into_iter().collect();
+    let stale_platforms =
+        pixi_outdated::platform::stale_platforms(&locked_platforms, &solved_platforms);
+    if cli.verbose && !structured && !stale_platforms.is_empty() {
+        let stale: Vec<&str> = stale_platforms.iter().map(|p| p.as_str()).collect();
+        println!(
+            "Skipping stale lockfile entries for platforms: {}\n",
+            stale.join(", ")
+        );
+    }
+
+    // Make sure the requested platform is actually locked for this environment
+    if let Some(platform) = requested_platform {
+        pixi_outdated::platform::ensure_locked(
+            platform,
+            &locked_platforms,
+            environment.name().as_str(),
+        )?;
+        if let Err(e) = pixi_outdated::platform::ensure_solved(
+            platform,
+            &solved_platforms,
+            environment.name().as_str(),
+        ) {
+            // Other environments may still be solved for it
+            if !context.combined_output {
+                return Err(e);
+            }
+            if cli.verbose {
+                eprintln!("{}", e);
+            }
+            return report_empty_run(
+                cli.format,
+                &locale,
+                context.combined_output,
+                &cli.output_files,
+                workspace.root(),
+                &tool_config,
+                &http_client,
+                metadata,
+                RunStatus::EmptyEnvironment,
+            )
+            .await;
+        }
+    }
+
+    // Restrict the check to what changed since a revision, e.g. in a pull request
+    let changed = match cli.changed_since {
+        Some(ref revision) => {
+            let changed = changed_dependencies(
+                revision,
+                &workspace,
+                manifest_path.as_deref(),
+                &lock_file,
+                environment.name().as_str(),
+                &platforms_to_check,
+                environment_features,
+                &dependency_specs,
+            )?;
+            if changed.is_empty() {
+                return report_empty_run(
+                    cli.format,
+                    &locale,
+                    context.combined_output,
+                    &cli.output_files,
+                    workspace.root(),
+                    &tool_config,
+                    &http_client,
+                    metadata,
+                    RunStatus::NoChanges,
+                )
+                .await;
+            }
+            if cli.verbose && !structured {
+                println!(
+                    "Checking {} dependencies changed since {}\n",
+                    changed.len(),
+                    revision
+                );
+            }
+            Some(changed)
+        }
+        None => None,
+    };
 
     // Track updates per platform (used for both JSON and text output)
-    let mut platform_updates: std::collections::HashMap<String, Vec<PackageUpdate>> =
-        std::collections::HashMap::new();
+    let mut platform_updates = PlatformUpdates::new();
 
     // Collect all packages from all platforms first
     let mut platform_packages: std::collections::HashMap<
@@ -160,8 +1144,31 @@ async fn run(cli: Cli) -> Result<()> {
         Vec<pixi_outdated::pixi::PixiPackage>,
     > = std::collections::HashMap::new();
 
+    // Number of locked packages before any filtering, to tell an empty
+    // environment apart from one where everything was filtered out
+    let mut locked_package_count = 0;
+    // Every locked package, filtered or not, for `--audit` and the SBOM
+    let mut all_locked: std::collections::BTreeMap<String, Vec<pixi_outdated::pixi::PixiPackage>> =
+        std::collections::BTreeMap::new();
+
+    // The locked interpreter per platform, which limits the PyPI releases
+    // that can be installed
+    let mut locked_python: std::collections::HashMap<String, String> =
+        std::collections::HashMap::new();
+
+    // How each transitive dependency is pulled in, per platform
+    let mut dependency_chains: std::collections::HashMap<
+        String,
+        std::collections::HashMap<String, Vec<String>>,
+    > = std::collections::HashMap::new();
+    // The locked dependency graph and its roots per platform, for `--tree`
+    let mut dependency_graphs: std::collections::HashMap<
+        String,
+        (pixi_outdated::graph::DependencyGraph, Vec<String>),
+    > = std::collections::HashMap::new();
+
     for platform in &platforms_to_check {
-        if cli.verbose && !cli.json {
+        if cli.verbose && !structured {
             println!("Fetching package list for {}...", platform);
         }
 
@@ -169,79 +1176,98 @@ async fn run(cli: Cli) -> Result<()> {
         let platform_parsed = match platform.parse() {
             Ok(p) => p,
             Err(e) => {
-                if cli.verbose && !cli.json {
-                    eprintln!("Skipping platform {}: invalid platform: {}", platform, e);
-                }
+                eprintln!("Skipping platform {}: invalid platform: {}", platform, e);
                 continue;
             }
         };
 
-        let locked_deps = lock_file
-            .environment(environment.name().as_str())
-            .and_then(|env| env.packages(platform_parsed).map(Vec::from_iter))
-            .unwrap_or_default();
+        let locked_deps = lock_file.packages(environment.name().as_str(), platform_parsed);
 
         if locked_deps.is_empty() {
-            if cli.verbose && !cli.json {
+            if cli.verbose && !structured {
                 println!("No packages found for platform {}", platform);
             }
             continue;
         }
+        locked_package_count += locked_deps.len();
+        all_locked.insert(platform.clone(), locked_deps.clone());
 
-        // Convert LockedPackageRef to PixiPackage
-        let packages: Vec<pixi_outdated::pixi::PixiPackage> = locked_deps
+        if let Some(python) = pixi_outdated::pixi::python_version(&locked_deps) {
+            locked_python.insert(platform.clone(), python.to_string());
+        }
+
+        let graph = pixi_outdated::graph::DependencyGraph::new(&locked_deps);
+        let roots: Vec<&str> = locked_deps
             .iter()
+            .filter(|locked_pkg| dependency_specs.is_direct(&locked_pkg.name, locked_pkg.kind))
+            .map(|locked_pkg| locked_pkg.name.as_str())
+            .collect();
+        dependency_chains.insert(platform.clone(), graph.chains(roots.iter().copied()));
+        if cli.tree {
+            // Without manifest dependencies, start at what nothing depends on
+            let roots = if roots.is_empty() {
+                graph.leaves()
+            } else {
+                roots
+            };
+            let roots = roots.into_iter().map(str::to_string).collect();
+            dependency_graphs.insert(platform.clone(), (graph, roots));
+        }
+
+        let packages: Vec<pixi_outdated::pixi::PixiPackage> = locked_deps
+            .into_iter()
             .filter_map(|locked_pkg| {
-                let pkg_name = match locked_pkg {
-                    rattler_lock::LockedPackageRef::Conda(conda_pkg) => {
-                        conda_pkg.record().name.as_normalized().to_string()
-                    }
-                    rattler_lock::LockedPackageRef::Pypi(pypi_pkg, _) => pypi_pkg.name.to_string(),
-                };
+                let pkg_name = locked_pkg.name.clone();
+                let kind = locked_pkg.kind;
 
-                // Filter by package names if specified
-                if !cli.packages.is_empty() && !cli.packages.contains(&pkg_name) {
+                // Filter by the package names, patterns and regexes given
+                if !selection.packages.matches(&pkg_name) {
                     return None;
                 }
 
-                // Determine package kind and convert
-                match locked_pkg {
-                    rattler_lock::LockedPackageRef::Conda(conda_pkg) => {
-                        let record = conda_pkg.record();
-                        let location = conda_pkg.location();
-                        Some(pixi_outdated::pixi::PixiPackage {
-                            name: record.name.as_normalized().to_string(),
-                            version: record.version.to_string(),
-                            build: Some(record.build.clone()),
-                            size_bytes: record.size,
-                            kind: pixi_outdated::pixi::PackageKind::Conda,
-                            source: Some(location.to_string()),
-                            is_explicit: true, // TODO: determine if explicit from manifest
-                        })
+                if selection.exclude.excludes(&pkg_name) {
+                    if cli.verbose && !structured {
+                        println!("{}: excluded", pkg_name);
                     }
-                    rattler_lock::LockedPackageRef::Pypi(pypi_pkg, _) => {
-                        Some(pixi_outdated::pixi::PixiPackage {
-                            name: pypi_pkg.name.to_string(),
-                            version: pypi_pkg.version.to_string(),
-                            build: None,
-                            size_bytes: None,
-                            kind: pixi_outdated::pixi::PackageKind::Pypi,
-                            source: None,
-                            is_explicit: true, // TODO: determine if explicit from manifest
-                        })
+                    return None;
+                }
+
+                if changed
+                    .as_ref()
+                    .is_some_and(|changed| !changed.contains(&pkg_name, kind))
+                {
+                    return None;
+                }
+
+                // Only packages declared in the manifest count as explicit
+                let is_explicit = dependency_specs.is_direct(&pkg_name, kind);
+                if cli.explicit && !is_explicit {
+                    return None;
+                }
+
+                // Skip packages that are ignored for every version before querying them
+                if selection.ignore.ignores_all_versions(&pkg_name) {
+                    if cli.verbose && !structured {
+                        println!("{}: ignored", pkg_name);
                     }
+                    return None;
                 }
+
+                Some(pixi_outdated::pixi::PixiPackage {
+                    is_explicit,
+                    ..locked_pkg
+                })
             })
             .collect();
 
         if packages.is_empty() {
-            if cli.verbose && !cli.json {
+            if cli.verbose && !structured {
                 println!("No matching packages found for platform {}", platform);
             }
             continue;
         }
 
-        if cli.verbose && !cli.json {
+        if cli.verbose && !structured {
             println!("Found {} packages\n", packages.len());
         }
 
@@ -249,30 +1275,89 @@ async fn run(cli: Cli) -> Result<()> {
     }
 
     if platform_packages.is_empty() {
-        if !cli.json {
-            println!("No packages found for any platform");
-        }
-        return Ok(());
+        let status = if locked_package_count == 0 {
+            RunStatus::EmptyEnvironment
+        } else {
+            RunStatus::AllPackagesSkipped
+        };
+        return report_empty_run(
+            cli.format,
+            &locale,
+            context.combined_output,
+            &cli.output_files,
+            workspace.root(),
+            &tool_config,
+            &http_client,
+            metadata,
+            status,
+        )
+        .await;
     }
 
-    // Build a unique set of packages to check (package name + channel)
-    #[derive(Hash, Eq, PartialEq, Clone)]
-    struct PackageKey {
-        name: String,
-        channel: Option<String>,
-        kind: pixi_outdated::pixi::PackageKind,
+    // Channels the manifest lists for the environment now, which a relock
+    // will resolve from
+    let manifest_channels = {
+        // The priority pixi solves the environment with, which its features
+        // can set
+        let priority = match environment
+            .channel_priority()
+            .map_err(|e| anyhow::anyhow!("Failed to get channel priority: {}", e))?
+        {
+            Some(pixi_manifest::ChannelPriority::Disabled) => {
+                pixi_outdated::priority::ChannelPriority::Disabled
+            }
+            Some(pixi_manifest::ChannelPriority::Strict) | None => {
+                pixi_outdated::priority::ChannelPriority::Strict
+            }
+        };
+        // Resolved against the workspace's channel config, so relative paths
+        // are relative to the manifest
+        let channel_config = workspace.channel_config();
+        let channels: Vec<String> = environment
+            .channels()
+            .into_iter()
+            .map(|channel| {
+                channel
+                    .clone()
+                    .into_base_url(&channel_config)
+                    .map_or_else(|_| channel.to_string(), |url| url.as_str().to_string())
+            })
+            .collect();
+        pixi_outdated::parser::ManifestChannels::new(&channels, &channel_alias, priority)
+    };
+
+    // With several channels, a relock may take a package from another
+    // channel than it's locked from, so every channel of the environment is
+    // queried and the latest version picked by channel priority. Channels
+    // the manifest lists for the environment but the lockfile doesn't yet
+    // are searched too, for newer versions elsewhere. Channels of features
+    // the environment doesn't use aren't, since a relock never sees them.
+    let environment_channels = lock_file.channels(environment.name().as_str(), &channel_alias);
+    let mut searched_channels = environment_channels.clone();
+    for channel in manifest_channels.urls() {
+        if !searched_channels.contains(channel) {
+            searched_channels.push(channel.clone());
+        }
     }
+    let mut prioritized_packages: std::collections::HashSet<PackageKey> =
+        std::collections::HashSet::new();
+    // Packages in channels they aren't locked from, only queried for that
+    let mut other_channel_packages: std::collections::HashSet<PackageKey> =
+        std::collections::HashSet::new();
 
+    // Build a unique set of packages to check (package name + channel)
     let mut unique_packages: std::collections::HashMap<PackageKey, String> =
         std::collections::HashMap::new();
 
+    // Conda packages locked as noarch on every platform, whose platform
+    // subdirs don't need to be fetched
+    let mut noarch_packages: std::collections::HashMap<PackageKey, bool> =
+        std::collections::HashMap::new();
+
     // Collect unique packages across all platforms
-    for packages in platform_packages.values() {
+    for (platform, packages) in &platform_packages {
         for package in packages {
-            let channel = package
-                .source
-                .as_ref()
-                .and_then(|s| pixi_outdated::conda::extract_channel_url(s));
+            let channel = package_channel(package, platform, &channel_overrides, &channel_alias);
 
             let key = PackageKey {
                 name: package.name.clone(),
@@ -280,119 +1365,1282 @@ async fn run(cli: Cli) -> Result<()> {
                 kind: package.kind,
             };
 
+            if package.kind == pixi_outdated::pixi::PackageKind::Conda {
+                let noarch = package
+                    .source
+                    .as_deref()
+                    .and_then(pixi_outdated::conda::extract_subdir)
+                    .is_some_and(|subdir| subdir == "noarch");
+                *noarch_packages.entry(key.clone()).or_insert(true) &= noarch;
+            }
+
+            if package.kind == pixi_outdated::pixi::PackageKind::Conda
+                && key.channel.is_some()
+                && channel_overrides.get(&package.name, platform).is_none()
+                && searched_channels.len() > 1
+            {
+                prioritized_packages.insert(key.clone());
+                for channel in &searched_channels {
+                    let other = PackageKey {
+                        channel: Some(channel.clone()),
+                        ..key.clone()
+                    };
+                    if other != key {
+                        unique_packages
+                            .entry(other.clone())
+                            .or_insert(package.version.clone());
+                        other_channel_packages.insert(other);
+                    }
+                }
+            }
+
             // Store the first version we see (they might differ per platform)
             unique_packages
                 .entry(key)
                 .or_insert(package.version.clone());
         }
     }
+    // Unless another platform locks the package from that channel
+    other_channel_packages.retain(|key| !noarch_packages.contains_key(key));
 
-    // Cache for version queries (package_key -> latest_version)
-    let mut version_cache: std::collections::HashMap<PackageKey, Option<String>> =
+    // Cache for version queries (package_key -> upstream info)
+    let mut version_cache: std::collections::HashMap<PackageKey, VersionLookup> =
         std::collections::HashMap::new();
 
-    // Query each unique package once
-    for key in unique_packages.keys() {
-        match key.kind {
-            pixi_outdated::pixi::PackageKind::Conda => {
-                if let Some(ref channel_url) = key.channel {
-                    if cli.verbose && !cli.json {
-                        println!("Checking {} (conda) from {}...", key.name, channel_url);
-                    }
+    // Honor pixi's keyring provider setting for authenticated indexes
+    let keyring_provider = pixi_outdated::pypi::KeyringProvider::from_config(workspace.config());
+    // Use the indexes configured for this environment, e.g. an internal index
+    // set in a feature's `pypi-options`
+    let pypi_options = environment.pypi_options();
+    let extra_index_urls: Vec<&str> = pypi_options
+        .extra_index_urls
+        .iter()
+        .flatten()
+        .map(|url| url.as_str())
+        .collect();
+    let mut pypi_client = pixi_outdated::pypi::PyPiClient::new(
+        keyring_provider,
+        pixi_outdated::auth::CredentialStore::from_environment(),
+    )
+    .with_http_client(http_client.clone())
+    .with_indexes(
+        pypi_options.index_url.as_ref().map(|url| url.as_str()),
+        &extra_index_urls,
+    );
+    if let Some(ref index_url) = cli.index_url {
+        pypi_client = pypi_client.with_index_url(index_url);
+    }
+    // Keep PyPI responses between runs, next to pixi's own caches
+    if let Some(cache) = pixi_outdated::cache::pypi_cache(cli.cache_ttl) {
+        pypi_client = pypi_client.with_cache(cache);
+    }
 
-                    // Query all platforms at once for efficiency
-                    let platform_refs: Vec<&str> =
-                        platforms_to_check.iter().map(|s| s.as_str()).collect();
-                    let latest_result =
-                        pixi_outdated::conda::get_latest_conda_version_multi_platform(
-                            gateway,
-                            &key.name,
-                            channel_url,
-                            &platform_refs,
-                        )
-                        .await;
-
-                    match latest_result {
-                        Ok(latest) => {
-                            version_cache.insert(key.clone(), latest);
-                        }
-                        Err(e) => {
-                            if !cli.json {
-                                eprintln!("Error checking {}: {}", key.name, e);
-                            }
-                            version_cache.insert(key.clone(), None);
-                        }
-                    }
-                } else if cli.verbose && !cli.json {
-                    println!(
-                        "Skipping {} (conda): unable to extract channel URL",
-                        key.name
-                    );
-                }
+    let index_urls = pypi_client.index_urls().to_vec();
+
+    // Per channel/index statistics for the health summary
+    let mut source_tracker = pixi_outdated::sources::SourceTracker::new();
+
+    // Query each unique package once, through every version source. Packages
+    // an earlier environment queried for the same platforms are reused.
+    let platform_refs: Vec<&str> = platforms_to_check.iter().map(|s| s.as_str()).collect();
+    let shared_lookups = context
+        .lookups
+        .entry(platforms_to_check.clone())
+        .or_default();
+    let keys: Vec<PackageKey> = unique_packages
+        .keys()
+        .filter(|key| match shared_lookups.get(*key) {
+            Some(lookup) => {
+                version_cache.insert((*key).clone(), lookup.clone());
+                false
             }
-            pixi_outdated::pixi::PackageKind::Pypi => {
-                if cli.verbose && !cli.json {
-                    println!("Checking {} (PyPI)...", key.name);
+            None => true,
+        })
+        .cloned()
+        .collect();
+    if cli.verbose && !structured {
+        for key in &keys {
+            match (key.kind, key.channel.as_deref()) {
+                (pixi_outdated::pixi::PackageKind::Conda, Some(channel_url)) => {
+                    println!("Checking {} (conda) from {}...", key.name, channel_url);
                 }
-
-                match pixi_outdated::pypi::get_latest_pypi_version(&key.name).await {
-                    Ok(latest) => {
-                        version_cache.insert(key.clone(), Some(latest));
-                    }
-                    Err(e) => {
-                        if !cli.json {
-                            eprintln!("Error checking {}: {}", key.name, e);
-                        }
-                        version_cache.insert(key.clone(), None);
-                    }
+                (pixi_outdated::pixi::PackageKind::Conda, None) => println!(
+                    "Skipping {} (conda): no channel locked, reporting it as unchecked",
+                    key.name
+                ),
+                (pixi_outdated::pixi::PackageKind::Pypi, _) => {
+                    println!("Checking {} (PyPI)...", key.name);
                 }
             }
         }
     }
 
-    // Now build updates per platform using the cached results
-    for (platform, packages) in &platform_packages {
-        let mut platform_package_updates: Vec<PackageUpdate> = Vec::new();
-
-        for package in packages {
-            let channel = package
-                .source
-                .as_ref()
-                .and_then(|s| pixi_outdated::conda::extract_channel_url(s));
-
-            let key = PackageKey {
-                name: package.name.clone(),
-                channel,
-                kind: package.kind,
-            };
+    let jobs = cli.jobs.map_or_else(
+        || workspace.config().max_concurrent_downloads(),
+        |jobs| jobs.get(),
+    );
+    let retry = pixi_outdated::retry::RetryPolicy {
+        max_attempts: cli.max_attempts.get(),
+        ..Default::default()
+    };
+    let mut repodata = Vec::new();
+    let sources: Vec<Box<dyn VersionSource>> = match &fixtures_dir {
+        Some(dir) => vec![Box::new(pixi_outdated::fixtures::FixtureSource::new(dir))],
+        None => {
+            let noarch_packages = noarch_packages
+                .into_iter()
+                .filter_map(|(key, noarch)| noarch.then_some(key));
+            // Rechecks skip the repodata cache, but go through the
+            // workspace's client so its credentials and mirrors still apply
+            let client = workspace
+                .authenticated_client()
+                .map_err(|e| anyhow::anyhow!("Failed to get the HTTP client: {}", e))?
+                .clone();
+            let mut refresh_gateway = rattler_repodata_gateway::Gateway::builder()
+                .with_client(client)
+                .with_channel_config(pixi_outdated::conda::uncached_channel_config());
+            // Refreshed repodata replaces the stale entries in pixi's cache
+            if let Ok(cache_dir) = pixi_config::get_cache_dir() {
+                refresh_gateway = refresh_gateway.with_cache_dir(cache_dir.join("repodata"));
+            }
+            let mut conda_source = pixi_outdated::conda::CondaSource::new(gateway.clone())
+                .with_jobs(jobs)
+                .with_retry(retry)
+                .with_noarch_packages(noarch_packages)
+                .with_refresh_gateway(refresh_gateway.finish());
+            let mut pypi_client = pypi_client.with_jobs(jobs).with_retry(retry);
+            if let Some(timeout) = cli.timeout.map(std::time::Duration::from_secs) {
+                conda_source = conda_source.with_timeout(timeout);
+                pypi_client = pypi_client.with_timeout(timeout);
+            }
 
-            if let Some(Some(latest)) = version_cache.get(&key) {
-                if latest != &package.version {
-                    let update = PackageUpdate {
+            // Fetch every channel subdir the queries read once, in parallel,
+            // skipping those an earlier environment already loaded
+            let warmed_subdirs = &mut context
+                .gateways
+                .entry(workspace_root.clone())
+                .or_insert_with(|| WorkspaceGateway {
+                    gateway: gateway.clone(),
+                    warmed_subdirs: Default::default(),
+                })
+                .warmed_subdirs;
+            let (shared, subdirs): (std::collections::BTreeSet<_>, _) = conda_source
+                .subdirs(&keys, &platform_refs)
+                .into_iter()
+                .partition(|subdir| warmed_subdirs.contains(subdir));
+            repodata = conda_source.warm(&subdirs).await;
+            warmed_subdirs.extend(
+                repodata
+                    .iter()
+                    .filter(|fetch| !fetch.failed)
+                    .map(|fetch| (fetch.channel.clone(), fetch.subdir.clone())),
+            );
+            repodata.extend(shared.into_iter().map(|(channel, subdir)| {
+                pixi_outdated::sources::RepodataFetch {
+                    channel,
+                    subdir,
+                    total_ms: 0,
+                    shared: true,
+                    failed: false,
+                    bytes: 0,
+                }
+            }));
+            repodata.sort_by(|a, b| (&a.channel, &a.subdir).cmp(&(&b.channel, &b.subdir)));
+
+            vec![
+                Box::new(conda_source),
+                Box::new(pixi_outdated::local::LocalChannelSource),
+                Box::new(pypi_client),
+            ]
+        }
+    };
+    // Save answers as they come in, so an interrupted run can be resumed.
+    // Sessions expire with the PyPI cache, and fixtures need none.
+    let mut session = pixi_config::get_cache_dir()
+        .ok()
+        .filter(|_| fixtures_dir.is_none() && !cli.cache_ttl.is_zero())
+        .and_then(|cache_dir| {
+            // Runs against other manifests or environments of the same
+            // workspace keep sessions of their own
+            let manifest = manifest_path.as_deref().unwrap_or_else(|| workspace.root());
+            let scope = format!(
+                "{}\n{}\n{}\n{}",
+                manifest.display(),
+                environment.name().as_str(),
+                platforms_to_check.join(","),
+                index_urls.join(",")
+            );
+            pixi_outdated::session::Session::open(
+                &cache_dir.join("pixi-outdated").join("sessions"),
+                &scope,
+                cli.cache_ttl,
+                !cli.no_resume,
+            )
+            .inspect_err(|e| tracing::debug!("Not saving the session: {:#}", e))
+            .ok()
+        });
+
+    let mut responses = Vec::new();
+    let mut pending = keys.clone();
+    if let Some(ref mut session) = session {
+        if session.answered() > 0 && !structured {
+            eprintln!(
+                "Resuming an interrupted run: {} packages were already checked (--no-resume to start over)",
+                locale.format_count(session.answered() as u64)
+            );
+        }
+        let (answered, rest) = session.take_answered(&keys);
+        responses = answered;
+        pending = rest;
+    }
+    let chunk_size = match session {
+        Some(_) => pixi_outdated::session::CHUNK_SIZE,
+        None => pending.len().max(1),
+    };
+    for chunk in pending.chunks(chunk_size) {
+        // Packages are commonly missing from the other channels of an
+        // environment, which is nothing suspicious
+        let (mut chunk_responses, other_channel_responses): (Vec<_>, Vec<_>) =
+            pixi_outdated::upstream::query_all(&sources, chunk, &platform_refs)
+                .await
+                .into_iter()
+                .partition(|(key, _)| !other_channel_packages.contains(key));
+        // Stale or truncated cached metadata would report packages as
+        // missing or outdated in the wrong direction, so double-check those
+        let rechecked = pixi_outdated::upstream::recheck_suspicious(
+            &sources,
+            &mut chunk_responses,
+            &unique_packages,
+            &platform_refs,
+        )
+        .await;
+        if cli.verbose && !structured {
+            for key in &rechecked {
+                println!("{}: queried again, bypassing the cache", key.name);
+            }
+        }
+        chunk_responses.extend(other_channel_responses);
+        if let Some(ref mut session) = session {
+            if let Err(e) = session.record(&chunk_responses) {
+                tracing::debug!("Failed to save the session: {:#}", e);
+            }
+        }
+        responses.extend(chunk_responses);
+    }
+    if let Some(session) = session {
+        if let Err(e) = session.finish() {
+            tracing::debug!("{:#}", e);
+        }
+    }
+
+    let mut pypi_bytes = 0;
+    for (key, response) in responses {
+        source_tracker.add_bytes(&response.source_url, key.kind, response.bytes);
+        if key.kind == pixi_outdated::pixi::PackageKind::Pypi {
+            pypi_bytes += response.bytes;
+        }
+
+        // A package the source doesn't know is an answer, not a failure
+        let lookup = VersionLookup::from_response(response.info);
+        let outcome = match lookup {
+            VersionLookup::Failed(ref message) => {
+                if !structured {
+                    eprintln!("Error checking {}: {}", key.name, message);
+                }
+                QueryOutcome::Failed
+            }
+            _ => response.outcome,
+        };
+        source_tracker.record(&response.source_url, key.kind, response.elapsed, outcome);
+        if response.batched {
+            source_tracker.record_batched(&response.source_url, key.kind);
+        }
+
+        let lookup = selection.with_prereleases(&key.name, lookup);
+        shared_lookups.insert(key.clone(), lookup.clone());
+        version_cache.insert(key, lookup);
+    }
+
+    // What each package resolves to by channel priority, with the channel
+    // its latest version comes from. Locked versions are still checked
+    // against the channel they're locked from.
+    let mut prioritized_lookups: std::collections::HashMap<PackageKey, (String, VersionLookup)> =
+        std::collections::HashMap::new();
+    // The channel with the newest version of each package, of all searched
+    let mut newest_versions: std::collections::HashMap<PackageKey, (String, String)> =
+        std::collections::HashMap::new();
+    for key in &prioritized_packages {
+        let lookups: Vec<(&str, &VersionLookup)> = searched_channels
+            .iter()
+            .filter_map(|channel| {
+                let key = PackageKey {
+                    channel: Some(channel.clone()),
+                    ..key.clone()
+                };
+                version_cache
+                    .get(&key)
+                    .map(|lookup| (channel.as_str(), lookup))
+            })
+            .collect();
+        if let Some(newest) = pixi_outdated::priority::newest(&lookups) {
+            newest_versions.insert(key.clone(), newest);
+        }
+        let environment_lookups: Vec<(&str, &VersionLookup)> = lookups
+            .into_iter()
+            .filter(|(channel, _)| environment_channels.iter().any(|c| c == channel))
+            .collect();
+        if let Some(resolved) =
+            pixi_outdated::priority::resolve(&environment_lookups, manifest_channels.priority)
+        {
+            prioritized_lookups.insert(key.clone(), resolved);
+        }
+    }
+
+    let network = pixi_outdated::sources::NetworkUsage {
+        pypi_bytes,
+        repodata_bytes: repodata.iter().map(|fetch| fetch.bytes).sum(),
+    };
+
+    let now = chrono::Utc::now();
+
+    // Locked versions that are no longer available upstream
+    let mut locked_issues: Vec<LockedIssue> = Vec::new();
+
+    // Packages whose latest version couldn't be determined
+    let mut unchecked: Vec<UncheckedPackage> = Vec::new();
+
+    // Conda packages installed from labels such as `rc` or `dev`
+    let mut labeled_packages: Vec<LabeledPackage> = Vec::new();
+
+    // Conda packages with a newer version in a channel priority passes over
+    let mut newer_in_other_channels: Vec<OtherChannelRelease> = Vec::new();
+
+    // Where locked conda packages may come from, if the manifest restricts it
+    let allowlist = tool_config
+        .allowed_channels
+        .as_deref()
+        .map(|channels| pixi_outdated::policy::ChannelAllowlist::new(channels, &channel_alias));
+    let mut disallowed_channels: Vec<DisallowedPackage> = Vec::new();
+
+    let mut unlisted_channels: Vec<DisallowedPackage> = Vec::new();
+
+    // Which licenses updates may be published under, if the manifest restricts it
+    let license_allowlist = tool_config
+        .allowed_licenses
+        .as_deref()
+        .map(pixi_outdated::policy::LicenseAllowlist::new)
+        .transpose()?;
+    let mut held_back_by_license: Vec<HeldBackPackage> = Vec::new();
+
+    // Activation scripts of the versions updates move to, read from their
+    // package files, or from the installed environment for fixtures
+    let activation_scripts = pixi_outdated::activation::ActivationLookup::new(
+        pixi_outdated::activation::ActivationScripts::load(&environment.dir()),
+        match fixtures_dir {
+            Some(_) => None,
+            None => Some(
+                workspace
+                    .authenticated_client()
+                    .map_err(|e| anyhow::anyhow!("Failed to get the HTTP client: {}", e))?
+                    .clone(),
+            ),
+        },
+        pixi_config::get_cache_dir().ok(),
+    );
+
+    // The same updates in a form `pixi-outdated apply` can act on
+    let mut plan = UpdatePlan::new(environment.name().as_str());
+
+    // The candidates of `--why-latest`, decided on like the reported updates
+    let mut why_latest: Vec<WhyLatest> = Vec::new();
+
+    // Now build updates per platform using the cached results
+    for (platform, packages) in &platform_packages {
+        let mut platform_package_updates: Vec<PackageUpdate> = Vec::new();
+
+        for package in packages {
+            let channel = package_channel(package, platform, &channel_overrides, &channel_alias);
+
+            let key = PackageKey {
+                name: package.name.clone(),
+                channel,
+                kind: package.kind,
+            };
+
+            // Check where the artifact was actually downloaded from, not the
+            // channel pinned in the manifest
+            let origin = package
+                .source
+                .as_deref()
+                .filter(|_| package.kind == pixi_outdated::pixi::PackageKind::Conda)
+                .and_then(pixi_outdated::conda::extract_channel_url);
+            if let Some(ref origin) = origin {
+                let locked_from = || DisallowedPackage {
+                    name: package.name.clone(),
+                    version: package.version.clone(),
+                    platform: platform.clone(),
+                    channel: origin.clone(),
+                };
+                if allowlist
+                    .as_ref()
+                    .is_some_and(|allowlist| !allowlist.allows(origin))
+                {
+                    disallowed_channels.push(locked_from());
+                }
+                if !manifest_channels.is_empty() && !manifest_channels.lists(origin) {
+                    unlisted_channels.push(locked_from());
+                }
+            }
+
+            let lookup = version_cache.get(&key);
+            let locked_info = lookup.and_then(VersionLookup::info);
+            let (latest_channel, info) = match prioritized_lookups.get(&key) {
+                Some((channel, resolved)) => (Some(channel.clone()), resolved.info()),
+                None => (key.channel.clone(), locked_info),
+            };
+
+            // Only the channel's main label is queried, so compare packages from
+            // other labels against the stable release instead
+            let label = package
+                .source
+                .as_deref()
+                .and_then(pixi_outdated::conda::extract_label);
+            let python_version = locked_python.get(platform).map(String::as_str);
+            let latest = info.and_then(|info| info.latest_version_for(python_version));
+
+            // Fall back to the newest version under an allowed license
+            let latest_allowed = match (&license_allowlist, info, latest) {
+                (Some(allowlist), Some(info), Some(latest)) => info
+                    .license(latest)
+                    .filter(|license| !allowlist.allows(license))
+                    .map(|license| {
+                        let allowed = info.latest_allowed_for(
+                            &|license| allowlist.allows(license),
+                            python_version,
+                        );
+                        if info.is_update(latest, &package.version)
+                            && !selection.ignore.is_ignored(&package.name, Some(latest))
+                        {
+                            held_back_by_license.push(HeldBackPackage {
+                                name: package.name.clone(),
+                                platform: platform.clone(),
+                                installed_version: package.version.clone(),
+                                newest_version: latest.to_string(),
+                                license: license.to_string(),
+                                latest_allowed: allowed.clone(),
+                            });
+                        }
+                        allowed
+                    }),
+                _ => None,
+            };
+            let latest = match latest_allowed {
+                Some(ref allowed) => allowed.as_deref(),
+                None => latest,
+            };
+
+            // Versions beyond the maximum set in the project config aren't updates
+            let latest_capped = match (info, latest) {
+                (Some(info), Some(latest)) => selection
+                    .capped(info, &package.name, latest, &package.version)
+                    .inspect(|capped| {
+                        if cli.verbose && !structured {
+                            println!(
+                                "{}: {} is beyond the maximum version {}, using {}",
+                                package.name, latest, selection.max_versions[&package.name], capped
+                            );
+                        }
+                    }),
+                _ => None,
+            };
+            let latest = match latest_capped {
+                Some(ref capped) => Some(capped.as_str()),
+                None => latest,
+            };
+            if cli.why_latest.as_deref() == Some(package.name.as_str()) {
+                why_latest.push(WhyLatest {
+                    platform: platform.clone(),
+                    source: match package.kind {
+                        pixi_outdated::pixi::PackageKind::Conda => format!(
+                            "conda, {}",
+                            latest_channel.as_deref().unwrap_or("unknown channel")
+                        ),
+                        pixi_outdated::pixi::PackageKind::Pypi => "pypi".to_string(),
+                    },
+                    candidates: match (info, lookup) {
+                        (Some(info), _) => {
+                            use pixi_outdated::candidates::{hold_back, ExclusionReason};
+                            let mut candidates = info.candidates();
+                            hold_back(
+                                &mut candidates,
+                                info.latest_version_for(python_version),
+                                ExclusionReason::RequiresNewerPython,
+                            );
+                            if let Some(ref allowed) = latest_allowed {
+                                hold_back(
+                                    &mut candidates,
+                                    allowed.as_deref(),
+                                    ExclusionReason::License,
+                                );
+                            }
+                            if let Some(ref capped) = latest_capped {
+                                hold_back(
+                                    &mut candidates,
+                                    Some(capped),
+                                    ExclusionReason::AboveMaximum,
+                                );
+                            }
+                            pixi_outdated::candidates::render_candidates(&candidates)
+                        }
+                        (None, Some(VersionLookup::Failed(message))) => {
+                            format!("  query failed: {}\n", message)
+                        }
+                        (None, _) => "  no versions found\n".to_string(),
+                    },
+                });
+            }
+            if let (Some(latest), Some(newest), Some(python_version)) = (
+                latest,
+                info.and_then(|info| info.latest_version()),
+                python_version,
+            ) {
+                if latest != newest
+                    && latest_allowed.is_none()
+                    && latest_capped.is_none()
+                    && cli.verbose
+                    && !structured
+                {
+                    println!(
+                        "{}: {} requires a newer Python than {}, using {}",
+                        package.name, newest, python_version, latest
+                    );
+                }
+            }
+            let superseded = latest
+                .is_some_and(|latest| pixi_outdated::conda::supersedes(latest, &package.version));
+            if let Some((channel, version)) = newest_versions.get(&key) {
+                let newer = |than: &str| !pixi_outdated::conda::supersedes(than, version);
+                if latest_channel.as_deref() != Some(channel.as_str())
+                    && newer(&package.version)
+                    && latest.is_none_or(newer)
+                    && !selection.ignore.is_ignored(&package.name, Some(version))
+                {
+                    newer_in_other_channels.push(OtherChannelRelease {
+                        name: package.name.clone(),
+                        platform: platform.clone(),
+                        installed_version: package.version.clone(),
+                        latest_version: latest.map(str::to_string),
+                        channel: channel.clone(),
+                        version: version.clone(),
+                    });
+                }
+            }
+            if let Some(ref label) = label {
+                labeled_packages.push(LabeledPackage {
+                    name: package.name.clone(),
+                    version: package.version.clone(),
+                    platform: platform.clone(),
+                    label: label.clone(),
+                    stable_version: latest.map(str::to_string),
+                    superseded,
+                });
+            }
+
+            // Flag locked versions that were yanked or removed, independent of updates.
+            // A pinned channel or a non-main label may not carry the locked artifact,
+            // so skip those.
+            let channel_pinned = package.kind == pixi_outdated::pixi::PackageKind::Conda
+                && channel_overrides.get(&package.name, platform).is_some();
+            if let Some(info) = locked_info.filter(|_| !channel_pinned && label.is_none()) {
+                let status = info.locked_status(package);
+                if status != LockedStatus::Available {
+                    locked_issues.push(LockedIssue {
+                        name: package.name.clone(),
+                        version: package.version.clone(),
+                        platform: platform.clone(),
+                        status,
+                        annotations: Default::default(),
+                    });
+                }
+            }
+
+            if let Some(latest) = latest {
+                // R packages carry CRAN versions, where `_` separates a revision
+                // that is part of the release rather than a build suffix
+                let r_package = package.kind == pixi_outdated::pixi::PackageKind::Conda
+                    && pixi_outdated::cran::is_r_package(&package.name);
+                let (severity, is_update) = if r_package {
+                    (
+                        pixi_outdated::cran::classify(&package.version, latest),
+                        pixi_outdated::cran::is_newer(latest, &package.version),
+                    )
+                } else {
+                    (
+                        pixi_outdated::severity::classify(&package.version, latest),
+                        info.is_some_and(|info| info.is_update(latest, &package.version)),
+                    )
+                };
+                if selection.ignore.is_ignored(&package.name, Some(latest)) {
+                    if cli.verbose && !structured {
+                        println!("{}: {} (ignored)", package.name, latest);
+                    }
+                } else if label.is_some() && !superseded {
+                    // An older stable release is not an update for a pre-release
+                    if cli.verbose && !structured {
+                        println!(
+                            "{}: {} (no stable release supersedes it yet)",
+                            package.name, package.version
+                        );
+                    }
+                } else if is_update && !selection.passes_filter(severity) {
+                    if cli.verbose && !structured {
+                        println!(
+                            "{}: {} ({} update, filtered out)",
+                            package.name, latest, severity
+                        );
+                    }
+                } else if is_update {
+                    // Only dependencies with a requirement in the manifest have a
+                    // meaningful latest compatible version
+                    let specs = dependency_specs.get(&package.name, package.kind, platform);
+                    let latest_compatible = info
+                        .filter(|_| !specs.is_empty())
+                        .and_then(|info| info.latest_matching(&specs));
+
+                    let mut update = PackageUpdate {
                         name: package.name.clone(),
+                        kind: Some(package.kind),
+                        channel: latest_channel.clone(),
                         installed_version: package.version.clone(),
-                        latest_version: latest.clone(),
+                        latest_version: latest.to_string(),
+                        direct: package.is_explicit,
+                        features: dependency_specs
+                            .features(&package.name, package.kind)
+                            .into_iter()
+                            .map(str::to_string)
+                            .collect(),
+                        required_by: dependency_chains
+                            .get(platform)
+                            .and_then(|chains| chains.get(&package.name))
+                            .filter(|_| !package.is_explicit)
+                            .cloned()
+                            .unwrap_or_default(),
+                        latest_compatible,
+                        severity: Some(severity),
+                        activation_scripts: match package.kind {
+                            pixi_outdated::pixi::PackageKind::Conda => {
+                                let latest_artifact = info
+                                    .map(|info| info.artifacts(latest, platform))
+                                    .unwrap_or_default();
+                                activation_scripts
+                                    .scripts(&package.name, latest_artifact.first())
+                                    .await
+                            }
+                            pixi_outdated::pixi::PackageKind::Pypi => Vec::new(),
+                        },
+                        ..Default::default()
                     };
+
+                    update.installed_age_days = info
+                        .and_then(|info| info.release_date(&package.version))
+                        .map(|released| pixi_outdated::age::age_in_days(released, now));
+                    update.newer_available_days = info
+                        .and_then(|info| info.first_release_after(&package.version, latest))
+                        .map(|released| pixi_outdated::age::age_in_days(released, now));
+                    update.lag_days =
+                        info.and_then(|info| info.release_lag_days(&package.version, latest));
+                    // Flag installed versions older than the allowed lag
+                    if let (Some(max_lag), Some(age)) = (cli.max_lag, update.installed_age_days) {
+                        update.exceeds_max_lag = age > max_lag;
+                    }
+
+                    if cli.release_notes {
+                        update.release_notes = info
+                            .and_then(UpstreamInfo::release_notes)
+                            .map(str::to_string);
+                    }
+
+                    let subdir = package
+                        .source
+                        .as_deref()
+                        .and_then(pixi_outdated::conda::extract_subdir);
+                    update.size_delta = info.and_then(|info| info.size_delta(package, latest));
+                    if let Some(profile) = cli.risk_profile {
+                        let assessment = profile.assess(&pixi_outdated::risk::RiskInput {
+                            installed_version: &package.version,
+                            latest_version: latest,
+                            kind: package.kind,
+                            is_binary: subdir.as_deref().is_some_and(|s| s != "noarch"),
+                            size_delta: update.size_delta,
+                            has_activation_scripts: !update.activation_scripts.is_empty(),
+                        });
+                        update.risk = Some(assessment.group);
+                        update.risk_reasons = assessment.reasons;
+                    }
+
+                    if cli.auto_merge {
+                        let latest_dependencies =
+                            info.and_then(|info| info.dependencies(latest, subdir.as_deref()));
+                        let skipped_yanked = info
+                            .map(|info| info.yanked_between(&package.version, latest))
+                            .unwrap_or_default();
+                        let (decision, reasons) =
+                            tool_config
+                                .auto_merge
+                                .decide(&pixi_outdated::automerge::MergeInput {
+                                    severity,
+                                    installed_dependencies: &package.depends,
+                                    latest_dependencies: latest_dependencies.as_deref(),
+                                    skipped_yanked: &skipped_yanked,
+                                    activation_scripts: &update.activation_scripts,
+                                });
+                        update.merge = Some(decision);
+                        update.merge_reasons = reasons;
+                    }
+
+                    plan.updates.push(PlannedUpdate {
+                        name: package.name.clone(),
+                        kind: package.kind,
+                        platform: platform.clone(),
+                        from_version: package.version.clone(),
+                        to_version: latest.to_string(),
+                    });
+
                     platform_package_updates.push(update);
-                } else if cli.verbose && !cli.json {
-                    println!("{}: {} (up to date)", package.name, package.version);
+                } else if cli.verbose && !structured {
+                    println!(
+                        "{}: {} ({})",
+                        package.name,
+                        package.version,
+                        CheckStatus::UpToDate
+                    );
+                }
+            } else {
+                let status = match lookup {
+                    Some(VersionLookup::NotFound) => CheckStatus::NotFound,
+                    Some(VersionLookup::Failed(message)) => CheckStatus::Error {
+                        message: message.clone(),
+                    },
+                    // Nothing newer passed the pins, requirements or filters
+                    Some(VersionLookup::Found(_)) => {
+                        if cli.verbose && !structured {
+                            println!(
+                                "{}: {} ({})",
+                                package.name,
+                                package.version,
+                                CheckStatus::UpToDate
+                            );
+                        }
+                        continue;
+                    }
+                    // Packages without a channel URL were never queried
+                    None if key.channel.is_none() => CheckStatus::NoSource,
+                    None => continue,
+                };
+                if cli.verbose && !structured {
+                    println!("{}: {} ({})", package.name, package.version, status);
                 }
-            } else if cli.verbose && !cli.json {
+                unchecked.push(UncheckedPackage {
+                    name: package.name.clone(),
+                    kind: package.kind,
+                    version: package.version.clone(),
+                    platform: platform.clone(),
+                    status,
+                    annotations: Default::default(),
+                });
+            }
+        }
+
+        platform_updates.insert(platform.clone(), platform_package_updates);
+    }
+
+    if let Some(ref package) = cli.why_latest {
+        print_candidates(package, &why_latest, structured);
+    }
+
+    if cli.release_notes {
+        // channeldata.json comes from the same hosts as the repodata, so it
+        // is fetched with the workspace's credentials and mirrors
+        let client = workspace
+            .authenticated_client()
+            .map_err(|e| anyhow::anyhow!("Failed to get the HTTP client: {}", e))?;
+        let cache = pixi_outdated::cache::channeldata_cache(cli.cache_ttl);
+        link_conda_release_notes(
+            client,
+            cache.as_ref(),
+            &mut platform_updates,
+            fixtures_dir.as_deref(),
+        )
+        .await;
+    }
+
+    // An interpreter update also has to be allowed by the manifest, and every
+    // locked PyPI package needs wheels for the new version
+    let mut python_upgrades = Vec::new();
+    for (platform, updates) in &platform_updates {
+        let Some(python) = updates.iter().find(|u| {
+            u.name == "python" && u.kind == Some(pixi_outdated::pixi::PackageKind::Conda)
+        }) else {
+            continue;
+        };
+
+        let locked_pypi = platform_packages[platform]
+            .iter()
+            .filter(|p| p.kind == pixi_outdated::pixi::PackageKind::Pypi)
+            .filter_map(|package| {
+                let key = PackageKey {
+                    name: package.name.clone(),
+                    channel: None,
+                    kind: package.kind,
+                };
+                match version_cache.get(&key).and_then(VersionLookup::info) {
+                    Some(UpstreamInfo::Pypi(info)) => info
+                        .release(&package.version)
+                        .map(|files| (package.name.as_str(), files)),
+                    _ => None,
+                }
+            });
+
+        python_upgrades.push(pixi_outdated::python::PythonUpgrade::assess(
+            platform,
+            &python.installed_version,
+            &python.latest_version,
+            &dependency_specs.get("python", pixi_outdated::pixi::PackageKind::Conda, platform),
+            dependency_specs.requires_python.as_deref(),
+            locked_pypi,
+        ));
+    }
+
+    locked_issues.sort_by(|a, b| (&a.name, &a.platform).cmp(&(&b.name, &b.platform)));
+    unchecked.sort_by(|a, b| (&a.name, &a.platform).cmp(&(&b.name, &b.platform)));
+    disallowed_channels.sort_by(|a, b| (&a.name, &a.platform).cmp(&(&b.name, &b.platform)));
+    unlisted_channels.sort_by(|a, b| (&a.name, &a.platform).cmp(&(&b.name, &b.platform)));
+    labeled_packages.sort_by(|a, b| (&a.name, &a.platform).cmp(&(&b.name, &b.platform)));
+    newer_in_other_channels.sort_by(|a, b| (&a.name, &a.platform).cmp(&(&b.name, &b.platform)));
+    held_back_by_license.sort_by(|a, b| (&a.name, &a.platform).cmp(&(&b.name, &b.platform)));
+    plan.updates
+        .sort_by(|a, b| (&a.platform, &a.name).cmp(&(&b.platform, &b.name)));
+
+    // Every package was skipped before it could be queried (e.g. no channel URL)
+    let status = if version_cache.is_empty() {
+        RunStatus::AllPackagesSkipped
+    } else {
+        RunStatus::Ok
+    };
+
+    let libyears = pixi_outdated::libyears::Libyears::from_updates(&platform_updates);
+    let mut report = Report {
+        metadata: Some(metadata),
+        status,
+        platforms: platform_updates,
+        locked_issues,
+        unchecked,
+        disallowed_channels,
+        unlisted_channels,
+        labeled_packages,
+        newer_in_other_channels,
+        held_back_by_license,
+        python_upgrades,
+        vulnerabilities: Vec::new(),
+        audit_unavailable: None,
+        unaudited: Vec::new(),
+        commands: Vec::new(),
+        explanations: Vec::new(),
+        libyears: Some(libyears),
+        sources: source_tracker.into_stats(),
+        repodata,
+        network,
+        content_hash: None,
+        locked: all_locked,
+        checked: platform_packages
+            .iter()
+            .flat_map(|(platform, packages)| {
+                packages
+                    .iter()
+                    .map(|package| (platform.clone(), package.kind, package.name.clone()))
+            })
+            .collect(),
+    };
+    // Auto-merge labels need the audit too, but only `--audit` reports it
+    if cli.audit || cli.auto_merge {
+        let osv = match fixtures_dir {
+            Some(ref dir) => pixi_outdated::audit::OsvSource::Fixtures(dir.join("osv")),
+            None => pixi_outdated::audit::OsvSource::default(),
+        };
+        let overrides = pixi_outdated::audit::parse_overrides(&tool_config.osv_packages)?;
+        // An OSV outage shouldn't cost the rest of the report
+        let audit = pixi_outdated::audit::audit(&http_client, &osv, &report, &overrides)
+            .await
+            .inspect_err(|e| eprintln!("Warning: vulnerability audit unavailable: {:#}", e));
+        if cli.auto_merge {
+            tool_config
+                .auto_merge
+                .review_vulnerable(&mut report.platforms, audit.as_deref().ok());
+        }
+        if cli.audit {
+            report.unaudited = pixi_outdated::audit::unaudited(&report, &overrides);
+            match audit {
+                Ok(vulnerabilities) => report.vulnerabilities = vulnerabilities,
+                Err(e) => report.audit_unavailable = Some(format!("{:#}", e)),
+            }
+        }
+    }
+    if cli.verify_solvable {
+        let LoadedLockFile::Full(ref locked) = lock_file else {
+            anyhow::bail!("--verify-solvable can't solve a lockfile in a newer format");
+        };
+        let environment_name = environment.name().as_str();
+        let locked_environment = locked
+            .environment(environment_name)
+            .ok_or_else(|| anyhow::anyhow!("Environment '{}' is not locked", environment_name))?;
+        // Each platform is solved on its own, from the environment's
+        // current requirements and locked records
+        let mut environments = std::collections::HashMap::new();
+        for platform in &platforms_to_check {
+            let parsed: rattler_conda_types::Platform = platform.parse()?;
+            let records = locked_environment
+                .conda_repodata_records(parsed)
+                .map_err(|e| anyhow::anyhow!("Failed to read the locked records: {}", e))?
+                .unwrap_or_default();
+            environments.insert(
+                platform.clone(),
+                pixi_outdated::solvable::SolveEnvironment {
+                    channels: manifest_channels.urls().to_vec(),
+                    channel_priority: manifest_channels.priority,
+                    specs: pixi_outdated::solvable::requirements(
+                        &dependency_specs,
+                        &records,
+                        platform,
+                    )?,
+                    locked: records,
+                    virtual_packages: environment.virtual_packages(parsed),
+                },
+            );
+        }
+        pixi_outdated::solvable::verify_solvable(
+            &mut report,
+            &gateway,
+            &environments,
+            workspace.config().max_concurrent_solves(),
+        )
+        .await?;
+    }
+    if let Some(ref name) = cli.explain {
+        // The locked requirements, which the loaded lockfile doesn't keep
+        let lock = pixi_outdated::parser::parse_lockfile(&lock_file_path)?;
+        report.explanations = pixi_outdated::explain::explain(
+            &report,
+            name,
+            &lock,
+            environment.name().as_str(),
+            &dependency_specs,
+        );
+    }
+    if cli.suggest_commands {
+        report.commands =
+            pixi_outdated::commands::suggest_commands(&report, environment.name().as_str());
+    }
+    report.annotate(&tool_config.annotations);
+    report.content_hash = Some(report.compute_content_hash()?);
+    if !context.combined_output {
+        // The tree of each platform with updates, for `--tree`
+        let trees: std::collections::BTreeMap<String, String> = dependency_graphs
+            .iter()
+            .filter_map(|(platform, (graph, roots))| {
+                let latest: std::collections::HashMap<String, String> = report
+                    .platforms
+                    .get(platform)?
+                    .iter()
+                    .map(|update| (update.name.clone(), update.latest_version.clone()))
+                    .collect();
+                let roots: Vec<&str> = roots.iter().map(String::as_str).collect();
+                (!latest.is_empty()).then(|| (platform.clone(), graph.render_tree(&roots, &latest)))
+            })
+            .collect();
+        print_report(&report, &cli, &platforms_to_check, &trees, locale)?;
+    }
+
+    let pins = pixi_outdated::pins::Pins::from_updates(&report.platforms, |key| {
+        version_cache.get(key).and_then(VersionLookup::info)
+    });
+    // Report files hold a single report, which several environments or
+    // workspaces would overwrite one after another
+    if !context.combined_output {
+        write_output_files(
+            &cli.output_files,
+            workspace.root(),
+            &report,
+            &plan,
+            &pins,
+            &locale,
+        )?;
+    }
+    run_post_hooks(
+        &tool_config.hooks,
+        &cli.output_files,
+        workspace.root(),
+        &report,
+        &plan.environment,
+    )?;
+    send_stats(&http_client, &tool_config.stats, &report, &plan.environment).await;
+
+    if cli.update {
+        let manifest_path = manifest_path
+            .as_deref()
+            .ok_or_else(|| anyhow::anyhow!("No manifest found to update"))?;
+        if update_manifest(
+            manifest_path,
+            &plan,
+            &report.checked,
+            cli.dry_run,
+            structured,
+        )? {
+            let hint = "Run `pixi update` to bring the lockfile in line with the new requirements";
+            if structured {
+                eprintln!("{}", hint);
+            } else {
+                println!("{}", hint);
+            }
+        }
+    }
+
+    if cli.write_lockfile {
+        write_lockfile(
+            &cli,
+            &workspace.lock_file_path(),
+            &report,
+            plan.clone(),
+            structured,
+        )
+        .await?;
+    }
+
+    if cli.interactive {
+        let manifest_path = manifest_path
+            .as_deref()
+            .ok_or_else(|| anyhow::anyhow!("No manifest found to update"))?;
+        select_and_apply_updates(&cli, manifest_path, plan, &report.checked).await?;
+    }
+
+    Ok(report)
+}
+
+/// The locale to format numbers and sizes in: `--locale`, or the one the
+/// environment sets
+fn locale(cli: &Cli) -> pixi_outdated::locale::Locale {
+    cli.locale
+        .unwrap_or_else(pixi_outdated::locale::Locale::from_environment)
+}
+
+/// Print a report in the format the run asked for
+fn print_report(
+    report: &Report,
+    cli: &Cli,
+    platforms: &[String],
+    trees: &std::collections::BTreeMap<String, String>,
+    locale: pixi_outdated::locale::Locale,
+) -> Result<()> {
+    let structured = cli.format != OutputFormat::Text;
+    if let Some(rendered) = pixi_outdated::report::render(report, cli.format, &locale)? {
+        println!("{}", rendered.trim_end());
+    } else if cli.tree {
+        for (platform, tree) in trees {
+            if cli.platform.is_none() {
+                println!("\n=== Platform: {} ===", platform);
+            }
+            print!("{}", tree);
+        }
+    } else {
+        print_updates(
+            &report.platforms,
+            platforms,
+            cli.platform.is_none(),
+            cli.risk_profile.is_some(),
+            &locale,
+        );
+    }
+
+    if !structured && report.status != RunStatus::Ok {
+        println!("{}", report.status_message());
+    }
+
+    if let (Some(max_lag), false) = (cli.max_lag, structured) {
+        let lagging: std::collections::BTreeSet<&str> = report
+            .platforms
+            .values()
+            .flatten()
+            .filter(|update| update.exceeds_max_lag)
+            .map(|update| update.name.as_str())
+            .collect();
+        if !lagging.is_empty() {
+            println!(
+                "\n{} package(s) exceed the maximum lag of {} days: {}",
+                lagging.len(),
+                max_lag,
+                lagging.into_iter().collect::<Vec<_>>().join(", ")
+            );
+        }
+    }
+
+    let size_deltas = report.size_delta_totals();
+    if !structured && size_deltas.values().any(|delta| *delta != 0) {
+        let totals: Vec<String> = size_deltas
+            .iter()
+            .map(|(platform, delta)| format!("{} ({})", locale.format_size_delta(*delta), platform))
+            .collect();
+        println!("\nDownload size change: {}", totals.join(", "));
+    }
+
+    if let Some(libyears) = report
+        .libyears
+        .filter(|libyears| !structured && libyears.days() > 0)
+    {
+        println!("\nBehind by {}", libyears);
+    }
+
+    if cli.fail_on_direct {
+        let outdated = report.outdated_direct();
+        if !outdated.is_empty() {
+            eprintln!(
+                "\n{} direct dependency(s) outdated: {}",
+                outdated.len(),
+                outdated.into_iter().collect::<Vec<_>>().join(", ")
+            );
+        }
+    }
+
+    if !structured && !report.locked_issues.is_empty() {
+        print_locked_issues(&report.locked_issues);
+    }
+
+    if !structured && !report.unchecked.is_empty() {
+        println!("\n=== Not checked ===");
+        for unchecked in &report.unchecked {
+            println!("{} [{}]", unchecked, unchecked.platform);
+        }
+    }
+
+    if !structured && !report.disallowed_channels.is_empty() {
+        println!("\n=== Not from an allowed channel ===");
+        for disallowed in &report.disallowed_channels {
+            println!("{} [{}]", disallowed, disallowed.platform);
+        }
+    }
+
+    if !structured && !report.unlisted_channels.is_empty() {
+        println!("\n=== Locked from channels the manifest no longer lists ===");
+        for unlisted in &report.unlisted_channels {
+            println!("{} [{}]", unlisted, unlisted.platform);
+        }
+    }
+
+    if !structured && !report.newer_in_other_channels.is_empty() {
+        println!("\n=== Newer in other channels ===");
+        for release in &report.newer_in_other_channels {
+            println!("{} [{}]", release, release.platform);
+        }
+    }
+
+    if !structured && report.audit_unavailable.is_some() {
+        println!("\n=== Known vulnerabilities ===");
+        println!("Audit unavailable, locked versions were not checked");
+    } else if !structured && !report.vulnerabilities.is_empty() {
+        println!("\n=== Known vulnerabilities ===");
+        for vulnerable in &report.vulnerabilities {
+            println!("{}", vulnerable);
+        }
+    }
+    if !structured && !report.unaudited.is_empty() {
+        println!(
+            "\nNot audited (no OSV package known, map them in osv-packages): {}",
+            report.unaudited.join(", ")
+        );
+    }
+
+    if !structured && !report.held_back_by_license.is_empty() {
+        println!("\n=== Held back by license ===");
+        for held_back in &report.held_back_by_license {
+            println!("{} [{}]", held_back, held_back.platform);
+        }
+    }
+
+    if !structured && !report.python_upgrades.is_empty() {
+        println!("\n=== Python ===");
+        for upgrade in &report.python_upgrades {
+            println!("{}: {}", upgrade.platform, upgrade);
+        }
+    }
+
+    if !structured && !report.labeled_packages.is_empty() {
+        println!("\n=== Installed from non-main labels ===");
+        for labeled in &report.labeled_packages {
+            println!("{} ({})", labeled, labeled.platform);
+        }
+    }
+
+    if let Some(ref name) = cli.explain {
+        if !structured {
+            println!("\n=== Why {} isn't at its latest version ===", name);
+            if report.explanations.is_empty() {
                 println!(
-                    "{}: {} (no newer version found)",
-                    package.name, package.version
+                    "{}",
+                    pixi_outdated::explain::nothing_to_explain(&report, name)
                 );
             }
+            for explanation in &report.explanations {
+                print!("{}", explanation);
+            }
         }
+    }
 
-        platform_updates.insert(platform.clone(), platform_package_updates);
+    // Unindented, so the block can be pasted into a shell as it is
+    if !structured && !report.commands.is_empty() {
+        println!("\n=== To apply every update ===");
+        for command in &report.commands {
+            println!("{}", command);
+        }
     }
 
-    // Output results
-    if cli.json {
-        // JSON output: grouped by platform
-        println!("{}", serde_json::to_string_pretty(&platform_updates)?);
-    } else if check_multiple_platforms {
+    if (cli.verbose || cli.timings) && !structured && !report.sources.is_empty() {
+        println!("\n=== Sources ===");
+        for source in &report.sources {
+            println!("{}", source.describe(&locale));
+        }
+        println!("{}", report.network.describe(&locale));
+    }
+
+    if (cli.verbose || cli.timings) && !structured && !report.repodata.is_empty() {
+        println!("\n=== Repodata ===");
+        for fetch in &report.repodata {
+            println!("{}", fetch.describe(&locale));
+        }
+    }
+
+    if let (Some(metadata), false) = (&report.metadata, structured) {
+        println!("\n{}", metadata.summary());
+    }
+
+    Ok(())
+}
+
+/// Print updates as text: grouped by risk, as a table on terminals, or
+/// coalesced across platforms
+fn print_updates(
+    platform_updates: &PlatformUpdates,
+    platforms_to_check: &[String],
+    multiple_platforms: bool,
+    risk_profile: bool,
+    locale: &pixi_outdated::locale::Locale,
+) {
+    if risk_profile {
+        print_risk_groups(platform_updates, multiple_platforms, locale);
+    } else if std::io::stdout().is_terminal() {
+        // Interactive terminals get an aligned table with one row per update
+        let rows = pixi_outdated::output::table_rows(platform_updates, locale);
+        if !rows.is_empty() {
+            print!("{}", pixi_outdated::output::render_table(&rows, locale));
+        }
+    } else if multiple_platforms {
         // Coalesce updates: find packages that have the same update across ALL platforms
         let mut common_updates: Vec<PackageUpdate> = Vec::new();
         let mut platform_specific_updates: std::collections::HashMap<String, Vec<PackageUpdate>> =
@@ -408,11 +2656,7 @@ async fn run(cli: Cli) -> Result<()> {
                         // Check if this exact update exists in all other platforms
                         let is_common = platforms.iter().skip(1).all(|plat| {
                             platform_updates.get(plat).is_some_and(|updates| {
-                                updates.iter().any(|u| {
-                                    u.name == update.name
-                                        && u.installed_version == update.installed_version
-                                        && u.latest_version == update.latest_version
-                                })
+                                updates.iter().any(|u| u.is_same_update(update))
                             })
                         });
 
@@ -424,15 +2668,13 @@ async fn run(cli: Cli) -> Result<()> {
             }
 
             // Now collect platform-specific updates (excluding common ones)
-            for (platform, updates) in &platform_updates {
+            for (platform, updates) in platform_updates {
                 let specific: Vec<PackageUpdate> = updates
                     .iter()
                     .filter(|update| {
-                        !common_updates.iter().any(|common| {
-                            common.name == update.name
-                                && common.installed_version == update.installed_version
-                                && common.latest_version == update.latest_version
-                        })
+                        !common_updates
+                            .iter()
+                            .any(|common| common.is_same_update(update))
                     })
                     .cloned()
                     .collect();
@@ -447,23 +2689,17 @@ async fn run(cli: Cli) -> Result<()> {
         if !common_updates.is_empty() {
             println!("\n=== All Platforms ===");
             for update in &common_updates {
-                println!(
-                    "{}: {} -> {}",
-                    update.name, update.installed_version, update.latest_version
-                );
+                println!("{}", update.describe(locale));
             }
         }
 
         // Print platform-specific updates
-        for platform in &platforms_to_check {
+        for platform in platforms_to_check {
             if let Some(updates) = platform_specific_updates.get(platform) {
                 if !updates.is_empty() {
                     println!("\n=== Platform: {} ===", platform);
                     for update in updates {
-                        println!(
-                            "{}: {} -> {}",
-                            update.name, update.installed_version, update.latest_version
-                        );
+                        println!("{}", update.describe(locale));
                     }
                 }
             }
@@ -472,17 +2708,1199 @@ async fn run(cli: Cli) -> Result<()> {
         // Single platform output
         if let Some(updates) = platform_updates.values().next() {
             for update in updates {
-                println!(
-                    "{}: {} -> {}",
-                    update.name, update.installed_version, update.latest_version
-                );
+                println!("{}", update.describe(locale));
+            }
+        }
+    }
+}
+
+/// Print updates grouped by risk, listing the platforms each update applies to
+fn print_risk_groups(
+    platform_updates: &PlatformUpdates,
+    show_platforms: bool,
+    locale: &pixi_outdated::locale::Locale,
+) {
+    use pixi_outdated::risk::RiskGroup;
+
+    for group in RiskGroup::ALL {
+        // The same update can land in different groups per platform, e.g. when
+        // only some builds grew in size
+        let mut updates: std::collections::BTreeMap<String, (&PackageUpdate, Vec<&str>)> =
+            std::collections::BTreeMap::new();
+        for (platform, platform_updates) in platform_updates {
+            for update in platform_updates.iter().filter(|u| u.risk == Some(group)) {
+                updates
+                    .entry(update.describe(locale))
+                    .or_insert_with(|| (update, Vec::new()))
+                    .1
+                    .push(platform.as_str());
             }
         }
+
+        if updates.is_empty() {
+            continue;
+        }
+
+        println!("\n=== {} ===", group);
+        for (update, platforms) in updates.values() {
+            if show_platforms {
+                println!("{} ({})", update.describe(locale), platforms.join(", "));
+            } else {
+                println!("{}", update.describe(locale));
+            }
+        }
+    }
+}
+
+/// The channel to query for a package: the channel pinned in the manifest,
+/// resolved against the configured channel alias, falling back to the
+/// channel the locked artifact came from. Packages without a source URL,
+/// such as those built from a local path, get none and are reported as
+/// unchecked with `CheckStatus::NoSource`.
+fn package_channel(
+    package: &pixi_outdated::pixi::PixiPackage,
+    platform: &str,
+    overrides: &pixi_outdated::parser::ChannelOverrides,
+    channel_alias: &str,
+) -> Option<String> {
+    package.source.as_ref()?;
+    overrides
+        .get(&package.name, platform)
+        .filter(|_| package.kind == pixi_outdated::pixi::PackageKind::Conda)
+        .map(|channel| pixi_outdated::policy::channel_url(channel, channel_alias))
+        .or_else(|| pixi_outdated::conda::locked_channel(package))
+}
+
+/// Link conda updates to the GitHub releases named in their channel's
+/// `channeldata.json`, read from `<fixtures>/<channel>/channeldata.json`
+/// with `--source fixtures=<DIR>`. Updates from a channel without one go unlinked.
+async fn link_conda_release_notes(
+    client: &reqwest_middleware::ClientWithMiddleware,
+    cache: Option<&pixi_outdated::cache::ResponseCache>,
+    updates: &mut PlatformUpdates,
+    fixtures_dir: Option<&std::path::Path>,
+) {
+    let channels: std::collections::BTreeSet<String> = updates
+        .values()
+        .flatten()
+        .filter(|update| update.kind == Some(pixi_outdated::pixi::PackageKind::Conda))
+        .filter_map(|update| update.channel.clone())
+        .collect();
+
+    let mut channeldata = std::collections::HashMap::new();
+    for channel in channels {
+        let loaded = match fixtures_dir {
+            Some(dir) => {
+                let path = dir
+                    .join(pixi_outdated::output::channel_label(&channel))
+                    .join("channeldata.json");
+                if !path.is_file() {
+                    continue;
+                }
+                pixi_outdated::release_notes::load_channeldata(&path)
+            }
+            None => pixi_outdated::release_notes::fetch_channeldata(client, cache, &channel).await,
+        };
+        match loaded {
+            Ok(data) => {
+                channeldata.insert(channel, data);
+            }
+            Err(e) => eprintln!("Warning: {:#}", e),
+        }
+    }
+
+    for update in updates.values_mut().flatten() {
+        if let Some(data) = update.channel.as_ref().and_then(|c| channeldata.get(c)) {
+            update.release_notes = data.release_notes(&update.name);
+        }
+    }
+}
+
+/// Bump the version requirements of the planned updates in the manifest, or
+/// print the changes as a unified diff for a dry run. Returns whether the
+/// manifest was written.
+fn update_manifest(
+    manifest_path: &std::path::Path,
+    plan: &UpdatePlan,
+    checked: &std::collections::BTreeSet<(String, pixi_outdated::pixi::PackageKind, String)>,
+    dry_run: bool,
+    structured: bool,
+) -> Result<bool> {
+    let latest = pixi_outdated::upgrade::common_versions(&plan.updates, checked);
+
+    let content = std::fs::read_to_string(manifest_path)
+        .with_context(|| format!("Failed to read {}", manifest_path.display()))?;
+    let is_pyproject = manifest_path
+        .file_name()
+        .is_some_and(|name| name == "pyproject.toml");
+    let (updated, changes) =
+        pixi_outdated::upgrade::update_manifest(&content, is_pyproject, &latest)?;
+
+    // Keep structured output on stdout parseable
+    let print = |line: String| {
+        if structured {
+            eprintln!("{}", line);
+        } else {
+            println!("{}", line);
+        }
+    };
+
+    if changes.is_empty() {
+        print(format!(
+            "No version requirements in {} need to change",
+            manifest_path.display()
+        ));
+        return Ok(false);
+    }
+
+    if dry_run {
+        // Paths relative to the current directory, so the diff can be fed to `git apply`
+        let label = std::env::current_dir()
+            .ok()
+            .and_then(|dir| {
+                manifest_path
+                    .strip_prefix(dir)
+                    .ok()
+                    .map(|p| p.to_path_buf())
+            })
+            .unwrap_or_else(|| manifest_path.to_path_buf());
+        print(
+            pixi_outdated::diff::unified_diff(
+                &content,
+                &updated,
+                &format!("a/{}", label.display()),
+                &format!("b/{}", label.display()),
+                pixi_outdated::diff::DEFAULT_CONTEXT,
+            )
+            .trim_end()
+            .to_string(),
+        );
+        return Ok(false);
+    }
+
+    std::fs::write(manifest_path, updated)
+        .with_context(|| format!("Failed to write {}", manifest_path.display()))?;
+
+    print(format!(
+        "\nUpdated {} version requirement(s) in {}:",
+        changes.len(),
+        manifest_path.display()
+    ));
+    for change in &changes {
+        print(change.to_string());
+    }
+
+    Ok(true)
+}
+
+/// Relock the updates of a run like `pixi update`, leaving the manifest as
+/// it is. Updates whose requirement excludes the latest version are left
+/// out, unless `--update` has just bumped the requirements, as are those
+/// found unsolvable.
+async fn write_lockfile(
+    cli: &Cli,
+    lock_file_path: &std::path::Path,
+    report: &Report,
+    mut plan: UpdatePlan,
+    structured: bool,
+) -> Result<()> {
+    let requirements_bumped = cli.update;
+    // Keep structured output on stdout parseable
+    let print = |line: String| {
+        if structured {
+            eprintln!("{}", line);
+        } else {
+            println!("{}", line);
+        }
+    };
+
+    let blocked: std::collections::BTreeSet<&str> = report
+        .platforms
+        .values()
+        .flatten()
+        .filter(|update| !requirements_bumped && update.needs_requirement_change())
+        .map(|update| update.name.as_str())
+        .collect();
+    if !blocked.is_empty() {
+        print(format!(
+            "Not relocking {}: the manifest excludes the latest version, use --update to bump the requirement",
+            blocked.iter().copied().collect::<Vec<_>>().join(", ")
+        ));
+    }
+    // Updates a trial solve found to be dead ends
+    let unsolvable: std::collections::BTreeSet<&str> = report
+        .platforms
+        .values()
+        .flatten()
+        .filter(|update| update.unsolvable.is_some())
+        .map(|update| update.name.as_str())
+        .collect();
+    if !unsolvable.is_empty() {
+        print(format!(
+            "Not relocking {}: the workspace can't be solved with the latest version",
+            unsolvable.iter().copied().collect::<Vec<_>>().join(", ")
+        ));
+    }
+    let admitted: Vec<(String, pixi_outdated::pixi::PackageKind)> = plan
+        .choices()
+        .into_iter()
+        .filter(|choice| {
+            !blocked.contains(choice.name.as_str()) && !unsolvable.contains(choice.name.as_str())
+        })
+        .map(|choice| (choice.name, choice.kind))
+        .collect();
+    plan.retain_packages(&admitted);
+    if plan.updates.is_empty() {
+        print("Nothing to relock".to_string());
+        return Ok(());
+    }
+
+    let workspace = locate_workspace(cli.manifest.clone(), cli.config.clone(), cli.proxy.as_ref())?;
+    update_packages(&workspace, &plan).await?;
+
+    // Other requirements can hold a package below its latest version
+    let after = locked_versions(lock_file_path, &plan.environment)?;
+    let unapplied = plan.unapplied_updates(&after);
+    print(format!(
+        "Relocked {} of {} updates in {}",
+        plan.updates.len() - unapplied.len(),
+        plan.updates.len(),
+        lock_file_path.display()
+    ));
+    for u in unapplied {
+        print(format!(
+            "  {} on {}: latest {}, locked {}",
+            u.name,
+            u.platform,
+            u.to_version,
+            after
+                .get(&(u.platform.clone(), u.name.clone()))
+                .map_or("nothing", String::as_str)
+        ));
+    }
+    Ok(())
+}
+
+/// Let the user pick which outdated packages to update, then bump their
+/// requirements in the manifest and update them in the lockfile
+async fn select_and_apply_updates(
+    cli: &Cli,
+    manifest_path: &std::path::Path,
+    mut plan: UpdatePlan,
+    checked: &std::collections::BTreeSet<(String, pixi_outdated::pixi::PackageKind, String)>,
+) -> Result<()> {
+    let choices = plan.choices();
+    if choices.is_empty() {
+        return Ok(());
+    }
+
+    println!();
+    let items: Vec<String> = choices.iter().map(ToString::to_string).collect();
+    let selected = dialoguer::MultiSelect::new()
+        .with_prompt("Select packages to update (space to toggle, enter to confirm)")
+        .items(&items)
+        .interact_opt()
+        .context("Failed to read the selection")?
+        .unwrap_or_default();
+
+    if selected.is_empty() {
+        println!("No packages selected");
+        return Ok(());
+    }
+
+    let packages: Vec<(String, pixi_outdated::pixi::PackageKind)> = selected
+        .iter()
+        .map(|&idx| (choices[idx].name.clone(), choices[idx].kind))
+        .collect();
+    plan.retain_packages(&packages);
+
+    update_manifest(manifest_path, &plan, checked, false, false)?;
+    // Loaded again to pick up the bumped requirements
+    let workspace = locate_workspace(
+        Some(manifest_path.to_path_buf()),
+        cli.config.clone(),
+        cli.proxy.as_ref(),
+    )?;
+    update_packages(&workspace, &plan).await?;
+    println!("Updated {} package(s) in the lockfile", packages.len());
+
+    Ok(())
+}
+
+/// The fixture directory given with `--source fixtures=<DIR>`, if any
+fn fixtures_dir(source: Option<&str>) -> Result<Option<PathBuf>> {
+    let Some(source) = source else {
+        return Ok(None);
+    };
+    match source.split_once('=') {
+        Some(("fixtures", dir)) if !dir.is_empty() => {
+            let dir = PathBuf::from(dir);
+            if !dir.is_dir() {
+                anyhow::bail!("Fixture directory {} does not exist", dir.display());
+            }
+            Ok(Some(dir))
+        }
+        Some((backend, _)) if backend != "fixtures" => {
+            anyhow::bail!("Unknown source '{}' (expected: fixtures)", backend)
+        }
+        _ => anyhow::bail!(
+            "--source expects a backend and a directory, e.g. --source fixtures=tests/fixtures"
+        ),
+    }
+}
+
+/// Check the packages listed on stdin, without looking for a workspace
+async fn check_stdin_packages(cli: &Cli) -> Result<Report> {
+    let mut input = String::new();
+    std::io::stdin()
+        .read_to_string(&mut input)
+        .context("Failed to read packages from stdin")?;
+    let packages: Vec<pixi_outdated::check::InputPackage> =
+        serde_json::from_str(&input).context("Failed to parse the packages on stdin")?;
+    let options = pixi_outdated::check::CheckOptions {
+        platforms: cli.platform.iter().cloned().collect(),
+        fixtures: fixtures_dir(cli.source.as_deref())?,
+        selection: standalone_selection(cli, &std::env::current_dir()?)?,
+        network: Some(network_config(cli)?),
+        ..Default::default()
+    };
+    let report = pixi_outdated::check::check_packages(&packages, &options).await?;
+
+    let locale = locale(cli);
+    let platforms: Vec<String> = report.platforms.keys().cloned().collect();
+    print_report(&report, cli, &platforms, &Default::default(), locale)?;
+    write_output_files(
+        &cli.output_files,
+        &std::env::current_dir()?,
+        &report,
+        &UpdatePlan::new("stdin"),
+        &Default::default(),
+        &locale,
+    )?;
+    Ok(report)
+}
+
+/// Relock the packages of an update plan on its platforms, in process like
+/// `pixi update --no-install`: they're dropped from the lockfile and solved
+/// again, while everything else stays locked
+async fn update_packages(workspace: &pixi_core::Workspace, plan: &UpdatePlan) -> Result<()> {
+    let platforms = plan
+        .platforms()
+        .iter()
+        .map(|platform| platform.parse::<rattler_conda_types::Platform>())
+        .collect::<Result<Vec<_>, _>>()?;
+    let package_names = plan.package_names();
+
+    let lock_file = workspace
+        .load_lock_file()
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to load lock file: {}", e))?;
+    let relaxed = pixi_core::lock_file::filter_lock_file(
+        workspace,
+        &lock_file,
+        |environment, platform, package| {
+            environment.name().as_str() != plan.environment
+                || !platforms.contains(&platform)
+                || !package_names.contains(&package.name())
+        },
+    );
+    pixi_core::lock_file::UpdateContext::builder(workspace)
+        .with_lock_file(relaxed)
+        .with_no_install(true)
+        .finish()
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to prepare the relock: {}", e))?
+        .update()
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to relock {}: {}", package_names.join(", "), e))?
+        .write_to_disk()
+        .map_err(|e| anyhow::anyhow!("Failed to write the lock file: {}", e))?;
+    Ok(())
+}
+
+/// Locate the workspace, with `proxy` taking the place of the one in pixi's
+/// config for the repodata gateway and every other client pixi builds
+fn locate_workspace(
+    manifest: Option<PathBuf>,
+    config: ConfigCli,
+    proxy: Option<&url::Url>,
+) -> Result<pixi_core::Workspace> {
+    let manifest_search_path = match manifest {
+        Some(path) => DiscoveryStart::ExplicitManifest(path),
+        None => DiscoveryStart::CurrentDir,
+    };
+
+    Ok(WorkspaceLocator::for_cli()
+        .with_search_start(manifest_search_path)
+        .locate()?
+        .with_cli_config(cli_config(config, proxy)))
+}
+
+/// The pixi config given on the command line, with `proxy` taking the place
+/// of the configured one
+fn cli_config(config: ConfigCli, proxy: Option<&url::Url>) -> pixi_config::Config {
+    let mut config = pixi_config::Config::from(config);
+    if let Some(proxy) = proxy {
+        config.proxy_config.https = Some(proxy.clone());
+        config.proxy_config.http = Some(proxy.clone());
+    }
+    config
+}
+
+/// The packages a run selects and the updates it reports, from the command
+/// line and the project config and ignore file in `dir`
+fn selection(
+    cli: &Cli,
+    packages: pixi_outdated::select::PackageSelection,
+    tool_config: &pixi_outdated::config::ToolConfig,
+    dir: &std::path::Path,
+) -> Result<pixi_outdated::select::Selection> {
+    // Ignore rules from the project config, then those shared via a
+    // `.pixi-outdated-ignore` next to the manifest, which can re-include packages
+    let mut ignore = pixi_outdated::ignore::IgnoreFile::from_rules(&tool_config.ignore)?;
+    ignore.extend(pixi_outdated::ignore::IgnoreFile::discover(dir)?);
+    Ok(pixi_outdated::select::Selection {
+        packages,
+        exclude: pixi_outdated::ignore::ExcludePatterns::new(&cli.exclude)?,
+        ignore,
+        prereleases: cli.pre.clone(),
+        max_versions: tool_config.max_versions.clone(),
+        severities: cli.filter.clone(),
+    })
+}
+
+/// The selection of a check outside a workspace, reading the project config
+/// and ignore file from `dir` as a workspace run reads them from its root
+fn standalone_selection(
+    cli: &Cli,
+    dir: &std::path::Path,
+) -> Result<pixi_outdated::select::Selection> {
+    selection(
+        cli,
+        pixi_outdated::select::PackageSelection::new(&cli.packages, &cli.regex)?,
+        &pixi_outdated::config::ToolConfig::discover(dir, None)?,
+        dir,
+    )
+}
+
+/// How checks outside a workspace reach the network: through the global
+/// pixi config and the command line's, like a workspace run
+fn network_config(cli: &Cli) -> Result<pixi_outdated::check::NetworkConfig> {
+    Ok(pixi_outdated::check::NetworkConfig {
+        pixi_config: pixi_config::Config::load_global()
+            .merge_config(cli_config(cli.config.clone(), cli.proxy.as_ref())),
+        http_client: pixi_outdated::http::client(cli.proxy.as_ref())?,
+        cache_ttl: cli.cache_ttl,
+        jobs: cli.jobs.map(std::num::NonZeroUsize::get),
+        retry: pixi_outdated::retry::RetryPolicy {
+            max_attempts: cli.max_attempts.get(),
+            ..Default::default()
+        },
+        timeout: cli.timeout.map(std::time::Duration::from_secs),
+    })
+}
+
+/// Read the locked version of every package in an environment, on every platform
+fn locked_versions(lock_file_path: &std::path::Path, environment: &str) -> Result<LockedVersions> {
+    let content = std::fs::read_to_string(lock_file_path)
+        .with_context(|| format!("Failed to read {}", lock_file_path.display()))?;
+    let version = pixi_outdated::parser::lockfile_version(&content)
+        .with_context(|| format!("Failed to read {}", lock_file_path.display()))?;
+    if version > pixi_outdated::parser::SUPPORTED_LOCK_VERSION {
+        anyhow::bail!(
+            "{} uses lockfile format version {}, newer than version {} supported by this \
+             version of pixi-outdated; upgrade pixi-outdated to apply update plans",
+            lock_file_path.display(),
+            version,
+            pixi_outdated::parser::SUPPORTED_LOCK_VERSION
+        );
+    }
+    let lock_file = rattler_lock::LockFile::from_path(lock_file_path)
+        .map_err(|e| anyhow::anyhow!("Failed to read {}: {}", lock_file_path.display(), e))?;
+    let env = lock_file.environment(environment).ok_or_else(|| {
+        anyhow::anyhow!(
+            "Environment '{}' is not in {}",
+            environment,
+            lock_file_path.display()
+        )
+    })?;
+
+    let mut versions = LockedVersions::new();
+    for platform in env.platforms() {
+        for package in env.packages(platform).into_iter().flatten() {
+            let (name, version) = match package {
+                rattler_lock::LockedPackageRef::Conda(conda_pkg) => (
+                    conda_pkg.record().name.as_normalized().to_string(),
+                    conda_pkg.record().version.to_string(),
+                ),
+                rattler_lock::LockedPackageRef::Pypi(pypi_pkg, _) => {
+                    (pypi_pkg.name.to_string(), pypi_pkg.version.to_string())
+                }
+            };
+            versions.insert((platform.to_string(), name), version);
+        }
+    }
+
+    Ok(versions)
+}
+
+/// Apply an update plan: check that it still matches the lockfile, let pixi
+/// update the planned packages and verify the versions that were locked
+async fn apply_plan(
+    plan_path: &std::path::Path,
+    manifest: Option<PathBuf>,
+    config: ConfigCli,
+    proxy: Option<&url::Url>,
+) -> Result<()> {
+    let plan = UpdatePlan::load(plan_path)?;
+    if plan.updates.is_empty() {
+        println!("Nothing to apply: the plan contains no updates");
+        return Ok(());
+    }
+
+    let workspace = locate_workspace(manifest.clone(), config, proxy)?;
+    let lock_file_path = workspace.lock_file_path();
+
+    let before = locked_versions(&lock_file_path, &plan.environment)?;
+    let stale = plan.stale_updates(&before);
+    if !stale.is_empty() {
+        let details: Vec<String> = stale
+            .iter()
+            .map(|u| {
+                format!(
+                    "  {} on {}: planned from {}, locked {}",
+                    u.name,
+                    u.platform,
+                    u.from_version,
+                    before
+                        .get(&(u.platform.clone(), u.name.clone()))
+                        .map_or("nothing", String::as_str)
+                )
+            })
+            .collect();
+        anyhow::bail!(
+            "The lockfile changed since the plan was written; create a new plan:\n{}",
+            details.join("\n")
+        );
+    }
+
+    let original = std::fs::read(&lock_file_path)
+        .with_context(|| format!("Failed to read {}", lock_file_path.display()))?;
+    if let Err(e) = relock_and_verify(&workspace, &plan, &lock_file_path).await {
+        std::fs::write(&lock_file_path, &original).with_context(|| {
+            format!(
+                "{:#}; restoring {} also failed",
+                e,
+                lock_file_path.display()
+            )
+        })?;
+        return Err(e.context(format!(
+            "Applying the plan failed; restored the original {}",
+            lock_file_path.display()
+        )));
     }
 
     Ok(())
 }
 
+/// Relock the planned packages and fail unless every one of them ended up at
+/// its planned version
+async fn relock_and_verify(
+    workspace: &pixi_core::Workspace,
+    plan: &UpdatePlan,
+    lock_file_path: &std::path::Path,
+) -> Result<()> {
+    update_packages(workspace, plan).await?;
+
+    let after = locked_versions(lock_file_path, &plan.environment)?;
+    let unapplied = plan.unapplied_updates(&after);
+    println!(
+        "Applied {} of {} planned updates",
+        plan.updates.len() - unapplied.len(),
+        plan.updates.len()
+    );
+
+    if !unapplied.is_empty() {
+        let details: Vec<String> = unapplied
+            .iter()
+            .map(|u| {
+                format!(
+                    "  {} on {}: planned {}, locked {}",
+                    u.name,
+                    u.platform,
+                    u.to_version,
+                    after
+                        .get(&(u.platform.clone(), u.name.clone()))
+                        .map_or("nothing", String::as_str)
+                )
+            })
+            .collect();
+        anyhow::bail!(
+            "{} update(s) did not resolve to the planned version (check the manifest's version constraints):\n{}",
+            unapplied.len(),
+            details.join("\n")
+        );
+    }
+
+    Ok(())
+}
+
+/// Summarize many reports: worst offenders, most common outdated packages and per-team counts
+fn aggregate_reports(args: &AggregateArgs) -> Result<()> {
+    let reports = args
+        .reports
+        .iter()
+        .map(|path| {
+            let (project, team) = pixi_outdated::aggregate::project_and_team(path);
+            Ok((project, team, pixi_outdated::aggregate::load_report(path)?))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let summary = pixi_outdated::aggregate::aggregate(&reports);
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&summary)?);
+    } else {
+        print!(
+            "{}",
+            pixi_outdated::aggregate::render_text(&summary, args.top)
+        );
+    }
+
+    Ok(())
+}
+
+/// Print the report of the bundled example workspace, checked against its
+/// fixtures
+async fn print_demo_report(cli: &Cli) -> Result<()> {
+    let report = pixi_outdated::demo::demo_report().await?;
+    let locale = locale(cli);
+    let platforms: Vec<String> = report.platforms.keys().cloned().collect();
+    print_report(&report, cli, &platforms, &Default::default(), locale)
+}
+
+/// Report a run that had nothing to check
+async fn report_empty_run(
+    format: OutputFormat,
+    locale: &pixi_outdated::locale::Locale,
+    combined_output: bool,
+    files: &OutputFiles,
+    root: &std::path::Path,
+    config: &pixi_outdated::config::ToolConfig,
+    client: &reqwest::Client,
+    metadata: pixi_outdated::report::ReportMetadata,
+    status: RunStatus,
+) -> Result<Report> {
+    let environment = metadata.environment.clone();
+    let mut report = Report {
+        metadata: Some(metadata),
+        status,
+        ..Report::default()
+    };
+    report.content_hash = Some(report.compute_content_hash()?);
+
+    if !combined_output {
+        match pixi_outdated::report::render(&report, format, locale)? {
+            Some(rendered) => println!("{}", rendered.trim_end()),
+            None => {
+                println!("{}", status.message());
+                if let Some(ref metadata) = report.metadata {
+                    println!("\n{}", metadata.summary());
+                }
+            }
+        }
+    }
+
+    // An empty plan keeps a separate `apply` stage working
+    write_output_files(
+        files,
+        root,
+        &report,
+        &UpdatePlan::new(&environment),
+        &Default::default(),
+        locale,
+    )?;
+    run_post_hooks(&config.hooks, files, root, &report, &environment)?;
+    send_stats(client, &config.stats, &report, &environment).await;
+
+    Ok(report)
+}
+
+/// The candidates for `--why-latest` on one platform
+struct WhyLatest {
+    platform: String,
+    source: String,
+    /// The rendered candidates, with the decision taken for each
+    candidates: String,
+}
+
+/// Print the candidates considered for the latest version of a package,
+/// naming the platforms only where their candidates differ
+fn print_candidates(package: &str, why_latest: &[WhyLatest], json: bool) {
+    let mut output = String::new();
+
+    if why_latest.is_empty() {
+        output.push_str(&format!(
+            "No locked package named {} was checked\n",
+            package
+        ));
+    }
+
+    let mut groups: std::collections::BTreeMap<(&str, &str), Vec<&str>> =
+        std::collections::BTreeMap::new();
+    for entry in why_latest {
+        groups
+            .entry((&entry.source, &entry.candidates))
+            .or_default()
+            .push(&entry.platform);
+    }
+    let per_platform = groups.len() > 1;
+    for ((source, candidates), platforms) in groups {
+        if per_platform {
+            output.push_str(&format!(
+                "\nCandidates for {} ({}) on {}:\n",
+                package,
+                source,
+                platforms.join(", ")
+            ));
+        } else {
+            output.push_str(&format!("\nCandidates for {} ({}):\n", package, source));
+        }
+        output.push_str(candidates);
+    }
+
+    // Keep stdout valid JSON when combined with --json
+    if json {
+        eprint!("{}", output);
+    } else {
+        print!("{}", output);
+    }
+}
+
+/// A lockfile loaded through pixi, or read on a best-effort basis because it
+/// was written in a newer format
+enum LoadedLockFile {
+    Full(rattler_lock::LockFile),
+    BestEffort(pixi_outdated::parser::PixiLock),
+}
+
+impl LoadedLockFile {
+    /// Platforms locked for an environment
+    fn platforms(&self, environment: &str) -> Vec<String> {
+        match self {
+            LoadedLockFile::Full(lock_file) => lock_file
+                .environment(environment)
+                .map(|env| env.platforms().map(|p| p.to_string()).collect())
+                .unwrap_or_default(),
+            LoadedLockFile::BestEffort(lock_file) => lock_file.platforms(environment),
+        }
+    }
+
+    /// The channels of an environment in priority order, as full base URLs
+    /// like those extracted from locked package URLs. Channel names resolve
+    /// against `channel_alias`.
+    fn channels(&self, environment: &str, channel_alias: &str) -> Vec<String> {
+        let urls = match self {
+            LoadedLockFile::Full(lock_file) => lock_file
+                .environment(environment)
+                .map(|env| {
+                    env.channels()
+                        .iter()
+                        .map(|channel| channel.url.clone())
+                        .collect()
+                })
+                .unwrap_or_default(),
+            LoadedLockFile::BestEffort(lock_file) => lock_file.channels(environment),
+        };
+        urls.into_iter()
+            .map(|url| pixi_outdated::conda::lockfile_channel(&url, channel_alias))
+            .collect()
+    }
+
+    /// The packages locked for an environment on a platform, none of them
+    /// marked explicit yet
+    fn packages(
+        &self,
+        environment: &str,
+        platform: rattler_conda_types::Platform,
+    ) -> Vec<pixi_outdated::pixi::PixiPackage> {
+        match self {
+            LoadedLockFile::Full(lock_file) => lock_file
+                .environment(environment)
+                .and_then(|env| {
+                    env.packages(platform)
+                        .map(|packages| packages.map(locked_package).collect())
+                })
+                .unwrap_or_default(),
+            LoadedLockFile::BestEffort(lock_file) => {
+                lock_file.packages(environment, platform.as_str())
+            }
+        }
+    }
+}
+
+/// The environments in the workspace's lockfile, the default one first, or
+/// just the default environment if there is no lockfile yet
+fn lockfile_environments(manifest: Option<PathBuf>, config: ConfigCli) -> Result<Vec<String>> {
+    let workspace = locate_workspace(manifest, config, None)?;
+    let default = workspace.default_environment().name().as_str().to_string();
+    let lock_file_path = workspace.lock_file_path();
+    if !lock_file_path.is_file() {
+        return Ok(vec![default]);
+    }
+
+    let lock_file = pixi_outdated::parser::parse_lockfile(&lock_file_path)?;
+    let mut environments: Vec<String> = lock_file.environments.into_keys().collect();
+    environments.sort_by_key(|name| (*name != default, name.clone()));
+    Ok(environments)
+}
+
+/// The packages the lockfile locks at different versions across
+/// `environments`, among those the run checks
+fn version_spreads(
+    cli: &Cli,
+    environments: &[String],
+) -> Result<Vec<pixi_outdated::spread::VersionSpread>> {
+    let workspace = locate_workspace(cli.manifest.clone(), cli.config.clone(), None)?;
+    let lock_file_path = workspace.lock_file_path();
+    if !lock_file_path.is_file() {
+        return Ok(Vec::new());
+    }
+
+    let lock_file = pixi_outdated::parser::parse_lockfile(&lock_file_path)?;
+    let selection = pixi_outdated::select::PackageSelection::new(&cli.packages, &cli.regex)?;
+    let exclude = pixi_outdated::ignore::ExcludePatterns::new(&cli.exclude)?;
+    Ok(pixi_outdated::spread::version_spreads(
+        &lock_file,
+        environments,
+        cli.platform.as_deref(),
+        |name| selection.matches(name) && !exclude.excludes(name),
+    ))
+}
+
+/// The dependencies whose requirement or locked version differs from the
+/// manifest and lockfile at a git revision
+fn changed_dependencies(
+    revision: &str,
+    workspace: &pixi_core::Workspace,
+    manifest_path: Option<&std::path::Path>,
+    lock_file: &LoadedLockFile,
+    environment: &str,
+    platforms: &[String],
+    features: &pixi_outdated::parser::EnvironmentFeatures,
+    dependency_specs: &pixi_outdated::parser::DependencySpecs,
+) -> Result<pixi_outdated::changes::ChangedDependencies> {
+    use pixi_outdated::changes::{file_at_revision, ChangedDependencies, LockedPackages};
+
+    let root = workspace.root();
+    let lock_file_path = workspace.lock_file_path();
+
+    let old_specs = match manifest_path {
+        Some(path) => match file_at_revision(root, revision, path)? {
+            Some(content) => {
+                let is_pyproject = path
+                    .file_name()
+                    .is_some_and(|name| name == "pyproject.toml");
+                pixi_outdated::parser::DependencySpecs::parse(&content, is_pyproject, features)
+                    .with_context(|| {
+                        format!("Failed to parse {} at {}", path.display(), revision)
+                    })?
+            }
+            None => Default::default(),
+        },
+        None => Default::default(),
+    };
+    let old_lock_file = file_at_revision(root, revision, &lock_file_path)?
+        .map(|content| pixi_outdated::parser::PixiLock::parse(&content))
+        .transpose()
+        .with_context(|| {
+            format!(
+                "Failed to parse {} at {}",
+                lock_file_path.display(),
+                revision
+            )
+        })?;
+
+    let mut old_locked = LockedPackages::new();
+    let mut new_locked = LockedPackages::new();
+    for platform in platforms {
+        let Ok(parsed) = platform.parse() else {
+            continue;
+        };
+        new_locked.insert(platform.clone(), lock_file.packages(environment, parsed));
+        if let Some(ref old_lock_file) = old_lock_file {
+            old_locked.insert(
+                platform.clone(),
+                old_lock_file.packages(environment, platform),
+            );
+        }
+    }
+
+    Ok(ChangedDependencies::between(
+        &old_specs,
+        dependency_specs,
+        &old_locked,
+        &new_locked,
+    ))
+}
+
+/// Convert a package from a lockfile loaded through pixi
+fn locked_package(
+    locked_pkg: rattler_lock::LockedPackageRef<'_>,
+) -> pixi_outdated::pixi::PixiPackage {
+    match locked_pkg {
+        rattler_lock::LockedPackageRef::Conda(conda_pkg) => {
+            let record = conda_pkg.record();
+            pixi_outdated::pixi::PixiPackage {
+                name: record.name.as_normalized().to_string(),
+                version: record.version.to_string(),
+                build: Some(record.build.clone()),
+                size_bytes: record.size,
+                kind: pixi_outdated::pixi::PackageKind::Conda,
+                source: Some(conda_pkg.location().to_string()),
+                is_explicit: false,
+                depends: record
+                    .depends
+                    .iter()
+                    .filter_map(|spec| pixi_outdated::conda::dependency_name(spec))
+                    .collect(),
+            }
+        }
+        rattler_lock::LockedPackageRef::Pypi(pypi_pkg, _) => pixi_outdated::pixi::PixiPackage {
+            name: pypi_pkg.name.to_string(),
+            version: pypi_pkg.version.to_string(),
+            build: None,
+            size_bytes: None,
+            kind: pixi_outdated::pixi::PackageKind::Pypi,
+            source: None,
+            is_explicit: false,
+            depends: pypi_pkg
+                .requires_dist
+                .iter()
+                .filter_map(|requirement| {
+                    pixi_outdated::pypi::dependency_name(&requirement.to_string())
+                })
+                .collect(),
+        },
+    }
+}
+
+/// Explain what a run against a lockfile in a newer format can't do
+fn warn_newer_lock_file(path: &std::path::Path, lock_file: &pixi_outdated::parser::PixiLock) {
+    eprintln!(
+        "Warning: {} uses lockfile format version {}, newer than version {} supported by this \
+         version of pixi-outdated. Reading it on a best-effort basis:",
+        path.display(),
+        lock_file.version,
+        pixi_outdated::parser::SUPPORTED_LOCK_VERSION
+    );
+    eprintln!("  - the lockfile is not checked against the manifest");
+    eprintln!("  - `pixi-outdated apply` can't be used with this lockfile");
+    let unreadable = lock_file.unreadable_packages();
+    if unreadable > 0 {
+        eprintln!(
+            "  - {} locked package(s) of an unknown kind are not checked",
+            unreadable
+        );
+    }
+    eprintln!("Upgrade pixi-outdated for full support.");
+}
+
+/// Print locked versions that are no longer available, merging identical
+/// issues across platforms
+fn print_locked_issues(issues: &[LockedIssue]) {
+    let mut grouped: std::collections::BTreeMap<(String, &str, String), Vec<&str>> =
+        std::collections::BTreeMap::new();
+    for issue in issues {
+        grouped
+            .entry((
+                pixi_outdated::report::annotated(&issue.name, &issue.annotations),
+                issue.version.as_str(),
+                issue.status.to_string(),
+            ))
+            .or_default()
+            .push(issue.platform.as_str());
+    }
+
+    println!("\n=== Yanked or removed upstream ===");
+    for ((name, version, status), platforms) in grouped {
+        println!(
+            "{}: {} {} ({})",
+            name,
+            version,
+            status,
+            platforms.join(", ")
+        );
+    }
+}
+
+/// Write the report to any additional output files requested on the command line
+fn write_output_files(
+    files: &OutputFiles,
+    root: &std::path::Path,
+    report: &Report,
+    plan: &UpdatePlan,
+    pins: &pixi_outdated::pins::Pins,
+    locale: &pixi_outdated::locale::Locale,
+) -> Result<()> {
+    if let Some(ref path) = files.json_out {
+        pixi_outdated::report::write_report(path, &pixi_outdated::report::render_json(report)?)?;
+    }
+
+    if let Some(ref path) = files.markdown_out {
+        pixi_outdated::report::write_report(
+            path,
+            &pixi_outdated::report::render_markdown(report, locale),
+        )?;
+    }
+
+    if let Some(ref path) = files.report_html {
+        pixi_outdated::report::write_report(path, &pixi_outdated::html::render_html(report))?;
+    }
+
+    if let Some(ref path) = files.junit_out {
+        pixi_outdated::report::write_report(path, &pixi_outdated::junit::render_junit(report))?;
+    }
+
+    if let Some(ref path) = files.plan_out {
+        pixi_outdated::report::write_report(path, &plan.to_json()?)?;
+    }
+
+    if let Some(ref path) = files.pins_out {
+        pixi_outdated::report::write_report(path, &pins.to_json()?)?;
+    }
+
+    if let Some(ref path) = files.hash_file {
+        if let Some(ref hash) = report.content_hash {
+            pixi_outdated::report::write_report(path, hash)?;
+        }
+    }
+
+    if let Some(ref path) = files.audit_log {
+        pixi_outdated::audit_log::AuditRecord::new(
+            report,
+            &plan.environment,
+            pixi_outdated::audit_log::git_sha(root),
+            chrono::Utc::now(),
+        )
+        .append_to(path)?;
+    }
+
+    Ok(())
+}
+
+/// Run the pre hooks before `environments` of a workspace are checked,
+/// naming them in `PIXI_OUTDATED_ENVIRONMENT` separated by spaces
+fn run_pre_hooks(
+    hooks: &pixi_outdated::hooks::Hooks,
+    root: &std::path::Path,
+    manifest_path: Option<&std::path::Path>,
+    environments: &[String],
+) -> Result<()> {
+    if hooks.pre.is_empty() {
+        return Ok(());
+    }
+
+    let mut env = vec![("PIXI_OUTDATED_ENVIRONMENT", environments.join(" "))];
+    if let Some(path) = manifest_path {
+        env.push(("PIXI_OUTDATED_MANIFEST", path.display().to_string()));
+    }
+    pixi_outdated::hooks::run(&hooks.pre, root, &env)
+}
+
+/// Run the post hooks with the report and a summary of the run in their
+/// environment. Without `--json-out` the report goes to a temporary file that
+/// is removed once the hooks finish.
+fn run_post_hooks(
+    hooks: &pixi_outdated::hooks::Hooks,
+    files: &OutputFiles,
+    root: &std::path::Path,
+    report: &Report,
+    environment: &str,
+) -> Result<()> {
+    if hooks.post.is_empty() {
+        return Ok(());
+    }
+
+    // The temporary report is removed when dropped, once the hooks finish
+    let (report_path, _temporary) = match files.json_out {
+        Some(ref path) => (path.clone(), None),
+        None => {
+            let file = tempfile::Builder::new()
+                .prefix("pixi-outdated-report-")
+                .suffix(".json")
+                .tempfile()
+                .context("Failed to create a temporary report file")?;
+            pixi_outdated::report::write_report(
+                file.path(),
+                &pixi_outdated::report::render_json(report)?,
+            )?;
+            let path = file.into_temp_path();
+            (path.to_path_buf(), Some(path))
+        }
+    };
+
+    let record =
+        pixi_outdated::audit_log::AuditRecord::new(report, environment, None, chrono::Utc::now());
+    pixi_outdated::hooks::run(
+        &hooks.post,
+        root,
+        &pixi_outdated::hooks::report_env(&report_path, &record),
+    )
+}
+
+/// The URL and payload format of `--notify-webhook`, if given, with the
+/// proxy to post through
+fn webhook(
+    cli: &Cli,
+) -> Option<(
+    String,
+    pixi_outdated::webhook::WebhookFormat,
+    Option<url::Url>,
+)> {
+    let url = cli.notify_webhook.clone()?;
+    let format = cli
+        .notify_format
+        .unwrap_or_else(|| pixi_outdated::webhook::WebhookFormat::for_url(&url));
+    Some((url, format, cli.proxy.clone()))
+}
+
+/// Post the findings of the run to `--notify-webhook`, given the reports by
+/// environment or project name. A failed notification is reported but
+/// doesn't fail the run, whose exit code stays about the packages.
+async fn notify_webhook<'a>(
+    (url, format, proxy): &(
+        String,
+        pixi_outdated::webhook::WebhookFormat,
+        Option<url::Url>,
+    ),
+    reports: impl IntoIterator<Item = (&'a str, &'a Report)>,
+) {
+    let summary = pixi_outdated::webhook::WebhookSummary::new(reports);
+    let result = match pixi_outdated::http::client(proxy.as_ref()) {
+        Ok(client) => pixi_outdated::webhook::notify(&client, url, *format, &summary).await,
+        Err(e) => Err(e),
+    };
+    if let Err(e) = result {
+        eprintln!("Warning: {:#}", e);
+    }
+}
+
+/// Send a summary of the run to the configured stats endpoint. Collecting
+/// statistics never fails the run.
+async fn send_stats(
+    client: &reqwest::Client,
+    config: &pixi_outdated::stats::StatsConfig,
+    report: &Report,
+    environment: &str,
+) {
+    let record =
+        pixi_outdated::audit_log::AuditRecord::new(report, environment, None, chrono::Utc::now());
+    let stats = pixi_outdated::stats::RunStats::new(config, record);
+    if let Err(e) = pixi_outdated::stats::send(client, config, &stats).await {
+        eprintln!("Warning: {:#}", e);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -544,6 +3962,7 @@ mod tests {
             name: "python".to_string(),
             installed_version: "3.12.0".to_string(),
             latest_version: "3.13.0".to_string(),
+            ..Default::default()
         };
 
         let json = serde_json::to_string(&update).unwrap();
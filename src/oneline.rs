@@ -0,0 +1,291 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::IsTerminal;
+
+use crate::report::{Report, OUTDATED_EXIT_CODE};
+use crate::severity::{self, Severity};
+
+/// Counts of distinct updates by severity, across platforms and environments
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OnelineSummary {
+    pub updates: BTreeMap<Severity, usize>,
+    /// Locked versions that were yanked or removed upstream
+    pub locked_issues: usize,
+    /// Locked versions with known vulnerabilities, with `--audit`
+    pub vulnerable: usize,
+}
+
+impl OnelineSummary {
+    /// Count the updates of each report once, however many platforms or
+    /// environments they show up on
+    pub fn from_reports<'a>(reports: impl IntoIterator<Item = &'a Report>) -> Self {
+        let mut updates = BTreeSet::new();
+        let mut locked_issues = BTreeSet::new();
+        let mut vulnerable = BTreeSet::new();
+        for report in reports {
+            for update in report.platforms.values().flatten() {
+                let severity = update.severity.unwrap_or_else(|| {
+                    severity::classify(&update.installed_version, &update.latest_version)
+                });
+                updates.insert((
+                    &update.name,
+                    &update.installed_version,
+                    &update.latest_version,
+                    severity,
+                ));
+            }
+            for issue in &report.locked_issues {
+                locked_issues.insert((&issue.name, &issue.version));
+            }
+            for package in &report.vulnerabilities {
+                vulnerable.insert((&package.name, &package.version));
+            }
+        }
+
+        let mut summary = OnelineSummary {
+            locked_issues: locked_issues.len(),
+            vulnerable: vulnerable.len(),
+            ..Default::default()
+        };
+        for (_, _, _, severity) in updates {
+            *summary.updates.entry(severity).or_default() += 1;
+        }
+        summary
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.updates.is_empty() && self.locked_issues == 0 && self.vulnerable == 0
+    }
+
+    pub fn exit_code(&self) -> u8 {
+        if self.is_empty() {
+            0
+        } else {
+            OUTDATED_EXIT_CODE
+        }
+    }
+}
+
+impl OnelineSummary {
+    /// The summary with each count colored by how urgent it is, as
+    /// [`use_color`] decides, e.g. major updates and vulnerabilities in red
+    pub fn render(&self, color: bool) -> String {
+        let paint = |text: String, code: &str| {
+            if color {
+                format!("\x1b[{}m{}\x1b[0m", code, text)
+            } else {
+                text
+            }
+        };
+
+        let mut line = if self.updates.is_empty() {
+            paint("up to date".to_string(), GREEN)
+        } else {
+            let counts: Vec<String> = self
+                .updates
+                .iter()
+                .rev()
+                .map(|(severity, count)| {
+                    paint(format!("{} {}", count, severity), severity_color(*severity))
+                })
+                .collect();
+            format!("outdated: {}", counts.join(", "))
+        };
+        let mut issues = Vec::new();
+        if self.vulnerable > 0 {
+            issues.push(paint(format!("{} vulnerable", self.vulnerable), RED));
+        }
+        if self.locked_issues > 0 {
+            issues.push(paint(
+                format!("{} yanked or removed", self.locked_issues),
+                YELLOW,
+            ));
+        }
+        if !issues.is_empty() {
+            line.push_str(&format!(" ({})", issues.join(", ")));
+        }
+        line
+    }
+}
+
+impl std::fmt::Display for OnelineSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.render(false))
+    }
+}
+
+const RED: &str = "31";
+const YELLOW: &str = "33";
+const GREEN: &str = "32";
+const CYAN: &str = "36";
+
+fn severity_color(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Major => RED,
+        Severity::Minor => YELLOW,
+        Severity::Patch => GREEN,
+        Severity::Other => CYAN,
+    }
+}
+
+/// Whether to color the summary: only when it's printed to a terminal and
+/// `NO_COLOR` isn't set, so prompts and status bars that capture the output
+/// get plain text
+pub fn use_color() -> bool {
+    std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none_or(|v| v.is_empty())
+}
+
+/// Render the report as a single line, e.g.
+/// `outdated: 3 major, 7 minor (2 vulnerable)`, colored if `color` is set
+pub fn render_oneline(report: &Report, color: bool) -> String {
+    OnelineSummary::from_reports([report]).render(color)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::{LockedIssue, LockedStatus, PackageUpdate, PlatformUpdates};
+
+    fn update(name: &str, installed: &str, latest: &str) -> PackageUpdate {
+        PackageUpdate {
+            name: name.to_string(),
+            installed_version: installed.to_string(),
+            latest_version: latest.to_string(),
+            severity: Some(severity::classify(installed, latest)),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_render_oneline() {
+        let mut platforms = PlatformUpdates::new();
+        platforms.insert(
+            "linux-64".to_string(),
+            vec![
+                update("numpy", "1.26.0", "2.1.0"),
+                update("python", "3.12.11", "3.14.0"),
+                update("zlib", "1.3.0", "1.3.1"),
+            ],
+        );
+        platforms.insert(
+            "osx-arm64".to_string(),
+            vec![
+                update("numpy", "1.26.0", "2.1.0"),
+                update("libcxx", "18.1.0", "18.1.8"),
+            ],
+        );
+        let report = Report {
+            platforms,
+            ..Report::default()
+        };
+
+        assert_eq!(
+            render_oneline(&report, false),
+            "outdated: 1 major, 1 minor, 2 patch"
+        );
+        assert_eq!(
+            OnelineSummary::from_reports([&report]).exit_code(),
+            OUTDATED_EXIT_CODE
+        );
+    }
+
+    #[test]
+    fn test_render_oneline_colored() {
+        let mut platforms = PlatformUpdates::new();
+        platforms.insert(
+            "linux-64".to_string(),
+            vec![
+                update("numpy", "1.26.0", "2.1.0"),
+                update("zlib", "1.3.0", "1.3.1"),
+            ],
+        );
+        let report = Report {
+            platforms,
+            ..Report::default()
+        };
+        assert_eq!(
+            render_oneline(&report, true),
+            "outdated: \x1b[31m1 major\x1b[0m, \x1b[32m1 patch\x1b[0m"
+        );
+        assert_eq!(
+            render_oneline(&Report::default(), true),
+            "\x1b[32mup to date\x1b[0m"
+        );
+    }
+
+    #[test]
+    fn test_render_oneline_up_to_date() {
+        let report = Report::default();
+        assert_eq!(render_oneline(&report, false), "up to date");
+        assert_eq!(OnelineSummary::from_reports([&report]).exit_code(), 0);
+    }
+
+    #[test]
+    fn test_render_oneline_locked_issues() {
+        let issue = |platform: &str| LockedIssue {
+            name: "requests".to_string(),
+            version: "2.32.0".to_string(),
+            platform: platform.to_string(),
+            status: LockedStatus::Yanked { reason: None },
+            annotations: Default::default(),
+        };
+        let report = Report {
+            locked_issues: vec![issue("linux-64"), issue("osx-arm64")],
+            ..Report::default()
+        };
+        assert_eq!(
+            render_oneline(&report, false),
+            "up to date (1 yanked or removed)"
+        );
+        assert_eq!(
+            OnelineSummary::from_reports([&report]).exit_code(),
+            OUTDATED_EXIT_CODE
+        );
+    }
+
+    #[test]
+    fn test_render_oneline_vulnerable() {
+        let mut platforms = PlatformUpdates::new();
+        platforms.insert(
+            "linux-64".to_string(),
+            vec![update("requests", "2.28.1", "2.32.3")],
+        );
+        let report = Report {
+            platforms,
+            vulnerabilities: vec![crate::audit::VulnerablePackage {
+                name: "requests".to_string(),
+                kind: crate::pixi::PackageKind::Pypi,
+                version: "2.28.1".to_string(),
+                platforms: vec!["linux-64".to_string()],
+                latest_version: Some("2.32.3".to_string()),
+                matched_as: None,
+                vulnerabilities: vec![],
+            }],
+            ..Report::default()
+        };
+        assert_eq!(
+            render_oneline(&report, false),
+            "outdated: 1 minor (1 vulnerable)"
+        );
+    }
+
+    #[test]
+    fn test_summary_across_environments() {
+        let mut platforms = PlatformUpdates::new();
+        platforms.insert(
+            "linux-64".to_string(),
+            vec![update("numpy", "1.26.0", "2.1.0")],
+        );
+        let default = Report {
+            platforms: platforms.clone(),
+            ..Report::default()
+        };
+        let test = Report {
+            platforms,
+            ..Report::default()
+        };
+        assert_eq!(
+            OnelineSummary::from_reports([&default, &test]).to_string(),
+            "outdated: 1 major"
+        );
+    }
+}
@@ -0,0 +1,404 @@
+use std::collections::BTreeMap;
+
+use crate::locale::Locale;
+use crate::report::PlatformUpdates;
+
+const HEADERS: [&str; 7] = [
+    "Package",
+    "Kind",
+    "Channel",
+    "Installed",
+    "Latest",
+    "Compatible",
+    "Platforms",
+];
+
+/// One row of the update table: an update merged across the platforms it applies to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableRow {
+    pub name: String,
+    pub kind: String,
+    pub channel: String,
+    pub installed: String,
+    pub latest: String,
+    /// The newest version the manifest requirement allows, or `-`
+    pub compatible: String,
+    pub platforms: Vec<String>,
+    /// Days since the installed version was released, when known
+    pub installed_age_days: Option<u32>,
+    /// Days since the first release newer than the installed one, when known
+    pub newer_available_days: Option<u32>,
+    /// What the columns don't show, such as activation scripts or why the
+    /// update can't be solved, listed as footnotes below the table
+    pub notes: Vec<String>,
+}
+
+impl TableRow {
+    /// The cells of the row, with an age cell after the installed version
+    /// and one after the latest version when the table shows them
+    fn cells(
+        &self,
+        locale: &Locale,
+        with_age: bool,
+        with_newer: bool,
+        footnote: Option<usize>,
+    ) -> Vec<String> {
+        let name = match footnote {
+            Some(number) => format!("{} [{}]", self.name, number),
+            None => self.name.clone(),
+        };
+        let mut cells = vec![
+            name,
+            self.kind.clone(),
+            self.channel.clone(),
+            self.installed.clone(),
+        ];
+        if with_age {
+            cells.push(
+                self.installed_age_days
+                    .map_or_else(|| "-".to_string(), |days| locale.format_age(days)),
+            );
+        }
+        cells.push(self.latest.clone());
+        if with_newer {
+            cells.push(
+                self.newer_available_days
+                    .map_or_else(|| "-".to_string(), |days| locale.format_age(days)),
+            );
+        }
+        cells.extend([self.compatible.clone(), self.platforms.join(", ")]);
+        cells
+    }
+}
+
+/// The short name of a channel, e.g. `conda-forge` for `https://conda.anaconda.org/conda-forge/`,
+/// keeping a label: `conda-forge/label/rc`
+pub fn channel_label(channel: &str) -> &str {
+    let channel = channel.trim_end_matches('/');
+    let name = crate::conda::without_label(channel);
+    let start = name.rfind('/').map_or(0, |slash| slash + 1);
+    &channel[start..]
+}
+
+/// Merge identical updates across platforms into table rows, sorted by package name
+pub fn table_rows(updates: &PlatformUpdates, locale: &Locale) -> Vec<TableRow> {
+    type Key = (String, String, String, String, String, String);
+    type Merged = (Vec<String>, Option<u32>, Option<u32>, Vec<String>);
+    let mut rows: BTreeMap<Key, Merged> = BTreeMap::new();
+
+    for (platform, platform_updates) in updates {
+        for update in platform_updates {
+            let kind = update
+                .kind
+                .map_or_else(|| "-".to_string(), |k| k.to_string());
+            let channel = update
+                .channel
+                .as_deref()
+                .map_or("-", channel_label)
+                .to_string();
+            let update_notes = update.notes(locale);
+            rows.entry((
+                update.label(),
+                kind,
+                channel,
+                update.installed_version.clone(),
+                update.latest_version.clone(),
+                update
+                    .latest_compatible
+                    .clone()
+                    .unwrap_or_else(|| "-".to_string()),
+            ))
+            .and_modify(|(platforms, age, newer, notes)| {
+                platforms.push(platform.clone());
+                // Release dates can differ between platforms; show the oldest
+                *age = (*age).max(update.installed_age_days);
+                *newer = (*newer).max(update.newer_available_days);
+                for note in &update_notes {
+                    if !notes.contains(note) {
+                        notes.push(note.clone());
+                    }
+                }
+            })
+            .or_insert_with(|| {
+                (
+                    vec![platform.clone()],
+                    update.installed_age_days,
+                    update.newer_available_days,
+                    update_notes.clone(),
+                )
+            });
+        }
+    }
+
+    rows.into_iter()
+        .map(
+            |(
+                (name, kind, channel, installed, latest, compatible),
+                (platforms, age, newer, notes),
+            )| TableRow {
+                name,
+                kind,
+                channel,
+                installed,
+                latest,
+                compatible,
+                platforms,
+                installed_age_days: age,
+                newer_available_days: newer,
+                notes,
+            },
+        )
+        .collect()
+}
+
+/// Render rows as an aligned table with a header, one row per line. An age
+/// column is added when the age of any installed version is known, and a
+/// `Newer since` column when the release date of any newer version is.
+/// Rows with notes get a `[n]` marker after the package name, and the notes
+/// follow the table as numbered footnotes.
+pub fn render_table(rows: &[TableRow], locale: &Locale) -> String {
+    let with_age = rows.iter().any(|row| row.installed_age_days.is_some());
+    let with_newer = rows.iter().any(|row| row.newer_available_days.is_some());
+    let mut footnotes = Vec::new();
+    let cells: Vec<Vec<String>> = rows
+        .iter()
+        .map(|row| {
+            let footnote = (!row.notes.is_empty()).then(|| {
+                footnotes.push(row);
+                footnotes.len()
+            });
+            row.cells(locale, with_age, with_newer, footnote)
+        })
+        .collect();
+
+    let mut headers = HEADERS.to_vec();
+    if with_age {
+        headers.insert(4, "Age");
+    }
+    if with_newer {
+        let latest = headers
+            .iter()
+            .position(|header| *header == "Latest")
+            .unwrap_or(4);
+        headers.insert(latest + 1, "Newer since");
+    }
+
+    let mut widths: Vec<usize> = headers.iter().map(|header| header.len()).collect();
+    for row in &cells {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let format_line = |values: &[&str]| -> String {
+        let line: Vec<String> = values
+            .iter()
+            .zip(&widths)
+            .map(|(value, width)| format!("{:width$}", value, width = *width))
+            .collect();
+        format!("{}\n", line.join("  ").trim_end())
+    };
+
+    let mut out = format_line(&headers);
+    let separators: Vec<String> = widths.iter().map(|w| "-".repeat(*w)).collect();
+    out.push_str(&format_line(
+        &separators.iter().map(String::as_str).collect::<Vec<_>>(),
+    ));
+    for row in &cells {
+        out.push_str(&format_line(
+            &row.iter().map(String::as_str).collect::<Vec<_>>(),
+        ));
+    }
+
+    if !footnotes.is_empty() {
+        out.push('\n');
+        for (index, row) in footnotes.iter().enumerate() {
+            out.push_str(&format!(
+                "[{}] {} ({}): {}\n",
+                index + 1,
+                row.name,
+                row.platforms.join(", "),
+                row.notes.join("; ")
+            ));
+        }
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pixi::PackageKind;
+    use crate::report::PackageUpdate;
+
+    fn update(name: &str, kind: PackageKind, installed: &str, latest: &str) -> PackageUpdate {
+        PackageUpdate {
+            name: name.to_string(),
+            kind: Some(kind),
+            channel: (kind == PackageKind::Conda)
+                .then(|| "https://conda.anaconda.org/conda-forge".to_string()),
+            installed_version: installed.to_string(),
+            latest_version: latest.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_channel_label() {
+        assert_eq!(
+            channel_label("https://conda.anaconda.org/conda-forge/"),
+            "conda-forge"
+        );
+        assert_eq!(channel_label("pytorch"), "pytorch");
+        assert_eq!(
+            channel_label("https://conda.anaconda.org/conda-forge/label/rc"),
+            "conda-forge/label/rc"
+        );
+    }
+
+    #[test]
+    fn test_table_rows_merge_platforms() {
+        let mut updates = PlatformUpdates::new();
+        updates.insert(
+            "linux-64".to_string(),
+            vec![
+                update("python", PackageKind::Conda, "3.12.11", "3.14.0"),
+                update("cowsay", PackageKind::Pypi, "6.0", "6.1"),
+            ],
+        );
+        updates.insert(
+            "osx-arm64".to_string(),
+            vec![update("python", PackageKind::Conda, "3.12.11", "3.14.0")],
+        );
+
+        let rows = table_rows(&updates, &Locale::default());
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].name, "cowsay");
+        assert_eq!(rows[0].channel, "-");
+        assert_eq!(rows[1].name, "python");
+        assert_eq!(rows[1].channel, "conda-forge");
+        assert_eq!(rows[1].compatible, "-");
+        assert_eq!(rows[1].platforms, vec!["linux-64", "osx-arm64"]);
+        assert!(rows[1].notes.is_empty());
+    }
+
+    #[test]
+    fn test_table_rows_notes() {
+        let mut updates = PlatformUpdates::new();
+        let mut python = update("python", PackageKind::Conda, "3.12.11", "3.14.0");
+        python.activation_scripts = vec!["etc/conda/activate.d/python.sh".to_string()];
+        python.risk_reasons = vec!["major version bump".to_string()];
+        updates.insert("linux-64".to_string(), vec![python.clone()]);
+        python.unsolvable = Some("nothing provides libfoo".to_string());
+        updates.insert("osx-arm64".to_string(), vec![python]);
+
+        let rows = table_rows(&updates, &Locale::default());
+        assert_eq!(rows.len(), 1);
+        assert_eq!(
+            rows[0].notes,
+            vec![
+                "activation: etc/conda/activate.d/python.sh",
+                "risk: major version bump",
+                "unsolvable: nothing provides libfoo",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_render_table() {
+        let rows = vec![TableRow {
+            name: "python".to_string(),
+            kind: "conda".to_string(),
+            channel: "conda-forge".to_string(),
+            installed: "3.12.11".to_string(),
+            latest: "3.14.0".to_string(),
+            compatible: "3.12.12".to_string(),
+            platforms: vec!["linux-64".to_string()],
+            installed_age_days: None,
+            newer_available_days: None,
+            notes: Vec::new(),
+        }];
+
+        assert_eq!(
+            render_table(&rows, &Locale::default()),
+            "Package  Kind   Channel      Installed  Latest  Compatible  Platforms\n\
+             -------  -----  -----------  ---------  ------  ----------  ---------\n\
+             python   conda  conda-forge  3.12.11    3.14.0  3.12.12     linux-64\n"
+        );
+    }
+
+    #[test]
+    fn test_render_table_with_age() {
+        let rows = vec![TableRow {
+            name: "python".to_string(),
+            kind: "conda".to_string(),
+            channel: "conda-forge".to_string(),
+            installed: "3.12.11".to_string(),
+            latest: "3.14.0".to_string(),
+            compatible: "-".to_string(),
+            platforms: vec!["linux-64".to_string()],
+            installed_age_days: Some(120),
+            newer_available_days: None,
+            notes: Vec::new(),
+        }];
+
+        let table = render_table(&rows, &Locale::default());
+        assert!(table.starts_with("Package  Kind   Channel      Installed  Age           Latest"));
+        assert!(table.contains("3.12.11    4 months ago  3.14.0"));
+    }
+
+    #[test]
+    fn test_render_table_with_newer_since() {
+        let rows = vec![TableRow {
+            name: "python".to_string(),
+            kind: "conda".to_string(),
+            channel: "conda-forge".to_string(),
+            installed: "3.12.11".to_string(),
+            latest: "3.14.0".to_string(),
+            compatible: "-".to_string(),
+            platforms: vec!["linux-64".to_string()],
+            installed_age_days: Some(120),
+            newer_available_days: Some(21),
+            notes: Vec::new(),
+        }];
+
+        let table = render_table(&rows, &Locale::default());
+        assert!(table.starts_with(
+            "Package  Kind   Channel      Installed  Age           Latest  Newer since  Compatible"
+        ));
+        assert!(table.contains("4 months ago  3.14.0  3 weeks ago  -"));
+    }
+
+    #[test]
+    fn test_render_table_footnotes() {
+        let row = |name: &str, notes: Vec<String>| TableRow {
+            name: name.to_string(),
+            kind: "conda".to_string(),
+            channel: "conda-forge".to_string(),
+            installed: "1.0".to_string(),
+            latest: "2.0".to_string(),
+            compatible: "-".to_string(),
+            platforms: vec!["linux-64".to_string()],
+            installed_age_days: None,
+            newer_available_days: None,
+            notes,
+        };
+        let rows = vec![
+            row("libffi", Vec::new()),
+            row(
+                "python",
+                vec![
+                    "risk: major version bump".to_string(),
+                    "auto-mergeable".to_string(),
+                ],
+            ),
+        ];
+
+        let table = render_table(&rows, &Locale::default());
+        assert!(table.contains("\nlibffi      conda"));
+        assert!(table.contains("\npython [1]  conda"));
+        assert!(table
+            .ends_with("\n\n[1] python (linux-64): risk: major version bump; auto-mergeable\n"));
+    }
+}
@@ -1,8 +1,11 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use serde::Deserialize;
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+
+use crate::pixi::{PackageKind, PixiPackage};
+use crate::priority::ChannelPriority;
 
 #[derive(Debug, Deserialize)]
 pub struct PixiManifest {
@@ -21,6 +24,10 @@ pub struct ProjectMetadata {
     pub platforms: Vec<String>,
 }
 
+/// The newest lockfile format version this crate fully understands: the
+/// newest one the rattler_lock it's built with can load
+pub const SUPPORTED_LOCK_VERSION: u32 = rattler_lock::FileFormatVersion::LATEST as u32;
+
 #[derive(Debug, Deserialize)]
 pub struct PixiLock {
     pub version: u32,
@@ -30,9 +37,11 @@ pub struct PixiLock {
 
 #[derive(Debug, Deserialize)]
 pub struct Environment {
+    #[serde(default)]
     pub channels: Vec<Channel>,
     #[serde(default)]
     pub indexes: Vec<String>,
+    #[serde(default)]
     pub packages: HashMap<String, Vec<PackageRef>>,
 }
 
@@ -50,18 +59,270 @@ pub struct PackageRef {
 #[derive(Debug, Deserialize)]
 #[serde(untagged)]
 pub enum PackageSource {
-    Conda { conda: String },
-    PyPI { pypi: String },
+    Conda {
+        conda: String,
+    },
+    PyPI {
+        pypi: String,
+    },
+    /// A kind of package introduced by a newer lockfile format
+    Unknown(serde_yaml::Value),
 }
 
 #[derive(Debug, Deserialize)]
 pub struct LockedPackage {
     #[serde(flatten)]
     pub source: PackageSource,
-    #[serde(default)]
+    #[serde(default, deserialize_with = "scalar_string")]
     pub name: Option<String>,
-    #[serde(default)]
+    #[serde(default, deserialize_with = "scalar_string")]
     pub version: Option<String>,
+    #[serde(default, deserialize_with = "scalar_string")]
+    pub build: Option<String>,
+    #[serde(default)]
+    pub size: Option<u64>,
+    #[serde(default)]
+    pub depends: Vec<String>,
+    /// Requirements on packages that are only enforced if they're installed
+    #[serde(default)]
+    pub constrains: Vec<String>,
+    #[serde(default)]
+    pub requires_dist: Vec<String>,
+}
+
+/// A requirement a locked package places on another package
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LockedRequirement {
+    /// The locked package with the requirement, e.g. `python`
+    pub package: String,
+    pub version: String,
+    /// The requirement as locked, e.g. `python_abi 3.12.* *_cp312`
+    pub requirement: String,
+    /// Its version constraint, e.g. `3.12.*`; empty if any version will do
+    pub constraint: String,
+}
+
+/// Read a YAML scalar as a string, since versions like `5.0` may be unquoted
+fn scalar_string<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<String>, D::Error> {
+    Ok(
+        match Option::<serde_yaml::Value>::deserialize(deserializer)? {
+            Some(serde_yaml::Value::String(value)) => Some(value),
+            Some(serde_yaml::Value::Number(value)) => Some(value.to_string()),
+            _ => None,
+        },
+    )
+}
+
+impl PixiLock {
+    pub fn parse(content: &str) -> Result<Self> {
+        Ok(serde_yaml::from_str(content)?)
+    }
+
+    /// Platforms locked for an environment
+    pub fn platforms(&self, environment: &str) -> Vec<String> {
+        let mut platforms: Vec<String> = self
+            .environments
+            .get(environment)
+            .map(|env| env.packages.keys().cloned().collect())
+            .unwrap_or_default();
+        platforms.sort();
+        platforms
+    }
+
+    /// The channels of an environment, in priority order
+    pub fn channels(&self, environment: &str) -> Vec<String> {
+        self.environments
+            .get(environment)
+            .map(|env| {
+                env.channels
+                    .iter()
+                    .map(|channel| channel.url.clone())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// The packages locked for an environment on a platform. Entries this
+    /// crate doesn't understand are left out; see [`PixiLock::unreadable_packages`].
+    pub fn packages(&self, environment: &str, platform: &str) -> Vec<PixiPackage> {
+        let Some(refs) = self
+            .environments
+            .get(environment)
+            .and_then(|env| env.packages.get(platform))
+        else {
+            return Vec::new();
+        };
+
+        refs.iter()
+            .filter_map(|package_ref| {
+                self.packages
+                    .iter()
+                    .find(|package| package.source.url() == package_ref.source.url())
+                    .and_then(LockedPackage::to_pixi_package)
+            })
+            .collect()
+    }
+
+    /// The requirements the packages locked for an environment on a platform
+    /// place on a package, including conda `constrains`
+    pub fn requirements_on(
+        &self,
+        environment: &str,
+        platform: &str,
+        name: &str,
+        kind: PackageKind,
+    ) -> Vec<LockedRequirement> {
+        let Some(refs) = self
+            .environments
+            .get(environment)
+            .and_then(|env| env.packages.get(platform))
+        else {
+            return Vec::new();
+        };
+
+        let mut requirements = Vec::new();
+        for package_ref in refs {
+            let Some(locked) = self
+                .packages
+                .iter()
+                .find(|package| package.source.url() == package_ref.source.url())
+            else {
+                continue;
+            };
+            let Some(package) = locked.to_pixi_package() else {
+                continue;
+            };
+            let found: Vec<(&String, String)> = match (package.kind, kind) {
+                (PackageKind::Conda, PackageKind::Conda) => locked
+                    .depends
+                    .iter()
+                    .chain(&locked.constrains)
+                    .filter(|spec| crate::conda::dependency_name(spec).as_deref() == Some(name))
+                    .map(|spec| {
+                        // The version is the first field after the name,
+                        // a build string may follow
+                        let constraint = spec.trim()[name.len()..]
+                            .split_whitespace()
+                            .next()
+                            .unwrap_or_default();
+                        (spec, constraint.to_string())
+                    })
+                    .collect(),
+                (PackageKind::Pypi, PackageKind::Pypi) => {
+                    let name = name.to_ascii_lowercase().replace(['_', '.'], "-");
+                    locked
+                        .requires_dist
+                        .iter()
+                        .filter(|requirement| {
+                            crate::pypi::dependency_name(requirement).as_ref() == Some(&name)
+                        })
+                        .map(|requirement| {
+                            (requirement, split_requirement(requirement).1.to_string())
+                        })
+                        .collect()
+                }
+                _ => Vec::new(),
+            };
+            requirements.extend(found.into_iter().map(|(requirement, constraint)| {
+                LockedRequirement {
+                    package: package.name.clone(),
+                    version: package.version.clone(),
+                    requirement: requirement.clone(),
+                    constraint,
+                }
+            }));
+        }
+        requirements
+    }
+
+    /// Number of locked packages that couldn't be read, e.g. package kinds
+    /// added in a newer lockfile format
+    pub fn unreadable_packages(&self) -> usize {
+        self.packages
+            .iter()
+            .filter(|package| package.to_pixi_package().is_none())
+            .count()
+    }
+}
+
+impl PackageSource {
+    fn url(&self) -> Option<&str> {
+        match self {
+            PackageSource::Conda { conda } => Some(conda),
+            PackageSource::PyPI { pypi } => Some(pypi),
+            PackageSource::Unknown(_) => None,
+        }
+    }
+}
+
+impl LockedPackage {
+    /// The package as `pixi list` would describe it, if enough is known
+    /// about it. Conda packages may omit the name, version and build that
+    /// their file name already contains.
+    fn to_pixi_package(&self) -> Option<PixiPackage> {
+        match self.source {
+            PackageSource::Conda { ref conda } => {
+                let (name, version, build) = conda_file_name_parts(conda)?;
+                Some(PixiPackage {
+                    name: self.name.clone().unwrap_or(name),
+                    version: self.version.clone().unwrap_or(version),
+                    build: Some(self.build.clone().unwrap_or(build)),
+                    size_bytes: self.size,
+                    kind: PackageKind::Conda,
+                    source: Some(conda.clone()),
+                    is_explicit: false,
+                    depends: self
+                        .depends
+                        .iter()
+                        .filter_map(|spec| crate::conda::dependency_name(spec))
+                        .collect(),
+                })
+            }
+            PackageSource::PyPI { .. } => Some(PixiPackage {
+                name: self.name.clone()?,
+                version: self.version.clone()?,
+                build: None,
+                size_bytes: None,
+                kind: PackageKind::Pypi,
+                source: None,
+                is_explicit: false,
+                depends: self
+                    .requires_dist
+                    .iter()
+                    .filter_map(|requirement| crate::pypi::dependency_name(requirement))
+                    .collect(),
+            }),
+            PackageSource::Unknown(_) => None,
+        }
+    }
+}
+
+/// Name, version and build of a conda package from its URL, e.g.
+/// `.../linux-64/python-3.12.11-h9e4cc4f_0_cpython.conda`
+fn conda_file_name_parts(url: &str) -> Option<(String, String, String)> {
+    let file_name = url.rsplit('/').next()?;
+    let stem = file_name
+        .strip_suffix(".conda")
+        .or_else(|| file_name.strip_suffix(".tar.bz2"))?;
+    let mut parts = stem.rsplitn(3, '-');
+    let build = parts.next()?;
+    let version = parts.next()?;
+    let name = parts.next()?;
+    Some((name.to_string(), version.to_string(), build.to_string()))
+}
+
+/// The format version of a lockfile, read without parsing the rest of it
+pub fn lockfile_version(content: &str) -> Result<u32> {
+    #[derive(Deserialize)]
+    struct Header {
+        version: u32,
+    }
+
+    let header: Header =
+        serde_yaml::from_str(content).context("The lockfile has no valid `version` field")?;
+    Ok(header.version)
 }
 
 pub fn parse_manifest(path: &Path) -> Result<PixiManifest> {
@@ -72,6 +333,904 @@ pub fn parse_manifest(path: &Path) -> Result<PixiManifest> {
 
 pub fn parse_lockfile(path: &Path) -> Result<PixiLock> {
     let content = fs::read_to_string(path)?;
-    let lockfile: PixiLock = serde_yaml::from_str(&content)?;
-    Ok(lockfile)
+    PixiLock::parse(&content)
+}
+
+/// Dependency tables that may pin a conda dependency to a channel
+const DEPENDENCY_TABLES: [&str; 3] = ["dependencies", "host-dependencies", "build-dependencies"];
+
+/// The features of the manifest whose tables apply to the checked
+/// environment, as pixi resolves them for it
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub enum EnvironmentFeatures {
+    /// Every feature, for callers without a resolved environment
+    #[default]
+    All,
+    /// The named features, including `default` unless the environment
+    /// sets `no-default-feature`
+    Only(HashSet<String>),
+}
+
+impl EnvironmentFeatures {
+    pub fn only<S: Into<String>>(features: impl IntoIterator<Item = S>) -> Self {
+        Self::Only(features.into_iter().map(Into::into).collect())
+    }
+
+    /// Whether the tables of `feature` apply to the environment
+    pub fn includes(&self, feature: &str) -> bool {
+        match self {
+            Self::All => true,
+            Self::Only(features) => features.contains(feature),
+        }
+    }
+}
+
+/// Find the manifest in a workspace root, preferring `pixi.toml` over `pyproject.toml`
+pub fn find_manifest(root: &Path) -> Option<PathBuf> {
+    ["pixi.toml", "pyproject.toml"]
+        .iter()
+        .map(|name| root.join(name))
+        .find(|path| path.is_file())
+}
+
+/// Channels pinned on individual conda dependencies, e.g.
+/// `pytorch = { version = "*", channel = "pytorch" }`
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ChannelOverrides {
+    all_platforms: HashMap<String, String>,
+    /// Overrides from `[target.<platform>.dependencies]`, keyed by `(platform, name)`
+    per_platform: HashMap<(String, String), String>,
+}
+
+impl ChannelOverrides {
+    /// Read the overrides that `features` declare in a `pixi.toml` or
+    /// `pyproject.toml`
+    pub fn load(path: &Path, features: &EnvironmentFeatures) -> Result<Self> {
+        let content = fs::read_to_string(path)?;
+        let is_pyproject = path
+            .file_name()
+            .is_some_and(|name| name == "pyproject.toml");
+        Self::parse(&content, is_pyproject, features)
+    }
+
+    pub fn parse(
+        content: &str,
+        is_pyproject: bool,
+        features: &EnvironmentFeatures,
+    ) -> Result<Self> {
+        let document: toml::Table = toml::from_str(content)?;
+        let Some(root) = pixi_root(&document, is_pyproject) else {
+            return Ok(Self::default());
+        };
+
+        let mut overrides = Self::default();
+        if features.includes(DEFAULT_FEATURE) {
+            overrides.collect(root);
+        }
+
+        // Features contribute their dependencies to the environments that use them
+        if let Some(tables) = root.get("feature").and_then(toml::Value::as_table) {
+            for (name, feature) in tables {
+                if let Some(feature) = feature.as_table().filter(|_| features.includes(name)) {
+                    overrides.collect(feature);
+                }
+            }
+        }
+
+        Ok(overrides)
+    }
+
+    /// Collect overrides from the dependency tables of the manifest root or a feature
+    fn collect(&mut self, table: &toml::Table) {
+        for (name, channel) in pinned_channels(table) {
+            self.all_platforms.insert(name, channel);
+        }
+
+        if let Some(targets) = table.get("target").and_then(toml::Value::as_table) {
+            for (platform, target) in targets {
+                let Some(target) = target.as_table() else {
+                    continue;
+                };
+                for (name, channel) in pinned_channels(target) {
+                    self.per_platform.insert((platform.clone(), name), channel);
+                }
+            }
+        }
+    }
+
+    /// The channel pinned for a package on a platform, if any
+    pub fn get(&self, name: &str, platform: &str) -> Option<&str> {
+        self.per_platform
+            .get(&(platform.to_string(), name.to_string()))
+            .or_else(|| self.all_platforms.get(name))
+            .map(String::as_str)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.all_platforms.is_empty() && self.per_platform.is_empty()
+    }
+}
+
+/// The channels a manifest lists for an environment: those of the
+/// workspace and of the environment's features
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ManifestChannels {
+    /// Full base URLs, like the channels extracted from locked URLs
+    urls: Vec<String>,
+    /// The effective `channel-priority` of the environment
+    pub priority: ChannelPriority,
+}
+
+impl ManifestChannels {
+    /// The channels pixi resolved for an environment, as names or URLs.
+    /// Names resolve against `channel_alias`.
+    pub fn new<S: AsRef<str>>(
+        channels: &[S],
+        channel_alias: &str,
+        priority: ChannelPriority,
+    ) -> Self {
+        let urls = channels
+            .iter()
+            .map(|channel| {
+                let url = crate::policy::channel_url(channel.as_ref(), channel_alias);
+                crate::conda::extract_channel_url(&url).unwrap_or(url)
+            })
+            .collect();
+        Self { urls, priority }
+    }
+
+    /// Read the channels of the workspace and of `features` from a
+    /// `pixi.toml` or `pyproject.toml`
+    pub fn load(path: &Path, features: &EnvironmentFeatures) -> Result<Self> {
+        let content = fs::read_to_string(path)?;
+        let is_pyproject = path
+            .file_name()
+            .is_some_and(|name| name == "pyproject.toml");
+        Self::parse(&content, is_pyproject, features)
+    }
+
+    pub fn parse(
+        content: &str,
+        is_pyproject: bool,
+        features: &EnvironmentFeatures,
+    ) -> Result<Self> {
+        let document: toml::Table = toml::from_str(content)?;
+        let Some(root) = pixi_root(&document, is_pyproject) else {
+            return Ok(Self::default());
+        };
+
+        let mut tables: Vec<&toml::Table> = ["workspace", "project"]
+            .iter()
+            .filter_map(|key| root.get(*key).and_then(toml::Value::as_table))
+            .collect();
+        let workspace_tables = tables.len();
+        if let Some(feature_tables) = root.get("feature").and_then(toml::Value::as_table) {
+            tables.extend(
+                feature_tables
+                    .iter()
+                    .filter(|(name, _)| features.includes(name))
+                    .filter_map(|(_, feature)| feature.as_table()),
+            );
+        }
+        // A feature's `channel-priority` takes precedence over the workspace's
+        let priority = tables[workspace_tables..]
+            .iter()
+            .chain(&tables[..workspace_tables])
+            .find_map(|table| table.get("channel-priority").and_then(toml::Value::as_str))
+            .and_then(ChannelPriority::from_manifest)
+            .unwrap_or_default();
+
+        // Channels are names, URLs or tables such as
+        // `{ channel = "pytorch", priority = 1 }`
+        let channels: Vec<&str> = tables
+            .into_iter()
+            .filter_map(|table| table.get("channels").and_then(toml::Value::as_array))
+            .flatten()
+            .filter_map(|channel| match channel {
+                toml::Value::String(channel) => Some(channel.as_str()),
+                toml::Value::Table(table) => table.get("channel").and_then(toml::Value::as_str),
+                _ => None,
+            })
+            .collect();
+
+        Ok(Self::new(
+            &channels,
+            crate::policy::DEFAULT_CHANNEL_ALIAS,
+            priority,
+        ))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.urls.is_empty()
+    }
+
+    /// The listed channels, normalized like the channels of a lockfile
+    pub fn urls(&self) -> &[String] {
+        &self.urls
+    }
+
+    /// Whether `channel`, as extracted from a locked URL, is still listed
+    pub fn lists(&self, channel: &str) -> bool {
+        let channel = channel.trim_end_matches('/');
+        self.urls.iter().any(|url| url == channel)
+    }
+}
+
+/// The `[tool.pixi]` table of a `pyproject.toml`, or the root of a `pixi.toml`
+fn pixi_root(document: &toml::Table, is_pyproject: bool) -> Option<&toml::Table> {
+    if is_pyproject {
+        document
+            .get("tool")
+            .and_then(|tool| tool.get("pixi"))
+            .and_then(toml::Value::as_table)
+    } else {
+        Some(document)
+    }
+}
+
+/// The feature the dependency tables at the root of a manifest belong to
+const DEFAULT_FEATURE: &str = "default";
+
+/// Version requirements of the dependencies declared in the manifest.
+///
+/// A package can be required by several features or targets; all of their
+/// requirements are kept, since an environment combining them has to satisfy
+/// each one.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct DependencySpecs {
+    all_platforms: HashMap<(String, PackageKind), Vec<String>>,
+    /// Requirements from `[target.<platform>.*]` tables, keyed by `(platform, name, kind)`
+    per_platform: HashMap<(String, String, PackageKind), Vec<String>>,
+    /// Every declared dependency, with or without a requirement
+    direct: HashSet<(String, PackageKind)>,
+    /// The features declaring each dependency, `default` for the manifest's
+    /// own dependency tables
+    features: HashMap<(String, PackageKind), BTreeSet<String>>,
+    /// `requires-python` of a `pyproject.toml`
+    pub requires_python: Option<String>,
+}
+
+impl DependencySpecs {
+    /// Read the requirements that `features` declare in a `pixi.toml` or
+    /// `pyproject.toml`
+    pub fn load(path: &Path, features: &EnvironmentFeatures) -> Result<Self> {
+        let content = fs::read_to_string(path)?;
+        let is_pyproject = path
+            .file_name()
+            .is_some_and(|name| name == "pyproject.toml");
+        Self::parse(&content, is_pyproject, features)
+    }
+
+    pub fn parse(
+        content: &str,
+        is_pyproject: bool,
+        features: &EnvironmentFeatures,
+    ) -> Result<Self> {
+        let document: toml::Table = toml::from_str(content)?;
+        let mut specs = Self::default();
+
+        if is_pyproject {
+            let project = document.get("project").and_then(toml::Value::as_table);
+            specs.requires_python = project
+                .and_then(|project| project.get("requires-python"))
+                .and_then(toml::Value::as_str)
+                .map(str::to_string);
+
+            // pixi installs the project's dependencies from PyPI, as part of
+            // the default feature
+            let requirements = project
+                .filter(|_| features.includes(DEFAULT_FEATURE))
+                .and_then(|project| project.get("dependencies"))
+                .and_then(toml::Value::as_array);
+            for requirement in requirements
+                .into_iter()
+                .flatten()
+                .filter_map(toml::Value::as_str)
+            {
+                let (name, spec) = split_requirement(requirement);
+                specs.declare(DEFAULT_FEATURE, name, PackageKind::Pypi);
+                specs.insert(None, name, PackageKind::Pypi, spec);
+            }
+        }
+
+        let Some(root) = pixi_root(&document, is_pyproject) else {
+            return Ok(specs);
+        };
+        if features.includes(DEFAULT_FEATURE) {
+            specs.collect(DEFAULT_FEATURE, root);
+        }
+
+        if let Some(tables) = root.get("feature").and_then(toml::Value::as_table) {
+            for (name, feature) in tables {
+                if let Some(feature) = feature.as_table().filter(|_| features.includes(name)) {
+                    specs.collect(name, feature);
+                }
+            }
+        }
+
+        Ok(specs)
+    }
+
+    fn declare(&mut self, feature: &str, name: &str, kind: PackageKind) {
+        self.direct.insert((name.to_string(), kind));
+        self.features
+            .entry((name.to_string(), kind))
+            .or_default()
+            .insert(feature.to_string());
+    }
+
+    fn insert(&mut self, platform: Option<&str>, name: &str, kind: PackageKind, spec: &str) {
+        let spec = spec.trim();
+        if spec.is_empty() || spec == "*" {
+            return;
+        }
+        let specs = match platform {
+            Some(platform) => self
+                .per_platform
+                .entry((platform.to_string(), name.to_string(), kind))
+                .or_default(),
+            None => self
+                .all_platforms
+                .entry((name.to_string(), kind))
+                .or_default(),
+        };
+        if !specs.iter().any(|existing| existing == spec) {
+            specs.push(spec.to_string());
+        }
+    }
+
+    /// Collect requirements from the dependency tables of the manifest root or a feature
+    fn collect(&mut self, feature: &str, table: &toml::Table) {
+        self.collect_tables(feature, None, table);
+
+        if let Some(targets) = table.get("target").and_then(toml::Value::as_table) {
+            for (platform, target) in targets {
+                if let Some(target) = target.as_table() {
+                    self.collect_tables(feature, Some(platform.as_str()), target);
+                }
+            }
+        }
+    }
+
+    fn collect_tables(&mut self, feature: &str, platform: Option<&str>, table: &toml::Table) {
+        let tables = DEPENDENCY_TABLES
+            .iter()
+            .map(|key| (*key, PackageKind::Conda))
+            .chain([("pypi-dependencies", PackageKind::Pypi)]);
+
+        for (key, kind) in tables {
+            let Some(dependencies) = table.get(key).and_then(toml::Value::as_table) else {
+                continue;
+            };
+            for (name, value) in dependencies {
+                self.declare(feature, name, kind);
+                let spec = match value {
+                    toml::Value::String(spec) => Some(spec.as_str()),
+                    toml::Value::Table(table) => table.get("version").and_then(toml::Value::as_str),
+                    _ => None,
+                };
+                if let Some(spec) = spec {
+                    self.insert(platform, name, kind, spec);
+                }
+            }
+        }
+    }
+
+    /// Whether the manifest declares the package, as opposed to it being
+    /// pulled in by another package
+    pub fn is_direct(&self, name: &str, kind: PackageKind) -> bool {
+        self.direct.contains(&(name.to_string(), kind))
+    }
+
+    /// The features declaring a package, sorted. Empty if only the manifest's
+    /// own dependency tables do, i.e. the package isn't tied to a feature.
+    pub fn features(&self, name: &str, kind: PackageKind) -> Vec<&str> {
+        match self.features.get(&(name.to_string(), kind)) {
+            Some(features) if features.iter().any(|f| f != DEFAULT_FEATURE) => {
+                features.iter().map(String::as_str).collect()
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// Dependencies that were added, removed or whose requirements differ
+    /// from `old`, on any platform
+    pub fn changed_from(&self, old: &Self) -> HashSet<(String, PackageKind)> {
+        self.direct
+            .symmetric_difference(&old.direct)
+            .chain(
+                self.direct
+                    .intersection(&old.direct)
+                    .filter(|(name, kind)| {
+                        self.requirements(name, *kind) != old.requirements(name, *kind)
+                    }),
+            )
+            .cloned()
+            .collect()
+    }
+
+    /// Every requirement on a package with the platform it is limited to, sorted
+    fn requirements(&self, name: &str, kind: PackageKind) -> Vec<(Option<&str>, &str)> {
+        let all_platforms = self
+            .all_platforms
+            .get(&(name.to_string(), kind))
+            .into_iter()
+            .flatten()
+            .map(|spec| (None, spec.as_str()));
+        let per_platform = self
+            .per_platform
+            .iter()
+            .filter(|((_, n, k), _)| n == name && *k == kind)
+            .flat_map(|((platform, _, _), specs)| {
+                specs
+                    .iter()
+                    .map(move |spec| (Some(platform.as_str()), spec.as_str()))
+            });
+
+        let mut requirements: Vec<_> = all_platforms.chain(per_platform).collect();
+        requirements.sort();
+        requirements
+    }
+
+    /// Every requirement on a package for a platform, target-specific ones first
+    pub fn get(&self, name: &str, kind: PackageKind, platform: &str) -> Vec<&str> {
+        self.per_platform
+            .get(&(platform.to_string(), name.to_string(), kind))
+            .into_iter()
+            .chain(self.all_platforms.get(&(name.to_string(), kind)))
+            .flatten()
+            .map(String::as_str)
+            .collect()
+    }
+}
+
+/// Split a PEP 508 requirement such as `requests[socks]>=2.31; python_version > "3.8"`
+/// into its name and version specifier
+fn split_requirement(requirement: &str) -> (&str, &str) {
+    let requirement = requirement.split(';').next().unwrap_or(requirement).trim();
+    let name_end = requirement
+        .find(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')))
+        .unwrap_or(requirement.len());
+    let (name, rest) = requirement.split_at(name_end);
+    // Skip extras
+    let spec = match rest.trim_start().strip_prefix('[') {
+        Some(extras) => extras.split_once(']').map_or("", |(_, spec)| spec),
+        None => rest,
+    };
+    (
+        name,
+        spec.trim().trim_start_matches('(').trim_end_matches(')'),
+    )
+}
+
+/// `(name, channel)` of every dependency that pins a channel in the given table
+fn pinned_channels(table: &toml::Table) -> Vec<(String, String)> {
+    DEPENDENCY_TABLES
+        .iter()
+        .filter_map(|key| table.get(*key).and_then(toml::Value::as_table))
+        .flatten()
+        .filter_map(|(name, spec)| {
+            let channel = spec.get("channel")?.as_str()?;
+            Some((name.clone(), channel.to_string()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_manifest_channels() {
+        let manifest = r#"
+            [workspace]
+            channels = ["conda-forge/label/rc", "https://prefix.dev/internal/"]
+
+            [feature.gpu]
+            channels = [{ channel = "pytorch", priority = 1 }]
+        "#;
+        let channels = ManifestChannels::parse(manifest, false, &EnvironmentFeatures::All).unwrap();
+        assert!(channels.lists("https://conda.anaconda.org/conda-forge/label/rc"));
+        assert!(!channels.lists("https://conda.anaconda.org/conda-forge"));
+        assert!(channels.lists("https://conda.anaconda.org/pytorch/"));
+        assert!(channels.lists("https://prefix.dev/internal"));
+        assert!(!channels.lists("https://conda.anaconda.org/bioconda"));
+        assert_eq!(
+            channels.urls(),
+            [
+                "https://conda.anaconda.org/conda-forge/label/rc",
+                "https://prefix.dev/internal",
+                "https://conda.anaconda.org/pytorch",
+            ]
+        );
+        assert_eq!(channels.priority, ChannelPriority::Strict);
+
+        let pyproject = r#"
+            [tool.pixi.project]
+            channels = ["bioconda"]
+            channel-priority = "disabled"
+        "#;
+        let channels = ManifestChannels::parse(pyproject, true, &EnvironmentFeatures::All).unwrap();
+        assert!(channels.lists("https://conda.anaconda.org/bioconda"));
+        assert_eq!(channels.priority, ChannelPriority::Disabled);
+        assert!(!channels.lists("https://conda.anaconda.org/conda-forge"));
+
+        // The channels of features the environment doesn't use aren't listed
+        let features = EnvironmentFeatures::only(["default"]);
+        let channels = ManifestChannels::parse(manifest, false, &features).unwrap();
+        assert!(!channels.lists("https://conda.anaconda.org/pytorch"));
+        assert!(channels.lists("https://prefix.dev/internal"));
+
+        // Features can set the priority of the environments using them
+        let manifest = r#"
+            [workspace]
+            channels = ["conda-forge"]
+
+            [feature.nightly]
+            channels = ["pytorch-nightly"]
+            channel-priority = "disabled"
+        "#;
+        let features = EnvironmentFeatures::only(["default", "nightly"]);
+        let channels = ManifestChannels::parse(manifest, false, &features).unwrap();
+        assert_eq!(channels.priority, ChannelPriority::Disabled);
+        let features = EnvironmentFeatures::only(["default"]);
+        let channels = ManifestChannels::parse(manifest, false, &features).unwrap();
+        assert_eq!(channels.priority, ChannelPriority::Strict);
+
+        assert!(
+            ManifestChannels::parse("", false, &EnvironmentFeatures::All)
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_manifest_channels_new() {
+        let channels = ManifestChannels::new(
+            &["conda-forge", "https://repo.prefix.dev/org/internal/"],
+            "https://conda.example.com",
+            ChannelPriority::Disabled,
+        );
+        assert_eq!(
+            channels.urls(),
+            [
+                "https://conda.example.com/conda-forge",
+                "https://repo.prefix.dev/org/internal",
+            ]
+        );
+        assert_eq!(channels.priority, ChannelPriority::Disabled);
+    }
+
+    #[test]
+    fn test_channel_overrides() {
+        let manifest = r#"
+            [workspace]
+            channels = ["conda-forge"]
+
+            [dependencies]
+            python = ">=3.12"
+            pytorch = { version = "*", channel = "pytorch" }
+
+            [target.linux-64.dependencies]
+            cuda-toolkit = { version = "12.*", channel = "nvidia" }
+            pytorch = { version = "*", channel = "https://example.com/pytorch-linux" }
+
+            [feature.test.dependencies]
+            pytest = { version = "*", channel = "my-mirror" }
+        "#;
+
+        let overrides =
+            ChannelOverrides::parse(manifest, false, &EnvironmentFeatures::All).unwrap();
+        assert_eq!(overrides.get("python", "linux-64"), None);
+        assert_eq!(overrides.get("pytorch", "osx-arm64"), Some("pytorch"));
+        assert_eq!(
+            overrides.get("pytorch", "linux-64"),
+            Some("https://example.com/pytorch-linux")
+        );
+        assert_eq!(overrides.get("cuda-toolkit", "linux-64"), Some("nvidia"));
+        assert_eq!(overrides.get("cuda-toolkit", "osx-arm64"), None);
+        assert_eq!(overrides.get("pytest", "linux-64"), Some("my-mirror"));
+
+        // A pin in a feature the environment doesn't use doesn't apply to it
+        let features = EnvironmentFeatures::only(["default"]);
+        let overrides = ChannelOverrides::parse(manifest, false, &features).unwrap();
+        assert_eq!(overrides.get("pytest", "linux-64"), None);
+        assert_eq!(overrides.get("pytorch", "osx-arm64"), Some("pytorch"));
+    }
+
+    #[test]
+    fn test_channel_overrides_pyproject() {
+        let manifest = r#"
+            [project]
+            name = "example"
+
+            [tool.pixi.dependencies]
+            pytorch = { version = "*", channel = "pytorch" }
+        "#;
+
+        let overrides = ChannelOverrides::parse(manifest, true, &EnvironmentFeatures::All).unwrap();
+        assert_eq!(overrides.get("pytorch", "linux-64"), Some("pytorch"));
+
+        // Without a [tool.pixi] table there is nothing to override
+        let overrides =
+            ChannelOverrides::parse("[project]\nname = \"x\"\n", true, &EnvironmentFeatures::All)
+                .unwrap();
+        assert!(overrides.is_empty());
+    }
+
+    #[test]
+    fn test_dependency_specs() {
+        let manifest = r#"
+            [dependencies]
+            python = ">=3.12.0,<3.13"
+            rust = "*"
+            pytorch = { version = ">=2.0", channel = "pytorch" }
+
+            [target.osx-arm64.dependencies]
+            icu = "==73.2"
+
+            [feature.py313.dependencies]
+            python = "3.13.*"
+
+            [pypi-dependencies]
+            cowsay = ">=5.0, <6"
+        "#;
+
+        let specs = DependencySpecs::parse(manifest, false, &EnvironmentFeatures::All).unwrap();
+        assert_eq!(
+            specs.get("python", PackageKind::Conda, "linux-64"),
+            vec![">=3.12.0,<3.13", "3.13.*"]
+        );
+        assert_eq!(
+            specs.get("pytorch", PackageKind::Conda, "linux-64"),
+            vec![">=2.0"]
+        );
+        assert_eq!(
+            specs.get("icu", PackageKind::Conda, "osx-arm64"),
+            vec!["==73.2"]
+        );
+        assert!(specs.get("icu", PackageKind::Conda, "linux-64").is_empty());
+        assert_eq!(
+            specs.get("cowsay", PackageKind::Pypi, "linux-64"),
+            vec![">=5.0, <6"]
+        );
+        // `*` doesn't constrain anything
+        assert!(specs.get("rust", PackageKind::Conda, "linux-64").is_empty());
+        assert!(specs.is_direct("rust", PackageKind::Conda));
+        assert!(specs.is_direct("icu", PackageKind::Conda));
+        assert!(specs.is_direct("cowsay", PackageKind::Pypi));
+        assert!(!specs.is_direct("cowsay", PackageKind::Conda));
+        assert!(!specs.is_direct("libsqlite", PackageKind::Conda));
+        assert_eq!(specs.requires_python, None);
+    }
+
+    #[test]
+    fn test_dependency_features() {
+        let manifest = r#"
+            [dependencies]
+            python = ">=3.12"
+            numpy = "*"
+
+            [feature.test.dependencies]
+            pytest = "*"
+            numpy = ">=2"
+
+            [feature.docs.target.linux-64.pypi-dependencies]
+            mkdocs = "*"
+
+            [feature.lint.dependencies]
+            pytest = "*"
+        "#;
+
+        let specs = DependencySpecs::parse(manifest, false, &EnvironmentFeatures::All).unwrap();
+        assert!(specs.features("python", PackageKind::Conda).is_empty());
+        assert_eq!(
+            specs.features("numpy", PackageKind::Conda),
+            ["default", "test"]
+        );
+        assert_eq!(
+            specs.features("pytest", PackageKind::Conda),
+            ["lint", "test"]
+        );
+        assert_eq!(specs.features("mkdocs", PackageKind::Pypi), ["docs"]);
+        assert!(specs.features("mkdocs", PackageKind::Conda).is_empty());
+    }
+
+    #[test]
+    fn test_dependency_specs_environment() {
+        let manifest = r#"
+            [dependencies]
+            python = ">=3.12"
+
+            [feature.py313.dependencies]
+            python = "3.13.*"
+
+            [feature.test.dependencies]
+            pytest = ">=8"
+        "#;
+
+        // An environment made of the default and `test` features doesn't see
+        // the requirements of `py313`
+        let features = EnvironmentFeatures::only(["default", "test"]);
+        let specs = DependencySpecs::parse(manifest, false, &features).unwrap();
+        assert_eq!(
+            specs.get("python", PackageKind::Conda, "linux-64"),
+            vec![">=3.12"]
+        );
+        assert!(specs.is_direct("pytest", PackageKind::Conda));
+        assert_eq!(specs.features("pytest", PackageKind::Conda), ["test"]);
+
+        // With `no-default-feature` the manifest's own tables don't apply
+        let features = EnvironmentFeatures::only(["py313"]);
+        let specs = DependencySpecs::parse(manifest, false, &features).unwrap();
+        assert_eq!(
+            specs.get("python", PackageKind::Conda, "linux-64"),
+            vec!["3.13.*"]
+        );
+        assert!(!specs.is_direct("pytest", PackageKind::Conda));
+    }
+
+    #[test]
+    fn test_dependency_specs_pyproject() {
+        let manifest = r#"
+            [project]
+            name = "example"
+            requires-python = ">=3.10"
+            dependencies = ["requests[socks]>=2.31; python_version > '3.8'", "rich"]
+
+            [tool.pixi.dependencies]
+            python = ">=3.10"
+        "#;
+
+        let specs = DependencySpecs::parse(manifest, true, &EnvironmentFeatures::All).unwrap();
+        assert_eq!(specs.requires_python.as_deref(), Some(">=3.10"));
+        assert_eq!(
+            specs.get("requests", PackageKind::Pypi, "linux-64"),
+            vec![">=2.31"]
+        );
+        assert!(specs.get("rich", PackageKind::Pypi, "linux-64").is_empty());
+        assert!(specs.is_direct("rich", PackageKind::Pypi));
+        assert_eq!(
+            specs.get("python", PackageKind::Conda, "linux-64"),
+            vec![">=3.10"]
+        );
+    }
+
+    #[test]
+    fn test_dependency_specs_changed_from() {
+        let old = DependencySpecs::parse(
+            r#"
+            [dependencies]
+            python = ">=3.12.0,<3.13"
+            rust = "*"
+            openssl = "3.*"
+
+            [target.osx-arm64.dependencies]
+            icu = "==73.2"
+        "#,
+            false,
+            &EnvironmentFeatures::All,
+        )
+        .unwrap();
+        let new = DependencySpecs::parse(
+            r#"
+            [dependencies]
+            python = ">=3.12.0,<3.13"
+            rust = "1.*"
+
+            [target.osx-arm64.dependencies]
+            icu = "==75.1"
+
+            [pypi-dependencies]
+            cowsay = ">=5.0, <6"
+        "#,
+            false,
+            &EnvironmentFeatures::All,
+        )
+        .unwrap();
+
+        let changed = new.changed_from(&old);
+        let mut changed: Vec<&str> = changed.iter().map(|(name, _)| name.as_str()).collect();
+        changed.sort();
+        assert_eq!(changed, ["cowsay", "icu", "openssl", "rust"]);
+        assert!(new.changed_from(&new).is_empty());
+    }
+
+    #[test]
+    fn test_lockfile_version() {
+        assert_eq!(
+            lockfile_version("version: 7\nenvironments: {}\n").unwrap(),
+            7
+        );
+        assert!(lockfile_version("environments: {}\n").is_err());
+    }
+
+    #[test]
+    fn test_parse_lockfile_packages() {
+        let lockfile =
+            parse_lockfile(&Path::new(env!("CARGO_MANIFEST_DIR")).join("examples/pixi.lock"))
+                .unwrap();
+        assert_eq!(lockfile.platforms("default"), vec!["linux-64", "osx-arm64"]);
+        assert_eq!(
+            lockfile.channels("default"),
+            vec!["https://conda.anaconda.org/conda-forge/"]
+        );
+        assert_eq!(lockfile.unreadable_packages(), 0);
+
+        let packages = lockfile.packages("default", "linux-64");
+        let python = packages.iter().find(|p| p.name == "python").unwrap();
+        assert_eq!(python.version, "3.12.11");
+        assert_eq!(python.build.as_deref(), Some("h9e4cc4f_0_cpython"));
+        assert_eq!(python.kind, PackageKind::Conda);
+        assert!(python.depends.contains(&"libzlib".to_string()));
+
+        let cowsay = packages.iter().find(|p| p.name == "cowsay").unwrap();
+        assert_eq!(cowsay.version, "5.0");
+        assert_eq!(cowsay.kind, PackageKind::Pypi);
+    }
+
+    #[test]
+    fn test_requirements_on() {
+        let lockfile =
+            parse_lockfile(&Path::new(env!("CARGO_MANIFEST_DIR")).join("examples/pixi.lock"))
+                .unwrap();
+        let requirements =
+            lockfile.requirements_on("default", "linux-64", "python_abi", PackageKind::Conda);
+        assert_eq!(
+            requirements,
+            [LockedRequirement {
+                package: "python".to_string(),
+                version: "3.12.11".to_string(),
+                requirement: "python_abi 3.12.* *_cp312".to_string(),
+                constraint: "3.12.*".to_string(),
+            }]
+        );
+        assert!(lockfile
+            .requirements_on("default", "linux-64", "libzlib", PackageKind::Pypi)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_parse_lockfile_with_unknown_package_kinds() {
+        let content = r#"
+version: 7
+environments:
+  default:
+    packages:
+      linux-64:
+      - conda: https://conda.anaconda.org/conda-forge/noarch/tzdata-2025b-h78e105d_0.conda
+      - git: https://github.com/example/tool
+packages:
+- conda: https://conda.anaconda.org/conda-forge/noarch/tzdata-2025b-h78e105d_0.conda
+  size: 123
+- git: https://github.com/example/tool
+  name: tool
+"#;
+        let lockfile: PixiLock = serde_yaml::from_str(content).unwrap();
+        assert_eq!(lockfile.unreadable_packages(), 1);
+
+        let packages = lockfile.packages("default", "linux-64");
+        assert_eq!(packages.len(), 1);
+        assert_eq!(packages[0].name, "tzdata");
+        assert_eq!(packages[0].version, "2025b");
+        assert_eq!(packages[0].size_bytes, Some(123));
+    }
+
+    #[test]
+    fn test_find_manifest() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(find_manifest(dir.path()), None);
+
+        fs::write(dir.path().join("pyproject.toml"), "").unwrap();
+        assert_eq!(
+            find_manifest(dir.path()),
+            Some(dir.path().join("pyproject.toml"))
+        );
+
+        fs::write(dir.path().join("pixi.toml"), "").unwrap();
+        assert_eq!(
+            find_manifest(dir.path()),
+            Some(dir.path().join("pixi.toml"))
+        );
+    }
 }
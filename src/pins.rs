@@ -0,0 +1,155 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::pixi::PackageKind;
+use crate::report::PlatformUpdates;
+use crate::upstream::{PackageKey, UpstreamInfo};
+
+/// Bumped whenever the pins format changes incompatibly
+pub const PINS_FORMAT_VERSION: u32 = 1;
+
+/// A file the latest version of an outdated package would download, in a
+/// form fetchers such as Nix's `fetchurl` accept
+#[derive(Debug, Serialize, Clone, Deserialize, PartialEq, Eq)]
+pub struct Pin {
+    pub name: String,
+    pub kind: PackageKind,
+    pub platform: String,
+    pub version: String,
+    pub file_name: String,
+    pub url: String,
+    /// Hex-encoded; missing if the channel or index doesn't publish one
+    pub sha256: Option<String>,
+}
+
+/// The artifacts of every available update, written with `--pins-out` so
+/// they can be mirrored before the update lands
+#[derive(Debug, Serialize, Clone, Deserialize, PartialEq, Eq)]
+pub struct Pins {
+    pub format_version: u32,
+    pub pins: Vec<Pin>,
+}
+
+impl Default for Pins {
+    fn default() -> Self {
+        Self {
+            format_version: PINS_FORMAT_VERSION,
+            pins: Vec::new(),
+        }
+    }
+}
+
+impl Pins {
+    /// Collect the files of the latest version of each update, using `lookup`
+    /// to find what the sources answered for a package
+    pub fn from_updates<'a>(
+        updates: &PlatformUpdates,
+        lookup: impl Fn(&PackageKey) -> Option<&'a UpstreamInfo>,
+    ) -> Self {
+        let mut pins = Vec::new();
+        for (platform, platform_updates) in updates {
+            for update in platform_updates {
+                let Some(kind) = update.kind else {
+                    continue;
+                };
+                let key = PackageKey {
+                    name: update.name.clone(),
+                    channel: update.channel.clone(),
+                    kind,
+                };
+                let Some(info) = lookup(&key) else {
+                    continue;
+                };
+                pins.extend(
+                    info.artifacts(&update.latest_version, platform)
+                        .into_iter()
+                        .map(|artifact| Pin {
+                            name: update.name.clone(),
+                            kind,
+                            platform: platform.clone(),
+                            version: update.latest_version.clone(),
+                            file_name: artifact.file_name,
+                            url: artifact.url,
+                            sha256: artifact.sha256,
+                        }),
+                );
+            }
+        }
+
+        Self {
+            format_version: PINS_FORMAT_VERSION,
+            pins,
+        }
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self).context("Failed to serialize pins")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conda::CondaVersionInfo;
+    use crate::report::PackageUpdate;
+    use crate::upstream::Artifact;
+    use std::collections::HashMap;
+
+    const CHANNEL: &str = "https://conda.anaconda.org/conda-forge";
+
+    fn update(name: &str, kind: PackageKind, latest: &str) -> PackageUpdate {
+        PackageUpdate {
+            name: name.to_string(),
+            kind: Some(kind),
+            channel: (kind == PackageKind::Conda).then(|| CHANNEL.to_string()),
+            installed_version: "1.0".to_string(),
+            latest_version: latest.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn artifact(file_name: &str) -> Artifact {
+        Artifact {
+            file_name: file_name.to_string(),
+            url: format!("{}/noarch/{}", CHANNEL, file_name),
+            sha256: Some("ab".repeat(32)),
+        }
+    }
+
+    #[test]
+    fn test_pins_from_updates() {
+        let info = UpstreamInfo::Conda(CondaVersionInfo {
+            latest_version: Some("2.0".to_string()),
+            files: HashMap::from([(
+                ("2.0".to_string(), "noarch".to_string()),
+                vec![artifact("tzdata-2.0-h0_0.conda")],
+            )]),
+            ..Default::default()
+        });
+        let updates = PlatformUpdates::from([(
+            "linux-64".to_string(),
+            vec![
+                update("tzdata", PackageKind::Conda, "2.0"),
+                update("unknown", PackageKind::Conda, "3.0"),
+            ],
+        )]);
+
+        let pins = Pins::from_updates(&updates, |key| (key.name == "tzdata").then_some(&info));
+        assert_eq!(
+            pins.pins,
+            [Pin {
+                name: "tzdata".to_string(),
+                kind: PackageKind::Conda,
+                platform: "linux-64".to_string(),
+                version: "2.0".to_string(),
+                file_name: "tzdata-2.0-h0_0.conda".to_string(),
+                url: format!("{}/noarch/tzdata-2.0-h0_0.conda", CHANNEL),
+                sha256: Some("ab".repeat(32)),
+            }]
+        );
+
+        let json: serde_json::Value = serde_json::from_str(&pins.to_json().unwrap()).unwrap();
+        assert_eq!(json["format_version"], PINS_FORMAT_VERSION);
+        assert_eq!(json["pins"][0]["kind"], "conda");
+    }
+}
@@ -1,8 +1,8 @@
 use anyhow::{Context, Result};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::process::Command;
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Clone, PartialEq, Eq)]
 pub struct PixiPackage {
     pub name: String,
     pub version: String,
@@ -14,15 +14,36 @@ pub struct PixiPackage {
     #[serde(default)]
     pub source: Option<String>,
     pub is_explicit: bool,
+    /// Names of the packages the locked version depends on
+    #[serde(default)]
+    pub depends: Vec<String>,
 }
 
-#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[serde(rename_all = "lowercase")]
 pub enum PackageKind {
     Conda,
     Pypi,
 }
 
+impl std::fmt::Display for PackageKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PackageKind::Conda => write!(f, "conda"),
+            PackageKind::Pypi => write!(f, "pypi"),
+        }
+    }
+}
+
+/// The version of the conda `python` among `packages`, which limits the
+/// PyPI releases that can be installed next to it
+pub fn python_version(packages: &[PixiPackage]) -> Option<&str> {
+    packages
+        .iter()
+        .find(|package| package.kind == PackageKind::Conda && package.name == "python")
+        .map(|package| package.version.as_str())
+}
+
 /// Get the list of packages from `pixi list --json`
 pub fn get_package_list(
     explicit: bool,
@@ -170,6 +191,7 @@ mod tests {
             kind: PackageKind::Conda,
             source: Some("https://example.com/package.tar.bz2".to_string()),
             is_explicit: true,
+            depends: vec![],
         };
 
         let cloned = package.clone();
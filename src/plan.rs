@@ -0,0 +1,279 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use crate::pixi::PackageKind;
+
+/// Bumped whenever the plan format changes incompatibly
+pub const PLAN_FORMAT_VERSION: u32 = 1;
+
+/// Locked versions of an environment, keyed by `(platform, package name)`
+pub type LockedVersions = HashMap<(String, String), String>;
+
+/// A single locked package that the plan moves to a new version
+#[derive(Debug, Serialize, Clone, Deserialize, PartialEq, Eq)]
+pub struct PlannedUpdate {
+    pub name: String,
+    pub kind: PackageKind,
+    pub platform: String,
+    pub from_version: String,
+    pub to_version: String,
+}
+
+impl PlannedUpdate {
+    fn locked_version<'a>(&self, locked: &'a LockedVersions) -> Option<&'a str> {
+        locked
+            .get(&(self.platform.clone(), self.name.clone()))
+            .map(String::as_str)
+    }
+}
+
+/// All planned updates of one package, merged across platforms, for picking
+/// which packages to update
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlanChoice {
+    pub name: String,
+    pub kind: PackageKind,
+    pub to_version: String,
+    /// Distinct installed versions, in platform order
+    pub from_versions: Vec<String>,
+    pub platforms: Vec<String>,
+}
+
+impl fmt::Display for PlanChoice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} ({}): {} -> {} [{}]",
+            self.name,
+            self.kind,
+            self.from_versions.join(", "),
+            self.to_version,
+            self.platforms.join(", ")
+        )
+    }
+}
+
+/// The updates found by a run, written with `--plan-out` and consumed by `apply`
+#[derive(Debug, Serialize, Clone, Deserialize, PartialEq, Eq)]
+pub struct UpdatePlan {
+    pub format_version: u32,
+    pub environment: String,
+    pub updates: Vec<PlannedUpdate>,
+}
+
+impl UpdatePlan {
+    pub fn new(environment: impl Into<String>) -> Self {
+        Self {
+            format_version: PLAN_FORMAT_VERSION,
+            environment: environment.into(),
+            updates: Vec::new(),
+        }
+    }
+
+    /// Read a plan from disk
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read update plan {}", path.display()))?;
+        let plan: UpdatePlan = serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse update plan {}", path.display()))?;
+
+        if plan.format_version != PLAN_FORMAT_VERSION {
+            anyhow::bail!(
+                "Update plan {} has format version {}, but this version of pixi-outdated only supports version {}",
+                path.display(),
+                plan.format_version,
+                PLAN_FORMAT_VERSION
+            );
+        }
+
+        Ok(plan)
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self).context("Failed to serialize update plan")
+    }
+
+    /// Names of all packages in the plan, sorted and deduplicated
+    pub fn package_names(&self) -> Vec<&str> {
+        let names: BTreeSet<&str> = self.updates.iter().map(|u| u.name.as_str()).collect();
+        names.into_iter().collect()
+    }
+
+    /// Platforms touched by the plan, sorted and deduplicated
+    pub fn platforms(&self) -> Vec<&str> {
+        let platforms: BTreeSet<&str> = self.updates.iter().map(|u| u.platform.as_str()).collect();
+        platforms.into_iter().collect()
+    }
+
+    /// One choice per package, sorted by name
+    pub fn choices(&self) -> Vec<PlanChoice> {
+        let mut choices: BTreeMap<(&str, PackageKind), PlanChoice> = BTreeMap::new();
+        for update in &self.updates {
+            let choice = choices
+                .entry((update.name.as_str(), update.kind))
+                .or_insert_with(|| PlanChoice {
+                    name: update.name.clone(),
+                    kind: update.kind,
+                    to_version: update.to_version.clone(),
+                    from_versions: Vec::new(),
+                    platforms: Vec::new(),
+                });
+            if !choice.from_versions.contains(&update.from_version) {
+                choice.from_versions.push(update.from_version.clone());
+            }
+            choice.platforms.push(update.platform.clone());
+        }
+        choices.into_values().collect()
+    }
+
+    /// Keep only the updates of the given packages
+    pub fn retain_packages(&mut self, packages: &[(String, PackageKind)]) {
+        self.updates.retain(|u| {
+            packages
+                .iter()
+                .any(|(name, kind)| *name == u.name && *kind == u.kind)
+        });
+    }
+
+    /// Updates whose starting version no longer matches the lockfile
+    pub fn stale_updates(&self, locked: &LockedVersions) -> Vec<&PlannedUpdate> {
+        self.updates
+            .iter()
+            .filter(|u| u.locked_version(locked) != Some(u.from_version.as_str()))
+            .collect()
+    }
+
+    /// Updates whose target version is not what ended up in the lockfile
+    pub fn unapplied_updates(&self, locked: &LockedVersions) -> Vec<&PlannedUpdate> {
+        self.updates
+            .iter()
+            .filter(|u| u.locked_version(locked) != Some(u.to_version.as_str()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_plan() -> UpdatePlan {
+        let mut plan = UpdatePlan::new("default");
+        for platform in ["linux-64", "osx-arm64"] {
+            plan.updates.push(PlannedUpdate {
+                name: "python".to_string(),
+                kind: PackageKind::Conda,
+                platform: platform.to_string(),
+                from_version: "3.12.11".to_string(),
+                to_version: "3.14.0".to_string(),
+            });
+        }
+        plan.updates.push(PlannedUpdate {
+            name: "cowsay".to_string(),
+            kind: PackageKind::Pypi,
+            platform: "linux-64".to_string(),
+            from_version: "6.0".to_string(),
+            to_version: "6.1".to_string(),
+        });
+        plan
+    }
+
+    fn locked(entries: &[(&str, &str, &str)]) -> LockedVersions {
+        entries
+            .iter()
+            .map(|(platform, name, version)| {
+                (
+                    (platform.to_string(), name.to_string()),
+                    version.to_string(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_plan_roundtrip() {
+        let plan = sample_plan();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("plan.json");
+        fs::write(&path, plan.to_json().unwrap()).unwrap();
+
+        assert_eq!(UpdatePlan::load(&path).unwrap(), plan);
+    }
+
+    #[test]
+    fn test_load_rejects_unknown_format_version() {
+        let mut plan = sample_plan();
+        plan.format_version = PLAN_FORMAT_VERSION + 1;
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("plan.json");
+        fs::write(&path, plan.to_json().unwrap()).unwrap();
+
+        let err = UpdatePlan::load(&path).unwrap_err();
+        assert!(err.to_string().contains("format version"));
+    }
+
+    #[test]
+    fn test_package_names_and_platforms() {
+        let plan = sample_plan();
+        assert_eq!(plan.package_names(), vec!["cowsay", "python"]);
+        assert_eq!(plan.platforms(), vec!["linux-64", "osx-arm64"]);
+    }
+
+    #[test]
+    fn test_choices_merge_platforms() {
+        let mut plan = sample_plan();
+        plan.updates[1].from_version = "3.12.12".to_string();
+
+        let choices = plan.choices();
+        assert_eq!(choices.len(), 2);
+        assert_eq!(
+            choices[0].to_string(),
+            "cowsay (pypi): 6.0 -> 6.1 [linux-64]"
+        );
+        assert_eq!(
+            choices[1].to_string(),
+            "python (conda): 3.12.11, 3.12.12 -> 3.14.0 [linux-64, osx-arm64]"
+        );
+    }
+
+    #[test]
+    fn test_retain_packages() {
+        let mut plan = sample_plan();
+        plan.retain_packages(&[("cowsay".to_string(), PackageKind::Pypi)]);
+        assert_eq!(plan.package_names(), vec!["cowsay"]);
+
+        // The kind has to match as well
+        plan.retain_packages(&[("cowsay".to_string(), PackageKind::Conda)]);
+        assert!(plan.updates.is_empty());
+    }
+
+    #[test]
+    fn test_stale_updates() {
+        let plan = sample_plan();
+        let current = locked(&[
+            ("linux-64", "python", "3.12.11"),
+            ("osx-arm64", "python", "3.13.0"),
+            ("linux-64", "cowsay", "6.0"),
+        ]);
+
+        let stale = plan.stale_updates(&current);
+        assert_eq!(stale.len(), 1);
+        assert_eq!(stale[0].platform, "osx-arm64");
+    }
+
+    #[test]
+    fn test_unapplied_updates() {
+        let plan = sample_plan();
+        let current = locked(&[
+            ("linux-64", "python", "3.14.0"),
+            ("osx-arm64", "python", "3.14.0"),
+        ]);
+
+        let unapplied = plan.unapplied_updates(&current);
+        assert_eq!(unapplied.len(), 1);
+        assert_eq!(unapplied[0].name, "cowsay");
+    }
+}
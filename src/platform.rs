@@ -0,0 +1,182 @@
+use anyhow::Result;
+use rattler_conda_types::Platform;
+use std::str::FromStr;
+
+/// Parse a platform string, suggesting the closest known platform on typos
+pub fn parse_platform(input: &str) -> Result<Platform> {
+    Platform::from_str(input).map_err(|_| {
+        let known: Vec<&str> = Platform::all().map(|p| p.as_str()).collect();
+        match suggest(input, known.iter().copied()) {
+            Some(suggestion) => anyhow::anyhow!(
+                "Invalid platform '{}'. Did you mean '{}'?",
+                input,
+                suggestion
+            ),
+            None => anyhow::anyhow!(
+                "Invalid platform '{}'. Known platforms: {}",
+                input,
+                known.join(", ")
+            ),
+        }
+    })
+}
+
+/// Make sure `platform` is locked for the environment, listing the locked
+/// platforms otherwise
+pub fn ensure_locked(platform: Platform, locked: &[Platform], environment: &str) -> Result<()> {
+    if locked.contains(&platform) {
+        return Ok(());
+    }
+
+    let mut available: Vec<&str> = locked.iter().map(|p| p.as_str()).collect();
+    available.sort_unstable();
+
+    if available.is_empty() {
+        anyhow::bail!(
+            "Platform '{}' is not locked for environment '{}', which has no locked platforms",
+            platform,
+            environment
+        );
+    }
+
+    anyhow::bail!(
+        "Platform '{}' is not locked for environment '{}'. Locked platforms: {}",
+        platform,
+        environment,
+        available.join(", ")
+    )
+}
+
+/// Make sure the environment is still solved for `platform`. A lockfile can
+/// carry platforms that a feature or environment no longer supports until
+/// it is locked again.
+pub fn ensure_solved(platform: Platform, solved: &[Platform], environment: &str) -> Result<()> {
+    if solved.contains(&platform) {
+        return Ok(());
+    }
+
+    let mut available: Vec<&str> = solved.iter().map(|p| p.as_str()).collect();
+    available.sort_unstable();
+    anyhow::bail!(
+        "Environment '{}' is not solved for platform '{}', only for: {}. \
+         Its entries in the lockfile are stale, run `pixi lock` to remove them",
+        environment,
+        platform,
+        available.join(", ")
+    )
+}
+
+/// Locked platforms the environment is no longer solved for, sorted
+pub fn stale_platforms(locked: &[Platform], solved: &[Platform]) -> Vec<Platform> {
+    let mut stale: Vec<Platform> = locked
+        .iter()
+        .filter(|platform| !solved.contains(platform))
+        .copied()
+        .collect();
+    stale.sort_unstable_by_key(|platform| platform.as_str());
+    stale
+}
+
+/// Find the candidate closest to `input`, if any is close enough to be a typo
+pub fn suggest<'a>(input: &str, candidates: impl IntoIterator<Item = &'a str>) -> Option<&'a str> {
+    let input = input.to_lowercase();
+    let max_distance = (input.len() / 3).max(2);
+
+    candidates
+        .into_iter()
+        .map(|candidate| (edit_distance(&input, candidate), candidate))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+/// Levenshtein distance between two strings
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("linux-64", "linux-64"), 0);
+        assert_eq!(edit_distance("linux64", "linux-64"), 1);
+        assert_eq!(edit_distance("osx_arm64", "osx-arm64"), 1);
+        assert_eq!(edit_distance("", "abc"), 3);
+    }
+
+    #[test]
+    fn test_parse_valid_platform() {
+        assert_eq!(parse_platform("linux-64").unwrap(), Platform::Linux64);
+    }
+
+    #[test]
+    fn test_parse_platform_suggests_typo_fix() {
+        let err = parse_platform("linux64").unwrap_err().to_string();
+        assert!(err.contains("Did you mean 'linux-64'?"), "{}", err);
+
+        let err = parse_platform("osx_arm64").unwrap_err().to_string();
+        assert!(err.contains("Did you mean 'osx-arm64'?"), "{}", err);
+    }
+
+    #[test]
+    fn test_parse_platform_without_suggestion() {
+        let err = parse_platform("windows-x64-foo-bar")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("Known platforms:"), "{}", err);
+    }
+
+    #[test]
+    fn test_ensure_locked() {
+        let locked = [Platform::OsxArm64, Platform::Linux64];
+        assert!(ensure_locked(Platform::Linux64, &locked, "default").is_ok());
+
+        let err = ensure_locked(Platform::Win64, &locked, "default")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("environment 'default'"), "{}", err);
+        assert!(
+            err.contains("Locked platforms: linux-64, osx-arm64"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_ensure_solved() {
+        let solved = [Platform::OsxArm64];
+        assert!(ensure_solved(Platform::OsxArm64, &solved, "metal").is_ok());
+
+        let err = ensure_solved(Platform::Linux64, &solved, "metal")
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains(
+                "Environment 'metal' is not solved for platform 'linux-64', only for: osx-arm64"
+            ),
+            "{}",
+            err
+        );
+
+        let locked = [Platform::Win64, Platform::OsxArm64, Platform::Linux64];
+        assert_eq!(
+            stale_platforms(&locked, &solved),
+            [Platform::Linux64, Platform::Win64]
+        );
+    }
+}
@@ -0,0 +1,151 @@
+use anyhow::Result;
+use globset::GlobMatcher;
+
+/// Base URL that channel names like `conda-forge` resolve to, unless pixi is
+/// configured with another `channel-alias`
+pub const DEFAULT_CHANNEL_ALIAS: &str = "https://conda.anaconda.org";
+
+/// The channels locked conda packages may come from, configured with
+/// `allowed-channels` in `[tool.pixi-outdated]`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChannelAllowlist {
+    urls: Vec<String>,
+}
+
+impl ChannelAllowlist {
+    /// Build the allowlist from channel names (`conda-forge`), resolved
+    /// against `channel_alias`, or URLs
+    pub fn new<S: AsRef<str>>(channels: &[S], channel_alias: &str) -> Self {
+        Self {
+            urls: channels
+                .iter()
+                .map(|channel| channel_url(channel.as_ref(), channel_alias))
+                .collect(),
+        }
+    }
+
+    /// Whether a package from `channel`, as extracted from its locked URL,
+    /// is allowed. Allowing a channel allows its labels.
+    pub fn allows(&self, channel: &str) -> bool {
+        let channel = channel.trim_end_matches('/');
+        let main = crate::conda::without_label(channel);
+        self.urls.iter().any(|url| url == channel || url == main)
+    }
+}
+
+/// The base URL of a channel given by name, URL or absolute path, without a
+/// trailing slash. Names are resolved against `channel_alias`.
+pub fn channel_url(channel: &str, channel_alias: &str) -> String {
+    let channel = channel.trim_end_matches('/');
+    if channel.contains("://") {
+        channel.to_string()
+    } else if let Some(url) = crate::local::file_url(channel) {
+        url.to_string()
+    } else {
+        format!("{}/{}", channel_alias.trim_end_matches('/'), channel)
+    }
+}
+
+/// The licenses updates may be published under, configured with
+/// `allowed-licenses` in `[tool.pixi-outdated]`. Entries are SPDX identifiers
+/// or globs such as `BSD-*`, matched case-insensitively.
+#[derive(Debug, Clone)]
+pub struct LicenseAllowlist {
+    matchers: Vec<GlobMatcher>,
+}
+
+impl LicenseAllowlist {
+    pub fn new<S: AsRef<str>>(licenses: &[S]) -> Result<Self> {
+        let matchers = crate::glob::matchers(licenses, true, "allowed license")?;
+        Ok(Self { matchers })
+    }
+
+    /// Whether a license expression such as `MIT OR GPL-3.0-only` or
+    /// `BSD-3-Clause AND Apache-2.0` is allowed: every license of one of its
+    /// alternatives has to be. Parentheses are ignored and exceptions
+    /// (`WITH ...`) don't need to be listed.
+    pub fn allows(&self, expression: &str) -> bool {
+        let expression = expression.replace(['(', ')'], " ");
+        let mut alternatives: Vec<Vec<&str>> = vec![Vec::new()];
+        let mut tokens = expression.split_whitespace();
+        while let Some(token) = tokens.next() {
+            if token.eq_ignore_ascii_case("or") {
+                alternatives.push(Vec::new());
+            } else if token.eq_ignore_ascii_case("with") {
+                tokens.next();
+            } else if !token.eq_ignore_ascii_case("and") {
+                alternatives.last_mut().unwrap().push(token);
+            }
+        }
+
+        alternatives
+            .iter()
+            .filter(|licenses| !licenses.is_empty())
+            .any(|licenses| {
+                licenses.iter().all(|license| {
+                    self.matchers
+                        .iter()
+                        .any(|matcher| matcher.is_match(license))
+                })
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_license_allowlist() {
+        let allowlist = LicenseAllowlist::new(&["MIT", "BSD-*", "Apache-2.0"]).unwrap();
+        assert!(allowlist.allows("MIT"));
+        assert!(allowlist.allows("mit"));
+        assert!(allowlist.allows("BSD-3-Clause AND Apache-2.0"));
+        assert!(allowlist.allows("GPL-3.0-only OR MIT"));
+        assert!(allowlist.allows("(Apache-2.0 WITH LLVM-exception)"));
+        assert!(!allowlist.allows("GPL-3.0-or-later"));
+        assert!(!allowlist.allows("MIT AND LGPL-2.1-only"));
+        assert!(!allowlist.allows(""));
+
+        assert!(LicenseAllowlist::new(&["BSD-[3"]).is_err());
+    }
+
+    #[test]
+    fn test_channel_url() {
+        assert_eq!(
+            channel_url("conda-forge", DEFAULT_CHANNEL_ALIAS),
+            "https://conda.anaconda.org/conda-forge"
+        );
+        assert_eq!(
+            channel_url("conda-forge", "https://conda.example.com/"),
+            "https://conda.example.com/conda-forge"
+        );
+        assert_eq!(
+            channel_url("https://repo.prefix.dev/internal/", DEFAULT_CHANNEL_ALIAS),
+            "https://repo.prefix.dev/internal"
+        );
+        assert_eq!(
+            channel_url("/opt/channel/", DEFAULT_CHANNEL_ALIAS),
+            "file:///opt/channel"
+        );
+    }
+
+    #[test]
+    fn test_allows() {
+        let allowlist = ChannelAllowlist::new(
+            &["conda-forge", "https://repo.prefix.dev/org/internal"],
+            DEFAULT_CHANNEL_ALIAS,
+        );
+        assert!(allowlist.allows("https://conda.anaconda.org/conda-forge"));
+        assert!(allowlist.allows("https://repo.prefix.dev/org/internal/"));
+        assert!(!allowlist.allows("https://conda.anaconda.org/bioconda"));
+        assert!(!allowlist.allows("https://repo.prefix.dev/org"));
+        assert!(allowlist.allows("https://conda.anaconda.org/conda-forge/label/rc"));
+    }
+
+    #[test]
+    fn test_empty_allowlist_allows_nothing() {
+        let allowlist = ChannelAllowlist::new::<&str>(&[], DEFAULT_CHANNEL_ALIAS);
+        assert!(!allowlist.allows("https://conda.anaconda.org/conda-forge"));
+    }
+}
@@ -0,0 +1,177 @@
+use crate::conda::CondaVersionInfo;
+use crate::upstream::{UpstreamInfo, VersionLookup};
+
+/// How pixi picks between the channels of an environment that carry the
+/// same package, set with `channel-priority` in the manifest
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ChannelPriority {
+    /// Only the first channel that has the package counts, pixi's default
+    #[default]
+    Strict,
+    /// Every channel counts, and the newest version wins wherever it is
+    Disabled,
+}
+
+impl ChannelPriority {
+    /// The priority a manifest sets, e.g. `channel-priority = "disabled"`
+    pub fn from_manifest(value: &str) -> Option<Self> {
+        match value {
+            "strict" => Some(ChannelPriority::Strict),
+            "disabled" => Some(ChannelPriority::Disabled),
+            _ => None,
+        }
+    }
+}
+
+/// Pick the versions of a conda package that a relock chooses from, given
+/// what each channel of the environment has of it, in priority order.
+/// Returns them with the channel the latest version comes from, or `None`
+/// when that can't be told: no channel has the package, or a channel that
+/// counts couldn't be queried.
+pub fn resolve(
+    lookups: &[(&str, &VersionLookup)],
+    priority: ChannelPriority,
+) -> Option<(String, VersionLookup)> {
+    let mut resolved: Option<(&str, CondaVersionInfo)> = None;
+    for (channel, lookup) in lookups {
+        match lookup {
+            VersionLookup::Found(UpstreamInfo::Conda(info)) => match resolved {
+                None => resolved = Some((*channel, info.clone())),
+                Some((ref mut latest_channel, ref mut merged)) => {
+                    let latest = merged.latest_version.clone();
+                    merged.merge(info.clone());
+                    if merged.latest_version != latest {
+                        *latest_channel = *channel;
+                    }
+                }
+            },
+            VersionLookup::Found(_) | VersionLookup::NotFound => continue,
+            VersionLookup::Failed(_) => return None,
+        }
+        // Lower priority channels are never looked at once one has the package
+        if priority == ChannelPriority::Strict {
+            break;
+        }
+    }
+    resolved.map(|(channel, info)| {
+        (
+            channel.to_string(),
+            VersionLookup::Found(UpstreamInfo::Conda(info)),
+        )
+    })
+}
+
+/// The channel with the newest version of a conda package among those that
+/// have it, whatever their priority, with that version. Channels that
+/// couldn't be queried are passed over.
+pub fn newest(lookups: &[(&str, &VersionLookup)]) -> Option<(String, String)> {
+    let mut newest: Option<(&str, CondaVersionInfo)> = None;
+    for (channel, lookup) in lookups {
+        let VersionLookup::Found(UpstreamInfo::Conda(info)) = lookup else {
+            continue;
+        };
+        match newest {
+            None => newest = Some((*channel, info.clone())),
+            Some((ref mut newest_channel, ref mut merged)) => {
+                let latest = merged.latest_version.clone();
+                merged.merge(info.clone());
+                if merged.latest_version != latest {
+                    *newest_channel = *channel;
+                }
+            }
+        }
+    }
+    newest.and_then(|(channel, info)| Some((channel.to_string(), info.latest_version?)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn found(latest: &str) -> VersionLookup {
+        VersionLookup::Found(UpstreamInfo::Conda(CondaVersionInfo {
+            latest_version: Some(latest.to_string()),
+            artifacts: [(latest.to_string(), "h1_0".to_string())].into(),
+            ..Default::default()
+        }))
+    }
+
+    fn latest(resolved: Option<(String, VersionLookup)>) -> Option<(String, String)> {
+        resolved.map(|(channel, lookup)| {
+            (
+                channel,
+                lookup.info().unwrap().latest_version().unwrap().to_string(),
+            )
+        })
+    }
+
+    #[test]
+    fn test_resolve() {
+        let (defaults, conda_forge) = (found("1.26.4"), found("2.1.0"));
+        let lookups = [
+            ("https://repo.anaconda.com/pkgs", &VersionLookup::NotFound),
+            ("https://conda.anaconda.org/defaults", &defaults),
+            ("https://conda.anaconda.org/conda-forge", &conda_forge),
+        ];
+
+        assert_eq!(
+            latest(resolve(&lookups, ChannelPriority::Strict)),
+            Some((
+                "https://conda.anaconda.org/defaults".to_string(),
+                "1.26.4".to_string()
+            ))
+        );
+        assert_eq!(
+            latest(resolve(&lookups, ChannelPriority::Disabled)),
+            Some((
+                "https://conda.anaconda.org/conda-forge".to_string(),
+                "2.1.0".to_string()
+            ))
+        );
+
+        // A channel that could have the package but couldn't be queried
+        let failed = VersionLookup::Failed("timed out".to_string());
+        let lookups = [
+            ("https://conda.anaconda.org/defaults", &failed),
+            ("https://conda.anaconda.org/conda-forge", &conda_forge),
+        ];
+        assert!(resolve(&lookups, ChannelPriority::Strict).is_none());
+        assert!(resolve(&[], ChannelPriority::Strict).is_none());
+    }
+
+    #[test]
+    fn test_newest() {
+        let (defaults, conda_forge) = (found("1.26.4"), found("2.1.0"));
+        let failed = VersionLookup::Failed("timed out".to_string());
+        let lookups = [
+            ("https://conda.anaconda.org/defaults", &defaults),
+            ("https://conda.anaconda.org/pytorch", &failed),
+            ("https://conda.anaconda.org/conda-forge", &conda_forge),
+        ];
+        assert_eq!(
+            newest(&lookups),
+            Some((
+                "https://conda.anaconda.org/conda-forge".to_string(),
+                "2.1.0".to_string()
+            ))
+        );
+
+        // A pre-release elsewhere doesn't beat a stable release
+        let rc = found("2.2.0rc1");
+        let lookups = [
+            ("https://conda.anaconda.org/conda-forge", &conda_forge),
+            ("https://conda.anaconda.org/conda-forge/label/rc", &rc),
+        ];
+        assert_eq!(newest(&lookups).unwrap().1, "2.1.0");
+        assert!(newest(&[("https://conda.anaconda.org/pytorch", &failed)]).is_none());
+    }
+
+    #[test]
+    fn test_from_manifest() {
+        assert_eq!(
+            ChannelPriority::from_manifest("disabled"),
+            Some(ChannelPriority::Disabled)
+        );
+        assert_eq!(ChannelPriority::from_manifest("loose"), None);
+    }
+}
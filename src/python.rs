@@ -0,0 +1,241 @@
+use serde::{Deserialize, Serialize};
+
+use crate::conda::spec_matches;
+use crate::pypi::{specifiers_match, PyPiFile};
+use crate::severity::numeric_components;
+
+/// What updating the workspace interpreter on a platform would involve
+#[derive(Debug, Serialize, Clone, Deserialize, PartialEq, Eq)]
+pub struct PythonUpgrade {
+    pub platform: String,
+    pub installed_version: String,
+    pub latest_version: String,
+    /// Requirements on `python` in the manifest
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub specs: Vec<String>,
+    /// `requires-python` of a `pyproject.toml`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub requires_python: Option<String>,
+    /// Whether the manifest requirements and `requires-python` admit the latest version
+    pub allowed: bool,
+    /// Locked PyPI packages whose locked release has wheels, but none for the
+    /// latest interpreter
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub without_wheels: Vec<String>,
+}
+
+impl std::fmt::Display for PythonUpgrade {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "python: {} -> {}",
+            self.installed_version, self.latest_version
+        )?;
+
+        let mut constraints: Vec<String> = self.specs.clone();
+        if let Some(ref requires_python) = self.requires_python {
+            constraints.push(format!("requires-python {}", requires_python));
+        }
+        match (self.allowed, constraints.is_empty()) {
+            (true, true) => {}
+            (true, false) => write!(f, " (allowed by {})", constraints.join(", "))?,
+            (false, _) => write!(f, " (blocked by {})", constraints.join(", "))?,
+        }
+
+        if !self.without_wheels.is_empty() {
+            write!(
+                f,
+                "; no wheels for Python {}: {}",
+                python_minor(&self.latest_version),
+                self.without_wheels.join(", ")
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl PythonUpgrade {
+    /// Check an interpreter update against the manifest requirements and the
+    /// files of the locked PyPI packages, given as `(name, files of the locked release)`
+    pub fn assess<'a>(
+        platform: &str,
+        installed_version: &str,
+        latest_version: &str,
+        specs: &[&str],
+        requires_python: Option<&str>,
+        locked_pypi: impl IntoIterator<Item = (&'a str, &'a [PyPiFile])>,
+    ) -> Self {
+        let allowed = specs.iter().all(|spec| spec_matches(spec, latest_version))
+            && requires_python.is_none_or(|rp| specifiers_match(rp, latest_version));
+
+        let components = numeric_components(latest_version);
+        let (major, minor) = (
+            components.first().copied().unwrap_or(0),
+            components.get(1).copied().unwrap_or(0),
+        );
+        let mut without_wheels: Vec<String> = locked_pypi
+            .into_iter()
+            .filter(|(_, files)| lacks_wheels(files, major, minor))
+            .map(|(name, _)| name.to_string())
+            .collect();
+        without_wheels.sort();
+
+        Self {
+            platform: platform.to_string(),
+            installed_version: installed_version.to_string(),
+            latest_version: latest_version.to_string(),
+            specs: specs.iter().map(|spec| spec.to_string()).collect(),
+            requires_python: requires_python.map(str::to_string),
+            allowed,
+            without_wheels,
+        }
+    }
+}
+
+/// The `major.minor` part of a Python version, e.g. `3.14` for `3.14.0`
+pub fn python_minor(version: &str) -> String {
+    numeric_components(version)
+        .iter()
+        .take(2)
+        .map(u64::to_string)
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+/// Whether a wheel can be installed on CPython `major.minor`, judging by the
+/// python and ABI tags in its file name
+pub fn wheel_supports(filename: &str, major: u64, minor: u64) -> bool {
+    let Some(stem) = filename.strip_suffix(".whl") else {
+        return false;
+    };
+    // name-version[-build]-python-abi-platform
+    let parts: Vec<&str> = stem.split('-').collect();
+    let [.., python_tags, abi_tag, _platform] = parts.as_slice() else {
+        return false;
+    };
+
+    python_tags.split('.').any(|tag| {
+        if tag == format!("py{}", major) || tag == format!("py{}{}", major, minor) {
+            return true;
+        }
+        let Some(version) = tag.strip_prefix(&format!("cp{}", major)) else {
+            return false;
+        };
+        match version.parse::<u64>() {
+            Ok(tag_minor) if tag_minor == minor => true,
+            // Stable ABI wheels work on every later interpreter
+            Ok(tag_minor) => *abi_tag == "abi3" && tag_minor < minor,
+            Err(_) => false,
+        }
+    })
+}
+
+/// Whether a release ships wheels, but none for CPython `major.minor`.
+/// Releases with only an sdist might still build, so they don't count.
+pub fn lacks_wheels(files: &[PyPiFile], major: u64, minor: u64) -> bool {
+    let mut wheels = files
+        .iter()
+        .filter(|f| f.filename.ends_with(".whl"))
+        .peekable();
+    wheels.peek().is_some() && !wheels.any(|f| wheel_supports(&f.filename, major, minor))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn files(names: &[&str]) -> Vec<PyPiFile> {
+        names
+            .iter()
+            .map(|name| PyPiFile {
+                filename: name.to_string(),
+                yanked: false,
+                yanked_reason: None,
+                upload_time_iso_8601: None,
+                requires_python: None,
+                url: String::new(),
+                digests: Default::default(),
+                core_metadata: false,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_wheel_supports() {
+        assert!(wheel_supports("cowsay-6.1-py3-none-any.whl", 3, 14));
+        assert!(wheel_supports("six-1.16.0-py2.py3-none-any.whl", 3, 14));
+        assert!(wheel_supports(
+            "numpy-2.3.4-cp314-cp314-manylinux_2_27_x86_64.whl",
+            3,
+            14
+        ));
+        assert!(!wheel_supports(
+            "numpy-1.26.4-cp312-cp312-manylinux_2_17_x86_64.whl",
+            3,
+            14
+        ));
+        assert!(wheel_supports(
+            "cryptography-46.0.2-cp311-abi3-manylinux_2_34_x86_64.whl",
+            3,
+            14
+        ));
+        assert!(!wheel_supports("numpy-1.26.4.tar.gz", 3, 14));
+    }
+
+    #[test]
+    fn test_lacks_wheels() {
+        assert!(lacks_wheels(
+            &files(&[
+                "numpy-1.26.4.tar.gz",
+                "numpy-1.26.4-cp312-cp312-manylinux_2_17_x86_64.whl",
+            ]),
+            3,
+            14
+        ));
+        assert!(!lacks_wheels(
+            &files(&["cowsay-6.1-py3-none-any.whl"]),
+            3,
+            14
+        ));
+        // Only an sdist: it may build from source
+        assert!(!lacks_wheels(&files(&["cowsay-5.0.tar.gz"]), 3, 14));
+    }
+
+    #[test]
+    fn test_assess() {
+        let numpy = files(&["numpy-1.26.4-cp312-cp312-manylinux_2_17_x86_64.whl"]);
+        let cowsay = files(&["cowsay-5.0.tar.gz"]);
+
+        let upgrade = PythonUpgrade::assess(
+            "linux-64",
+            "3.12.11",
+            "3.14.0",
+            &[">=3.12"],
+            Some(">=3.10"),
+            [("numpy", numpy.as_slice()), ("cowsay", cowsay.as_slice())],
+        );
+        assert!(upgrade.allowed);
+        assert_eq!(upgrade.without_wheels, vec!["numpy".to_string()]);
+
+        let upgrade = PythonUpgrade::assess("linux-64", "3.12.11", "3.14.0", &["3.12.*"], None, []);
+        assert!(!upgrade.allowed);
+    }
+
+    #[test]
+    fn test_display() {
+        let upgrade = PythonUpgrade {
+            platform: "linux-64".to_string(),
+            installed_version: "3.12.11".to_string(),
+            latest_version: "3.14.0".to_string(),
+            specs: vec![">=3.12.0,<3.13".to_string()],
+            requires_python: None,
+            allowed: false,
+            without_wheels: vec!["numpy".to_string()],
+        };
+        assert_eq!(
+            upgrade.to_string(),
+            "python: 3.12.11 -> 3.14.0 (blocked by >=3.12.0,<3.13); no wheels for Python 3.14: numpy"
+        );
+        assert_eq!(python_minor("3.14.0"), "3.14");
+    }
+}
@@ -0,0 +1,256 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+use tracing::debug;
+
+use crate::cache::ResponseCache;
+
+/// How long fetching a channel's `channeldata.json` may take
+const FETCH_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// The name `channeldata.json` is cached under, per channel
+const CHANNELDATA: &str = "channeldata";
+
+/// `project_urls` labels naming a changelog, compared ignoring case,
+/// spaces, dashes and underscores
+const CHANGELOG_LABELS: &[&str] = &[
+    "changelog",
+    "changes",
+    "releasenotes",
+    "release",
+    "releases",
+    "history",
+    "news",
+    "whatsnew",
+];
+
+/// The releases page of a GitHub repository URL such as a package's home
+/// or source URL, e.g. `https://github.com/numpy/numpy/releases`
+pub fn github_releases(url: &str) -> Option<String> {
+    let path = url
+        .trim()
+        .strip_prefix("https://")
+        .or_else(|| url.trim().strip_prefix("http://"))?;
+    let path = path.strip_prefix("www.").unwrap_or(path);
+    let mut segments = path.strip_prefix("github.com/")?.split('/');
+    let owner = segments.next().filter(|owner| !owner.is_empty())?;
+    let repo = segments.next().filter(|repo| !repo.is_empty())?;
+    let repo = repo.strip_suffix(".git").unwrap_or(repo);
+    Some(format!("https://github.com/{}/{}/releases", owner, repo))
+}
+
+/// The release notes of a PyPI project: the URL its metadata labels as a
+/// changelog, else the releases page of its GitHub repository
+pub fn pypi_release_notes(
+    project_urls: &HashMap<String, String>,
+    home_page: Option<&str>,
+) -> Option<String> {
+    let mut labeled: Vec<(&String, &String)> = project_urls.iter().collect();
+    labeled.sort();
+    let changelog = labeled.iter().find(|(label, _)| {
+        let label: String = label
+            .chars()
+            .filter(|c| !matches!(c, ' ' | '-' | '_' | '\''))
+            .collect::<String>()
+            .to_lowercase();
+        CHANGELOG_LABELS.contains(&label.as_str())
+    });
+    if let Some((_, url)) = changelog {
+        return Some(url.to_string());
+    }
+    labeled
+        .iter()
+        .map(|(_, url)| url.as_str())
+        .chain(home_page)
+        .find_map(github_releases)
+}
+
+/// The `channeldata.json` of a conda channel, which describes each package
+/// the channel serves with the metadata of its latest build
+#[derive(Debug, Default, Clone, Deserialize)]
+pub struct ChannelData {
+    #[serde(default)]
+    packages: HashMap<String, ChannelPackage>,
+}
+
+#[derive(Debug, Default, Clone, Deserialize)]
+struct ChannelPackage {
+    #[serde(default)]
+    home: Option<String>,
+    #[serde(default)]
+    dev_url: Option<String>,
+}
+
+impl ChannelData {
+    pub fn from_json(body: &[u8]) -> Result<Self> {
+        Ok(serde_json::from_slice(body)?)
+    }
+
+    /// The GitHub releases page of a package, from its source URL or else
+    /// its home page
+    pub fn release_notes(&self, name: &str) -> Option<String> {
+        let package = self.packages.get(name)?;
+        [&package.dev_url, &package.home]
+            .into_iter()
+            .flatten()
+            .find_map(|url| github_releases(url))
+    }
+}
+
+/// Fetch the `channeldata.json` of a channel URL with `client`, e.g. the
+/// workspace's authenticated client, so private channels and mirrors work as
+/// they do for repodata. A copy in `cache` that hasn't expired is used
+/// instead, and fresh downloads are stored in it.
+pub async fn fetch_channeldata(
+    client: &reqwest_middleware::ClientWithMiddleware,
+    cache: Option<&ResponseCache>,
+    channel: &str,
+) -> Result<ChannelData> {
+    let url = format!("{}/channeldata.json", channel.trim_end_matches('/'));
+    if let Some(body) = cache.and_then(|cache| cache.get(channel, CHANNELDATA)) {
+        match ChannelData::from_json(&body) {
+            Ok(data) => return Ok(data),
+            Err(e) => debug!(url, error = %e, "Ignoring unreadable cached channeldata"),
+        }
+    }
+
+    let response = client
+        .get(url.as_str())
+        .timeout(FETCH_TIMEOUT)
+        .send()
+        .await
+        .with_context(|| format!("Failed to fetch {}", url))?;
+    let body = response
+        .error_for_status()
+        .with_context(|| format!("Failed to fetch {}", url))?
+        .bytes()
+        .await
+        .with_context(|| format!("Failed to read {}", url))?;
+    let data = ChannelData::from_json(&body).with_context(|| format!("Failed to parse {}", url))?;
+    if let Some(cache) = cache {
+        if let Err(e) = cache.put(channel, CHANNELDATA, &body) {
+            debug!(url, error = %e, "Failed to cache channeldata");
+        }
+    }
+    Ok(data)
+}
+
+/// Read a `channeldata.json` from disk, e.g. a fixture
+pub fn load_channeldata(path: &Path) -> Result<ChannelData> {
+    let content = fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    ChannelData::from_json(&content).with_context(|| format!("Failed to parse {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_github_releases() {
+        assert_eq!(
+            github_releases("https://github.com/numpy/numpy").as_deref(),
+            Some("https://github.com/numpy/numpy/releases")
+        );
+        assert_eq!(
+            github_releases("http://www.github.com/psf/requests.git/").as_deref(),
+            Some("https://github.com/psf/requests/releases")
+        );
+        assert_eq!(
+            github_releases("https://github.com/conda-forge/numpy-feedstock/tree/main").as_deref(),
+            Some("https://github.com/conda-forge/numpy-feedstock/releases")
+        );
+        assert_eq!(github_releases("https://github.com/numpy"), None);
+        assert_eq!(github_releases("https://numpy.org"), None);
+    }
+
+    #[test]
+    fn test_pypi_release_notes() {
+        let project_urls = HashMap::from([
+            (
+                "Source".to_string(),
+                "https://github.com/psf/requests".to_string(),
+            ),
+            (
+                "Release Notes".to_string(),
+                "https://requests.readthedocs.io/en/latest/community/updates/".to_string(),
+            ),
+        ]);
+        assert_eq!(
+            pypi_release_notes(&project_urls, None).as_deref(),
+            Some("https://requests.readthedocs.io/en/latest/community/updates/")
+        );
+
+        // Without a changelog, the GitHub releases stand in
+        let project_urls = HashMap::from([(
+            "Source".to_string(),
+            "https://github.com/psf/requests".to_string(),
+        )]);
+        assert_eq!(
+            pypi_release_notes(&project_urls, None).as_deref(),
+            Some("https://github.com/psf/requests/releases")
+        );
+        assert_eq!(
+            pypi_release_notes(
+                &HashMap::new(),
+                Some("https://github.com/VaasuDevanS/cowsay-python")
+            )
+            .as_deref(),
+            Some("https://github.com/VaasuDevanS/cowsay-python/releases")
+        );
+        assert_eq!(
+            pypi_release_notes(&HashMap::new(), Some("https://example.com")),
+            None
+        );
+    }
+
+    #[test]
+    fn test_channeldata_release_notes() {
+        let channeldata = ChannelData::from_json(
+            br#"{
+                "channeldata_version": 1,
+                "packages": {
+                    "numpy": {
+                        "home": "https://numpy.org",
+                        "dev_url": "https://github.com/numpy/numpy"
+                    },
+                    "zlib": {"home": "https://zlib.net"}
+                }
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            channeldata.release_notes("numpy").as_deref(),
+            Some("https://github.com/numpy/numpy/releases")
+        );
+        assert_eq!(channeldata.release_notes("zlib"), None);
+        assert_eq!(channeldata.release_notes("python"), None);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_channeldata_cached() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = ResponseCache::new(dir.path().to_path_buf(), crate::cache::DEFAULT_TTL);
+        // Nothing listens there, so the answer has to come from the cache
+        let channel = "http://127.0.0.1:9/my-channel";
+        cache
+            .put(
+                channel,
+                CHANNELDATA,
+                br#"{"packages": {"numpy": {"dev_url": "https://github.com/numpy/numpy"}}}"#,
+            )
+            .unwrap();
+
+        let client = reqwest_middleware::ClientBuilder::new(reqwest::Client::new()).build();
+        let channeldata = fetch_channeldata(&client, Some(&cache), channel)
+            .await
+            .unwrap();
+        assert_eq!(
+            channeldata.release_notes("numpy").as_deref(),
+            Some("https://github.com/numpy/numpy/releases")
+        );
+        assert!(fetch_channeldata(&client, None, channel).await.is_err());
+    }
+}
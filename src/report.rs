@@ -0,0 +1,2359 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+use crate::annotations::{self, Annotation, Annotations};
+use crate::audit::VulnerablePackage;
+use crate::automerge::MergeDecision;
+use crate::explain::Explanation;
+use crate::libyears::Libyears;
+use crate::locale::Locale;
+use crate::pixi::{PackageKind, PixiPackage};
+use crate::python::PythonUpgrade;
+use crate::risk::RiskGroup;
+use crate::severity::Severity;
+use crate::sources::{NetworkUsage, RepodataFetch, SourceStats};
+use crate::spread::VersionSpread;
+
+#[derive(Debug, Serialize, Clone, Default, Deserialize, PartialEq, Eq)]
+pub struct PackageUpdate {
+    pub name: String,
+    /// Whether the package comes from a conda channel or a PyPI index
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<PackageKind>,
+    /// The conda channel the latest version was looked up in
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub channel: Option<String>,
+    pub installed_version: String,
+    pub latest_version: String,
+    /// Whether the manifest declares the package, rather than it being a
+    /// transitive dependency
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub direct: bool,
+    /// The manifest features declaring the package, if it's tied to any
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub features: Vec<String>,
+    /// For a transitive dependency, the shortest chain of locked packages
+    /// pulling it in, starting at a manifest dependency
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub required_by: Vec<String>,
+    /// The newest version allowed by the requirements in the manifest, for
+    /// packages the manifest constrains. When it differs from `latest_version`,
+    /// getting the latest version means editing the requirement.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latest_compatible: Option<String>,
+    /// With `--verify-solvable`, why pinning the latest version leaves the
+    /// workspace unsolvable, if it does
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unsolvable: Option<String>,
+    /// How big the jump from the installed to the latest version is
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub severity: Option<Severity>,
+    /// How many bytes the latest version's artifact adds to the installed
+    /// one's, negative if it's smaller. Known for conda packages only.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size_delta: Option<i64>,
+    /// Days since the installed version was released, when known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub installed_age_days: Option<u32>,
+    /// Days since the first release newer than the installed version, i.e.
+    /// how long the package has been outdated
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub newer_available_days: Option<u32>,
+    /// Days between the releases of the installed and latest versions
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lag_days: Option<u32>,
+    /// Whether the installed version is older than the `--max-lag` threshold
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub exceeds_max_lag: bool,
+    /// Activation scripts the installed version ships, which an update can
+    /// change for every shell the environment is activated in
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub activation_scripts: Vec<String>,
+    /// Risk group assigned by `--risk-profile`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub risk: Option<RiskGroup>,
+    /// What put the update into its risk group
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub risk_reasons: Vec<String>,
+    /// Whether the update can be merged without review, with `--auto-merge`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub merge: Option<MergeDecision>,
+    /// Which auto-merge rules the update broke
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub merge_reasons: Vec<String>,
+    /// Where the project publishes its release notes, with `--release-notes`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub release_notes: Option<String>,
+    /// Metadata from `[tool.pixi-outdated.annotations]`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub annotations: Annotation,
+}
+
+impl PackageUpdate {
+    /// Whether both move the same installed version to the same latest
+    /// version, e.g. on two platforms or in two environments
+    pub fn is_same_update(&self, other: &PackageUpdate) -> bool {
+        self.name == other.name
+            && self.installed_version == other.installed_version
+            && self.latest_version == other.latest_version
+    }
+
+    /// The name, annotated with the features declaring the package, e.g.
+    /// `pytest (feature: test)`
+    pub fn label(&self) -> String {
+        match self.features.as_slice() {
+            [] => self.name.clone(),
+            [feature] => format!("{} (feature: {})", self.name, feature),
+            features => format!("{} (features: {})", self.name, features.join(", ")),
+        }
+    }
+
+    /// Whether getting the latest version means changing the manifest: the
+    /// package's requirement there excludes it
+    pub fn needs_requirement_change(&self) -> bool {
+        self.direct
+            && self
+                .latest_compatible
+                .as_ref()
+                .is_some_and(|compatible| *compatible != self.latest_version)
+    }
+
+    /// The chain pulling in a transitive dependency, e.g. `python > libsqlite`
+    pub fn required_by_chain(&self) -> Option<String> {
+        (!self.required_by.is_empty()).then(|| self.required_by.join(" > "))
+    }
+
+    /// What line output appends after the versions, other than the
+    /// compatible version, one note each: the table has no column for them
+    pub fn notes(&self, locale: &Locale) -> Vec<String> {
+        let mut notes = Vec::new();
+        if let Some(chain) = self.required_by_chain() {
+            notes.push(format!("required by {}", chain));
+        }
+        if let Some(ref reason) = self.unsolvable {
+            notes.push(format!("unsolvable: {}", reason));
+        }
+        if self.exceeds_max_lag {
+            if let Some(age) = self.installed_age_days {
+                notes.push(format!("installed version is {} days old", age));
+            }
+        }
+        if let Some(delta) = self.size_delta.filter(|delta| *delta != 0) {
+            notes.push(locale.format_size_delta(delta));
+        }
+        if !self.activation_scripts.is_empty() {
+            notes.push(format!(
+                "activation: {}",
+                self.activation_scripts.join(", ")
+            ));
+        }
+        if !self.risk_reasons.is_empty() {
+            notes.push(format!("risk: {}", self.risk_reasons.join(", ")));
+        }
+        match self.merge {
+            Some(MergeDecision::ReviewRequired) if !self.merge_reasons.is_empty() => {
+                notes.push(format!(
+                    "{}: {}",
+                    MergeDecision::ReviewRequired,
+                    self.merge_reasons.join(", ")
+                ))
+            }
+            Some(decision) => notes.push(decision.to_string()),
+            None => {}
+        }
+        if let Some(ref release_notes) = self.release_notes {
+            notes.push(format!("release notes: {}", release_notes));
+        }
+        notes
+    }
+}
+
+impl PackageUpdate {
+    /// A one-line summary with sizes formatted for `locale`
+    pub fn describe(&self, locale: &Locale) -> String {
+        let mut out = format!(
+            "{}: {} -> {}",
+            annotated(&self.label(), &self.annotations),
+            self.installed_version,
+            self.latest_version
+        );
+        if let Some(chain) = self.required_by_chain() {
+            let _ = write!(out, " (required by {})", chain);
+        }
+        if let Some(ref compatible) = self.latest_compatible {
+            if *compatible != self.latest_version {
+                let _ = write!(out, " (requirement allows up to {})", compatible);
+            }
+        }
+        if let Some(ref reason) = self.unsolvable {
+            let _ = write!(out, " (unsolvable: {})", reason);
+        }
+        if self.exceeds_max_lag {
+            if let Some(age) = self.installed_age_days {
+                let _ = write!(out, " (installed version is {} days old)", age);
+            }
+        }
+        if let Some(delta) = self.size_delta.filter(|delta| *delta != 0) {
+            let _ = write!(out, " ({})", locale.format_size_delta(delta));
+        }
+        if !self.activation_scripts.is_empty() {
+            let _ = write!(out, " (activation: {})", self.activation_scripts.join(", "));
+        }
+        if !self.risk_reasons.is_empty() {
+            let _ = write!(out, " [{}]", self.risk_reasons.join(", "));
+        }
+        match self.merge {
+            Some(MergeDecision::ReviewRequired) if !self.merge_reasons.is_empty() => {
+                let _ = write!(
+                    out,
+                    " ({}: {})",
+                    MergeDecision::ReviewRequired,
+                    self.merge_reasons.join(", ")
+                );
+            }
+            Some(decision) => {
+                let _ = write!(out, " ({})", decision);
+            }
+            None => {}
+        }
+        if let Some(ref release_notes) = self.release_notes {
+            let _ = write!(out, " (release notes: {})", release_notes);
+        }
+        out
+    }
+}
+
+impl std::fmt::Display for PackageUpdate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.describe(&Locale::default()))
+    }
+}
+
+/// A name followed by its annotations, e.g. `torch {team=ml}`
+pub fn annotated(name: &str, annotation: &Annotation) -> String {
+    if annotation.is_empty() {
+        name.to_string()
+    } else {
+        format!("{} {{{}}}", name, annotations::describe(annotation, ", "))
+    }
+}
+
+/// Updates per platform, ordered by platform name so reports are stable
+pub type PlatformUpdates = BTreeMap<String, Vec<PackageUpdate>>;
+
+/// Whether a locked version can still be downloaded from its channel or index
+#[derive(Debug, Serialize, Clone, Deserialize, PartialEq, Eq)]
+#[serde(tag = "status", rename_all = "lowercase")]
+pub enum LockedStatus {
+    Available,
+    /// Every file of the locked PyPI release has been yanked
+    Yanked {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        reason: Option<String>,
+    },
+    /// The locked version no longer appears upstream
+    Removed,
+}
+
+impl std::fmt::Display for LockedStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LockedStatus::Available => write!(f, "available"),
+            LockedStatus::Yanked {
+                reason: Some(reason),
+            } => write!(f, "yanked ({})", reason),
+            LockedStatus::Yanked { reason: None } => write!(f, "yanked"),
+            LockedStatus::Removed => write!(f, "removed upstream"),
+        }
+    }
+}
+
+/// A locked package whose version is no longer available upstream
+#[derive(Debug, Serialize, Clone, Deserialize, PartialEq, Eq)]
+pub struct LockedIssue {
+    pub name: String,
+    pub version: String,
+    pub platform: String,
+    #[serde(flatten)]
+    pub status: LockedStatus,
+    /// Metadata from `[tool.pixi-outdated.annotations]`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub annotations: Annotation,
+}
+
+/// What checking a locked package against its channel or index found
+#[derive(Debug, Serialize, Clone, Deserialize, PartialEq, Eq)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum CheckStatus {
+    UpToDate,
+    Newer,
+    /// The channel or index has no versions of the package
+    NotFound,
+    /// The channel or index couldn't be queried
+    Error {
+        message: String,
+    },
+    /// The lockfile records no channel or index to query, e.g. for a
+    /// package built from a local path
+    NoSource,
+}
+
+impl std::fmt::Display for CheckStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CheckStatus::UpToDate => write!(f, "up to date"),
+            CheckStatus::Newer => write!(f, "newer version available"),
+            CheckStatus::NotFound => write!(f, "not found upstream"),
+            CheckStatus::Error { message } => write!(f, "query failed: {}", message),
+            CheckStatus::NoSource => write!(f, "no channel or index locked"),
+        }
+    }
+}
+
+impl CheckStatus {
+    /// The status name used in JSON and CSV output
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CheckStatus::UpToDate => "up_to_date",
+            CheckStatus::Newer => "newer",
+            CheckStatus::NotFound => "not_found",
+            CheckStatus::Error { .. } => "error",
+            CheckStatus::NoSource => "no_source",
+        }
+    }
+}
+
+/// A locked package whose latest version couldn't be determined, so it is
+/// neither reported as outdated nor known to be up to date
+#[derive(Debug, Serialize, Clone, Deserialize, PartialEq, Eq)]
+pub struct UncheckedPackage {
+    pub name: String,
+    pub kind: PackageKind,
+    pub version: String,
+    pub platform: String,
+    #[serde(flatten)]
+    pub status: CheckStatus,
+    /// Metadata from `[tool.pixi-outdated.annotations]`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub annotations: Annotation,
+}
+
+impl std::fmt::Display for UncheckedPackage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} ({}): {} ({})",
+            annotated(&self.name, &self.annotations),
+            self.kind,
+            self.version,
+            self.status
+        )
+    }
+}
+
+/// A locked conda package from a channel that is not in `allowed-channels`
+#[derive(Debug, Serialize, Clone, Deserialize, PartialEq, Eq)]
+pub struct DisallowedPackage {
+    pub name: String,
+    pub version: String,
+    pub platform: String,
+    pub channel: String,
+}
+
+impl std::fmt::Display for DisallowedPackage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {} from {}", self.name, self.version, self.channel)
+    }
+}
+
+/// A package whose newest version is published under a license that isn't
+/// allowed, so an older version was considered its latest instead
+#[derive(Debug, Serialize, Clone, Deserialize, PartialEq, Eq)]
+pub struct HeldBackPackage {
+    pub name: String,
+    pub platform: String,
+    pub installed_version: String,
+    pub newest_version: String,
+    pub license: String,
+    /// The newest version with an allowed or unknown license, if any
+    pub latest_allowed: Option<String>,
+}
+
+impl std::fmt::Display for HeldBackPackage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: {} is {}, ",
+            self.name, self.newest_version, self.license
+        )?;
+        match self.latest_allowed {
+            Some(ref allowed) if *allowed != self.installed_version => {
+                write!(f, "latest allowed is {}", allowed)
+            }
+            _ => write!(f, "no allowed update from {}", self.installed_version),
+        }
+    }
+}
+
+/// A locked conda package that was installed from a label other than `main`,
+/// such as `rc` or `dev`
+#[derive(Debug, Serialize, Clone, Deserialize, PartialEq, Eq)]
+pub struct LabeledPackage {
+    pub name: String,
+    pub version: String,
+    pub platform: String,
+    pub label: String,
+    /// The latest release on the channel's main label
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stable_version: Option<String>,
+    /// Whether the stable release is at least as new as the installed version
+    pub superseded: bool,
+}
+
+impl std::fmt::Display for LabeledPackage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {} (label: {})", self.name, self.version, self.label)?;
+        match (&self.stable_version, self.superseded) {
+            (Some(stable), true) => write!(f, ", superseded by stable {}", stable),
+            _ => write!(f, ", no stable release supersedes it yet"),
+        }
+    }
+}
+
+/// A locked conda package with a newer version in another listed channel,
+/// one that channel priority passes over
+#[derive(Debug, Serialize, Clone, Deserialize, PartialEq, Eq)]
+pub struct OtherChannelRelease {
+    pub name: String,
+    pub platform: String,
+    pub installed_version: String,
+    /// The latest version the environment's channels give by priority
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latest_version: Option<String>,
+    /// The channel the newest version lives in
+    pub channel: String,
+    pub version: String,
+}
+
+impl std::fmt::Display for OtherChannelRelease {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: {} installed, {} on {}",
+            self.name, self.installed_version, self.version, self.channel
+        )?;
+        if let Some(ref latest) = self.latest_version {
+            write!(f, " (channel priority picks {})", latest)?;
+        }
+        Ok(())
+    }
+}
+
+/// Overall outcome of a run, including the degenerate cases where nothing
+/// could be checked. Each status maps to a distinct process exit code.
+#[derive(Debug, Serialize, Clone, Copy, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RunStatus {
+    /// Packages were checked normally
+    #[default]
+    Ok,
+    /// The environment has no locked packages for any platform
+    EmptyEnvironment,
+    /// Packages are locked, but every one of them was filtered out or skipped
+    AllPackagesSkipped,
+    /// The workspace has no lockfile
+    LockfileMissing,
+    /// `--changed-since` found no changed dependencies to check
+    NoChanges,
+    /// The check itself failed, e.g. for one workspace of `--recursive`
+    Failed,
+}
+
+/// Exit code of a `--fail-on-direct` run that found outdated direct dependencies
+pub const OUTDATED_DIRECT_EXIT_CODE: u8 = 2;
+
+/// Exit code of a run that found locked packages from channels outside
+/// `allowed-channels`
+pub const DISALLOWED_CHANNEL_EXIT_CODE: u8 = 6;
+
+impl RunStatus {
+    pub fn exit_code(self) -> u8 {
+        match self {
+            RunStatus::Ok => 0,
+            RunStatus::EmptyEnvironment => 3,
+            RunStatus::AllPackagesSkipped => 4,
+            RunStatus::LockfileMissing => 5,
+            // Nothing to check is what a pull request gate expects for a
+            // change that doesn't touch dependencies
+            RunStatus::NoChanges => 0,
+            RunStatus::Failed => 1,
+        }
+    }
+
+    pub fn message(self) -> &'static str {
+        match self {
+            RunStatus::Ok => "Checked packages",
+            RunStatus::EmptyEnvironment => "No packages found for any platform",
+            RunStatus::AllPackagesSkipped => "All packages were filtered out or skipped",
+            RunStatus::LockfileMissing => "No lockfile found, run `pixi lock` to create one",
+            RunStatus::NoChanges => "No dependencies changed since the given revision",
+            RunStatus::Failed => "The check failed",
+        }
+    }
+}
+
+/// Where a report came from, so an archived report describes itself
+#[derive(Debug, Serialize, Clone, Deserialize, PartialEq, Eq)]
+pub struct ReportMetadata {
+    /// The version of pixi-outdated that wrote the report
+    pub tool_version: String,
+    pub generated_at: DateTime<Utc>,
+    /// The manifest of the checked workspace, if it has one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub manifest: Option<String>,
+    /// SHA-256 of the lockfile that was checked, if there was one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lockfile_sha256: Option<String>,
+    pub environment: String,
+    /// The platforms that were checked
+    pub platforms: Vec<String>,
+}
+
+impl ReportMetadata {
+    /// Describe a run of `environment`, hashing the lockfile if it exists
+    pub fn new(
+        environment: &str,
+        platforms: &[String],
+        manifest: Option<&Path>,
+        lockfile: Option<&Path>,
+        generated_at: DateTime<Utc>,
+    ) -> Result<Self> {
+        let lockfile_sha256 = match lockfile.filter(|path| path.is_file()) {
+            Some(path) => {
+                let content =
+                    fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+                let digest = Sha256::digest(content);
+                Some(digest.iter().map(|byte| format!("{:02x}", byte)).collect())
+            }
+            None => None,
+        };
+        Ok(Self {
+            tool_version: env!("CARGO_PKG_VERSION").to_string(),
+            generated_at,
+            manifest: manifest.map(|path| path.display().to_string()),
+            lockfile_sha256,
+            environment: environment.to_string(),
+            platforms: platforms.to_vec(),
+        })
+    }
+
+    /// A one-line description, for text and markdown output
+    pub fn summary(&self) -> String {
+        let mut summary = format!(
+            "Generated by pixi-outdated {} at {} for environment {}",
+            self.tool_version,
+            self.generated_at.to_rfc3339_opts(SecondsFormat::Secs, true),
+            self.environment
+        );
+        if !self.platforms.is_empty() {
+            let _ = write!(summary, " ({})", self.platforms.join(", "));
+        }
+        if let Some(ref manifest) = self.manifest {
+            let _ = write!(summary, " of {}", manifest);
+        }
+        if let Some(ref sha256) = self.lockfile_sha256 {
+            let _ = write!(summary, ", lockfile sha256 {}", sha256);
+        }
+        summary
+    }
+}
+
+/// The version of the JSON and YAML report schema, written as
+/// `schema_version`. Version 1 was the bare map of updates per platform
+/// that reports had before they gained other sections.
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// A structured report with the schema version it follows
+#[derive(Serialize)]
+struct Versioned<'a, T> {
+    schema_version: u32,
+    #[serde(flatten)]
+    report: &'a T,
+}
+
+impl<'a, T> Versioned<'a, T> {
+    fn new(report: &'a T) -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            report,
+        }
+    }
+}
+
+/// The full result of a run
+#[derive(Debug, Serialize, Clone, Default, Deserialize, PartialEq, Eq)]
+pub struct Report {
+    /// Where the report came from; left out of the content hash
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<ReportMetadata>,
+    /// Whether the run checked anything at all
+    #[serde(default)]
+    pub status: RunStatus,
+    /// Why the check failed, with [`RunStatus::Failed`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Available updates, grouped by platform
+    pub platforms: PlatformUpdates,
+    /// Locked versions that were yanked or removed upstream
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub locked_issues: Vec<LockedIssue>,
+    /// Locked packages that were not found upstream or whose query failed
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub unchecked: Vec<UncheckedPackage>,
+    /// Locked conda packages from channels that are not allowed
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub disallowed_channels: Vec<DisallowedPackage>,
+    /// Locked conda packages from channels the manifest no longer lists,
+    /// which the next relock will move to another channel or fail to resolve
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub unlisted_channels: Vec<DisallowedPackage>,
+    /// Locked conda packages installed from non-main labels
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub labeled_packages: Vec<LabeledPackage>,
+    /// Locked conda packages with a newer version in a listed channel that
+    /// channel priority passes over
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub newer_in_other_channels: Vec<OtherChannelRelease>,
+    /// Updates whose newest version has a license outside `allowed-licenses`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub held_back_by_license: Vec<HeldBackPackage>,
+    /// What updating the workspace interpreter would involve, per platform
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub python_upgrades: Vec<PythonUpgrade>,
+    /// Locked versions with known vulnerabilities, with `--audit`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub vulnerabilities: Vec<VulnerablePackage>,
+    /// Why `--audit` couldn't look up advisories, e.g. because OSV was
+    /// unreachable; the rest of the report is unaffected
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub audit_unavailable: Option<String>,
+    /// Locked conda packages `--audit` couldn't look up, since OSV files
+    /// their advisories under no known package
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub unaudited: Vec<String>,
+    /// The pixi commands that apply the updates, with `--suggest-commands`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub commands: Vec<String>,
+    /// What keeps the package given to `--explain` from its latest version
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub explanations: Vec<Explanation>,
+    /// How far behind the updates' release dates put the environment, see
+    /// [`Libyears`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub libyears: Option<Libyears>,
+    /// Health statistics for every channel and index that was consulted
+    #[serde(default)]
+    pub sources: Vec<SourceStats>,
+    /// The channel subdirs whose repodata was fetched ahead of the queries
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub repodata: Vec<RepodataFetch>,
+    /// Metadata downloaded during the run
+    #[serde(default)]
+    pub network: NetworkUsage,
+    /// SHA-256 of the findings, see [`Report::compute_content_hash`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_hash: Option<String>,
+    /// Every locked package per platform, including those left out of the
+    /// check, for the audit and the SBOM. Not part of the serialized report.
+    #[serde(skip)]
+    pub locked: BTreeMap<String, Vec<PixiPackage>>,
+    /// The packages of `locked` the run checked, as (platform, kind, name).
+    /// Not part of the serialized report.
+    #[serde(skip)]
+    pub checked: BTreeSet<(String, PackageKind, String)>,
+}
+
+/// Exit code of a `--format oneline` run that found updates or locked
+/// versions that were yanked or removed, so a prompt can color on it alone
+pub const OUTDATED_EXIT_CODE: u8 = 8;
+
+/// Exit code of an `--audit` run that found locked versions with known
+/// vulnerabilities
+pub const VULNERABLE_EXIT_CODE: u8 = 9;
+
+impl Report {
+    /// The status message, with the error of a failed check
+    pub fn status_message(&self) -> String {
+        match &self.error {
+            Some(error) => format!("{}: {}", self.status.message(), error),
+            None => self.status.message().to_string(),
+        }
+    }
+
+    /// The report of a check that failed with `error`
+    pub fn failed(error: &anyhow::Error) -> Self {
+        Report {
+            status: RunStatus::Failed,
+            error: Some(format!("{:#}", error)),
+            ..Report::default()
+        }
+    }
+
+    /// Hash the findings of the run, leaving out what differs between
+    /// otherwise identical runs: the metadata, source and repodata timings,
+    /// download sizes and the age of installed versions. Two runs with the same hash
+    /// found the same updates and issues.
+    pub fn compute_content_hash(&self) -> Result<String> {
+        let mut normalized = self.clone();
+        normalized.metadata = None;
+        normalized.sources.clear();
+        normalized.repodata.clear();
+        normalized.network = NetworkUsage::default();
+        normalized.content_hash = None;
+        for update in normalized.platforms.values_mut().flatten() {
+            update.installed_age_days = None;
+            update.newer_available_days = None;
+        }
+
+        let digest = Sha256::digest(serde_json::to_vec(&normalized)?);
+        Ok(digest.iter().map(|byte| format!("{:02x}", byte)).collect())
+    }
+
+    /// The total change in download size of the updates on each platform
+    /// whose sizes are known
+    pub fn size_delta_totals(&self) -> BTreeMap<String, i64> {
+        self.platforms
+            .iter()
+            .filter_map(|(platform, updates)| {
+                let deltas: Vec<i64> = updates.iter().filter_map(|u| u.size_delta).collect();
+                (!deltas.is_empty()).then(|| (platform.clone(), deltas.iter().sum()))
+            })
+            .collect()
+    }
+
+    /// A copy of the report without the given updates, e.g. those already
+    /// listed for every environment
+    pub fn without_updates(&self, updates: &PlatformUpdates) -> Report {
+        let mut report = self.clone();
+        for (platform, platform_updates) in &mut report.platforms {
+            if let Some(removed) = updates.get(platform) {
+                platform_updates
+                    .retain(|update| !removed.iter().any(|other| other.is_same_update(update)));
+            }
+        }
+        report
+    }
+
+    /// Attach the metadata configured for each package to its entries
+    pub fn annotate(&mut self, annotations: &Annotations) {
+        if annotations.is_empty() {
+            return;
+        }
+        for update in self.platforms.values_mut().flatten() {
+            update.annotations = annotations.get(&update.name);
+        }
+        for issue in &mut self.locked_issues {
+            issue.annotations = annotations.get(&issue.name);
+        }
+        for unchecked in &mut self.unchecked {
+            unchecked.annotations = annotations.get(&unchecked.name);
+        }
+    }
+
+    /// Whether the run checked a locked package on a platform, rather than
+    /// leaving it out with a filter
+    pub fn was_checked(&self, platform: &str, package: &PixiPackage) -> bool {
+        self.checked
+            .contains(&(platform.to_string(), package.kind, package.name.clone()))
+    }
+
+    /// The checked packages per platform that are neither outdated nor
+    /// unchecked. Only known for reports of this run, since the locked
+    /// packages aren't serialized.
+    pub fn up_to_date(&self) -> BTreeMap<&str, Vec<&PixiPackage>> {
+        self.locked
+            .iter()
+            .map(|(platform, packages)| {
+                let updates = self.platforms.get(platform);
+                let up_to_date: Vec<&PixiPackage> = packages
+                    .iter()
+                    .filter(|package| self.was_checked(platform, package))
+                    .filter(|package| {
+                        !updates.is_some_and(|updates| {
+                            updates.iter().any(|update| {
+                                update.name == package.name
+                                    && update.kind.map_or(true, |kind| kind == package.kind)
+                            })
+                        })
+                    })
+                    .filter(|package| {
+                        !self.unchecked.iter().any(|unchecked| {
+                            unchecked.platform == *platform
+                                && unchecked.name == package.name
+                                && unchecked.kind == package.kind
+                        })
+                    })
+                    .collect();
+                (platform.as_str(), up_to_date)
+            })
+            .filter(|(_, packages)| !packages.is_empty())
+            .collect()
+    }
+
+    /// Names of the direct dependencies with an update on any platform
+    pub fn outdated_direct(&self) -> BTreeSet<&str> {
+        self.platforms
+            .values()
+            .flatten()
+            .filter(|update| update.direct)
+            .map(|update| update.name.as_str())
+            .collect()
+    }
+}
+
+/// The formats a report can be printed in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum OutputFormat {
+    /// Human readable text (a table on terminals)
+    #[default]
+    Text,
+    Json,
+    Yaml,
+    /// One row per update and platform, for spreadsheets
+    Csv,
+    /// A GitHub-flavored summary, for PR comments
+    Markdown,
+    /// GitHub Actions workflow commands, which annotate the manifest
+    Github,
+    /// A single line of counts by severity, for shell prompts
+    Oneline,
+    /// A CycloneDX BOM of the locked packages, with their latest versions
+    Sbom,
+}
+
+/// Render the report in a structured format, or `None` for text, which is
+/// printed incrementally by the caller
+pub fn render(report: &Report, format: OutputFormat, locale: &Locale) -> Result<Option<String>> {
+    Ok(match format {
+        OutputFormat::Text => None,
+        OutputFormat::Json => Some(render_json(report)?),
+        OutputFormat::Yaml => Some(render_yaml(report)?),
+        OutputFormat::Csv => Some(render_csv(report)?),
+        OutputFormat::Markdown => Some(render_markdown(report, locale)),
+        OutputFormat::Github => Some(crate::github::render_github(report)),
+        OutputFormat::Oneline => Some(crate::oneline::render_oneline(
+            report,
+            crate::oneline::use_color(),
+        )),
+        OutputFormat::Sbom => Some(crate::sbom::render_sbom(report)?),
+    })
+}
+
+/// The reports of every environment checked with `--all-environments`
+#[derive(Debug, Serialize, Clone, Default, Deserialize, PartialEq, Eq)]
+pub struct EnvironmentReports {
+    pub environments: BTreeMap<String, Report>,
+    /// Packages the environments lock at different versions
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub version_spreads: Vec<VersionSpread>,
+}
+
+impl EnvironmentReports {
+    /// Render the reports in a structured format, or `None` for text, which
+    /// is printed per environment by the caller
+    pub fn render(&self, format: OutputFormat, locale: &Locale) -> Result<Option<String>> {
+        Ok(match format {
+            OutputFormat::Text => None,
+            OutputFormat::Json => Some(serde_json::to_string_pretty(&Versioned::new(self))?),
+            OutputFormat::Yaml => Some(serde_yaml::to_string(&Versioned::new(self))?),
+            OutputFormat::Csv => Some(write_csv(
+                "environment",
+                self.environments
+                    .iter()
+                    .map(|(name, report)| (Some(name.as_str()), report)),
+            )?),
+            OutputFormat::Markdown => Some(
+                self.environments
+                    .iter()
+                    .map(|(name, report)| {
+                        render_markdown(report, locale).replacen(
+                            "## Outdated packages",
+                            &format!("## Outdated packages in `{}`", name),
+                            1,
+                        )
+                    })
+                    .chain(
+                        (!self.version_spreads.is_empty())
+                            .then(|| crate::spread::render_markdown(&self.version_spreads)),
+                    )
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+            ),
+            OutputFormat::Github => Some(
+                self.environments
+                    .values()
+                    .map(crate::github::render_github)
+                    .collect(),
+            ),
+            OutputFormat::Oneline => Some(
+                crate::oneline::OnelineSummary::from_reports(self.environments.values())
+                    .render(crate::oneline::use_color()),
+            ),
+            OutputFormat::Sbom => Some(crate::sbom::render_environments(
+                self.environments
+                    .iter()
+                    .map(|(name, report)| (Some(name.as_str()), report)),
+            )?),
+        })
+    }
+
+    /// The updates every environment has on a platform, so they can be
+    /// listed once. Empty unless there are several environments.
+    pub fn shared_updates(&self) -> PlatformUpdates {
+        let mut reports = self.environments.values();
+        let Some(first) = reports.next().filter(|_| self.environments.len() > 1) else {
+            return PlatformUpdates::new();
+        };
+
+        first
+            .platforms
+            .iter()
+            .filter_map(|(platform, updates)| {
+                let shared: Vec<PackageUpdate> = updates
+                    .iter()
+                    .filter(|update| {
+                        reports.clone().all(|report| {
+                            report.platforms.get(platform).is_some_and(|updates| {
+                                updates.iter().any(|other| other.is_same_update(update))
+                            })
+                        })
+                    })
+                    .cloned()
+                    .collect();
+                (!shared.is_empty()).then(|| (platform.clone(), shared))
+            })
+            .collect()
+    }
+
+    /// The exit code of the whole run: a policy violation in any environment
+    /// fails it, and it only counts as empty if every environment was
+    pub fn exit_code(&self) -> u8 {
+        combined_exit_code(self.environments.values())
+    }
+}
+
+/// The reports of every workspace checked with `--recursive`, keyed by the
+/// directory of the workspace relative to the scanned root
+#[derive(Debug, Serialize, Clone, Default, Deserialize, PartialEq, Eq)]
+pub struct ProjectReports {
+    pub projects: BTreeMap<String, Report>,
+}
+
+impl ProjectReports {
+    /// Render the reports in a structured format, or `None` for text, which
+    /// is printed per project by the caller
+    pub fn render(&self, format: OutputFormat, locale: &Locale) -> Result<Option<String>> {
+        let named = || {
+            self.projects
+                .iter()
+                .map(|(name, report)| (Some(name.as_str()), report))
+        };
+        Ok(match format {
+            OutputFormat::Text => None,
+            OutputFormat::Json => Some(serde_json::to_string_pretty(&Versioned::new(self))?),
+            OutputFormat::Yaml => Some(serde_yaml::to_string(&Versioned::new(self))?),
+            OutputFormat::Csv => Some(write_csv("project", named())?),
+            OutputFormat::Markdown => Some(
+                self.projects
+                    .iter()
+                    .map(|(name, report)| {
+                        render_markdown(report, locale).replacen(
+                            "## Outdated packages",
+                            &format!("## Outdated packages in `{}`", name),
+                            1,
+                        )
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+            ),
+            OutputFormat::Github => Some(
+                self.projects
+                    .values()
+                    .map(crate::github::render_github)
+                    .collect(),
+            ),
+            OutputFormat::Oneline => Some(
+                crate::oneline::OnelineSummary::from_reports(self.projects.values())
+                    .render(crate::oneline::use_color()),
+            ),
+            OutputFormat::Sbom => Some(crate::sbom::render_environments(named())?),
+        })
+    }
+
+    /// The exit code of the whole run, combined like that of several
+    /// environments
+    pub fn exit_code(&self) -> u8 {
+        combined_exit_code(self.projects.values())
+    }
+}
+
+/// A policy violation in any report fails the run, and it only counts as
+/// empty if every report was
+fn combined_exit_code<'a>(reports: impl IntoIterator<Item = &'a Report>) -> u8 {
+    let reports: Vec<&Report> = reports.into_iter().collect();
+    if reports
+        .iter()
+        .any(|report| !report.disallowed_channels.is_empty())
+    {
+        DISALLOWED_CHANNEL_EXIT_CODE
+    } else if reports
+        .iter()
+        .any(|report| !report.vulnerabilities.is_empty())
+    {
+        VULNERABLE_EXIT_CODE
+    } else if reports
+        .iter()
+        .any(|report| report.status == RunStatus::Failed)
+    {
+        RunStatus::Failed.exit_code()
+    } else if reports.iter().any(|report| report.status.exit_code() == 0) {
+        0
+    } else {
+        reports
+            .first()
+            .map_or(0, |report| report.status.exit_code())
+    }
+}
+
+/// Render the report as pretty-printed JSON
+pub fn render_json(report: &Report) -> Result<String> {
+    Ok(serde_json::to_string_pretty(&Versioned::new(report))?)
+}
+
+/// Render the report as YAML, with the same shape as the JSON output
+pub fn render_yaml(report: &Report) -> Result<String> {
+    Ok(serde_yaml::to_string(&Versioned::new(report))?)
+}
+
+/// Render the updates as CSV with one row per package and platform
+pub fn render_csv(report: &Report) -> Result<String> {
+    write_csv("environment", [(None, report)])
+}
+
+/// Write the rows of each report, with a leading column of the given name
+/// if the reports are named, e.g. after their environments
+fn write_csv<'a>(
+    column: &str,
+    reports: impl IntoIterator<Item = (Option<&'a str>, &'a Report)>,
+) -> Result<String> {
+    let mut reports = reports.into_iter().peekable();
+    let named = reports
+        .peek()
+        .is_some_and(|(environment, _)| environment.is_some());
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(named.then_some(column).into_iter().chain([
+        "platform",
+        "name",
+        "kind",
+        "channel",
+        "installed_version",
+        "latest_version",
+        "installed_age_days",
+        "exceeds_max_lag",
+        "risk",
+        "severity",
+        "latest_compatible",
+        "status",
+        "merge",
+        "annotations",
+        "required_by",
+    ]))?;
+
+    for (environment, report) in reports {
+        for (platform, updates) in &report.platforms {
+            for update in updates {
+                writer.write_record(
+                    environment.into_iter().chain([
+                        platform.as_str(),
+                        &update.name,
+                        &update.kind.map(|kind| kind.to_string()).unwrap_or_default(),
+                        update.channel.as_deref().unwrap_or_default(),
+                        &update.installed_version,
+                        &update.latest_version,
+                        &update
+                            .installed_age_days
+                            .map(|age| age.to_string())
+                            .unwrap_or_default(),
+                        &update.exceeds_max_lag.to_string(),
+                        update.risk.map_or("", RiskGroup::as_str),
+                        &update
+                            .severity
+                            .map(|severity| severity.to_string())
+                            .unwrap_or_default(),
+                        update.latest_compatible.as_deref().unwrap_or_default(),
+                        CheckStatus::Newer.as_str(),
+                        update.merge.map_or("", MergeDecision::as_str),
+                        &annotations::describe(&update.annotations, ";"),
+                        &update.required_by_chain().unwrap_or_default(),
+                    ]),
+                )?;
+            }
+        }
+
+        // Packages without a latest version get a row too, so a missing row
+        // always means the package is up to date
+        for unchecked in &report.unchecked {
+            writer.write_record(environment.into_iter().chain([
+                unchecked.platform.as_str(),
+                &unchecked.name,
+                &unchecked.kind.to_string(),
+                "",
+                &unchecked.version,
+                "",
+                "",
+                "false",
+                "",
+                "",
+                "",
+                unchecked.status.as_str(),
+                "",
+                &annotations::describe(&unchecked.annotations, ";"),
+                "",
+            ]))?;
+        }
+    }
+
+    let bytes = writer
+        .into_inner()
+        .map_err(|e| anyhow::anyhow!("Failed to write CSV: {}", e))?;
+    String::from_utf8(bytes).context("CSV output was not valid UTF-8")
+}
+
+/// Render the report as a GitHub-flavored markdown summary
+pub fn render_markdown(report: &Report, locale: &Locale) -> String {
+    let mut out = String::from("## Outdated packages\n");
+    if let Some(ref metadata) = report.metadata {
+        let _ = writeln!(out, "\n<sub>{}</sub>", metadata.summary());
+    }
+
+    if report.status != RunStatus::Ok {
+        let _ = writeln!(out, "\n{}.", report.status_message());
+        return out;
+    }
+
+    if !report.locked_issues.is_empty() {
+        out.push_str("\n### Yanked or removed upstream\n\n");
+        out.push_str("| Package | Locked | Platform | Status |\n");
+        out.push_str("| --- | --- | --- | --- |\n");
+        for issue in &report.locked_issues {
+            let _ = writeln!(
+                out,
+                "| {} | {} | {} | {} |",
+                annotated(&issue.name, &issue.annotations),
+                issue.version,
+                issue.platform,
+                issue.status
+            );
+        }
+    }
+
+    if !report.labeled_packages.is_empty() {
+        out.push_str("\n### Installed from non-main labels\n\n");
+        out.push_str("| Package | Locked | Label | Platform | Stable |\n");
+        out.push_str("| --- | --- | --- | --- | --- |\n");
+        for labeled in &report.labeled_packages {
+            let stable = match (&labeled.stable_version, labeled.superseded) {
+                (Some(stable), true) => format!("{} (supersedes)", stable),
+                (Some(stable), false) => stable.clone(),
+                (None, _) => "-".to_string(),
+            };
+            let _ = writeln!(
+                out,
+                "| {} | {} | {} | {} | {} |",
+                labeled.name, labeled.version, labeled.label, labeled.platform, stable
+            );
+        }
+    }
+
+    if !report.python_upgrades.is_empty() {
+        out.push_str("\n### Python\n\n");
+        out.push_str("| Platform | Installed | Latest | Allowed | No wheels for latest |\n");
+        out.push_str("| --- | --- | --- | --- | --- |\n");
+        for upgrade in &report.python_upgrades {
+            let without_wheels = if upgrade.without_wheels.is_empty() {
+                "-".to_string()
+            } else {
+                upgrade.without_wheels.join(", ")
+            };
+            let _ = writeln!(
+                out,
+                "| {} | {} | {} | {} | {} |",
+                upgrade.platform,
+                upgrade.installed_version,
+                upgrade.latest_version,
+                if upgrade.allowed { "yes" } else { "no" },
+                without_wheels
+            );
+        }
+    }
+
+    if let Some(ref reason) = report.audit_unavailable {
+        let _ = writeln!(
+            out,
+            "\n### Known vulnerabilities\n\nThe audit was unavailable: {}",
+            reason
+        );
+    } else if !report.vulnerabilities.is_empty() {
+        out.push_str("\n### Known vulnerabilities\n\n");
+        out.push_str("| Package | Locked | Vulnerability | Fixed in | Fixed by latest |\n");
+        out.push_str("| --- | --- | --- | --- | --- |\n");
+        for package in &report.vulnerabilities {
+            for vulnerability in &package.vulnerabilities {
+                let fixed_in = if vulnerability.fixed_in.is_empty() {
+                    "-".to_string()
+                } else {
+                    vulnerability.fixed_in.join(", ")
+                };
+                let _ = writeln!(
+                    out,
+                    "| {} | {} | {} | {} | {} |",
+                    package.name,
+                    package.version,
+                    vulnerability.id,
+                    fixed_in,
+                    if vulnerability.fixed_by_latest {
+                        "yes"
+                    } else {
+                        "no"
+                    }
+                );
+            }
+        }
+    }
+    if !report.unaudited.is_empty() {
+        let _ = writeln!(
+            out,
+            "\nNot audited (no OSV package known, map them in `osv-packages`): {}",
+            report.unaudited.join(", ")
+        );
+    }
+
+    if !report.held_back_by_license.is_empty() {
+        out.push_str("\n### Held back by license\n\n");
+        out.push_str("| Package | Installed | Newest | License | Latest allowed | Platform |\n");
+        out.push_str("| --- | --- | --- | --- | --- | --- |\n");
+        for held_back in &report.held_back_by_license {
+            let _ = writeln!(
+                out,
+                "| {} | {} | {} | {} | {} | {} |",
+                held_back.name,
+                held_back.installed_version,
+                held_back.newest_version,
+                held_back.license,
+                held_back.latest_allowed.as_deref().unwrap_or("-"),
+                held_back.platform
+            );
+        }
+    }
+
+    if !report.unchecked.is_empty() {
+        out.push_str("\n### Not checked\n\n");
+        out.push_str("| Package | Locked | Platform | Status |\n");
+        out.push_str("| --- | --- | --- | --- |\n");
+        for unchecked in &report.unchecked {
+            let _ = writeln!(
+                out,
+                "| {} | {} | {} | {} |",
+                annotated(&unchecked.name, &unchecked.annotations),
+                unchecked.version,
+                unchecked.platform,
+                unchecked.status
+            );
+        }
+    }
+
+    if !report.disallowed_channels.is_empty() {
+        out.push_str("\n### Not from an allowed channel\n\n");
+        out.push_str("| Package | Locked | Platform | Channel |\n");
+        out.push_str("| --- | --- | --- | --- |\n");
+        for disallowed in &report.disallowed_channels {
+            let _ = writeln!(
+                out,
+                "| {} | {} | {} | {} |",
+                disallowed.name, disallowed.version, disallowed.platform, disallowed.channel
+            );
+        }
+    }
+
+    if !report.unlisted_channels.is_empty() {
+        out.push_str("\n### Locked from channels the manifest no longer lists\n\n");
+        out.push_str("| Package | Locked | Platform | Channel |\n");
+        out.push_str("| --- | --- | --- | --- |\n");
+        for unlisted in &report.unlisted_channels {
+            let _ = writeln!(
+                out,
+                "| {} | {} | {} | {} |",
+                unlisted.name, unlisted.version, unlisted.platform, unlisted.channel
+            );
+        }
+    }
+
+    if !report.newer_in_other_channels.is_empty() {
+        out.push_str("\n### Newer in other channels\n\n");
+        out.push_str("| Package | Locked | Platform | By priority | Newest | Channel |\n");
+        out.push_str("| --- | --- | --- | --- | --- | --- |\n");
+        for release in &report.newer_in_other_channels {
+            let _ = writeln!(
+                out,
+                "| {} | {} | {} | {} | {} | {} |",
+                release.name,
+                release.installed_version,
+                release.platform,
+                release.latest_version.as_deref().unwrap_or("-"),
+                release.version,
+                release.channel
+            );
+        }
+    }
+
+    let updates = &report.platforms;
+    if updates.values().all(|u| u.is_empty()) {
+        if report.unchecked.is_empty() {
+            out.push_str("\nAll packages are up to date.\n");
+        } else {
+            out.push_str("\nAll other packages are up to date.\n");
+        }
+        return out;
+    }
+
+    let size_deltas = report.size_delta_totals();
+    for (platform, platform_updates) in updates {
+        if platform_updates.is_empty() {
+            continue;
+        }
+
+        let _ = writeln!(out, "\n### {}\n", platform);
+        out.push_str("| Package | Installed | Latest | Severity | Channel |\n");
+        out.push_str("| --- | --- | --- | --- | --- |\n");
+        for update in platform_updates {
+            let lag = match update.installed_age_days {
+                Some(age) if update.exceeds_max_lag => format!(" ({} days old)", age),
+                _ => String::new(),
+            };
+            let required_by = match update.required_by_chain() {
+                Some(chain) => format!(" (required by {})", chain),
+                None => String::new(),
+            };
+            let severity = update
+                .severity
+                .map_or_else(|| "-".to_string(), |severity| severity.to_string());
+            let channel = match (update.channel.as_deref(), update.kind) {
+                (Some(channel), _) => crate::output::channel_label(channel),
+                (None, Some(PackageKind::Pypi)) => "PyPI",
+                (None, _) => "-",
+            };
+            let label = annotated(&update.label(), &update.annotations);
+            let label = match update.release_notes {
+                Some(ref release_notes) => format!("[{}]({})", label, release_notes),
+                None => label,
+            };
+            let unsolvable = match update.unsolvable {
+                Some(_) => " (unsolvable)",
+                None => "",
+            };
+            let _ = writeln!(
+                out,
+                "| {}{} | {}{} | {}{} | {} | {} |",
+                label,
+                required_by,
+                update.installed_version,
+                lag,
+                update.latest_version,
+                unsolvable,
+                severity,
+                channel
+            );
+        }
+        if let Some(delta) = size_deltas.get(platform).filter(|delta| **delta != 0) {
+            let _ = writeln!(
+                out,
+                "\nDownload size change: {}",
+                locale.format_size_delta(*delta)
+            );
+        }
+    }
+    if let Some(libyears) = report.libyears.filter(|libyears| libyears.days() > 0) {
+        let _ = writeln!(out, "\nBehind by {}.", libyears);
+    }
+    if !report.commands.is_empty() {
+        let _ = writeln!(
+            out,
+            "\nTo apply every update:\n\n```bash\n{}\n```",
+            report.commands.join("\n")
+        );
+    }
+
+    out
+}
+
+/// Write a rendered report to a file
+pub fn write_report(path: &Path, content: &str) -> Result<()> {
+    let mut content = content.to_string();
+    if !content.ends_with('\n') {
+        content.push('\n');
+    }
+    fs::write(path, content).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_report() -> Report {
+        let mut updates = PlatformUpdates::new();
+        updates.insert(
+            "linux-64".to_string(),
+            vec![PackageUpdate {
+                name: "python".to_string(),
+                installed_version: "3.12.0".to_string(),
+                latest_version: "3.13.0".to_string(),
+                ..Default::default()
+            }],
+        );
+        updates.insert("osx-arm64".to_string(), vec![]);
+        Report {
+            metadata: None,
+            status: RunStatus::Ok,
+            error: None,
+            platforms: updates,
+            locked_issues: vec![],
+            unchecked: vec![],
+            disallowed_channels: vec![],
+            unlisted_channels: vec![],
+            labeled_packages: vec![],
+            newer_in_other_channels: vec![],
+            held_back_by_license: vec![],
+            python_upgrades: vec![],
+            vulnerabilities: vec![],
+            audit_unavailable: None,
+            unaudited: vec![],
+            commands: vec![],
+            explanations: vec![],
+            libyears: None,
+            sources: vec![],
+            repodata: vec![],
+            network: NetworkUsage::default(),
+            content_hash: None,
+            locked: BTreeMap::new(),
+            checked: BTreeSet::new(),
+        }
+    }
+
+    fn unchecked(name: &str, status: CheckStatus) -> UncheckedPackage {
+        UncheckedPackage {
+            name: name.to_string(),
+            kind: PackageKind::Pypi,
+            version: "1.0".to_string(),
+            platform: "linux-64".to_string(),
+            status,
+            annotations: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_render_json_roundtrip() {
+        let mut report = sample_report();
+        report.locked_issues.push(LockedIssue {
+            name: "cowsay".to_string(),
+            version: "5.0".to_string(),
+            platform: "linux-64".to_string(),
+            status: LockedStatus::Yanked {
+                reason: Some("broken".to_string()),
+            },
+            annotations: Default::default(),
+        });
+
+        let json = render_json(&report).unwrap();
+        assert!(json.contains(r#""status": "yanked""#));
+
+        let parsed: Report = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, report);
+    }
+
+    #[test]
+    fn test_render_json_schema_version() {
+        let json: serde_json::Value =
+            serde_json::from_str(&render_json(&sample_report()).unwrap()).unwrap();
+        assert_eq!(json["schema_version"], SCHEMA_VERSION);
+
+        let environments = EnvironmentReports {
+            environments: BTreeMap::from([("default".to_string(), sample_report())]),
+            ..Default::default()
+        };
+        let json: serde_json::Value = serde_json::from_str(
+            &environments
+                .render(OutputFormat::Json, &Locale::default())
+                .unwrap()
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(json["schema_version"], SCHEMA_VERSION);
+        assert!(json["environments"]["default"]["platforms"].is_object());
+    }
+
+    #[test]
+    fn test_render_json_omits_empty_locked_issues() {
+        let json = render_json(&sample_report()).unwrap();
+        assert!(!json.contains("locked_issues"));
+    }
+
+    #[test]
+    fn test_render_yaml() {
+        let yaml = render_yaml(&sample_report()).unwrap();
+        let parsed: Report = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(parsed, sample_report());
+    }
+
+    #[test]
+    fn test_render_csv() {
+        let mut report = sample_report();
+        report.platforms.get_mut("linux-64").unwrap()[0].kind = Some(PackageKind::Conda);
+        report.platforms.get_mut("linux-64").unwrap()[0].severity = Some(Severity::Minor);
+
+        let csv = render_csv(&report).unwrap();
+        let mut lines = csv.lines();
+        assert_eq!(
+            lines.next(),
+            Some("platform,name,kind,channel,installed_version,latest_version,installed_age_days,exceeds_max_lag,risk,severity,latest_compatible,status,merge,annotations,required_by")
+        );
+        assert_eq!(
+            lines.next(),
+            Some("linux-64,python,conda,,3.12.0,3.13.0,,false,,minor,,newer,,,")
+        );
+        assert_eq!(lines.next(), None);
+    }
+
+    #[test]
+    fn test_environment_reports() {
+        let empty = Report {
+            status: RunStatus::EmptyEnvironment,
+            ..Default::default()
+        };
+        let mut reports = EnvironmentReports {
+            environments: BTreeMap::from([
+                ("default".to_string(), sample_report()),
+                ("lint".to_string(), empty.clone()),
+            ]),
+            ..Default::default()
+        };
+
+        let json: serde_json::Value = serde_json::from_str(
+            &reports
+                .render(OutputFormat::Json, &Locale::default())
+                .unwrap()
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            json["environments"]["default"]["platforms"]["linux-64"][0]["name"],
+            "python"
+        );
+        assert_eq!(json["environments"]["lint"]["status"], "empty_environment");
+
+        let csv = reports
+            .render(OutputFormat::Csv, &Locale::default())
+            .unwrap()
+            .unwrap();
+        let mut lines = csv.lines();
+        assert!(lines
+            .next()
+            .unwrap()
+            .starts_with("environment,platform,name,"));
+        assert!(lines
+            .next()
+            .unwrap()
+            .starts_with("default,linux-64,python,"));
+        assert_eq!(lines.next(), None);
+
+        let markdown = reports
+            .render(OutputFormat::Markdown, &Locale::default())
+            .unwrap()
+            .unwrap();
+        assert!(markdown.contains("## Outdated packages in `default`"));
+        assert!(markdown.contains("## Outdated packages in `lint`"));
+
+        // One environment with something to check is enough
+        assert_eq!(reports.exit_code(), 0);
+        reports
+            .environments
+            .insert("default".to_string(), empty.clone());
+        assert_eq!(reports.exit_code(), empty.status.exit_code());
+    }
+
+    #[test]
+    fn test_project_reports() {
+        let mut disallowed = sample_report();
+        disallowed.disallowed_channels.push(DisallowedPackage {
+            name: "numpy".to_string(),
+            version: "2.3.0".to_string(),
+            platform: "linux-64".to_string(),
+            channel: "https://repo.anaconda.com/pkgs/main".to_string(),
+        });
+        let mut reports = ProjectReports {
+            projects: BTreeMap::from([
+                (".".to_string(), sample_report()),
+                ("services/api".to_string(), sample_report()),
+            ]),
+        };
+
+        let json: serde_json::Value = serde_json::from_str(
+            &reports
+                .render(OutputFormat::Json, &Locale::default())
+                .unwrap()
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            json["projects"]["services/api"]["platforms"]["linux-64"][0]["name"],
+            "python"
+        );
+
+        let csv = reports
+            .render(OutputFormat::Csv, &Locale::default())
+            .unwrap()
+            .unwrap();
+        let mut lines = csv.lines();
+        assert!(lines.next().unwrap().starts_with("project,platform,name,"));
+        assert!(lines.next().unwrap().starts_with(".,linux-64,python,"));
+        assert!(lines
+            .next()
+            .unwrap()
+            .starts_with("services/api,linux-64,python,"));
+
+        let markdown = reports
+            .render(OutputFormat::Markdown, &Locale::default())
+            .unwrap()
+            .unwrap();
+        assert!(markdown.contains("## Outdated packages in `services/api`"));
+
+        assert_eq!(reports.exit_code(), 0);
+        reports
+            .projects
+            .insert("services/api".to_string(), disallowed);
+        assert_eq!(reports.exit_code(), DISALLOWED_CHANNEL_EXIT_CODE);
+    }
+
+    #[test]
+    fn test_shared_updates() {
+        let update = |name: &str, latest: &str| PackageUpdate {
+            name: name.to_string(),
+            installed_version: "1.0".to_string(),
+            latest_version: latest.to_string(),
+            ..Default::default()
+        };
+        let report = |updates: Vec<PackageUpdate>| Report {
+            platforms: PlatformUpdates::from([("linux-64".to_string(), updates)]),
+            ..Default::default()
+        };
+        let mut reports = EnvironmentReports {
+            environments: BTreeMap::from([(
+                "default".to_string(),
+                report(vec![update("python", "3.13"), update("numpy", "2.3")]),
+            )]),
+            ..Default::default()
+        };
+        // A single environment has nothing to share
+        assert!(reports.shared_updates().is_empty());
+
+        reports.environments.insert(
+            "test".to_string(),
+            report(vec![update("python", "3.13"), update("numpy", "2.4")]),
+        );
+        let shared = reports.shared_updates();
+        assert_eq!(shared["linux-64"], [update("python", "3.13")]);
+
+        let remaining = reports.environments["test"].without_updates(&shared);
+        assert_eq!(remaining.platforms["linux-64"], [update("numpy", "2.4")]);
+    }
+
+    #[test]
+    fn test_unchecked_packages_in_every_format() {
+        let mut report = sample_report();
+        report.unchecked = vec![
+            unchecked("internal-lib", CheckStatus::NotFound),
+            unchecked(
+                "cowsay",
+                CheckStatus::Error {
+                    message: "connection refused".to_string(),
+                },
+            ),
+            unchecked("mylib", CheckStatus::NoSource),
+        ];
+
+        let json = render_json(&report).unwrap();
+        assert!(json.contains(r#""status": "not_found""#));
+        assert!(json.contains(r#""status": "no_source""#));
+        assert!(json.contains(r#""message": "connection refused""#));
+        let parsed: Report = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, report);
+
+        let csv = render_csv(&report).unwrap();
+        assert!(csv.contains("linux-64,internal-lib,pypi,,1.0,,,false,,,,not_found"));
+        assert!(csv.contains("linux-64,cowsay,pypi,,1.0,,,false,,,,error"));
+        assert!(csv.contains("linux-64,mylib,pypi,,1.0,,,false,,,,no_source"));
+
+        let markdown = render_markdown(&report, &Locale::default());
+        assert!(markdown.contains("| internal-lib | 1.0 | linux-64 | not found upstream |"));
+        assert!(markdown.contains("| cowsay | 1.0 | linux-64 | query failed: connection refused |"));
+
+        assert_eq!(
+            report.unchecked[1].to_string(),
+            "cowsay (pypi): 1.0 (query failed: connection refused)"
+        );
+    }
+
+    #[test]
+    fn test_render_markdown_up_to_date_except_unchecked() {
+        let report = Report {
+            unchecked: vec![unchecked("internal-lib", CheckStatus::NotFound)],
+            ..Report::default()
+        };
+        let markdown = render_markdown(&report, &Locale::default());
+        assert!(markdown.contains("All other packages are up to date."));
+    }
+
+    #[test]
+    fn test_render_text_is_left_to_the_caller() {
+        assert_eq!(
+            render(&sample_report(), OutputFormat::Text, &Locale::default()).unwrap(),
+            None
+        );
+        assert!(
+            render(&sample_report(), OutputFormat::Markdown, &Locale::default())
+                .unwrap()
+                .unwrap()
+                .starts_with("## Outdated packages")
+        );
+    }
+
+    #[test]
+    fn test_render_markdown() {
+        let markdown = render_markdown(&sample_report(), &Locale::default());
+        assert!(markdown.contains("### linux-64"));
+        assert!(markdown.contains("| Package | Installed | Latest | Severity | Channel |"));
+        assert!(markdown.contains("| python | 3.12.0 | 3.13.0 | - | - |"));
+        // Platforms without updates are omitted
+        assert!(!markdown.contains("osx-arm64"));
+        assert!(!markdown.contains("Yanked or removed"));
+    }
+
+    #[test]
+    fn test_render_markdown_severity_and_channel() {
+        let mut report = sample_report();
+        let python = &mut report.platforms.get_mut("linux-64").unwrap()[0];
+        python.kind = Some(PackageKind::Conda);
+        python.channel = Some("https://conda.anaconda.org/conda-forge/".to_string());
+        python.severity = Some(Severity::Minor);
+        report
+            .platforms
+            .get_mut("osx-arm64")
+            .unwrap()
+            .push(PackageUpdate {
+                name: "cowsay".to_string(),
+                kind: Some(PackageKind::Pypi),
+                installed_version: "5.0".to_string(),
+                latest_version: "6.1".to_string(),
+                severity: Some(Severity::Major),
+                ..Default::default()
+            });
+
+        let markdown = render_markdown(&report, &Locale::default());
+        assert!(markdown.contains("| python | 3.12.0 | 3.13.0 | minor | conda-forge |"));
+        assert!(markdown.contains("| cowsay | 5.0 | 6.1 | major | PyPI |"));
+    }
+
+    #[test]
+    fn test_render_markdown_locked_issues() {
+        let mut report = sample_report();
+        report.locked_issues.push(LockedIssue {
+            name: "libfoo".to_string(),
+            version: "1.0".to_string(),
+            platform: "linux-64".to_string(),
+            status: LockedStatus::Removed,
+            annotations: Default::default(),
+        });
+
+        let markdown = render_markdown(&report, &Locale::default());
+        assert!(markdown.contains("### Yanked or removed upstream"));
+        assert!(markdown.contains("| libfoo | 1.0 | linux-64 | removed upstream |"));
+    }
+
+    #[test]
+    fn test_render_markdown_audit_unavailable() {
+        let mut report = sample_report();
+        report.audit_unavailable = Some("OSV query failed: 503".to_string());
+
+        let markdown = render_markdown(&report, &Locale::default());
+        assert!(markdown.contains(
+            "### Known vulnerabilities\n\nThe audit was unavailable: OSV query failed: 503\n"
+        ));
+        let json = serde_json::to_string(&report).unwrap();
+        assert!(json.contains(r#""audit_unavailable":"OSV query failed: 503""#));
+    }
+
+    #[test]
+    fn test_disallowed_channels() {
+        let mut report = sample_report();
+        report.disallowed_channels.push(DisallowedPackage {
+            name: "samtools".to_string(),
+            version: "1.21".to_string(),
+            platform: "linux-64".to_string(),
+            channel: "https://conda.anaconda.org/bioconda".to_string(),
+        });
+
+        assert_eq!(
+            report.disallowed_channels[0].to_string(),
+            "samtools: 1.21 from https://conda.anaconda.org/bioconda"
+        );
+        let markdown = render_markdown(&report, &Locale::default());
+        assert!(markdown.contains("### Not from an allowed channel"));
+        assert!(markdown
+            .contains("| samtools | 1.21 | linux-64 | https://conda.anaconda.org/bioconda |"));
+        let json = render_json(&report).unwrap();
+        assert!(json.contains("\"disallowed_channels\""));
+        assert!(!render_json(&sample_report())
+            .unwrap()
+            .contains("disallowed_channels"));
+    }
+
+    #[test]
+    fn test_unlisted_channels() {
+        let mut report = sample_report();
+        report.unlisted_channels.push(DisallowedPackage {
+            name: "pytorch".to_string(),
+            version: "2.4.1".to_string(),
+            platform: "linux-64".to_string(),
+            channel: "https://conda.anaconda.org/pytorch".to_string(),
+        });
+
+        let markdown = render_markdown(&report, &Locale::default());
+        assert!(markdown.contains("### Locked from channels the manifest no longer lists"));
+        assert!(markdown
+            .contains("| pytorch | 2.4.1 | linux-64 | https://conda.anaconda.org/pytorch |"));
+        let json = render_json(&report).unwrap();
+        assert!(json.contains("\"unlisted_channels\""));
+        assert_ne!(
+            report.compute_content_hash().unwrap(),
+            sample_report().compute_content_hash().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_newer_in_other_channels() {
+        let mut report = sample_report();
+        let release = OtherChannelRelease {
+            name: "numpy".to_string(),
+            platform: "linux-64".to_string(),
+            installed_version: "1.26.4".to_string(),
+            latest_version: Some("1.26.4".to_string()),
+            channel: "https://conda.anaconda.org/conda-forge".to_string(),
+            version: "2.1.0".to_string(),
+        };
+        assert_eq!(
+            release.to_string(),
+            "numpy: 1.26.4 installed, 2.1.0 on https://conda.anaconda.org/conda-forge (channel priority picks 1.26.4)"
+        );
+        report.newer_in_other_channels.push(release);
+
+        let markdown = render_markdown(&report, &Locale::default());
+        assert!(markdown.contains("### Newer in other channels"));
+        assert!(markdown.contains(
+            "| numpy | 1.26.4 | linux-64 | 1.26.4 | 2.1.0 | https://conda.anaconda.org/conda-forge |"
+        ));
+        assert!(render_json(&report)
+            .unwrap()
+            .contains("\"newer_in_other_channels\""));
+    }
+
+    #[test]
+    fn test_labeled_package() {
+        let mut labeled = LabeledPackage {
+            name: "pytorch".to_string(),
+            version: "2.5.0rc1".to_string(),
+            platform: "linux-64".to_string(),
+            label: "rc".to_string(),
+            stable_version: Some("2.4.1".to_string()),
+            superseded: false,
+        };
+        assert_eq!(
+            labeled.to_string(),
+            "pytorch: 2.5.0rc1 (label: rc), no stable release supersedes it yet"
+        );
+
+        labeled.stable_version = Some("2.5.0".to_string());
+        labeled.superseded = true;
+        assert_eq!(
+            labeled.to_string(),
+            "pytorch: 2.5.0rc1 (label: rc), superseded by stable 2.5.0"
+        );
+
+        let mut report = sample_report();
+        report.labeled_packages.push(labeled);
+        let markdown = render_markdown(&report, &Locale::default());
+        assert!(markdown.contains("| pytorch | 2.5.0rc1 | rc | linux-64 | 2.5.0 (supersedes) |"));
+
+        let parsed: Report = serde_json::from_str(&render_json(&report).unwrap()).unwrap();
+        assert_eq!(parsed, report);
+    }
+
+    #[test]
+    fn test_render_markdown_up_to_date() {
+        let markdown = render_markdown(&Report::default(), &Locale::default());
+        assert!(markdown.contains("All packages are up to date."));
+    }
+
+    #[test]
+    fn test_markdown_commands() {
+        let mut report = sample_report();
+        report.commands = vec!["pixi update python".to_string()];
+        assert!(render_markdown(&report, &Locale::default())
+            .ends_with("\nTo apply every update:\n\n```bash\npixi update python\n```\n"));
+    }
+
+    #[test]
+    fn test_markdown_libyears() {
+        let mut report = sample_report();
+        assert!(!render_markdown(&report, &Locale::default()).contains("libyears"));
+        report.libyears = Some(Libyears::from_days(548));
+        assert!(
+            render_markdown(&report, &Locale::default()).ends_with("\nBehind by 1.5 libyears.\n")
+        );
+    }
+
+    #[test]
+    fn test_size_delta() {
+        let update = |name: &str, size_delta| PackageUpdate {
+            name: name.to_string(),
+            installed_version: "1.0".to_string(),
+            latest_version: "1.1".to_string(),
+            size_delta,
+            ..Default::default()
+        };
+        let mut platforms = PlatformUpdates::new();
+        platforms.insert(
+            "linux-64".to_string(),
+            vec![
+                update("python", Some(2_500_000)),
+                update("zlib", Some(-1_000)),
+                update("requests", None),
+            ],
+        );
+        platforms.insert("osx-arm64".to_string(), vec![update("requests", None)]);
+        let report = Report {
+            platforms,
+            ..Report::default()
+        };
+
+        assert_eq!(
+            report.size_delta_totals(),
+            BTreeMap::from([("linux-64".to_string(), 2_499_000)])
+        );
+        assert_eq!(
+            report.platforms["linux-64"][0].to_string(),
+            "python: 1.0 -> 1.1 (+2.5 MB)"
+        );
+        assert!(render_markdown(&report, &Locale::default())
+            .contains("\nDownload size change: +2.5 MB\n"));
+
+        // The run's locale applies to the text line and markdown alike
+        let german = Locale::parse("de").unwrap();
+        assert_eq!(
+            report.platforms["linux-64"][0].describe(&german),
+            "python: 1.0 -> 1.1 (+2,5 MB)"
+        );
+        assert!(render_markdown(&report, &german).contains("\nDownload size change: +2,5 MB\n"));
+    }
+
+    #[test]
+    fn test_release_notes_link() {
+        let update = PackageUpdate {
+            name: "requests".to_string(),
+            kind: Some(PackageKind::Pypi),
+            installed_version: "2.31.0".to_string(),
+            latest_version: "2.32.3".to_string(),
+            release_notes: Some("https://github.com/psf/requests/releases".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            update.to_string(),
+            "requests: 2.31.0 -> 2.32.3 (release notes: https://github.com/psf/requests/releases)"
+        );
+
+        let mut platforms = PlatformUpdates::new();
+        platforms.insert("linux-64".to_string(), vec![update]);
+        let report = Report {
+            platforms,
+            ..Report::default()
+        };
+        assert!(render_markdown(&report, &Locale::default())
+            .contains("| [requests](https://github.com/psf/requests/releases) | 2.31.0 |"));
+    }
+
+    #[test]
+    fn test_package_update_display_with_lag() {
+        let mut update = PackageUpdate {
+            name: "numpy".to_string(),
+            installed_version: "1.26.0".to_string(),
+            latest_version: "2.1.0".to_string(),
+            installed_age_days: Some(400),
+            exceeds_max_lag: false,
+            ..Default::default()
+        };
+        assert_eq!(update.to_string(), "numpy: 1.26.0 -> 2.1.0");
+
+        update.exceeds_max_lag = true;
+        assert_eq!(
+            update.to_string(),
+            "numpy: 1.26.0 -> 2.1.0 (installed version is 400 days old)"
+        );
+
+        let json = serde_json::to_string(&update).unwrap();
+        assert!(json.contains(r#""exceeds_max_lag":true"#));
+    }
+
+    #[test]
+    fn test_package_update_json_omits_lag_by_default() {
+        let update = PackageUpdate {
+            name: "numpy".to_string(),
+            installed_version: "1.26.0".to_string(),
+            latest_version: "2.1.0".to_string(),
+            ..Default::default()
+        };
+        let json = serde_json::to_string(&update).unwrap();
+        assert!(!json.contains("installed_age_days"));
+        assert!(!json.contains("exceeds_max_lag"));
+        assert!(!json.contains("risk"));
+    }
+
+    #[test]
+    fn test_package_update_with_latest_compatible() {
+        let mut update = PackageUpdate {
+            name: "python".to_string(),
+            installed_version: "3.12.11".to_string(),
+            latest_version: "3.14.0".to_string(),
+            latest_compatible: Some("3.12.12".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            update.to_string(),
+            "python: 3.12.11 -> 3.14.0 (requirement allows up to 3.12.12)"
+        );
+
+        // Nothing to point out when the requirement already admits the latest version
+        update.latest_compatible = Some("3.14.0".to_string());
+        assert_eq!(update.to_string(), "python: 3.12.11 -> 3.14.0");
+    }
+
+    #[test]
+    fn test_package_update_unsolvable() {
+        let update = PackageUpdate {
+            name: "numpy".to_string(),
+            installed_version: "1.26.4".to_string(),
+            latest_version: "2.3.0".to_string(),
+            unsolvable: Some("failed to solve the conda requirements".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            update.to_string(),
+            "numpy: 1.26.4 -> 2.3.0 (unsolvable: failed to solve the conda requirements)"
+        );
+
+        let mut platforms = PlatformUpdates::new();
+        platforms.insert("linux-64".to_string(), vec![update]);
+        let report = Report {
+            platforms,
+            ..Report::default()
+        };
+        assert!(render_markdown(&report, &Locale::default())
+            .contains("| numpy | 1.26.4 | 2.3.0 (unsolvable) |"));
+    }
+
+    #[test]
+    fn test_package_update_with_features() {
+        let mut update = PackageUpdate {
+            name: "pytest".to_string(),
+            installed_version: "8.3.0".to_string(),
+            latest_version: "8.4.2".to_string(),
+            features: vec!["test".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(update.to_string(), "pytest (feature: test): 8.3.0 -> 8.4.2");
+
+        update.features.push("lint".to_string());
+        assert_eq!(
+            update.to_string(),
+            "pytest (features: test, lint): 8.3.0 -> 8.4.2"
+        );
+        let json = serde_json::to_value(&update).unwrap();
+        assert_eq!(json["features"], serde_json::json!(["test", "lint"]));
+    }
+
+    #[test]
+    fn test_annotate() {
+        let mut report = sample_report();
+        report.unchecked = vec![unchecked("python", CheckStatus::NotFound)];
+        let annotations: Annotations = toml::from_str::<BTreeMap<String, Annotation>>(
+            r#""py*" = { team = "runtime", criticality = "high" }"#,
+        )
+        .unwrap()
+        .try_into()
+        .unwrap();
+        report.annotate(&annotations);
+
+        let update = &report.platforms["linux-64"][0];
+        assert_eq!(update.annotations["team"], "runtime");
+        assert_eq!(
+            update.to_string(),
+            "python {criticality=high, team=runtime}: 3.12.0 -> 3.13.0"
+        );
+        assert_eq!(report.unchecked[0].annotations["criticality"], "high");
+
+        let json: serde_json::Value = serde_json::from_str(&render_json(&report).unwrap()).unwrap();
+        assert_eq!(
+            json["platforms"]["linux-64"][0]["annotations"]["team"],
+            "runtime"
+        );
+        let csv = render_csv(&report).unwrap();
+        assert!(csv.contains(",newer,,criticality=high;team=runtime\n"));
+        assert!(render_markdown(&report, &Locale::default())
+            .contains("| python {criticality=high, team=runtime} |"));
+    }
+
+    #[test]
+    fn test_held_back_by_license() {
+        let mut held_back = HeldBackPackage {
+            name: "readline".to_string(),
+            platform: "linux-64".to_string(),
+            installed_version: "8.2".to_string(),
+            newest_version: "8.3".to_string(),
+            license: "GPL-3.0-only".to_string(),
+            latest_allowed: Some("8.2".to_string()),
+        };
+        assert_eq!(
+            held_back.to_string(),
+            "readline: 8.3 is GPL-3.0-only, no allowed update from 8.2"
+        );
+        held_back.installed_version = "8.1".to_string();
+        assert_eq!(
+            held_back.to_string(),
+            "readline: 8.3 is GPL-3.0-only, latest allowed is 8.2"
+        );
+
+        let mut report = sample_report();
+        report.held_back_by_license.push(held_back);
+        assert!(render_markdown(&report, &Locale::default())
+            .contains("| readline | 8.1 | 8.3 | GPL-3.0-only | 8.2 | linux-64 |"));
+        let json = render_json(&report).unwrap();
+        assert_eq!(serde_json::from_str::<Report>(&json).unwrap(), report);
+    }
+
+    #[test]
+    fn test_package_update_with_risk() {
+        let update = PackageUpdate {
+            name: "numpy".to_string(),
+            installed_version: "1.26.0".to_string(),
+            latest_version: "2.1.0".to_string(),
+            risk: Some(RiskGroup::NeedsReview),
+            risk_reasons: vec!["major version bump".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(
+            update.to_string(),
+            "numpy: 1.26.0 -> 2.1.0 [major version bump]"
+        );
+
+        let json = serde_json::to_string(&update).unwrap();
+        assert!(json.contains(r#""risk":"needs-review""#));
+        assert!(json.contains(r#""risk_reasons":["major version bump"]"#));
+    }
+
+    #[test]
+    fn test_render_markdown_degenerate_status() {
+        let report = Report {
+            status: RunStatus::LockfileMissing,
+            ..Report::default()
+        };
+        let markdown = render_markdown(&report, &Locale::default());
+        assert!(markdown.contains("No lockfile found"));
+        assert!(!markdown.contains("All packages are up to date."));
+    }
+
+    #[test]
+    fn test_failed_report() {
+        let report = Report::failed(&anyhow::anyhow!("Failed to load pixi.lock"));
+        assert_eq!(report.status, RunStatus::Failed);
+        assert!(render_markdown(&report, &Locale::default())
+            .contains("The check failed: Failed to load pixi.lock"));
+        assert_eq!(
+            combined_exit_code(&[&Report::default(), &report]),
+            RunStatus::Failed.exit_code()
+        );
+    }
+
+    #[test]
+    fn test_run_status_exit_codes_are_distinct() {
+        let statuses = [
+            RunStatus::Ok,
+            RunStatus::EmptyEnvironment,
+            RunStatus::AllPackagesSkipped,
+            RunStatus::LockfileMissing,
+            RunStatus::Failed,
+        ];
+        let codes: std::collections::HashSet<u8> = statuses.iter().map(|s| s.exit_code()).collect();
+        assert_eq!(codes.len(), statuses.len());
+        assert_eq!(RunStatus::Ok.exit_code(), 0);
+        assert_eq!(RunStatus::NoChanges.exit_code(), 0);
+        assert!(!codes.contains(&OUTDATED_DIRECT_EXIT_CODE));
+        assert!(!codes.contains(&DISALLOWED_CHANNEL_EXIT_CODE));
+    }
+
+    #[test]
+    fn test_content_hash_is_stable() {
+        let report = sample_report();
+        let hash = report.compute_content_hash().unwrap();
+        assert_eq!(hash.len(), 64);
+        assert_eq!(report.compute_content_hash().unwrap(), hash);
+
+        let mut changed = report.clone();
+        changed.platforms.get_mut("linux-64").unwrap()[0].latest_version = "3.13.1".to_string();
+        assert_ne!(changed.compute_content_hash().unwrap(), hash);
+    }
+
+    #[test]
+    fn test_content_hash_ignores_run_details() {
+        let report = sample_report();
+        let mut rerun = report.clone();
+        rerun.network.pypi_bytes = 12_345;
+        rerun.repodata.push(RepodataFetch {
+            channel: "https://conda.anaconda.org/conda-forge".to_string(),
+            subdir: "noarch".to_string(),
+            total_ms: 800,
+            shared: false,
+            failed: false,
+            bytes: 0,
+        });
+        rerun.content_hash = Some("previous".to_string());
+        for update in rerun.platforms.values_mut().flatten() {
+            update.installed_age_days = Some(400);
+            update.newer_available_days = Some(30);
+        }
+        assert_eq!(
+            rerun.compute_content_hash().unwrap(),
+            report.compute_content_hash().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_report_metadata() {
+        let dir = tempfile::tempdir().unwrap();
+        let lockfile = dir.path().join("pixi.lock");
+        fs::write(&lockfile, "version: 6\n").unwrap();
+        let generated_at = DateTime::parse_from_rfc3339("2026-10-15T08:30:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let metadata = ReportMetadata::new(
+            "default",
+            &["linux-64".to_string()],
+            Some(Path::new("pixi.toml")),
+            Some(&lockfile),
+            generated_at,
+        )
+        .unwrap();
+        let sha256 = metadata.lockfile_sha256.clone().unwrap();
+        assert_eq!(sha256.len(), 64);
+        assert_eq!(
+            metadata.summary(),
+            format!(
+                "Generated by pixi-outdated {} at 2026-10-15T08:30:00Z for environment default (linux-64) of pixi.toml, lockfile sha256 {}",
+                env!("CARGO_PKG_VERSION"),
+                sha256
+            )
+        );
+
+        let mut report = sample_report();
+        let hash = report.compute_content_hash().unwrap();
+        report.metadata = Some(metadata);
+        // Reruns against the same lockfile keep their hash
+        assert_eq!(report.compute_content_hash().unwrap(), hash);
+
+        let json = render_json(&report).unwrap();
+        assert!(json.contains(r#""generated_at": "2026-10-15T08:30:00Z""#));
+        let parsed: Report = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, report);
+
+        // CSV readers expect the header first, so CSV output has no metadata
+        let csv = render_csv(&report).unwrap();
+        assert!(csv.starts_with("platform,name,"));
+
+        let markdown = render_markdown(&report, &Locale::default());
+        assert!(markdown.contains("\n<sub>Generated by pixi-outdated"));
+
+        // Without a lockfile there is nothing to hash
+        let metadata = ReportMetadata::new(
+            "default",
+            &[],
+            None,
+            Some(&dir.path().join("missing.lock")),
+            generated_at,
+        )
+        .unwrap();
+        assert_eq!(metadata.lockfile_sha256, None);
+        assert!(metadata.summary().ends_with("for environment default"));
+    }
+
+    #[test]
+    fn test_outdated_direct() {
+        let mut report = sample_report();
+        assert!(report.outdated_direct().is_empty());
+
+        report.platforms.get_mut("linux-64").unwrap()[0].direct = true;
+        report
+            .platforms
+            .get_mut("osx-arm64")
+            .unwrap()
+            .push(PackageUpdate {
+                name: "libsqlite".to_string(),
+                installed_version: "3.50.0".to_string(),
+                latest_version: "3.50.4".to_string(),
+                ..Default::default()
+            });
+        assert_eq!(report.outdated_direct(), BTreeSet::from(["python"]));
+
+        let json = render_json(&report).unwrap();
+        assert_eq!(json.matches(r#""direct": true"#).count(), 1);
+    }
+
+    #[test]
+    fn test_run_status_serialization() {
+        let report = Report {
+            status: RunStatus::AllPackagesSkipped,
+            ..Report::default()
+        };
+        let json = render_json(&report).unwrap();
+        assert!(json.contains(r#""status": "all_packages_skipped""#));
+    }
+
+    #[test]
+    fn test_write_report() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("report.json");
+        write_report(&path, "{}").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "{}\n");
+    }
+}
@@ -0,0 +1,192 @@
+use reqwest::StatusCode;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+
+/// How many times a query is attempted when `--max-attempts` isn't given
+pub const DEFAULT_MAX_ATTEMPTS: u32 = 3;
+
+/// When to retry queries that failed for a reason that may go away, like a
+/// rate limit or an overloaded server
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Attempts in total, including the first one
+    pub max_attempts: u32,
+    /// Delay before the first retry, doubled for every further retry
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// A policy that never retries
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Self::default()
+        }
+    }
+
+    /// Whether another attempt may follow attempt number `attempt`
+    pub fn should_retry(&self, attempt: u32) -> bool {
+        attempt < self.max_attempts
+    }
+
+    /// How long to wait after attempt number `attempt` failed: exponential
+    /// backoff with a random half of the delay added, so that concurrent
+    /// queries don't retry in lockstep
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(16);
+        let backoff = self
+            .base_delay
+            .saturating_mul(1 << exponent)
+            .min(self.max_delay);
+        let half = backoff / 2;
+        half + half.mul_f64(jitter())
+    }
+}
+
+/// A random number in `[0, 1)`
+fn jitter() -> f64 {
+    // Every `RandomState` is seeded differently, which is random enough to
+    // spread out retries without another dependency
+    let random = RandomState::new().build_hasher().finish();
+    (random >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// Whether an HTTP status says the same request may succeed later
+pub fn is_transient_status(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS
+        || status == StatusCode::REQUEST_TIMEOUT
+        || status.is_server_error()
+}
+
+/// Whether a request failed before getting a response in a way that may not
+/// happen again, like a timeout or a dropped connection
+pub fn is_transient_error(error: &reqwest::Error) -> bool {
+    error.is_timeout() || error.is_connect() || error.is_request()
+}
+
+/// Whether a failed query may succeed when tried again: it timed out, lost
+/// its connection or got a transient status somewhere down its chain of
+/// causes. Anything else, like a 404, bad credentials or unparseable
+/// repodata, fails the same way every time.
+pub fn is_transient_failure(error: &anyhow::Error) -> bool {
+    error.chain().any(is_transient_cause)
+}
+
+fn is_transient_cause(cause: &(dyn std::error::Error + 'static)) -> bool {
+    if cause.is::<tokio::time::error::Elapsed>() {
+        return true;
+    }
+    if let Some(error) = cause.downcast_ref::<reqwest::Error>() {
+        return is_transient_reqwest_error(error);
+    }
+    if let Some(error) = cause.downcast_ref::<reqwest_middleware::Error>() {
+        return match error {
+            reqwest_middleware::Error::Reqwest(error) => is_transient_reqwest_error(error),
+            reqwest_middleware::Error::Middleware(error) => is_transient_failure(error),
+        };
+    }
+    if let Some(error) = cause.downcast_ref::<std::io::Error>() {
+        return matches!(
+            error.kind(),
+            std::io::ErrorKind::TimedOut
+                | std::io::ErrorKind::ConnectionReset
+                | std::io::ErrorKind::ConnectionAborted
+                | std::io::ErrorKind::ConnectionRefused
+                | std::io::ErrorKind::UnexpectedEof
+                | std::io::ErrorKind::Interrupted
+        );
+    }
+    false
+}
+
+fn is_transient_reqwest_error(error: &reqwest::Error) -> bool {
+    match error.status() {
+        Some(status) => is_transient_status(status),
+        None => is_transient_error(error),
+    }
+}
+
+/// The delay a server asked for with `Retry-After`, in seconds
+pub fn retry_after(response: &reqwest::Response) -> Option<Duration> {
+    response
+        .headers()
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+        .map(Duration::from_secs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_delay_grows_exponentially() {
+        let policy = RetryPolicy {
+            max_attempts: 5,
+            base_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(10),
+        };
+        for (attempt, backoff) in [(1, 1), (2, 2), (3, 4), (4, 8), (5, 10), (40, 10)] {
+            let backoff = Duration::from_secs(backoff);
+            let delay = policy.delay(attempt);
+            assert!(delay >= backoff / 2, "attempt {}: {:?}", attempt, delay);
+            assert!(delay <= backoff, "attempt {}: {:?}", attempt, delay);
+        }
+    }
+
+    #[test]
+    fn test_should_retry() {
+        let policy = RetryPolicy::default();
+        assert!(policy.should_retry(1));
+        assert!(policy.should_retry(2));
+        assert!(!policy.should_retry(3));
+        assert!(!RetryPolicy::none().should_retry(1));
+    }
+
+    #[test]
+    fn test_transient_statuses() {
+        assert!(is_transient_status(StatusCode::TOO_MANY_REQUESTS));
+        assert!(is_transient_status(StatusCode::SERVICE_UNAVAILABLE));
+        assert!(is_transient_status(StatusCode::BAD_GATEWAY));
+        assert!(!is_transient_status(StatusCode::NOT_FOUND));
+        assert!(!is_transient_status(StatusCode::UNAUTHORIZED));
+        assert!(!is_transient_status(StatusCode::OK));
+    }
+
+    #[tokio::test]
+    async fn test_transient_failures() {
+        let elapsed = tokio::time::timeout(Duration::ZERO, std::future::pending::<()>())
+            .await
+            .unwrap_err();
+        assert!(is_transient_failure(
+            &anyhow::Error::new(elapsed).context("Repodata query timed out")
+        ));
+
+        let reset = std::io::Error::from(std::io::ErrorKind::ConnectionReset);
+        assert!(is_transient_failure(
+            &anyhow::Error::new(reset).context("Failed to query channel")
+        ));
+
+        let missing = std::io::Error::from(std::io::ErrorKind::NotFound);
+        assert!(!is_transient_failure(&anyhow::Error::new(missing)));
+        assert!(!is_transient_failure(&anyhow::anyhow!(
+            "Failed to parse the repodata"
+        )));
+    }
+}
@@ -0,0 +1,215 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::pixi::PackageKind;
+use crate::severity::{classify, Severity};
+
+const MB: i64 = 1024 * 1024;
+
+/// A preset describing how updates are weighted into risk groups
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum RiskProfile {
+    /// For environments baked into container images: image growth, ABI
+    /// changes in compiled packages and major bumps all weigh in
+    Container,
+}
+
+/// How much scrutiny an update needs before it is applied
+#[derive(Debug, Serialize, Clone, Copy, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "kebab-case")]
+pub enum RiskGroup {
+    SafeToAutoApply,
+    NeedsRebuildTest,
+    NeedsReview,
+}
+
+impl RiskGroup {
+    pub const ALL: [RiskGroup; 3] = [
+        RiskGroup::SafeToAutoApply,
+        RiskGroup::NeedsRebuildTest,
+        RiskGroup::NeedsReview,
+    ];
+
+    /// The identifier used in JSON and CSV output
+    pub fn as_str(self) -> &'static str {
+        match self {
+            RiskGroup::SafeToAutoApply => "safe-to-auto-apply",
+            RiskGroup::NeedsRebuildTest => "needs-rebuild-test",
+            RiskGroup::NeedsReview => "needs-review",
+        }
+    }
+}
+
+impl fmt::Display for RiskGroup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RiskGroup::SafeToAutoApply => write!(f, "Safe to auto-apply"),
+            RiskGroup::NeedsRebuildTest => write!(f, "Needs rebuild test"),
+            RiskGroup::NeedsReview => write!(f, "Needs review"),
+        }
+    }
+}
+
+/// The facts about a single update that a risk profile weighs
+#[derive(Debug, Clone)]
+pub struct RiskInput<'a> {
+    pub installed_version: &'a str,
+    pub latest_version: &'a str,
+    pub kind: PackageKind,
+    /// Whether the package contains compiled code (a non-noarch conda package)
+    pub is_binary: bool,
+    /// Change in artifact size in bytes, if both sizes are known
+    pub size_delta: Option<i64>,
+    /// Whether the installed version ships activation scripts, which an
+    /// update can change for every shell the environment is activated in
+    pub has_activation_scripts: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RiskAssessment {
+    pub group: RiskGroup,
+    pub reasons: Vec<String>,
+}
+
+impl RiskProfile {
+    pub fn assess(self, input: &RiskInput) -> RiskAssessment {
+        match self {
+            RiskProfile::Container => assess_container(input),
+        }
+    }
+}
+
+fn assess_container(input: &RiskInput) -> RiskAssessment {
+    let severity = classify(input.installed_version, input.latest_version);
+    let mut score = 0;
+    let mut reasons = Vec::new();
+
+    match severity {
+        Severity::Major => {
+            score += 4;
+            reasons.push("major version bump".to_string());
+        }
+        Severity::Minor => score += 1,
+        Severity::Patch | Severity::Other => {}
+    }
+
+    // Compiled conda packages frequently change ABI on anything above a patch release
+    let abi_change = input.kind == PackageKind::Conda
+        && input.is_binary
+        && matches!(severity, Severity::Major | Severity::Minor);
+    if abi_change {
+        score += 2;
+        reasons.push("possible ABI change".to_string());
+    }
+
+    if let Some(delta) = input.size_delta {
+        if delta >= 50 * MB {
+            score += 4;
+        } else if delta >= 10 * MB {
+            score += 2;
+        }
+        if delta >= 10 * MB {
+            reasons.push(format!("grows the image by {} MB", delta / MB));
+        }
+    }
+
+    if input.has_activation_scripts {
+        score += 2;
+        reasons.push("ships activation scripts".to_string());
+    }
+
+    let group = match score {
+        0..=1 => RiskGroup::SafeToAutoApply,
+        2..=3 => RiskGroup::NeedsRebuildTest,
+        _ => RiskGroup::NeedsReview,
+    };
+
+    RiskAssessment { group, reasons }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input<'a>(installed: &'a str, latest: &'a str) -> RiskInput<'a> {
+        RiskInput {
+            installed_version: installed,
+            latest_version: latest,
+            kind: PackageKind::Conda,
+            is_binary: false,
+            size_delta: None,
+            has_activation_scripts: false,
+        }
+    }
+
+    #[test]
+    fn test_patch_and_noarch_minor_are_safe() {
+        let profile = RiskProfile::Container;
+        assert_eq!(
+            profile.assess(&input("1.2.3", "1.2.4")).group,
+            RiskGroup::SafeToAutoApply
+        );
+        assert_eq!(
+            profile.assess(&input("1.2.3", "1.3.0")).group,
+            RiskGroup::SafeToAutoApply
+        );
+    }
+
+    #[test]
+    fn test_binary_minor_needs_rebuild_test() {
+        let assessment = RiskProfile::Container.assess(&RiskInput {
+            is_binary: true,
+            ..input("3.12.11", "3.14.0")
+        });
+        assert_eq!(assessment.group, RiskGroup::NeedsRebuildTest);
+        assert_eq!(assessment.reasons, vec!["possible ABI change".to_string()]);
+    }
+
+    #[test]
+    fn test_major_needs_review() {
+        let assessment = RiskProfile::Container.assess(&RiskInput {
+            kind: PackageKind::Pypi,
+            ..input("1.26.4", "2.1.0")
+        });
+        assert_eq!(assessment.group, RiskGroup::NeedsReview);
+        assert_eq!(assessment.reasons, vec!["major version bump".to_string()]);
+    }
+
+    #[test]
+    fn test_activation_scripts_need_rebuild_test() {
+        let assessment = RiskProfile::Container.assess(&RiskInput {
+            has_activation_scripts: true,
+            ..input("3.5.4", "3.5.5")
+        });
+        assert_eq!(assessment.group, RiskGroup::NeedsRebuildTest);
+        assert_eq!(
+            assessment.reasons,
+            vec!["ships activation scripts".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_size_growth() {
+        let profile = RiskProfile::Container;
+
+        let medium = profile.assess(&RiskInput {
+            size_delta: Some(20 * MB),
+            ..input("1.2.3", "1.2.4")
+        });
+        assert_eq!(medium.group, RiskGroup::NeedsRebuildTest);
+        assert_eq!(medium.reasons, vec!["grows the image by 20 MB".to_string()]);
+
+        let large = profile.assess(&RiskInput {
+            size_delta: Some(80 * MB),
+            ..input("1.2.3", "1.2.4")
+        });
+        assert_eq!(large.group, RiskGroup::NeedsReview);
+
+        // Shrinking never adds risk
+        let smaller = profile.assess(&RiskInput {
+            size_delta: Some(-80 * MB),
+            ..input("1.2.3", "1.2.4")
+        });
+        assert_eq!(smaller.group, RiskGroup::SafeToAutoApply);
+    }
+}
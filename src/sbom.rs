@@ -0,0 +1,351 @@
+use anyhow::Result;
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::conda::{extract_channel_url, extract_subdir};
+use crate::pixi::{PackageKind, PixiPackage};
+use crate::report::{CheckStatus, PackageUpdate, Report};
+
+/// The CycloneDX specification version of the BOMs written
+pub const SPEC_VERSION: &str = "1.5";
+
+/// What package URLs percent-encode: all but the unreserved characters
+const PURL_ENCODE_SET: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~');
+
+/// A locked package, with what the run found for it on every platform and
+/// environment that locks the same artifact
+struct Component<'a> {
+    package: &'a PixiPackage,
+    platforms: BTreeSet<&'a str>,
+    environments: BTreeSet<&'a str>,
+    update: Option<&'a PackageUpdate>,
+    /// Why the package couldn't be checked, if it couldn't
+    unchecked: Option<&'a CheckStatus>,
+    /// Whether the run checked the package on some platform, rather than
+    /// leaving it out with a filter
+    checked: bool,
+}
+
+/// Render the locked packages of the report as a CycloneDX JSON BOM
+pub fn render_sbom(report: &Report) -> Result<String> {
+    let environment = report
+        .metadata
+        .as_ref()
+        .map(|metadata| metadata.environment.as_str());
+    render_environments([(environment, report)])
+}
+
+/// Render the locked packages of several reports as one BOM, listing each
+/// artifact once with the environments locking it
+pub fn render_environments<'a>(
+    reports: impl IntoIterator<Item = (Option<&'a str>, &'a Report)>,
+) -> Result<String> {
+    let mut components: BTreeMap<String, Component> = BTreeMap::new();
+    let mut generated_at = None;
+    for (environment, report) in reports {
+        if let Some(ref metadata) = report.metadata {
+            generated_at.get_or_insert(metadata.generated_at);
+        }
+        for (platform, packages) in &report.locked {
+            for package in packages {
+                let update = report.platforms.get(platform).and_then(|updates| {
+                    updates.iter().find(|update| {
+                        update.name == package.name
+                            && update.kind.is_none_or(|kind| kind == package.kind)
+                    })
+                });
+                let unchecked = report
+                    .unchecked
+                    .iter()
+                    .find(|unchecked| {
+                        unchecked.name == package.name
+                            && unchecked.kind == package.kind
+                            && unchecked.platform == *platform
+                    })
+                    .map(|unchecked| &unchecked.status);
+                let component = components
+                    .entry(purl(package, platform))
+                    .or_insert_with(|| Component {
+                        package,
+                        platforms: BTreeSet::new(),
+                        environments: BTreeSet::new(),
+                        update: None,
+                        unchecked: None,
+                        checked: false,
+                    });
+                component.platforms.insert(platform);
+                component.environments.extend(environment);
+                component.update = component.update.or(update);
+                component.unchecked = component.unchecked.or(unchecked);
+                component.checked |= report.was_checked(platform, package);
+            }
+        }
+    }
+
+    let mut metadata = json!({
+        "tools": {
+            "components": [{
+                "type": "application",
+                "name": "pixi-outdated",
+                "version": env!("CARGO_PKG_VERSION"),
+            }]
+        }
+    });
+    if let Some(generated_at) = generated_at {
+        metadata["timestamp"] = json!(generated_at.to_rfc3339());
+    }
+
+    let components: Vec<Value> = components
+        .iter()
+        .map(|(purl, component)| render_component(purl, component))
+        .collect();
+    let bom = json!({
+        "bomFormat": "CycloneDX",
+        "specVersion": SPEC_VERSION,
+        "version": 1,
+        "metadata": metadata,
+        "components": components,
+    });
+    Ok(serde_json::to_string_pretty(&bom)?)
+}
+
+fn render_component(purl: &str, component: &Component) -> Value {
+    let mut properties = vec![
+        property("kind", &component.package.kind.to_string()),
+        property("platforms", &join(&component.platforms)),
+    ];
+    if !component.environments.is_empty() {
+        properties.push(property("environments", &join(&component.environments)));
+    }
+    match (component.update, component.unchecked) {
+        (Some(update), _) => {
+            properties.push(property("latest-version", &update.latest_version));
+            if let Some(severity) = update.severity {
+                properties.push(property("update-severity", &severity.to_string()));
+            }
+        }
+        // Without an answer, the latest version is unknown
+        (None, Some(status)) => properties.push(property("check-status", status.as_str())),
+        (None, None) if component.checked => {
+            properties.push(property("latest-version", &component.package.version));
+            properties.push(property("check-status", CheckStatus::UpToDate.as_str()));
+        }
+        // Packages left out of the check are listed without a latest version
+        (None, None) => {}
+    }
+
+    json!({
+        "type": "library",
+        "bom-ref": purl,
+        "name": component.package.name,
+        "version": component.package.version,
+        "purl": purl,
+        "properties": properties,
+    })
+}
+
+fn property(name: &str, value: &str) -> Value {
+    json!({ "name": format!("pixi-outdated:{}", name), "value": value })
+}
+
+fn join(values: &BTreeSet<&str>) -> String {
+    values.iter().copied().collect::<Vec<_>>().join(",")
+}
+
+/// The package URL of a locked package, e.g.
+/// `pkg:conda/numpy@2.1.0?build=py312_0&channel=conda-forge&subdir=linux-64`
+/// or `pkg:pypi/requests@2.32.3`
+pub fn purl(package: &PixiPackage, platform: &str) -> String {
+    match package.kind {
+        PackageKind::Pypi => format!(
+            "pkg:pypi/{}@{}",
+            encode(&package.name.to_lowercase().replace('_', "-")),
+            encode(&package.version)
+        ),
+        PackageKind::Conda => {
+            let source = package.source.as_deref();
+            let mut qualifiers = Vec::new();
+            if let Some(ref build) = package.build {
+                qualifiers.push(format!("build={}", encode(build)));
+            }
+            let channel = source
+                .and_then(extract_channel_url)
+                .map(|url| crate::output::channel_label(&url).to_string());
+            if let Some(channel) = channel.filter(|channel| !channel.is_empty()) {
+                qualifiers.push(format!("channel={}", encode(&channel)));
+            }
+            let subdir = source
+                .and_then(extract_subdir)
+                .unwrap_or_else(|| platform.to_string());
+            qualifiers.push(format!("subdir={}", encode(&subdir)));
+            format!(
+                "pkg:conda/{}@{}?{}",
+                encode(&package.name),
+                encode(&package.version),
+                qualifiers.join("&")
+            )
+        }
+    }
+}
+
+/// Percent-encode everything but the unreserved characters of a URL
+fn encode(text: &str) -> String {
+    utf8_percent_encode(text, PURL_ENCODE_SET).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::{PlatformUpdates, UncheckedPackage};
+    use crate::severity::Severity;
+
+    fn conda(name: &str, version: &str, subdir: &str) -> PixiPackage {
+        PixiPackage {
+            name: name.to_string(),
+            version: version.to_string(),
+            build: Some("h0_0".to_string()),
+            size_bytes: None,
+            kind: PackageKind::Conda,
+            source: Some(format!(
+                "https://conda.anaconda.org/conda-forge/{}/{}-{}-h0_0.conda",
+                subdir, name, version
+            )),
+            is_explicit: false,
+            depends: vec![],
+        }
+    }
+
+    fn pypi(name: &str, version: &str) -> PixiPackage {
+        PixiPackage {
+            name: name.to_string(),
+            version: version.to_string(),
+            build: None,
+            size_bytes: None,
+            kind: PackageKind::Pypi,
+            source: None,
+            is_explicit: false,
+            depends: vec![],
+        }
+    }
+
+    #[test]
+    fn test_purl() {
+        assert_eq!(
+            purl(&conda("numpy", "2.1.0", "linux-64"), "linux-64"),
+            "pkg:conda/numpy@2.1.0?build=h0_0&channel=conda-forge&subdir=linux-64"
+        );
+        assert_eq!(
+            purl(&pypi("Typing_Extensions", "4.12.2+local"), "linux-64"),
+            "pkg:pypi/typing-extensions@4.12.2%2Blocal"
+        );
+    }
+
+    #[test]
+    fn test_render_sbom() {
+        let mut report = Report::default();
+        report.locked.insert(
+            "linux-64".to_string(),
+            vec![
+                conda("python", "3.12.11", "linux-64"),
+                conda("tzdata", "2025b", "noarch"),
+                pypi("requests", "2.32.3"),
+            ],
+        );
+        report.locked.insert(
+            "osx-arm64".to_string(),
+            vec![conda("tzdata", "2025b", "noarch")],
+        );
+        // requests was left out of the check, e.g. with `--exclude`
+        for (platform, name) in [
+            ("linux-64", "python"),
+            ("linux-64", "tzdata"),
+            ("osx-arm64", "tzdata"),
+        ] {
+            report
+                .checked
+                .insert((platform.to_string(), PackageKind::Conda, name.to_string()));
+        }
+        let mut platforms = PlatformUpdates::new();
+        platforms.insert(
+            "linux-64".to_string(),
+            vec![PackageUpdate {
+                name: "python".to_string(),
+                kind: Some(PackageKind::Conda),
+                installed_version: "3.12.11".to_string(),
+                latest_version: "3.14.0".to_string(),
+                severity: Some(Severity::Minor),
+                ..Default::default()
+            }],
+        );
+        report.platforms = platforms;
+
+        let bom: Value = serde_json::from_str(&render_sbom(&report).unwrap()).unwrap();
+        assert_eq!(bom["bomFormat"], "CycloneDX");
+        assert_eq!(bom["specVersion"], SPEC_VERSION);
+
+        let components = bom["components"].as_array().unwrap();
+        assert_eq!(components.len(), 3);
+        let python = components
+            .iter()
+            .find(|component| component["name"] == "python")
+            .unwrap();
+        assert_eq!(
+            python["properties"],
+            json!([
+                { "name": "pixi-outdated:kind", "value": "conda" },
+                { "name": "pixi-outdated:platforms", "value": "linux-64" },
+                { "name": "pixi-outdated:latest-version", "value": "3.14.0" },
+                { "name": "pixi-outdated:update-severity", "value": "minor" },
+            ])
+        );
+
+        // The noarch package is the same artifact on both platforms
+        let tzdata = components
+            .iter()
+            .find(|component| component["name"] == "tzdata")
+            .unwrap();
+        assert_eq!(tzdata["properties"][1]["value"], "linux-64,osx-arm64");
+        assert_eq!(tzdata["properties"][2]["value"], "2025b");
+        assert_eq!(tzdata["properties"][3]["value"], "up_to_date");
+
+        // Unchecked packages are listed, without a latest version
+        let requests = components
+            .iter()
+            .find(|component| component["name"] == "requests")
+            .unwrap();
+        assert_eq!(
+            requests["properties"],
+            json!([
+                { "name": "pixi-outdated:kind", "value": "pypi" },
+                { "name": "pixi-outdated:platforms", "value": "linux-64" },
+            ])
+        );
+    }
+
+    #[test]
+    fn test_render_sbom_unchecked() {
+        let mut report = Report::default();
+        report
+            .locked
+            .insert("linux-64".to_string(), vec![pypi("private-lib", "1.0")]);
+        report.unchecked.push(UncheckedPackage {
+            name: "private-lib".to_string(),
+            kind: PackageKind::Pypi,
+            version: "1.0".to_string(),
+            platform: "linux-64".to_string(),
+            status: CheckStatus::NotFound,
+            annotations: Default::default(),
+        });
+
+        let bom: Value = serde_json::from_str(&render_sbom(&report).unwrap()).unwrap();
+        assert_eq!(
+            bom["components"][0]["properties"][2],
+            json!({ "name": "pixi-outdated:check-status", "value": "not_found" })
+        );
+    }
+}
@@ -0,0 +1,194 @@
+use anyhow::{Context, Result};
+use globset::GlobMatcher;
+use regex::Regex;
+use std::collections::BTreeMap;
+
+use crate::ignore::{ExcludePatterns, IgnoreFile};
+use crate::severity::Severity;
+use crate::upstream::{UpstreamInfo, VersionLookup};
+
+/// The packages a run is limited to: names or glob patterns given as
+/// arguments (`'jupyter*'`), and regular expressions given with `--regex`,
+/// which match anywhere in the name like `pixi list` does. Without either,
+/// every package is selected.
+#[derive(Debug, Clone, Default)]
+pub struct PackageSelection {
+    globs: Vec<GlobMatcher>,
+    regexes: Vec<Regex>,
+}
+
+impl PackageSelection {
+    pub fn new<S: AsRef<str>>(patterns: &[S], regexes: &[S]) -> Result<Self> {
+        let globs = crate::glob::matchers(patterns, false, "package pattern")?;
+        let regexes = regexes
+            .iter()
+            .map(|regex| {
+                let regex = regex.as_ref();
+                Regex::new(regex).with_context(|| format!("Invalid package regex: {}", regex))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { globs, regexes })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.globs.is_empty() && self.regexes.is_empty()
+    }
+
+    /// Whether `name` is selected, by any pattern or regex
+    pub fn matches(&self, name: &str) -> bool {
+        self.is_empty()
+            || self.globs.iter().any(|glob| glob.is_match(name))
+            || self.regexes.iter().any(|regex| regex.is_match(name))
+    }
+}
+
+/// Which packages a check looks at and which of their releases it reports.
+/// Workspace runs and the checks of [`crate::check`] decide the same way.
+#[derive(Debug, Clone, Default)]
+pub struct Selection {
+    /// The packages given as arguments or with `--regex`
+    pub packages: PackageSelection,
+    /// Packages left out with `--exclude`
+    pub exclude: ExcludePatterns,
+    /// Ignore rules from the project config and `.pixi-outdated-ignore`
+    pub ignore: IgnoreFile,
+    /// Packages whose pre-releases count, as given with `--pre`; every
+    /// package when empty
+    pub prereleases: Option<Vec<String>>,
+    /// The maximum version of packages, from the project config
+    pub max_versions: BTreeMap<String, String>,
+    /// The update severities to report, as given with `--filter`; all when
+    /// empty
+    pub severities: Vec<Severity>,
+}
+
+impl Selection {
+    /// Whether `name` is looked up at all: it is selected, not excluded and
+    /// not ignored for every version
+    pub fn includes(&self, name: &str) -> bool {
+        self.packages.matches(name)
+            && !self.exclude.excludes(name)
+            && !self.ignore.ignores_all_versions(name)
+    }
+
+    /// Consider pre-releases for the latest version of `name`, if `--pre`
+    /// asks for them
+    pub fn with_prereleases(&self, name: &str, lookup: VersionLookup) -> VersionLookup {
+        match (lookup, &self.prereleases) {
+            (VersionLookup::Found(info), Some(packages))
+                if packages.is_empty() || packages.iter().any(|package| package == name) =>
+            {
+                VersionLookup::Found(info.with_prereleases())
+            }
+            (lookup, _) => lookup,
+        }
+    }
+
+    /// The newest release at or below the maximum version of `name`, if
+    /// `latest` is beyond it. Nothing newer than `installed` below the
+    /// maximum leaves the installed version.
+    pub fn capped(
+        &self,
+        info: &UpstreamInfo,
+        name: &str,
+        latest: &str,
+        installed: &str,
+    ) -> Option<String> {
+        let max_version = self.max_versions.get(name)?;
+        if info.allows(max_version, latest) {
+            return None;
+        }
+        Some(
+            info.latest_matching(&[max_version.as_str()])
+                .filter(|capped| !info.is_older(capped, installed))
+                .unwrap_or_else(|| installed.to_string()),
+        )
+    }
+
+    /// The latest version of `name` to compare `installed` against: the
+    /// newest release for the locked Python, capped at the maximum version
+    pub fn latest(
+        &self,
+        info: &UpstreamInfo,
+        name: &str,
+        installed: &str,
+        python_version: Option<&str>,
+    ) -> Option<String> {
+        let latest = info.latest_version_for(python_version)?;
+        Some(
+            self.capped(info, name, latest, installed)
+                .unwrap_or_else(|| latest.to_string()),
+        )
+    }
+
+    /// Whether an update of `name` to `latest` is reported: no ignore rule
+    /// covers it and its severity passes `--filter`
+    pub fn reports(&self, name: &str, latest: &str, severity: Severity) -> bool {
+        !self.ignore.is_ignored(name, Some(latest)) && self.passes_filter(severity)
+    }
+
+    /// Whether updates of `severity` pass `--filter`
+    pub fn passes_filter(&self, severity: Severity) -> bool {
+        self.severities.is_empty() || self.severities.contains(&severity)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_everything_selected_by_default() {
+        let selection = PackageSelection::new::<&str>(&[], &[]).unwrap();
+        assert!(selection.matches("python"));
+    }
+
+    #[test]
+    fn test_names_and_globs() {
+        let selection = PackageSelection::new(&["python", "jupyter*"], &[]).unwrap();
+        assert!(selection.matches("python"));
+        assert!(selection.matches("jupyterlab"));
+        assert!(selection.matches("jupyter"));
+        assert!(!selection.matches("python-dateutil"));
+        assert!(!selection.matches("ipython"));
+    }
+
+    #[test]
+    fn test_regexes() {
+        let selection = PackageSelection::new(&["numpy"], &["^lib.*ssl"]).unwrap();
+        assert!(selection.matches("libssl"));
+        assert!(selection.matches("numpy"));
+        assert!(!selection.matches("openssl"));
+
+        // Unanchored regexes match anywhere in the name
+        let selection = PackageSelection::new(&[], &["py"]).unwrap();
+        assert!(selection.matches("ipython"));
+    }
+
+    #[test]
+    fn test_selection() {
+        let selection = Selection {
+            packages: PackageSelection::new(&["py*"], &[]).unwrap(),
+            exclude: ExcludePatterns::new(&["pyyaml"]).unwrap(),
+            ignore: IgnoreFile::from_rules(&["pytest".to_string(), "python 3.14.*".to_string()])
+                .unwrap(),
+            severities: vec![Severity::Major, Severity::Minor],
+            ..Default::default()
+        };
+        assert!(selection.includes("python"));
+        assert!(!selection.includes("numpy"));
+        assert!(!selection.includes("pyyaml"));
+        assert!(!selection.includes("pytest"));
+
+        assert!(selection.reports("python", "3.13.0", Severity::Minor));
+        assert!(!selection.reports("python", "3.14.0", Severity::Minor));
+        assert!(!selection.reports("python", "3.12.12", Severity::Patch));
+        assert!(Selection::default().reports("python", "3.12.12", Severity::Patch));
+    }
+
+    #[test]
+    fn test_invalid_patterns() {
+        assert!(PackageSelection::new(&["py["], &[]).is_err());
+        assert!(PackageSelection::new(&[], &["(lib"]).is_err());
+    }
+}
@@ -0,0 +1,328 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::sources::QueryOutcome;
+use crate::upstream::{PackageKey, PackageNotFound, SourceResponse, UpstreamInfo};
+
+/// How many packages are queried between two saves of the session
+pub const CHUNK_SIZE: usize = 50;
+
+/// The first line of a session file
+#[derive(Debug, Serialize, Deserialize)]
+struct Header {
+    started: DateTime<Utc>,
+}
+
+/// The answer to a finished query
+#[derive(Debug, Serialize, Deserialize)]
+struct Entry {
+    key: PackageKey,
+    source_url: String,
+    /// `None` if the source has no such package
+    info: Option<UpstreamInfo>,
+}
+
+impl Entry {
+    fn into_response(self) -> (PackageKey, SourceResponse) {
+        let info = self.info.ok_or_else(|| {
+            PackageNotFound {
+                name: self.key.name.clone(),
+                location: self.source_url.clone(),
+            }
+            .into()
+        });
+        let response = SourceResponse {
+            source_url: self.source_url,
+            elapsed: Duration::ZERO,
+            outcome: QueryOutcome::CacheHit,
+            bytes: 0,
+            batched: false,
+            info,
+        };
+        (self.key, response)
+    }
+}
+
+/// The query results of a run, saved one package at a time so that a run
+/// that was interrupted picks up where it left off.
+///
+/// Sessions are stored as JSON lines, one file per scope. Every save
+/// replaces the whole file at once, so concurrent runs of the same scope
+/// never interleave their lines; the last one to save wins. Failed queries
+/// aren't saved, so they are retried when the run is resumed.
+#[derive(Debug)]
+pub struct Session {
+    path: PathBuf,
+    header: Header,
+    /// The saved answers, one JSON line each
+    lines: Vec<String>,
+    answered: HashMap<PackageKey, Entry>,
+}
+
+impl Session {
+    /// Open the session of `scope`, e.g. the manifest, environment and
+    /// platforms being checked, in `dir`. A session started more than
+    /// `max_age` ago is discarded, as is any session when `resume` is false.
+    pub fn open(dir: &Path, scope: &str, max_age: Duration, resume: bool) -> Result<Self> {
+        fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create session directory {}", dir.display()))?;
+        let digest = format!("{:x}", Sha256::digest(scope.as_bytes()));
+        let path = dir.join(format!("{}.jsonl", &digest[..16]));
+
+        let (header, answered) = match resume.then(|| load(&path, max_age)).flatten() {
+            Some(loaded) => loaded,
+            None => (
+                Header {
+                    started: Utc::now(),
+                },
+                HashMap::new(),
+            ),
+        };
+        let lines = answered
+            .values()
+            .map(serde_json::to_string)
+            .collect::<serde_json::Result<_>>()?;
+        let session = Self {
+            path,
+            header,
+            lines,
+            answered,
+        };
+        session.save()?;
+        Ok(session)
+    }
+
+    /// How many packages an earlier run already queried
+    pub fn answered(&self) -> usize {
+        self.answered.len()
+    }
+
+    /// Split `keys` into the responses saved by an earlier run and the keys
+    /// that still have to be queried
+    pub fn take_answered(
+        &mut self,
+        keys: &[PackageKey],
+    ) -> (Vec<(PackageKey, SourceResponse)>, Vec<PackageKey>) {
+        let mut responses = Vec::new();
+        let mut pending = Vec::new();
+        for key in keys {
+            match self.answered.remove(key) {
+                Some(entry) => responses.push(entry.into_response()),
+                None => pending.push(key.clone()),
+            }
+        }
+        (responses, pending)
+    }
+
+    /// Save the answers among `responses`
+    pub fn record(&mut self, responses: &[(PackageKey, SourceResponse)]) -> Result<()> {
+        for (key, response) in responses {
+            let info = match response.info {
+                Ok(ref info) => Some(info.clone()),
+                Err(ref e) if e.downcast_ref::<PackageNotFound>().is_some() => None,
+                Err(_) => continue,
+            };
+            let entry = Entry {
+                key: key.clone(),
+                source_url: response.source_url.clone(),
+                info,
+            };
+            // Answers that can't be serialized, like those of sources other
+            // than conda channels and PyPI indexes, are queried again
+            if let Ok(line) = serde_json::to_string(&entry) {
+                self.lines.push(line);
+            }
+        }
+        self.save()
+    }
+
+    /// Delete the session once every package was queried. A concurrent run
+    /// of the same scope may have deleted it already.
+    pub fn finish(self) -> Result<()> {
+        match fs::remove_file(&self.path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                Err(e).with_context(|| format!("Failed to remove session {}", self.path.display()))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Write the session to a temporary file next to it and move that into
+    /// place, so a reader only ever sees a complete session
+    fn save(&self) -> Result<()> {
+        let write = || -> Result<()> {
+            let dir = self.path.parent().unwrap_or_else(|| Path::new("."));
+            let mut file = tempfile::NamedTempFile::new_in(dir)?;
+            writeln!(file, "{}", serde_json::to_string(&self.header)?)?;
+            for line in &self.lines {
+                writeln!(file, "{}", line)?;
+            }
+            file.persist(&self.path)?;
+            Ok(())
+        };
+        write().with_context(|| format!("Failed to write session {}", self.path.display()))
+    }
+}
+
+/// The answers of an unfinished session, unless there is none or it is too
+/// old to trust
+fn load(path: &Path, max_age: Duration) -> Option<(Header, HashMap<PackageKey, Entry>)> {
+    let mut lines = BufReader::new(File::open(path).ok()?).lines();
+    let header: Header = serde_json::from_str(&lines.next()?.ok()?).ok()?;
+    let age = (Utc::now() - header.started).to_std().unwrap_or_default();
+    if age >= max_age {
+        return None;
+    }
+
+    // Lines that don't parse were written by an incompatible version
+    let answered = lines
+        .map_while(|line| line.ok())
+        .filter_map(|line| serde_json::from_str::<Entry>(&line).ok())
+        .map(|entry| (entry.key.clone(), entry))
+        .collect();
+    Some((header, answered))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conda::CondaVersionInfo;
+    use crate::pixi::PackageKind;
+    use std::collections::HashSet;
+
+    const MAX_AGE: Duration = Duration::from_secs(60 * 60);
+
+    fn key(name: &str) -> PackageKey {
+        PackageKey {
+            name: name.to_string(),
+            channel: Some("https://conda.anaconda.org/conda-forge".to_string()),
+            kind: PackageKind::Conda,
+        }
+    }
+
+    fn response(info: Result<UpstreamInfo>) -> SourceResponse {
+        SourceResponse {
+            source_url: "https://conda.anaconda.org/conda-forge".to_string(),
+            elapsed: Duration::from_secs(2),
+            outcome: QueryOutcome::Fetched,
+            bytes: 1024,
+            batched: false,
+            info,
+        }
+    }
+
+    fn python_info() -> UpstreamInfo {
+        UpstreamInfo::Conda(CondaVersionInfo {
+            latest_version: Some("3.14.0".to_string()),
+            artifacts: HashSet::from([("3.14.0".to_string(), "h0_0".to_string())]),
+            sizes: HashMap::from([(("3.14.0".to_string(), "linux-64".to_string()), 31_000_000)]),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_resume_interrupted_session() {
+        let dir = tempfile::tempdir().unwrap();
+        let keys = [key("python"), key("missing"), key("flaky"), key("numpy")];
+
+        let mut session = Session::open(dir.path(), "workspace", MAX_AGE, true).unwrap();
+        session
+            .record(&[
+                (keys[0].clone(), response(Ok(python_info()))),
+                (
+                    keys[1].clone(),
+                    response(Err(PackageNotFound {
+                        name: "missing".to_string(),
+                        location: "conda-forge".to_string(),
+                    }
+                    .into())),
+                ),
+                (
+                    keys[2].clone(),
+                    response(Err(anyhow::anyhow!("connection reset"))),
+                ),
+            ])
+            .unwrap();
+        // The run is interrupted before numpy was queried
+        drop(session);
+
+        let mut session = Session::open(dir.path(), "workspace", MAX_AGE, true).unwrap();
+        assert_eq!(session.answered(), 2);
+        let (responses, pending) = session.take_answered(&keys);
+        assert_eq!(pending, [key("flaky"), key("numpy")]);
+
+        let (python, response) = &responses[0];
+        assert_eq!(python.name, "python");
+        assert_eq!(response.outcome, QueryOutcome::CacheHit);
+        let info = response.info.as_ref().unwrap();
+        assert_eq!(info.latest_version(), Some("3.14.0"));
+        assert_eq!(info.artifact_size("3.14.0", "linux-64"), Some(31_000_000));
+
+        let (missing, response) = &responses[1];
+        assert_eq!(missing.name, "missing");
+        assert!(response
+            .info
+            .as_ref()
+            .unwrap_err()
+            .downcast_ref::<PackageNotFound>()
+            .is_some());
+
+        session.finish().unwrap();
+        let session = Session::open(dir.path(), "workspace", MAX_AGE, true).unwrap();
+        assert_eq!(session.answered(), 0);
+    }
+
+    #[test]
+    fn test_sessions_are_not_resumed_when_stale_or_disabled() {
+        let dir = tempfile::tempdir().unwrap();
+        let record = |scope: &str| {
+            let mut session = Session::open(dir.path(), scope, MAX_AGE, true).unwrap();
+            session
+                .record(&[(key("python"), response(Ok(python_info())))])
+                .unwrap();
+        };
+
+        record("stale");
+        let session = Session::open(dir.path(), "stale", Duration::ZERO, true).unwrap();
+        assert_eq!(session.answered(), 0);
+
+        record("fresh");
+        let session = Session::open(dir.path(), "fresh", MAX_AGE, false).unwrap();
+        assert_eq!(session.answered(), 0);
+
+        // Each scope has its own session
+        record("one");
+        let session = Session::open(dir.path(), "other", MAX_AGE, true).unwrap();
+        assert_eq!(session.answered(), 0);
+    }
+
+    #[test]
+    fn test_concurrent_sessions_do_not_interleave() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut first = Session::open(dir.path(), "workspace", MAX_AGE, true).unwrap();
+        let mut second = Session::open(dir.path(), "workspace", MAX_AGE, true).unwrap();
+        first
+            .record(&[(key("python"), response(Ok(python_info())))])
+            .unwrap();
+        second
+            .record(&[(key("numpy"), response(Ok(python_info())))])
+            .unwrap();
+
+        // The session holds what the last run saved, and nothing else
+        let mut session = Session::open(dir.path(), "workspace", MAX_AGE, true).unwrap();
+        let (responses, pending) = session.take_answered(&[key("python"), key("numpy")]);
+        assert_eq!(pending, [key("python")]);
+        assert_eq!(responses[0].0, key("numpy"));
+
+        first.finish().unwrap();
+        second.finish().unwrap();
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
+}
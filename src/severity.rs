@@ -0,0 +1,106 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// How big a version jump is, based on the first numeric component that changed
+#[derive(
+    Debug,
+    Serialize,
+    Clone,
+    Copy,
+    Deserialize,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    clap::ValueEnum,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// Only non-numeric parts changed (e.g. a pre-release tag or local segment)
+    #[value(skip)]
+    Other,
+    Patch,
+    Minor,
+    Major,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Major => write!(f, "major"),
+            Severity::Minor => write!(f, "minor"),
+            Severity::Patch => write!(f, "patch"),
+            Severity::Other => write!(f, "other"),
+        }
+    }
+}
+
+/// The leading number of each dot-separated component, e.g. `1.26rc1` -> `[1, 26]`
+pub(crate) fn numeric_components(version: &str) -> Vec<u64> {
+    // Strip a conda epoch (`1!2.0`) and local/build suffixes (`+local`, `_1`)
+    let version = version.split_once('!').map_or(version, |(_, v)| v);
+    let version = version.split(['+', '_']).next().unwrap_or(version);
+
+    version
+        .split(['.', '-'])
+        .map_while(|component| {
+            let digits: String = component.chars().take_while(char::is_ascii_digit).collect();
+            digits.parse().ok()
+        })
+        .collect()
+}
+
+/// Classify the jump from `installed` to `latest`
+pub fn classify(installed: &str, latest: &str) -> Severity {
+    let installed = numeric_components(installed);
+    let latest = numeric_components(latest);
+
+    let len = installed.len().max(latest.len());
+    let first_difference = (0..len).find(|&idx| {
+        installed.get(idx).copied().unwrap_or(0) != latest.get(idx).copied().unwrap_or(0)
+    });
+
+    match first_difference {
+        Some(0) => Severity::Major,
+        Some(1) => Severity::Minor,
+        Some(_) => Severity::Patch,
+        None => Severity::Other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify() {
+        assert_eq!(classify("1.26.4", "2.1.0"), Severity::Major);
+        assert_eq!(classify("3.12.11", "3.14.0"), Severity::Minor);
+        assert_eq!(classify("3.50.1", "3.50.4"), Severity::Patch);
+        assert_eq!(classify("1.2.3.4", "1.2.3.5"), Severity::Patch);
+        assert_eq!(classify("73.2", "75.1"), Severity::Major);
+    }
+
+    #[test]
+    fn test_classify_missing_components() {
+        assert_eq!(classify("5.0", "5.0.1"), Severity::Patch);
+        assert_eq!(classify("6", "6.1"), Severity::Minor);
+        assert_eq!(classify("6.0", "6"), Severity::Other);
+    }
+
+    #[test]
+    fn test_classify_non_numeric_changes() {
+        assert_eq!(classify("2.0.0rc1", "2.0.0"), Severity::Other);
+        assert_eq!(classify("1.0+local", "1.0"), Severity::Other);
+        assert_eq!(classify("1!1.0", "1!2.0"), Severity::Major);
+        assert_eq!(classify("4.3.0_1", "4.3.0_2"), Severity::Other);
+    }
+
+    #[test]
+    fn test_severity_ordering() {
+        assert!(Severity::Major > Severity::Minor);
+        assert!(Severity::Minor > Severity::Patch);
+        assert!(Severity::Patch > Severity::Other);
+    }
+}
@@ -0,0 +1,396 @@
+use anyhow::{Context, Result};
+use futures::stream::{self, StreamExt};
+use rattler_conda_types::{
+    Channel, ChannelConfig, GenericVirtualPackage, MatchSpec, PackageName, ParseStrictness,
+    Platform, RepoDataRecord,
+};
+use rattler_repodata_gateway::Gateway;
+use rattler_solve::{SolveError, SolverImpl, SolverTask};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::parser::DependencySpecs;
+use crate::pixi::PackageKind;
+use crate::priority::ChannelPriority;
+use crate::report::Report;
+
+/// How long a single trial solve may take before it's given up on
+pub const SOLVE_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// An update to try out: a conda package pinned to its latest version on
+/// one platform
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Candidate {
+    pub name: String,
+    pub version: String,
+    pub platform: String,
+}
+
+/// The conda updates of a report to try, each once per platform it's
+/// outdated on. PyPI updates are left to pixi's own solve.
+pub fn candidates(report: &Report) -> Vec<Candidate> {
+    let candidates: BTreeSet<Candidate> = report
+        .platforms
+        .iter()
+        .flat_map(|(platform, updates)| {
+            updates
+                .iter()
+                .filter(|update| update.kind.unwrap_or(PackageKind::Conda) == PackageKind::Conda)
+                .map(move |update| Candidate {
+                    name: update.name.clone(),
+                    version: update.latest_version.clone(),
+                    platform: platform.clone(),
+                })
+        })
+        .collect();
+    candidates.into_iter().collect()
+}
+
+/// What the environment is solved from on one platform
+#[derive(Debug, Clone)]
+pub struct SolveEnvironment {
+    /// The environment's channels as full URLs, in priority order
+    pub channels: Vec<String>,
+    pub channel_priority: ChannelPriority,
+    /// The requirements of the environment's direct conda dependencies
+    pub specs: Vec<MatchSpec>,
+    /// The locked records, which the solver keeps where it can
+    pub locked: Vec<RepoDataRecord>,
+    pub virtual_packages: Vec<GenericVirtualPackage>,
+}
+
+/// The requirements the manifest declares on `platform` for the conda
+/// packages locked there, one spec per requirement. Dependencies declared
+/// without one are required at any version.
+pub fn requirements(
+    specs: &DependencySpecs,
+    locked: &[RepoDataRecord],
+    platform: &str,
+) -> Result<Vec<MatchSpec>> {
+    let mut requirements = Vec::new();
+    for record in locked {
+        let name = record.package_record.name.as_normalized();
+        if !specs.is_direct(name, PackageKind::Conda) {
+            continue;
+        }
+        let declared = specs.get(name, PackageKind::Conda, platform);
+        if declared.is_empty() {
+            requirements.push(match_spec(name)?);
+        }
+        for requirement in declared {
+            requirements.push(match_spec(&format!("{} {}", name, requirement))?);
+        }
+    }
+    Ok(requirements)
+}
+
+fn match_spec(spec: &str) -> Result<MatchSpec> {
+    MatchSpec::from_str(spec, ParseStrictness::Lenient)
+        .with_context(|| format!("Invalid requirement '{}'", spec))
+}
+
+/// How a trial solve ended
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SolveOutcome {
+    Solvable,
+    /// The solver's reason the pin can't be solved
+    Unsolvable(String),
+    /// The solve took longer than [`SOLVE_TIMEOUT`], which tells nothing
+    TimedOut,
+}
+
+/// Solve the environment on the candidate's platform with the candidate
+/// pinned, in memory so the workspace is never touched. A direct dependency
+/// has its requirement replaced by the pin; any other package is only
+/// constrained to it, so it stays a transitive dependency. Failing to fetch
+/// repodata is an error, not an unsolvable update.
+pub async fn trial_solve(
+    gateway: &Gateway,
+    environment: &SolveEnvironment,
+    candidate: &Candidate,
+) -> Result<SolveOutcome> {
+    let name = PackageName::try_from(candidate.name.as_str())
+        .with_context(|| format!("Invalid package name: {}", candidate.name))?;
+    let pin = match_spec(&format!("{} =={}", candidate.name, candidate.version))?;
+    let direct = environment
+        .specs
+        .iter()
+        .any(|spec| spec.name.as_ref() == Some(&name));
+    let mut specs: Vec<MatchSpec> = environment
+        .specs
+        .iter()
+        .filter(|spec| spec.name.as_ref() != Some(&name))
+        .cloned()
+        .collect();
+    let mut constraints = Vec::new();
+    if direct {
+        specs.push(pin);
+    } else {
+        constraints.push(pin);
+    }
+
+    let channel_config = ChannelConfig::default_with_root_dir(std::env::current_dir()?);
+    let channels = environment
+        .channels
+        .iter()
+        .map(|channel| {
+            Channel::from_str(channel, &channel_config)
+                .with_context(|| format!("Invalid channel URL: {}", channel))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let platform: Platform = candidate
+        .platform
+        .parse()
+        .with_context(|| format!("Invalid platform: {}", candidate.platform))?;
+    let repodata = gateway
+        .query(
+            channels,
+            [platform, Platform::NoArch],
+            specs.iter().chain(&constraints).cloned(),
+        )
+        .recursive(true)
+        .await
+        .with_context(|| format!("Failed to fetch the repodata to solve {}", platform))?;
+
+    let locked = environment.locked.clone();
+    let virtual_packages = environment.virtual_packages.clone();
+    let channel_priority = match environment.channel_priority {
+        ChannelPriority::Strict => rattler_solve::ChannelPriority::Strict,
+        ChannelPriority::Disabled => rattler_solve::ChannelPriority::Disabled,
+    };
+    // The solver blocks, so it runs off the async workers
+    let result = tokio::task::spawn_blocking(move || {
+        let task = SolverTask {
+            locked_packages: locked,
+            virtual_packages,
+            specs,
+            constraints,
+            channel_priority,
+            timeout: Some(SOLVE_TIMEOUT),
+            ..SolverTask::from_iter(&repodata)
+        };
+        rattler_solve::resolvo::Solver.solve(task).map(|_| ())
+    })
+    .await
+    .context("The solver panicked")?;
+
+    match result {
+        Ok(()) => Ok(SolveOutcome::Solvable),
+        Err(SolveError::Unsolvable(messages)) => {
+            Ok(SolveOutcome::Unsolvable(solver_error(&messages.join("\n"))))
+        }
+        Err(SolveError::Cancelled) => Ok(SolveOutcome::TimedOut),
+        Err(e) => Err(e).with_context(|| {
+            format!(
+                "Failed to solve {} {} on {}",
+                candidate.name, candidate.version, candidate.platform
+            )
+        }),
+    }
+}
+
+/// The gist of a solver error: its first line, without an `Error:` prefix
+/// and the decorations of an error report
+pub fn solver_error(stderr: &str) -> String {
+    stderr
+        .lines()
+        .map(|line| {
+            line.trim_start()
+                .trim_start_matches("Error:")
+                .trim_matches(|c: char| c.is_whitespace() || "×│├╰─▶".contains(c))
+        })
+        .find(|line| !line.is_empty())
+        .unwrap_or("the solve failed")
+        .to_string()
+}
+
+/// Trial-solve every conda update of a report on its platform, up to `jobs`
+/// at once, marking those that would leave the environment unsolvable.
+/// `environments` holds what the environment is solved from on each
+/// platform; updates on other platforms aren't tried. Solves that time out
+/// leave their update unmarked, with a warning.
+pub async fn verify_solvable(
+    report: &mut Report,
+    gateway: &Gateway,
+    environments: &HashMap<String, SolveEnvironment>,
+    jobs: usize,
+) -> Result<()> {
+    let outcomes: Vec<(Candidate, Result<SolveOutcome>)> =
+        stream::iter(candidates(report).into_iter().filter_map(|candidate| {
+            let environment = environments.get(&candidate.platform)?;
+            Some(async move {
+                let outcome = trial_solve(gateway, environment, &candidate).await;
+                (candidate, outcome)
+            })
+        }))
+        .buffer_unordered(jobs.max(1))
+        .collect()
+        .await;
+
+    let mut unsolvable = BTreeMap::new();
+    for (candidate, outcome) in outcomes {
+        match outcome? {
+            SolveOutcome::Solvable => {}
+            SolveOutcome::Unsolvable(reason) => {
+                unsolvable.insert(
+                    (candidate.name, candidate.version, candidate.platform),
+                    reason,
+                );
+            }
+            SolveOutcome::TimedOut => eprintln!(
+                "Warning: gave up solving {} {} on {} after {}s",
+                candidate.name,
+                candidate.version,
+                candidate.platform,
+                SOLVE_TIMEOUT.as_secs()
+            ),
+        }
+    }
+    mark_unsolvable(report, &unsolvable);
+    Ok(())
+}
+
+/// Mark the updates to unsolvable versions, keyed by name, version and
+/// platform
+fn mark_unsolvable(report: &mut Report, unsolvable: &BTreeMap<(String, String, String), String>) {
+    for (platform, updates) in report.platforms.iter_mut() {
+        for update in updates {
+            let key = (
+                update.name.clone(),
+                update.latest_version.clone(),
+                platform.clone(),
+            );
+            update.unsolvable = unsolvable.get(&key).cloned();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::EnvironmentFeatures;
+    use crate::report::{PackageUpdate, PlatformUpdates};
+
+    fn report() -> Report {
+        let python = PackageUpdate {
+            name: "python".to_string(),
+            kind: Some(PackageKind::Conda),
+            installed_version: "3.12.11".to_string(),
+            latest_version: "3.14.0".to_string(),
+            direct: true,
+            ..Default::default()
+        };
+        let pytest = PackageUpdate {
+            name: "pytest".to_string(),
+            kind: Some(PackageKind::Conda),
+            installed_version: "7.4.0".to_string(),
+            latest_version: "8.3.0".to_string(),
+            direct: true,
+            features: vec!["test".to_string()],
+            ..Default::default()
+        };
+        let requests = PackageUpdate {
+            name: "requests".to_string(),
+            kind: Some(PackageKind::Pypi),
+            installed_version: "2.31.0".to_string(),
+            latest_version: "2.32.3".to_string(),
+            ..Default::default()
+        };
+        let mut platforms = PlatformUpdates::new();
+        platforms.insert(
+            "linux-64".to_string(),
+            vec![python.clone(), pytest, requests],
+        );
+        platforms.insert("osx-arm64".to_string(), vec![python]);
+        Report {
+            platforms,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_candidates() {
+        let candidates = candidates(&report());
+        let names: Vec<(&str, &str)> = candidates
+            .iter()
+            .map(|c| (c.name.as_str(), c.platform.as_str()))
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                ("pytest", "linux-64"),
+                ("python", "linux-64"),
+                ("python", "osx-arm64")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_requirements() {
+        let specs = DependencySpecs::parse(
+            r#"
+[dependencies]
+python = ">=3.12,<3.14"
+numpy = "*"
+
+[target.osx-arm64.dependencies]
+python = "3.12.*"
+"#,
+            false,
+            &EnvironmentFeatures::All,
+        )
+        .unwrap();
+        let record = |name: &str| RepoDataRecord {
+            package_record: rattler_conda_types::PackageRecord::new(
+                PackageName::try_from(name).unwrap(),
+                rattler_conda_types::Version::from_str("1.0").unwrap(),
+                "0".to_string(),
+            ),
+            file_name: format!("{}-1.0-0.conda", name),
+            url: url::Url::parse("https://conda.anaconda.org/conda-forge/linux-64/x.conda")
+                .unwrap(),
+            channel: Some("https://conda.anaconda.org/conda-forge/".to_string()),
+        };
+        let locked = [record("python"), record("numpy"), record("openssl")];
+
+        let linux = requirements(&specs, &locked, "linux-64").unwrap();
+        let names: Vec<&str> = linux
+            .iter()
+            .filter_map(|spec| spec.name.as_ref().map(PackageName::as_normalized))
+            .collect();
+        assert_eq!(names, vec!["python", "numpy"]);
+        assert!(linux[0].to_string().contains("<3.14"));
+
+        let osx = requirements(&specs, &locked, "osx-arm64").unwrap();
+        assert!(osx.iter().any(|spec| spec.to_string().contains("3.12.*")));
+    }
+
+    #[test]
+    fn test_mark_unsolvable() {
+        let mut report = report();
+        let unsolvable = BTreeMap::from([(
+            (
+                "python".to_string(),
+                "3.14.0".to_string(),
+                "linux-64".to_string(),
+            ),
+            "failed to solve the conda requirements of 'default' 'linux-64'".to_string(),
+        )]);
+        mark_unsolvable(&mut report, &unsolvable);
+        assert!(report.platforms["linux-64"][0].unsolvable.is_some());
+        assert!(report.platforms["linux-64"][1].unsolvable.is_none());
+        // Only the platform the solve failed on
+        assert!(report.platforms["osx-arm64"][0].unsolvable.is_none());
+    }
+
+    #[test]
+    fn test_solver_error() {
+        let stderr = "Error:   × failed to solve the conda requirements of 'default' 'linux-64'\n  ├─▶ Cannot solve the request because of: The following packages are incompatible\n";
+        assert_eq!(
+            solver_error(stderr),
+            "failed to solve the conda requirements of 'default' 'linux-64'"
+        );
+        assert_eq!(solver_error(""), "the solve failed");
+    }
+}
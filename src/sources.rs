@@ -0,0 +1,346 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use crate::locale::Locale;
+use crate::pixi::PackageKind;
+
+/// Health statistics for a single channel or package index
+#[derive(Debug, Serialize, Clone, Deserialize, PartialEq, Eq)]
+pub struct SourceStats {
+    pub url: String,
+    pub kind: PackageKind,
+    pub requests: u64,
+    pub cache_hits: u64,
+    pub failures: u64,
+    pub total_ms: u64,
+    /// Bytes of metadata downloaded from this source, where it can be measured
+    #[serde(default)]
+    pub bytes: u64,
+    /// Queries answered by a repodata query batched with other channels
+    /// checked for the same packages
+    #[serde(default)]
+    pub batched: u64,
+}
+
+/// How a single query against a source turned out
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueryOutcome {
+    Fetched,
+    CacheHit,
+    Failed,
+}
+
+/// Collects per-source statistics over the course of a run
+#[derive(Debug, Default)]
+pub struct SourceTracker {
+    stats: BTreeMap<String, SourceStats>,
+}
+
+impl SourceTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn entry(&mut self, url: &str, kind: PackageKind) -> &mut SourceStats {
+        self.stats
+            .entry(url.to_string())
+            .or_insert_with(|| SourceStats {
+                url: url.to_string(),
+                kind,
+                requests: 0,
+                cache_hits: 0,
+                failures: 0,
+                total_ms: 0,
+                bytes: 0,
+                batched: 0,
+            })
+    }
+
+    pub fn record(
+        &mut self,
+        url: &str,
+        kind: PackageKind,
+        elapsed: Duration,
+        outcome: QueryOutcome,
+    ) {
+        let stats = self.entry(url, kind);
+
+        stats.requests += 1;
+        stats.total_ms += u64::try_from(elapsed.as_millis()).unwrap_or(u64::MAX);
+        match outcome {
+            QueryOutcome::Fetched => {}
+            QueryOutcome::CacheHit => stats.cache_hits += 1,
+            QueryOutcome::Failed => stats.failures += 1,
+        }
+    }
+
+    /// Count a query that was answered together with other channels
+    pub fn record_batched(&mut self, url: &str, kind: PackageKind) {
+        self.entry(url, kind).batched += 1;
+    }
+
+    /// Attribute downloaded bytes to a source
+    pub fn add_bytes(&mut self, url: &str, kind: PackageKind, bytes: u64) {
+        self.entry(url, kind).bytes += bytes;
+    }
+
+    /// The collected statistics, ordered by source URL
+    pub fn into_stats(self) -> Vec<SourceStats> {
+        self.stats.into_values().collect()
+    }
+}
+
+impl SourceStats {
+    /// A one-line summary with numbers formatted for `locale`
+    pub fn describe(&self, locale: &Locale) -> String {
+        let kind = match self.kind {
+            PackageKind::Conda => "conda",
+            PackageKind::Pypi => "pypi",
+        };
+        let mut line = format!(
+            "{} ({}): {} requests, {} cache hits, {} failures, {}s",
+            self.url,
+            kind,
+            locale.format_count(self.requests),
+            locale.format_count(self.cache_hits),
+            locale.format_count(self.failures),
+            locale.format_decimal(self.total_ms as f64 / 1000.0)
+        );
+        if self.batched > 0 {
+            line.push_str(&format!(", {} batched", locale.format_count(self.batched)));
+        }
+        if self.bytes > 0 {
+            line.push_str(&format!(", {} downloaded", locale.format_size(self.bytes)));
+        }
+        line
+    }
+}
+
+impl std::fmt::Display for SourceStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.describe(&Locale::default()))
+    }
+}
+
+/// The repodata of one channel subdir, fetched once ahead of the queries
+/// that read it
+#[derive(Debug, Serialize, Clone, Deserialize, PartialEq, Eq)]
+pub struct RepodataFetch {
+    pub channel: String,
+    pub subdir: String,
+    pub total_ms: u64,
+    /// Already fetched for an earlier environment of the run, and reused
+    #[serde(default)]
+    pub shared: bool,
+    /// The fetch failed; the queries reading the subdir try again
+    #[serde(default)]
+    pub failed: bool,
+    /// Bytes the gateway downloaded, 0 when pixi's repodata cache was fresh
+    #[serde(default)]
+    pub bytes: u64,
+}
+
+impl RepodataFetch {
+    /// A one-line summary with numbers formatted for `locale`
+    pub fn describe(&self, locale: &Locale) -> String {
+        let subdir = format!("{}/{}", self.channel.trim_end_matches('/'), self.subdir);
+        let seconds = locale.format_decimal(self.total_ms as f64 / 1000.0);
+        if self.shared {
+            format!("{}: shared with an earlier environment", subdir)
+        } else if self.failed {
+            format!("{}: failed after {}s", subdir, seconds)
+        } else {
+            format!("{}: {}s", subdir, seconds)
+        }
+    }
+}
+
+impl std::fmt::Display for RepodataFetch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.describe(&Locale::default()))
+    }
+}
+
+/// Bytes of package metadata transferred during a run
+#[derive(Debug, Serialize, Clone, Copy, Default, Deserialize, PartialEq, Eq)]
+pub struct NetworkUsage {
+    /// Bytes of PyPI JSON metadata downloaded
+    pub pypi_bytes: u64,
+    /// Bytes of repodata downloaded, summed over the fetched subdirs
+    pub repodata_bytes: u64,
+}
+
+impl NetworkUsage {
+    pub fn total(&self) -> u64 {
+        self.pypi_bytes + self.repodata_bytes
+    }
+}
+
+impl NetworkUsage {
+    /// A one-line summary with sizes formatted for `locale`
+    pub fn describe(&self, locale: &Locale) -> String {
+        format!(
+            "Downloaded {} of metadata ({} repodata, {} PyPI)",
+            locale.format_size(self.total()),
+            locale.format_size(self.repodata_bytes),
+            locale.format_size(self.pypi_bytes)
+        )
+    }
+}
+
+impl std::fmt::Display for NetworkUsage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.describe(&Locale::default()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tracker_aggregates_per_source() {
+        let mut tracker = SourceTracker::new();
+        let channel = "https://conda.anaconda.org/conda-forge";
+
+        tracker.record(
+            channel,
+            PackageKind::Conda,
+            Duration::from_millis(1500),
+            QueryOutcome::Fetched,
+        );
+        tracker.record(
+            channel,
+            PackageKind::Conda,
+            Duration::from_millis(5),
+            QueryOutcome::CacheHit,
+        );
+        tracker.record(
+            "https://pypi.org/pypi",
+            PackageKind::Pypi,
+            Duration::from_millis(200),
+            QueryOutcome::Failed,
+        );
+
+        let stats = tracker.into_stats();
+        assert_eq!(stats.len(), 2);
+
+        let conda = &stats[0];
+        assert_eq!(conda.url, channel);
+        assert_eq!(conda.requests, 2);
+        assert_eq!(conda.cache_hits, 1);
+        assert_eq!(conda.failures, 0);
+        assert_eq!(conda.total_ms, 1505);
+
+        let pypi = &stats[1];
+        assert_eq!(pypi.kind, PackageKind::Pypi);
+        assert_eq!(pypi.failures, 1);
+    }
+
+    #[test]
+    fn test_tracker_bytes() {
+        let mut tracker = SourceTracker::new();
+        tracker.add_bytes("https://pypi.org/pypi", PackageKind::Pypi, 1000);
+        tracker.add_bytes("https://pypi.org/pypi", PackageKind::Pypi, 536);
+
+        let stats = tracker.into_stats();
+        assert_eq!(stats[0].bytes, 1536);
+        assert!(stats[0].to_string().ends_with(", 1.5 kB downloaded"));
+    }
+
+    #[test]
+    fn test_tracker_batched() {
+        let mut tracker = SourceTracker::new();
+        let channel = "https://conda.anaconda.org/pytorch";
+        tracker.record(
+            channel,
+            PackageKind::Conda,
+            Duration::from_millis(5),
+            QueryOutcome::CacheHit,
+        );
+        tracker.record_batched(channel, PackageKind::Conda);
+
+        let stats = tracker.into_stats();
+        assert_eq!(stats[0].batched, 1);
+        assert_eq!(
+            stats[0].to_string(),
+            "https://conda.anaconda.org/pytorch (conda): 1 requests, 1 cache hits, 0 failures, 0s, 1 batched"
+        );
+    }
+
+    #[test]
+    fn test_network_usage_display() {
+        let usage = NetworkUsage {
+            pypi_bytes: 512_000,
+            repodata_bytes: 2_000_000,
+        };
+        assert_eq!(
+            usage.to_string(),
+            "Downloaded 2.5 MB of metadata (2.0 MB repodata, 512 kB PyPI)"
+        );
+    }
+
+    #[test]
+    fn test_source_stats_display() {
+        let stats = SourceStats {
+            url: "https://pypi.org/pypi".to_string(),
+            kind: PackageKind::Pypi,
+            requests: 3,
+            cache_hits: 0,
+            failures: 1,
+            total_ms: 2500,
+            bytes: 0,
+            batched: 0,
+        };
+        assert_eq!(
+            stats.to_string(),
+            "https://pypi.org/pypi (pypi): 3 requests, 0 cache hits, 1 failures, 2.5s"
+        );
+        assert_eq!(
+            stats.describe(&Locale::parse("de").unwrap()),
+            "https://pypi.org/pypi (pypi): 3 requests, 0 cache hits, 1 failures, 2,5s"
+        );
+    }
+
+    #[test]
+    fn test_repodata_fetch_display() {
+        let mut fetch = RepodataFetch {
+            channel: "https://conda.anaconda.org/conda-forge/".to_string(),
+            subdir: "linux-64".to_string(),
+            total_ms: 1300,
+            shared: false,
+            failed: false,
+            bytes: 0,
+        };
+        assert_eq!(
+            fetch.to_string(),
+            "https://conda.anaconda.org/conda-forge/linux-64: 1.3s"
+        );
+
+        fetch.failed = true;
+        assert!(fetch.to_string().ends_with(": failed after 1.3s"));
+
+        fetch.shared = true;
+        assert_eq!(
+            fetch.to_string(),
+            "https://conda.anaconda.org/conda-forge/linux-64: shared with an earlier environment"
+        );
+    }
+
+    #[test]
+    fn test_source_stats_serialization() {
+        let stats = SourceStats {
+            url: "https://conda.anaconda.org/conda-forge".to_string(),
+            kind: PackageKind::Conda,
+            requests: 1,
+            cache_hits: 0,
+            failures: 0,
+            total_ms: 10,
+            bytes: 0,
+            batched: 0,
+        };
+        let json = serde_json::to_string(&stats).unwrap();
+        assert!(json.contains(r#""kind":"conda""#));
+    }
+}
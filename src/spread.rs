@@ -0,0 +1,296 @@
+use rattler_conda_types::Version;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write as _;
+use std::str::FromStr;
+
+use crate::parser::PixiLock;
+use crate::pixi::PackageKind;
+use crate::report::EnvironmentReports;
+
+/// A package that environments of the same lockfile lock at different
+/// versions on the same platform, which is rarely intended
+#[derive(Debug, Serialize, Clone, Deserialize, PartialEq, Eq)]
+pub struct VersionSpread {
+    pub name: String,
+    pub kind: PackageKind,
+    pub min_version: String,
+    pub max_version: String,
+    /// The environments locking each version, from oldest to newest
+    pub versions: Vec<SpreadVersion>,
+    /// The platforms on which the environments disagree
+    pub platforms: Vec<String>,
+    /// The version to lock in every environment: the latest one, if a
+    /// newer version than all locked ones was found, else the newest locked
+    pub consolidate_on: String,
+}
+
+#[derive(Debug, Serialize, Clone, Deserialize, PartialEq, Eq)]
+pub struct SpreadVersion {
+    pub version: String,
+    pub environments: Vec<String>,
+}
+
+impl VersionSpread {
+    /// The environments that would change to consolidate on one version
+    pub fn behind(&self) -> Vec<&str> {
+        self.versions
+            .iter()
+            .filter(|version| version.version != self.consolidate_on)
+            .flat_map(|version| version.environments.iter().map(String::as_str))
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect()
+    }
+}
+
+impl std::fmt::Display for VersionSpread {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let versions: Vec<String> = self
+            .versions
+            .iter()
+            .map(|version| format!("{} ({})", version.version, version.environments.join(", ")))
+            .collect();
+        write!(
+            f,
+            "{}: {}; consolidate on {} in {}",
+            self.name,
+            versions.join(", "),
+            self.consolidate_on,
+            self.behind().join(", ")
+        )
+    }
+}
+
+/// Compare two locked versions the way their package kind orders them.
+/// Versions that don't parse are compared as strings.
+fn compare_versions(kind: PackageKind, a: &str, b: &str) -> Ordering {
+    let ordering = match kind {
+        PackageKind::Conda => match (Version::from_str(a), Version::from_str(b)) {
+            (Ok(a), Ok(b)) => Some(a.cmp(&b)),
+            _ => None,
+        },
+        PackageKind::Pypi => match (
+            pep440_rs::Version::from_str(a),
+            pep440_rs::Version::from_str(b),
+        ) {
+            (Ok(a), Ok(b)) => Some(a.cmp(&b)),
+            _ => None,
+        },
+    };
+    ordering.unwrap_or_else(|| a.cmp(b))
+}
+
+/// The packages whose locked version differs between `environments` on
+/// some platform, limited to `platform` if given and to the packages
+/// `include` accepts
+pub fn version_spreads(
+    lock: &PixiLock,
+    environments: &[String],
+    platform: Option<&str>,
+    include: impl Fn(&str) -> bool,
+) -> Vec<VersionSpread> {
+    let platforms: BTreeSet<String> = environments
+        .iter()
+        .flat_map(|environment| lock.platforms(environment))
+        .filter(|name| platform.is_none_or(|platform| platform == name))
+        .collect();
+
+    // name -> (kind, version -> environments, platforms)
+    type Spread = (
+        PackageKind,
+        BTreeMap<String, BTreeSet<String>>,
+        BTreeSet<String>,
+    );
+    let mut spreads: BTreeMap<String, Spread> = BTreeMap::new();
+    for platform in &platforms {
+        let mut locked: BTreeMap<String, (PackageKind, BTreeMap<String, BTreeSet<String>>)> =
+            BTreeMap::new();
+        for environment in environments {
+            for package in lock.packages(environment, platform) {
+                if !include(&package.name) {
+                    continue;
+                }
+                locked
+                    .entry(package.name)
+                    .or_insert_with(|| (package.kind, BTreeMap::new()))
+                    .1
+                    .entry(package.version)
+                    .or_default()
+                    .insert(environment.clone());
+            }
+        }
+
+        for (name, (kind, versions)) in locked.into_iter().filter(|(_, (_, v))| v.len() > 1) {
+            let spread = spreads
+                .entry(name)
+                .or_insert_with(|| (kind, BTreeMap::new(), BTreeSet::new()));
+            for (version, environments) in versions {
+                spread.1.entry(version).or_default().extend(environments);
+            }
+            spread.2.insert(platform.clone());
+        }
+    }
+
+    spreads
+        .into_iter()
+        .map(|(name, (kind, versions, platforms))| {
+            let mut versions: Vec<SpreadVersion> = versions
+                .into_iter()
+                .map(|(version, environments)| SpreadVersion {
+                    version,
+                    environments: environments.into_iter().collect(),
+                })
+                .collect();
+            versions.sort_by(|a, b| compare_versions(kind, &a.version, &b.version));
+            let min_version = versions[0].version.clone();
+            let max_version = versions[versions.len() - 1].version.clone();
+            VersionSpread {
+                name,
+                kind,
+                min_version,
+                consolidate_on: max_version.clone(),
+                max_version,
+                versions,
+                platforms: platforms.into_iter().collect(),
+            }
+        })
+        .collect()
+}
+
+/// Recommend consolidating on the latest version the reports found for a
+/// package, where it's newer than every locked version
+pub fn consolidate_on_latest(spreads: &mut [VersionSpread], reports: &EnvironmentReports) {
+    for spread in spreads {
+        let latest = reports
+            .environments
+            .values()
+            .flat_map(|report| report.platforms.values().flatten())
+            .filter(|update| update.name == spread.name)
+            .map(|update| update.latest_version.as_str())
+            .max_by(|a, b| compare_versions(spread.kind, a, b));
+        if let Some(latest) = latest {
+            if compare_versions(spread.kind, latest, &spread.max_version).is_gt() {
+                spread.consolidate_on = latest.to_string();
+            }
+        }
+    }
+}
+
+/// Render the spreads as a markdown table
+pub fn render_markdown(spreads: &[VersionSpread]) -> String {
+    let mut out = String::from("## Versions differing between environments\n\n");
+    out.push_str("| Package | Min | Max | Locked in | Consolidate on |\n");
+    out.push_str("| --- | --- | --- | --- | --- |\n");
+    for spread in spreads {
+        let locked: Vec<String> = spread
+            .versions
+            .iter()
+            .map(|version| format!("{}: {}", version.version, version.environments.join(", ")))
+            .collect();
+        let _ = writeln!(
+            out,
+            "| {} | {} | {} | {} | {} |",
+            spread.name,
+            spread.min_version,
+            spread.max_version,
+            locked.join("; "),
+            spread.consolidate_on
+        );
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::{PackageUpdate, PlatformUpdates, Report};
+
+    const LOCK: &str = r#"
+version: 6
+environments:
+  default:
+    channels:
+    - url: https://conda.anaconda.org/conda-forge/
+    packages:
+      linux-64:
+      - conda: https://conda.anaconda.org/conda-forge/linux-64/numpy-1.26.4-py312_0.conda
+      - conda: https://conda.anaconda.org/conda-forge/linux-64/zlib-1.3.1-h0_0.conda
+      osx-arm64:
+      - conda: https://conda.anaconda.org/conda-forge/osx-arm64/numpy-1.26.4-py312_0.conda
+  test:
+    channels:
+    - url: https://conda.anaconda.org/conda-forge/
+    packages:
+      linux-64:
+      - conda: https://conda.anaconda.org/conda-forge/linux-64/numpy-1.9.0-py312_0.conda
+      - conda: https://conda.anaconda.org/conda-forge/linux-64/zlib-1.3.1-h0_0.conda
+      osx-arm64:
+      - conda: https://conda.anaconda.org/conda-forge/osx-arm64/numpy-1.26.4-py312_0.conda
+packages:
+- conda: https://conda.anaconda.org/conda-forge/linux-64/numpy-1.26.4-py312_0.conda
+- conda: https://conda.anaconda.org/conda-forge/linux-64/numpy-1.9.0-py312_0.conda
+- conda: https://conda.anaconda.org/conda-forge/linux-64/zlib-1.3.1-h0_0.conda
+- conda: https://conda.anaconda.org/conda-forge/osx-arm64/numpy-1.26.4-py312_0.conda
+"#;
+
+    fn environments() -> Vec<String> {
+        vec!["default".to_string(), "test".to_string()]
+    }
+
+    #[test]
+    fn test_version_spreads() {
+        let lock = PixiLock::parse(LOCK).unwrap();
+        let spreads = version_spreads(&lock, &environments(), None, |_| true);
+
+        assert_eq!(spreads.len(), 1);
+        let spread = &spreads[0];
+        assert_eq!(spread.name, "numpy");
+        assert_eq!(spread.min_version, "1.9.0");
+        assert_eq!(spread.max_version, "1.26.4");
+        assert_eq!(spread.platforms, vec!["linux-64"]);
+        assert_eq!(spread.consolidate_on, "1.26.4");
+        assert_eq!(
+            spread.to_string(),
+            "numpy: 1.9.0 (test), 1.26.4 (default); consolidate on 1.26.4 in test"
+        );
+    }
+
+    #[test]
+    fn test_version_spreads_filters() {
+        let lock = PixiLock::parse(LOCK).unwrap();
+        assert!(version_spreads(&lock, &environments(), Some("osx-arm64"), |_| true).is_empty());
+        assert!(version_spreads(&lock, &environments(), None, |name| name != "numpy").is_empty());
+        assert!(version_spreads(&lock, &environments()[..1], None, |_| true).is_empty());
+    }
+
+    #[test]
+    fn test_consolidate_on_latest() {
+        let lock = PixiLock::parse(LOCK).unwrap();
+        let mut spreads = version_spreads(&lock, &environments(), None, |_| true);
+
+        let mut platforms = PlatformUpdates::new();
+        platforms.insert(
+            "linux-64".to_string(),
+            vec![PackageUpdate {
+                name: "numpy".to_string(),
+                installed_version: "1.9.0".to_string(),
+                latest_version: "2.1.0".to_string(),
+                ..Default::default()
+            }],
+        );
+        let mut reports = EnvironmentReports::default();
+        reports.environments.insert(
+            "test".to_string(),
+            Report {
+                platforms,
+                ..Report::default()
+            },
+        );
+
+        consolidate_on_latest(&mut spreads, &reports);
+        assert_eq!(spreads[0].consolidate_on, "2.1.0");
+        assert_eq!(spreads[0].behind(), vec!["default", "test"]);
+    }
+}
@@ -0,0 +1,95 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use crate::audit_log::AuditRecord;
+
+/// How long sending the statistics of a run may take
+const SEND_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Where run statistics are collected, configured in
+/// `[tool.pixi-outdated.stats]`. Nothing is sent unless an endpoint is set.
+#[derive(Debug, Default, Clone, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct StatsConfig {
+    /// The URL each run summary is POSTed to as JSON
+    #[serde(default)]
+    pub endpoint: Option<String>,
+    /// A name for the project, to tell repositories apart in the collected
+    /// statistics
+    #[serde(default)]
+    pub project: Option<String>,
+}
+
+/// The summary of a run sent to the stats endpoint. It holds counts only:
+/// no package names, paths, commits or user details.
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+pub struct RunStats {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub project: Option<String>,
+    #[serde(flatten)]
+    pub record: AuditRecord,
+}
+
+impl RunStats {
+    pub fn new(config: &StatsConfig, mut record: AuditRecord) -> Self {
+        record.git_sha = None;
+        Self {
+            project: config.project.clone(),
+            record,
+        }
+    }
+}
+
+/// POST the statistics of a run to the configured endpoint, if any
+pub async fn send(client: &reqwest::Client, config: &StatsConfig, stats: &RunStats) -> Result<()> {
+    let Some(ref endpoint) = config.endpoint else {
+        return Ok(());
+    };
+
+    client
+        .post(endpoint.as_str())
+        .timeout(SEND_TIMEOUT)
+        .json(stats)
+        .send()
+        .await
+        .and_then(reqwest::Response::error_for_status)
+        .with_context(|| format!("Failed to send run statistics to {}", endpoint))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::Report;
+    use chrono::Utc;
+
+    #[test]
+    fn test_run_stats_are_anonymous() {
+        let config = StatsConfig {
+            endpoint: Some("https://metrics.example.com/pixi-outdated".to_string()),
+            project: Some("backend/api".to_string()),
+        };
+        let record = AuditRecord::new(
+            &Report::default(),
+            "default",
+            Some("0123abc".to_string()),
+            Utc::now(),
+        );
+
+        let json = serde_json::to_value(RunStats::new(&config, record)).unwrap();
+        assert_eq!(json["project"], "backend/api");
+        assert_eq!(json["environment"], "default");
+        assert_eq!(json["outdated"], 0);
+        assert!(json.get("git_sha").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_send_without_endpoint() {
+        let record = AuditRecord::new(&Report::default(), "default", None, Utc::now());
+        let stats = RunStats::new(&StatsConfig::default(), record);
+        send(&reqwest::Client::new(), &StatsConfig::default(), &stats)
+            .await
+            .unwrap();
+    }
+}
@@ -0,0 +1,594 @@
+use anyhow::{Context, Result};
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+use std::ops::Range;
+use toml_edit::{Array, DocumentMut, Item, Table, Value};
+
+use crate::pixi::PackageKind;
+use crate::plan::PlannedUpdate;
+use crate::pypi::dependency_name;
+use crate::severity::numeric_components;
+
+/// A version requirement in the manifest that was bumped
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpecChange {
+    pub name: String,
+    pub kind: PackageKind,
+    pub old_spec: String,
+    pub new_spec: String,
+}
+
+impl fmt::Display for SpecChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {} -> {}", self.name, self.old_spec, self.new_spec)
+    }
+}
+
+/// Compare two versions by their numeric components, padding the shorter one with zeros
+fn compare(a: &str, b: &str) -> std::cmp::Ordering {
+    let (a, b) = (numeric_components(a), numeric_components(b));
+    let len = a.len().max(b.len());
+    (0..len)
+        .map(|idx| {
+            a.get(idx)
+                .copied()
+                .unwrap_or(0)
+                .cmp(&b.get(idx).copied().unwrap_or(0))
+        })
+        .find(|ordering| ordering.is_ne())
+        .unwrap_or(std::cmp::Ordering::Equal)
+}
+
+/// The first `len` components of `latest`, e.g. `3.14.0` with a length of 2 is `3.14`
+fn truncate(latest: &str, len: usize) -> String {
+    let mut components = numeric_components(latest);
+    components.resize(len.max(1), 0);
+    components
+        .iter()
+        .map(u64::to_string)
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+/// The exclusive upper bound that keeps the granularity of `bound` but admits `latest`,
+/// e.g. `<3.13` becomes `<3.15` for `3.14.0`
+fn next_upper_bound(bound: &str, latest: &str) -> String {
+    let mut components = numeric_components(latest);
+    components.resize(numeric_components(bound).len().max(1), 0);
+    if let Some(last) = components.last_mut() {
+        *last += 1;
+    }
+    components
+        .iter()
+        .map(u64::to_string)
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+/// Rewrite a single constraint such as `>=3.12` so that it admits `latest`
+fn bump_constraint(constraint: &str, latest: &str) -> String {
+    let operators = ["==", ">=", "<=", "~=", "!=", "<", ">", "="];
+    let (operator, version) = operators
+        .iter()
+        .find_map(|op| constraint.strip_prefix(op).map(|rest| (*op, rest.trim())))
+        .unwrap_or(("", constraint));
+
+    match operator {
+        ">=" => format!(">={}", latest),
+        "<" if compare(latest, version).is_ge() => {
+            format!("<{}", next_upper_bound(version, latest))
+        }
+        "<=" if compare(latest, version).is_gt() => format!("<={}", latest),
+        "~=" => format!("~={}", truncate(latest, numeric_components(version).len())),
+        "==" | "=" | "" => match version.strip_suffix(".*") {
+            // Wildcards keep their granularity, e.g. `3.12.*` becomes `3.14.*`
+            Some(prefix) => format!(
+                "{}{}.*",
+                operator,
+                truncate(latest, numeric_components(prefix).len())
+            ),
+            None if version == "*" => constraint.to_string(),
+            None => format!("{}{}", operator, latest),
+        },
+        _ => constraint.to_string(),
+    }
+}
+
+/// Rewrite a version requirement so that it admits `latest`, raising lower
+/// bounds to it. Returns `None` if the requirement doesn't need to change.
+pub fn bump_spec(spec: &str, latest: &str) -> Option<String> {
+    let bumped = spec
+        .split(',')
+        .map(|constraint| {
+            // Keep the whitespace around each constraint as written
+            let trimmed = constraint.trim();
+            let leading = &constraint[..constraint.len() - constraint.trim_start().len()];
+            let trailing = &constraint[constraint.trim_end().len()..];
+            format!(
+                "{}{}{}",
+                leading,
+                bump_constraint(trimmed, latest),
+                trailing
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(",");
+
+    (bumped != spec).then_some(bumped)
+}
+
+/// Bump the version requirement of a dependency entry, which is either a
+/// string or a table with a `version` key. Returns the old and new spec.
+fn bump_entry(entry: &mut Item, latest: &str) -> Option<(String, String)> {
+    let value = match entry {
+        Item::Value(Value::InlineTable(table)) => table.get_mut("version")?,
+        Item::Table(table) => table.get_mut("version")?.as_value_mut()?,
+        Item::Value(value) => value,
+        _ => return None,
+    };
+
+    let old_spec = value.as_str()?.to_string();
+    let new_spec = bump_spec(&old_spec, latest)?;
+
+    // Replace the string but keep the comments and whitespace around it
+    let decor = value.decor().clone();
+    *value = Value::from(new_spec.clone());
+    *value.decor_mut() = decor;
+
+    Some((old_spec, new_spec))
+}
+
+/// Bump every dependency in `table[key]` that has a latest version
+fn bump_table(
+    table: &mut Table,
+    key: &str,
+    kind: PackageKind,
+    latest: &HashMap<(String, PackageKind), String>,
+    changes: &mut Vec<SpecChange>,
+) {
+    let Some(dependencies) = table.get_mut(key).and_then(Item::as_table_like_mut) else {
+        return;
+    };
+
+    for (name, entry) in dependencies.iter_mut() {
+        let Some(latest) = latest.get(&(name.get().to_string(), kind)) else {
+            continue;
+        };
+        if let Some((old_spec, new_spec)) = bump_entry(entry, latest) {
+            changes.push(SpecChange {
+                name: name.get().to_string(),
+                kind,
+                old_spec,
+                new_spec,
+            });
+        }
+    }
+}
+
+/// Bump the dependency tables of the manifest root or a feature, including
+/// their `[target.<platform>]` tables
+fn bump_dependency_tables(
+    table: &mut Table,
+    latest: &HashMap<(String, PackageKind), String>,
+    changes: &mut Vec<SpecChange>,
+) {
+    bump_table(table, "dependencies", PackageKind::Conda, latest, changes);
+    bump_table(
+        table,
+        "pypi-dependencies",
+        PackageKind::Pypi,
+        latest,
+        changes,
+    );
+
+    if let Some(targets) = table.get_mut("target").and_then(Item::as_table_mut) {
+        for (_, target) in targets.iter_mut() {
+            if let Some(target) = target.as_table_mut() {
+                bump_table(target, "dependencies", PackageKind::Conda, latest, changes);
+                bump_table(
+                    target,
+                    "pypi-dependencies",
+                    PackageKind::Pypi,
+                    latest,
+                    changes,
+                );
+            }
+        }
+    }
+}
+
+/// The version each package's requirement can be bumped to: the oldest of
+/// its latest versions across platforms, so the requirement still resolves
+/// on each of them. Packages that `checked` found up to date on a platform
+/// have no newer version available everywhere and are left out.
+pub fn common_versions(
+    updates: &[PlannedUpdate],
+    checked: &BTreeSet<(String, PackageKind, String)>,
+) -> HashMap<(String, PackageKind), String> {
+    let mut versions: HashMap<(String, PackageKind), String> = HashMap::new();
+    for update in updates {
+        versions
+            .entry((update.name.clone(), update.kind))
+            .and_modify(|version| {
+                if compare(&update.to_version, version).is_lt() {
+                    *version = update.to_version.clone();
+                }
+            })
+            .or_insert_with(|| update.to_version.clone());
+    }
+
+    versions.retain(|(name, kind), _| {
+        checked
+            .iter()
+            .filter(|(_, checked_kind, checked_name)| checked_kind == kind && checked_name == name)
+            .all(|(platform, _, _)| {
+                updates.iter().any(|update| {
+                    update.name == *name && update.kind == *kind && update.platform == *platform
+                })
+            })
+    });
+    versions
+}
+
+/// The name of a PEP 508 requirement such as
+/// `requests[socks]>=2.0 ; python_version < "3.13"` and the byte range of
+/// its version specifier, or `None` if it has none
+fn requirement_parts(requirement: &str) -> Option<(&str, Range<usize>)> {
+    let body = requirement.split(';').next().unwrap_or_default();
+    // Direct references like `pkg @ https://...` have no specifier to bump
+    if body.contains('@') {
+        return None;
+    }
+
+    let name_start = body.len() - body.trim_start().len();
+    let name_end = body[name_start..]
+        .find(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')))
+        .map_or(body.len(), |idx| name_start + idx);
+    let mut spec_start = name_end;
+    if body[name_end..].trim_start().starts_with('[') {
+        spec_start += body[name_end..].find(']')? + 1;
+    }
+    spec_start += body[spec_start..].len() - body[spec_start..].trim_start().len();
+    let mut spec_end = body.trim_end().len();
+
+    // The legacy `name (>=2.0)` form
+    if body[spec_start..spec_end].starts_with('(') && body[..spec_end].ends_with(')') {
+        spec_start += 1;
+        spec_end -= 1;
+    }
+
+    (name_start < name_end && spec_start < spec_end)
+        .then(|| (&body[name_start..name_end], spec_start..spec_end))
+}
+
+/// Bump the PEP 508 requirements in a `[project]` dependency array
+fn bump_requirements(
+    requirements: &mut Array,
+    latest: &HashMap<(String, PackageKind), String>,
+    changes: &mut Vec<SpecChange>,
+) {
+    for requirement in requirements.iter_mut() {
+        let Some(text) = requirement.as_str() else {
+            continue;
+        };
+        let Some((name, spec)) = requirement_parts(text) else {
+            continue;
+        };
+        let Some(latest) = dependency_name(name)
+            .and_then(|normalized| latest.get(&(normalized, PackageKind::Pypi)))
+        else {
+            continue;
+        };
+        let old_spec = &text[spec.clone()];
+        let Some(new_spec) = bump_spec(old_spec, latest) else {
+            continue;
+        };
+
+        changes.push(SpecChange {
+            name: name.to_string(),
+            kind: PackageKind::Pypi,
+            old_spec: old_spec.to_string(),
+            new_spec: new_spec.clone(),
+        });
+        let bumped = format!("{}{}{}", &text[..spec.start], new_spec, &text[spec.end..]);
+        let decor = requirement.decor().clone();
+        *requirement = Value::from(bumped);
+        *requirement.decor_mut() = decor;
+    }
+}
+
+/// Bump the PEP 621 dependencies of a pyproject manifest: the
+/// `[project.dependencies]` array and the arrays of
+/// `[project.optional-dependencies]`
+fn bump_project_dependencies(
+    document: &mut DocumentMut,
+    latest: &HashMap<(String, PackageKind), String>,
+    changes: &mut Vec<SpecChange>,
+) {
+    let Some(project) = document
+        .get_mut("project")
+        .and_then(Item::as_table_like_mut)
+    else {
+        return;
+    };
+
+    if let Some(dependencies) = project.get_mut("dependencies").and_then(Item::as_array_mut) {
+        bump_requirements(dependencies, latest, changes);
+    }
+
+    if let Some(extras) = project
+        .get_mut("optional-dependencies")
+        .and_then(Item::as_table_like_mut)
+    {
+        for (_, extra) in extras.iter_mut() {
+            if let Some(dependencies) = extra.as_array_mut() {
+                bump_requirements(dependencies, latest, changes);
+            }
+        }
+    }
+}
+
+/// Bump the version requirements of outdated dependencies in a manifest,
+/// preserving its formatting and comments. `latest` maps `(name, kind)` to
+/// the latest version of each outdated package.
+pub fn update_manifest(
+    content: &str,
+    is_pyproject: bool,
+    latest: &HashMap<(String, PackageKind), String>,
+) -> Result<(String, Vec<SpecChange>)> {
+    let mut document: DocumentMut = content.parse().context("Failed to parse manifest")?;
+    let mut changes = Vec::new();
+
+    if is_pyproject {
+        bump_project_dependencies(&mut document, latest, &mut changes);
+    }
+
+    let root = if is_pyproject {
+        match document
+            .get_mut("tool")
+            .and_then(|tool| tool.get_mut("pixi"))
+            .and_then(Item::as_table_mut)
+        {
+            Some(pixi) => pixi,
+            None => return Ok((document.to_string(), changes)),
+        }
+    } else {
+        document.as_table_mut()
+    };
+
+    bump_dependency_tables(root, latest, &mut changes);
+
+    if let Some(features) = root.get_mut("feature").and_then(Item::as_table_mut) {
+        for (_, feature) in features.iter_mut() {
+            if let Some(feature) = feature.as_table_mut() {
+                bump_dependency_tables(feature, latest, &mut changes);
+            }
+        }
+    }
+
+    Ok((document.to_string(), changes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bump_spec_ranges() {
+        assert_eq!(
+            bump_spec(">=3.12.0,<3.13", "3.14.0").as_deref(),
+            Some(">=3.14.0,<3.15")
+        );
+        assert_eq!(bump_spec(">=5.0, <6", "6.1").as_deref(), Some(">=6.1, <7"));
+        assert_eq!(bump_spec(">=1.0", "2.1").as_deref(), Some(">=2.1"));
+        assert_eq!(bump_spec("<=1.5", "1.7").as_deref(), Some("<=1.7"));
+    }
+
+    #[test]
+    fn test_bump_spec_pins_and_wildcards() {
+        assert_eq!(bump_spec("==73.2", "75.1").as_deref(), Some("==75.1"));
+        assert_eq!(bump_spec("1.2.3", "1.3.0").as_deref(), Some("1.3.0"));
+        assert_eq!(bump_spec("3.12.*", "3.14.0").as_deref(), Some("3.14.*"));
+        assert_eq!(bump_spec("~=1.4", "2.0.1").as_deref(), Some("~=2.0"));
+    }
+
+    #[test]
+    fn test_bump_spec_unchanged() {
+        assert_eq!(bump_spec("*", "1.0"), None);
+        assert_eq!(bump_spec(">=2.1", "2.1"), None);
+        assert_eq!(bump_spec("!=1.1", "2.0"), None);
+    }
+
+    #[test]
+    fn test_update_manifest_preserves_formatting() {
+        let manifest = r#"[workspace]
+channels = ["conda-forge"]
+
+[dependencies]
+# The interpreter
+python = ">=3.12.0,<3.13"   # keep in sync with CI
+rust = "*"
+pytorch = { version = ">=2.0", channel = "pytorch" }
+
+[target.osx-arm64.dependencies]
+icu = "==73.2"
+
+[pypi-dependencies]
+cowsay = ">=5.0, <6"
+"#;
+        let latest = HashMap::from([
+            (
+                ("python".to_string(), PackageKind::Conda),
+                "3.14.0".to_string(),
+            ),
+            (
+                ("rust".to_string(), PackageKind::Conda),
+                "1.90.0".to_string(),
+            ),
+            (
+                ("pytorch".to_string(), PackageKind::Conda),
+                "2.5.1".to_string(),
+            ),
+            (("icu".to_string(), PackageKind::Conda), "75.1".to_string()),
+            (("cowsay".to_string(), PackageKind::Pypi), "6.1".to_string()),
+        ]);
+
+        let (updated, changes) = update_manifest(manifest, false, &latest).unwrap();
+        assert_eq!(
+            updated,
+            r#"[workspace]
+channels = ["conda-forge"]
+
+[dependencies]
+# The interpreter
+python = ">=3.14.0,<3.15"   # keep in sync with CI
+rust = "*"
+pytorch = { version = ">=2.5.1", channel = "pytorch" }
+
+[target.osx-arm64.dependencies]
+icu = "==75.1"
+
+[pypi-dependencies]
+cowsay = ">=6.1, <7"
+"#
+        );
+
+        let changed: Vec<String> = changes.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            changed,
+            vec![
+                "python: >=3.12.0,<3.13 -> >=3.14.0,<3.15",
+                "pytorch: >=2.0 -> >=2.5.1",
+                "cowsay: >=5.0, <6 -> >=6.1, <7",
+                "icu: ==73.2 -> ==75.1",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_update_manifest_pyproject() {
+        let manifest = r#"[project]
+name = "example"
+
+[tool.pixi.feature.test.dependencies]
+pytest = ">=7"
+"#;
+        let latest = HashMap::from([(
+            ("pytest".to_string(), PackageKind::Conda),
+            "8.3.3".to_string(),
+        )]);
+
+        let (updated, changes) = update_manifest(manifest, true, &latest).unwrap();
+        assert!(updated.contains(r#"pytest = ">=8.3.3""#));
+        assert_eq!(changes.len(), 1);
+    }
+
+    #[test]
+    fn test_update_manifest_project_dependencies() {
+        let manifest = r#"[project]
+name = "example"
+dependencies = [
+    "Requests[socks] >=2.0,<3 ; python_version >= '3.9'",  # the HTTP client
+    "cowsay (>=5.0)",
+    "rich",
+    "mylib @ https://example.com/mylib-1.0.tar.gz",
+]
+
+[project.optional-dependencies]
+test = ["pytest>=7"]
+
+[tool.pixi.dependencies]
+python = ">=3.12"
+"#;
+        let latest = HashMap::from([
+            (
+                ("requests".to_string(), PackageKind::Pypi),
+                "3.1.0".to_string(),
+            ),
+            (("cowsay".to_string(), PackageKind::Pypi), "6.1".to_string()),
+            (("rich".to_string(), PackageKind::Pypi), "14.0".to_string()),
+            (("mylib".to_string(), PackageKind::Pypi), "2.0".to_string()),
+            (
+                ("pytest".to_string(), PackageKind::Pypi),
+                "8.3.3".to_string(),
+            ),
+        ]);
+
+        let (updated, changes) = update_manifest(manifest, true, &latest).unwrap();
+        assert_eq!(
+            updated,
+            r#"[project]
+name = "example"
+dependencies = [
+    "Requests[socks] >=3.1.0,<4 ; python_version >= '3.9'",  # the HTTP client
+    "cowsay (>=6.1)",
+    "rich",
+    "mylib @ https://example.com/mylib-1.0.tar.gz",
+]
+
+[project.optional-dependencies]
+test = ["pytest>=8.3.3"]
+
+[tool.pixi.dependencies]
+python = ">=3.12"
+"#
+        );
+        let changed: Vec<String> = changes.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            changed,
+            vec![
+                "Requests: >=2.0,<3 -> >=3.1.0,<4",
+                "cowsay: >=5.0 -> >=6.1",
+                "pytest: >=7 -> >=8.3.3",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_common_versions() {
+        let update = |name: &str, platform: &str, to_version: &str| PlannedUpdate {
+            name: name.to_string(),
+            kind: PackageKind::Conda,
+            platform: platform.to_string(),
+            from_version: "1.0".to_string(),
+            to_version: to_version.to_string(),
+        };
+        let updates = [
+            update("numpy", "linux-64", "2.3.4"),
+            update("numpy", "osx-arm64", "2.3.2"),
+            update("rust", "linux-64", "1.90.0"),
+        ];
+        let checked = BTreeSet::from([
+            (
+                "linux-64".to_string(),
+                PackageKind::Conda,
+                "numpy".to_string(),
+            ),
+            (
+                "osx-arm64".to_string(),
+                PackageKind::Conda,
+                "numpy".to_string(),
+            ),
+            (
+                "linux-64".to_string(),
+                PackageKind::Conda,
+                "rust".to_string(),
+            ),
+            // Up to date on osx-arm64, where no newer rust was found
+            (
+                "osx-arm64".to_string(),
+                PackageKind::Conda,
+                "rust".to_string(),
+            ),
+        ]);
+
+        let versions = common_versions(&updates, &checked);
+        assert_eq!(
+            versions,
+            HashMap::from([(
+                ("numpy".to_string(), PackageKind::Conda),
+                "2.3.2".to_string()
+            )])
+        );
+    }
+}
@@ -0,0 +1,691 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::candidates::Candidate;
+use crate::conda::CondaVersionInfo;
+use crate::pixi::{PackageKind, PixiPackage};
+use crate::pypi::PyPiVersionInfo;
+use crate::report::LockedStatus;
+use crate::sources::QueryOutcome;
+
+/// A package to query, unique per name, channel and kind
+#[derive(Debug, Hash, Eq, PartialEq, Clone, Serialize, Deserialize)]
+pub struct PackageKey {
+    pub name: String,
+    /// The channel of a conda package; PyPI packages have none
+    pub channel: Option<String>,
+    pub kind: PackageKind,
+}
+
+/// A file of a release that can be downloaded, e.g. a conda package or a wheel
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Artifact {
+    pub file_name: String,
+    pub url: String,
+    /// Hex-encoded, when the channel or index publishes one
+    pub sha256: Option<String>,
+}
+
+/// What a channel or index told us about a package. Conda channels and
+/// PyPI indexes have variants of their own; other sources answer with
+/// [`UpstreamInfo::Other`], so they don't have to touch this enum.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "info", rename_all = "lowercase")]
+#[non_exhaustive]
+pub enum UpstreamInfo {
+    Conda(CondaVersionInfo),
+    Pypi(PyPiVersionInfo),
+    /// The answer of any other source. It isn't serialized, so it's neither
+    /// cached nor saved in sessions.
+    #[serde(skip)]
+    Other(Arc<dyn PackageVersions>),
+}
+
+/// The versions of a package as a source other than a conda channel or a
+/// PyPI index knows them, answered as [`UpstreamInfo::Other`]. Only the
+/// latest version, version ordering and what happened to a locked version
+/// are required; everything else defaults to unknown.
+pub trait PackageVersions: std::fmt::Debug + Send + Sync {
+    fn latest_version(&self) -> Option<&str>;
+
+    /// Whether `version` sorts before `than`
+    fn is_older(&self, version: &str, than: &str) -> bool;
+
+    /// Whether the locked version is still available, yanked or gone
+    fn locked_status(&self, package: &PixiPackage) -> LockedStatus;
+
+    /// Every version the source knows of
+    fn versions(&self) -> Vec<&str> {
+        self.latest_version().into_iter().collect()
+    }
+
+    /// Whether a version requirement such as `<2` admits `version`.
+    /// Requirements the source doesn't understand rule nothing out.
+    fn allows(&self, _spec: &str, _version: &str) -> bool {
+        true
+    }
+
+    fn release_date(&self, _version: &str) -> Option<DateTime<Utc>> {
+        None
+    }
+
+    fn release_notes(&self) -> Option<&str> {
+        None
+    }
+
+    fn artifacts(&self, _version: &str, _subdir: &str) -> Vec<Artifact> {
+        Vec::new()
+    }
+
+    fn dependencies(&self, _version: &str, _subdir: Option<&str>) -> Option<Vec<String>> {
+        None
+    }
+
+    fn license(&self, _version: &str) -> Option<&str> {
+        None
+    }
+
+    fn candidates(&self) -> Vec<Candidate> {
+        Vec::new()
+    }
+}
+
+impl UpstreamInfo {
+    pub fn latest_version(&self) -> Option<&str> {
+        match self {
+            UpstreamInfo::Conda(info) => info.latest_version.as_deref(),
+            UpstreamInfo::Pypi(info) => Some(info.latest_version.as_str()),
+            UpstreamInfo::Other(info) => info.latest_version(),
+        }
+    }
+
+    /// Consider pre-releases for the latest version, as `--pre` does
+    pub fn with_prereleases(self) -> Self {
+        match self {
+            UpstreamInfo::Conda(info) => UpstreamInfo::Conda(info.with_prereleases()),
+            UpstreamInfo::Pypi(info) => UpstreamInfo::Pypi(info.with_prereleases()),
+            // Other sources decide themselves which versions they offer
+            other => other,
+        }
+    }
+
+    /// The latest version that installs on the given Python interpreter.
+    /// Only PyPI releases declare which interpreters they support.
+    pub fn latest_version_for(&self, python_version: Option<&str>) -> Option<&str> {
+        match (self, python_version) {
+            (UpstreamInfo::Pypi(info), Some(python_version)) => {
+                info.latest_for_python(python_version)
+            }
+            _ => self.latest_version(),
+        }
+    }
+
+    /// Whether `latest` is an update for the installed version, ordered by
+    /// PEP 440 for PyPI and by conda's version ordering for conda
+    pub fn is_update(&self, latest: &str, installed: &str) -> bool {
+        match self {
+            UpstreamInfo::Conda(_) => crate::conda::is_newer(latest, installed),
+            UpstreamInfo::Pypi(_) => crate::pypi::is_newer(latest, installed),
+            UpstreamInfo::Other(info) => info.is_older(installed, latest),
+        }
+    }
+
+    /// Whether `version` sorts before `than`, e.g. a latest version that is
+    /// older than the locked one
+    pub fn is_older(&self, version: &str, than: &str) -> bool {
+        match self {
+            UpstreamInfo::Conda(_) => crate::conda::is_older(version, than),
+            UpstreamInfo::Pypi(_) => crate::pypi::is_older(version, than),
+            UpstreamInfo::Other(info) => info.is_older(version, than),
+        }
+    }
+
+    /// Whether a version requirement such as `<2` admits `version`
+    pub fn allows(&self, spec: &str, version: &str) -> bool {
+        match self {
+            UpstreamInfo::Conda(_) => crate::conda::spec_matches(spec, version),
+            UpstreamInfo::Pypi(_) => crate::pypi::specifiers_match(spec, version),
+            UpstreamInfo::Other(info) => info.allows(spec, version),
+        }
+    }
+
+    /// The newest version allowed by the given manifest requirements
+    pub fn latest_matching(&self, specs: &[&str]) -> Option<String> {
+        match self {
+            UpstreamInfo::Conda(info) => info.latest_matching(specs),
+            UpstreamInfo::Pypi(info) => info.latest_matching(specs),
+            UpstreamInfo::Other(info) => info
+                .versions()
+                .into_iter()
+                .filter(|version| specs.iter().all(|spec| info.allows(spec, version)))
+                .reduce(|newest, version| {
+                    if info.is_older(newest, version) {
+                        version
+                    } else {
+                        newest
+                    }
+                })
+                .map(str::to_string),
+        }
+    }
+
+    pub fn release_date(&self, version: &str) -> Option<DateTime<Utc>> {
+        match self {
+            UpstreamInfo::Conda(info) => info.release_date(version),
+            UpstreamInfo::Pypi(info) => info.release_date(version),
+            UpstreamInfo::Other(info) => info.release_date(version),
+        }
+    }
+
+    /// Where the project publishes its release notes. Conda repodata doesn't
+    /// say; those come from the channel's `channeldata.json`.
+    pub fn release_notes(&self) -> Option<&str> {
+        match self {
+            UpstreamInfo::Conda(_) => None,
+            UpstreamInfo::Pypi(info) => info.release_notes.as_deref(),
+            UpstreamInfo::Other(info) => info.release_notes(),
+        }
+    }
+
+    /// When the first version newer than `installed`, up to `latest`, was
+    /// released: how long an update has been available
+    pub fn first_release_after(&self, installed: &str, latest: &str) -> Option<DateTime<Utc>> {
+        let versions: Vec<&str> = match self {
+            UpstreamInfo::Conda(info) => info.release_dates.keys().map(String::as_str).collect(),
+            UpstreamInfo::Pypi(info) => info.releases.keys().map(String::as_str).collect(),
+            UpstreamInfo::Other(info) => info.versions(),
+        };
+        versions
+            .into_iter()
+            .filter(|version| self.is_older(installed, version) && !self.is_older(latest, version))
+            .filter_map(|version| self.release_date(version))
+            .min()
+    }
+
+    /// Whole days between the releases of `installed` and `latest`, the
+    /// package's contribution to the libyears metric
+    pub fn release_lag_days(&self, installed: &str, latest: &str) -> Option<u32> {
+        let installed = self.release_date(installed)?;
+        let latest = self.release_date(latest)?;
+        Some(crate::age::age_in_days(installed, latest))
+    }
+
+    /// Artifact size of a conda version in a subdir; PyPI sizes are not tracked
+    pub fn artifact_size(&self, version: &str, subdir: &str) -> Option<u64> {
+        match self {
+            UpstreamInfo::Conda(info) => info.artifact_size(version, subdir),
+            UpstreamInfo::Pypi(_) | UpstreamInfo::Other(_) => None,
+        }
+    }
+
+    /// How many bytes larger the artifact of `latest` is than the locked
+    /// one, negative if smaller. Only known for conda packages whose locked
+    /// size is recorded.
+    pub fn size_delta(&self, package: &PixiPackage, latest: &str) -> Option<i64> {
+        let installed = package.size_bytes?;
+        let subdir = package
+            .source
+            .as_deref()
+            .and_then(crate::conda::extract_subdir)?;
+        let latest = self.artifact_size(latest, &subdir)?;
+        Some(latest as i64 - installed as i64)
+    }
+
+    /// The files of a version that installing it on `subdir` may download.
+    /// Every build of a conda version is included, and every file of a PyPI
+    /// release that isn't yanked.
+    pub fn artifacts(&self, version: &str, subdir: &str) -> Vec<Artifact> {
+        match self {
+            UpstreamInfo::Conda(info) => info.artifact_files(version, subdir),
+            UpstreamInfo::Pypi(info) => info.artifact_files(version),
+            UpstreamInfo::Other(info) => info.artifacts(version, subdir),
+        }
+    }
+
+    /// Names of the dependencies of a version, if known. Conda dependencies
+    /// are per subdir; PyPI indexes only describe the latest release.
+    pub fn dependencies(&self, version: &str, subdir: Option<&str>) -> Option<Vec<String>> {
+        match self {
+            UpstreamInfo::Conda(info) => info.dependencies(version, subdir?),
+            UpstreamInfo::Pypi(info) => info.dependencies(version),
+            UpstreamInfo::Other(info) => info.dependencies(version, subdir),
+        }
+    }
+
+    /// Releases between the installed and latest version that were yanked.
+    /// Conda channels don't yank, they remove.
+    pub fn yanked_between(&self, installed: &str, latest: &str) -> Vec<String> {
+        match self {
+            UpstreamInfo::Conda(_) | UpstreamInfo::Other(_) => Vec::new(),
+            UpstreamInfo::Pypi(info) => info.yanked_between(installed, latest),
+        }
+    }
+
+    pub fn candidates(&self) -> Vec<Candidate> {
+        match self {
+            UpstreamInfo::Conda(info) => info.candidates(),
+            UpstreamInfo::Pypi(info) => info.candidates(),
+            UpstreamInfo::Other(info) => info.candidates(),
+        }
+    }
+
+    /// The license a version is published under, if known. PyPI indexes
+    /// only describe the latest release.
+    pub fn license(&self, version: &str) -> Option<&str> {
+        match self {
+            UpstreamInfo::Conda(info) => info.license(version),
+            UpstreamInfo::Pypi(info) => info.license(version),
+            UpstreamInfo::Other(info) => info.license(version),
+        }
+    }
+
+    /// The newest version whose license is unknown or passes `allowed`, and
+    /// that installs on the given Python interpreter
+    pub fn latest_allowed_for(
+        &self,
+        allowed: &dyn Fn(&str) -> bool,
+        python_version: Option<&str>,
+    ) -> Option<String> {
+        match self {
+            UpstreamInfo::Conda(info) => info.latest_allowed(allowed),
+            UpstreamInfo::Pypi(info) => info
+                .latest_allowed(allowed, python_version)
+                .map(str::to_string),
+            // Without a license per version there is nothing to hold back to
+            UpstreamInfo::Other(info) => info.latest_version().map(str::to_string),
+        }
+    }
+
+    pub fn locked_status(&self, package: &PixiPackage) -> LockedStatus {
+        match self {
+            UpstreamInfo::Conda(info) => {
+                info.locked_status(&package.version, package.build.as_deref())
+            }
+            UpstreamInfo::Pypi(info) => info.locked_status(&package.version),
+            UpstreamInfo::Other(info) => info.locked_status(package),
+        }
+    }
+}
+
+/// The error of a source that has no record of a package, as opposed to one
+/// that couldn't be queried
+#[derive(Debug, thiserror::Error)]
+#[error("{name} was not found on {location}")]
+pub struct PackageNotFound {
+    pub name: String,
+    /// The channel or indexes that were searched
+    pub location: String,
+}
+
+/// What querying the sources found out about a package
+#[derive(Debug, Clone)]
+pub enum VersionLookup {
+    Found(UpstreamInfo),
+    /// The source answered, but has no versions of the package
+    NotFound,
+    /// The source couldn't be queried, e.g. because of a network error
+    Failed(String),
+}
+
+impl VersionLookup {
+    pub fn from_response(info: Result<UpstreamInfo>) -> Self {
+        match info {
+            Ok(info) if info.latest_version().is_none() => VersionLookup::NotFound,
+            Ok(info) => VersionLookup::Found(info),
+            Err(e) if e.downcast_ref::<PackageNotFound>().is_some() => VersionLookup::NotFound,
+            Err(e) => VersionLookup::Failed(format!("{:#}", e)),
+        }
+    }
+
+    pub fn info(&self) -> Option<&UpstreamInfo> {
+        match self {
+            VersionLookup::Found(info) => Some(info),
+            VersionLookup::NotFound | VersionLookup::Failed(_) => None,
+        }
+    }
+}
+
+/// The answer of a version source for a single package
+#[derive(Debug)]
+pub struct SourceResponse {
+    /// The channel or index that was queried, for the source statistics
+    pub source_url: String,
+    pub elapsed: Duration,
+    /// Whether the answer came from the network or a cache; ignored on errors
+    pub outcome: QueryOutcome,
+    /// Metadata downloaded for this query, if the source can tell
+    pub bytes: u64,
+    /// Answered by a query batched with other channels, which read their
+    /// repodata in one pass
+    pub batched: bool,
+    pub info: Result<UpstreamInfo>,
+}
+
+/// A backend that knows the available versions of packages, such as a conda
+/// channel or a Python package index
+#[async_trait]
+pub trait VersionSource: Send + Sync {
+    /// Query a batch of packages on the given platforms. Keys the source
+    /// doesn't handle, e.g. PyPI packages for a conda source, are left out
+    /// of the result.
+    async fn query(
+        &self,
+        keys: &[PackageKey],
+        platforms: &[&str],
+    ) -> Vec<(PackageKey, SourceResponse)>;
+
+    /// The same source bypassing its caches, to double-check answers that
+    /// look wrong. `None` for sources that don't cache.
+    fn refreshed(&self) -> Option<Box<dyn VersionSource>> {
+        None
+    }
+}
+
+/// How many packages a source queries at once unless told otherwise
+pub const DEFAULT_JOBS: usize = 10;
+
+/// Query every source, in order, for the keys it handles
+pub async fn query_all(
+    sources: &[Box<dyn VersionSource>],
+    keys: &[PackageKey],
+    platforms: &[&str],
+) -> Vec<(PackageKey, SourceResponse)> {
+    let mut responses = Vec::new();
+    for source in sources {
+        responses.extend(source.query(keys, platforms).await);
+    }
+    responses
+}
+
+/// Whether an answer looks like it came from stale or truncated metadata:
+/// the package is missing, or its latest version is older than the locked one
+fn is_suspicious(response: &SourceResponse, locked_version: Option<&str>) -> bool {
+    match response.info {
+        Ok(ref info) => match info.latest_version() {
+            Some(latest) => locked_version.is_some_and(|locked| info.is_older(latest, locked)),
+            None => true,
+        },
+        Err(ref e) => e.downcast_ref::<PackageNotFound>().is_some(),
+    }
+}
+
+/// Query the packages with a suspicious answer again, bypassing the caches
+/// of the sources, and use the fresh answers unless the queries fail.
+/// Returns the packages that were queried again.
+pub async fn recheck_suspicious(
+    sources: &[Box<dyn VersionSource>],
+    responses: &mut [(PackageKey, SourceResponse)],
+    locked_versions: &HashMap<PackageKey, String>,
+    platforms: &[&str],
+) -> Vec<PackageKey> {
+    let suspicious: Vec<PackageKey> = responses
+        .iter()
+        .filter(|(key, response)| {
+            is_suspicious(response, locked_versions.get(key).map(String::as_str))
+        })
+        .map(|(key, _)| key.clone())
+        .collect();
+    let refreshed: Vec<Box<dyn VersionSource>> = sources
+        .iter()
+        .filter_map(|source| source.refreshed())
+        .collect();
+    if suspicious.is_empty() || refreshed.is_empty() {
+        return Vec::new();
+    }
+
+    let mut fresh: HashMap<PackageKey, SourceResponse> =
+        query_all(&refreshed, &suspicious, platforms)
+            .await
+            .into_iter()
+            .collect();
+    let mut rechecked = Vec::new();
+    for (key, response) in responses.iter_mut() {
+        let Some(fresh) = fresh.remove(&*key) else {
+            continue;
+        };
+        rechecked.push(key.clone());
+        let answered = match fresh.info {
+            Ok(_) => true,
+            Err(ref e) => e.downcast_ref::<PackageNotFound>().is_some(),
+        };
+        if answered {
+            *response = fresh;
+        }
+    }
+    rechecked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::{HashMap, HashSet};
+
+    /// Answers conda queries from a fixed set of latest versions
+    struct MockSource {
+        latest: HashMap<String, String>,
+        /// Latest versions answered when bypassing the cache, if it has one
+        fresh: Option<HashMap<String, String>>,
+    }
+
+    #[async_trait]
+    impl VersionSource for MockSource {
+        async fn query(
+            &self,
+            keys: &[PackageKey],
+            _platforms: &[&str],
+        ) -> Vec<(PackageKey, SourceResponse)> {
+            keys.iter()
+                .filter(|key| key.kind == PackageKind::Conda)
+                .map(|key| {
+                    let info = match self.latest.get(&key.name) {
+                        Some(latest) => Ok(UpstreamInfo::Conda(CondaVersionInfo {
+                            latest_version: Some(latest.clone()),
+                            artifacts: HashSet::from([(latest.clone(), "h0_0".to_string())]),
+                            ..Default::default()
+                        })),
+                        None => Err(PackageNotFound {
+                            name: key.name.clone(),
+                            location: "mock".to_string(),
+                        }
+                        .into()),
+                    };
+                    let response = SourceResponse {
+                        source_url: "mock".to_string(),
+                        elapsed: Duration::ZERO,
+                        outcome: QueryOutcome::Fetched,
+                        bytes: 0,
+                        batched: false,
+                        info,
+                    };
+                    (key.clone(), response)
+                })
+                .collect()
+        }
+
+        fn refreshed(&self) -> Option<Box<dyn VersionSource>> {
+            let latest = self.fresh.clone()?;
+            Some(Box::new(MockSource {
+                latest,
+                fresh: None,
+            }))
+        }
+    }
+
+    fn key(name: &str, kind: PackageKind) -> PackageKey {
+        PackageKey {
+            name: name.to_string(),
+            channel: None,
+            kind,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_query_all_with_mock_source() {
+        let sources: Vec<Box<dyn VersionSource>> = vec![Box::new(MockSource {
+            latest: HashMap::from([("python".to_string(), "3.14.0".to_string())]),
+            fresh: None,
+        })];
+        let keys = [
+            key("python", PackageKind::Conda),
+            key("missing", PackageKind::Conda),
+            key("cowsay", PackageKind::Pypi),
+        ];
+
+        let responses = query_all(&sources, &keys, &["linux-64"]).await;
+
+        // The PyPI package isn't handled by the mock
+        assert_eq!(responses.len(), 2);
+        let (python, response) = &responses[0];
+        assert_eq!(python.name, "python");
+        let info = response.info.as_ref().unwrap();
+        assert_eq!(info.latest_version(), Some("3.14.0"));
+        assert!(info.candidates().iter().any(|c| c.version == "3.14.0"));
+        assert!(responses[1].1.info.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_recheck_suspicious() {
+        let sources: Vec<Box<dyn VersionSource>> = vec![Box::new(MockSource {
+            // A truncated cache that lost cowsay and newer pythons
+            latest: HashMap::from([
+                ("python".to_string(), "3.11.0".to_string()),
+                ("openssl".to_string(), "3.6.0".to_string()),
+            ]),
+            fresh: Some(HashMap::from([
+                ("python".to_string(), "3.14.0".to_string()),
+                ("cowsay".to_string(), "6.1".to_string()),
+            ])),
+        })];
+        let keys = [
+            key("python", PackageKind::Conda),
+            key("openssl", PackageKind::Conda),
+            key("cowsay", PackageKind::Conda),
+        ];
+        let locked = HashMap::from([
+            (keys[0].clone(), "3.12.11".to_string()),
+            (keys[1].clone(), "3.5.4".to_string()),
+            (keys[2].clone(), "6.0".to_string()),
+        ]);
+
+        let mut responses = query_all(&sources, &keys, &["linux-64"]).await;
+        let rechecked = recheck_suspicious(&sources, &mut responses, &locked, &["linux-64"]).await;
+
+        let names: Vec<&str> = rechecked.iter().map(|key| key.name.as_str()).collect();
+        assert_eq!(names, vec!["python", "cowsay"]);
+        let latest: Vec<Option<&str>> = responses
+            .iter()
+            .map(|(_, response)| response.info.as_ref().ok()?.latest_version())
+            .collect();
+        assert_eq!(latest, vec![Some("3.14.0"), Some("3.6.0"), Some("6.1")]);
+    }
+
+    #[tokio::test]
+    async fn test_recheck_suspicious_without_cache() {
+        let sources: Vec<Box<dyn VersionSource>> = vec![Box::new(MockSource {
+            latest: HashMap::new(),
+            fresh: None,
+        })];
+        let keys = [key("cowsay", PackageKind::Conda)];
+
+        let mut responses = query_all(&sources, &keys, &["linux-64"]).await;
+        let rechecked =
+            recheck_suspicious(&sources, &mut responses, &HashMap::new(), &["linux-64"]).await;
+        assert!(rechecked.is_empty());
+        assert!(responses[0].1.info.is_err());
+    }
+
+    #[test]
+    fn test_version_lookup_from_response() {
+        let found = VersionLookup::from_response(Ok(UpstreamInfo::Conda(CondaVersionInfo {
+            latest_version: Some("3.14.0".to_string()),
+            ..Default::default()
+        })));
+        assert!(found.info().is_some());
+
+        // A channel without any records of the package
+        let empty = VersionLookup::from_response(Ok(UpstreamInfo::Conda(Default::default())));
+        assert!(matches!(empty, VersionLookup::NotFound));
+
+        let not_found = VersionLookup::from_response(Err(PackageNotFound {
+            name: "missing".to_string(),
+            location: "https://pypi.org/pypi".to_string(),
+        }
+        .into()));
+        assert!(matches!(not_found, VersionLookup::NotFound));
+
+        let failed =
+            VersionLookup::from_response(Err(anyhow::anyhow!("connection refused")
+                .context("Failed to query channel conda-forge")));
+        match failed {
+            VersionLookup::Failed(message) => {
+                assert_eq!(
+                    message,
+                    "Failed to query channel conda-forge: connection refused"
+                )
+            }
+            other => panic!("expected a failure, got {:?}", other),
+        }
+    }
+
+    /// A source numbering its releases, e.g. the builds of an internal tool
+    #[derive(Debug)]
+    struct Builds(Vec<String>);
+
+    impl PackageVersions for Builds {
+        fn latest_version(&self) -> Option<&str> {
+            self.0.last().map(String::as_str)
+        }
+
+        fn is_older(&self, version: &str, than: &str) -> bool {
+            version.parse::<u32>().ok() < than.parse::<u32>().ok()
+        }
+
+        fn locked_status(&self, package: &PixiPackage) -> LockedStatus {
+            if self.0.contains(&package.version) {
+                LockedStatus::Available
+            } else {
+                LockedStatus::Removed
+            }
+        }
+
+        fn versions(&self) -> Vec<&str> {
+            self.0.iter().map(String::as_str).collect()
+        }
+
+        fn allows(&self, spec: &str, version: &str) -> bool {
+            spec.strip_prefix('<')
+                .is_none_or(|bound| self.is_older(version, bound))
+        }
+    }
+
+    #[test]
+    fn test_other_source() {
+        let builds = ["9", "10", "11"].map(str::to_string).to_vec();
+        let info = UpstreamInfo::Other(Arc::new(Builds(builds)));
+        assert_eq!(info.latest_version(), Some("11"));
+        assert!(info.is_update("11", "9"));
+        assert!(!info.is_update("9", "11"));
+        assert_eq!(info.latest_matching(&["<11"]).as_deref(), Some("10"));
+        assert_eq!(info.release_notes(), None);
+
+        let package = PixiPackage {
+            name: "tool".to_string(),
+            version: "8".to_string(),
+            build: None,
+            size_bytes: None,
+            kind: PackageKind::Conda,
+            source: None,
+            is_explicit: true,
+            depends: Vec::new(),
+        };
+        assert_eq!(info.locked_status(&package), LockedStatus::Removed);
+
+        // Answers of other sources are never written to the cache or a session
+        assert!(serde_json::to_string(&info).is_err());
+    }
+}
@@ -0,0 +1,279 @@
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use crate::oneline::OnelineSummary;
+use crate::report::Report;
+
+/// How long posting to a webhook may take
+const POST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How many updates a Slack message lists before summarizing the rest
+const SLACK_MAX_UPDATES: usize = 20;
+
+/// The payload a webhook expects
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum WebhookFormat {
+    /// A JSON summary of the updates, see [`WebhookSummary`]
+    Json,
+    /// A Slack incoming webhook message, also understood by Mattermost and
+    /// Rocket.Chat
+    Slack,
+}
+
+impl WebhookFormat {
+    /// Slack for Slack's own webhook URLs, JSON otherwise
+    pub fn for_url(url: &str) -> Self {
+        match url::Url::parse(url) {
+            Ok(url) if url.host_str() == Some("hooks.slack.com") => WebhookFormat::Slack,
+            _ => WebhookFormat::Json,
+        }
+    }
+}
+
+/// An update in a webhook payload, listed once with every platform and
+/// report it was found in
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+pub struct WebhookUpdate {
+    pub name: String,
+    pub installed_version: String,
+    pub latest_version: String,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub direct: bool,
+    pub platforms: Vec<String>,
+    /// The environments or projects the update was found in, when the run
+    /// checked several
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub reports: Vec<String>,
+}
+
+/// The JSON payload posted to a webhook
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+pub struct WebhookSummary {
+    /// The one-line summary of the run, e.g. `outdated: 1 major, 2 minor`
+    pub text: String,
+    pub tool_version: String,
+    pub updates: Vec<WebhookUpdate>,
+    pub locked_issues: usize,
+    pub vulnerable: usize,
+}
+
+impl WebhookSummary {
+    /// Summarize reports given as `(name, report)`; names are left out of
+    /// the updates when there's a single report
+    pub fn new<'a>(reports: impl IntoIterator<Item = (&'a str, &'a Report)>) -> Self {
+        let reports: Vec<(&str, &Report)> = reports.into_iter().collect();
+        let named = reports.len() > 1;
+        let oneline = OnelineSummary::from_reports(reports.iter().map(|(_, report)| *report));
+
+        let mut updates: BTreeMap<(&str, &str, &str), WebhookUpdate> = BTreeMap::new();
+        for (name, report) in &reports {
+            for (platform, platform_updates) in &report.platforms {
+                for update in platform_updates {
+                    let entry = updates
+                        .entry((
+                            update.name.as_str(),
+                            update.installed_version.as_str(),
+                            update.latest_version.as_str(),
+                        ))
+                        .or_insert_with(|| WebhookUpdate {
+                            name: update.name.clone(),
+                            installed_version: update.installed_version.clone(),
+                            latest_version: update.latest_version.clone(),
+                            direct: false,
+                            platforms: Vec::new(),
+                            reports: Vec::new(),
+                        });
+                    entry.direct |= update.direct;
+                    if !entry.platforms.contains(platform) {
+                        entry.platforms.push(platform.clone());
+                    }
+                    if named && !entry.reports.iter().any(|report| report == name) {
+                        entry.reports.push(name.to_string());
+                    }
+                }
+            }
+        }
+
+        Self {
+            text: oneline.to_string(),
+            tool_version: env!("CARGO_PKG_VERSION").to_string(),
+            updates: updates.into_values().collect(),
+            locked_issues: oneline.locked_issues,
+            vulnerable: oneline.vulnerable,
+        }
+    }
+
+    /// Whether there is anything to notify about
+    pub fn is_empty(&self) -> bool {
+        self.updates.is_empty() && self.locked_issues == 0 && self.vulnerable == 0
+    }
+
+    /// A Slack message listing the updates, direct dependencies in bold
+    pub fn to_slack(&self) -> serde_json::Value {
+        let mut lines = vec![format!("*pixi-outdated*: {}", self.text)];
+        for update in self.updates.iter().take(SLACK_MAX_UPDATES) {
+            let name = if update.direct {
+                format!("*{}*", update.name)
+            } else {
+                update.name.clone()
+            };
+            let mut where_found = update.platforms.clone();
+            where_found.extend(update.reports.iter().map(|report| format!("`{}`", report)));
+            lines.push(format!(
+                "• {} {} → {} ({})",
+                name,
+                update.installed_version,
+                update.latest_version,
+                where_found.join(", ")
+            ));
+        }
+        if self.updates.len() > SLACK_MAX_UPDATES {
+            lines.push(format!(
+                "…and {} more",
+                self.updates.len() - SLACK_MAX_UPDATES
+            ));
+        }
+        serde_json::json!({ "text": lines.join("\n") })
+    }
+}
+
+/// A webhook URL reduced to its scheme, host and port, for messages. The
+/// path and query of a webhook URL usually hold its secret.
+pub fn redact(url: &str) -> String {
+    match url::Url::parse(url) {
+        Ok(url) => match (url.host_str(), url.port()) {
+            (Some(host), Some(port)) => format!("{}://{}:{}", url.scheme(), host, port),
+            (Some(host), None) => format!("{}://{}", url.scheme(), host),
+            (None, _) => "the webhook".to_string(),
+        },
+        Err(_) => "the webhook".to_string(),
+    }
+}
+
+/// Post the summary of a run to a webhook, unless it found nothing
+pub async fn notify(
+    client: &reqwest::Client,
+    url: &str,
+    format: WebhookFormat,
+    summary: &WebhookSummary,
+) -> Result<()> {
+    if summary.is_empty() {
+        return Ok(());
+    }
+    let payload = match format {
+        WebhookFormat::Json => serde_json::to_value(summary)?,
+        WebhookFormat::Slack => summary.to_slack(),
+    };
+    client
+        .post(url)
+        .json(&payload)
+        .timeout(POST_TIMEOUT)
+        .send()
+        .await
+        .and_then(reqwest::Response::error_for_status)
+        // reqwest's errors name the full URL
+        .map_err(reqwest::Error::without_url)
+        .with_context(|| format!("Failed to notify the webhook at {}", redact(url)))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::{PackageUpdate, PlatformUpdates};
+
+    fn report(platforms: &[&str], direct: bool) -> Report {
+        let mut updates = PlatformUpdates::new();
+        for platform in platforms {
+            updates.insert(
+                platform.to_string(),
+                vec![PackageUpdate {
+                    name: "python".to_string(),
+                    installed_version: "3.12.11".to_string(),
+                    latest_version: "3.14.0".to_string(),
+                    direct,
+                    ..Default::default()
+                }],
+            );
+        }
+        Report {
+            platforms: updates,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_for_url() {
+        assert_eq!(
+            WebhookFormat::for_url("https://hooks.slack.com/services/T0/B0/x"),
+            WebhookFormat::Slack
+        );
+        assert_eq!(
+            WebhookFormat::for_url("https://ci.example.com/hooks/outdated"),
+            WebhookFormat::Json
+        );
+    }
+
+    #[test]
+    fn test_redact() {
+        assert_eq!(
+            redact("https://hooks.slack.com/services/T000/B000/XXXX"),
+            "https://hooks.slack.com"
+        );
+        assert_eq!(
+            redact("http://localhost:8080/hook?token=secret"),
+            "http://localhost:8080"
+        );
+        assert_eq!(redact("not a url/secret"), "the webhook");
+    }
+
+    #[tokio::test]
+    async fn test_notify_error_is_redacted() {
+        let summary = WebhookSummary::new([("default", &report(&["linux-64"], true))]);
+        let error = notify(
+            &reqwest::Client::new(),
+            "http://127.0.0.1:9/services/T000/B000/XXXX",
+            WebhookFormat::Json,
+            &summary,
+        )
+        .await
+        .unwrap_err();
+        let message = format!("{:#}", error);
+        assert!(message.contains("http://127.0.0.1:9"));
+        assert!(!message.contains("XXXX"));
+    }
+
+    #[test]
+    fn test_summary() {
+        let default = report(&["linux-64", "osx-arm64"], true);
+        let summary = WebhookSummary::new([("default", &default)]);
+        assert_eq!(summary.updates.len(), 1);
+        assert_eq!(summary.updates[0].platforms, ["linux-64", "osx-arm64"]);
+        assert!(summary.updates[0].reports.is_empty());
+        assert!(summary.updates[0].direct);
+        assert!(!summary.is_empty());
+
+        let lint = report(&["linux-64"], false);
+        let summary = WebhookSummary::new([("default", &default), ("lint", &lint)]);
+        assert_eq!(summary.updates[0].reports, ["default", "lint"]);
+
+        let up_to_date = Report::default();
+        assert!(WebhookSummary::new([("default", &up_to_date)]).is_empty());
+    }
+
+    #[test]
+    fn test_to_slack() {
+        let default = report(&["linux-64"], true);
+        let slack = WebhookSummary::new([("default", &default)]).to_slack();
+        let text = slack["text"].as_str().unwrap();
+        let mut lines = text.lines();
+        assert!(lines
+            .next()
+            .unwrap()
+            .starts_with("*pixi-outdated*: outdated: 1 "));
+        assert_eq!(lines.next(), Some("• *python* 3.12.11 → 3.14.0 (linux-64)"));
+        assert_eq!(lines.next(), None);
+    }
+}
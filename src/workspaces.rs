@@ -0,0 +1,141 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Directories never searched for workspaces: environments, build output
+/// and vendored dependencies, which can contain manifests of their own
+const SKIPPED_DIRS: &[&str] = &["node_modules", "target", "site-packages", "__pycache__"];
+
+/// Find the manifest of every pixi workspace under `root`, sorted by path.
+/// A directory with a `pixi.toml` is a workspace, as is one whose
+/// `pyproject.toml` has a `[tool.pixi.workspace]` table; `pixi.toml` wins if
+/// a directory has both, like pixi itself. Hidden directories such as `.pixi`
+/// and `.git` are skipped. Workspaces nested in other workspaces are found
+/// too.
+pub fn find_workspaces(root: &Path) -> Result<Vec<PathBuf>> {
+    let mut manifests = Vec::new();
+    let mut dirs = vec![root.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        let pixi_toml = dir.join("pixi.toml");
+        let pyproject_toml = dir.join("pyproject.toml");
+        if pixi_toml.is_file() {
+            manifests.push(pixi_toml);
+        } else if pyproject_toml.is_file() && is_pixi_pyproject(&pyproject_toml)? {
+            manifests.push(pyproject_toml);
+        }
+
+        let entries =
+            fs::read_dir(&dir).with_context(|| format!("Failed to read {}", dir.display()))?;
+        for entry in entries {
+            let entry = entry?;
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            if name.starts_with('.') || SKIPPED_DIRS.contains(&name.as_ref()) {
+                continue;
+            }
+            // Symlinked directories are not followed, so cycles can't occur
+            if entry.file_type()?.is_dir() {
+                dirs.push(entry.path());
+            }
+        }
+    }
+    manifests.sort();
+    Ok(manifests)
+}
+
+/// Whether a `pyproject.toml` configures a pixi workspace, in
+/// `[tool.pixi.workspace]` or the older `[tool.pixi.project]`
+pub fn is_pixi_pyproject(path: &Path) -> Result<bool> {
+    let content =
+        fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    // A malformed pyproject.toml of some other tool is not ours to report
+    let Ok(document) = toml::from_str::<toml::Table>(&content) else {
+        return Ok(false);
+    };
+    Ok(document
+        .get("tool")
+        .and_then(|tool| tool.get("pixi"))
+        .is_some_and(|pixi| pixi.get("workspace").or(pixi.get("project")).is_some()))
+}
+
+/// The name of a workspace in a combined report: the directory of its
+/// manifest relative to the scanned root, `.` for the root itself
+pub fn project_name(root: &Path, manifest: &Path) -> String {
+    let dir = manifest.parent().unwrap_or(manifest);
+    match dir.strip_prefix(root) {
+        Ok(relative) if relative.as_os_str().is_empty() => ".".to_string(),
+        Ok(relative) => relative
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/"),
+        Err(_) => dir.display().to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(path: &Path, content: &str) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn test_find_workspaces() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        write(&root.join("pixi.toml"), "");
+        write(&root.join("services/api/pixi.toml"), "");
+        write(
+            &root.join("services/worker/pyproject.toml"),
+            "[project]\nname = \"worker\"\n\n[tool.pixi.workspace]\nchannels = [\"conda-forge\"]\n",
+        );
+        // Python packages that don't use pixi
+        write(
+            &root.join("libs/shared/pyproject.toml"),
+            "[project]\nname = \"shared\"\n",
+        );
+        // Manifests in environments and hidden directories are ignored
+        write(&root.join(".pixi/envs/default/pixi.toml"), "");
+        write(&root.join("web/node_modules/pkg/pixi.toml"), "");
+
+        let manifests = find_workspaces(root).unwrap();
+        let names: Vec<String> = manifests
+            .iter()
+            .map(|manifest| project_name(root, manifest))
+            .collect();
+        assert_eq!(names, [".", "services/api", "services/worker"]);
+        assert!(manifests[2].ends_with("pyproject.toml"));
+    }
+
+    #[test]
+    fn test_is_pixi_pyproject() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("pyproject.toml");
+        for (content, expected) in [
+            ("[tool.pixi.project]\nchannels = []\n", true),
+            ("[tool.pixi.workspace]\nchannels = []\n", true),
+            ("[tool.ruff]\nline-length = 100\n", false),
+            ("not toml [", false),
+        ] {
+            fs::write(&path, content).unwrap();
+            assert_eq!(is_pixi_pyproject(&path).unwrap(), expected, "{}", content);
+        }
+    }
+
+    #[test]
+    fn test_project_name() {
+        let root = Path::new("/repo");
+        assert_eq!(project_name(root, Path::new("/repo/pixi.toml")), ".");
+        assert_eq!(
+            project_name(root, Path::new("/repo/services/api/pixi.toml")),
+            "services/api"
+        );
+        assert_eq!(
+            project_name(root, Path::new("/elsewhere/pixi.toml")),
+            "/elsewhere"
+        );
+    }
+}
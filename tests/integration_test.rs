@@ -172,3 +172,2207 @@ fn test_multiple_packages() {
         .assert()
         .success();
 }
+
+#[test]
+fn test_output_files() {
+    let manifest_path = get_example_path("pixi.toml");
+    let dir = tempfile::tempdir().unwrap();
+    let json_path = dir.path().join("report.json");
+    let markdown_path = dir.path().join("summary.md");
+
+    cmd()
+        .arg("--manifest")
+        .arg(manifest_path)
+        .arg("--json-out")
+        .arg(&json_path)
+        .arg("--markdown-out")
+        .arg(&markdown_path)
+        .assert()
+        .success();
+
+    let json = std::fs::read_to_string(&json_path).unwrap();
+    let _: serde_json::Value = serde_json::from_str(&json).expect("Report should be valid JSON");
+
+    let markdown = std::fs::read_to_string(&markdown_path).unwrap();
+    assert!(markdown.starts_with("## Outdated packages"));
+}
+
+#[test]
+fn test_junit_out() {
+    let manifest_path = get_example_path("pixi.toml");
+    let dir = tempfile::tempdir().unwrap();
+    let junit_path = dir.path().join("outdated.xml");
+
+    cmd()
+        .arg("--manifest")
+        .arg(manifest_path)
+        .arg("--source")
+        .arg(fixtures_source())
+        .arg("--platform")
+        .arg("linux-64")
+        .arg("--junit-out")
+        .arg(&junit_path)
+        .arg("python")
+        .assert()
+        .success();
+
+    let xml = std::fs::read_to_string(&junit_path).unwrap();
+    assert!(xml.contains("<testsuite name=\"linux-64\" tests=\"1\" failures=\"1\" skipped=\"0\">"));
+    assert!(xml.contains("<failure message=\"python 3.12.11 -&gt; 3.14.0\""));
+}
+
+#[test]
+fn test_stdin_packages() {
+    // No workspace is needed, so run somewhere without one
+    let dir = tempfile::tempdir().unwrap();
+
+    let output = cmd()
+        .current_dir(dir.path())
+        .arg("--stdin-packages")
+        .arg("--source")
+        .arg(fixtures_source())
+        .arg("--platform")
+        .arg("linux-64")
+        .arg("--json")
+        .write_stdin(
+            r#"[{"name": "python", "version": "3.12.11", "kind": "conda", "channel": "conda-forge"}]"#,
+        )
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["schema_version"], 2);
+    let updates = json["platforms"]["linux-64"].as_array().unwrap();
+    assert_eq!(updates[0]["name"], "python");
+    assert_eq!(updates[0]["latest_version"], "3.14.0");
+
+    cmd()
+        .current_dir(dir.path())
+        .arg("--stdin-packages")
+        .write_stdin("not json")
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains(
+            "Failed to parse the packages on stdin",
+        ));
+}
+
+#[test]
+fn test_global() {
+    let dir = tempfile::tempdir().unwrap();
+
+    let output = cmd()
+        .current_dir(dir.path())
+        .env("PIXI_HOME", get_fixtures_path().join("pixi-home"))
+        .arg("--global")
+        .arg("--source")
+        .arg(fixtures_source())
+        .arg("--platform")
+        .arg("linux-64")
+        .arg("--json")
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let updates = json["environments"]["python"]["platforms"]["linux-64"]
+        .as_array()
+        .unwrap();
+    assert_eq!(updates[0]["name"], "python");
+    assert_eq!(updates[0]["latest_version"], "3.14.0");
+    assert_eq!(updates[0]["direct"], true);
+
+    cmd()
+        .current_dir(dir.path())
+        .env("PIXI_HOME", dir.path())
+        .arg("--global")
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("pixi-global.toml"));
+}
+
+#[test]
+fn test_stdin_packages_and_global_selection() {
+    let dir = tempfile::tempdir().unwrap();
+
+    // Python 3.14 is a minor update
+    let output = cmd()
+        .current_dir(dir.path())
+        .arg("--stdin-packages")
+        .arg("--source")
+        .arg(fixtures_source())
+        .arg("--platform")
+        .arg("linux-64")
+        .arg("--filter")
+        .arg("major")
+        .arg("--json")
+        .write_stdin(
+            r#"[{"name": "python", "version": "3.12.11", "kind": "conda", "channel": "conda-forge"}]"#,
+        )
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert!(json["platforms"]["linux-64"]
+        .as_array()
+        .is_none_or(|updates| updates.is_empty()));
+
+    // The ignore file of the current directory applies to stdin packages
+    std::fs::write(dir.path().join(".pixi-outdated-ignore"), "python\n").unwrap();
+    let output = cmd()
+        .current_dir(dir.path())
+        .arg("--stdin-packages")
+        .arg("--source")
+        .arg(fixtures_source())
+        .arg("--platform")
+        .arg("linux-64")
+        .arg("--json")
+        .write_stdin(
+            r#"[{"name": "python", "version": "3.12.11", "kind": "conda", "channel": "conda-forge"}]"#,
+        )
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert!(json["platforms"]["linux-64"]
+        .as_array()
+        .is_none_or(|updates| updates.is_empty()));
+
+    let output = cmd()
+        .current_dir(dir.path())
+        .env("PIXI_HOME", get_fixtures_path().join("pixi-home"))
+        .arg("--global")
+        .arg("--source")
+        .arg(fixtures_source())
+        .arg("--platform")
+        .arg("linux-64")
+        .arg("--exclude")
+        .arg("python")
+        .arg("--json")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert!(json["environments"]["python"]["platforms"]["linux-64"]
+        .as_array()
+        .is_none_or(|updates| updates.iter().all(|update| update["name"] != "python")));
+}
+
+#[test]
+fn test_recursive() {
+    let dir = tempfile::tempdir().unwrap();
+    for project in ["app", "services/api"] {
+        let project_dir = dir.path().join(project);
+        std::fs::create_dir_all(&project_dir).unwrap();
+        std::fs::copy(get_example_path("pixi.toml"), project_dir.join("pixi.toml")).unwrap();
+        std::fs::copy(get_example_path("pixi.lock"), project_dir.join("pixi.lock")).unwrap();
+    }
+
+    let output = cmd()
+        .arg("--recursive")
+        .arg(dir.path())
+        .arg("--source")
+        .arg(fixtures_source())
+        .arg("--platform")
+        .arg("linux-64")
+        .arg("--json")
+        .arg("python")
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    for project in ["app", "services/api"] {
+        let updates = json["projects"][project]["platforms"]["linux-64"]
+            .as_array()
+            .unwrap();
+        assert_eq!(updates[0]["name"], "python");
+        assert_eq!(updates[0]["latest_version"], "3.14.0");
+    }
+
+    for option in ["--json-out", "--markdown-out", "--junit-out", "--hash-file"] {
+        cmd()
+            .arg("--recursive")
+            .arg(dir.path())
+            .arg(option)
+            .arg("report.out")
+            .assert()
+            .failure()
+            .stderr(predicates::str::contains("cannot be used with"));
+    }
+
+    let empty = tempfile::tempdir().unwrap();
+    cmd()
+        .arg("--recursive")
+        .arg(empty.path())
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("No pixi workspaces found"));
+}
+
+#[test]
+fn test_report_html_conflicts_with_combined_reports() {
+    for option in ["--all-environments", "--global", "--recursive"] {
+        cmd()
+            .arg(option)
+            .arg("--report-html")
+            .arg("outdated.html")
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("cannot be used with"));
+    }
+}
+
+#[test]
+fn test_report_html() {
+    let manifest_path = get_example_path("pixi.toml");
+    let dir = tempfile::tempdir().unwrap();
+    let html_path = dir.path().join("outdated.html");
+
+    cmd()
+        .arg("--manifest")
+        .arg(manifest_path)
+        .arg("--source")
+        .arg(fixtures_source())
+        .arg("--report-html")
+        .arg(&html_path)
+        .assert()
+        .success();
+
+    let html = std::fs::read_to_string(&html_path).unwrap();
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(html.contains(">linux-64 ("));
+    assert!(html.contains(">osx-arm64 ("));
+    assert!(html.contains("<td>python</td>"));
+}
+
+#[test]
+fn test_invalid_platform_suggests_fix() {
+    let manifest_path = get_example_path("pixi.toml");
+
+    cmd()
+        .arg("--manifest")
+        .arg(manifest_path)
+        .arg("--platform")
+        .arg("linux64")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Did you mean 'linux-64'?"));
+}
+
+#[test]
+fn test_unlocked_platform_lists_locked_platforms() {
+    let manifest_path = get_example_path("pixi.toml");
+
+    cmd()
+        .arg("--manifest")
+        .arg(manifest_path)
+        .arg("--platform")
+        .arg("win-64")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Locked platforms: linux-64, osx-arm64",
+        ));
+}
+
+#[test]
+fn test_stale_locked_platform_is_not_checked() {
+    let dir = example_workspace_with("");
+    let manifest_path = dir.path().join("pixi.toml");
+    let manifest = std::fs::read_to_string(&manifest_path).unwrap();
+    std::fs::write(
+        &manifest_path,
+        manifest.replace(
+            r#"platforms = ["osx-arm64", "linux-64"]"#,
+            r#"platforms = ["osx-arm64"]"#,
+        ),
+    )
+    .unwrap();
+
+    // The lockfile still has linux-64 packages
+    cmd()
+        .arg("--manifest")
+        .arg(&manifest_path)
+        .arg("--source")
+        .arg(fixtures_source())
+        .arg("--platform")
+        .arg("linux-64")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Environment 'default' is not solved for platform 'linux-64', only for: osx-arm64",
+        ));
+
+    cmd()
+        .arg("--manifest")
+        .arg(&manifest_path)
+        .arg("--source")
+        .arg(fixtures_source())
+        .arg("python")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("python: 3.12.12 -> 3.14.0"))
+        .stdout(predicate::str::contains("3.12.11").not());
+}
+
+/// Helper to create a workspace without a lockfile
+fn workspace_without_lockfile() -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(
+        dir.path().join("pixi.toml"),
+        r#"[workspace]
+name = "no-lockfile"
+channels = ["conda-forge"]
+platforms = ["linux-64"]
+
+[dependencies]
+python = "*"
+"#,
+    )
+    .unwrap();
+    dir
+}
+
+#[test]
+fn test_missing_lockfile_exit_code() {
+    let dir = workspace_without_lockfile();
+
+    let output = cmd()
+        .arg("--manifest")
+        .arg(dir.path().join("pixi.toml"))
+        .arg("--json")
+        .assert()
+        .code(5);
+
+    let stdout = String::from_utf8(output.get_output().stdout.clone()).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(json["status"], "lockfile_missing");
+}
+
+#[test]
+fn test_missing_lockfile_allow_empty() {
+    let dir = workspace_without_lockfile();
+
+    cmd()
+        .arg("--manifest")
+        .arg(dir.path().join("pixi.toml"))
+        .arg("--allow-empty")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No lockfile found"));
+}
+
+#[test]
+fn test_all_packages_skipped_exit_code() {
+    let manifest_path = get_example_path("pixi.toml");
+
+    cmd()
+        .arg("--manifest")
+        .arg(manifest_path)
+        .arg("package-that-does-not-exist")
+        .assert()
+        .code(4);
+}
+
+#[test]
+fn test_invalid_max_lag() {
+    let manifest_path = get_example_path("pixi.toml");
+
+    cmd()
+        .arg("--manifest")
+        .arg(manifest_path)
+        .arg("--max-lag")
+        .arg("ninety days")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid lag"));
+}
+
+#[test]
+fn test_jobs_must_be_positive() {
+    cmd()
+        .arg("--manifest")
+        .arg(get_example_path("pixi.toml"))
+        .arg("--jobs")
+        .arg("0")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--jobs"));
+}
+
+#[test]
+fn test_max_attempts_must_be_positive() {
+    cmd()
+        .arg("--manifest")
+        .arg(get_example_path("pixi.toml"))
+        .arg("--max-attempts")
+        .arg("0")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--max-attempts"));
+}
+
+#[test]
+fn test_timeout_must_be_positive() {
+    cmd()
+        .arg("--manifest")
+        .arg(get_example_path("pixi.toml"))
+        .arg("--timeout")
+        .arg("0")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--timeout"));
+}
+
+#[test]
+fn test_proxy_must_be_a_url() {
+    cmd()
+        .arg("--manifest")
+        .arg(get_example_path("pixi.toml"))
+        .arg("--proxy")
+        .arg("not a url")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--proxy"));
+}
+
+#[test]
+fn test_why_latest() {
+    let manifest_path = get_example_path("pixi.toml");
+
+    cmd()
+        .arg("--manifest")
+        .arg(manifest_path)
+        .arg("--why-latest")
+        .arg("cowsay")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Candidates for cowsay (pypi):"))
+        .stdout(predicate::str::contains("selected (latest)"));
+}
+
+#[test]
+fn test_risk_profile_groups_updates() {
+    let manifest_path = get_example_path("pixi.toml");
+
+    cmd()
+        .arg("--manifest")
+        .arg(manifest_path)
+        .arg("--risk-profile")
+        .arg("container")
+        .arg("python")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("=== Needs"));
+}
+
+#[test]
+fn test_risk_profile_json() {
+    let manifest_path = get_example_path("pixi.toml");
+
+    let output = cmd()
+        .arg("--manifest")
+        .arg(manifest_path)
+        .arg("--risk-profile")
+        .arg("container")
+        .arg("--json")
+        .arg("python")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let updates = report["platforms"].as_object().unwrap();
+    assert!(updates
+        .values()
+        .flat_map(|updates| updates.as_array().unwrap())
+        .all(|update| update["risk"].is_string()));
+}
+
+#[test]
+fn test_plan_out() {
+    let manifest_path = get_example_path("pixi.toml");
+    let dir = tempfile::tempdir().unwrap();
+    let plan_path = dir.path().join("plan.json");
+
+    cmd()
+        .arg("--manifest")
+        .arg(manifest_path)
+        .arg("--plan-out")
+        .arg(&plan_path)
+        .arg("python")
+        .assert()
+        .success();
+
+    let plan: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&plan_path).unwrap()).unwrap();
+    assert_eq!(plan["format_version"], 1);
+    assert_eq!(plan["environment"], "default");
+    assert!(plan["updates"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .all(|update| update["name"] == "python" && update["from_version"].is_string()));
+}
+
+#[test]
+fn test_apply_stale_plan() {
+    let manifest_path = get_example_path("pixi.toml");
+    let dir = tempfile::tempdir().unwrap();
+    let plan_path = dir.path().join("plan.json");
+    std::fs::write(
+        &plan_path,
+        r#"{
+            "format_version": 1,
+            "environment": "default",
+            "updates": [
+                { "name": "python", "kind": "conda", "platform": "linux-64", "from_version": "0.0.1", "to_version": "3.14.0" }
+            ]
+        }"#,
+    )
+    .unwrap();
+
+    cmd()
+        .arg("apply")
+        .arg("--plan")
+        .arg(&plan_path)
+        .arg("--manifest")
+        .arg(manifest_path)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "The lockfile changed since the plan was written",
+        ));
+}
+
+#[test]
+fn test_apply_empty_plan() {
+    let dir = tempfile::tempdir().unwrap();
+    let plan_path = dir.path().join("plan.json");
+    std::fs::write(
+        &plan_path,
+        r#"{ "format_version": 1, "environment": "default", "updates": [] }"#,
+    )
+    .unwrap();
+
+    cmd()
+        .arg("apply")
+        .arg("--plan")
+        .arg(&plan_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Nothing to apply"));
+}
+
+#[test]
+fn test_format_csv() {
+    let manifest_path = get_example_path("pixi.toml");
+
+    cmd()
+        .arg("--manifest")
+        .arg(manifest_path)
+        .arg("--format")
+        .arg("csv")
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with(
+            "platform,name,kind,channel,installed_version,latest_version",
+        ));
+}
+
+#[test]
+fn test_format_github() {
+    let manifest_path = get_example_path("pixi.toml");
+
+    cmd()
+        .env("GITHUB_WORKSPACE", env!("CARGO_MANIFEST_DIR"))
+        .arg("--manifest")
+        .arg(manifest_path)
+        .arg("--source")
+        .arg(fixtures_source())
+        .arg("--platform")
+        .arg("linux-64")
+        .arg("--format")
+        .arg("github")
+        .arg("python")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "::warning file=examples/pixi.toml,line=11,title=Outdated on linux-64::python 3.12.11 -> 3.14.0",
+        ));
+}
+
+#[test]
+fn test_format_oneline() {
+    let manifest_path = get_example_path("pixi.toml");
+
+    cmd()
+        .arg("--manifest")
+        .arg(manifest_path)
+        .arg("--source")
+        .arg(fixtures_source())
+        .arg("--platform")
+        .arg("linux-64")
+        .arg("--format")
+        .arg("oneline")
+        .arg("python")
+        .assert()
+        .code(8)
+        .stdout("outdated: 1 minor\n");
+}
+
+#[test]
+fn test_format_sbom() {
+    let manifest_path = get_example_path("pixi.toml");
+
+    let output = cmd()
+        .arg("--manifest")
+        .arg(manifest_path)
+        .arg("--source")
+        .arg(fixtures_source())
+        .arg("--platform")
+        .arg("linux-64")
+        .arg("--format")
+        .arg("sbom")
+        .arg("python")
+        .assert()
+        .success();
+
+    let bom: serde_json::Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+    assert_eq!(bom["bomFormat"], "CycloneDX");
+    let python = &bom["components"][0];
+    assert_eq!(python["name"], "python");
+    assert_eq!(python["version"], "3.12.11");
+    assert!(python["purl"]
+        .as_str()
+        .unwrap()
+        .starts_with("pkg:conda/python@3.12.11?build="));
+    assert!(python["properties"].as_array().unwrap().contains(
+        &serde_json::json!({ "name": "pixi-outdated:update-severity", "value": "minor" })
+    ));
+}
+
+#[test]
+fn test_release_notes() {
+    let manifest_path = get_example_path("pixi.toml");
+
+    let output = cmd()
+        .arg("--manifest")
+        .arg(manifest_path)
+        .arg("--source")
+        .arg(fixtures_source())
+        .arg("--platform")
+        .arg("linux-64")
+        .arg("--release-notes")
+        .arg("--json")
+        .arg("python")
+        .assert()
+        .success();
+
+    let report: serde_json::Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+    assert_eq!(
+        report["platforms"]["linux-64"][0]["release_notes"],
+        "https://github.com/python/cpython/releases"
+    );
+}
+
+#[test]
+fn test_audit() {
+    let manifest_path = get_example_path("pixi.toml");
+
+    let output = cmd()
+        .arg("--manifest")
+        .arg(manifest_path)
+        .arg("--source")
+        .arg(fixtures_source())
+        .arg("--platform")
+        .arg("linux-64")
+        .arg("--audit")
+        .arg("--json")
+        .arg("cowsay")
+        .assert()
+        .code(9);
+
+    let report: serde_json::Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+    let cowsay = &report["vulnerabilities"][0];
+    assert_eq!(cowsay["name"], "cowsay");
+    assert_eq!(cowsay["version"], "5.0");
+    assert_eq!(cowsay["vulnerabilities"][0]["id"], "PYSEC-TEST-1");
+    assert_eq!(cowsay["vulnerabilities"][0]["fixed_in"][0], "6.0");
+    assert_eq!(cowsay["vulnerabilities"][0]["fixed_by_latest"], true);
+}
+
+#[test]
+fn test_size_delta() {
+    let manifest_path = get_example_path("pixi.toml");
+
+    let output = cmd()
+        .arg("--manifest")
+        .arg(manifest_path)
+        .arg("--source")
+        .arg(fixtures_source())
+        .arg("--platform")
+        .arg("linux-64")
+        .arg("--json")
+        .arg("python")
+        .assert()
+        .success();
+
+    let report: serde_json::Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+    // 3.14.0 is 36,000,000 bytes in the fixture, the locked 3.12.11 31,445,023
+    assert_eq!(report["platforms"]["linux-64"][0]["size_delta"], 4_554_977);
+}
+
+#[test]
+fn test_suggest_commands() {
+    let manifest_path = get_example_path("pixi.toml");
+
+    // The manifest requires python <3.13, so getting 3.14 means upgrading it
+    cmd()
+        .arg("--manifest")
+        .arg(manifest_path)
+        .arg("--source")
+        .arg(fixtures_source())
+        .arg("--platform")
+        .arg("linux-64")
+        .arg("--suggest-commands")
+        .arg("python")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "=== To apply every update ===\npixi upgrade python\n",
+        ));
+}
+
+#[test]
+fn test_explain() {
+    let manifest_path = get_example_path("pixi.toml");
+
+    cmd()
+        .arg("--manifest")
+        .arg(manifest_path)
+        .arg("--source")
+        .arg(fixtures_source())
+        .arg("--platform")
+        .arg("linux-64")
+        .arg("--explain")
+        .arg("python")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "=== Why python isn't at its latest version ===\npython [linux-64]: 3.12.11 -> 3.14.0\n  the manifest requires python >=3.12.0,<3.13\n",
+        ));
+}
+
+#[test]
+fn test_libyears() {
+    let manifest_path = get_example_path("pixi.toml");
+
+    let output = cmd()
+        .arg("--manifest")
+        .arg(manifest_path)
+        .arg("--source")
+        .arg(fixtures_source())
+        .arg("--platform")
+        .arg("linux-64")
+        .arg("--json")
+        .arg("python")
+        .assert()
+        .success();
+
+    let report: serde_json::Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+    // 3.14.0 was released 126 days after the locked 3.12.11 in the fixture
+    assert_eq!(report["platforms"]["linux-64"][0]["lag_days"], 126);
+    assert_eq!(report["libyears"], 0.34);
+}
+
+#[test]
+fn test_format_yaml() {
+    let manifest_path = get_example_path("pixi.toml");
+
+    let output = cmd()
+        .arg("--manifest")
+        .arg(manifest_path)
+        .arg("--format")
+        .arg("yaml")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let report: serde_yaml::Value = serde_yaml::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["status"].as_str(), Some("ok"));
+}
+
+#[test]
+fn test_format_conflicts_with_json() {
+    cmd()
+        .arg("--json")
+        .arg("--format")
+        .arg("csv")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+}
+
+#[test]
+fn test_demo() {
+    let output = cmd()
+        .arg("demo")
+        .arg("--format")
+        .arg("json")
+        .assert()
+        .success();
+
+    let report: serde_json::Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+    assert_eq!(report["metadata"]["manifest"], "pixi.toml");
+    assert!(report["platforms"]["linux-64"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|update| update["name"] == "python"));
+
+    cmd()
+        .arg("demo")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("python: 3.12.11 -> "));
+}
+
+#[test]
+fn test_aggregate_reports() {
+    let dir = tempfile::tempdir().unwrap();
+    let team_dir = dir.path().join("backend");
+    std::fs::create_dir(&team_dir).unwrap();
+    std::fs::write(
+        team_dir.join("api.json"),
+        r#"{
+            "status": "ok",
+            "platforms": {
+                "linux-64": [{ "name": "python", "installed_version": "3.12.0", "latest_version": "3.14.0" }]
+            },
+            "sources": []
+        }"#,
+    )
+    .unwrap();
+    std::fs::write(
+        team_dir.join("etl.json"),
+        r#"{ "status": "ok", "platforms": {}, "sources": [] }"#,
+    )
+    .unwrap();
+
+    cmd()
+        .arg("aggregate")
+        .arg(team_dir.join("api.json"))
+        .arg(team_dir.join("etl.json"))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Aggregated 2 report(s)"))
+        .stdout(predicate::str::contains("backend/api: 1 outdated"))
+        .stdout(predicate::str::contains("python: 1 project(s)"))
+        .stdout(predicate::str::contains(
+            "backend: 2 project(s), 1 outdated package(s)",
+        ));
+}
+
+#[test]
+fn test_update_dry_run_prints_diff() {
+    let manifest_path = get_example_path("pixi.toml");
+    let before = std::fs::read_to_string(&manifest_path).unwrap();
+
+    cmd()
+        .arg("--manifest")
+        .arg(&manifest_path)
+        .arg("--write")
+        .arg("--dry-run")
+        .arg("python")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("+++ b/"))
+        .stdout(predicate::str::contains(r#"-python = ">=3.12.0,<3.13""#));
+
+    // A dry run never touches the manifest
+    assert_eq!(std::fs::read_to_string(&manifest_path).unwrap(), before);
+}
+
+#[test]
+fn test_write_lockfile_skips_excluded_versions() {
+    let manifest_path = get_example_path("pixi.toml");
+
+    // The manifest requires python <3.13, so 3.14 can't be locked without
+    // bumping the requirement, and pixi is never run
+    cmd()
+        .arg("--manifest")
+        .arg(manifest_path)
+        .arg("--source")
+        .arg(fixtures_source())
+        .arg("--platform")
+        .arg("linux-64")
+        .arg("--write-lockfile")
+        .arg("python")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Not relocking python: the manifest excludes the latest version",
+        ))
+        .stdout(predicate::str::contains("Nothing to relock"));
+}
+
+#[test]
+fn test_dry_run_requires_update() {
+    cmd()
+        .arg("--dry-run")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--update"));
+}
+
+#[test]
+fn test_interactive_requires_terminal() {
+    let manifest_path = get_example_path("pixi.toml");
+
+    cmd()
+        .arg("--manifest")
+        .arg(manifest_path)
+        .arg("--interactive")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "--interactive requires a terminal",
+        ));
+}
+
+#[test]
+fn test_interactive_conflicts_with_json() {
+    cmd()
+        .arg("--interactive")
+        .arg("--json")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+}
+
+#[test]
+fn test_timings() {
+    let manifest_path = get_example_path("pixi.toml");
+
+    cmd()
+        .arg("--manifest")
+        .arg(manifest_path)
+        .arg("--timings")
+        .arg("cowsay")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("=== Sources ==="))
+        .stdout(predicate::str::contains("of metadata"));
+}
+
+/// Helper to get the path to the offline fixtures
+fn get_fixtures_path() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+}
+
+/// The `--source` value reading versions from the fixtures
+fn fixtures_source() -> String {
+    format!("fixtures={}", get_fixtures_path().display())
+}
+
+#[test]
+fn test_fixture_source() {
+    let manifest_path = get_example_path("pixi.toml");
+
+    cmd()
+        .arg("--manifest")
+        .arg(manifest_path)
+        .arg("--source")
+        .arg(fixtures_source())
+        .arg("python")
+        .arg("cowsay")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("python: 3.12.11 -> 3.14.0"))
+        .stdout(predicate::str::contains("python: 3.12.12 -> 3.14.0"))
+        .stdout(predicate::str::contains("cowsay: 5.0 -> 6.1"));
+}
+
+#[test]
+fn test_fixture_source_json() {
+    let manifest_path = get_example_path("pixi.toml");
+
+    let output = cmd()
+        .arg("--manifest")
+        .arg(manifest_path)
+        .arg("--source")
+        .arg(fixtures_source())
+        .arg("--platform")
+        .arg("osx-arm64")
+        .arg("--json")
+        .arg("icu")
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let updates = json["platforms"]["osx-arm64"].as_array().unwrap();
+    assert_eq!(updates.len(), 1);
+    assert_eq!(updates[0]["name"], "icu");
+    assert_eq!(updates[0]["latest_version"], "75.1");
+}
+
+#[test]
+fn test_report_metadata() {
+    let manifest_path = get_example_path("pixi.toml");
+
+    let output = cmd()
+        .arg("--manifest")
+        .arg(&manifest_path)
+        .arg("--source")
+        .arg(fixtures_source())
+        .arg("--platform")
+        .arg("linux-64")
+        .arg("--json")
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let metadata = &json["metadata"];
+    assert_eq!(metadata["tool_version"], env!("CARGO_PKG_VERSION"));
+    assert_eq!(metadata["environment"], "default");
+    assert_eq!(metadata["platforms"], serde_json::json!(["linux-64"]));
+    assert_eq!(metadata["lockfile_sha256"].as_str().unwrap().len(), 64);
+    assert!(metadata["generated_at"].is_string());
+
+    cmd()
+        .arg("--manifest")
+        .arg(manifest_path)
+        .arg("--source")
+        .arg(fixtures_source())
+        .arg("--platform")
+        .arg("linux-64")
+        .arg("python")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Generated by pixi-outdated"))
+        .stdout(predicate::str::contains(
+            "for environment default (linux-64)",
+        ));
+}
+
+#[test]
+fn test_python_upgrade() {
+    let manifest_path = get_example_path("pixi.toml");
+
+    cmd()
+        .arg("--manifest")
+        .arg(manifest_path)
+        .arg("--source")
+        .arg(fixtures_source())
+        .arg("--platform")
+        .arg("linux-64")
+        .arg("python")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("=== Python ==="))
+        .stdout(predicate::str::contains(
+            "linux-64: python: 3.12.11 -> 3.14.0 (blocked by >=3.12.0,<3.13)",
+        ));
+}
+
+#[test]
+fn test_latest_compatible() {
+    let manifest_path = get_example_path("pixi.toml");
+
+    let output = cmd()
+        .arg("--manifest")
+        .arg(manifest_path)
+        .arg("--source")
+        .arg(fixtures_source())
+        .arg("--platform")
+        .arg("osx-arm64")
+        .arg("--json")
+        .arg("python")
+        .arg("cowsay")
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let updates = json["platforms"]["osx-arm64"].as_array().unwrap();
+    let python = updates.iter().find(|u| u["name"] == "python").unwrap();
+    assert_eq!(python["latest_version"], "3.14.0");
+    assert_eq!(python["latest_compatible"], "3.12.12");
+    let cowsay = updates.iter().find(|u| u["name"] == "cowsay").unwrap();
+    assert_eq!(cowsay["latest_compatible"], "5.0");
+}
+
+#[test]
+fn test_filter_by_severity() {
+    let manifest_path = get_example_path("pixi.toml");
+
+    cmd()
+        .arg("--manifest")
+        .arg(manifest_path)
+        .arg("--source")
+        .arg(fixtures_source())
+        .arg("--filter")
+        .arg("major")
+        .arg("python")
+        .arg("icu")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("icu: 73.2 -> 75.1"))
+        .stdout(predicate::str::contains("python").not());
+}
+
+#[test]
+fn test_unknown_source() {
+    cmd()
+        .arg("--source")
+        .arg("oci=somewhere")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Unknown source 'oci'"));
+}
+
+#[test]
+fn test_fail_on_direct() {
+    let manifest_path = get_example_path("pixi.toml");
+
+    // icu is declared in the manifest
+    cmd()
+        .arg("--manifest")
+        .arg(&manifest_path)
+        .arg("--source")
+        .arg(fixtures_source())
+        .arg("--platform")
+        .arg("osx-arm64")
+        .arg("--fail-on-direct")
+        .arg("icu")
+        .arg("openssl")
+        .assert()
+        .code(2)
+        .stdout(predicate::str::contains("openssl: 3.5.4 -> 3.6.0"))
+        .stderr(predicate::str::contains(
+            "1 direct dependency(s) outdated: icu",
+        ));
+
+    // openssl is only pulled in by python
+    cmd()
+        .arg("--manifest")
+        .arg(&manifest_path)
+        .arg("--source")
+        .arg(fixtures_source())
+        .arg("--platform")
+        .arg("osx-arm64")
+        .arg("--fail-on-direct")
+        .arg("openssl")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("openssl: 3.5.4 -> 3.6.0"));
+}
+
+#[test]
+fn test_explicit_skips_transitive_dependencies() {
+    let manifest_path = get_example_path("pixi.toml");
+
+    cmd()
+        .arg("--manifest")
+        .arg(manifest_path)
+        .arg("--source")
+        .arg(fixtures_source())
+        .arg("--platform")
+        .arg("osx-arm64")
+        .arg("--explicit")
+        .arg("--json")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(r#""name": "icu""#))
+        .stdout(predicate::str::contains(r#""direct": true"#))
+        .stdout(predicate::str::contains("openssl").not());
+}
+
+#[test]
+fn test_package_patterns() {
+    let manifest_path = get_example_path("pixi.toml");
+
+    cmd()
+        .arg("--manifest")
+        .arg(&manifest_path)
+        .arg("--source")
+        .arg(fixtures_source())
+        .arg("--platform")
+        .arg("linux-64")
+        .arg("py*")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("python: 3.12.11 -> 3.14.0"))
+        .stdout(predicate::str::contains("openssl").not());
+
+    cmd()
+        .arg("--manifest")
+        .arg(&manifest_path)
+        .arg("--source")
+        .arg(fixtures_source())
+        .arg("--platform")
+        .arg("linux-64")
+        .arg("--regex")
+        .arg("^open")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("openssl: 3.5.4 -> 3.6.0"))
+        .stdout(predicate::str::contains("python: 3.12.11").not());
+}
+
+#[test]
+fn test_exclude() {
+    let manifest_path = get_example_path("pixi.toml");
+
+    cmd()
+        .arg("--manifest")
+        .arg(manifest_path)
+        .arg("--source")
+        .arg(fixtures_source())
+        .arg("--platform")
+        .arg("linux-64")
+        .arg("--exclude")
+        .arg("py*")
+        .arg("--exclude")
+        .arg("openssl")
+        .arg("--verbose")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("python: excluded"))
+        .stdout(predicate::str::contains("openssl: excluded"))
+        .stdout(predicate::str::contains("python: 3.12.11 -> 3.14.0").not())
+        .stdout(predicate::str::contains("openssl: 3.5.4 -> 3.6.0").not());
+}
+
+#[test]
+fn test_transitive_updates_name_what_requires_them() {
+    let manifest_path = get_example_path("pixi.toml");
+
+    cmd()
+        .arg("--manifest")
+        .arg(&manifest_path)
+        .arg("--source")
+        .arg(fixtures_source())
+        .arg("--platform")
+        .arg("linux-64")
+        .arg("openssl")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "openssl: 3.5.4 -> 3.6.0 (required by python)",
+        ));
+
+    let output = cmd()
+        .arg("--manifest")
+        .arg(&manifest_path)
+        .arg("--source")
+        .arg(fixtures_source())
+        .arg("--platform")
+        .arg("linux-64")
+        .arg("--json")
+        .arg("openssl")
+        .arg("python")
+        .output()
+        .unwrap();
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let updates = report["platforms"]["linux-64"].as_array().unwrap();
+    let required_by = |name: &str| {
+        updates
+            .iter()
+            .find(|update| update["name"] == name)
+            .map(|update| update["required_by"].clone())
+            .unwrap()
+    };
+    assert_eq!(required_by("openssl"), serde_json::json!(["python"]));
+    // Manifest dependencies aren't required by anything
+    assert!(required_by("python").is_null());
+}
+
+#[test]
+fn test_tree() {
+    let manifest_path = get_example_path("pixi.toml");
+
+    cmd()
+        .arg("--manifest")
+        .arg(&manifest_path)
+        .arg("--source")
+        .arg(fixtures_source())
+        .arg("--platform")
+        .arg("linux-64")
+        .arg("--tree")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("python 3.12.11 -> 3.14.0\n"))
+        .stdout(predicate::str::contains("── openssl 3.5.4 -> 3.6.0\n"))
+        .stdout(predicate::str::contains("up to date\n"));
+}
+
+#[test]
+fn test_tree_conflicts_with_structured_output() {
+    cmd()
+        .arg("--tree")
+        .arg("--json")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+}
+
+#[test]
+fn test_prereleases_are_opt_in() {
+    let manifest_path = get_example_path("pixi.toml");
+
+    cmd()
+        .arg("--manifest")
+        .arg(&manifest_path)
+        .arg("--source")
+        .arg(fixtures_source())
+        .arg("--platform")
+        .arg("linux-64")
+        .arg("python")
+        .arg("cowsay")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("python: 3.12.11 -> 3.14.0"))
+        .stdout(predicate::str::contains("cowsay: 5.0 -> 6.1"));
+
+    cmd()
+        .arg("--manifest")
+        .arg(&manifest_path)
+        .arg("--source")
+        .arg(fixtures_source())
+        .arg("--platform")
+        .arg("linux-64")
+        .arg("--pre")
+        .arg("python")
+        .arg("cowsay")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("python: 3.12.11 -> 3.15.0a1"))
+        .stdout(predicate::str::contains("cowsay: 5.0 -> 7.0b1"));
+
+    // Only for the listed packages
+    cmd()
+        .arg("--manifest")
+        .arg(&manifest_path)
+        .arg("--source")
+        .arg(fixtures_source())
+        .arg("--platform")
+        .arg("linux-64")
+        .arg("--pre=cowsay")
+        .arg("python")
+        .arg("cowsay")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("python: 3.12.11 -> 3.14.0"))
+        .stdout(predicate::str::contains("cowsay: 5.0 -> 7.0b1"));
+}
+
+#[test]
+fn test_audit_log_appends_one_record_per_run() {
+    let manifest_path = get_example_path("pixi.toml");
+    let dir = tempfile::tempdir().unwrap();
+    let audit_log = dir.path().join("audit.ndjson");
+
+    for _ in 0..2 {
+        cmd()
+            .arg("--manifest")
+            .arg(&manifest_path)
+            .arg("--source")
+            .arg(fixtures_source())
+            .arg("--platform")
+            .arg("osx-arm64")
+            .arg("--audit-log")
+            .arg(&audit_log)
+            .assert()
+            .success();
+    }
+
+    let content = std::fs::read_to_string(&audit_log).unwrap();
+    let records: Vec<serde_json::Value> = content
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("Each line should be valid JSON"))
+        .collect();
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[0]["status"], "ok");
+    assert_eq!(records[0]["environment"], "default");
+    assert_eq!(records[0]["tool_version"], env!("CARGO_PKG_VERSION"));
+    assert!(records[0]["outdated"].as_u64().unwrap() > 0);
+    assert!(records[0]["timestamp"].is_string());
+}
+
+#[test]
+fn test_not_found_and_failed_queries_are_reported() {
+    let manifest_path = get_example_path("pixi.toml");
+
+    // libzlib is locked, but has no records in the fixtures
+    let output = cmd()
+        .arg("--manifest")
+        .arg(&manifest_path)
+        .arg("--source")
+        .arg(fixtures_source())
+        .arg("--platform")
+        .arg("linux-64")
+        .arg("--json")
+        .arg("python")
+        .arg("libzlib")
+        .assert()
+        .success();
+    let stdout = String::from_utf8(output.get_output().stdout.clone()).unwrap();
+    let report: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    let unchecked = report["unchecked"].as_array().unwrap();
+    assert_eq!(unchecked.len(), 1);
+    assert_eq!(unchecked[0]["name"], "libzlib");
+    assert_eq!(unchecked[0]["status"], "not_found");
+
+    // Without conda-forge fixtures every conda query fails
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir(dir.path().join("pypi")).unwrap();
+    cmd()
+        .arg("--manifest")
+        .arg(&manifest_path)
+        .arg("--source")
+        .arg(format!("fixtures={}", dir.path().display()))
+        .arg("--platform")
+        .arg("linux-64")
+        .arg("python")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("=== Not checked ==="))
+        .stdout(predicate::str::contains(
+            "python (conda): 3.12.11 (query failed: No fixtures for channel",
+        ));
+}
+
+#[test]
+fn test_pypi_latest_honors_locked_python() {
+    let manifest_path = get_example_path("pixi.toml");
+
+    // cowsay 6.2 requires Python >=3.13, but 3.12 is locked
+    cmd()
+        .arg("--manifest")
+        .arg(manifest_path)
+        .arg("--source")
+        .arg(fixtures_source())
+        .arg("--platform")
+        .arg("linux-64")
+        .arg("--verbose")
+        .arg("cowsay")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "cowsay: 6.2 requires a newer Python than 3.12.11, using 6.1",
+        ))
+        .stdout(predicate::str::contains("cowsay: 5.0 -> 6.1"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_hooks_run_around_the_check() {
+    let dir = workspace_without_lockfile();
+    let manifest = dir.path().join("pixi.toml");
+    let mut content = std::fs::read_to_string(&manifest).unwrap();
+    content.push_str(
+        r#"
+[tool.pixi-outdated.hooks]
+pre = ["echo \"$PIXI_OUTDATED_ENVIRONMENT\" > pre.txt"]
+post = ["echo \"$PIXI_OUTDATED_STATUS $PIXI_OUTDATED_OUTDATED\" > post.txt && cp \"$PIXI_OUTDATED_REPORT\" report.json"]
+"#,
+    );
+    std::fs::write(&manifest, content).unwrap();
+
+    cmd()
+        .arg("--manifest")
+        .arg(&manifest)
+        .arg("--allow-empty")
+        .assert()
+        .success();
+
+    let pre = std::fs::read_to_string(dir.path().join("pre.txt")).unwrap();
+    assert_eq!(pre.trim(), "default");
+    let post = std::fs::read_to_string(dir.path().join("post.txt")).unwrap();
+    assert_eq!(post.trim(), "lockfile_missing 0");
+    let report = std::fs::read_to_string(dir.path().join("report.json")).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&report).unwrap();
+    assert_eq!(json["status"], "lockfile_missing");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_failing_pre_hook_aborts_the_run() {
+    let dir = workspace_without_lockfile();
+    let manifest = dir.path().join("pixi.toml");
+    let mut content = std::fs::read_to_string(&manifest).unwrap();
+    content.push_str(
+        "\n[tool.pixi-outdated.hooks]\npre = [\"exit 1\"]\npost = [\"touch post.txt\"]\n",
+    );
+    std::fs::write(&manifest, content).unwrap();
+
+    cmd()
+        .arg("--manifest")
+        .arg(&manifest)
+        .arg("--allow-empty")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Hook `exit 1` failed"));
+    assert!(!dir.path().join("post.txt").exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_recursive_skips_hooks_unless_asked() {
+    let dir = tempfile::tempdir().unwrap();
+    let project_dir = dir.path().join("app");
+    std::fs::create_dir_all(&project_dir).unwrap();
+    let mut manifest = std::fs::read_to_string(get_example_path("pixi.toml")).unwrap();
+    manifest.push_str(
+        "\n[tool.pixi-outdated.hooks]\npre = [\"touch pre.txt\"]\npost = [\"touch post.txt\"]\n",
+    );
+    std::fs::write(project_dir.join("pixi.toml"), manifest).unwrap();
+    std::fs::copy(get_example_path("pixi.lock"), project_dir.join("pixi.lock")).unwrap();
+
+    cmd()
+        .arg("--recursive")
+        .arg(dir.path())
+        .arg("--source")
+        .arg(fixtures_source())
+        .arg("--platform")
+        .arg("linux-64")
+        .assert();
+    assert!(!project_dir.join("pre.txt").exists());
+    assert!(!project_dir.join("post.txt").exists());
+
+    cmd()
+        .arg("--recursive")
+        .arg(dir.path())
+        .arg("--run-hooks")
+        .arg("--source")
+        .arg(fixtures_source())
+        .arg("--platform")
+        .arg("linux-64")
+        .assert();
+    assert!(project_dir.join("pre.txt").exists());
+    assert!(project_dir.join("post.txt").exists());
+
+    cmd()
+        .arg("--run-hooks")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--recursive"));
+}
+
+/// Helper to copy the example workspace, with `extra` appended to its manifest
+fn example_workspace_with(extra: &str) -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    let mut manifest = std::fs::read_to_string(get_example_path("pixi.toml")).unwrap();
+    manifest.push_str(extra);
+    std::fs::write(dir.path().join("pixi.toml"), manifest).unwrap();
+    std::fs::copy(get_example_path("pixi.lock"), dir.path().join("pixi.lock")).unwrap();
+    dir
+}
+
+#[test]
+fn test_project_config_file() {
+    let dir = example_workspace_with("");
+    std::fs::write(
+        dir.path().join(".pixi-outdated.toml"),
+        r#"
+ignore = ["openssl"]
+default-args = ["--platform", "linux-64"]
+
+[max-versions]
+python = "<3.14"
+"#,
+    )
+    .unwrap();
+
+    let run = |extra: &[&str]| {
+        cmd()
+            .arg("--manifest")
+            .arg(dir.path().join("pixi.toml"))
+            .arg("--source")
+            .arg(fixtures_source())
+            .args(extra)
+            .assert()
+            .success()
+    };
+    run(&[])
+        .stdout(predicate::str::contains("python: 3.12.11 -> 3.13.9"))
+        .stdout(predicate::str::contains("openssl").not())
+        .stdout(predicate::str::contains("3.12.12").not());
+
+    // The command line overrides the defaults
+    run(&["--platform", "osx-arm64"]).stdout(predicate::str::contains("python: 3.12.12 -> 3.13.9"));
+}
+
+#[test]
+fn test_channel_allowlist() {
+    let dir = example_workspace_with("\n[tool.pixi-outdated]\nallowed-channels = [\"bioconda\"]\n");
+
+    cmd()
+        .arg("--manifest")
+        .arg(dir.path().join("pixi.toml"))
+        .arg("--source")
+        .arg(fixtures_source())
+        .arg("--platform")
+        .arg("linux-64")
+        .arg("python")
+        .assert()
+        .code(6)
+        .stdout(predicate::str::contains(
+            "=== Not from an allowed channel ===",
+        ))
+        .stdout(predicate::str::contains(
+            "python: 3.12.11 from https://conda.anaconda.org/conda-forge [linux-64]",
+        ));
+
+    let dir =
+        example_workspace_with("\n[tool.pixi-outdated]\nallowed-channels = [\"conda-forge\"]\n");
+    cmd()
+        .arg("--manifest")
+        .arg(dir.path().join("pixi.toml"))
+        .arg("--source")
+        .arg(fixtures_source())
+        .arg("--platform")
+        .arg("linux-64")
+        .arg("python")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("allowed channel").not());
+}
+
+#[test]
+fn test_allowed_licenses() {
+    let dir = example_workspace_with("\n[tool.pixi-outdated]\nallowed-licenses = [\"MIT\"]\n");
+
+    let output = cmd()
+        .arg("--manifest")
+        .arg(dir.path().join("pixi.toml"))
+        .arg("--source")
+        .arg(fixtures_source())
+        .arg("--platform")
+        .arg("linux-64")
+        .arg("--json")
+        .arg("python")
+        .output()
+        .unwrap();
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let held_back = &report["held_back_by_license"][0];
+    assert_eq!(held_back["name"], "python");
+    assert_eq!(held_back["license"], "Python-2.0");
+    assert_eq!(held_back["newest_version"], "3.14.0");
+    assert!(held_back["latest_allowed"].is_null());
+    assert!(report["platforms"]["linux-64"]
+        .as_array()
+        .is_none_or(|updates| updates.is_empty()));
+
+    let dir = example_workspace_with("\n[tool.pixi-outdated]\nallowed-licenses = [\"python-*\"]\n");
+    cmd()
+        .arg("--manifest")
+        .arg(dir.path().join("pixi.toml"))
+        .arg("--source")
+        .arg(fixtures_source())
+        .arg("--platform")
+        .arg("linux-64")
+        .arg("python")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("python: 3.12.11 -> 3.14.0"))
+        .stdout(predicate::str::contains("Held back by license").not());
+}
+
+#[test]
+fn test_stats_endpoint() {
+    use std::io::{BufRead, BufReader, Read, Write};
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let endpoint = format!("http://{}/runs", listener.local_addr().unwrap());
+    let server = std::thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(stream);
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line).unwrap();
+        let mut content_length = 0;
+        loop {
+            let mut header = String::new();
+            reader.read_line(&mut header).unwrap();
+            if header.trim().is_empty() {
+                break;
+            }
+            if let Some((name, value)) = header.split_once(':') {
+                if name.eq_ignore_ascii_case("content-length") {
+                    content_length = value.trim().parse().unwrap();
+                }
+            }
+        }
+        let mut body = vec![0; content_length];
+        reader.read_exact(&mut body).unwrap();
+        reader
+            .get_mut()
+            .write_all(b"HTTP/1.1 204 No Content\r\nContent-Length: 0\r\n\r\n")
+            .unwrap();
+        (request_line, body)
+    });
+
+    let dir = example_workspace_with(&format!(
+        "\n[tool.pixi-outdated.stats]\nendpoint = \"{}\"\nproject = \"examples\"\n",
+        endpoint
+    ));
+    cmd()
+        .env("NO_PROXY", "127.0.0.1")
+        .arg("--manifest")
+        .arg(dir.path().join("pixi.toml"))
+        .arg("--source")
+        .arg(fixtures_source())
+        .arg("--platform")
+        .arg("linux-64")
+        .arg("python")
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("run statistics").not());
+
+    let (request_line, body) = server.join().unwrap();
+    assert!(request_line.starts_with("POST /runs "));
+    let stats: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(stats["project"], "examples");
+    assert_eq!(stats["environment"], "default");
+    assert_eq!(stats["outdated"], 1);
+    assert!(stats.get("git_sha").is_none());
+}
+
+#[test]
+fn test_notify_webhook() {
+    use std::io::{BufRead, BufReader, Read, Write};
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/hooks/outdated", listener.local_addr().unwrap());
+    let server = std::thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(stream);
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line).unwrap();
+        let mut content_length = 0;
+        loop {
+            let mut header = String::new();
+            reader.read_line(&mut header).unwrap();
+            if header.trim().is_empty() {
+                break;
+            }
+            if let Some((name, value)) = header.split_once(':') {
+                if name.eq_ignore_ascii_case("content-length") {
+                    content_length = value.trim().parse().unwrap();
+                }
+            }
+        }
+        let mut body = vec![0; content_length];
+        reader.read_exact(&mut body).unwrap();
+        reader
+            .get_mut()
+            .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n")
+            .unwrap();
+        (request_line, body)
+    });
+
+    let manifest_path = get_example_path("pixi.toml");
+    cmd()
+        .env("NO_PROXY", "127.0.0.1")
+        .arg("--manifest")
+        .arg(manifest_path)
+        .arg("--source")
+        .arg(fixtures_source())
+        .arg("--platform")
+        .arg("linux-64")
+        .arg("--notify-webhook")
+        .arg(&url)
+        .arg("python")
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("webhook").not());
+
+    let (request_line, body) = server.join().unwrap();
+    assert!(request_line.starts_with("POST /hooks/outdated "));
+    let summary: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(summary["updates"][0]["name"], "python");
+    assert_eq!(summary["updates"][0]["latest_version"], "3.14.0");
+    assert_eq!(summary["updates"][0]["platforms"][0], "linux-64");
+}
+
+#[test]
+fn test_auto_merge_labels() {
+    let find_update = |stdout: &[u8], name: &str| -> serde_json::Value {
+        let report: serde_json::Value = serde_json::from_slice(stdout).unwrap();
+        report["platforms"]["linux-64"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|update| update["name"] == name)
+            .cloned()
+            .unwrap()
+    };
+
+    // python 3.12.11 -> 3.14.0 is a minor update, and only patches are auto-merged by default
+    let output = cmd()
+        .arg("--manifest")
+        .arg(get_example_path("pixi.toml"))
+        .arg("--source")
+        .arg(fixtures_source())
+        .arg("--platform")
+        .arg("linux-64")
+        .arg("--auto-merge")
+        .arg("--json")
+        .arg("python")
+        .assert()
+        .success();
+    let python = find_update(&output.get_output().stdout, "python");
+    assert_eq!(python["merge"], "review-required");
+    assert_eq!(python["merge_reasons"], serde_json::json!(["minor update"]));
+
+    let dir =
+        example_workspace_with("\n[tool.pixi-outdated.auto-merge]\nmax-severity = \"minor\"\n");
+    let output = cmd()
+        .arg("--manifest")
+        .arg(dir.path().join("pixi.toml"))
+        .arg("--source")
+        .arg(fixtures_source())
+        .arg("--platform")
+        .arg("linux-64")
+        .arg("--auto-merge")
+        .arg("--json")
+        .arg("python")
+        .assert()
+        .success();
+    let python = find_update(&output.get_output().stdout, "python");
+    assert_eq!(python["merge"], "auto-mergeable");
+    assert!(python.get("merge_reasons").is_none());
+}
+
+#[test]
+fn test_if_changed_exit_zero() {
+    let dir = tempfile::tempdir().unwrap();
+    let hash_file = dir.path().join("report.sha256");
+    let run = || {
+        cmd()
+            .arg("--manifest")
+            .arg(get_example_path("pixi.toml"))
+            .arg("--source")
+            .arg(fixtures_source())
+            .arg("--platform")
+            .arg("osx-arm64")
+            .arg("--hash-file")
+            .arg(&hash_file)
+            .arg("--if-changed-exit-zero")
+            .arg("--fail-on-direct")
+            .arg("--json")
+            .arg("icu")
+            .assert()
+    };
+
+    // A new finding fails the run as usual
+    let output = run().code(2);
+    let report: serde_json::Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+    let hash = std::fs::read_to_string(&hash_file).unwrap();
+    assert_eq!(report["content_hash"].as_str().unwrap(), hash.trim());
+
+    // Nothing changed since the first run
+    run().success().stderr(predicate::str::contains(
+        "Report unchanged since the previous run",
+    ));
+}
+
+#[test]
+fn test_newer_lockfile_format_is_read_best_effort() {
+    let dir = example_workspace_with("");
+    let lock_path = dir.path().join("pixi.lock");
+    let lock = std::fs::read_to_string(&lock_path).unwrap();
+    std::fs::write(&lock_path, lock.replacen("version: 6", "version: 7", 1)).unwrap();
+
+    let output = cmd()
+        .arg("--manifest")
+        .arg(dir.path().join("pixi.toml"))
+        .arg("--source")
+        .arg(fixtures_source())
+        .arg("--platform")
+        .arg("linux-64")
+        .arg("--json")
+        .arg("python")
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("lockfile format version 7"))
+        .stderr(predicate::str::contains(
+            "the lockfile is not checked against the manifest",
+        ));
+
+    let report: serde_json::Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+    let updates = report["platforms"]["linux-64"].as_array().unwrap();
+    assert!(updates.iter().any(|update| update["name"] == "python"));
+}
+
+/// Helper to run git in a test workspace
+fn git(dir: &std::path::Path, args: &[&str]) {
+    let status = std::process::Command::new("git")
+        .current_dir(dir)
+        .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+        .args(args)
+        .status()
+        .unwrap();
+    assert!(status.success(), "git {:?} failed", args);
+}
+
+#[test]
+fn test_changed_since_only_checks_changed_dependencies() {
+    let dir = example_workspace_with("");
+    let lock_path = dir.path().join("pixi.lock");
+    let lock = std::fs::read_to_string(&lock_path).unwrap();
+
+    // The base revision locked an older python
+    std::fs::write(
+        &lock_path,
+        lock.replace(
+            "python-3.12.11-h9e4cc4f_0_cpython",
+            "python-3.12.10-h9e4cc4f_0_cpython",
+        ),
+    )
+    .unwrap();
+    git(dir.path(), &["init", "--quiet"]);
+    git(dir.path(), &["add", "."]);
+    git(dir.path(), &["commit", "--quiet", "-m", "base"]);
+    std::fs::write(&lock_path, lock).unwrap();
+
+    let run = || {
+        cmd()
+            .arg("--manifest")
+            .arg(dir.path().join("pixi.toml"))
+            .arg("--source")
+            .arg(fixtures_source())
+            .arg("--platform")
+            .arg("linux-64")
+            .arg("--json")
+            .arg("--changed-since")
+            .arg("HEAD")
+            .assert()
+            .success()
+    };
+
+    let output = run();
+    let report: serde_json::Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+    let updates = report["platforms"]["linux-64"].as_array().unwrap();
+    assert!(!updates.is_empty());
+    assert!(updates.iter().all(|update| update["name"] == "python"));
+
+    git(dir.path(), &["commit", "--quiet", "-am", "relock"]);
+    let output = run();
+    let report: serde_json::Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+    assert_eq!(report["status"], "no_changes");
+}
+
+#[test]
+fn test_changed_since_unknown_revision() {
+    let dir = example_workspace_with("");
+    git(dir.path(), &["init", "--quiet"]);
+
+    cmd()
+        .arg("--manifest")
+        .arg(dir.path().join("pixi.toml"))
+        .arg("--source")
+        .arg(fixtures_source())
+        .arg("--changed-since")
+        .arg("does-not-exist")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Unknown git revision 'does-not-exist'",
+        ));
+}
+
+#[test]
+fn test_pins_out() {
+    let dir = tempfile::tempdir().unwrap();
+    let pins_path = dir.path().join("pins.json");
+
+    cmd()
+        .arg("--manifest")
+        .arg(get_example_path("pixi.toml"))
+        .arg("--source")
+        .arg(fixtures_source())
+        .arg("--platform")
+        .arg("linux-64")
+        .arg("--pins-out")
+        .arg(&pins_path)
+        .arg("python")
+        .assert()
+        .success();
+
+    let pins: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&pins_path).unwrap()).unwrap();
+    let pins = pins["pins"].as_array().unwrap();
+    let python = pins
+        .iter()
+        .find(|pin| pin["name"] == "python")
+        .expect("python should be pinned");
+    assert_eq!(python["version"], "3.14.0");
+    assert_eq!(
+        python["url"],
+        "https://conda.anaconda.org/conda-forge/linux-64/python-3.14.0-h32b2ec7_102_cp314.conda"
+    );
+    assert_eq!(
+        python["sha256"],
+        "3b7d2b8f29fc1da2c7dee2e1a0a0c5e3b1f5e2a4d6c8b0a2e4f6a8c0e2b4d6f8"
+    );
+}
+
+/// The example workspace with a `lint` environment, declared in `extra`,
+/// locked the same as the default one
+fn example_workspace_with_lint_environment(extra: &str) -> tempfile::TempDir {
+    let dir = example_workspace_with(extra);
+
+    let lock_path = dir.path().join("pixi.lock");
+    let lock = std::fs::read_to_string(&lock_path).unwrap();
+    let (environments, packages) = lock.split_once("\npackages:\n").unwrap();
+    let default = environments.split_once("  default:\n").unwrap().1;
+    std::fs::write(
+        &lock_path,
+        format!(
+            "{}\n  lint:\n{}\npackages:\n{}",
+            environments, default, packages
+        ),
+    )
+    .unwrap();
+    dir
+}
+
+#[test]
+fn test_all_environments() {
+    let dir = example_workspace_with_lint_environment("\n[environments]\nlint = []\n");
+
+    let run = |format: &str| {
+        cmd()
+            .arg("--manifest")
+            .arg(dir.path().join("pixi.toml"))
+            .arg("--source")
+            .arg(fixtures_source())
+            .arg("--platform")
+            .arg("linux-64")
+            .arg("--format")
+            .arg(format)
+            .arg("--all-environments")
+            .arg("python")
+            .assert()
+            .success()
+    };
+
+    // Both environments lock the same python, so its update is listed once
+    let output = run("text");
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    let (shared, environments) = stdout
+        .split_once("##### Environment: default #####")
+        .unwrap();
+    assert!(shared.contains("##### All Environments #####"));
+    assert!(shared.contains("python: 3.12.11 -> "));
+    assert!(environments.contains("##### Environment: lint #####"));
+    assert!(!environments.contains("python: "));
+
+    let output = run("json");
+    let reports: serde_json::Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+    for environment in ["default", "lint"] {
+        let updates = reports["environments"][environment]["platforms"]["linux-64"]
+            .as_array()
+            .unwrap();
+        assert_eq!(updates[0]["name"], "python");
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn test_all_environments_runs_pre_hooks_once() {
+    let dir = example_workspace_with_lint_environment(
+        "\n[environments]\nlint = []\n\n[tool.pixi-outdated.hooks]\npre = [\"echo \\\"$PIXI_OUTDATED_ENVIRONMENT\\\" >> pre.txt\"]\n",
+    );
+
+    cmd()
+        .arg("--manifest")
+        .arg(dir.path().join("pixi.toml"))
+        .arg("--source")
+        .arg(fixtures_source())
+        .arg("--platform")
+        .arg("linux-64")
+        .arg("--all-environments")
+        .assert()
+        .success();
+
+    let pre = std::fs::read_to_string(dir.path().join("pre.txt")).unwrap();
+    assert_eq!(pre, "default lint\n");
+}
+
+#[test]
+fn test_all_environments_conflicts_with_report_files() {
+    for option in ["--report-html", "--junit-out"] {
+        cmd()
+            .arg("--all-environments")
+            .arg(option)
+            .arg("report.out")
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("cannot be used with"));
+    }
+}
+
+#[test]
+fn test_all_environments_conflicts_with_environment() {
+    cmd()
+        .arg("--all-environments")
+        .arg("--environment")
+        .arg("default")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+}
+
+#[test]
+fn test_updates_name_the_features_declaring_them() {
+    let dir = example_workspace_with_lint_environment(
+        "\n[feature.lint.dependencies]\npython = \">=3.12\"\n\n[environments]\nlint = [\"lint\"]\n",
+    );
+
+    cmd()
+        .arg("--manifest")
+        .arg(dir.path().join("pixi.toml"))
+        .arg("--source")
+        .arg(fixtures_source())
+        .arg("--platform")
+        .arg("linux-64")
+        .arg("python")
+        .assert()
+        .stdout(predicate::str::contains(
+            "python (features: default, lint): 3.12.11 -> ",
+        ));
+}
+
+#[test]
+fn test_annotations() {
+    let dir = example_workspace_with(
+        "\n[tool.pixi-outdated.annotations]\n\"py*\" = { team = \"runtime\" }\n",
+    );
+
+    let output = cmd()
+        .arg("--manifest")
+        .arg(dir.path().join("pixi.toml"))
+        .arg("--source")
+        .arg(fixtures_source())
+        .arg("--platform")
+        .arg("linux-64")
+        .arg("--json")
+        .arg("python")
+        .assert()
+        .success();
+    let report: serde_json::Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+    assert_eq!(
+        report["platforms"]["linux-64"][0]["annotations"]["team"],
+        "runtime"
+    );
+}